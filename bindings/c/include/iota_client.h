@@ -0,0 +1,113 @@
+#ifndef IOTA_CLIENT_H
+#define IOTA_CLIENT_H
+
+/* Generated with cbindgen from src/ffi.rs, run `make header` to update it */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// A client, created with `iota_client_new` and freed with `iota_client_free`. It can be used by several threads at
+// once.
+typedef struct IotaClient IotaClient;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a client from the JSON options `{ nodes, networkId, bech32Hrp, minPowScore, localPow, nodeSync,
+// requestTimeout }`, only `nodes` is required. Returns `NULL` if the options are invalid.
+//
+// # Safety
+//
+// `options` must be `NULL` or a NUL terminated string.
+IotaClient *iota_client_new(const char *options);
+
+// Frees a client, `NULL` is ignored.
+//
+// # Safety
+//
+// `client` must be `NULL` or a client of `iota_client_new` not freed yet, no other thread may use it.
+void iota_client_free(IotaClient *client);
+
+// Frees a string returned by a function of the library, `NULL` is ignored.
+//
+// # Safety
+//
+// `value` must be `NULL` or a string of the library not freed yet.
+void iota_string_free(char *value);
+
+// The message of the error of the last failed call of the calling thread, `NULL` if no call failed. The string
+// belongs to the library, it's valid until the next failed call of the thread.
+const char *iota_last_error_message(void);
+
+// The code of the error of the last failed call of the calling thread, the codes of the Node.js binding like
+// `ERESPONSE`. `NULL` if no call failed or if the error has no code. The string belongs to the library, it's valid
+// until the next failed call of the thread.
+const char *iota_last_error_code(void);
+
+// Gets the info of a node, as JSON.
+//
+// # Safety
+//
+// `client` must be a client of `iota_client_new`.
+char *iota_get_info(const IotaClient *client);
+
+// Gets a message by its hex id, as JSON in the shape of the messages of the Node.js binding.
+//
+// # Safety
+//
+// `client` must be a client of `iota_client_new` and `message_id` a NUL terminated string.
+char *iota_get_message(const IotaClient *client, const char *message_id);
+
+// Gets an output by its hex id, as JSON with its address bech32 encoded with the network HRP.
+//
+// # Safety
+//
+// `client` must be a client of `iota_client_new` and `output_id` a NUL terminated string.
+char *iota_get_output(const IotaClient *client, const char *output_id);
+
+// Gets the balance of a bech32 or hex address, as the JSON `{ address, addressHex, balance, dustAllowed }`.
+//
+// # Safety
+//
+// `client` must be a client of `iota_client_new` and `address` a NUL terminated string.
+char *iota_get_address_balance(const IotaClient *client, const char *address);
+
+// Submits an indexation message with `data_length` bytes of data, returning the JSON `{ messageId, message }` of the
+// message accepted by the node.
+//
+// # Safety
+//
+// `client` must be a client of `iota_client_new`, `index` a NUL terminated string and `data` `NULL` or
+// `data_length` readable bytes.
+char *iota_send_indexation(const IotaClient *client,
+                           const char *index,
+                           const uint8_t *data,
+                           size_t data_length);
+
+// Derives the public addresses `start` to `end` (excluded) of an account of a hex seed, as a JSON array of bech32
+// addresses with the network HRP. No node is queried, set the `bech32Hrp` option to derive them offline.
+//
+// # Safety
+//
+// `client` must be a client of `iota_client_new` and `seed` a NUL terminated string.
+char *iota_generate_addresses(const IotaClient *client,
+                              const char *seed,
+                              size_t account_index,
+                              size_t start,
+                              size_t end);
+
+// Checks a bech32 or hex address, a bech32 address must have the `expected_hrp` unless it's `NULL`. An invalid
+// address isn't an error, `iota_last_error_message` is only set by invalid arguments.
+//
+// # Safety
+//
+// `address` must be a NUL terminated string and `expected_hrp` `NULL` or a NUL terminated string.
+bool iota_is_address_valid(const char *address, const char *expected_hrp);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* IOTA_CLIENT_H */
@@ -0,0 +1,385 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `extern "C"` API of `include/iota_client.h`.
+//!
+//! The strings are UTF-8 and NUL terminated. The strings returned by the functions belong to the caller, who frees
+//! them with `iota_string_free`, and the clients are freed with `iota_client_free`. A failed call returns `NULL` or
+//! `false` and keeps its error for the calling thread until its next failed call, see `iota_last_error_message`. The
+//! panics are caught at the boundary and fail the call with the `EINTERNAL` code, they never unwind into C.
+
+use crate::{
+    address::{address_to_bech32, parse_address, AddressEncoding},
+    dto::{AddressBalanceDto, MessageResponseDto, OutputMetadataDto},
+    error_codes::error_code,
+    Error,
+};
+
+use iota::{
+    client::{Bip39Seed, SignerType},
+    MessageId, Seed, UTXOInput,
+};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use tokio::runtime::Runtime;
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    ffi::{CStr, CString},
+    future::Future,
+    os::raw::c_char,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr, slice,
+    str::FromStr,
+    time::Duration,
+};
+
+const BIP39_SEED_LENGTH: usize = 64;
+
+thread_local! {
+    // the message and the code of the error of the last failed call of the thread
+    static LAST_ERROR: RefCell<Option<(CString, Option<CString>)>> = RefCell::new(None);
+}
+
+/// A client, created with `iota_client_new` and freed with `iota_client_free`. It can be used by several threads at
+/// once.
+pub struct IotaClient {
+    client: iota::Client,
+}
+
+/// The options of `iota_client_new`, the network parameters not set are the ones of the nodes.
+#[derive(Deserialize)]
+struct ClientOptionsDto {
+    nodes: Vec<String>,
+    #[serde(rename = "networkId")]
+    network_id: Option<String>,
+    #[serde(rename = "bech32Hrp")]
+    bech32_hrp: Option<String>,
+    #[serde(rename = "minPowScore")]
+    min_pow_score: Option<f64>,
+    #[serde(rename = "localPow")]
+    local_pow: Option<bool>,
+    #[serde(rename = "nodeSync")]
+    node_sync: Option<bool>,
+    /// The request timeout in milliseconds
+    #[serde(rename = "requestTimeout")]
+    request_timeout: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct SentMessageDto {
+    #[serde(rename = "messageId")]
+    message_id: String,
+    message: MessageResponseDto,
+}
+
+/// Runs the future on the shared runtime, it can be entered from several threads at once.
+fn block_on<F: Future>(future: F) -> F::Output {
+    static INSTANCE: OnceCell<Runtime> = OnceCell::new();
+    let runtime = INSTANCE.get_or_init(|| Runtime::new().unwrap());
+    runtime.block_on(future)
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => match panic.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+fn c_string(value: String) -> CString {
+    CString::new(value).unwrap_or_else(|e| {
+        let end = e.nul_position();
+        let mut value = e.into_vec();
+        value.truncate(end);
+        CString::new(value).unwrap()
+    })
+}
+
+/// Runs a call, returning `failed` and keeping the error for the thread if it fails or panics.
+fn ffi_call<T>(failed: T, call: impl FnOnce() -> crate::Result<T>) -> T {
+    let result = catch_unwind(AssertUnwindSafe(call)).unwrap_or_else(|panic| Err(Error::Panic(panic_message(panic))));
+    match result {
+        Ok(value) => value,
+        Err(error) => {
+            let code = error_code(&error).map(|(code, _)| c_string(code.to_string()));
+            let message = c_string(error.to_string());
+            LAST_ERROR.with(|last_error| last_error.borrow_mut().replace((message, code)));
+            failed
+        }
+    }
+}
+
+/// Runs a call returning a string, `NULL` if it fails.
+fn ffi_string_call(call: impl FnOnce() -> crate::Result<String>) -> *mut c_char {
+    ffi_call(ptr::null_mut(), || call().map(|value| c_string(value).into_raw()))
+}
+
+unsafe fn str_argument<'a>(name: &str, value: *const c_char) -> crate::Result<&'a str> {
+    if value.is_null() {
+        return Err(anyhow::anyhow!("`{}` is NULL", name).into());
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map_err(|_| anyhow::anyhow!("`{}` isn't valid UTF-8", name).into())
+}
+
+unsafe fn client_argument<'a>(client: *const IotaClient) -> crate::Result<&'a iota::Client> {
+    client
+        .as_ref()
+        .map(|client| &client.client)
+        .ok_or_else(|| anyhow::anyhow!("`client` is NULL").into())
+}
+
+fn parse_message_id(message_id: &str) -> crate::Result<MessageId> {
+    MessageId::from_str(message_id).map_err(|_| anyhow::anyhow!("invalid message id `{}`", message_id).into())
+}
+
+/// Creates a client from the JSON options `{ nodes, networkId, bech32Hrp, minPowScore, localPow, nodeSync,
+/// requestTimeout }`, only `nodes` is required. Returns `NULL` if the options are invalid.
+///
+/// # Safety
+///
+/// `options` must be `NULL` or a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn iota_client_new(options: *const c_char) -> *mut IotaClient {
+    ffi_call(ptr::null_mut(), || {
+        let options: ClientOptionsDto = serde_json::from_str(str_argument("options", options)?)
+            .map_err(|e| anyhow::anyhow!("invalid client options: {}", e))?;
+        let nodes: Vec<&str> = options.nodes.iter().map(String::as_str).collect();
+        let mut builder = iota::Client::build().with_nodes(&nodes)?;
+        if let Some(network_id) = &options.network_id {
+            builder = builder.with_network_id(network_id);
+        }
+        if let Some(bech32_hrp) = &options.bech32_hrp {
+            builder = builder.with_bech32_hrp(bech32_hrp);
+        }
+        if let Some(min_pow_score) = options.min_pow_score {
+            builder = builder.with_min_pow_score(min_pow_score);
+        }
+        if let Some(local_pow) = options.local_pow {
+            builder = builder.with_local_pow(local_pow);
+        }
+        if options.node_sync == Some(false) {
+            builder = builder.with_node_sync_disabled();
+        }
+        if let Some(request_timeout) = options.request_timeout {
+            builder = builder.with_request_timeout(Duration::from_millis(request_timeout));
+        }
+        let client = IotaClient {
+            client: builder.finish()?,
+        };
+        Ok(Box::into_raw(Box::new(client)))
+    })
+}
+
+/// Frees a client, `NULL` is ignored.
+///
+/// # Safety
+///
+/// `client` must be `NULL` or a client of `iota_client_new` not freed yet, no other thread may use it.
+#[no_mangle]
+pub unsafe extern "C" fn iota_client_free(client: *mut IotaClient) {
+    if !client.is_null() {
+        ffi_call((), || {
+            drop(Box::from_raw(client));
+            Ok(())
+        })
+    }
+}
+
+/// Frees a string returned by a function of the library, `NULL` is ignored.
+///
+/// # Safety
+///
+/// `value` must be `NULL` or a string of the library not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn iota_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        ffi_call((), || {
+            drop(CString::from_raw(value));
+            Ok(())
+        })
+    }
+}
+
+/// The message of the error of the last failed call of the calling thread, `NULL` if no call failed. The string
+/// belongs to the library, it's valid until the next failed call of the thread.
+#[no_mangle]
+pub extern "C" fn iota_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last_error| match &*last_error.borrow() {
+        Some((message, _)) => message.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+/// The code of the error of the last failed call of the calling thread, the codes of the Node.js binding like
+/// `ERESPONSE`. `NULL` if no call failed or if the error has no code. The string belongs to the library, it's valid
+/// until the next failed call of the thread.
+#[no_mangle]
+pub extern "C" fn iota_last_error_code() -> *const c_char {
+    LAST_ERROR.with(|last_error| match &*last_error.borrow() {
+        Some((_, Some(code))) => code.as_ptr(),
+        _ => ptr::null(),
+    })
+}
+
+/// Gets the info of a node, as JSON.
+///
+/// # Safety
+///
+/// `client` must be a client of `iota_client_new`.
+#[no_mangle]
+pub unsafe extern "C" fn iota_get_info(client: *const IotaClient) -> *mut c_char {
+    ffi_string_call(|| {
+        let info = block_on(client_argument(client)?.get_info())?;
+        Ok(serde_json::to_string(&info).unwrap())
+    })
+}
+
+/// Gets a message by its hex id, as JSON in the shape of the messages of the Node.js binding.
+///
+/// # Safety
+///
+/// `client` must be a client of `iota_client_new` and `message_id` a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn iota_get_message(client: *const IotaClient, message_id: *const c_char) -> *mut c_char {
+    ffi_string_call(|| {
+        let client = client_argument(client)?;
+        let message_id = parse_message_id(str_argument("message_id", message_id)?)?;
+        let message = block_on(client.get_message().data(&message_id))?;
+        let bech32_hrp = client.get_network_info().bech32_hrp;
+        Ok(serde_json::to_string(&MessageResponseDto::new(&message, &bech32_hrp)?).unwrap())
+    })
+}
+
+/// Gets an output by its hex id, as JSON with its address bech32 encoded with the network HRP.
+///
+/// # Safety
+///
+/// `client` must be a client of `iota_client_new` and `output_id` a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn iota_get_output(client: *const IotaClient, output_id: *const c_char) -> *mut c_char {
+    ffi_string_call(|| {
+        let client = client_argument(client)?;
+        let output_id = str_argument("output_id", output_id)?;
+        let output_id =
+            UTXOInput::from_str(output_id).map_err(|_| anyhow::anyhow!("invalid output id `{}`", output_id))?;
+        let output = block_on(client.get_output(&output_id))?;
+        let bech32_hrp = client.get_network_info().bech32_hrp;
+        Ok(serde_json::to_string(&OutputMetadataDto::new(output, &bech32_hrp)?).unwrap())
+    })
+}
+
+/// Gets the balance of a bech32 or hex address, as the JSON `{ address, addressHex, balance, dustAllowed }`.
+///
+/// # Safety
+///
+/// `client` must be a client of `iota_client_new` and `address` a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn iota_get_address_balance(client: *const IotaClient, address: *const c_char) -> *mut c_char {
+    ffi_string_call(|| {
+        let client = client_argument(client)?;
+        let address = str_argument("address", address)?;
+        let parsed = parse_address(address, None).map_err(|e| Error::InvalidAddress(address.to_string(), e))?;
+        let bech32_hrp = client.get_network_info().bech32_hrp;
+        let address = match parsed.encoding {
+            AddressEncoding::Bech32 => address.to_string(),
+            AddressEncoding::Hex => address_to_bech32(&parsed.address(), &bech32_hrp)?,
+        };
+        let balance = block_on(client.get_address_balances(&[address.into()]))?
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("the node returned no balance"))?;
+        Ok(serde_json::to_string(&AddressBalanceDto::new(balance, &bech32_hrp)?).unwrap())
+    })
+}
+
+/// Submits an indexation message with `data_length` bytes of data, returning the JSON `{ messageId, message }` of the
+/// message accepted by the node.
+///
+/// # Safety
+///
+/// `client` must be a client of `iota_client_new`, `index` a NUL terminated string and `data` `NULL` or
+/// `data_length` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn iota_send_indexation(
+    client: *const IotaClient,
+    index: *const c_char,
+    data: *const u8,
+    data_length: usize,
+) -> *mut c_char {
+    ffi_string_call(|| {
+        let client = client_argument(client)?;
+        let mut sender = client.send().with_index(str_argument("index", index)?);
+        if !data.is_null() && data_length > 0 {
+            sender = sender.with_data(slice::from_raw_parts(data, data_length).to_vec());
+        }
+        let (message_id, message) = block_on(sender.finish_with_message())?;
+        let bech32_hrp = client.get_network_info().bech32_hrp;
+        let message = SentMessageDto {
+            message_id: message_id.to_string(),
+            message: MessageResponseDto::new(&message, &bech32_hrp)?,
+        };
+        Ok(serde_json::to_string(&message).unwrap())
+    })
+}
+
+/// Derives the public addresses `start` to `end` (excluded) of an account of a hex seed, as a JSON array of bech32
+/// addresses with the network HRP. No node is queried, set the `bech32Hrp` option to derive them offline.
+///
+/// # Safety
+///
+/// `client` must be a client of `iota_client_new` and `seed` a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn iota_generate_addresses(
+    client: *const IotaClient,
+    seed: *const c_char,
+    account_index: usize,
+    start: usize,
+    end: usize,
+) -> *mut c_char {
+    ffi_string_call(|| {
+        let client = client_argument(client)?;
+        let seed = hex::decode(str_argument("seed", seed)?)?;
+        let invalid_seed = || -> Error { anyhow::anyhow!("invalid seed").into() };
+        let bip39_seed;
+        let ed25519_seed;
+        let signer = if seed.len() == BIP39_SEED_LENGTH {
+            bip39_seed = Bip39Seed::from_bytes(&seed).map_err(|_| invalid_seed())?;
+            SignerType::Bip39Seed(&bip39_seed)
+        } else {
+            ed25519_seed = Seed::from_ed25519_bytes(&seed).map_err(|_| invalid_seed())?;
+            SignerType::Seed(&ed25519_seed)
+        };
+        let addresses = client
+            .find_addresses(signer)
+            .with_account_index(account_index)
+            .with_range(start..end)
+            .finish()?;
+        let addresses: Vec<String> = addresses.iter().map(ToString::to_string).collect();
+        Ok(serde_json::to_string(&addresses).unwrap())
+    })
+}
+
+/// Checks a bech32 or hex address, a bech32 address must have the `expected_hrp` unless it's `NULL`. An invalid
+/// address isn't an error, `iota_last_error_message` is only set by invalid arguments.
+///
+/// # Safety
+///
+/// `address` must be a NUL terminated string and `expected_hrp` `NULL` or a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn iota_is_address_valid(address: *const c_char, expected_hrp: *const c_char) -> bool {
+    ffi_call(false, || {
+        let address = str_argument("address", address)?;
+        let expected_hrp = if expected_hrp.is_null() {
+            None
+        } else {
+            Some(str_argument("expected_hrp", expected_hrp)?)
+        };
+        Ok(parse_address(address, expected_hrp).is_ok())
+    })
+}
@@ -0,0 +1,50 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! C binding to the client library, see `include/iota_client.h`. The address utilities, the DTOs and the error codes
+//! are the ones of the Node.js binding, the DTOs are returned as JSON strings with the same keys as the JS objects.
+
+// the shared modules are only used by the C API
+#![cfg_attr(not(feature = "ffi"), allow(dead_code))]
+
+#[path = "../../node/native/src/address.rs"]
+mod address;
+// the DTOs of the Node.js binding, only a part of them is used here
+#[allow(dead_code)]
+#[path = "../../node/native/src/classes/client/dto.rs"]
+mod dto;
+#[path = "../../node/native/src/error_codes.rs"]
+mod error_codes;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+use address::{AddressError, Bech32Error};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0}")]
+    Anyhow(#[from] anyhow::Error),
+    #[error("{0}")]
+    Client(#[from] iota::client::Error),
+    #[error("{0}")]
+    Address(#[from] AddressError),
+    #[error("{0}")]
+    Bech32(#[from] Bech32Error),
+    #[error("invalid address `{0}`: {1}")]
+    InvalidAddress(String, AddressError),
+    #[error("{0}")]
+    Hex(#[from] hex::FromHexError),
+    /// A panic caught at the FFI boundary.
+    #[error("{0}")]
+    Panic(String),
+    #[error("{0}")]
+    Message(iota::message::Error),
+}
+
+impl From<iota::message::Error> for Error {
+    fn from(error: iota::message::Error) -> Self {
+        Self::Message(error)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,73 @@
+"""Runs a command against a mock node: `python3 tests/mock_node.py <command>...`.
+
+The node url is appended to the command, which exits with the status of the command. The node records the posted
+messages and serves them back under the tip id, any other message is unknown.
+"""
+
+import json
+import subprocess
+import sys
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+TIP_ID = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+
+
+class MockNodeHandler(BaseHTTPRequestHandler):
+    def log_message(self, *args):
+        pass
+
+    def respond(self, status, data=None):
+        body = json.dumps({'data': data}).encode() if data is not None else b''
+        self.send_response(status)
+        self.send_header('content-type', 'application/json')
+        self.send_header('content-length', str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+    def do_GET(self):
+        messages = self.server.messages
+        if self.path == '/api/v1/info':
+            self.respond(200, {
+                'name': 'HORNET',
+                'version': '0.6.0',
+                'isHealthy': True,
+                'networkId': 'testnet',
+                'bech32HRP': 'atoi',
+                'minPowScore': 10,
+                'latestMilestoneIndex': 1,
+                'solidMilestoneIndex': 1,
+                'pruningIndex': 0,
+                'features': [],
+            })
+        elif self.path == '/api/v1/tips':
+            self.respond(200, {'tip1MessageId': TIP_ID, 'tip2MessageId': TIP_ID})
+        elif self.path == '/api/v1/messages/{}'.format(TIP_ID) and messages:
+            self.respond(200, messages[-1])
+        else:
+            self.respond(404)
+
+    def do_POST(self):
+        body = self.rfile.read(int(self.headers['content-length']))
+        if self.path == '/api/v1/messages':
+            self.server.messages.append(json.loads(body))
+            self.respond(201, {'messageId': TIP_ID})
+        else:
+            self.respond(404)
+
+
+def main():
+    node = ThreadingHTTPServer(('127.0.0.1', 0), MockNodeHandler)
+    node.messages = []
+    threading.Thread(target=node.serve_forever, daemon=True).start()
+    url = 'http://127.0.0.1:{}'.format(node.server_address[1])
+    try:
+        status = subprocess.call(sys.argv[1:] + [url])
+    finally:
+        node.shutdown()
+        node.server_close()
+    sys.exit(status)
+
+
+if __name__ == '__main__':
+    main()
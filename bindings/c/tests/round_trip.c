@@ -0,0 +1,79 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// The round trip of the C binding against a node, run by `make test` with the url of a mock node as argument.
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "iota_client.h"
+
+#define TIP_ID "52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649"
+#define SEED "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2"
+#define INDEX "IOTA.RS C"
+
+#define CHECK(condition)                                                                                              \
+    do {                                                                                                              \
+        if (!(condition)) {                                                                                           \
+            const char *error = iota_last_error_message();                                                            \
+            fprintf(stderr, "%s:%d: `%s` failed, last error: %s\n", __FILE__, __LINE__, #condition,                   \
+                    error ? error : "none");                                                                          \
+            exit(1);                                                                                                  \
+        }                                                                                                             \
+    } while (0)
+
+int main(int argc, char **argv) {
+    CHECK(argc == 2);
+
+    char options[256];
+    snprintf(options, sizeof(options), "{\"nodes\":[\"%s\"],\"nodeSync\":false,\"localPow\":true}", argv[1]);
+    IotaClient *client = iota_client_new(options);
+    CHECK(client != NULL);
+
+    char *info = iota_get_info(client);
+    CHECK(info != NULL);
+    CHECK(strstr(info, "\"networkId\":\"testnet\"") != NULL);
+    iota_string_free(info);
+
+    // the errors
+    CHECK(iota_get_message(client, "invalid") == NULL);
+    CHECK(strstr(iota_last_error_message(), "invalid message id") != NULL);
+    CHECK(iota_get_message(client, TIP_ID) == NULL);
+    CHECK(strcmp(iota_last_error_code(), "ERESPONSE") == 0);
+    CHECK(iota_client_new("{}") == NULL);
+    CHECK(strstr(iota_last_error_message(), "invalid client options") != NULL);
+
+    // the indexation round trip
+    const char data[] = "c round trip";
+    char *sent = iota_send_indexation(client, INDEX, (const uint8_t *)data, strlen(data));
+    CHECK(sent != NULL);
+    CHECK(strstr(sent, "\"messageId\":\"" TIP_ID "\"") != NULL);
+    CHECK(strstr(sent, "\"index\":\"" INDEX "\"") != NULL);
+    iota_string_free(sent);
+
+    char *message = iota_get_message(client, TIP_ID);
+    CHECK(message != NULL);
+    CHECK(strstr(message, "\"parent1\":\"" TIP_ID "\"") != NULL);
+    CHECK(strstr(message, "\"index\":\"" INDEX "\"") != NULL);
+    iota_string_free(message);
+
+    // the addresses, bech32 encoded with the HRP of the node
+    char *addresses = iota_generate_addresses(client, SEED, 0, 0, 2);
+    CHECK(addresses != NULL);
+    CHECK(addresses[0] == '[' && addresses[1] == '"');
+    char *address = addresses + 2;
+    *strchr(address, '"') = '\0';
+    CHECK(strncmp(address, "atoi1", 5) == 0);
+    CHECK(iota_is_address_valid(address, "atoi"));
+    CHECK(!iota_is_address_valid(address, "iota"));
+    CHECK(!iota_is_address_valid("atoi1invalid", NULL));
+    CHECK(iota_generate_addresses(client, "invalid", 0, 0, 2) == NULL);
+    iota_string_free(addresses);
+
+    iota_client_free(client);
+    iota_client_free(NULL);
+    iota_string_free(NULL);
+    printf("round trip passed\n");
+    return 0;
+}
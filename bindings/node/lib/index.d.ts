@@ -1,46 +1,298 @@
 import {
   NodeInfo,
   MessageMetadata,
+  MessageConfirmation,
   OutputMetadata,
+  AddressActivity,
   MilestoneMetadata,
+  MilestoneUtxoChanges,
+  NodeAuth,
+  NodeOptions,
+  StorageOptions,
+  Peer,
   BrokerOptions,
   Address,
   AddressBalance,
   Message,
-  MessageDto
+  MessageDto,
+  MessageJson,
+  ParsedAddress,
+  HexAddress,
+  Bech32Data,
+  PreparedTransactionDataDto,
+  RemainderDto,
+  TransactionPayloadDto,
+  LedgerInclusionState
 } from './types'
 
-export declare type Api = 'GetHealth' | 'GetInfo' | 'GetTips' | 'PostMessage' | 'GetOutput' | 'GetMilestone'
+export declare type Api = 'GetHealth' | 'GetInfo' | 'GetTips' | 'PostMessage' | 'GetOutput' | 'GetMilestone' | 'GetMessage' | 'GetAddress' | 'GetPeers'
+
+export declare type NonceProvider = (bytes: Buffer, targetScore: number) => Promise<bigint | number | string> | bigint | number | string
 
 export declare class ClientBuilder {
-  node(url: string): ClientBuilder
+  // a node with options only submits the messages, does the remote PoW or serves MQTT with the allowed roles
+  node(url: string, options?: NodeOptions): ClientBuilder
   nodes(urls: string[]): ClientBuilder
+  // cross-checks the balance and the output queries
+  quorum(enabled: boolean): ClientBuilder
   quorumSize(size: number): ClientBuilder
   quorumThreshold(threshold: number): ClientBuilder
+  // only counts the quorum answers of the nodes whose latest milestone is valid
+  quorumMilestoneValidation(enabled: boolean): ClientBuilder
+  // the mainnet coordinator keys by default
+  coordinatorKeys(keys: CoordinatorKeys): ClientBuilder
+  // the address indexes queried at once by the seed scans, 20 by default
+  addressScanWindow(windowSize: number): ClientBuilder
+  // the concurrent requests of the seed scans, 10 by default
+  addressScanParallelism(parallelism: number): ClientBuilder
+  // the concurrent requests of the output, message and balance lookups, 10 by default
+  requestParallelism(parallelism: number): ClientBuilder
+  // refuses the transfers spending from an address that already spent outputs, disabled by default
+  addressReuseCheck(enabled: boolean): ClientBuilder
   brokerOptions(options: BrokerOptions): ClientBuilder
   nodeSyncInterval(interval: number): ClientBuilder
   disableNodeSync(): ClientBuilder
+  // a client without nodes, the bech32 HRP is required and the network id to build messages
+  offline(): ClientBuilder
   requestTimeout(timeoutMs: number): ClientBuilder
+  // the time the info of each node is cached, a minute by default, 0 disables the cache
+  nodeInfoTtl(ttlMs: number): ClientBuilder
+  // the retries of a request answered with 429 or 503, 3 by default
+  rateLimitRetries(retries: number): ClientBuilder
+  // the longest `Retry-After` delay waited, a minute by default
+  rateLimitMaxDelay(maxDelayMs: number): ClientBuilder
+  outputCache(unspentTtlMs: number): ClientBuilder
   apiTimeout(api: Api, timeoutMs: number): ClientBuilder
   localPow(local: boolean): ClientBuilder
+  powWorkerCount(count: number): ClientBuilder
+  // outsources the PoW, the provider gets the message bytes without the nonce, 60 seconds timeout by default
+  nonceProvider(provider: NonceProvider, options?: { timeout?: number }): ClientBuilder
+  // does the local PoW when the nonce provider fails or times out, disabled by default
+  localPowFallback(enabled: boolean): ClientBuilder
+  // the network parameters reported by the nodes unless set, set all three for a fully offline use
+  bech32Hrp(hrp: string): ClientBuilder
+  networkId(networkId: string): ClientBuilder
+  minPowScore(score: number): ClientBuilder
+  nodeAuth(auth: NodeAuth): ClientBuilder
+  // path of a PEM CA certificate trusted by the node requests and the TLS broker connections
+  caCertificate(path: string): ClientBuilder
+  // stores the client state in a directory instead of the memory, so it's kept across restarts
+  storage(options: StorageOptions): ClientBuilder
+  // headers added to every node request, like a trace id
+  requestHeaders(headers: { [name: string]: string }): ClientBuilder
   build(): Client
+  // syncs the nodes on a background thread
+  buildAsync(): Promise<Client>
+}
+
+export declare interface ParentsOptions {
+  skipVerification?: boolean
 }
 
 export declare class MessageSender {
-  seed(seed: string): MessageSender
-  index(index: string): MessageSender
-  data(data: Uint8Array): MessageSender
+  seed(seed: Seed): MessageSender
+  // the addresses to select the inputs from, required without seed
+  accountAddresses(addresses: AccountAddress[]): MessageSender
+  // signs the transaction with an external signer or a Stronghold signer instead of the seed
+  signer(signer: TransactionSigner | SignerOptions): MessageSender
+  index(index: string | Uint8Array): MessageSender
+  data(data: Uint8Array | string): MessageSender
   parent(messageId: string): MessageSender
+  // the parents instead of the tips of the node, checked on the node unless `skipVerification` is set
+  parents(messageIds: string[], options?: ParentsOptions): MessageSender
   accountIndex(index: number): MessageSender
   initialAddressIndex(index: number): MessageSender
+  // skips the dust protection check of the outputs below 1 Mi, the node validates them after the PoW
+  dustCheckDisabled(): MessageSender
+  // skips the address reuse check of the client for this transfer
+  addressReuseAllowed(): MessageSender
+  // the strategy selecting the inputs, ignored if explicit inputs are added
+  inputSelection(strategy: InputSelectionStrategy): MessageSender
+  // also spends the dust allowance outputs, skipped by default
+  dustAllowanceInputs(): MessageSender
+  // the address receiving the remainder, a new internal address of the account by default
+  remainderStrategy(strategy: RemainderStrategy): MessageSender
+  // sends the remainder to the address instead, which must be an address of the client network
+  remainderAddress(address: string): MessageSender
+  // adds a remainder below the dust threshold to the first output instead of failing the dust check
+  dustRemainderToFirstOutput(): MessageSender
+  // the explicit inputs are spent in their order until the outputs are covered
   input(transactionId: string, index: number): MessageSender
   output(address: string, value: number): MessageSender
-  submit(): Promise<string>
+  // the address can then hold a dust output per 100000 of allowance, the amount must be at least 1000000
+  dustAllowanceOutput(address: string, value: number): MessageSender
+  // the outputs of the same type to the same address are merged into one output
+  outputs(outputs: TransferOutput[]): MessageSender
+  indexation(indexation: { index: string | Uint8Array, data?: Uint8Array | string }): MessageSender
+  // the calls of the sender reject with `ECANCELLED` once the signal aborts
+  signal(signal: AbortSignal): MessageSender
+  prepare(): Promise<PreparedTransactionDataDto>
+  // selects the inputs and builds the essence without any PoW or submit, signed with `sign`
+  dryRun(options?: DryRunOptions): Promise<TransactionPlan>
+  // submits the essence of the plan, rejects with `ESTALEPLAN` if one of its inputs was spent since the dry run
+  submitPlan(plan: TransactionPlan): Promise<SentMessage>
+  submit(): Promise<SentMessage>
+  /** @deprecated use the messageId of the SentMessage */
+  submit(options: MessageIdOnlyOptions): Promise<string>
+}
+
+export declare interface DryRunOptions {
+  // signs the essence with the seed or the signer, defaults to false
+  sign?: boolean
+}
+
+// a dust output of a transaction plan, its address must allow one more dust output
+export declare interface DustWarning {
+  address: string
+  amount: number
+  // whether it's the remainder output
+  remainder: boolean
+}
+
+export declare interface TransactionPlan {
+  prepared: PreparedTransactionDataDto
+  // the signed transaction of a signed dry run
+  payload?: TransactionPayloadDto
+  // the length of the packed message in bytes
+  messageLength: number
+  dustWarnings: DustWarning[]
+}
+
+export declare type TransferOutputType = 'SignatureLockedSingle' | 'DustAllowance'
+
+export declare interface TransferOutput {
+  // bech32 or hex address
+  address: string
+  amount: number
+  // defaults to SignatureLockedSingle
+  type?: TransferOutputType
+}
+
+export declare interface AccountAddress {
+  // bech32 or hex address
+  address: string
+  addressIndex: number
+  // whether it's an internal (remainder) address, defaults to false
+  internal?: boolean
+}
+
+export declare interface TransactionSigningAddress {
+  address: string
+  accountIndex: number
+  addressIndex: number
+  internal: boolean
+}
+
+export declare interface EssenceSignature {
+  // hex-encoded, optionally `0x` prefixed
+  publicKey: string
+  signature: string
+}
+
+// gets the packed essence and the addresses signing it, resolves to one Ed25519 signature of the essence per address
+export declare type TransactionSigner = (essence: Buffer, signers: TransactionSigningAddress[]) => Promise<EssenceSignature[]> | EssenceSignature[]
+
+// the options of a signer whose seed is held in a Stronghold snapshot, created with `createStrongholdSnapshot`
+export declare interface StrongholdSignerOptions {
+  type: 'Stronghold'
+  snapshotPath: string
+  password: string
+}
+
+// the options of a signer delegating the derivation and the signing to a Ledger Nano running the IOTA app, or to the
+// Speculos simulator
+export declare interface LedgerNanoSignerOptions {
+  type: 'LedgerNano'
+  simulator?: boolean
+}
+
+export declare type SignerOptions = StrongholdSignerOptions | LedgerNanoSignerOptions
+
+// a hex encoded seed, a mnemonic, or a Stronghold or Ledger Nano signer
+export declare type Seed = string | { signer: SignerOptions }
+
+// the largest outputs first minimize the number of inputs, the smallest outputs first consolidate them
+export declare type InputSelectionStrategy = 'LargestFirst' | 'SmallestFirst'
+
+// the first unused internal address of the account, or the address of the first input
+export declare type RemainderStrategy = 'ChangeAddress' | 'ReuseFirstInputAddress'
+
+// an unspent output of the account exported by `Client.getInputCandidates`, to prepare a transaction offline
+export declare interface InputCandidate {
+  outputId: string
+  address: { type: 1, address: string }
+  addressIndex: number
+  internal: boolean
+  amount: number
+  outputType: 'SignatureLockedSingle' | 'SignatureLockedDustAllowance'
+}
+
+export declare interface PrepareTransactionOptions {
+  // the seed or the account addresses to select the inputs from, the addresses don't need the seed
+  seed?: Seed
+  // the Stronghold signer to select the inputs from, instead of the seed
+  signer?: SignerOptions
+  addresses?: AccountAddress[]
+  // the unspent outputs to select the inputs from, neither the seed nor a node is needed
+  inputCandidates?: InputCandidate[]
+  accountIndex?: number
+  initialAddressIndex?: number
+  // `LargestFirst` by default
+  inputSelection?: InputSelectionStrategy
+  // the output ids to spend, in their order instead of the strategy
+  inputs?: string[]
+  includeDustAllowanceInputs?: boolean
+  // `ChangeAddress` by default, exclusive with the `remainderAddress`
+  remainderStrategy?: RemainderStrategy
+  remainderAddress?: string
+  dustRemainderToFirstOutput?: boolean
+  index?: string
+  data?: Uint8Array | string
+  // skips the dust protection check of the outputs below 1 Mi
+  disableDustCheck?: boolean
+  // skips the address reuse check of the client
+  allowAddressReuse?: boolean
+}
+
+export declare interface SentMessage {
+  messageId: string
+  message: Message
+  // set on the messages sent by `MessageSender.submit` with a remainder output
+  remainder?: RemainderDto
+}
+
+// resolves to the message id only, kept during the deprecation period
+export declare interface MessageIdOnlyOptions {
+  messageIdOnly: true
+}
+
+export declare class ValueTransferSender {
+  seed(seed: Seed): ValueTransferSender
+  accountAddresses(addresses: AccountAddress[]): ValueTransferSender
+  signer(signer: TransactionSigner): ValueTransferSender
+  accountIndex(index: number): ValueTransferSender
+  initialAddressIndex(index: number): ValueTransferSender
+  dustCheckDisabled(): ValueTransferSender
+  addressReuseAllowed(): ValueTransferSender
+  parents(messageIds: string[], options?: ParentsOptions): ValueTransferSender
+  output(address: string, value: number): ValueTransferSender
+  dustAllowanceOutput(address: string, value: number): ValueTransferSender
+  outputs(outputs: TransferOutput[]): ValueTransferSender
+  // the indexation payload is nested in the transaction essence
+  index(index: string | Uint8Array): ValueTransferSender
+  data(data: Uint8Array | string): ValueTransferSender
+  indexation(indexation: { index: string | Uint8Array, data?: Uint8Array | string }): ValueTransferSender
+  prepare(): Promise<PreparedTransactionDataDto>
+  submit(): Promise<SentMessage>
+  /** @deprecated use the messageId of the SentMessage */
+  submit(options: MessageIdOnlyOptions): Promise<string>
 }
 
 export declare class UnspentAddressGetter {
   accountIndex(index: number): UnspentAddressGetter
   initialAddressIndex(index: number): UnspentAddressGetter
+  // also accepts an address holding a balance that was never spent from
+  includeFunded(): UnspentAddressGetter
   get(): Promise<[Address, number]>
 }
 
@@ -50,10 +302,58 @@ export declare class AddressFinder {
   get(): [Address, boolean][]
 }
 
+export declare class AddressGetter {
+  accountIndex(index: number): AddressGetter
+  range(start: number, end: number): AddressGetter
+  bech32Hrp(hrp: string): AddressGetter
+  includeInternal(): AddressGetter
+  // `[address, internal]` tuples if `includeInternal()` was called
+  get(): string[] | [string, boolean][]
+}
+
+export declare interface SeedBalance {
+  balance: string
+  // the used addresses with their balance
+  addresses: Array<{ address: string, balance: string, dustAllowed: boolean }>
+}
+
+export declare interface BalanceSplit {
+  // the amount of the outputs whose message is included in the ledger
+  confirmed: string
+  pending: string
+}
+
 export declare class BalanceGetter {
   accountIndex(index: number): BalanceGetter
   initialAddressIndex(index: number): BalanceGetter
-  get(): Promise<number>
+  gapLimit(limit: number): BalanceGetter
+  includeAddresses(): BalanceGetter
+  // true by default
+  confirmedOnly(confirmedOnly: boolean): BalanceGetter
+  split(): BalanceGetter
+  // the address scan rejects with `ECANCELLED` once the signal aborts
+  signal(signal: AbortSignal): BalanceGetter
+  // resolves to `BalanceSplit` if `split()` was called, or `SeedBalance` if `includeAddresses()` was called
+  get(): Promise<string | SeedBalance | BalanceSplit>
+}
+
+export declare type OutputType = 'SignatureLockedSingle' | 'SignatureLockedDustAllowance'
+
+export declare interface AddressOutputs {
+  outputIds: string[]
+  count: number
+  maxResults: number
+  // whether the node returned `maxResults` outputs, there might be more outputs on the address
+  truncated: boolean
+  // set if `resolve()` was called
+  outputs?: OutputMetadata[]
+}
+
+export declare class AddressOutputsGetter {
+  includeSpent(includeSpent: boolean): AddressOutputsGetter
+  outputType(type: OutputType): AddressOutputsGetter
+  resolve(): AddressOutputsGetter
+  get(): Promise<AddressOutputs>
 }
 
 export declare interface NetworkInfo {
@@ -61,45 +361,598 @@ export declare interface NetworkInfo {
   networkId: string
   minPowScore: number
   localPow: boolean
+  bech32HRP: string
+  powWorkerCount: number | null
+}
+
+export declare interface Histogram {
+  // the cumulative number of observations up to each bucket bound of 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1,
+  // 2.5, 5 and 10 seconds
+  buckets: number[]
+  // in seconds
+  sum: number
+  count: number
+}
+
+export declare interface EndpointMetrics {
+  node: string
+  method: string
+  // the path of the route, with `:id` in place of the ids, the addresses and the indexes
+  endpoint: string
+  requests: number
+  latency: Histogram
+}
+
+// the requests of the node syncing aren't included
+export declare interface Metrics {
+  endpoints: EndpointMetrics[]
+  // `timeout`, `connection`, `request`, `status_4xx` or `status_5xx`
+  errors: { [category: string]: number }
+  // the duration of the local PoW
+  pow: Histogram
+  messagesSubmitted: number
+  promotions: number
+  reattachments: number
+  confirmations: number
+  // the requests retried after a 429 or 503 answer
+  rateLimitRetries: number
+}
+
+export declare interface NodeSyncStatus {
+  url: string
+  synced: boolean
+  // null if the node didn't answer or was never checked
+  latestMilestoneIndex: number | null
+  solidMilestoneIndex: number | null
+  // Unix timestamp of the last check in milliseconds, null with the node syncing disabled
+  lastCheck: number | null
+}
+
+export declare interface FindMessagesOptions {
+  // maximum number of messages to return
+  limit?: number
+}
+
+export declare interface FindMessagePagesOptions {
+  // number of message ids of a page, 100 by default
+  pageSize?: number
+  // the nextCursor of the last processed page
+  cursor?: string
+  // only the messages referenced by a milestone after this index
+  referencedAfter?: number
+  // only the messages whose indexation data starts with the prefix
+  dataPrefix?: string | Uint8Array
+  // the page lookup rejects with `ECANCELLED` once the signal aborts
+  signal?: AbortSignal
+}
+
+export declare interface MessagePage {
+  messages: UnpackedMessage[]
+  // null after the last page
+  nextCursor: string | null
+  hasMore: boolean
+}
+
+export declare interface TransactionHistoryOptions {
+  // the seed options, ignored for a list of addresses
+  accountIndex?: number
+  initialAddressIndex?: number
+  gapLimit?: number
+  // 'entries' by default, 'jsonl' and 'csv' resolve to the export as a string
+  format?: 'entries' | 'jsonl' | 'csv'
+}
+
+export declare interface AddressDelta {
+  address: string
+  // as a string to avoid losing numeric precision, negative for a spend
+  delta: string
+}
+
+export declare interface TransactionHistoryEntry {
+  transactionId: string
+  // the message that got the transaction included, not its reattachments
+  messageId: string
+  milestoneIndex: number | null
+  timestamp: number | null
+  inclusionState: LedgerInclusionState | null
+  deltas: AddressDelta[]
+  counterparts: string[]
+}
+
+export declare interface InclusionProof {
+  messageId: string
+  milestoneIndex: number
+  // the hex encoded packed messages from the milestone message down to the message, each one a parent of the
+  // previous one
+  chain: string[]
+}
+
+export declare interface CoordinatorPublicKey {
+  // hex encoded
+  publicKey: string
+  // the first milestone index signed by the key
+  start: number
+  // the first milestone index not signed by the key anymore, every index from `start` without an end
+  end?: number
+}
+
+export declare interface CoordinatorKeys {
+  publicKeys: CoordinatorPublicKey[]
+  // the signatures of distinct keys a milestone needs
+  signatureCount: number
+}
+
+export declare interface FindOutputsQuery {
+  outputIds?: string[]
+  // bech32 or hex addresses
+  addresses?: string[]
+}
+
+export declare interface FindOutputsOptions {
+  // rejects with the first failed lookup instead of returning it in `errors`
+  atomic?: boolean
+}
+
+export declare interface OutputLookupError {
+  // set if the lookup of an output failed
+  outputId?: string
+  // set if the lookup of the outputs of an address failed
+  address?: string
+  error: string
+}
+
+export declare interface FoundOutputs {
+  outputs: OutputMetadata[]
+  errors: OutputLookupError[]
+}
+
+export declare interface ConsolidationOptions {
+  accountIndex?: number
+  // the address indexes to scan, the end is excluded, 0 to 20 by default
+  addressRange?: { start: number, end: number }
+  includeDustAllowance?: boolean
+  waitForConfirmation?: boolean
+  // rejects with `ECANCELLED` once the signal aborts, the batches already sent stay sent
+  signal?: AbortSignal
+}
+
+// an output, or the outputs sent in the same transaction
+export declare type BatchTransfer = { address: string, amount: number } | Array<{ address: string, amount: number }>
+
+export declare interface SendBatchOptions {
+  accountIndex?: number
+  initialAddressIndex?: number
+  // the transactions signed and submitted at once, the request parallelism of the client by default
+  parallelism?: number
+  // packs the consecutive transfers into a transaction while their outputs fit
+  packing?: boolean
+  // rejects with `ECANCELLED` once the signal aborts, the transfers already submitted stay submitted
+  signal?: AbortSignal
+}
+
+// the message id of the transfer, or its error
+export declare type BatchTransferResult =
+  | { messageId: string }
+  | { error: { message: string, code?: ErrorCode, details?: ErrorDetails } }
+
+export declare interface SendDataOptions {
+  // each fragment message is a parent of the next one, the messages are then submitted one after the other
+  chaining?: boolean
+  // the data bytes of a fragment, without its header, a fragment fills a message by default
+  fragmentLength?: number
+  // rejects with `ECANCELLED` once the signal aborts
+  signal?: AbortSignal
+}
+
+// the fragment messages of the data, in the order of the fragments
+export declare interface DataMessage {
+  messageIds: string[]
+  // the hex-encoded BLAKE2b-256 hash of the data
+  contentHash: string
+}
+
+export declare interface RetryUntilIncludedOptions {
+  // milliseconds between the metadata polls, 5 seconds by default
+  interval?: number
+  // 40 by default
+  maxAttempts?: number
+  // stops the polls, rejects with `ECANCELLED` once the signal aborts
+  signal?: AbortSignal
+}
+
+export declare interface WaitForBalanceOptions {
+  // milliseconds between the balance polls, 5 seconds by default
+  interval?: number
+  // milliseconds, 10 minutes by default
+  timeout?: number
+  // stops the polls, rejects with `ECANCELLED` once the signal aborts
+  signal?: AbortSignal
+}
+
+export declare interface ReachedBalance {
+  address: string
+  balance: number
+  // the unspent outputs of the address
+  outputs: OutputMetadata[]
+}
+
+export declare interface MessageInclusionState {
+  messageId: string
+  // null while no milestone references the message
+  inclusionState: 'included' | 'conflicting' | 'noTransaction' | null
+}
+
+export declare type ErrorCode =
+  // a request to a node timed out
+  | 'ETIMEDOUT'
+  // no node could be reached
+  | 'ENODEUNREACHABLE'
+  // the proof of work failed or no node does the remote PoW
+  | 'EPOW'
+  // the nonce provider failed or timed out
+  | 'ENONCEPROVIDER'
+  // the milestone or the message is pruned by the node
+  | 'EPRUNED'
+  // the node refused the credentials
+  | 'EUNAUTHORIZED'
+  // a dust output or remainder the address can't hold
+  | 'EDUST'
+  | 'ENOTENOUGHBALANCE'
+  // an amount, a balance or a sum of amounts exceeds the IOTA supply, see `amount`
+  | 'EVALUEOVERFLOW'
+  // the outputs need more than 127 inputs, consolidate the outputs first
+  | 'ETOOMANYINPUTS'
+  // input addresses already spent outputs, with the address reuse check of the client
+  | 'EADDRESSREUSE'
+  // an input of the plan of `MessageSender.dryRun` was spent since the dry run
+  | 'ESTALEPLAN'
+  // the packed message of a `sendBatch` transfer failed, the transfer `transferIndex` has its error
+  | 'EBATCHMESSAGE'
+  // the transaction conflicts with the ledger
+  | 'ECONFLICTING'
+  // the message isn't included after the max attempts, see `states`, or the transaction isn't included
+  | 'ENOTINCLUDED'
+  // the balance of `waitForBalance` didn't reach the minimum amount before the timeout
+  | 'EBALANCETIMEOUT'
+  // the message is already referenced by a milestone
+  | 'EREFERENCED'
+  // the message of `createInclusionProof` isn't referenced by a milestone yet
+  | 'ENOTREFERENCED'
+  // the inclusion proof doesn't verify, see `link` and `reason`
+  | 'EINVALIDPROOF'
+  // the milestone isn't signed by the coordinator keys, see `milestoneIndex` and `reason`
+  | 'EINVALIDMILESTONE'
+  // the node doesn't know the custom parents of the message
+  | 'EUNKNOWNPARENTS'
+  // the node answered a message the client can't parse
+  | 'EINVALIDMESSAGE'
+  // the node answered with an error status
+  | 'ERESPONSE'
+  // the node is still rate limited once the retries are exhausted, see `status` and `retryAfterMs`
+  | 'ERATELIMITED'
+  // the operation needs a node, the client is offline
+  | 'EOFFLINE'
+  | 'ESIGNING'
+  // the Stronghold snapshot doesn't exist
+  | 'ESNAPSHOTNOTFOUND'
+  // the password can't decrypt the Stronghold snapshot
+  | 'ESTRONGHOLDPASSWORD'
+  // no Ledger Nano device or simulator was found
+  | 'ELEDGERNOTFOUND'
+  // the Ledger Nano is locked or the IOTA app isn't open
+  | 'ELEDGERAPPNOTOPEN'
+  // the user rejected the address or the transaction on the Ledger Nano
+  | 'ELEDGERDENIED'
+  | 'EINVALIDPARAMETER'
+  | 'EINVALIDADDRESS'
+  // the message is built for another network
+  | 'ENETWORKMISMATCH'
+  // the quorum nodes disagree, or fewer nodes than the quorum size are synced
+  | 'EQUORUM'
+  // an unexpected failure of the library
+  | 'EINTERNAL'
+
+// the fields depend on the code
+export declare interface ErrorDetails {
+  url?: string
+  // the node request of `ETIMEDOUT`, `ENODEUNREACHABLE` and `ERESPONSE`
+  node?: string
+  endpoint?: string
+  elapsedMs?: number
+  attempt?: number
+  address?: string
+  // the reused input addresses of `EADDRESSREUSE`
+  addresses?: string[]
+  // the custom parents of `EUNKNOWNPARENTS`, with the `answer` of the node refusing the message
+  parents?: string[]
+  answer?: string
+  amount?: number
+  availableAmount?: number
+  requiredAmount?: number
+  availableDustOutputs?: number
+  // the dust outputs left by the dust allowance inputs of `EDUST`, `availableDustOutputs` is the number allowed
+  dustOutputs?: number
+  messageId?: string
+  transactionId?: string
+  // the spent input of `ESTALEPLAN`
+  outputId?: string
+  // the transfer of `EBATCHMESSAGE` with the error of the packed message
+  transferIndex?: number
+  milestoneIndex?: number
+  pruningIndex?: number
+  attempts?: number
+  minAmount?: number
+  balance?: number
+  status?: number
+  // the delay the rate limited node of `ERATELIMITED` asks for
+  retryAfterMs?: number
+  parameter?: string
+  outputCount?: number
+  maxOutputCount?: number
+  // the answer of each quorum node
+  answers?: Array<{ node: string, answer: string }>
+  syncedNodes?: number
+  quorumSize?: number
+  snapshotPath?: string
+  // the network id of the message, and the network and the network id of the client
+  messageNetworkId?: string
+  network?: string
+  networkId?: string
+  // the index of the first broken link of the chain of `EINVALIDPROOF`, with the `reason`; the reason of
+  // `EINVALIDMILESTONE`
+  link?: number
+  reason?: string
+}
+
+export declare interface ClientError extends Error {
+  // not set if the error has no stable code
+  code?: ErrorCode
+  details?: ErrorDetails
+  // set with the `ENOTINCLUDED` code
+  states?: MessageInclusionState[]
 }
 
 export declare class Client {
   networkInfo(): NetworkInfo
+  getNetworkId(): string
+  getBech32Hrp(): string
+  // the HRP defaults to the network HRP of the client
+  hexToBech32(address: string, bech32Hrp?: string): string
+  bech32ToHex(address: string): HexAddress
+  refreshNetworkInfo(): Promise<NetworkInfo>
+  refreshNodeInfo(): Promise<NodeInfo>
+  getMetrics(): Metrics
+  // the metrics in the Prometheus text exposition format
+  getPrometheusMetrics(): string
+  currentNode(): string | null
+  syncedNodes(): string[]
+  nodeSyncStatus(): NodeSyncStatus[]
+  pinNode(url: string): Client
+  unpinNode(): Client
   subscriber(): TopicSubscriber
+  // typed and deduplicated topic events, stopped with `unsubscribe`
+  watchAddress(address: string, cb: (err: any, output: OutputMetadata) => void): TopicSubscriber
+  watchIndex(index: string | Uint8Array, cb: (err: any, message: UnpackedMessage) => void): TopicSubscriber
+  // the activity since the milestone is caught up on start and after every reconnection to the broker
+  monitorAddress(address: string, cb: AddressActivityCallback): AddressMonitor
+  monitorAddress(address: string, sinceMilestoneIndex: number, cb: AddressActivityCallback): AddressMonitor
+  confirmationListener(): ConfirmationListener
+  on(event: 'confirmed', cb: ConfirmationCallback, options?: ConfirmationOptions): ConfirmationListener
+  off(event: 'confirmed', cb: ConfirmationCallback): Client
+  requestListener(): RequestListener
+  // notified asynchronously, after the request is sent
+  on(event: 'request', cb: RequestEventCallback): RequestListener
+  on(event: 'response', cb: RequestEventCallback): RequestListener
+  off(event: 'request' | 'response', cb: RequestEventCallback): Client
   send(): MessageSender
-  getUnspentAddress(seed: string): UnspentAddressGetter
-  findAddresses(seed: string): AddressFinder
-  findMessages(indexationKeys: string[], messageIds: string[]): Promise<Message[]>
-  getBalance(seed: string): BalanceGetter
+  message(): ValueTransferSender
+  getUnspentAddress(seed: Seed): UnspentAddressGetter
+  findAddresses(seed: Seed): AddressFinder
+  getAddresses(seed: Seed): AddressGetter
+  findMessages(indexes: Array<string | Uint8Array>, messageIds?: string[], options?: FindMessagesOptions): Promise<UnpackedMessage[]>
+  findMessagePages(index: string | Uint8Array, options?: FindMessagePagesOptions): AsyncGenerator<MessagePage, void, undefined>
+  // in the order of the ids, null for the unknown messages
+  getMessages(messageIds: string[]): Promise<Array<UnpackedMessage | null>>
+  getBalance(seed: Seed): BalanceGetter
   getAddressBalances(addresses: string[]): Promise<AddressBalance[]>
-  retry(messageId: string): Promise<Message>
+  getTransactionHistory(seedOrAddresses: Seed | string[], options?: TransactionHistoryOptions & { format?: 'entries' }): Promise<TransactionHistoryEntry[]>
+  getTransactionHistory(seedOrAddresses: Seed | string[], options: TransactionHistoryOptions & { format: 'jsonl' | 'csv' }): Promise<string>
+  retry(messageId: string): Promise<SentMessage>
+  retryUntilIncluded(messageId: string, options?: RetryUntilIncludedOptions): Promise<MessageInclusionState[]>
+  waitForBalance(address: string, minAmount: number, options?: WaitForBalanceOptions): Promise<ReachedBalance>
+  prepareTransaction(outputs: TransferOutput[], options: PrepareTransactionOptions): Promise<PreparedTransactionDataDto>
+  // the unspent outputs of the account, the outputs and the remainder options are ignored
+  getInputCandidates(options: PrepareTransactionOptions): Promise<InputCandidate[]>
+  submitSignedTransaction(payload: TransactionPayloadDto): Promise<SentMessage>
+  consolidateOutputs(seed: Seed, options?: ConsolidationOptions): Promise<string[]>
+  sendBatch(seed: Seed, transfers: BatchTransfer[], options?: SendBatchOptions): Promise<BatchTransferResult[]>
+  sendData(index: string, data: string | Uint8Array, options?: SendDataOptions): Promise<DataMessage>
+  reassembleData(index: string, contentHash: string): Promise<Buffer>
 
   getInfo(): Promise<NodeInfo>
   getTips(): Promise<[string, string]>
-  postMessage(message: MessageDto): Promise<string>
+  getPeers(): Promise<Peer[]>
+  // the data of the answer of a route under /api/, such as a plugin route
+  callNodeApi(method: string, path: string, query?: Record<string, string | number | boolean>, body?: any): Promise<any>
+  postMessage(message: MessageDto): Promise<SentMessage>
+  /** @deprecated use the messageId of the SentMessage */
+  postMessage(message: MessageDto, options: MessageIdOnlyOptions): Promise<string>
   getMessage(): MessageFinder
   getOutput(outputId: string): Promise<OutputMetadata>
-  findOutputs(outputIds: string[], addresses: string[]): Promise<OutputMetadata[]>
-  getAddressOutputs(address: string): Promise<string[]>
+  getIncludedMessage(transactionId: string): Promise<UnpackedMessage>
+  findOutputs(query: FindOutputsQuery, options?: FindOutputsOptions): Promise<FoundOutputs>
+  getAddressOutputs(address: string): AddressOutputsGetter
   getAddressBalance(address: string): Promise<number>
   getMilestone(index: number): Promise<MilestoneMetadata>
-  reattach(messageId: string): Promise<Message>
-  promote(messageId: string): Promise<Message>
+  // rejects with `EINVALIDMILESTONE` if the milestone isn't signed by the coordinator keys
+  getValidatedMilestone(index: number): Promise<MilestoneMetadata>
+  getMilestoneUtxoChanges(index: number): Promise<MilestoneUtxoChanges>
+  // rejects with `ENOTREFERENCED` if no milestone references the message yet
+  createInclusionProof(messageId: string): Promise<InclusionProof>
+  // the submitted messages not referenced by a milestone yet, kept in the storage
+  getPendingMessages(): Promise<string[]>
+  reattach(messageId: string): Promise<SentMessage>
+  promote(messageId: string): Promise<SentMessage>
+  rebroadcast(messageId: string): Promise<Buffer>
 }
 
 export declare class MessageFinder {
   index(index: string): Promise<string[]>
-  data(messageId: string): Promise<Message>
-  raw(messageId: string): Promise<string>
+  data(messageId: string, options?: MessageFormatOptions): Promise<Message | MessageJson>
+  // the packed bytes of the message
+  raw(messageId: string): Promise<Buffer>
   children(messageId: string): Promise<string[]>
   metadata(messageId: string): Promise<MessageMetadata>
 }
 
 export declare type Callback = (err: any, data: any) => void
 
+export declare interface TopicEvent {
+  topic: string
+  // the parsed JSON payload, or the raw payload string if it isn't JSON
+  payload: any
+}
+
+export declare type TopicCallback = (err: any, event: TopicEvent) => void
+
+export declare interface ReconnectingEvent {
+  // starting at 1
+  attempt: number
+  // the delay before the attempt in milliseconds
+  delay: number
+}
+
 export declare class TopicSubscriber {
   topic(topic: string): TopicSubscriber
   topics(topic: string[]): TopicSubscriber
-  subscribe(cb: Callback): TopicSubscriber
-  unsubscribe(cb: Callback): TopicSubscriber
+  // delivers the payloads parsed into DTOs, skips the events delivered again
+  watch(kind: 'output' | 'message'): TopicSubscriber
+  // the topics are unsubscribed once the signal aborts
+  signal(signal: AbortSignal): TopicSubscriber
+  subscribe(cb: TopicCallback): TopicSubscriber
+  unsubscribe(cb?: Callback): TopicSubscriber
+  // the MQTT connection events, got once subscribed
+  on(event: 'connected' | 'disconnected', cb: () => void): TopicSubscriber
+  on(event: 'reconnecting', cb: (event: ReconnectingEvent) => void): TopicSubscriber
+  // the reconnection gave up
+  on(event: 'error', cb: (err: Error) => void): TopicSubscriber
+}
+
+export declare type ConfirmationCallback = (err: any, confirmation: MessageConfirmation) => void
+
+export declare interface ConfirmationOptions {
+  // the messages to watch, defaults to the messages submitted through the client
+  messageIds?: string[]
+  // the polling interval in milliseconds, defaults to 1000
+  interval?: number
+}
+
+export declare type AddressActivityCallback = (err: any, activity: AddressActivity) => void
+
+export declare class AddressMonitor {
+  // undefined until the first catch-up of a monitor without a starting milestone
+  lastMilestoneIndex(): number | undefined
+  stop(): AddressMonitor
+}
+
+export declare class ConfirmationListener {
+  messageId(messageId: string): ConfirmationListener
+  messageIds(messageIds: string[]): ConfirmationListener
+  unwatch(messageId: string): ConfirmationListener
+  submitted(): ConfirmationListener
+  interval(intervalMs: number): ConfirmationListener
+  listen(cb: ConfirmationCallback): ConfirmationListener
+  stop(): ConfirmationListener
+}
+
+export declare interface RequestEventRequest {
+  method: string
+  // with the query
+  path: string
+  bodySize: number
+  // without the credentials of the node URL
+  node: string
+}
+
+export declare interface RequestEventResponse {
+  // null if the request failed without a response
+  status: number | null
+  // in milliseconds
+  elapsed: number
+  // the error of the request if it failed without a response, like a timeout
+  error: string | null
+}
+
+export declare interface RequestEvent {
+  type: 'request' | 'response'
+  request: RequestEventRequest
+  // only set on the response events
+  response?: RequestEventResponse
+}
+
+export declare type RequestEventCallback = (err: any, event: RequestEvent) => void
+
+export declare class RequestListener {
+  // receives the request and the response events
+  listen(cb: RequestEventCallback): RequestListener
+  stop(): RequestListener
 }
+
+export declare function isAddressValid(address: string, expectedHrp?: string): boolean
+export declare function parseAddress(address: string, expectedHrp?: string): ParsedAddress
+export declare function hexToBech32(address: string, bech32Hrp: string): string
+export declare function bech32ToHex(address: string): HexAddress
+// the data starts with the version or address type byte, e.g. `01` for an Ed25519 address
+export declare function bech32Encode(hrp: string, dataHex: string): string
+export declare function bech32Decode(bech32String: string): Bech32Data
+// a 12, 15, 18, 21 or 24 (default) words English mnemonic
+export declare function generateMnemonic(wordCount?: number): string
+// the hex encoded BIP39 seed of the mnemonic
+export declare function mnemonicToSeed(mnemonic: string, passphrase?: string): string
+// creates a Stronghold snapshot holding the seed, it derives the same addresses as the seed
+export declare function createStrongholdSnapshot(snapshotPath: string, password: string, seed: string): void
+// signs a transaction prepared by `Client.prepareTransaction` without a node
+export declare function signTransaction(preparedData: PreparedTransactionDataDto, seed: Seed): TransactionPayloadDto
+export declare function signTransaction(preparedData: PreparedTransactionDataDto, signer: TransactionSigner): Promise<TransactionPayloadDto>
+
+export declare interface MessageFormatOptions {
+  // `dto` (default) returns the `MessageDto` shape, `rest` the node REST API shape
+  format?: 'dto' | 'rest'
+}
+
+export declare interface ParseMessageJsonOptions extends MessageFormatOptions {
+  // HRP of the bech32 output addresses in the `dto` format, defaults to `iot`
+  bech32Hrp?: string
+}
+
+export declare function parseMessageJson(message: string | MessageJson, options?: ParseMessageJsonOptions): Message | MessageJson
+
+export declare interface MessageFromBytesOptions {
+  // HRP of the bech32 output addresses, defaults to `iot`
+  bech32Hrp?: string
+}
+
+export declare type UnpackedMessage = Message & { messageId: string }
+
+export declare function messageFromBytes(bytes: Uint8Array | string, options?: MessageFromBytesOptions): UnpackedMessage
+
+export declare interface PackedMessage {
+  bytes: Buffer
+  messageId: string
+}
+
+// `networkId`, `parent1`, `parent2` and `nonce` must be set, a nonce of 0 skips the proof of work
+export declare function messageToBytes(message: MessageDto, options?: MessageFromBytesOptions): PackedMessage
+
+// the public key must be 32 bytes and the signature 64 bytes, hex encoded
+export declare function verifyEd25519Signature(publicKey: string, signature: string, messageBytes: Uint8Array | string): boolean
+
+export declare function computeMessageId(messageBytes: Uint8Array | string): string
+
+export declare function computeTransactionId(payload: TransactionPayloadDto): string
+
+// the coordinator keys default to the mainnet ones, throws with `EINVALIDPROOF` if the proof doesn't verify
+export declare function verifyInclusionProof(proof: InclusionProof | string, coordinatorKeys?: CoordinatorKeys): void
+
+// validates the milestone of a packed message, throws with `EINVALIDMILESTONE` if it isn't signed by the coordinator
+export declare function validateMilestone(messageBytes: Uint8Array | string, coordinatorKeys?: CoordinatorKeys): void
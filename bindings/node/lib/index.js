@@ -5,94 +5,712 @@ const {
   Client,
   ClientBuilder,
   TopicSubscriber,
+  ConfirmationListener,
+  RequestListener,
   MessageGetter,
   MessageSender,
   UnspentAddressGetter,
   AddressFinder,
-  BalanceGetter
+  AddressGetter,
+  BalanceGetter,
+  AddressOutputsGetter,
+  isAddressValid,
+  parseAddress,
+  hexToBech32,
+  bech32ToHex,
+  bech32Encode,
+  bech32Decode,
+  generateMnemonic,
+  mnemonicToSeed,
+  createStrongholdSnapshot,
+  signTransaction,
+  transactionSigningData,
+  signTransactionWithSignatures,
+  parseMessageJson,
+  messageFromBytes,
+  messageToBytes,
+  verifyEd25519Signature,
+  computeMessageId,
+  computeTransactionId,
+  verifyInclusionProof,
+  validateMilestone,
+  pollNonceRequests,
+  answerNonceRequest,
+  beginCancellation,
+  endCancellation,
+  cancel,
+  releaseCancellation
 } = require('../native')
 
 function promisify (fn, parse = true) {
   return function () {
-    return new Promise((resolve, reject) => fn.apply(this, [...Array.from(arguments), (err, data) => {
-      if (err) {
-        reject(err)
-      } else {
-        resolve(parse && data ? JSON.parse(data) : data)
+    return new Promise((resolve, reject) => {
+      pendingCalls++
+      pollNonceProviders()
+      try {
+        fn.apply(this, [...Array.from(arguments), (err, data) => {
+          pendingCalls--
+          if (err) {
+            reject(err)
+          } else {
+            resolve(parse && data ? JSON.parse(data) : data)
+          }
+        }])
+      } catch (error) {
+        pendingCalls--
+        throw error
       }
-    }]))
+    })
+  }
+}
+
+// the JS nonce providers of the clients by id, their nonce requests are only polled while a native call is pending so
+// an idle process can exit
+const nonceProviders = new Map()
+let nextNonceProviderId = 0
+let pendingCalls = 0
+let pollingNonceRequests = false
+const NONCE_POLL_INTERVAL = 100
+
+function pollNonceProviders () {
+  if (pollingNonceRequests || nonceProviders.size === 0) {
+    return
   }
+  pollingNonceRequests = true
+  pollNonceRequests(NONCE_POLL_INTERVAL, (err, request) => {
+    pollingNonceRequests = false
+    if (err) {
+      process.emitWarning(err)
+    } else if (request !== undefined) {
+      answerNonce(JSON.parse(request))
+    }
+    if (pendingCalls > 0) {
+      pollNonceProviders()
+    }
+  })
+}
+
+// the nonce is answered as a decimal string since JS numbers can't hold every nonce, a failing provider answers null
+function answerNonce ({ id, provider, bytes, targetScore }) {
+  Promise.resolve()
+    .then(() => nonceProviders.get(provider)(Buffer.from(bytes, 'hex'), targetScore))
+    .then(
+      nonce => answerNonceRequest(id, BigInt(nonce).toString()),
+      error => answerNonceRequest(id, null, String((error && error.message) || error))
+    )
+    .catch(error => answerNonceRequest(id, null, String(error.message)))
+}
+
+// schedules the native call with a cancellation token aborted with the signal, the aborted call rejects with the
+// `ECANCELLED` code, right away for a signal which is already aborted
+function withSignal (signal, call) {
+  if (signal === undefined || signal === null) {
+    return call()
+  }
+  const id = beginCancellation()
+  const abort = () => cancel(id)
+  let promise
+  try {
+    if (signal.aborted) {
+      abort()
+    }
+    promise = call()
+  } catch (error) {
+    releaseCancellation(id)
+    throw error
+  } finally {
+    endCancellation()
+  }
+  signal.addEventListener('abort', abort, { once: true })
+  return promise.finally(() => {
+    signal.removeEventListener('abort', abort)
+    releaseCancellation(id)
+  })
+}
+
+// the native functions take the bytes as a Buffer or a hex string
+function bytesArgument (bytes) {
+  return bytes instanceof Uint8Array && !Buffer.isBuffer(bytes) ? Buffer.from(bytes) : bytes
+}
+
+// a seed or a mnemonic, or the `{ signer }` options of a Stronghold or a Ledger Nano signer which are sent as JSON
+function seedArgument (seed) {
+  return typeof seed === 'object' && seed !== null ? JSON.stringify(seed) : seed
+}
+
+// signs a prepared transaction with an external signer, which gets the essence bytes and the signing addresses
+function signTransactionWithSigner (preparedData, signer) {
+  const prepared = JSON.stringify(preparedData)
+  return Promise.resolve()
+    .then(() => {
+      const { essence, signers } = JSON.parse(transactionSigningData(prepared))
+      return signer(Buffer.from(essence, 'hex'), signers)
+    })
+    .then(signatures => JSON.parse(signTransactionWithSignatures(prepared, JSON.stringify(signatures))))
+}
+
+const warnMessageIdOnly = require('util').deprecate(() => {},
+  'the messageIdOnly option is deprecated, use the messageId of the resolved { messageId, message } instead')
+
+// the submit paths resolve to { messageId, message }, the deprecated messageIdOnly option resolves to the id
+function isMessageIdOnly ({ messageIdOnly = false } = {}) {
+  if (messageIdOnly) {
+    warnMessageIdOnly()
+  }
+  return !!messageIdOnly
 }
 
 function poll(instance, cb) {
   instance.poll((err, data) => {
-    cb(err, err || !data ? null : JSON.parse(data))
+    if (err) {
+      cb(err, null)
+    } else if (data === undefined) {
+      // the subscriber was unsubscribed from all its topics
+      instance._polling = false
+      return
+    } else {
+      cb(null, JSON.parse(data))
+    }
     poll(instance, cb)
   })
 }
 
+// the connection events come with the topic events, with a type
+function emitConnectionEvent (instance, { type, ...event }, cb) {
+  const listeners = (instance._connectionListeners && instance._connectionListeners[type]) || []
+  if (type === 'error') {
+    const error = new Error(event.error)
+    // the terminal error goes to the topic callback if it isn't listened
+    if (listeners.length === 0) {
+      cb(error, null)
+    }
+    listeners.forEach(listener => listener(error))
+  } else {
+    listeners.forEach(listener => listener(event))
+  }
+}
+
+TopicSubscriber.prototype.signal = function (signal) {
+  this._signal = signal
+  return this
+}
 const subscribe = TopicSubscriber.prototype.subscribe
 TopicSubscriber.prototype.subscribe = function (cb) {
+  // the topics of an aborted signal aren't subscribed, and are unsubscribed once the signal aborts
+  const signal = this._signal
+  if (signal) {
+    if (signal.aborted) {
+      return this
+    }
+    if (!this._abortListener) {
+      this._abortListener = () => this.unsubscribe()
+      signal.addEventListener('abort', this._abortListener, { once: true })
+    }
+  }
   subscribe.call(this, function (err, _) {
     if (err) {
       cb(err)
     }
   })
-  poll(this, cb)
+  if (!this._polling) {
+    this._polling = true
+    poll(this, (err, data) => {
+      if (!err && data.type !== undefined) {
+        emitConnectionEvent(this, data, cb)
+      } else {
+        cb(err, data)
+      }
+    })
+  }
+  return this
+}
+const CONNECTION_EVENTS = ['connected', 'disconnected', 'reconnecting', 'error']
+TopicSubscriber.prototype.on = function (event, cb) {
+  if (!CONNECTION_EVENTS.includes(event)) {
+    throw new Error(`unknown event: ${event}, expected one of ${CONNECTION_EVENTS.join(', ')}`)
+  }
+  this._connectionListeners = this._connectionListeners || {}
+  this._connectionListeners[event] = (this._connectionListeners[event] || []).concat([cb])
+  return this
 }
 const unsubscribe = TopicSubscriber.prototype.unsubscribe
 TopicSubscriber.prototype.unsubscribe = function (cb) {
   return unsubscribe.call(this, cb || function () {})
 }
 
+const listen = ConfirmationListener.prototype.listen
+ConfirmationListener.prototype.listen = function (cb) {
+  listen.apply(this, [])
+  if (!this._polling) {
+    this._polling = true
+    poll(this, cb)
+  }
+  return this
+}
+
+const listenRequests = RequestListener.prototype.listen
+RequestListener.prototype.listen = function (cb) {
+  listenRequests.apply(this, [])
+  if (!this._polling) {
+    this._polling = true
+    // a throwing callback doesn't stop the events, its error is emitted as a warning
+    poll(this, (err, event) => {
+      try {
+        cb(err, event)
+      } catch (error) {
+        process.emitWarning(error)
+      }
+    })
+  }
+  return this
+}
+
+const CONFIRMATION_EVENTS = ['confirmed']
+const REQUEST_EVENTS = ['request', 'response']
+Client.prototype.on = function (event, cb, { messageIds, interval } = {}) {
+  if (REQUEST_EVENTS.includes(event)) {
+    return onRequestEvent(this, event, cb)
+  }
+  if (!CONFIRMATION_EVENTS.includes(event)) {
+    const events = CONFIRMATION_EVENTS.concat(REQUEST_EVENTS)
+    throw new Error(`unknown event: ${event}, expected one of ${events.join(', ')}`)
+  }
+  const listener = this.confirmationListener()
+  if (messageIds) {
+    listener.messageIds(messageIds)
+  } else {
+    listener.submitted()
+  }
+  if (interval !== undefined) {
+    listener.interval(interval)
+  }
+  this._confirmationListeners = this._confirmationListeners || new Map()
+  this.off(event, cb)
+  this._confirmationListeners.set(cb, listener.listen(cb))
+  return listener
+}
+Client.prototype.off = function (event, cb) {
+  const listeners = REQUEST_EVENTS.includes(event)
+    ? this._requestListeners && this._requestListeners[event]
+    : this._confirmationListeners
+  const listener = listeners && listeners.get(cb)
+  if (listener) {
+    listener.stop()
+    listeners.delete(cb)
+  }
+  return this
+}
+// the request listener of `cb` only gets the events of the given type
+function onRequestEvent (client, event, cb) {
+  const listener = client.requestListener()
+  client._requestListeners = client._requestListeners || { request: new Map(), response: new Map() }
+  client.off(event, cb)
+  client._requestListeners[event].set(cb, listener.listen((err, data) => {
+    if (err) {
+      cb(err, null)
+    } else if (data.type === event) {
+      cb(null, data)
+    }
+  }))
+  return listener
+}
+
+// the events of a watcher are parsed into DTOs by the native subscriber, which skips the events delivered again
+function watch (client, topic, kind, cb) {
+  return client.subscriber().topic(topic).watch(kind).subscribe((err, event) => {
+    if (err) {
+      cb(err, null)
+    } else if (event.error !== undefined) {
+      cb(new Error(`invalid ${kind} event on ${event.topic}: ${event.error}`), null)
+    } else {
+      cb(null, event.payload)
+    }
+  })
+}
+Client.prototype.watchAddress = function (address, cb) {
+  const { bytes } = JSON.parse(parseAddress(address, this.getBech32Hrp()))
+  return watch(this, `addresses/ed25519/${Buffer.from(bytes).toString('hex')}/outputs`, 'output', cb)
+}
+const INDEX_LENGTH_MAX = 64
+Client.prototype.watchIndex = function (index, cb) {
+  const bytes = Buffer.from(index)
+  if (bytes.length === 0 || bytes.length > INDEX_LENGTH_MAX) {
+    throw new Error(`invalid index: ${bytes.length} bytes, expected 1 to ${INDEX_LENGTH_MAX} bytes`)
+  }
+  // the broker publishes the indexation topics with the hex-encoded index
+  return watch(this, `messages/indexation/${bytes.toString('hex')}`, 'message', cb)
+}
+
+// the errors of the catch-ups come with the activities, with their code and details
+Client.prototype.monitorAddress = function (address, sinceMilestoneIndex, cb) {
+  if (typeof sinceMilestoneIndex === 'function') {
+    cb = sinceMilestoneIndex
+    sinceMilestoneIndex = undefined
+  }
+  const monitor = this.addressMonitor(address, sinceMilestoneIndex)
+  monitor.start(err => {
+    if (err) {
+      cb(err, null)
+    }
+  })
+  poll(monitor, (err, data) => {
+    if (err) {
+      cb(err, null)
+    } else if (data.error !== undefined) {
+      const { message, code, details } = data.error
+      cb(Object.assign(new Error(message), code === undefined ? {} : { code, details }), null)
+    } else {
+      cb(null, data)
+    }
+  })
+  return monitor
+}
+
+const nodeFn = ClientBuilder.prototype.node
+ClientBuilder.prototype.node = function (url, options) {
+  return options === undefined ? nodeFn.apply(this, [url]) : nodeFn.apply(this, [url, JSON.stringify(options)])
+}
+
 const brokerOptionsFn = ClientBuilder.prototype.brokerOptions
 ClientBuilder.prototype.brokerOptions = function (options) {
   const opt = { ...options }
   if (options.timeout !== undefined) {
     opt.timeout = { secs: options.timeout, nanos: 0 }
   }
+  if (options.reconnectWindow !== undefined) {
+    opt.reconnectWindow = { secs: Math.floor(options.reconnectWindow / 1000), nanos: (options.reconnectWindow % 1000) * 1000000 }
+  }
   return brokerOptionsFn.apply(this, [JSON.stringify(opt)])
 }
 
+const nonceProviderFn = ClientBuilder.prototype.nonceProvider
+ClientBuilder.prototype.nonceProvider = function (provider, { timeout = 60000 } = {}) {
+  if (typeof provider !== 'function') {
+    throw new Error('the nonce provider must be a function')
+  }
+  const id = nextNonceProviderId++
+  nonceProviders.set(id, provider)
+  return nonceProviderFn.apply(this, [id, timeout])
+}
+
+const nodeAuthFn = ClientBuilder.prototype.nodeAuth
+ClientBuilder.prototype.nodeAuth = function (auth) {
+  return nodeAuthFn.apply(this, [JSON.stringify(auth)])
+}
+const storageFn = ClientBuilder.prototype.storage
+ClientBuilder.prototype.storage = function (options) {
+  return storageFn.apply(this, [JSON.stringify(options)])
+}
+const coordinatorKeysFn = ClientBuilder.prototype.coordinatorKeys
+ClientBuilder.prototype.coordinatorKeys = function (keys) {
+  return coordinatorKeysFn.apply(this, [JSON.stringify(keys)])
+}
+const requestHeadersFn = ClientBuilder.prototype.requestHeaders
+ClientBuilder.prototype.requestHeaders = function (headers) {
+  return requestHeadersFn.apply(this, [JSON.stringify(headers)])
+}
+ClientBuilder.prototype.buildAsync = promisify(ClientBuilder.prototype.buildAsync, false)
+
 const getNetworkInfo = Client.prototype.networkInfo
 Client.prototype.networkInfo = function () {
   return JSON.parse(getNetworkInfo.apply(this, []))
 }
+const getMetrics = Client.prototype.getMetrics
+Client.prototype.getMetrics = function () {
+  return JSON.parse(getMetrics.apply(this, []))
+}
+Client.prototype.getNetworkId = function () {
+  return this.networkInfo().networkId
+}
+Client.prototype.getBech32Hrp = function () {
+  return this.networkInfo().bech32HRP
+}
+Client.prototype.hexToBech32 = function (address, bech32Hrp) {
+  return hexToBech32(address, bech32Hrp || this.getBech32Hrp())
+}
+Client.prototype.bech32ToHex = function (address) {
+  return JSON.parse(bech32ToHex(address))
+}
+Client.prototype.refreshNetworkInfo = promisify(Client.prototype.refreshNetworkInfo)
+Client.prototype.refreshNodeInfo = promisify(Client.prototype.refreshNodeInfo)
+const getSyncedNodes = Client.prototype.syncedNodes
+Client.prototype.syncedNodes = function () {
+  return JSON.parse(getSyncedNodes.apply(this, []))
+}
+const getNodeSyncStatus = Client.prototype.nodeSyncStatus
+Client.prototype.nodeSyncStatus = function () {
+  return JSON.parse(getNodeSyncStatus.apply(this, []))
+}
+
+for (const method of ['getUnspentAddress', 'findAddresses', 'getAddresses', 'getBalance']) {
+  const fn = Client.prototype[method]
+  Client.prototype[method] = function (seed) {
+    return fn.apply(this, [seedArgument(seed)])
+  }
+}
 
-Client.prototype.findMessages = promisify(Client.prototype.findMessages)
+const findMessages = promisify(Client.prototype.findMessages)
+Client.prototype.findMessages = function (indexes, messageIds = [], options = {}) {
+  const { limit } = options
+  if (limit !== undefined && !(Number.isInteger(limit) && limit > 0)) {
+    return Promise.reject(new Error(`invalid limit: ${limit}, expected a positive integer`))
+  }
+  // indexes are sent hex-encoded so binary indexes survive the native boundary
+  const hexIndexes = indexes.map(index => Buffer.from(index).toString('hex'))
+  return findMessages.apply(this, [hexIndexes, messageIds, limit || 0])
+}
+const findMessagePage = promisify(Client.prototype.findMessagePage)
+Client.prototype.findMessagePages = async function * (index, options = {}) {
+  const { pageSize = 100, referencedAfter, dataPrefix, signal } = options
+  if (!(Number.isInteger(pageSize) && pageSize > 0)) {
+    throw new Error(`invalid page size: ${pageSize}, expected a positive integer`)
+  }
+  if (referencedAfter !== undefined && !(Number.isInteger(referencedAfter) && referencedAfter >= 0)) {
+    throw new Error(`invalid milestone index: ${referencedAfter}, expected a non-negative integer`)
+  }
+  const hexIndex = Buffer.from(index).toString('hex')
+  const hexPrefix = dataPrefix === undefined ? null : Buffer.from(dataPrefix).toString('hex')
+  let cursor = options.cursor === undefined ? null : options.cursor
+  // each page is only fetched once the previous one is consumed, so breaking out of the loop stops the lookup
+  while (true) {
+    const page = await withSignal(signal, () => findMessagePage.apply(this, [
+      hexIndex,
+      pageSize,
+      cursor,
+      referencedAfter === undefined ? null : referencedAfter,
+      hexPrefix
+    ]))
+    yield page
+    if (!page.hasMore) {
+      return
+    }
+    cursor = page.nextCursor
+  }
+}
+Client.prototype.getMessages = promisify(Client.prototype.getMessages)
 Client.prototype.getAddressBalances = promisify(Client.prototype.getAddressBalances)
+const getTransactionHistory = promisify(Client.prototype.getTransactionHistory)
+const getAddressTransactionHistory = promisify(Client.prototype.getAddressTransactionHistory)
+// the history of the used addresses of a seed, or of a list of addresses
+Client.prototype.getTransactionHistory = function (seedOrAddresses, options = {}) {
+  if (Array.isArray(seedOrAddresses)) {
+    return getAddressTransactionHistory.apply(this, [seedOrAddresses, options.format || 'entries'])
+  }
+  return getTransactionHistory.apply(this, [seedArgument(seedOrAddresses), JSON.stringify(options)])
+}
+const prepareTransaction = promisify(Client.prototype.prepareTransaction)
+Client.prototype.prepareTransaction = function (outputs, options = {}) {
+  const opt = { ...options }
+  if (opt.signer !== undefined) {
+    opt.seed = seedArgument({ signer: opt.signer })
+    delete opt.signer
+  }
+  if (opt.data !== undefined) {
+    opt.data = Array.from(typeof opt.data === 'string' ? Buffer.from(opt.data) : opt.data)
+  }
+  return prepareTransaction.apply(this, [JSON.stringify(outputs), JSON.stringify(opt)])
+}
+const getInputCandidates = promisify(Client.prototype.getInputCandidates)
+Client.prototype.getInputCandidates = function (options = {}) {
+  const opt = { ...options }
+  if (opt.signer !== undefined) {
+    opt.seed = seedArgument({ signer: opt.signer })
+    delete opt.signer
+  }
+  return getInputCandidates.apply(this, [JSON.stringify(opt)])
+}
+const consolidateOutputs = promisify(Client.prototype.consolidateOutputs)
+Client.prototype.consolidateOutputs = function (seed, { signal, ...options } = {}) {
+  return withSignal(signal, () => consolidateOutputs.apply(this, [seedArgument(seed), JSON.stringify(options)]))
+}
+const sendBatch = promisify(Client.prototype.sendBatch)
+Client.prototype.sendBatch = function (seed, transfers, { signal, ...options } = {}) {
+  return withSignal(signal, () =>
+    sendBatch.apply(this, [seedArgument(seed), JSON.stringify(transfers), JSON.stringify(options)]))
+}
+const sendData = promisify(Client.prototype.sendData)
+Client.prototype.sendData = function (index, data, { signal, ...options } = {}) {
+  // the index and the data are sent hex-encoded like the indexes of findMessages
+  const hexIndex = Buffer.from(index).toString('hex')
+  return withSignal(signal, () =>
+    sendData.apply(this, [hexIndex, Buffer.from(data).toString('hex'), JSON.stringify(options)]))
+}
+const reassembleData = promisify(Client.prototype.reassembleData, false)
+Client.prototype.reassembleData = function (index, contentHash) {
+  const hexIndex = Buffer.from(index).toString('hex')
+  return reassembleData.apply(this, [hexIndex, contentHash]).then(data => Buffer.from(data, 'hex'))
+}
+const submitSignedTransaction = promisify(Client.prototype.submitSignedTransaction)
+Client.prototype.submitSignedTransaction = function (payload) {
+  return submitSignedTransaction.apply(this, [JSON.stringify(payload)])
+}
 Client.prototype.getInfo = promisify(Client.prototype.getInfo)
 Client.prototype.getTips = promisify(Client.prototype.getTips)
-const postMessage = Client.prototype.postMessage
-Client.prototype.postMessage = function (message) {
+Client.prototype.getPeers = promisify(Client.prototype.getPeers)
+const callNodeApi = promisify(Client.prototype.callNodeApi)
+Client.prototype.callNodeApi = function (method, path, query = {}, body = null) {
+  const entries = Object.entries(query).map(([name, value]) => [name, String(value)])
+  return callNodeApi.apply(this, [method, path, JSON.stringify(entries), JSON.stringify(body)])
+}
+function serializeMessage (message) {
   if (message && message.payload && message.payload.data instanceof Uint8Array) {
     message.payload.data = Array.from(message.payload.data)
   }
-  return promisify(postMessage).apply(this, [JSON.stringify(message)])
+  return JSON.stringify(message)
+}
+const postMessage = Client.prototype.postMessage
+Client.prototype.postMessage = function (message, options) {
+  return promisify(postMessage).apply(this, [serializeMessage(message), isMessageIdOnly(options)])
 }
 Client.prototype.getOutput = promisify(Client.prototype.getOutput)
-Client.prototype.findOutputs = promisify(Client.prototype.findOutputs)
-Client.prototype.getAddressOutputs = promisify(Client.prototype.getAddressOutputs)
+Client.prototype.getIncludedMessage = promisify(Client.prototype.getIncludedMessage)
+const findOutputs = promisify(Client.prototype.findOutputs)
+Client.prototype.findOutputs = function ({ outputIds = [], addresses = [] } = {}, { atomic = false } = {}) {
+  return findOutputs.apply(this, [outputIds, addresses, atomic])
+}
 Client.prototype.getAddressBalance = promisify(Client.prototype.getAddressBalance)
 Client.prototype.getMilestone = promisify(Client.prototype.getMilestone)
+Client.prototype.getValidatedMilestone = promisify(Client.prototype.getValidatedMilestone)
+Client.prototype.getMilestoneUtxoChanges = promisify(Client.prototype.getMilestoneUtxoChanges)
+Client.prototype.createInclusionProof = promisify(Client.prototype.createInclusionProof)
+Client.prototype.getPendingMessages = promisify(Client.prototype.getPendingMessages)
 Client.prototype.retry = promisify(Client.prototype.retry)
+const retryUntilIncluded = promisify(Client.prototype.retryUntilIncluded)
+Client.prototype.retryUntilIncluded = function (messageId, { interval, maxAttempts, signal } = {}) {
+  for (const [name, value] of [['interval', interval], ['maxAttempts', maxAttempts]]) {
+    if (value !== undefined && !(Number.isInteger(value) && value > 0)) {
+      return Promise.reject(new Error(`invalid ${name}: ${value}, expected a positive integer`))
+    }
+  }
+  return withSignal(signal, () => retryUntilIncluded.apply(this, [messageId, interval || 0, maxAttempts || 0]))
+}
+const waitForBalance = promisify(Client.prototype.waitForBalance)
+Client.prototype.waitForBalance = function (address, minAmount, { interval, timeout, signal } = {}) {
+  if (!(Number.isInteger(minAmount) && minAmount >= 0)) {
+    return Promise.reject(new Error(`invalid minAmount: ${minAmount}, expected a non-negative integer`))
+  }
+  for (const [name, value] of [['interval', interval], ['timeout', timeout]]) {
+    if (value !== undefined && !(Number.isInteger(value) && value > 0)) {
+      return Promise.reject(new Error(`invalid ${name}: ${value}, expected a positive integer`))
+    }
+  }
+  return withSignal(signal, () => waitForBalance.apply(this, [address, minAmount, interval || 0, timeout || 0]))
+}
 Client.prototype.reattach = promisify(Client.prototype.reattach)
 Client.prototype.promote = promisify(Client.prototype.promote)
+const rebroadcast = promisify(Client.prototype.rebroadcast, false)
+Client.prototype.rebroadcast = function (messageId) {
+  return rebroadcast.apply(this, [messageId]).then(bytes => Buffer.from(bytes, 'hex'))
+}
 
 MessageGetter.prototype.index = promisify(MessageGetter.prototype.index)
-MessageGetter.prototype.data = promisify(MessageGetter.prototype.data)
-MessageGetter.prototype.raw = promisify(MessageGetter.prototype.raw, false)
+const messageGetterData = promisify(MessageGetter.prototype.data)
+MessageGetter.prototype.data = function (messageId, options = {}) {
+  return messageGetterData.apply(this, [messageId, options.format || 'dto'])
+}
+const messageGetterRaw = promisify(MessageGetter.prototype.raw, false)
+MessageGetter.prototype.raw = function (messageId) {
+  return messageGetterRaw.apply(this, [messageId]).then(bytes => Buffer.from(bytes, 'hex'))
+}
 MessageGetter.prototype.children = promisify(MessageGetter.prototype.children)
 MessageGetter.prototype.metadata = promisify(MessageGetter.prototype.metadata)
 
-MessageSender.prototype.submit = promisify(MessageSender.prototype.submit)
+// the native calls of the sender are cancelled with its signal
+MessageSender.prototype.signal = function (signal) {
+  this._signal = signal
+  return this
+}
+const messageSenderSubmit = promisify(MessageSender.prototype.submit)
+const messageSenderSubmitSigned = promisify(MessageSender.prototype.submitSigned)
+const messageSenderPrepare = promisify(MessageSender.prototype.prepare)
+MessageSender.prototype.prepare = function () {
+  return withSignal(this._signal, () => messageSenderPrepare.apply(this, []))
+}
+MessageSender.prototype.submit = function (options) {
+  const messageIdOnly = isMessageIdOnly(options)
+  if (!this._signer) {
+    return withSignal(this._signal, () => messageSenderSubmit.apply(this, [messageIdOnly]))
+  }
+  return this.prepare()
+    .then(prepared => signTransactionWithSigner(prepared, this._signer))
+    .then(payload => withSignal(this._signal, () =>
+      messageSenderSubmitSigned.apply(this, [JSON.stringify(payload), messageIdOnly])))
+}
+const messageSenderDryRun = promisify(MessageSender.prototype.dryRun)
+MessageSender.prototype.dryRun = function ({ sign = false } = {}) {
+  if (!sign || !this._signer) {
+    return withSignal(this._signal, () => messageSenderDryRun.apply(this, [sign]))
+  }
+  // the external signer signs the essence of the unsigned plan
+  return withSignal(this._signal, () => messageSenderDryRun.apply(this, [false]))
+    .then(plan => signTransactionWithSigner(plan.prepared, this._signer).then(payload => ({ ...plan, payload })))
+}
+const messageSenderSubmitPlan = promisify(MessageSender.prototype.submitPlan)
+MessageSender.prototype.submitPlan = function (plan) {
+  if (plan.payload || !this._signer) {
+    return withSignal(this._signal, () => messageSenderSubmitPlan.apply(this, [JSON.stringify(plan)]))
+  }
+  return signTransactionWithSigner(plan.prepared, this._signer)
+    .then(payload => withSignal(this._signal, () =>
+      messageSenderSubmitPlan.apply(this, [JSON.stringify({ ...plan, payload })])))
+}
+const messageSenderSeed = MessageSender.prototype.seed
+MessageSender.prototype.seed = function (seed) {
+  return messageSenderSeed.apply(this, [seedArgument(seed)])
+}
+MessageSender.prototype.signer = function (signer) {
+  // the options of a Stronghold or a Ledger Nano signer
+  if (typeof signer === 'object' && signer !== null) {
+    return this.seed({ signer })
+  }
+  if (typeof signer !== 'function') {
+    throw new TypeError('the signer must be a function')
+  }
+  this._signer = signer
+  return this
+}
+const messageSenderAccountAddresses = MessageSender.prototype.accountAddresses
+MessageSender.prototype.accountAddresses = function (addresses) {
+  return messageSenderAccountAddresses.apply(this, [JSON.stringify(addresses)])
+}
+const messageSenderIndexSetter = MessageSender.prototype.index
+MessageSender.prototype.index = function (index) {
+  // the index is sent hex-encoded like the indexes of findMessages
+  return messageSenderIndexSetter.apply(this, [Buffer.from(index).toString('hex')])
+}
 const messageSenderDataSetter = MessageSender.prototype.data
 MessageSender.prototype.data = function (data) {
+  if (typeof data === 'string') {
+    data = Buffer.from(data)
+  }
   if (data instanceof Uint8Array) {
     return messageSenderDataSetter.apply(this, [Array.from(data)])
   }
   return messageSenderDataSetter.apply(this, [data])
 }
+MessageSender.prototype.outputs = function (outputs) {
+  for (const { address, amount, type } of outputs) {
+    if (type === 'DustAllowance') {
+      this.dustAllowanceOutput(address, amount)
+    } else if (type === undefined || type === 'SignatureLockedSingle') {
+      this.output(address, amount)
+    } else {
+      throw new TypeError(`invalid output type: ${type}, expected SignatureLockedSingle or DustAllowance`)
+    }
+  }
+  return this
+}
+const messageSenderParents = MessageSender.prototype.parents
+MessageSender.prototype.parents = function (messageIds, { skipVerification = false } = {}) {
+  return messageSenderParents.apply(this, [messageIds, skipVerification])
+}
+MessageSender.prototype.indexation = function ({ index, data }) {
+  this.index(index)
+  if (data !== undefined) {
+    this.data(data)
+  }
+  return this
+}
 
 UnspentAddressGetter.prototype.get = promisify(UnspentAddressGetter.prototype.get)
 
@@ -101,8 +719,55 @@ AddressFinder.prototype.get = function () {
   return JSON.parse(findAddressesGetter.apply(this))
 }
 
-BalanceGetter.prototype.get = promisify(BalanceGetter.prototype.get)
+const addressGetter = AddressGetter.prototype.get
+AddressGetter.prototype.get = function () {
+  return JSON.parse(addressGetter.apply(this))
+}
+
+const balanceGetterGet = promisify(BalanceGetter.prototype.get)
+BalanceGetter.prototype.signal = function (signal) {
+  this._signal = signal
+  return this
+}
+BalanceGetter.prototype.get = function () {
+  return withSignal(this._signal, () => balanceGetterGet.apply(this, []))
+}
+AddressOutputsGetter.prototype.get = promisify(AddressOutputsGetter.prototype.get)
 
 module.exports = {
-  ClientBuilder
+  ClientBuilder,
+  isAddressValid,
+  parseAddress: (address, expectedHrp) => JSON.parse(parseAddress(address, expectedHrp)),
+  hexToBech32: (address, bech32Hrp) => {
+    if (typeof bech32Hrp !== 'string') {
+      throw new TypeError('the bech32 HRP is required without a client, use `client.hexToBech32` for the network HRP')
+    }
+    return hexToBech32(address, bech32Hrp)
+  },
+  bech32ToHex: address => JSON.parse(bech32ToHex(address)),
+  bech32Encode,
+  bech32Decode: value => JSON.parse(bech32Decode(value)),
+  generateMnemonic: (wordCount = 24) => generateMnemonic(wordCount),
+  mnemonicToSeed: (mnemonic, passphrase = '') => mnemonicToSeed(mnemonic, passphrase),
+  createStrongholdSnapshot,
+  signTransaction: (preparedData, seedOrSigner) => typeof seedOrSigner === 'function'
+    ? signTransactionWithSigner(preparedData, seedOrSigner)
+    : JSON.parse(signTransaction(JSON.stringify(preparedData), seedArgument(seedOrSigner))),
+  parseMessageJson: (message, options = {}) => JSON.parse(parseMessageJson(
+    typeof message === 'string' ? message : JSON.stringify(message),
+    options.format || 'dto',
+    options.bech32Hrp || 'iot'
+  )),
+  messageFromBytes: (bytes, options = {}) => JSON.parse(messageFromBytes(bytesArgument(bytes), options.bech32Hrp || 'iot')),
+  messageToBytes: (message, options = {}) => messageToBytes(serializeMessage(message), options.bech32Hrp || 'iot'),
+  verifyEd25519Signature: (publicKey, signature, messageBytes) =>
+    verifyEd25519Signature(publicKey, signature, bytesArgument(messageBytes)),
+  computeMessageId: messageBytes => computeMessageId(bytesArgument(messageBytes)),
+  computeTransactionId: payload => computeTransactionId(typeof payload === 'string' ? payload : JSON.stringify(payload)),
+  verifyInclusionProof: (proof, coordinatorKeys) => coordinatorKeys === undefined
+    ? verifyInclusionProof(typeof proof === 'string' ? proof : JSON.stringify(proof))
+    : verifyInclusionProof(typeof proof === 'string' ? proof : JSON.stringify(proof), JSON.stringify(coordinatorKeys)),
+  validateMilestone: (messageBytes, coordinatorKeys) => coordinatorKeys === undefined
+    ? validateMilestone(bytesArgument(messageBytes))
+    : validateMilestone(bytesArgument(messageBytes), JSON.stringify(coordinatorKeys))
 }
@@ -1,3 +1,4 @@
 export * from './models'
 export * from './message'
 export * from './message-dto'
+export * from './message-json'
@@ -1,48 +1,119 @@
-export declare interface InputDto {
-  type: 'UTXO'
-  data: string
+import { AddressJson } from './message-json'
+
+export declare interface TreasuryInputDto {
+  type: 1
+  milestoneId: string
 }
 
-export declare interface OutputDto {
-  address: string
+export declare interface UtxoInputDto {
+  type?: 0
+  // hex-encoded, optionally `0x` prefixed
+  transactionId: string
+  // at most 126
+  transactionOutputIndex: number
+}
+
+// the output id string form of an UTXO input is deprecated
+export declare type InputDto = UtxoInputDto | string | TreasuryInputDto
+
+export declare interface SignatureLockedSingleOutputDto {
+  type?: 0
+  // bech32 or hex-encoded Ed25519 address, or the REST API address object
+  address: string | AddressJson
+  amount: number
+}
+
+export declare interface SignatureLockedDustAllowanceOutputDto {
+  type: 1
+  // bech32 or hex-encoded Ed25519 address, or the REST API address object
+  address: string | AddressJson
+  // at least 1000000
+  amount: number
+}
+
+export declare interface TreasuryOutputDto {
+  type: 2
   amount: number
 }
 
+export declare type OutputDto = SignatureLockedSingleOutputDto | SignatureLockedDustAllowanceOutputDto | TreasuryOutputDto
+
 export declare interface TransactionPayloadEssenceDto {
   inputs: InputDto[]
   outputs: OutputDto[]
   payload?: IndexationPayloadDto
 }
 
-export declare type WotsSignatureUnlockBlockDto = number[]
-
 export declare interface Ed25519SignatureUnlockBlockDto {
-  publicKey: number[]
-  signature: number[]
+  // hex-encoded, optionally `0x` prefixed
+  publicKey: string
+  signature: string
 }
 
 export declare interface SignatureUnlockBlockDto {
-  data: WotsSignatureUnlockBlockDto | Ed25519SignatureUnlockBlockDto
+  signature: Ed25519SignatureUnlockBlockDto
 }
 
-export declare type ReferenceUnlockBlockDto = number
+export declare interface ReferenceUnlockBlockDto {
+  // index of a previous unlock block
+  reference: number
+}
 
 export declare type UnlockBlockDto = SignatureUnlockBlockDto | ReferenceUnlockBlockDto
 
 export declare interface TransactionPayloadDto {
+  // omitting the payload type is deprecated
+  type?: 0
   essence: TransactionPayloadEssenceDto
   unlockBlocks: UnlockBlockDto[]
 }
 
 export declare interface IndexationPayloadDto {
-  index: string
-  data?: Uint8Array
+  // omitting the payload type is deprecated
+  type?: 2
+  // the index as UTF-8 string, mutually exclusive with `indexHex`
+  index?: string
+  // the hex encoded index, mutually exclusive with `index`
+  indexHex?: string
+  // returned as number[] by the node, hex-encoded in the REST API
+  data?: Uint8Array | number[] | string
 }
 
 export declare type PayloadDto = TransactionPayloadDto | IndexationPayloadDto
 
+export declare interface InputSigningDataDto {
+  outputId: string
+  // bech32 address of the consumed output
+  address: string
+  addressIndex: number
+  internal: boolean
+}
+
+// the remainder output of a transaction, `outputIndex` is its index in the essence outputs
+export declare interface RemainderDto {
+  address: string
+  amount: number
+  outputIndex: number
+  // unset for a custom remainder address
+  addressIndex?: number
+  internal: boolean
+}
+
+// a transaction selected by `Client.prepareTransaction`, signed offline by `signTransaction`
+export declare interface PreparedTransactionDataDto {
+  essence: TransactionPayloadEssenceDto
+  accountIndex: number
+  inputs: InputSigningDataDto[]
+  // unset if the inputs don't hold more than the outputs need
+  remainder?: RemainderDto
+}
+
 export declare interface MessageDto {
+  // `parent1MessageId` and `parent2MessageId` are accepted as aliases
   parent1?: string
   parent2?: string
   payload?: PayloadDto
+  // required by `messageToBytes`, checked against the client network by `postMessage`, as a number or a decimal string
+  networkId?: number | string
+  nonce?: number | string
 }
@@ -0,0 +1,71 @@
+// messages in the node REST API shape
+
+export declare interface AddressJson {
+  type: 1
+  // hex-encoded Ed25519 address
+  address: string
+}
+
+export declare interface UtxoInputJson {
+  type: 0
+  transactionId: string
+  transactionOutputIndex: number
+}
+
+export declare interface SignatureLockedSingleOutputJson {
+  type: 0
+  address: AddressJson
+  amount: number
+}
+
+export declare interface TransactionPayloadEssenceJson {
+  type: 0
+  inputs: UtxoInputJson[]
+  outputs: SignatureLockedSingleOutputJson[]
+  payload?: IndexationPayloadJson
+}
+
+export declare interface SignatureUnlockBlockJson {
+  type: 0
+  signature: {
+    type: 1
+    publicKey: string
+    signature: string
+  }
+}
+
+export declare interface ReferenceUnlockBlockJson {
+  type: 1
+  reference: number
+}
+
+export declare interface TransactionPayloadJson {
+  type: 0
+  essence: TransactionPayloadEssenceJson
+  unlockBlocks: Array<SignatureUnlockBlockJson | ReferenceUnlockBlockJson>
+}
+
+export declare interface MilestonePayloadJson {
+  type: 1
+  index: number
+  timestamp: number
+  inclusionMerkleProof: string
+  signatures: string[]
+}
+
+export declare interface IndexationPayloadJson {
+  type: 2
+  index: string
+  // hex-encoded
+  data: string
+}
+
+export declare type PayloadJson = TransactionPayloadJson | MilestonePayloadJson | IndexationPayloadJson
+
+export declare interface MessageJson {
+  networkId: string
+  parent1MessageId: string
+  parent2MessageId: string
+  payload?: PayloadJson
+  nonce: string
+}
@@ -1,79 +1,21 @@
-export declare interface Input {
-  type: 'UTXO'
-  data: string
-}
-
-export declare interface Output {
-  type: 'SignatureLockedSingle'
-  data: {
-    address: string
-    amount: number
-  }
-}
-
-export declare interface TransactionPayloadEssence {
-  inputs: Input[]
-  outputs: Output[]
-  payload?: Payload
-}
-
-export declare interface WotsSignatureUnlockBlock {
-  type: 'Wots'
-  data: number[]
-}
-
-export declare interface Ed25519SignatureUnlockBlock {
-  type: 'Ed25519'
-  data: {
-    public_key: number[]
-    signature: number[]
-  }
-}
-
-export declare interface SignatureUnlockBlock {
-  type: 'Signature'
-  data: WotsSignatureUnlockBlock | Ed25519SignatureUnlockBlock
-}
-
-export declare interface ReferenceUnlockBlock {
-  type: 'Reference'
-  data: number
-}
-
-export declare type UnlockBlock = SignatureUnlockBlock | ReferenceUnlockBlock
-
-export declare interface TransactionPayload {
-  essence: TransactionPayloadEssence
-  unlock_blocks: UnlockBlock[]
-}
-
-export declare interface IndexationPayload {
-  index: string
-  data: number[]
-}
-
-export declare interface MilestoneEssence {
-  index: string
-  timestamp: string
-  parent1: string
-  parent2: string
-  merkle_proof: number[]
-  public_keys: number[]
-}
+import { PayloadDto } from './message-dto'
 
 export declare interface MilestonePayload {
-  essence: MilestoneEssence
-  signatures: number[][]
+  type: 1
+  index: number
+  timestamp: number
+  inclusionMerkleProof: string
+  signatures: string[]
 }
 
-export declare type Payload = { type: 'Indexation', data: IndexationPayload } |
-{ type: 'Milestone', data: MilestonePayload } |
-{ type: 'Transaction', data: TransactionPayload }
+// messages returned by the node use the same payload shape as `MessageDto`
+export declare type Payload = PayloadDto | MilestonePayload
 
 export declare interface Message {
-  network_id: number
+  // serialized as string since JS numbers can't hold every u64 value
+  networkId: string
   parent1: string
   parent2: string
   payload?: Payload
-  nonce: number
+  nonce: string
 }
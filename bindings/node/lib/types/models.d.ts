@@ -3,22 +3,57 @@ export declare interface NodeInfo {
   version: string
   isHealthy: boolean
   networkId: string
-  minPoWScore: number
+  minPowScore: number
   latestMilestoneIndex: number
   solidMilestoneIndex: number
+  // null if the node doesn't report it
+  latestMilestoneTimestamp: number | null
   pruningIndex: number
+  // null if the node doesn't report it
+  bech32HRP: string | null
   features: string[]
 }
 
+export declare type LedgerInclusionState = 'included' | 'conflicting' | 'noTransaction'
+
 export declare interface MessageMetadata {
   messageId: string
   parent1MessageId: string
   parent2MessageId: string
   isSolid: boolean
-  shouldPromote?: boolean
-  shouldReattach?: boolean
-  referencedByMilestoneIndex?: number
-  ledgerInclusionState?: string
+  // null if the node omits them
+  shouldPromote: boolean | null
+  shouldReattach: boolean | null
+  // null while the message isn't referenced by a milestone
+  referencedByMilestoneIndex: number | null
+  ledgerInclusionState: LedgerInclusionState | null
+}
+
+export declare interface MessageConfirmation {
+  messageId: string
+  referencedByMilestoneIndex: number
+  ledgerInclusionState: LedgerInclusionState | null
+}
+
+export declare interface ParsedAddress {
+  encoding: 'bech32' | 'hex'
+  hrp: string | null
+  bytes: number[]
+}
+
+export declare interface HexAddress {
+  hex: string
+  // the HRP found in the bech32 address
+  hrp: string
+}
+
+export declare interface Bech32Data {
+  // the lowercase HRP
+  hrp: string
+  // the first data byte, the address type of the addresses, null without data
+  version: number | null
+  // the hex encoded data bytes after the version
+  payload: string
 }
 
 export declare interface OutputMetadata {
@@ -26,25 +61,93 @@ export declare interface OutputMetadata {
   transactionId: string
   outputIndex: number
   isSpent: boolean
-  address: Address
+  address: string
+  addressHex: string
   amount: number
 }
 
+export declare interface AddressActivity {
+  outputId: string
+  output: OutputMetadata
+  consumed: boolean
+  // null for a live event
+  milestoneIndex: number | null
+}
+
 export declare interface MilestoneMetadata {
   index: number
   messageId: string
   timestamp: number
 }
 
+export declare interface MilestoneUtxoChanges {
+  index: number
+  createdOutputs: string[]
+  consumedOutputs: string[]
+}
+
+export declare type NodeAuth = { jwt: string } | { username: string, password: string }
+
+// the roles of a node, all allowed by default, the reads use every synced node
+export declare interface NodeOptions {
+  auth?: NodeAuth
+  allowSubmit?: boolean
+  remotePow?: boolean
+  mqtt?: boolean
+}
+
+// the directory of the client state, created if it doesn't exist
+export declare interface StorageOptions {
+  storagePath: string
+}
+
+export declare interface GossipMetrics {
+  newMessages: number
+  knownMessages: number
+  receivedMessages: number
+  receivedMessageRequests: number
+  receivedMilestoneRequests: number
+  receivedHeartbeats: number
+  sentMessages: number
+  sentMessageRequests: number
+  sentMilestoneRequests: number
+  sentHeartbeats: number
+  droppedPackets: number
+}
+
+export declare interface Peer {
+  id: string
+  multiAddresses: string[]
+  alias: string | null
+  relation: 'known' | 'unknown' | 'autopeered'
+  connected: boolean
+  // null if the peer isn't connected
+  gossip: { metrics: GossipMetrics } | null
+}
+
 export declare interface BrokerOptions {
   automaticDisconnect: boolean
   // timeout in milliseconds
   timeout: number
+  // reconnects a lost connection, true by default
+  automaticReconnect?: boolean
+  // how long a lost connection is reconnected in milliseconds, 5 minutes by default
+  reconnectWindow?: number
+  // `mqtt`, `mqtts`, `ws` or `wss` URL of the broker, instead of the brokers of the nodes
+  brokerUrl?: string
+  auth?: BrokerAuth
+}
+
+export declare interface BrokerAuth {
+  username: string
+  password: string
 }
 
 export declare type Address = 'string'
 
 export declare interface AddressBalance {
   address: Address
-  balance: number
+  addressHex: string
+  balance: string
+  dustAllowed: boolean
 }
@@ -0,0 +1,326 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Address parsing and encoding utilities shared by the bindings, without any JS engine API: the Node.js functions
+//! are in `address_js`.
+
+use bech32::{FromBase32, ToBase32};
+use iota::message::prelude::{Address, Ed25519Address};
+use serde::{Serialize, Serializer};
+
+use std::{convert::TryInto, ops::RangeInclusive};
+
+const ED25519_ADDRESS_TYPE: u8 = 1;
+const ED25519_ADDRESS_LENGTH: usize = 32;
+// the BIP-173 limits
+const BECH32_LENGTH_RANGE: RangeInclusive<usize> = 8..=90;
+const BECH32_HRP_LENGTH_MAX: usize = 83;
+const BECH32_CHECKSUM_LENGTH: usize = 6;
+const BECH32_CHARACTER_RANGE: RangeInclusive<char> = '!'..='~';
+
+/// Bech32 encoding and decoding errors, naming the failed check.
+#[derive(Debug, thiserror::Error)]
+pub enum Bech32Error {
+    /// The string is shorter than 8 or longer than 90 characters, or has no room for the checksum.
+    #[error("invalid bech32 length: {0}")]
+    InvalidLength(String),
+    /// The HRP is empty or longer than 83 characters.
+    #[error("invalid bech32 HRP: {0}")]
+    InvalidHrp(String),
+    /// A character out of the US-ASCII range 33 to 126, or a data character out of the bech32 charset.
+    #[error("invalid bech32 character `{0}`")]
+    InvalidCharacter(char),
+    /// The string mixes upper and lower case characters.
+    #[error("invalid bech32 string: mixed case characters")]
+    MixedCase,
+    /// The string has no `1` separator between the HRP and the data.
+    #[error("invalid bech32 string: missing the `1` separator")]
+    MissingSeparator,
+    /// The checksum doesn't match the HRP and the data.
+    #[error("invalid bech32 checksum")]
+    InvalidChecksum,
+    /// The 5-bit data doesn't convert to bytes.
+    #[error("invalid bech32 data: {0}")]
+    InvalidData(String),
+}
+
+impl From<bech32::Error> for Bech32Error {
+    fn from(error: bech32::Error) -> Self {
+        match error {
+            bech32::Error::InvalidChecksum => Self::InvalidChecksum,
+            bech32::Error::InvalidChar(c) => Self::InvalidCharacter(c),
+            bech32::Error::MixedCase => Self::MixedCase,
+            bech32::Error::MissingSeparator => Self::MissingSeparator,
+            bech32::Error::InvalidPadding => Self::InvalidData("non-zero or more than 4 bits of padding".to_string()),
+            bech32::Error::InvalidData(value) => Self::InvalidData(format!("invalid 5-bit value {}", value)),
+            error => Self::InvalidLength(error.to_string()),
+        }
+    }
+}
+
+fn check_bech32_hrp(hrp: &str) -> Result<(), Bech32Error> {
+    if hrp.is_empty() {
+        return Err(Bech32Error::InvalidHrp("empty HRP".to_string()));
+    }
+    if hrp.len() > BECH32_HRP_LENGTH_MAX {
+        return Err(Bech32Error::InvalidHrp(format!(
+            "expected at most {} characters, found {}",
+            BECH32_HRP_LENGTH_MAX,
+            hrp.len()
+        )));
+    }
+    Ok(())
+}
+
+fn check_bech32_characters(value: &str) -> Result<(), Bech32Error> {
+    if let Some(c) = value.chars().find(|c| !BECH32_CHARACTER_RANGE.contains(c)) {
+        return Err(Bech32Error::InvalidCharacter(c));
+    }
+    if value.chars().any(|c| c.is_ascii_lowercase()) && value.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(Bech32Error::MixedCase);
+    }
+    Ok(())
+}
+
+/// Encodes the data bytes as bech32 with any human readable part, the first byte being the version or address type.
+/// The HRP is lowercased.
+pub fn bech32_encode(hrp: &str, data: &[u8]) -> Result<String, Bech32Error> {
+    check_bech32_characters(hrp)?;
+    check_bech32_hrp(hrp)?;
+    // the HRP, the separator and the checksum around the 5-bit data
+    let length = hrp.len() + 1 + (data.len() * 8 + 4) / 5 + BECH32_CHECKSUM_LENGTH;
+    if length > *BECH32_LENGTH_RANGE.end() {
+        return Err(Bech32Error::InvalidLength(format!(
+            "the encoded string would have {} characters, more than {}",
+            length,
+            BECH32_LENGTH_RANGE.end()
+        )));
+    }
+    Ok(bech32::encode(hrp, data.to_base32())?)
+}
+
+fn serialize_hex<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex::encode(bytes))
+}
+
+/// A decoded bech32 string.
+#[derive(Debug, Clone, Serialize)]
+pub struct Bech32Data {
+    /// The lowercase human readable part.
+    pub hrp: String,
+    /// The first data byte, the version or address type, `None` without data.
+    pub version: Option<u8>,
+    /// The data bytes after the version, hex encoded in JSON.
+    #[serde(serialize_with = "serialize_hex")]
+    pub payload: Vec<u8>,
+}
+
+/// Decodes a bech32 string with any human readable part, checking its length, characters, case and checksum.
+pub fn bech32_decode(value: &str) -> Result<Bech32Data, Bech32Error> {
+    if !BECH32_LENGTH_RANGE.contains(&value.len()) {
+        return Err(Bech32Error::InvalidLength(format!(
+            "expected between {} and {} characters, found {}",
+            BECH32_LENGTH_RANGE.start(),
+            BECH32_LENGTH_RANGE.end(),
+            value.len()
+        )));
+    }
+    check_bech32_characters(value)?;
+    let separator = value.rfind('1').ok_or(Bech32Error::MissingSeparator)?;
+    check_bech32_hrp(&value[..separator])?;
+    let data_length = value.len() - separator - 1;
+    if data_length < BECH32_CHECKSUM_LENGTH {
+        return Err(Bech32Error::InvalidLength(format!(
+            "expected at least {} data characters for the checksum, found {}",
+            BECH32_CHECKSUM_LENGTH, data_length
+        )));
+    }
+    let (hrp, data) = bech32::decode(value)?;
+    let bytes = Vec::from_base32(&data)?;
+    Ok(match bytes.split_first() {
+        Some((version, payload)) => Bech32Data {
+            hrp,
+            version: Some(*version),
+            payload: payload.to_vec(),
+        },
+        None => Bech32Data {
+            hrp,
+            version: None,
+            payload: Vec::new(),
+        },
+    })
+}
+
+/// Address parsing errors.
+#[derive(Debug, thiserror::Error)]
+pub enum AddressError {
+    /// The bech32 checksum doesn't match the address.
+    #[error("invalid address checksum")]
+    InvalidChecksum,
+    /// The bech32 HRP isn't the expected one.
+    #[error("invalid address HRP `{found}`, expected `{expected}`")]
+    InvalidHrp { expected: String, found: String },
+    /// The address or its decoded bytes have an invalid length.
+    #[error("invalid address length: {0}")]
+    InvalidLength(String),
+    /// The address contains a character that isn't allowed by its encoding.
+    #[error("invalid address character `{0}`")]
+    InvalidCharacter(char),
+    /// The bech32 address mixes upper and lower case characters.
+    #[error("invalid address: mixed case characters")]
+    MixedCase,
+    /// The address is neither hex nor bech32 (missing the `1` separator).
+    #[error("invalid address: not a hex or bech32 address")]
+    MissingSeparator,
+    /// The bech32 address type byte isn't supported.
+    #[error("unsupported address type {0}")]
+    UnsupportedType(u8),
+    /// The bech32 HRP or data is invalid.
+    #[error("invalid address: {0}")]
+    InvalidBech32(Bech32Error),
+}
+
+impl From<Bech32Error> for AddressError {
+    fn from(error: Bech32Error) -> Self {
+        match error {
+            Bech32Error::InvalidChecksum => Self::InvalidChecksum,
+            Bech32Error::InvalidCharacter(c) => Self::InvalidCharacter(c),
+            Bech32Error::MixedCase => Self::MixedCase,
+            Bech32Error::MissingSeparator => Self::MissingSeparator,
+            Bech32Error::InvalidLength(length) => Self::InvalidLength(length),
+            error => Self::InvalidBech32(error),
+        }
+    }
+}
+
+/// The encoding of a parsed address string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressEncoding {
+    /// Bech32 address with a human readable part.
+    Bech32,
+    /// Hex encoded Ed25519 address.
+    Hex,
+}
+
+/// An Ed25519 address parsed from a bech32 or hex string.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParsedAddress {
+    /// The detected encoding.
+    pub encoding: AddressEncoding,
+    /// The human readable part, if the address is bech32 encoded.
+    pub hrp: Option<String>,
+    /// The Ed25519 address bytes.
+    pub bytes: Vec<u8>,
+}
+
+impl ParsedAddress {
+    /// Gets the parsed address.
+    pub fn address(&self) -> Address {
+        let bytes: [u8; ED25519_ADDRESS_LENGTH] =
+            self.bytes[..].try_into().expect("parsed address has an invalid length");
+        Address::Ed25519(Ed25519Address::new(bytes))
+    }
+}
+
+/// Parses a bech32 or hex (optionally `0x` prefixed) Ed25519 address string.
+/// If `expected_hrp` is set, a bech32 address must use it.
+pub fn parse_address(address: &str, expected_hrp: Option<&str>) -> Result<ParsedAddress, AddressError> {
+    let (address_hex, prefixed) = match address.strip_prefix("0x") {
+        Some(address_hex) => (address_hex, true),
+        None => (address, false),
+    };
+    if prefixed || address_hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        parse_hex_address(address_hex)
+    } else {
+        parse_bech32_address(address, expected_hrp)
+    }
+}
+
+fn parse_hex_address(address: &str) -> Result<ParsedAddress, AddressError> {
+    if let Some(c) = address.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(AddressError::InvalidCharacter(c));
+    }
+    if address.len() != ED25519_ADDRESS_LENGTH * 2 {
+        return Err(AddressError::InvalidLength(format!(
+            "expected {} hex characters, found {}",
+            ED25519_ADDRESS_LENGTH * 2,
+            address.len()
+        )));
+    }
+    Ok(ParsedAddress {
+        encoding: AddressEncoding::Hex,
+        hrp: None,
+        bytes: hex::decode(address).expect("hex characters were checked"),
+    })
+}
+
+fn parse_bech32_address(address: &str, expected_hrp: Option<&str>) -> Result<ParsedAddress, AddressError> {
+    let Bech32Data { hrp, version, payload } = bech32_decode(address)?;
+    if let Some(expected_hrp) = expected_hrp {
+        if !hrp.eq_ignore_ascii_case(expected_hrp) {
+            return Err(AddressError::InvalidHrp {
+                expected: expected_hrp.to_string(),
+                found: hrp,
+            });
+        }
+    }
+    match version {
+        Some(ED25519_ADDRESS_TYPE) if payload.len() == ED25519_ADDRESS_LENGTH => Ok(ParsedAddress {
+            encoding: AddressEncoding::Bech32,
+            hrp: Some(hrp),
+            bytes: payload,
+        }),
+        Some(ED25519_ADDRESS_TYPE) => Err(AddressError::InvalidLength(format!(
+            "expected {} address bytes, found {}",
+            ED25519_ADDRESS_LENGTH,
+            payload.len()
+        ))),
+        Some(address_type) => Err(AddressError::UnsupportedType(address_type)),
+        None => Err(AddressError::InvalidLength("empty address data".to_string())),
+    }
+}
+
+/// Encodes an address as bech32 with the given human readable part.
+pub fn address_to_bech32(address: &Address, bech32_hrp: &str) -> crate::Result<String> {
+    match address {
+        Address::Ed25519(address) => {
+            let mut address_bytes = vec![ED25519_ADDRESS_TYPE];
+            address_bytes.extend_from_slice(address.as_ref());
+            Ok(bech32_encode(bech32_hrp, &address_bytes).map_err(AddressError::from)?)
+        }
+        _ => Err(anyhow::anyhow!("unsupported address type: {:?}", address).into()),
+    }
+}
+
+/// A bech32 address converted to hex, with the HRP found in the address.
+#[derive(Debug, Clone, Serialize)]
+pub struct HexAddress {
+    /// The hex encoded Ed25519 address.
+    pub hex: String,
+    /// The human readable part of the bech32 address.
+    pub hrp: String,
+}
+
+/// Converts a hex Ed25519 address of exactly 64 hex characters to bech32 with the given human readable part.
+pub fn hex_to_bech32(address: &str, bech32_hrp: &str) -> crate::Result<String> {
+    let address = parse_hex_address(address)?.address();
+    address_to_bech32(&address, bech32_hrp)
+}
+
+/// Converts a bech32 Ed25519 address to hex, verifying its checksum.
+pub fn bech32_to_hex(address: &str) -> Result<HexAddress, AddressError> {
+    let parsed = parse_bech32_address(address, None)?;
+    Ok(HexAddress {
+        hex: hex::encode(&parsed.bytes),
+        hrp: parsed.hrp.expect("bech32 addresses have an HRP"),
+    })
+}
+
+/// Encodes an Ed25519 address as hex.
+pub fn address_to_hex(address: &Address) -> crate::Result<String> {
+    match address {
+        Address::Ed25519(address) => Ok(address.to_string()),
+        _ => Err(anyhow::anyhow!("unsupported address type: {:?}", address).into()),
+    }
+}
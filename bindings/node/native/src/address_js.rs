@@ -0,0 +1,81 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The Node.js functions of the address utilities.
+
+use crate::address::{bech32_decode, bech32_encode, bech32_to_hex, hex_to_bech32, parse_address, AddressError};
+use neon::prelude::*;
+
+fn expected_hrp_argument(cx: &mut FunctionContext) -> NeonResult<Option<String>> {
+    match cx.argument_opt(1) {
+        Some(arg) if arg.is_a::<JsString>() => Ok(Some(arg.downcast_or_throw::<JsString, _>(cx)?.value())),
+        _ => Ok(None),
+    }
+}
+
+/// JS `isAddressValid(address, expectedHrp?)`.
+pub fn is_address_valid(mut cx: FunctionContext) -> JsResult<JsBoolean> {
+    let address = cx.argument::<JsString>(0)?.value();
+    let expected_hrp = expected_hrp_argument(&mut cx)?;
+    let valid = parse_address(&address, expected_hrp.as_deref()).is_ok();
+    Ok(cx.boolean(valid))
+}
+
+/// Throws the error of an invalid address, with the `EINVALIDADDRESS` code.
+fn throw_invalid_address<T>(cx: &mut FunctionContext, address: String, error: AddressError) -> NeonResult<T> {
+    let message = format!("invalid address `{}`: {}", address, error);
+    let js_error = crate::error_code::js_error(cx, message, &crate::Error::InvalidAddress(address, error))?;
+    cx.throw(js_error)
+}
+
+/// JS `parseAddress(address, expectedHrp?)`, returning the JSON encoded [`ParsedAddress`].
+pub fn parse_address_js(mut cx: FunctionContext) -> JsResult<JsString> {
+    let address = cx.argument::<JsString>(0)?.value();
+    let expected_hrp = expected_hrp_argument(&mut cx)?;
+    match parse_address(&address, expected_hrp.as_deref()) {
+        Ok(parsed) => Ok(cx.string(serde_json::to_string(&parsed).unwrap())),
+        Err(e) => throw_invalid_address(&mut cx, address, e),
+    }
+}
+
+/// JS `hexToBech32(address, bech32Hrp)`.
+pub fn hex_to_bech32_js(mut cx: FunctionContext) -> JsResult<JsString> {
+    let address = cx.argument::<JsString>(0)?.value();
+    let bech32_hrp = cx.argument::<JsString>(1)?.value();
+    match hex_to_bech32(&address, &bech32_hrp) {
+        Ok(address) => Ok(cx.string(address)),
+        Err(crate::Error::Address(e)) => throw_invalid_address(&mut cx, address, e),
+        Err(e) => crate::error_code::throw_error(&mut cx, &e),
+    }
+}
+
+/// JS `bech32ToHex(address)`, returning the JSON encoded [`HexAddress`].
+pub fn bech32_to_hex_js(mut cx: FunctionContext) -> JsResult<JsString> {
+    let address = cx.argument::<JsString>(0)?.value();
+    match bech32_to_hex(&address) {
+        Ok(address) => Ok(cx.string(serde_json::to_string(&address).unwrap())),
+        Err(e) => throw_invalid_address(&mut cx, address, e),
+    }
+}
+
+/// JS `bech32Encode(hrp, dataHex)`: encodes the data bytes, starting with the version or address type byte.
+pub fn bech32_encode_js(mut cx: FunctionContext) -> JsResult<JsString> {
+    let hrp = cx.argument::<JsString>(0)?.value();
+    let data = cx.argument::<JsString>(1)?.value();
+    let result = hex::decode(data.strip_prefix("0x").unwrap_or(&data))
+        .map_err(crate::Error::from)
+        .and_then(|data| Ok(bech32_encode(&hrp, &data)?));
+    match result {
+        Ok(encoded) => Ok(cx.string(encoded)),
+        Err(e) => crate::error_code::throw_error(&mut cx, &e),
+    }
+}
+
+/// JS `bech32Decode(bech32String)`, returning the JSON encoded [`Bech32Data`](crate::address::Bech32Data).
+pub fn bech32_decode_js(mut cx: FunctionContext) -> JsResult<JsString> {
+    let value = cx.argument::<JsString>(0)?.value();
+    match bech32_decode(&value) {
+        Ok(decoded) => Ok(cx.string(serde_json::to_string(&decoded).unwrap())),
+        Err(e) => crate::error_code::throw_error(&mut cx, &e.into()),
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use iota::{client::api::AddressMonitor as Monitor, types::Bech32Address};
+use neon::prelude::*;
+
+use super::topic_subscriber::{listen_mqtt_events, WaitForMessageTask};
+
+// Delivers the activities of the milestones since the last processed one while the monitor is listening, `false` if
+// the client was dropped.
+fn catch_up(client_id: &str, monitor: &Monitor, tx: &Sender<Option<String>>, listening: &AtomicBool) -> bool {
+    let client = match crate::find_client(client_id) {
+        Some(client) => client,
+        None => return false,
+    };
+    let client = client.read().unwrap();
+    let bech32_hrp = client.get_network_info().bech32_hrp;
+    let result = crate::block_on(monitor.catch_up(&client, |activity| {
+        if let Ok(activity) = crate::address_activity_dto(activity, &bech32_hrp) {
+            let _ = tx.send(Some(activity.to_string()));
+        }
+        listening.load(Ordering::SeqCst)
+    }));
+    if let Err(e) = result {
+        let error = serde_json::json!({ "error": crate::error_codes::error_json(&e.into()) });
+        let _ = tx.send(Some(error.to_string()));
+    }
+    true
+}
+
+struct StartTask {
+    client_id: String,
+    monitor: Arc<Monitor>,
+    tx: Sender<Option<String>>,
+    events: (Sender<Option<String>>, Arc<Mutex<Receiver<Option<String>>>>),
+    listening: Arc<AtomicBool>,
+}
+
+impl Task for StartTask {
+    type Output = ();
+    type Error = crate::Error;
+    type JsEvent = JsUndefined;
+
+    fn perform(&self) -> Result<Self::Output, Self::Error> {
+        crate::convert_panics(|| {
+            // the connection events trigger the catch-ups, the first one included
+            listen_mqtt_events(&self.client_id, self.listening.clone(), self.events.0.clone());
+            let topics = self.monitor.topics()?;
+            {
+                let client = crate::get_client(&self.client_id);
+                let mut client = client.write().unwrap();
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                let monitor = self.monitor.clone();
+                let tx = Mutex::new(self.tx.clone());
+                let listening = self.listening.clone();
+                let subscribed = client.subscriber().with_topics(topics.clone()).subscribe(move |event| {
+                    if !listening.load(Ordering::SeqCst) {
+                        return;
+                    }
+                    if let Ok(Some(activity)) = monitor.live_activity(event) {
+                        if let Ok(activity) = crate::address_activity_dto(activity, &bech32_hrp) {
+                            let _ = tx.lock().unwrap().send(Some(activity.to_string()));
+                        }
+                    }
+                });
+                if let Err(e) = subscribed {
+                    self.listening.store(false, Ordering::SeqCst);
+                    let _ = self.tx.send(None);
+                    return Err(e.into());
+                }
+            }
+
+            let client_id = self.client_id.clone();
+            let monitor = self.monitor.clone();
+            let tx = self.tx.clone();
+            let listening = self.listening.clone();
+            let events = self.events.1.clone();
+            thread::spawn(move || {
+                let events = events.lock().unwrap();
+                let mut caught_up = catch_up(&client_id, &monitor, &tx, &listening);
+                while caught_up && listening.load(Ordering::SeqCst) {
+                    let event = match events.recv() {
+                        Ok(Some(event)) => event,
+                        _ => break,
+                    };
+                    let event: serde_json::Value = serde_json::from_str(&event).unwrap_or_default();
+                    match event["type"].as_str() {
+                        Some("connected") => caught_up = catch_up(&client_id, &monitor, &tx, &listening),
+                        // the connection is lost for good, the next subscription of the client connects again
+                        Some("error") => {
+                            let error = serde_json::json!({ "error": { "message": event["error"] } });
+                            let _ = tx.send(Some(error.to_string()));
+                        }
+                        _ => {}
+                    }
+                }
+                // only the address topic is unsubscribed: the solid milestones are shared with the other subscribers,
+                // the handler of the monitor ignores them once it stopped
+                if let Some(client) = crate::find_client(&client_id) {
+                    if let Ok(mut client) = client.write() {
+                        let _ = client.subscriber().with_topics(topics[..1].to_vec()).unsubscribe();
+                    }
+                }
+                let _ = tx.send(None);
+            });
+            Ok(())
+        })
+    }
+
+    fn complete(self, mut cx: TaskContext, result: Result<Self::Output, Self::Error>) -> JsResult<Self::JsEvent> {
+        match result {
+            Ok(_) => Ok(cx.undefined()),
+            Err(e) => crate::error_code::throw_error(&mut cx, &e),
+        }
+    }
+}
+
+pub struct AddressMonitor {
+    client_id: String,
+    monitor: Arc<Monitor>,
+    // `None` is sent once the monitor stopped, ending the polling.
+    tx: Sender<Option<String>>,
+    rx: Arc<Mutex<Receiver<Option<String>>>>,
+    // the MQTT connection events of the client, `None` is sent when the monitor stops, ending its thread.
+    events_tx: Sender<Option<String>>,
+    events_rx: Arc<Mutex<Receiver<Option<String>>>>,
+    // set while monitoring, the activities and the connection events are ignored once cleared
+    listening: Arc<AtomicBool>,
+    started: bool,
+}
+
+impl Drop for AddressMonitor {
+    fn drop(&mut self) {
+        if self.listening.swap(false, Ordering::SeqCst) {
+            let _ = self.events_tx.send(None);
+        }
+    }
+}
+
+declare_types! {
+    pub class JsAddressMonitor for AddressMonitor {
+        init(mut cx) {
+            let client_id = cx.argument::<JsString>(0)?.value();
+            let address = cx.argument::<JsString>(1)?.value();
+            let since_milestone_index = match cx.argument_opt(2) {
+                Some(arg) if arg.is_a::<JsNumber>() => {
+                    Some(arg.downcast_or_throw::<JsNumber, _>(&mut cx)?.value() as u64)
+                }
+                _ => None,
+            };
+            let (tx, rx) = channel();
+            let (events_tx, events_rx) = channel();
+
+            Ok(AddressMonitor {
+                client_id,
+                monitor: Arc::new(Monitor::new(Bech32Address(address), since_milestone_index)),
+                tx,
+                rx: Arc::new(Mutex::new(rx)),
+                events_tx,
+                events_rx: Arc::new(Mutex::new(events_rx)),
+                listening: Arc::new(AtomicBool::new(false)),
+                started: false,
+            })
+        }
+
+        method start(mut cx) {
+            let cb = cx.argument::<JsFunction>(0)?;
+            let started = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let instance = this.borrow(&guard);
+                instance.started
+            };
+            // a monitor is only started once, a stopped one isn't started again
+            if started {
+                return cx.throw_error("the address monitor was already started");
+            }
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let instance = &mut this.borrow_mut(&guard);
+                instance.started = true;
+                instance.listening.store(true, Ordering::SeqCst);
+                let start_task = StartTask {
+                    client_id: instance.client_id.clone(),
+                    monitor: instance.monitor.clone(),
+                    tx: instance.tx.clone(),
+                    events: (instance.events_tx.clone(), instance.events_rx.clone()),
+                    listening: instance.listening.clone(),
+                };
+                start_task.schedule(cb);
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method lastMilestoneIndex(mut cx) {
+            let last_milestone_index = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let instance = this.borrow(&guard);
+                instance.monitor.last_milestone_index()
+            };
+
+            match last_milestone_index {
+                Some(index) => Ok(cx.number(index as f64).upcast()),
+                None => Ok(cx.undefined().upcast()),
+            }
+        }
+
+        method stop(mut cx) {
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let instance = this.borrow(&guard);
+                if instance.listening.swap(false, Ordering::SeqCst) {
+                    let _ = instance.events_tx.send(None);
+                }
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method poll(mut cx) {
+            let cb = cx.argument::<JsFunction>(0)?;
+
+            {
+                let this = cx.this();
+                let rx = cx.borrow(&this, |monitor| monitor.rx.clone());
+                let receive_task = WaitForMessageTask(rx);
+                receive_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use iota::CancellationToken;
+use neon::prelude::*;
+use once_cell::sync::Lazy;
+
+// The tokens of the calls made with an `AbortSignal` by id, and the token of the calls being scheduled.
+#[derive(Default)]
+struct Cancellations {
+    tokens: Mutex<HashMap<u64, CancellationToken>>,
+    pending: Mutex<Option<CancellationToken>>,
+    next_id: AtomicU64,
+}
+
+fn cancellations() -> &'static Cancellations {
+    static CANCELLATIONS: Lazy<Cancellations> = Lazy::new(Default::default);
+    &CANCELLATIONS
+}
+
+/// The token of the calls scheduled between `beginCancellation()` and `endCancellation()`. JS schedules a call
+/// synchronously, so the token only goes to the calls made with the signal.
+pub(crate) fn pending_cancellation() -> Option<CancellationToken> {
+    cancellations().pending.lock().unwrap().clone()
+}
+
+/// `beginCancellation()`: creates the token of the calls scheduled until `endCancellation()`, returns its id.
+pub fn begin_cancellation_js(mut cx: FunctionContext) -> JsResult<JsNumber> {
+    let cancellations = cancellations();
+    let id = cancellations.next_id.fetch_add(1, Ordering::SeqCst);
+    let token = CancellationToken::new();
+    cancellations.tokens.lock().unwrap().insert(id, token.clone());
+    cancellations.pending.lock().unwrap().replace(token);
+    Ok(cx.number(id as f64))
+}
+
+/// `endCancellation()`: the next calls are scheduled without a token.
+pub fn end_cancellation_js(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    cancellations().pending.lock().unwrap().take();
+    Ok(cx.undefined())
+}
+
+/// `cancel(id)`: cancels the calls of the token, they reject with the `ECANCELLED` code.
+pub fn cancel_js(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let id = cx.argument::<JsNumber>(0)?.value() as u64;
+    let token = cancellations().tokens.lock().unwrap().get(&id).cloned();
+    if let Some(token) = token {
+        token.cancel();
+    }
+    Ok(cx.undefined())
+}
+
+/// `releaseCancellation(id)`: forgets the token once its calls are settled.
+pub fn release_cancellation_js(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let id = cx.argument::<JsNumber>(0)?.value() as u64;
+    cancellations().tokens.lock().unwrap().remove(&id);
+    Ok(cx.undefined())
+}
@@ -1,7 +1,6 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use iota::Seed;
 use neon::prelude::*;
 
 use std::ops::Range;
@@ -27,7 +26,8 @@ declare_types! {
         }
 
         method accountIndex(mut cx) {
-            let account_index = cx.argument::<JsNumber>(0)?.value() as usize;
+            let account_index = cx.argument::<JsNumber>(0)?.value();
+            let account_index = super::derivation_index(&mut cx, account_index, "account index")?;
             {
                 let mut this = cx.this();
                 let guard = cx.lock();
@@ -58,26 +58,26 @@ declare_types! {
                 let guard = cx.lock();
                 let ref_ = &this.borrow(&guard);
 
-                let seed = Seed::from_ed25519_bytes(&hex::decode(&ref_.seed).expect("invalid seed hex")).expect("invalid seed");
+                let signer = crate::signer::Signer::parse(&ref_.seed).expect("invalid seed");
 
                 let client = crate::get_client(&ref_.client_id);
                 let client = client.read().unwrap();
-                let mut getter = client.find_addresses(&seed);
+                signer.load().and_then(|signer| {
+                    let mut getter = client.find_addresses(signer.signer_type());
 
-                if let Some(account_index) = &ref_.account_index {
-                    getter = getter.with_account_index(*account_index);
-                }
-                if let Some(range) = &ref_.range {
-                    getter = getter.with_range(range.clone());
-                }
-                getter.get_all().map(|addresses| {
-                    serde_json::to_string(&addresses).unwrap()
+                    if let Some(account_index) = &ref_.account_index {
+                        getter = getter.with_account_index(*account_index);
+                    }
+                    if let Some(range) = &ref_.range {
+                        getter = getter.with_range(range.clone());
+                    }
+                    Ok(serde_json::to_string(&getter.get_all()?).unwrap())
                 })
             };
 
             match addresses_json {
                 Ok(addresses) => Ok(cx.string(addresses).upcast()),
-                Err(e) => cx.throw_error(e.to_string()),
+                Err(e) => crate::error_code::throw_error(&mut cx, &e),
             }
         }
     }
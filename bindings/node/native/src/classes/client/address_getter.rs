@@ -0,0 +1,133 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use neon::prelude::*;
+
+use std::ops::Range;
+
+pub struct AddressGetter {
+    client_id: String,
+    seed: String,
+    account_index: Option<usize>,
+    range: Option<Range<usize>>,
+    bech32_hrp: Option<String>,
+    include_internal: bool,
+}
+
+declare_types! {
+    pub class JsAddressGetter for AddressGetter {
+        init(mut cx) {
+            let client_id = cx.argument::<JsString>(0)?.value();
+            let seed = cx.argument::<JsString>(1)?.value();
+            Ok(AddressGetter {
+                client_id,
+                seed,
+                account_index: None,
+                range: None,
+                bech32_hrp: None,
+                include_internal: false,
+            })
+        }
+
+        method accountIndex(mut cx) {
+            let account_index = cx.argument::<JsNumber>(0)?.value();
+            let account_index = super::derivation_index(&mut cx, account_index, "account index")?;
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let get_account_index = &mut this.borrow_mut(&guard).account_index;
+                get_account_index.replace(account_index);
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method range(mut cx) {
+            let start = cx.argument::<JsNumber>(0)?.value();
+            let end = cx.argument::<JsNumber>(1)?.value();
+            if start < 0.0 || end < 0.0 || start.fract() != 0.0 || end.fract() != 0.0 {
+                return cx.throw_error(format!("invalid address range {}..{}, expected non-negative integers", start, end));
+            }
+            let range = Range { start: start as usize, end: end as usize };
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let get_range = &mut this.borrow_mut(&guard).range;
+                get_range.replace(range);
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method bech32Hrp(mut cx) {
+            let bech32_hrp = cx.argument::<JsString>(0)?.value();
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let get_bech32_hrp = &mut this.borrow_mut(&guard).bech32_hrp;
+                get_bech32_hrp.replace(bech32_hrp);
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method includeInternal(mut cx) {
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).include_internal = true;
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method get(mut cx) {
+            let addresses_json = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let ref_ = &this.borrow(&guard);
+
+                let signer = crate::signer::Signer::parse(&ref_.seed).expect("invalid seed");
+
+                let client = crate::get_client(&ref_.client_id);
+                let client = client.read().unwrap();
+                // the explicit HRP allows deriving addresses without a synced node
+                let bech32_hrp = ref_
+                    .bech32_hrp
+                    .clone()
+                    .unwrap_or_else(|| client.get_network_info().bech32_hrp);
+                let include_internal = ref_.include_internal;
+                signer
+                    .load()
+                    .and_then(|signer| {
+                        let mut getter = client
+                            .find_addresses(signer.signer_type())
+                            .with_account_index(ref_.account_index.unwrap_or(0));
+                        if let Some(range) = &ref_.range {
+                            getter = getter.with_range(range.clone());
+                        }
+                        Ok(getter.get_all()?)
+                    })
+                    .and_then(|addresses| {
+                        let mut bech32_addresses = Vec::new();
+                        for (address, internal) in addresses {
+                            if !internal || include_internal {
+                                let address = crate::address::parse_address(&address.to_string(), None)?.address();
+                                bech32_addresses.push((crate::address::address_to_bech32(&address, &bech32_hrp)?, internal));
+                            }
+                        }
+                        Ok(if include_internal {
+                            serde_json::to_string(&bech32_addresses).unwrap()
+                        } else {
+                            serde_json::to_string(&bech32_addresses.into_iter().map(|(address, _)| address).collect::<Vec<String>>()).unwrap()
+                        })
+                    })
+            };
+
+            match addresses_json {
+                Ok(addresses) => Ok(cx.string(addresses).upcast()),
+                Err(e) => crate::error_code::throw_error(&mut cx, &e),
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota::{Address, OutputType};
+use neon::prelude::*;
+
+use super::{parse_address, Api, ClientTask};
+
+pub struct AddressOutputsGetter {
+    client_id: String,
+    address: Address,
+    include_spent: bool,
+    output_type: Option<OutputType>,
+    resolve: bool,
+}
+
+declare_types! {
+    pub class JsAddressOutputsGetter for AddressOutputsGetter {
+        init(mut cx) {
+            let client_id = cx.argument::<JsString>(0)?.value();
+            let address = cx.argument::<JsString>(1)?.value();
+            let address = match parse_address(&client_id, &address) {
+                Ok(address) => address,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            Ok(AddressOutputsGetter {
+                client_id,
+                address,
+                include_spent: false,
+                output_type: None,
+                resolve: false,
+            })
+        }
+
+        method includeSpent(mut cx) {
+            let include_spent = cx.argument::<JsBoolean>(0)?.value();
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).include_spent = include_spent;
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method outputType(mut cx) {
+            let output_type = cx.argument::<JsString>(0)?.value();
+            let output_type = match output_type.as_str() {
+                "SignatureLockedSingle" => OutputType::SignatureLockedSingle,
+                "SignatureLockedDustAllowance" => OutputType::SignatureLockedDustAllowance,
+                _ => return cx.throw_error(format!(
+                    "invalid output type: {}, expected SignatureLockedSingle or SignatureLockedDustAllowance",
+                    output_type
+                )),
+            };
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).output_type.replace(output_type);
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method resolve(mut cx) {
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).resolve = true;
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method get(mut cx) {
+            let cb = cx.argument::<JsFunction>(0)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let ref_ = &(*this.borrow(&guard));
+                let client_task = ClientTask {
+                    client_id: ref_.client_id.clone(),
+                    api: Api::GetAddressOutputs {
+                        address: ref_.address.clone(),
+                        include_spent: ref_.include_spent,
+                        output_type: ref_.output_type,
+                        resolve: ref_.resolve,
+                    },
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+    }
+}
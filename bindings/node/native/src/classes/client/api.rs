@@ -1,59 +1,336 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{convert::TryInto, str::FromStr};
+use std::{
+    convert::TryInto,
+    ops::Range,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
-use super::MessageDto;
+use crate::signer::Signer;
 
-use iota::{types::Bech32Address, Address, ClientMiner, MessageBuilder, MessageId, Seed, UTXOInput};
+use super::{
+    MessageDto, MessageFormat, MessagePayloadDto, MessageResponseDto, PreparedTransactionDataDto, RemainderDto,
+    TransactionPlanDto,
+};
+
+use iota::{
+    client::api::{
+        AccountAddress, InputCandidate, InputSelectionStrategy, RemainderStrategy, TransactionHistory, TransactionPlan,
+    },
+    types::Bech32Address,
+    Address, CancellationToken, Client, LedgerInclusionState, MessageId, Method, OutputType, OutputsOptions, Payload,
+    TransactionId, UTXOInput,
+};
 use neon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A submitted message along with its id, and the remainder output of its transaction if it has one.
+#[derive(Serialize)]
+struct SentMessageDto {
+    #[serde(rename = "messageId")]
+    message_id: MessageId,
+    message: MessageResponseDto,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remainder: Option<RemainderDto>,
+}
+
+/// The balance of a seed along with the balance of every used address.
+#[derive(Serialize)]
+struct SeedBalanceDto {
+    balance: String,
+    addresses: Vec<AddressBalanceDto>,
+}
+
+/// The balance of a seed split by the inclusion state of the messages of its outputs.
+#[derive(Serialize)]
+struct BalanceSplitDto {
+    confirmed: String,
+    pending: String,
+}
+
+/// A page of the messages of an index, with the cursor of the next page.
+#[derive(Serialize)]
+struct MessagePageDto {
+    messages: Vec<super::UnpackedMessageDto>,
+    #[serde(rename = "nextCursor")]
+    next_cursor: Option<String>,
+    #[serde(rename = "hasMore")]
+    has_more: bool,
+}
+
+/// The value delta of an address in a transaction, serialized as string since JS numbers can't hold every i64 value.
+#[derive(Serialize)]
+struct AddressDeltaDto {
+    address: String,
+    delta: String,
+}
+
+/// A transaction of the history of a seed or of addresses.
+#[derive(Serialize)]
+struct TransactionHistoryEntryDto {
+    #[serde(rename = "transactionId")]
+    transaction_id: String,
+    #[serde(rename = "messageId")]
+    message_id: String,
+    #[serde(rename = "milestoneIndex")]
+    milestone_index: Option<u64>,
+    timestamp: Option<u64>,
+    #[serde(rename = "inclusionState")]
+    inclusion_state: Option<LedgerInclusionState>,
+    deltas: Vec<AddressDeltaDto>,
+    counterparts: Vec<String>,
+}
+
+/// The output of `getTransactionHistory`.
+#[derive(Clone, Copy, Deserialize)]
+pub(crate) enum TransactionHistoryFormat {
+    /// The entries as objects.
+    #[serde(rename = "entries")]
+    Entries,
+    /// The JSON lines export of the core client, as a string.
+    #[serde(rename = "jsonl")]
+    JsonLines,
+    /// The CSV export of the core client, as a string.
+    #[serde(rename = "csv")]
+    Csv,
+}
+
+impl Default for TransactionHistoryFormat {
+    fn default() -> Self {
+        Self::Entries
+    }
+}
+
+// Serializes the history in the requested format.
+fn serialize_transaction_history(
+    history: TransactionHistory,
+    format: TransactionHistoryFormat,
+) -> crate::Result<String> {
+    let serialized = match format {
+        TransactionHistoryFormat::Entries => {
+            let entries: Vec<TransactionHistoryEntryDto> = history
+                .entries
+                .into_iter()
+                .map(|entry| TransactionHistoryEntryDto {
+                    transaction_id: entry.transaction_id,
+                    message_id: entry.message_id,
+                    milestone_index: entry.milestone_index,
+                    timestamp: entry.timestamp,
+                    inclusion_state: entry.inclusion_state,
+                    deltas: entry
+                        .deltas
+                        .into_iter()
+                        .map(|delta| AddressDeltaDto {
+                            address: delta.address.0,
+                            delta: delta.delta.to_string(),
+                        })
+                        .collect(),
+                    counterparts: entry.counterparts.into_iter().map(|address| address.0).collect(),
+                })
+                .collect();
+            serde_json::to_string(&entries).unwrap()
+        }
+        TransactionHistoryFormat::JsonLines => serde_json::to_string(&history.to_json_lines()?).unwrap(),
+        TransactionHistoryFormat::Csv => serde_json::to_string(&history.to_csv()).unwrap(),
+    };
+    Ok(serialized)
+}
+
+#[derive(Serialize)]
+struct AddressBalanceDto {
+    address: String,
+    balance: String,
+    #[serde(rename = "dustAllowed")]
+    dust_allowed: bool,
+}
 
 pub(crate) enum Api {
     // High level APIs
     Send {
-        seed: Option<Seed>,
+        signer: Option<Signer>,
         index: Option<String>,
         data: Option<Vec<u8>>,
         parent: Option<MessageId>,
+        parents: Option<Vec<MessageId>>,
+        parents_check: bool,
+        account_index: Option<usize>,
+        initial_address_index: Option<usize>,
+        input_selection: Option<InputSelectionStrategy>,
+        inputs: Vec<UTXOInput>,
+        include_dust_allowance: bool,
+        remainder_strategy: Option<RemainderStrategy>,
+        dust_remainder_to_first_output: bool,
+        outputs: Vec<(Address, u64)>,
+        dust_allowance_outputs: Vec<(Address, u64)>,
+        dust_check: bool,
+        address_reuse_allowed: bool,
+        with_message: bool,
+    },
+    PrepareTransaction {
+        signer: Option<Signer>,
+        account_addresses: Option<Vec<AccountAddress>>,
+        input_candidates: Option<Vec<InputCandidate>>,
         account_index: Option<usize>,
         initial_address_index: Option<usize>,
+        input_selection: Option<InputSelectionStrategy>,
         inputs: Vec<UTXOInput>,
+        include_dust_allowance: bool,
+        remainder_strategy: Option<RemainderStrategy>,
+        dust_remainder_to_first_output: bool,
         outputs: Vec<(Address, u64)>,
+        dust_allowance_outputs: Vec<(Address, u64)>,
+        dust_check: bool,
+        address_reuse_allowed: bool,
+        index: Option<String>,
+        data: Option<Vec<u8>>,
+        // the plan of a dry run instead, signed if set
+        dry_run: Option<bool>,
+    },
+    GetInputCandidates {
+        signer: Option<Signer>,
+        account_addresses: Option<Vec<AccountAddress>>,
+        account_index: Option<usize>,
+        initial_address_index: Option<usize>,
+        inputs: Vec<UTXOInput>,
+        include_dust_allowance: bool,
+    },
+    SubmitSignedTransaction {
+        payload: MessagePayloadDto,
+        parent: Option<MessageId>,
+        parents: Option<Vec<MessageId>>,
+        parents_check: bool,
+        with_message: bool,
+    },
+    SubmitPlan {
+        signer: Option<Signer>,
+        plan: TransactionPlan,
+        parent: Option<MessageId>,
+        parents: Option<Vec<MessageId>>,
+        parents_check: bool,
     },
     GetUnspentAddress {
-        seed: Seed,
+        signer: Signer,
         account_index: Option<usize>,
         initial_address_index: Option<usize>,
+        include_funded: bool,
     },
     FindMessages {
+        indexation_keys: Vec<String>,
         message_ids: Vec<MessageId>,
+        limit: Option<usize>,
+    },
+    FindMessagePage {
+        index: String,
+        page_size: usize,
+        cursor: Option<String>,
+        referenced_after: Option<u64>,
+        data_prefix: Option<Vec<u8>>,
     },
     GetBalance {
-        seed: Seed,
+        signer: Signer,
         account_index: Option<usize>,
         initial_address_index: Option<usize>,
+        gap_limit: Option<usize>,
+        include_addresses: bool,
+        confirmed_only: bool,
+        split: bool,
     },
     GetAddressBalances(Vec<Address>),
+    GetTransactionHistory {
+        signer: Signer,
+        account_index: Option<usize>,
+        initial_address_index: Option<usize>,
+        gap_limit: Option<usize>,
+        format: TransactionHistoryFormat,
+    },
+    GetAddressTransactionHistory {
+        addresses: Vec<Address>,
+        format: TransactionHistoryFormat,
+    },
+    GetMessages(Vec<MessageId>),
+    ConsolidateOutputs {
+        signer: Signer,
+        account_index: Option<usize>,
+        range: Option<Range<usize>>,
+        include_dust_allowance: bool,
+        wait_for_confirmation: bool,
+    },
+    SendBatch {
+        signer: Signer,
+        transfers: Vec<Vec<(Address, u64)>>,
+        account_index: Option<usize>,
+        initial_address_index: Option<usize>,
+        parallelism: Option<usize>,
+        packing: bool,
+    },
+    SendData {
+        index: String,
+        data: Vec<u8>,
+        chaining: bool,
+        fragment_length: Option<usize>,
+    },
+    ReassembleData {
+        index: String,
+        content_hash: [u8; 32],
+    },
+    RefreshNetworkInfo,
+    RefreshNodeInfo,
     // Node APIs
     GetInfo,
     GetTips,
-    PostMessage(MessageDto),
+    GetPeers,
+    CallNodeApi {
+        method: Method,
+        path: String,
+        query: Vec<(String, String)>,
+        body: Option<serde_json::Value>,
+    },
+    PostMessage {
+        message: MessageDto,
+        with_message: bool,
+    },
     GetMessagesByIndexation(String),
-    GetMessage(MessageId),
+    GetMessage(MessageId, MessageFormat),
     GetMessageMetadata(MessageId),
     GetRawMessage(MessageId),
     GetMessageChildren(MessageId),
     GetOutput(UTXOInput),
+    GetIncludedMessage(TransactionId),
     FindOutputs {
         outputs: Vec<UTXOInput>,
         addresses: Vec<Address>,
+        atomic: bool,
     },
     GetAddressBalance(Address),
-    GetAddressOutputs(Address),
+    GetAddressOutputs {
+        address: Address,
+        include_spent: bool,
+        output_type: Option<OutputType>,
+        resolve: bool,
+    },
     GetMilestone(u64),
+    GetValidatedMilestone(u64),
+    GetMilestoneUtxoChanges(u64),
+    CreateInclusionProof(MessageId),
+    GetPendingMessages,
     Retry(MessageId),
+    RetryUntilIncluded {
+        message_id: MessageId,
+        interval: Option<Duration>,
+        max_attempts: Option<u64>,
+    },
+    WaitForBalance {
+        address: Address,
+        min_amount: u64,
+        interval: Option<Duration>,
+        timeout: Option<Duration>,
+    },
     Reattach(MessageId),
     Promote(MessageId),
+    Rebroadcast(MessageId),
 }
 
 pub(crate) struct ClientTask {
@@ -61,195 +338,840 @@ pub(crate) struct ClientTask {
     pub api: Api,
 }
 
-impl Task for ClientTask {
-    type Output = String;
-    type Error = crate::Error;
-    type JsEvent = JsString;
+impl ClientTask {
+    /// Schedules the task on the libuv thread pool, the callback gets the JSON result.
+    /// The task holds the client instance, so it completes safely even if the JS client is garbage collected while it
+    /// runs, e.g. when the promise was discarded. A task scheduled with the pending token of an `AbortSignal` is
+    /// cancelled with it.
+    pub(crate) fn schedule(self, cb: Handle<JsFunction>) {
+        let client = crate::find_client(&self.client_id);
+        ScheduledClientTask {
+            client,
+            task: self,
+            cancellation: crate::pending_cancellation(),
+        }
+        .schedule(cb)
+    }
+
     // TODO: Try async-mutex
     #[allow(clippy::await_holding_lock)]
-    fn perform(&self) -> Result<Self::Output, Self::Error> {
-        crate::block_on(crate::convert_async_panics(|| async move {
-            let client = crate::get_client(&self.client_id);
-            let client = client.read().unwrap();
-            let res = match &self.api {
-                // High level API
-                Api::Send {
-                    seed,
-                    index,
-                    data,
-                    parent,
-                    account_index,
-                    initial_address_index,
-                    inputs,
-                    outputs,
-                } => {
-                    let mut sender = client.send();
-                    if let Some(seed) = seed {
-                        sender = sender.with_seed(seed);
-                    }
-                    if let Some(index) = index {
-                        sender = sender.with_index(index);
-                    }
-                    if let Some(data) = data {
-                        sender = sender.with_data(data.clone());
-                    }
-                    if let Some(parent) = parent {
-                        sender = sender.with_parent(*parent);
-                    }
-                    if let Some(account_index) = account_index {
-                        sender = sender.with_account_index(*account_index);
-                    }
-                    if let Some(initial_address_index) = initial_address_index {
-                        sender = sender.with_initial_address_index(*initial_address_index);
-                    }
-                    for input in inputs {
-                        sender = sender.with_input(input.clone());
-                    }
-                    for output in outputs {
-                        sender = sender
-                            .with_output(&output.0.clone().to_bech32().into(), output.1)
-                            .unwrap();
-                    }
+    async fn perform(&self, client: &RwLock<Client>) -> crate::Result<String> {
+        let client = client.read().unwrap();
+        let res = match &self.api {
+            // High level API
+            Api::Send {
+                signer,
+                index,
+                data,
+                parent,
+                parents,
+                parents_check,
+                account_index,
+                initial_address_index,
+                input_selection,
+                inputs,
+                include_dust_allowance,
+                remainder_strategy,
+                dust_remainder_to_first_output,
+                outputs,
+                dust_allowance_outputs,
+                dust_check,
+                address_reuse_allowed,
+                with_message,
+            } => {
+                let signer = signer.as_ref().map(Signer::load).transpose()?;
+                let mut sender = client.send();
+                if let Some(signer) = &signer {
+                    sender = sender.with_signer(signer.signer_type());
+                }
+                if let Some(index) = index {
+                    sender = sender.with_index(index);
+                }
+                if let Some(data) = data {
+                    sender = sender.with_data(data.clone());
+                }
+                if let Some(parent) = parent {
+                    sender = sender.with_parent(*parent);
+                }
+                if let Some(parents) = parents {
+                    sender = sender.with_parents(parents.clone())?;
+                }
+                if !*parents_check {
+                    sender = sender.with_parents_check_disabled();
+                }
+                if let Some(account_index) = account_index {
+                    sender = sender.with_account_index(*account_index);
+                }
+                if let Some(initial_address_index) = initial_address_index {
+                    sender = sender.with_initial_address_index(*initial_address_index);
+                }
+                if let Some(input_selection) = input_selection {
+                    sender = sender.with_input_selection_strategy(input_selection.clone());
+                }
+                // the explicit inputs are selected with the manual strategy
+                for input in inputs {
+                    sender = sender.with_input(input.clone());
+                }
+                sender = sender.with_dust_allowance_inputs(*include_dust_allowance);
+                if let Some(remainder_strategy) = remainder_strategy {
+                    sender = sender.with_remainder_strategy(remainder_strategy.clone());
+                }
+                sender = sender.with_dust_remainder_to_first_output(*dust_remainder_to_first_output);
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                for (address, amount) in outputs {
+                    let address = crate::address::address_to_bech32(address, &bech32_hrp)?;
+                    sender = sender.with_output(&address.into(), *amount)?;
+                }
+                for (address, amount) in dust_allowance_outputs {
+                    let address = crate::address::address_to_bech32(address, &bech32_hrp)?;
+                    sender = sender.with_dust_allowance_output(&address.into(), *amount)?;
+                }
+                if !*dust_check {
+                    sender = sender.with_dust_check_disabled();
+                }
+                sender = sender.with_address_reuse_allowed(*address_reuse_allowed);
+                if *with_message {
+                    let (message_id, message, remainder) = sender.finish_with_remainder().await?;
+                    crate::watch_submitted_message(&self.client_id, message_id);
+                    let message = SentMessageDto {
+                        message_id,
+                        message: MessageResponseDto::new(&message, &bech32_hrp)?,
+                        remainder: remainder
+                            .map(|remainder| RemainderDto::new(&remainder, &bech32_hrp))
+                            .transpose()?,
+                    };
+                    serde_json::to_string(&message).unwrap()
+                } else {
                     let message_id = sender.finish().await?;
+                    crate::watch_submitted_message(&self.client_id, message_id);
                     serde_json::to_string(&message_id).unwrap()
                 }
-                Api::GetUnspentAddress {
-                    seed,
-                    account_index,
-                    initial_address_index,
-                } => {
-                    let mut getter = client.get_unspent_address(seed);
-                    if let Some(account_index) = account_index {
-                        getter = getter.with_account_index(*account_index);
-                    }
-                    if let Some(initial_address_index) = initial_address_index {
-                        getter = getter.with_initial_address_index(*initial_address_index);
-                    }
-                    let (address, index) = getter.get().await?;
-                    serde_json::to_string(&(address, index)).unwrap()
-                }
-                Api::FindMessages { message_ids } => {
-                    let messages = client.find_messages(&message_ids[..]).await?;
-                    serde_json::to_string(&messages).unwrap()
-                }
-                Api::GetBalance {
-                    seed,
-                    account_index,
-                    initial_address_index,
-                } => {
-                    let mut getter = client.get_balance(seed);
-                    if let Some(account_index) = account_index {
-                        getter = getter.with_account_index(*account_index);
-                    }
-                    if let Some(initial_address_index) = initial_address_index {
-                        getter = getter.with_initial_address_index(*initial_address_index);
-                    }
-                    let balance = getter.finish().await?;
-                    serde_json::to_string(&balance).unwrap()
+            }
+            Api::PrepareTransaction {
+                signer,
+                account_addresses,
+                input_candidates,
+                account_index,
+                initial_address_index,
+                input_selection,
+                inputs,
+                include_dust_allowance,
+                remainder_strategy,
+                dust_remainder_to_first_output,
+                outputs,
+                dust_allowance_outputs,
+                dust_check,
+                address_reuse_allowed,
+                index,
+                data,
+                dry_run,
+            } => {
+                let signer = signer.as_ref().map(Signer::load).transpose()?;
+                let mut sender = client.send();
+                if let Some(signer) = &signer {
+                    sender = sender.with_signer(signer.signer_type());
                 }
-                Api::GetAddressBalances(addresses) => {
-                    let bech32_addresses: Vec<Bech32Address> = addresses.iter().map(|a| a.to_bech32().into()).collect();
-                    let balances = client.get_address_balances(&bech32_addresses[..]).await?;
-                    let balances: Vec<super::AddressBalanceDto> = balances.into_iter().map(|b| b.into()).collect();
-                    serde_json::to_string(&balances).unwrap()
+                if let Some(account_addresses) = account_addresses {
+                    sender = sender.with_account_addresses(account_addresses.clone());
                 }
-                // Node APIs
-                Api::GetInfo => serde_json::to_string(&client.get_info().await?).unwrap(),
-                Api::GetTips => {
-                    let tips = client.get_tips().await?;
-                    let tips = vec![tips.0, tips.1];
-                    serde_json::to_string(&tips).unwrap()
-                }
-                Api::PostMessage(message) => {
-                    let (parent1, parent2) = if message.parent1.is_none() || message.parent2.is_none() {
-                        let tips = client.get_tips().await?;
-                        let parent1 = match &message.parent1 {
-                            Some(id) => MessageId::from_str(&id)?,
-                            None => tips.0,
-                        };
-                        let parent2 = match &message.parent2 {
-                            Some(id) => MessageId::from_str(&id)?,
-                            None => tips.1,
-                        };
-                        (parent1, parent2)
-                    } else {
-                        (
-                            MessageId::from_str(&message.parent1.as_ref().unwrap())?,
-                            MessageId::from_str(&message.parent1.as_ref().unwrap())?,
-                        )
+                if let Some(input_candidates) = input_candidates {
+                    sender = sender.with_input_candidates(input_candidates.clone());
+                }
+                if let Some(account_index) = account_index {
+                    sender = sender.with_account_index(*account_index);
+                }
+                if let Some(initial_address_index) = initial_address_index {
+                    sender = sender.with_initial_address_index(*initial_address_index);
+                }
+                if let Some(input_selection) = input_selection {
+                    sender = sender.with_input_selection_strategy(input_selection.clone());
+                }
+                // the explicit inputs are selected with the manual strategy
+                for input in inputs {
+                    sender = sender.with_input(input.clone());
+                }
+                sender = sender.with_dust_allowance_inputs(*include_dust_allowance);
+                if let Some(remainder_strategy) = remainder_strategy {
+                    sender = sender.with_remainder_strategy(remainder_strategy.clone());
+                }
+                sender = sender.with_dust_remainder_to_first_output(*dust_remainder_to_first_output);
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                for (address, amount) in outputs {
+                    let address = crate::address::address_to_bech32(address, &bech32_hrp)?;
+                    sender = sender.with_output(&address.into(), *amount)?;
+                }
+                for (address, amount) in dust_allowance_outputs {
+                    let address = crate::address::address_to_bech32(address, &bech32_hrp)?;
+                    sender = sender.with_dust_allowance_output(&address.into(), *amount)?;
+                }
+                if !*dust_check {
+                    sender = sender.with_dust_check_disabled();
+                }
+                sender = sender.with_address_reuse_allowed(*address_reuse_allowed);
+                if let Some(index) = index {
+                    sender = sender.with_index(index);
+                }
+                if let Some(data) = data {
+                    sender = sender.with_data(data.clone());
+                }
+                if let Some(sign) = dry_run {
+                    let plan = sender.dry_run(*sign).await?;
+                    serde_json::to_string(&TransactionPlanDto::new(&plan, &bech32_hrp)?).unwrap()
+                } else {
+                    let prepared = sender.prepare_transaction().await?;
+                    serde_json::to_string(&PreparedTransactionDataDto::new(&prepared, &bech32_hrp)?).unwrap()
+                }
+            }
+            Api::GetInputCandidates {
+                signer,
+                account_addresses,
+                account_index,
+                initial_address_index,
+                inputs,
+                include_dust_allowance,
+            } => {
+                let signer = signer.as_ref().map(Signer::load).transpose()?;
+                let mut sender = client.send();
+                if let Some(signer) = &signer {
+                    sender = sender.with_signer(signer.signer_type());
+                }
+                if let Some(account_addresses) = account_addresses {
+                    sender = sender.with_account_addresses(account_addresses.clone());
+                }
+                if let Some(account_index) = account_index {
+                    sender = sender.with_account_index(*account_index);
+                }
+                if let Some(initial_address_index) = initial_address_index {
+                    sender = sender.with_initial_address_index(*initial_address_index);
+                }
+                for input in inputs {
+                    sender = sender.with_input(input.clone());
+                }
+                sender = sender.with_dust_allowance_inputs(*include_dust_allowance);
+                serde_json::to_string(&sender.get_input_candidates().await?).unwrap()
+            }
+            Api::SubmitSignedTransaction {
+                payload,
+                parent,
+                parents,
+                parents_check,
+                with_message,
+            } => {
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                payload.check_bech32_hrp(&bech32_hrp)?;
+                let payload: Payload = payload.clone().try_into()?;
+                let payload = match payload {
+                    Payload::Transaction(payload) => *payload,
+                    _ => unreachable!("the payload type is checked by the caller"),
+                };
+                let mut sender = client.send();
+                if let Some(parent) = parent {
+                    sender = sender.with_parent(*parent);
+                }
+                if let Some(parents) = parents {
+                    sender = sender.with_parents(parents.clone())?;
+                }
+                if !*parents_check {
+                    sender = sender.with_parents_check_disabled();
+                }
+                let (message_id, message) = sender.finish_signed_transaction(payload).await?;
+                crate::watch_submitted_message(&self.client_id, message_id);
+                if *with_message {
+                    let message = SentMessageDto {
+                        message_id,
+                        message: MessageResponseDto::new(&message, &bech32_hrp)?,
+                        remainder: None,
                     };
-                    let message = MessageBuilder::<ClientMiner>::new()
-                        .with_network_id(client.get_network_id().await?)
-                        .with_parent1(parent1)
-                        .with_parent2(parent2)
-                        .with_nonce_provider(client.get_pow_provider(), 4000f64)
-                        .with_payload(message.payload.clone().try_into()?)
-                        .finish()?;
-                    let message_id = client.post_message(&message).await?;
+                    serde_json::to_string(&message).unwrap()
+                } else {
                     serde_json::to_string(&message_id).unwrap()
                 }
-                Api::GetMessagesByIndexation(index) => {
-                    let messages = client.get_message().index(index.as_str()).await?;
-                    serde_json::to_string(&messages).unwrap()
+            }
+            Api::SubmitPlan {
+                signer,
+                plan,
+                parent,
+                parents,
+                parents_check,
+            } => {
+                let signer = signer.as_ref().map(Signer::load).transpose()?;
+                let mut sender = client.send();
+                if let Some(signer) = &signer {
+                    sender = sender.with_signer(signer.signer_type());
                 }
-                Api::GetMessage(id) => {
-                    let message = client.get_message().data(&id).await?;
-                    serde_json::to_string(&message).unwrap()
+                if let Some(parent) = parent {
+                    sender = sender.with_parent(*parent);
+                }
+                if let Some(parents) = parents {
+                    sender = sender.with_parents(parents.clone())?;
+                }
+                if !*parents_check {
+                    sender = sender.with_parents_check_disabled();
+                }
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                let remainder = plan
+                    .prepared
+                    .remainder
+                    .as_ref()
+                    .map(|remainder| RemainderDto::new(remainder, &bech32_hrp))
+                    .transpose()?;
+                let (message_id, message) = sender.finish_plan(plan.clone()).await?;
+                crate::watch_submitted_message(&self.client_id, message_id);
+                let message = SentMessageDto {
+                    message_id,
+                    message: MessageResponseDto::new(&message, &bech32_hrp)?,
+                    remainder,
+                };
+                serde_json::to_string(&message).unwrap()
+            }
+            Api::GetUnspentAddress {
+                signer,
+                account_index,
+                initial_address_index,
+                include_funded,
+            } => {
+                let signer = signer.load()?;
+                let mut getter = client
+                    .get_unspent_address(signer.signer_type())
+                    .with_include_funded(*include_funded);
+                if let Some(account_index) = account_index {
+                    getter = getter.with_account_index(*account_index);
+                }
+                if let Some(initial_address_index) = initial_address_index {
+                    getter = getter.with_initial_address_index(*initial_address_index);
                 }
-                Api::GetMessageMetadata(id) => {
-                    let metadata = client.get_message().metadata(&id).await?;
-                    serde_json::to_string(&metadata).unwrap()
-                }
-                Api::GetRawMessage(id) => client.get_message().raw(&id).await?,
-                Api::GetMessageChildren(id) => {
-                    let messages = client.get_message().children(&id).await?;
-                    serde_json::to_string(&messages).unwrap()
-                }
-                Api::GetOutput(id) => {
-                    let output = client.get_output(id).await?;
-                    let output: super::OutputMetadataDto = output.into();
-                    serde_json::to_string(&output).unwrap()
-                }
-                Api::FindOutputs { outputs, addresses } => {
-                    let bech32_addresses: Vec<Bech32Address> =
-                        addresses.iter().map(|a| Bech32Address(a.to_bech32())).collect();
-                    let outputs = client.find_outputs(outputs, &bech32_addresses[..]).await?;
-                    let outputs: Vec<super::OutputMetadataDto> = outputs.into_iter().map(|o| o.into()).collect();
-                    serde_json::to_string(&outputs).unwrap()
-                }
-                Api::GetAddressBalance(address) => {
-                    let balance = client.get_address().balance(&address.to_bech32().into()).await?;
+                let (address, index) = getter.get().await?;
+                serde_json::to_string(&(address, index)).unwrap()
+            }
+            Api::FindMessages {
+                indexation_keys,
+                message_ids,
+                limit,
+            } => {
+                let messages = client
+                    .find_messages_with_limit(&indexation_keys[..], &message_ids[..], *limit)
+                    .await?;
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                let messages = messages
+                    .iter()
+                    .map(|message| {
+                        Ok(super::UnpackedMessageDto {
+                            message_id: message.id().0,
+                            message: super::MessageResponseDto::new(message, &bech32_hrp)?,
+                        })
+                    })
+                    .collect::<crate::Result<Vec<super::UnpackedMessageDto>>>()?;
+                serde_json::to_string(&messages).unwrap()
+            }
+            Api::FindMessagePage {
+                index,
+                page_size,
+                cursor,
+                referenced_after,
+                data_prefix,
+            } => {
+                // the pages aren't kept between the calls, so each page looks the index up again from its cursor
+                let mut builder = client.find_message_pages(index).with_page_size(*page_size);
+                if let Some(cursor) = cursor {
+                    builder = builder.with_cursor(cursor);
+                }
+                if let Some(referenced_after) = referenced_after {
+                    builder = builder.with_referenced_after(*referenced_after);
+                }
+                if let Some(data_prefix) = data_prefix {
+                    builder = builder.with_data_prefix(data_prefix);
+                }
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                let page = match builder.finish_pages()?.next_page().await? {
+                    Some(page) => MessagePageDto {
+                        messages: page
+                            .messages
+                            .iter()
+                            .map(|message| {
+                                Ok(super::UnpackedMessageDto {
+                                    message_id: message.id().0,
+                                    message: super::MessageResponseDto::new(message, &bech32_hrp)?,
+                                })
+                            })
+                            .collect::<crate::Result<Vec<super::UnpackedMessageDto>>>()?,
+                        next_cursor: page.next_cursor,
+                        has_more: page.has_more,
+                    },
+                    // a cursor past the last message id
+                    None => MessagePageDto {
+                        messages: Vec::new(),
+                        next_cursor: None,
+                        has_more: false,
+                    },
+                };
+                serde_json::to_string(&page).unwrap()
+            }
+            Api::GetMessages(message_ids) => {
+                let messages = client.get_messages(&message_ids[..]).await?;
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                // the unknown messages are null
+                let messages = messages
+                    .iter()
+                    .map(|message| {
+                        message
+                            .as_ref()
+                            .map(|message| {
+                                Ok(super::UnpackedMessageDto {
+                                    message_id: message.id().0,
+                                    message: super::MessageResponseDto::new(message, &bech32_hrp)?,
+                                })
+                            })
+                            .transpose()
+                    })
+                    .collect::<crate::Result<Vec<Option<super::UnpackedMessageDto>>>>()?;
+                serde_json::to_string(&messages).unwrap()
+            }
+            Api::ConsolidateOutputs {
+                signer,
+                account_index,
+                range,
+                include_dust_allowance,
+                wait_for_confirmation,
+            } => {
+                let signer = signer.load()?;
+                let mut builder = client
+                    .consolidate_outputs(signer.signer_type())
+                    .with_dust_allowance_outputs(*include_dust_allowance)
+                    .with_wait_for_confirmation(*wait_for_confirmation);
+                if let Some(account_index) = account_index {
+                    builder = builder.with_account_index(*account_index);
+                }
+                if let Some(range) = range {
+                    builder = builder.with_range(range.clone());
+                }
+                let message_ids = builder.finish().await?;
+                for message_id in &message_ids {
+                    crate::watch_submitted_message(&self.client_id, *message_id);
+                }
+                serde_json::to_string(&message_ids).unwrap()
+            }
+            Api::SendBatch {
+                signer,
+                transfers,
+                account_index,
+                initial_address_index,
+                parallelism,
+                packing,
+            } => {
+                let signer = signer.load()?;
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                let mut builder = client.send_batch(signer.signer_type()).with_packing(*packing);
+                for outputs in transfers {
+                    let outputs = outputs
+                        .iter()
+                        .map(|(address, amount)| {
+                            Ok((crate::address::address_to_bech32(address, &bech32_hrp)?.into(), *amount))
+                        })
+                        .collect::<crate::Result<Vec<(Bech32Address, u64)>>>()?;
+                    builder = builder.with_transfer(outputs);
+                }
+                if let Some(account_index) = account_index {
+                    builder = builder.with_account_index(*account_index);
+                }
+                if let Some(initial_address_index) = initial_address_index {
+                    builder = builder.with_initial_address_index(*initial_address_index);
+                }
+                if let Some(parallelism) = parallelism {
+                    builder = builder.with_parallelism(*parallelism);
+                }
+                // the transfers fail on their own, their errors are answered with their code
+                let results: Vec<serde_json::Value> = builder
+                    .finish()
+                    .await?
+                    .into_iter()
+                    .map(|result| match result {
+                        Ok(message_id) => {
+                            crate::watch_submitted_message(&self.client_id, message_id);
+                            serde_json::json!({ "messageId": message_id.to_string() })
+                        }
+                        Err(e) => serde_json::json!({ "error": crate::error_codes::error_json(&e.into()) }),
+                    })
+                    .collect();
+                serde_json::to_string(&results).unwrap()
+            }
+            Api::GetBalance {
+                signer,
+                account_index,
+                initial_address_index,
+                gap_limit,
+                include_addresses,
+                confirmed_only,
+                split,
+            } => {
+                let signer = signer.load()?;
+                let mut getter = client
+                    .get_balance(signer.signer_type())
+                    .with_confirmed_only(*confirmed_only);
+                if let Some(account_index) = account_index {
+                    getter = getter.with_account_index(*account_index);
+                }
+                if let Some(initial_address_index) = initial_address_index {
+                    getter = getter.with_initial_address_index(*initial_address_index);
+                }
+                if let Some(gap_limit) = gap_limit {
+                    getter = getter.with_gap_limit(*gap_limit);
+                }
+                // the balances are serialized as strings since JS numbers can't hold every u64 value
+                if *split {
+                    let split = getter.finish_split().await?;
+                    let split = BalanceSplitDto {
+                        confirmed: split.confirmed.to_string(),
+                        pending: split.pending.to_string(),
+                    };
+                    serde_json::to_string(&split).unwrap()
+                } else if *include_addresses {
+                    let (balance, address_balances) = getter.finish_with_addresses().await?;
+                    let balance = SeedBalanceDto {
+                        balance: balance.to_string(),
+                        addresses: address_balances
+                            .into_iter()
+                            .map(|pair| AddressBalanceDto {
+                                address: pair.address.to_string(),
+                                balance: pair.balance.to_string(),
+                                dust_allowed: pair.dust_allowed,
+                            })
+                            .collect(),
+                    };
                     serde_json::to_string(&balance).unwrap()
+                } else {
+                    let balance = getter.finish().await?;
+                    serde_json::to_string(&balance.to_string()).unwrap()
                 }
-                Api::GetAddressOutputs(address) => {
-                    let output_ids = client.get_address().outputs(&address.to_bech32().into()).await?;
-                    serde_json::to_string(&output_ids).unwrap()
+            }
+            Api::GetAddressBalances(addresses) => {
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                let bech32_addresses = addresses
+                    .iter()
+                    .map(|address| crate::address::address_to_bech32(address, &bech32_hrp).map(Bech32Address))
+                    .collect::<crate::Result<Vec<Bech32Address>>>()?;
+                let balances = client.get_address_balances(&bech32_addresses[..]).await?;
+                let balances = balances
+                    .into_iter()
+                    .map(|b| super::AddressBalanceDto::new(b, &bech32_hrp))
+                    .collect::<crate::Result<Vec<super::AddressBalanceDto>>>()?;
+                serde_json::to_string(&balances).unwrap()
+            }
+            Api::GetTransactionHistory {
+                signer,
+                account_index,
+                initial_address_index,
+                gap_limit,
+                format,
+            } => {
+                let signer = signer.load()?;
+                let mut getter = client.get_transaction_history(signer.signer_type());
+                if let Some(account_index) = account_index {
+                    getter = getter.with_account_index(*account_index);
                 }
-                Api::GetMilestone(index) => {
-                    let milestone = client.get_milestone(*index).await?;
-                    serde_json::to_string(&milestone).unwrap()
+                if let Some(initial_address_index) = initial_address_index {
+                    getter = getter.with_initial_address_index(*initial_address_index);
                 }
-                Api::Retry(message_id) => {
-                    let message = client.retry(message_id).await?;
-                    serde_json::to_string(&message).unwrap()
+                if let Some(gap_limit) = gap_limit {
+                    getter = getter.with_gap_limit(*gap_limit);
                 }
-                Api::Reattach(message_id) => {
-                    let message = client.reattach(message_id).await?;
-                    serde_json::to_string(&message).unwrap()
+                serialize_transaction_history(getter.finish().await?, *format)?
+            }
+            Api::GetAddressTransactionHistory { addresses, format } => {
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                let bech32_addresses = addresses
+                    .iter()
+                    .map(|address| crate::address::address_to_bech32(address, &bech32_hrp).map(Bech32Address))
+                    .collect::<crate::Result<Vec<Bech32Address>>>()?;
+                serialize_transaction_history(
+                    client.get_address_transaction_history(&bech32_addresses).await?,
+                    *format,
+                )?
+            }
+            Api::SendData {
+                index,
+                data,
+                chaining,
+                fragment_length,
+            } => {
+                let mut builder = client.data_message(index, data.clone()).with_chaining(*chaining);
+                if let Some(fragment_length) = fragment_length {
+                    builder = builder.with_fragment_length(*fragment_length);
                 }
-                Api::Promote(message_id) => {
-                    let message = client.promote(message_id).await?;
+                let data_message = builder.finish().await?;
+                for message_id in &data_message.message_ids {
+                    crate::watch_submitted_message(&self.client_id, *message_id);
+                }
+                serde_json::to_string(&serde_json::json!({
+                    "messageIds": data_message.message_ids,
+                    "contentHash": hex::encode(data_message.content_hash),
+                }))
+                .unwrap()
+            }
+            Api::ReassembleData { index, content_hash } => {
+                hex::encode(client.reassemble_data(index, content_hash).await?)
+            }
+            Api::RefreshNetworkInfo => serde_json::to_string(&client.refresh_network_info().await?).unwrap(),
+            Api::RefreshNodeInfo => serde_json::to_string(&client.refresh_node_info().await?).unwrap(),
+            // Node APIs
+            Api::GetInfo => serde_json::to_string(&client.get_info().await?).unwrap(),
+            Api::GetPeers => serde_json::to_string(&client.get_peers().await?).unwrap(),
+            Api::CallNodeApi {
+                method,
+                path,
+                query,
+                body,
+            } => {
+                let query: Vec<(&str, &str)> = query
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_str()))
+                    .collect();
+                let answer = client
+                    .call_node_api(method.clone(), path, &query, body.as_ref())
+                    .await?;
+                serde_json::to_string(&answer).unwrap()
+            }
+            Api::GetTips => {
+                let tips = client.get_tips().await?;
+                let tips = vec![tips.0, tips.1];
+                serde_json::to_string(&tips).unwrap()
+            }
+            Api::PostMessage { message, with_message } => {
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                message.payload.check_bech32_hrp(&bech32_hrp)?;
+                let (parent1, parent2) = if message.parent1.is_none() || message.parent2.is_none() {
+                    let tips = client.get_tips().await?;
+                    let parent1 = match &message.parent1 {
+                        Some(id) => MessageId::from_str(&id)?,
+                        None => tips.0,
+                    };
+                    let parent2 = match &message.parent2 {
+                        Some(id) => MessageId::from_str(&id)?,
+                        None => tips.1,
+                    };
+                    (parent1, parent2)
+                } else {
+                    (
+                        MessageId::from_str(&message.parent1.as_ref().unwrap())?,
+                        MessageId::from_str(&message.parent1.as_ref().unwrap())?,
+                    )
+                };
+                let network_id = client.get_network_id().await?;
+                // a message of another network fails before its PoW
+                if let Some(message_network_id) = message.network_id.filter(|id| *id != network_id) {
+                    return Err(iota::client::Error::NetworkIdMismatch(
+                        message_network_id,
+                        client.get_network_info().network_id,
+                        network_id,
+                    )
+                    .into());
+                }
+                let payload: Payload = message.payload.clone().try_into()?;
+                super::check_message_length(network_id, parent1, parent2, &payload)?;
+                let message = client
+                    .build_message(network_id, (parent1, parent2), Some(payload))
+                    .await?;
+                let message_id = client.post_message(&message).await?;
+                crate::watch_submitted_message(&self.client_id, message_id);
+                if *with_message {
+                    // the locally built message, available before the node serves it
+                    let message = SentMessageDto {
+                        message_id,
+                        message: MessageResponseDto::new(&message, &bech32_hrp)?,
+                        remainder: None,
+                    };
                     serde_json::to_string(&message).unwrap()
+                } else {
+                    serde_json::to_string(&message_id).unwrap()
                 }
-            };
-            Ok(res)
-        }))
+            }
+            Api::GetMessagesByIndexation(index) => {
+                let messages = client.get_message().index(index.as_str()).await?;
+                serde_json::to_string(&messages).unwrap()
+            }
+            Api::GetMessage(id, format) => {
+                let message = client.get_message().data(&id).await?;
+                super::serialize_message(&message, *format, &client.get_network_info().bech32_hrp)?
+            }
+            Api::GetMessageMetadata(id) => {
+                let metadata = client.get_message().metadata(&id).await?;
+                serde_json::to_string(&super::MessageMetadataDto::from(metadata)).unwrap()
+            }
+            // the packed bytes are sent hex-encoded, the JS wrapper turns them into a Buffer
+            Api::GetRawMessage(id) => hex::encode(client.get_message().raw(&id).await?),
+            Api::GetMessageChildren(id) => {
+                let messages = client.get_message().children(&id).await?;
+                serde_json::to_string(&messages).unwrap()
+            }
+            Api::GetOutput(id) => {
+                let output = client.get_output(id).await?;
+                let output = super::OutputMetadataDto::new(output, &client.get_network_info().bech32_hrp)?;
+                serde_json::to_string(&output).unwrap()
+            }
+            Api::GetIncludedMessage(transaction_id) => {
+                let message = client.get_included_message(transaction_id).await?;
+                let message = super::UnpackedMessageDto {
+                    message_id: message.id().0,
+                    message: super::MessageResponseDto::new(&message, &client.get_network_info().bech32_hrp)?,
+                };
+                serde_json::to_string(&message).unwrap()
+            }
+            Api::FindOutputs {
+                outputs,
+                addresses,
+                atomic,
+            } => {
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                let bech32_addresses = addresses
+                    .iter()
+                    .map(|address| crate::address::address_to_bech32(address, &bech32_hrp).map(Bech32Address))
+                    .collect::<crate::Result<Vec<Bech32Address>>>()?;
+                let (outputs, errors) = if *atomic {
+                    (client.find_outputs(outputs, &bech32_addresses[..]).await?, Vec::new())
+                } else {
+                    client.find_outputs_settled(outputs, &bech32_addresses[..]).await
+                };
+                let outputs = super::FoundOutputsDto::new(outputs, errors, &bech32_hrp)?;
+                serde_json::to_string(&outputs).unwrap()
+            }
+            Api::GetAddressBalance(address) => {
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                let address = crate::address::address_to_bech32(address, &bech32_hrp)?;
+                let balance = client.get_address().balance(&address.into()).await?;
+                serde_json::to_string(&balance).unwrap()
+            }
+            Api::GetAddressOutputs {
+                address,
+                include_spent,
+                output_type,
+                resolve,
+            } => {
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                let address = crate::address::address_to_bech32(address, &bech32_hrp)?;
+                let options = OutputsOptions {
+                    include_spent: *include_spent,
+                    output_type: *output_type,
+                };
+                let response = client.get_address().outputs_response(&address.into(), options).await?;
+                let outputs = if *resolve {
+                    Some(client.find_outputs(&response.utxo_inputs()?, &[]).await?)
+                } else {
+                    None
+                };
+                let outputs = super::AddressOutputsDto::new(response, outputs, &bech32_hrp)?;
+                serde_json::to_string(&outputs).unwrap()
+            }
+            Api::GetMilestone(index) => {
+                let milestone = client.get_milestone(*index).await?;
+                serde_json::to_string(&milestone).unwrap()
+            }
+            Api::GetValidatedMilestone(index) => {
+                let milestone = client.get_validated_milestone(*index).await?;
+                serde_json::to_string(&milestone).unwrap()
+            }
+            Api::GetMilestoneUtxoChanges(index) => {
+                let changes = client.get_milestone_utxo_changes(*index).await?;
+                serde_json::to_string(&changes).unwrap()
+            }
+            Api::CreateInclusionProof(message_id) => {
+                let proof = client.create_inclusion_proof(message_id).await?;
+                serde_json::to_string(&proof).unwrap()
+            }
+            Api::GetPendingMessages => serde_json::to_string(&client.get_pending_messages().await?).unwrap(),
+            Api::Retry(message_id) => {
+                let (message_id, message) = client.retry(message_id).await?;
+                crate::watch_submitted_message(&self.client_id, message_id);
+                let message = SentMessageDto {
+                    message_id,
+                    message: MessageResponseDto::new(&message, &client.get_network_info().bech32_hrp)?,
+                    remainder: None,
+                };
+                serde_json::to_string(&message).unwrap()
+            }
+            Api::RetryUntilIncluded {
+                message_id,
+                interval,
+                max_attempts,
+            } => {
+                let states = client
+                    .retry_until_included(message_id, *interval, *max_attempts)
+                    .await?;
+                serde_json::to_string(&states).unwrap()
+            }
+            Api::WaitForBalance {
+                address,
+                min_amount,
+                interval,
+                timeout,
+            } => {
+                let bech32_hrp = client.get_network_info().bech32_hrp;
+                let address = crate::address::address_to_bech32(address, &bech32_hrp)?;
+                let reached = client
+                    .wait_for_balance(&address.into(), *min_amount, *interval, *timeout)
+                    .await?;
+                serde_json::to_string(&super::ReachedBalanceDto::new(reached, &bech32_hrp)?).unwrap()
+            }
+            Api::Reattach(message_id) => {
+                let (message_id, message) = client.reattach(message_id).await?;
+                crate::watch_submitted_message(&self.client_id, message_id);
+                let message = SentMessageDto {
+                    message_id,
+                    message: MessageResponseDto::new(&message, &client.get_network_info().bech32_hrp)?,
+                    remainder: None,
+                };
+                serde_json::to_string(&message).unwrap()
+            }
+            Api::Promote(message_id) => {
+                let (message_id, message) = client.promote(message_id).await?;
+                crate::watch_submitted_message(&self.client_id, message_id);
+                let message = SentMessageDto {
+                    message_id,
+                    message: MessageResponseDto::new(&message, &client.get_network_info().bech32_hrp)?,
+                    remainder: None,
+                };
+                serde_json::to_string(&message).unwrap()
+            }
+            Api::Rebroadcast(message_id) => {
+                let bytes = client.rebroadcast(message_id).await?;
+                crate::watch_submitted_message(&self.client_id, *message_id);
+                hex::encode(bytes)
+            }
+        };
+        Ok(res)
+    }
+}
+
+/// A [`ClientTask`] along with its client instance, run on a background thread.
+struct ScheduledClientTask {
+    client: Option<Arc<RwLock<Client>>>,
+    task: ClientTask,
+    cancellation: Option<CancellationToken>,
+}
+
+impl Task for ScheduledClientTask {
+    type Output = String;
+    type Error = crate::Error;
+    type JsEvent = JsString;
+
+    fn perform(&self) -> Result<Self::Output, Self::Error> {
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("client dropped or not initialised"))?;
+        let task = crate::convert_async_panics(|| self.task.perform(client));
+        match &self.cancellation {
+            // the cancelled task is dropped, which releases the client lock and aborts its requests
+            Some(token) => crate::block_on(token.run(task)),
+            None => crate::block_on(task),
+        }
     }
 
     fn complete(self, mut cx: TaskContext, result: Result<Self::Output, Self::Error>) -> JsResult<Self::JsEvent> {
         match result {
             Ok(s) => Ok(cx.string(s)),
-            Err(e) => cx.throw_error(format!("ClientTask error: {:?}", e)),
+            Err(e) => {
+                let error = crate::error_code::js_error(&mut cx, format!("ClientTask error: {:?}", e), &e)?;
+                cx.throw(error)
+            }
         }
     }
 }
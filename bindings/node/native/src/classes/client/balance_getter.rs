@@ -1,7 +1,6 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use iota::Seed;
 use neon::prelude::*;
 
 use super::{Api, ClientTask};
@@ -11,6 +10,10 @@ pub struct BalanceGetter {
     seed: String,
     account_index: Option<usize>,
     initial_address_index: Option<usize>,
+    gap_limit: Option<usize>,
+    include_addresses: bool,
+    confirmed_only: bool,
+    split: bool,
 }
 
 declare_types! {
@@ -23,11 +26,16 @@ declare_types! {
                 seed,
                 account_index: None,
                 initial_address_index: None,
+                gap_limit: None,
+                include_addresses: false,
+                confirmed_only: true,
+                split: false,
             })
         }
 
         method accountIndex(mut cx) {
-            let account_index = cx.argument::<JsNumber>(0)?.value() as usize;
+            let account_index = cx.argument::<JsNumber>(0)?.value();
+            let account_index = super::derivation_index(&mut cx, account_index, "account index")?;
             {
                 let mut this = cx.this();
                 let guard = cx.lock();
@@ -39,7 +47,8 @@ declare_types! {
         }
 
         method initialAddressIndex(mut cx) {
-            let index = cx.argument::<JsNumber>(0)?.value() as usize;
+            let index = cx.argument::<JsNumber>(0)?.value();
+            let index = super::derivation_index(&mut cx, index, "initial address index")?;
             {
                 let mut this = cx.this();
                 let guard = cx.lock();
@@ -50,6 +59,52 @@ declare_types! {
             Ok(cx.this().upcast())
         }
 
+        method gapLimit(mut cx) {
+            let gap_limit = cx.argument::<JsNumber>(0)?.value() as usize;
+            if gap_limit == 0 {
+                return cx.throw_error("the gap limit can't be zero");
+            }
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let get_gap_limit = &mut this.borrow_mut(&guard).gap_limit;
+                get_gap_limit.replace(gap_limit);
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method includeAddresses(mut cx) {
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).include_addresses = true;
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method confirmedOnly(mut cx) {
+            let confirmed_only = cx.argument::<JsBoolean>(0)?.value();
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).confirmed_only = confirmed_only;
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method split(mut cx) {
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).split = true;
+            }
+
+            Ok(cx.this().upcast())
+        }
+
         method get(mut cx) {
             let cb = cx.argument::<JsFunction>(0)?;
             {
@@ -59,9 +114,13 @@ declare_types! {
                 let client_task = ClientTask {
                     client_id: ref_.client_id.clone(),
                     api: Api::GetBalance {
-                        seed: Seed::from_ed25519_bytes(&hex::decode(&ref_.seed).expect("invalid seed hex")).expect("invalid seed"),
+                        signer: crate::signer::Signer::parse(&ref_.seed).expect("invalid seed"),
                         account_index: ref_.account_index,
                         initial_address_index: ref_.initial_address_index,
+                        gap_limit: ref_.gap_limit,
+                        include_addresses: ref_.include_addresses,
+                        confirmed_only: ref_.confirmed_only,
+                        split: ref_.split,
                     },
                 };
                 client_task.schedule(cb);
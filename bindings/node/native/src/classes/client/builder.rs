@@ -1,19 +1,234 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{collections::HashMap, num::NonZeroU64, str::FromStr, time::Duration};
+use std::{collections::HashMap, num::NonZeroU64, str::FromStr, sync::Mutex, time::Duration};
 
-use iota::client::{Api, BrokerOptions, ClientBuilder};
+use iota::client::{
+    api::{CoordinatorKeys, ADDRESS_RANGE_MAX},
+    Api, BrokerOptions, Client, ClientBuilder, NodeAuth, NodeOptions,
+};
 use neon::prelude::*;
+use serde::Deserialize;
+
+use crate::{forward_mqtt_events, JsNonceProvider, RequestEventForwarder, RequestListeners};
+
+/// The options of a node added with `node(url, options)`, every role is allowed by default.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct NodeOptionsDto {
+    auth: Option<NodeAuth>,
+    #[serde(default = "default_role")]
+    allow_submit: bool,
+    #[serde(default = "default_role")]
+    remote_pow: bool,
+    #[serde(default = "default_role")]
+    mqtt: bool,
+}
+
+fn default_role() -> bool {
+    true
+}
+
+/// The options of the client state storage set with `storage(options)`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct StorageOptionsDto {
+    storage_path: String,
+}
+
+impl From<NodeOptionsDto> for NodeOptions {
+    fn from(options: NodeOptionsDto) -> Self {
+        Self {
+            auth: options.auth,
+            submit: options.allow_submit,
+            remote_pow: options.remote_pow,
+            mqtt: options.mqtt,
+        }
+    }
+}
 
 pub struct ClientBuilderWrapper {
     nodes: Vec<String>,
+    node_options: Vec<(String, NodeOptions)>,
     broker_options: Option<BrokerOptions>,
     node_sync_interval: Option<NonZeroU64>,
     request_timeout: Option<Duration>,
+    node_info_ttl: Option<Duration>,
+    rate_limit_retries: Option<usize>,
+    rate_limit_max_delay: Option<Duration>,
+    output_cache: Option<Duration>,
     api_timeout: HashMap<Api, Duration>,
     local_pow: bool,
+    pow_worker_count: Option<usize>,
+    nonce_provider: Option<(u64, Duration)>,
+    local_pow_fallback: bool,
     node_sync_enabled: bool,
+    offline: bool,
+    bech32_hrp: Option<String>,
+    network_id: Option<String>,
+    min_pow_score: Option<f64>,
+    node_auth: Option<NodeAuth>,
+    ca_certificate: Option<String>,
+    storage_path: Option<String>,
+    quorum: bool,
+    quorum_size: Option<usize>,
+    quorum_threshold: Option<usize>,
+    quorum_milestone_validation: bool,
+    coordinator_keys: Option<CoordinatorKeys>,
+    address_scan_window: Option<usize>,
+    address_scan_parallelism: Option<usize>,
+    request_parallelism: Option<usize>,
+    address_reuse_check: bool,
+    request_headers: Vec<(String, String)>,
+}
+
+impl ClientBuilderWrapper {
+    /// The client builder with the options set from JS, building it syncs the nodes unless the syncing is disabled.
+    /// The request listeners of the client are registered once it's stored.
+    fn client_builder(&self) -> (ClientBuilder, RequestListeners) {
+        let listeners = RequestListeners::default();
+        let builder = if self.offline {
+            ClientBuilder::offline()
+        } else {
+            ClientBuilder::new()
+        };
+        let mut builder = builder
+            .with_local_pow(self.local_pow)
+            .with_middleware(RequestEventForwarder {
+                headers: self.request_headers.clone(),
+                listeners: listeners.clone(),
+            });
+
+        for node in &self.nodes {
+            builder = builder
+                .with_node(node.as_str())
+                .unwrap_or_else(|_| panic!("invalid node url: {}", node));
+        }
+        for (node, options) in &self.node_options {
+            builder = builder
+                .with_node_options(node.as_str(), options.clone())
+                .unwrap_or_else(|_| panic!("invalid node url: {}", node));
+        }
+        if let Some(broker_options) = &self.broker_options {
+            builder = builder.with_mqtt_broker_options(broker_options.clone());
+        }
+        if let Some(pow_worker_count) = self.pow_worker_count {
+            builder = builder.with_pow_worker_count(pow_worker_count);
+        }
+        if let Some((provider_id, timeout)) = self.nonce_provider {
+            builder = builder
+                .with_nonce_provider(JsNonceProvider { provider_id, timeout })
+                .with_local_pow_fallback(self.local_pow_fallback);
+        }
+        if let Some(bech32_hrp) = &self.bech32_hrp {
+            builder = builder.with_bech32_hrp(bech32_hrp);
+        }
+        if let Some(network_id) = &self.network_id {
+            builder = builder.with_network_id(network_id);
+        }
+        if let Some(min_pow_score) = self.min_pow_score {
+            builder = builder.with_min_pow_score(min_pow_score);
+        }
+        if let Some(node_sync_interval) = self.node_sync_interval {
+            builder = builder.with_node_sync_interval(Duration::from_millis(node_sync_interval.get()));
+        }
+        if let Some(node_auth) = &self.node_auth {
+            builder = builder.with_node_auth(node_auth.clone());
+        }
+        if let Some(ca_certificate) = &self.ca_certificate {
+            builder = builder.with_ca_certificate(ca_certificate);
+        }
+        if let Some(storage_path) = &self.storage_path {
+            builder = builder.with_storage_path(storage_path);
+        }
+        if let Some(request_timeout) = self.request_timeout {
+            builder = builder.with_request_timeout(request_timeout);
+        }
+        if let Some(node_info_ttl) = self.node_info_ttl {
+            builder = builder.with_node_info_ttl(node_info_ttl);
+        }
+        if let Some(rate_limit_retries) = self.rate_limit_retries {
+            builder = builder.with_rate_limit_retries(rate_limit_retries);
+        }
+        if let Some(rate_limit_max_delay) = self.rate_limit_max_delay {
+            builder = builder.with_rate_limit_max_delay(rate_limit_max_delay);
+        }
+        if let Some(unspent_ttl) = self.output_cache {
+            builder = builder.with_output_cache(unspent_ttl);
+        }
+        for (api, timeout) in &self.api_timeout {
+            builder = builder.with_api_timeout(*api, *timeout);
+        }
+        if self.quorum {
+            builder = builder.with_quorum(true);
+        }
+        if let Some(quorum_size) = self.quorum_size {
+            builder = builder.with_quorum_size(quorum_size);
+        }
+        if let Some(quorum_threshold) = self.quorum_threshold {
+            builder = builder.with_quorum_threshold(quorum_threshold);
+        }
+        if self.quorum_milestone_validation {
+            builder = builder.with_quorum_milestone_validation(true);
+        }
+        if let Some(coordinator_keys) = &self.coordinator_keys {
+            builder = builder.with_coordinator_keys(coordinator_keys.clone());
+        }
+        if let Some(address_scan_window) = self.address_scan_window {
+            builder = builder.with_address_scan_window(address_scan_window);
+        }
+        if let Some(address_scan_parallelism) = self.address_scan_parallelism {
+            builder = builder.with_address_scan_parallelism(address_scan_parallelism);
+        }
+        if let Some(request_parallelism) = self.request_parallelism {
+            builder = builder.with_request_parallelism(request_parallelism);
+        }
+        if self.address_reuse_check {
+            builder = builder.with_address_reuse_check(true);
+        }
+        if !self.node_sync_enabled {
+            builder = builder.with_node_sync_disabled();
+        }
+        (builder, listeners)
+    }
+}
+
+// Stores the client with its request and MQTT event listeners, returning its id.
+fn store_client(mut client: Client, listeners: RequestListeners) -> String {
+    let mqtt_event_listeners = forward_mqtt_events(&mut client);
+    let id = crate::store_client(client);
+    crate::register_request_listeners(&id, listeners);
+    crate::register_mqtt_event_listeners(&id, mqtt_event_listeners);
+    id
+}
+
+/// Builds the client on a background thread, the initial node syncing doesn't block the event loop.
+struct BuildClientTask(Mutex<Option<(ClientBuilder, RequestListeners)>>);
+
+impl Task for BuildClientTask {
+    type Output = String;
+    type Error = crate::Error;
+    type JsEvent = JsValue;
+
+    fn perform(&self) -> Result<Self::Output, Self::Error> {
+        crate::convert_panics(|| {
+            let (builder, listeners) = self.0.lock().unwrap().take().expect("the client is already built");
+            Ok(store_client(builder.finish()?, listeners))
+        })
+    }
+
+    fn complete(self, mut cx: TaskContext, result: Result<Self::Output, Self::Error>) -> JsResult<Self::JsEvent> {
+        match result {
+            Ok(id) => {
+                let id = cx.string(id);
+                Ok(super::JsClient::new(&mut cx, vec![id])?.upcast())
+            }
+            Err(e) => {
+                let error = crate::error_code::js_error(&mut cx, format!("BuildClientTask error: {:?}", e), &e)?;
+                cx.throw(error)
+            }
+        }
+    }
 }
 
 declare_types! {
@@ -21,22 +236,60 @@ declare_types! {
         init(_) {
             Ok(ClientBuilderWrapper {
                 nodes: Default::default(),
+                node_options: Default::default(),
                 broker_options: Default::default(),
                 node_sync_interval: Default::default(),
                 request_timeout: Default::default(),
+                node_info_ttl: Default::default(),
+                rate_limit_retries: Default::default(),
+                rate_limit_max_delay: Default::default(),
+                output_cache: Default::default(),
                 api_timeout: Default::default(),
                 local_pow: true,
+                pow_worker_count: Default::default(),
+                nonce_provider: Default::default(),
+                local_pow_fallback: false,
                 node_sync_enabled: true,
+                offline: false,
+                bech32_hrp: Default::default(),
+                network_id: Default::default(),
+                min_pow_score: Default::default(),
+                node_auth: Default::default(),
+                ca_certificate: Default::default(),
+                storage_path: Default::default(),
+                quorum: false,
+                quorum_size: Default::default(),
+                quorum_threshold: Default::default(),
+                quorum_milestone_validation: false,
+                coordinator_keys: Default::default(),
+                address_scan_window: Default::default(),
+                address_scan_parallelism: Default::default(),
+                request_parallelism: Default::default(),
+                address_reuse_check: false,
+                request_headers: Default::default(),
             })
         }
 
         method node(mut cx) {
             let node_url = cx.argument::<JsString>(0)?.value();
+            let options = match cx.argument_opt(1) {
+                Some(arg) if arg.is_a::<JsString>() => {
+                    let options = arg.downcast_or_throw::<JsString, _>(&mut cx)?.value();
+                    match serde_json::from_str::<NodeOptionsDto>(&options) {
+                        Ok(options) => Some(NodeOptions::from(options)),
+                        Err(e) => return cx.throw_error(format!("invalid node options: {}", e)),
+                    }
+                }
+                _ => None,
+            };
             {
                 let mut this = cx.this();
                 let guard = cx.lock();
-                let nodes = &mut this.borrow_mut(&guard).nodes;
-                nodes.push(node_url);
+                let mut this = this.borrow_mut(&guard);
+                match options {
+                    Some(options) => this.node_options.push((node_url, options)),
+                    None => this.nodes.push(node_url),
+                }
             }
             Ok(cx.this().upcast())
         }
@@ -96,6 +349,16 @@ declare_types! {
             Ok(cx.this().upcast())
         }
 
+        method offline(mut cx) {
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let offline = &mut this.borrow_mut(&guard).offline;
+                *offline = true;
+            }
+            Ok(cx.this().upcast())
+        }
+
         method requestTimeout(mut cx) {
             let timeout = cx.argument::<JsNumber>(0)?.value() as u64;
             {
@@ -107,9 +370,56 @@ declare_types! {
             Ok(cx.this().upcast())
         }
 
+        method nodeInfoTtl(mut cx) {
+            let ttl = cx.argument::<JsNumber>(0)?.value() as u64;
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let node_info_ttl = &mut this.borrow_mut(&guard).node_info_ttl;
+                node_info_ttl.replace(Duration::from_millis(ttl));
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method rateLimitRetries(mut cx) {
+            let retries = cx.argument::<JsNumber>(0)?.value() as usize;
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let rate_limit_retries = &mut this.borrow_mut(&guard).rate_limit_retries;
+                rate_limit_retries.replace(retries);
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method rateLimitMaxDelay(mut cx) {
+            let max_delay = cx.argument::<JsNumber>(0)?.value() as u64;
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let rate_limit_max_delay = &mut this.borrow_mut(&guard).rate_limit_max_delay;
+                rate_limit_max_delay.replace(Duration::from_millis(max_delay));
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method outputCache(mut cx) {
+            let unspent_ttl = cx.argument::<JsNumber>(0)?.value() as u64;
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let output_cache = &mut this.borrow_mut(&guard).output_cache;
+                output_cache.replace(Duration::from_millis(unspent_ttl));
+            }
+            Ok(cx.this().upcast())
+        }
+
         method apiTimeout(mut cx) {
             let api = cx.argument::<JsString>(0)?.value();
-            let api = Api::from_str(&api).expect("unknown api kind");
+            let api = match Api::from_str(&api) {
+                Ok(api) => api,
+                Err(e) => return cx.throw_error(e),
+            };
             let timeout = cx.argument::<JsNumber>(1)?.value() as u64;
             {
                 let mut this = cx.this();
@@ -131,28 +441,299 @@ declare_types! {
             Ok(cx.this().upcast())
         }
 
+        method powWorkerCount(mut cx) {
+            let worker_count = cx.argument::<JsNumber>(0)?.value() as usize;
+            if worker_count == 0 {
+                return cx.throw_error("the PoW worker count can't be zero");
+            }
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let pow_worker_count = &mut this.borrow_mut(&guard).pow_worker_count;
+                pow_worker_count.replace(worker_count);
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method nonceProvider(mut cx) {
+            let provider_id = cx.argument::<JsNumber>(0)?.value() as u64;
+            let timeout = cx.argument::<JsNumber>(1)?.value() as u64;
+            if timeout == 0 {
+                return cx.throw_error("the nonce provider timeout can't be zero");
+            }
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let nonce_provider = &mut this.borrow_mut(&guard).nonce_provider;
+                nonce_provider.replace((provider_id, Duration::from_millis(timeout)));
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method localPowFallback(mut cx) {
+            let local_pow_fallback = cx.argument::<JsBoolean>(0)?.value();
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).local_pow_fallback = local_pow_fallback;
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method bech32Hrp(mut cx) {
+            let hrp = cx.argument::<JsString>(0)?.value();
+            // validate the HRP
+            if bech32::encode(&hrp, Vec::<bech32::u5>::new()).is_err() {
+                return cx.throw_error(format!("invalid bech32 HRP `{}`", hrp));
+            }
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let bech32_hrp = &mut this.borrow_mut(&guard).bech32_hrp;
+                bech32_hrp.replace(hrp);
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method networkId(mut cx) {
+            let network_id = cx.argument::<JsString>(0)?.value();
+            if network_id.is_empty() {
+                return cx.throw_error("the network id can't be empty");
+            }
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let network_id_ref = &mut this.borrow_mut(&guard).network_id;
+                network_id_ref.replace(network_id);
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method minPowScore(mut cx) {
+            let min_pow_score = cx.argument::<JsNumber>(0)?.value();
+            if !min_pow_score.is_finite() || min_pow_score < 0.0 {
+                return cx.throw_error(format!(
+                    "invalid minimum PoW score {}, expected a positive number",
+                    min_pow_score
+                ));
+            }
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let min_pow_score_ref = &mut this.borrow_mut(&guard).min_pow_score;
+                min_pow_score_ref.replace(min_pow_score);
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method nodeAuth(mut cx) {
+            let auth = cx.argument::<JsString>(0)?.value();
+            let auth: NodeAuth = match serde_json::from_str(&auth) {
+                Ok(auth) => auth,
+                Err(_) => return cx.throw_error("invalid node auth, expected `{ jwt }` or `{ username, password }`"),
+            };
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let node_auth = &mut this.borrow_mut(&guard).node_auth;
+                node_auth.replace(auth);
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method caCertificate(mut cx) {
+            let path = cx.argument::<JsString>(0)?.value();
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let ca_certificate = &mut this.borrow_mut(&guard).ca_certificate;
+                ca_certificate.replace(path);
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method storage(mut cx) {
+            let options = cx.argument::<JsString>(0)?.value();
+            let options: StorageOptionsDto = match serde_json::from_str(&options) {
+                Ok(options) => options,
+                Err(_) => return cx.throw_error("invalid storage options, expected `{ storagePath }`"),
+            };
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let storage_path = &mut this.borrow_mut(&guard).storage_path;
+                storage_path.replace(options.storage_path);
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method requestHeaders(mut cx) {
+            let headers = cx.argument::<JsString>(0)?.value();
+            let headers: HashMap<String, String> = match serde_json::from_str(&headers) {
+                Ok(headers) => headers,
+                Err(_) => return cx.throw_error("invalid request headers, expected an object of string values"),
+            };
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let request_headers = &mut this.borrow_mut(&guard).request_headers;
+                request_headers.extend(headers);
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method quorum(mut cx) {
+            let quorum = cx.argument::<JsBoolean>(0)?.value();
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let quorum_ref = &mut this.borrow_mut(&guard).quorum;
+                *quorum_ref = quorum;
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method addressReuseCheck(mut cx) {
+            let address_reuse_check = cx.argument::<JsBoolean>(0)?.value();
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let address_reuse_check_ref = &mut this.borrow_mut(&guard).address_reuse_check;
+                *address_reuse_check_ref = address_reuse_check;
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method quorumSize(mut cx) {
+            let size = cx.argument::<JsNumber>(0)?.value() as usize;
+            if size == 0 {
+                return cx.throw_error("the quorum size can't be zero");
+            }
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let quorum_size = &mut this.borrow_mut(&guard).quorum_size;
+                quorum_size.replace(size);
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method quorumThreshold(mut cx) {
+            let threshold = cx.argument::<JsNumber>(0)?.value() as usize;
+            if threshold == 0 || threshold > 100 {
+                return cx.throw_error("the quorum threshold must be a percentage between 1 and 100");
+            }
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let quorum_threshold = &mut this.borrow_mut(&guard).quorum_threshold;
+                quorum_threshold.replace(threshold);
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method quorumMilestoneValidation(mut cx) {
+            let validation = cx.argument::<JsBoolean>(0)?.value();
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let validation_ref = &mut this.borrow_mut(&guard).quorum_milestone_validation;
+                *validation_ref = validation;
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method coordinatorKeys(mut cx) {
+            let keys = cx.argument::<JsString>(0)?.value();
+            let keys: CoordinatorKeys = match serde_json::from_str(&keys) {
+                Ok(keys) if keys.signature_count > 0 => keys,
+                _ => return cx.throw_error("invalid coordinator keys, expected `{ publicKeys, signatureCount }`"),
+            };
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let coordinator_keys = &mut this.borrow_mut(&guard).coordinator_keys;
+                coordinator_keys.replace(keys);
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method addressScanWindow(mut cx) {
+            let window_size = cx.argument::<JsNumber>(0)?.value();
+            if window_size < 1.0 || window_size.fract() != 0.0 || window_size > ADDRESS_RANGE_MAX as f64 {
+                return cx.throw_error(format!(
+                    "invalid address scan window {}, expected an integer from 1 to {}",
+                    window_size, ADDRESS_RANGE_MAX
+                ));
+            }
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let address_scan_window = &mut this.borrow_mut(&guard).address_scan_window;
+                address_scan_window.replace(window_size as usize);
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method addressScanParallelism(mut cx) {
+            let parallelism = cx.argument::<JsNumber>(0)?.value();
+            if parallelism < 1.0 || parallelism.fract() != 0.0 {
+                return cx.throw_error(format!(
+                    "invalid address scan parallelism {}, expected a positive integer",
+                    parallelism
+                ));
+            }
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let address_scan_parallelism = &mut this.borrow_mut(&guard).address_scan_parallelism;
+                address_scan_parallelism.replace(parallelism as usize);
+            }
+            Ok(cx.this().upcast())
+        }
+
+        method requestParallelism(mut cx) {
+            let parallelism = cx.argument::<JsNumber>(0)?.value();
+            if parallelism < 1.0 || parallelism.fract() != 0.0 {
+                return cx.throw_error(format!(
+                    "invalid request parallelism {}, expected a positive integer",
+                    parallelism
+                ));
+            }
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let request_parallelism = &mut this.borrow_mut(&guard).request_parallelism;
+                request_parallelism.replace(parallelism as usize);
+            }
+            Ok(cx.this().upcast())
+        }
+
         method build(mut cx) {
-            let client = {
+            let (builder, listeners) = {
                 let this = cx.this();
                 let guard = cx.lock();
                 let ref_ = &*this.borrow(&guard);
-                let mut builder = ClientBuilder::new().with_local_pow(ref_.local_pow);
-
-                for node in &ref_.nodes {
-                    builder = builder.with_node(node.as_str()).unwrap_or_else(|_| panic!("invalid node url: {}", node));
-                }
-                if let Some(broker_options) = &ref_.broker_options {
-                    builder = builder.with_mqtt_broker_options(broker_options.clone());
-                }
-                if ref_.node_sync_enabled {
-                    builder = builder.with_node_sync_disabled();
-                }
-
-                builder.finish().expect("failed to build client instance")
+                ref_.client_builder()
+            };
+            let client = match builder.finish() {
+                Ok(client) => client,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e.into()),
             };
-            let id = crate::store_client(client);
+            let id = store_client(client, listeners);
             let id = cx.string(id);
             Ok(super::JsClient::new(&mut cx, vec![id])?.upcast())
         }
+
+        method buildAsync(mut cx) {
+            let cb = cx.argument::<JsFunction>(0)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let (builder, listeners) = this.borrow(&guard).client_builder();
+                BuildClientTask(Mutex::new(Some((builder, listeners)))).schedule(cb);
+            }
+            Ok(cx.undefined().upcast())
+        }
     }
 }
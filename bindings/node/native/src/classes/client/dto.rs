@@ -1,81 +1,549 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::address::AddressError;
+
 use iota::{
-    AddressBalancePair, Ed25519Signature, IndexationPayload, Input, Output, OutputMetadata, Payload, ReferenceUnlock,
-    SignatureLockedSingleOutput, SignatureUnlock, TransactionPayload, TransactionPayloadEssence, UTXOInput,
-    UnlockBlock,
+    client::api::{
+        AddressActivity, DustWarning, InputSigningData, PreparedTransactionData, RemainderData, TransactionPlan,
+        DUST_THRESHOLD, IOTA_SUPPLY,
+    },
+    common::packable::Packable,
+    pow::providers::{Constant, ConstantBuilder, ProviderBuilder},
+    verify_ed25519_signature, Address, AddressBalancePair, AddressOutputs, Ed25519Signature, IndexationPayload, Input,
+    LedgerInclusionState, Message, MessageBuilder, MessageId, MessageJson, MessageMetadata, Output, OutputLookup,
+    OutputMetadata, Payload, ReachedBalance, ReferenceUnlock, SignatureLockedDustAllowanceOutput,
+    SignatureLockedSingleOutput, SignatureUnlock, TransactionId, TransactionPayload, TransactionPayloadEssence,
+    UTXOInput, UnlockBlock,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 
 use std::{
     convert::{TryFrom, TryInto},
+    ops::RangeInclusive,
     str::FromStr,
 };
 
+const INPUT_OUTPUT_COUNT_MAX: usize = 126;
+const INPUT_OUTPUT_COUNT_RANGE: RangeInclusive<usize> = 1..=INPUT_OUTPUT_COUNT_MAX;
+pub(super) const INDEXATION_INDEX_LENGTH_RANGE: RangeInclusive<usize> = 1..=64;
+pub(super) const MESSAGE_LENGTH_MAX: usize = 32768;
+const ED25519_PUBLIC_KEY_LENGTH: usize = 32;
+const ED25519_SIGNATURE_LENGTH: usize = 64;
+const TRANSACTION_ID_LENGTH: usize = 32;
+const OUTPUT_INDEX_MAX: u16 = 126;
+const UTXO_INPUT_TYPE: u64 = 0;
+const TREASURY_INPUT_TYPE: u64 = 1;
+const SIGNATURE_LOCKED_SINGLE_OUTPUT_TYPE: u64 = 0;
+const SIGNATURE_LOCKED_DUST_ALLOWANCE_OUTPUT_TYPE: u64 = 1;
+const TREASURY_OUTPUT_TYPE: u64 = 2;
+const TRANSACTION_PAYLOAD_TYPE: u64 = 0;
+const MILESTONE_PAYLOAD_TYPE: u64 = 1;
+const INDEXATION_PAYLOAD_TYPE: u64 = 2;
+const ED25519_ADDRESS_TYPE: u8 = 1;
+
+/// Serializes a DTO with its numeric `type` discriminator.
+#[derive(Serialize)]
+struct TaggedDto<'a, T> {
+    #[serde(rename = "type")]
+    type_: u64,
+    #[serde(flatten)]
+    dto: &'a T,
+}
+
+/// Reads the numeric `type` discriminator of a DTO object, if it's present.
+fn dto_type<E: serde::de::Error>(value: &serde_json::Value, kind: &str) -> std::result::Result<Option<u64>, E> {
+    match value.get("type") {
+        Some(type_) => type_
+            .as_u64()
+            .map(Some)
+            .ok_or_else(|| E::custom(format!("invalid {} type `{}`, expected a number", kind, type_))),
+        None => Ok(None),
+    }
+}
+
+/// An address, as a bech32 or hex string or as the REST API `{ type, address }` object.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AddressDto {
+    String(String),
+    Object {
+        #[serde(rename = "type")]
+        type_: u8,
+        address: String,
+    },
+}
+
+fn deserialize_address<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<String, D::Error> {
+    match AddressDto::deserialize(deserializer)? {
+        AddressDto::String(address) => Ok(address),
+        AddressDto::Object {
+            type_: ED25519_ADDRESS_TYPE,
+            address,
+        } => Ok(address),
+        AddressDto::Object { type_, .. } => Err(D::Error::custom(format!("unsupported address type `{}`", type_))),
+    }
+}
+
+/// Binary data, as a byte array or as the REST API hex string.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DataDto {
+    Bytes(Vec<u8>),
+    Hex(String),
+}
+
+fn deserialize_data<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<u8>, D::Error> {
+    match DataDto::deserialize(deserializer)? {
+        DataDto::Bytes(data) => Ok(data),
+        DataDto::Hex(data) => decode_hex("data", &data).map_err(D::Error::custom),
+    }
+}
+
+/// A `u64`, as a number or as a decimal string since JS numbers can't represent every `u64`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum U64Dto {
+    Number(u64),
+    String(String),
+}
+
+fn deserialize_optional_u64<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<u64>, D::Error> {
+    match Option::<U64Dto>::deserialize(deserializer)? {
+        Some(U64Dto::Number(value)) => Ok(Some(value)),
+        Some(U64Dto::String(value)) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| D::Error::custom(format!("invalid unsigned 64-bit integer `{}`", value))),
+        None => Ok(None),
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
-pub struct OutputDto {
+pub struct UtxoInputDto {
+    #[serde(rename = "transactionId")]
+    transaction_id: String,
+    #[serde(rename = "transactionOutputIndex")]
+    transaction_output_index: u16,
+}
+
+impl TryFrom<&UtxoInputDto> for UTXOInput {
+    type Error = crate::Error;
+
+    fn try_from(value: &UtxoInputDto) -> crate::Result<Self> {
+        let transaction_id = decode_hex_field("transactionId", &value.transaction_id, TRANSACTION_ID_LENGTH)?;
+        let transaction_id: [u8; TRANSACTION_ID_LENGTH] = transaction_id[..]
+            .try_into()
+            .expect("transaction id length already validated");
+        if value.transaction_output_index > OUTPUT_INDEX_MAX {
+            return Err(anyhow::anyhow!(
+                "invalid `transactionOutputIndex` {}, expected at most {}",
+                value.transaction_output_index,
+                OUTPUT_INDEX_MAX
+            )
+            .into());
+        }
+        Ok(UTXOInput::new(
+            TransactionId::new(transaction_id),
+            value.transaction_output_index,
+        )?)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TreasuryInputDto {
+    #[serde(rename = "milestoneId")]
+    milestone_id: String,
+}
+
+/// An input of the transaction essence.
+/// Inputs without a `type` field are UTXO inputs.
+#[derive(Clone)]
+pub enum InputDto {
+    /// A UTXO input (type 0).
+    Utxo(UtxoInputDto),
+    /// A UTXO input as its output id string.
+    /// Deprecated in favour of the structured form.
+    OutputId(String),
+    /// A treasury input (type 1).
+    Treasury(TreasuryInputDto),
+}
+
+impl<'de> Deserialize<'de> for InputDto {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        if let serde_json::Value::String(output_id) = value {
+            return Ok(Self::OutputId(output_id));
+        }
+        match dto_type(&value, "input")?.unwrap_or(UTXO_INPUT_TYPE) {
+            UTXO_INPUT_TYPE => serde_json::from_value(value)
+                .map(Self::Utxo)
+                .map_err(|e| D::Error::custom(format!("invalid UTXO input (type 0): {}", e))),
+            TREASURY_INPUT_TYPE => serde_json::from_value(value)
+                .map(Self::Treasury)
+                .map_err(|e| D::Error::custom(format!("invalid treasury input (type 1): {}", e))),
+            type_ => Err(D::Error::custom(format!("unknown input type `{}`", type_))),
+        }
+    }
+}
+
+impl TryFrom<&Input> for InputDto {
+    type Error = crate::Error;
+
+    fn try_from(input: &Input) -> crate::Result<Self> {
+        match input {
+            Input::UTXO(input) => Ok(Self::Utxo(UtxoInputDto {
+                transaction_id: input.output_id().transaction_id().to_string(),
+                transaction_output_index: input.output_id().index(),
+            })),
+            Input::Treasury(input) => Ok(Self::Treasury(TreasuryInputDto {
+                milestone_id: input.milestone_id().to_string(),
+            })),
+            _ => Err(anyhow::anyhow!("unsupported input type").into()),
+        }
+    }
+}
+
+impl Serialize for InputDto {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::Utxo(input) => TaggedDto {
+                type_: UTXO_INPUT_TYPE,
+                dto: input,
+            }
+            .serialize(serializer),
+            Self::OutputId(output_id) => output_id.serialize(serializer),
+            Self::Treasury(input) => TaggedDto {
+                type_: TREASURY_INPUT_TYPE,
+                dto: input,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SignatureLockedSingleOutputDto {
+    #[serde(deserialize_with = "deserialize_address")]
     address: String,
     amount: u64,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SignatureLockedDustAllowanceOutputDto {
+    #[serde(deserialize_with = "deserialize_address")]
+    address: String,
+    amount: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TreasuryOutputDto {
+    amount: u64,
+}
+
+/// An output of the transaction essence.
+/// Outputs without a `type` field are signature locked single outputs.
+#[derive(Clone)]
+pub enum OutputDto {
+    /// A signature locked single output (type 0).
+    SignatureLockedSingle(SignatureLockedSingleOutputDto),
+    /// A signature locked dust allowance output (type 1).
+    SignatureLockedDustAllowance(SignatureLockedDustAllowanceOutputDto),
+    /// A treasury output (type 2).
+    Treasury(TreasuryOutputDto),
+}
+
+impl<'de> Deserialize<'de> for OutputDto {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        match dto_type(&value, "output")?.unwrap_or(SIGNATURE_LOCKED_SINGLE_OUTPUT_TYPE) {
+            SIGNATURE_LOCKED_SINGLE_OUTPUT_TYPE => serde_json::from_value(value)
+                .map(Self::SignatureLockedSingle)
+                .map_err(|e| D::Error::custom(format!("invalid signature locked single output (type 0): {}", e))),
+            SIGNATURE_LOCKED_DUST_ALLOWANCE_OUTPUT_TYPE => serde_json::from_value(value)
+                .map(Self::SignatureLockedDustAllowance)
+                .map_err(|e| {
+                    D::Error::custom(format!(
+                        "invalid signature locked dust allowance output (type 1): {}",
+                        e
+                    ))
+                }),
+            TREASURY_OUTPUT_TYPE => serde_json::from_value(value)
+                .map(Self::Treasury)
+                .map_err(|e| D::Error::custom(format!("invalid treasury output (type 2): {}", e))),
+            type_ => Err(D::Error::custom(format!("unknown output type `{}`", type_))),
+        }
+    }
+}
+
+impl OutputDto {
+    fn new(output: &Output, bech32_hrp: &str) -> crate::Result<Self> {
+        match output {
+            Output::SignatureLockedSingle(output) => Ok(Self::SignatureLockedSingle(SignatureLockedSingleOutputDto {
+                address: crate::address::address_to_bech32(output.address(), bech32_hrp)?,
+                amount: output.amount(),
+            })),
+            Output::SignatureLockedDustAllowance(output) => Ok(Self::SignatureLockedDustAllowance(
+                SignatureLockedDustAllowanceOutputDto {
+                    address: crate::address::address_to_bech32(output.address(), bech32_hrp)?,
+                    amount: output.amount(),
+                },
+            )),
+            _ => Err(anyhow::anyhow!("unsupported output type").into()),
+        }
+    }
+}
+
+impl Serialize for OutputDto {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::SignatureLockedSingle(output) => TaggedDto {
+                type_: SIGNATURE_LOCKED_SINGLE_OUTPUT_TYPE,
+                dto: output,
+            }
+            .serialize(serializer),
+            Self::SignatureLockedDustAllowance(output) => TaggedDto {
+                type_: SIGNATURE_LOCKED_DUST_ALLOWANCE_OUTPUT_TYPE,
+                dto: output,
+            }
+            .serialize(serializer),
+            Self::Treasury(output) => TaggedDto {
+                type_: TREASURY_OUTPUT_TYPE,
+                dto: output,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct MessageTransactionPayloadEssenceDto {
-    inputs: Box<[String]>,
+    inputs: Box<[InputDto]>,
     outputs: Box<[OutputDto]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     payload: Option<Box<MessagePayloadDto>>,
 }
 
+impl MessageTransactionPayloadEssenceDto {
+    fn new(essence: &TransactionPayloadEssence, bech32_hrp: &str) -> crate::Result<Self> {
+        Ok(Self {
+            inputs: essence
+                .inputs()
+                .iter()
+                .map(InputDto::try_from)
+                .collect::<crate::Result<Box<[InputDto]>>>()?,
+            outputs: essence
+                .outputs()
+                .iter()
+                .map(|output| OutputDto::new(output, bech32_hrp))
+                .collect::<crate::Result<Box<[OutputDto]>>>()?,
+            payload: match essence.payload() {
+                Some(payload) => Some(Box::new(MessagePayloadDto::new(payload, bech32_hrp)?)),
+                None => None,
+            },
+        })
+    }
+}
+
+/// An essence built from its DTO, with the positions of its inputs and outputs in the DTO.
+struct SortedEssence {
+    essence: TransactionPayloadEssence,
+    /// The DTO index of each essence input.
+    input_order: Vec<usize>,
+    /// The DTO index of each essence output.
+    output_order: Vec<usize>,
+}
+
+impl SortedEssence {
+    /// Whether the DTO listed the inputs and the outputs in the protocol order.
+    fn kept_order(&self) -> bool {
+        let sorted = |order: &[usize]| order.iter().enumerate().all(|(index, dto_index)| index == *dto_index);
+        sorted(&self.input_order) && sorted(&self.output_order)
+    }
+}
+
+// Packs an input or an output, the protocol sorts them in the lexicographic order of their packed bytes.
+fn packed<P: Packable>(value: &P) -> crate::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    value
+        .pack(&mut bytes)
+        .map_err(|_| anyhow::anyhow!("failed to pack the transaction essence"))?;
+    Ok(bytes)
+}
+
+// Sorts the packed items and returns them with their original index.
+fn sort_packed<T>(items: Vec<(Vec<u8>, T)>) -> Vec<(usize, T)> {
+    let mut items: Vec<(Vec<u8>, usize, T)> = items
+        .into_iter()
+        .enumerate()
+        .map(|(index, (bytes, item))| (bytes, index, item))
+        .collect();
+    items.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+    items.into_iter().map(|(_, index, item)| (index, item)).collect()
+}
+
 impl TryFrom<MessageTransactionPayloadEssenceDto> for TransactionPayloadEssence {
     type Error = crate::Error;
+    fn try_from(value: MessageTransactionPayloadEssenceDto) -> crate::Result<Self> {
+        Ok(SortedEssence::try_from(value)?.essence)
+    }
+}
+
+impl TryFrom<MessageTransactionPayloadEssenceDto> for SortedEssence {
+    type Error = crate::Error;
+    /// Validates the essence and sorts its inputs and outputs in the protocol order, whatever their order in the DTO.
     fn try_from(value: MessageTransactionPayloadEssenceDto) -> crate::Result<Self> {
         let mut builder = TransactionPayloadEssence::builder();
 
-        let inputs: Vec<Input> = value
-            .inputs
-            .into_vec()
+        if !INPUT_OUTPUT_COUNT_RANGE.contains(&value.inputs.len()) {
+            return Err(anyhow::anyhow!(
+                "invalid inputs count {}, expected between {} and {}",
+                value.inputs.len(),
+                INPUT_OUTPUT_COUNT_RANGE.start(),
+                INPUT_OUTPUT_COUNT_RANGE.end()
+            )
+            .into());
+        }
+        if !INPUT_OUTPUT_COUNT_RANGE.contains(&value.outputs.len()) {
+            return Err(anyhow::anyhow!(
+                "invalid outputs count {}, expected between {} and {}",
+                value.outputs.len(),
+                INPUT_OUTPUT_COUNT_RANGE.start(),
+                INPUT_OUTPUT_COUNT_RANGE.end()
+            )
+            .into());
+        }
+
+        let mut inputs: Vec<UTXOInput> = Vec::new();
+        for (index, input) in value.inputs.iter().enumerate() {
+            let utxo_input = match input {
+                InputDto::Utxo(input) => {
+                    UTXOInput::try_from(input).map_err(|e| anyhow::anyhow!("invalid input {}: {}", index, e))?
+                }
+                InputDto::OutputId(output_id) => UTXOInput::from_str(output_id)
+                    .map_err(|_| anyhow::anyhow!("invalid input {}: `{}`", index, output_id))?,
+                InputDto::Treasury(_) => {
+                    return Err(anyhow::anyhow!(
+                        "input {} is a treasury input, which can't be used in client-submitted transactions",
+                        index
+                    )
+                    .into())
+                }
+            };
+            if inputs.contains(&utxo_input) {
+                return Err(anyhow::anyhow!("duplicate input {}: `{}`", index, utxo_input.output_id()).into());
+            }
+            inputs.push(utxo_input);
+        }
+        let inputs = inputs
             .into_iter()
             .map(|input| {
-                UTXOInput::from_str(&input)
-                    .unwrap_or_else(|_| panic!("invalid input: {}", input))
-                    .into()
+                let input: Input = input.into();
+                Ok((packed(&input)?, input))
             })
-            .collect();
-        for input in inputs {
+            .collect::<crate::Result<Vec<(Vec<u8>, Input)>>>()?;
+        let mut input_order = Vec::new();
+        for (index, input) in sort_packed(inputs) {
+            input_order.push(index);
             builder = builder.add_input(input);
         }
 
-        let outputs: Vec<Output> = value
-            .outputs
-            .into_vec()
-            .into_iter()
-            .map(|output| {
-                SignatureLockedSingleOutput::new(
-                    super::parse_address(output.address.clone())
-                        .unwrap_or_else(|_| panic!("invalid output address: {}", output.address)),
+        // an address can get one output of each type
+        let mut addresses: Vec<(Address, u64)> = Vec::new();
+        let mut outputs: Vec<(Vec<u8>, Output)> = Vec::new();
+        let mut total_amount: u64 = 0;
+        for (index, output) in value.outputs.iter().enumerate() {
+            let (output_address, amount, output_type) = match output {
+                OutputDto::SignatureLockedSingle(output) => {
+                    (&output.address, output.amount, SIGNATURE_LOCKED_SINGLE_OUTPUT_TYPE)
+                }
+                OutputDto::SignatureLockedDustAllowance(output) => (
+                    &output.address,
                     output.amount,
+                    SIGNATURE_LOCKED_DUST_ALLOWANCE_OUTPUT_TYPE,
+                ),
+                OutputDto::Treasury(_) => {
+                    return Err(anyhow::anyhow!(
+                        "output {} is a treasury output, which can't be used in client-submitted transactions",
+                        index
+                    )
+                    .into())
+                }
+            };
+            let address = crate::address::parse_address(output_address, None)
+                .map_err(|e| anyhow::anyhow!("invalid output {} address: `{}`: {}", index, output_address, e))?
+                .address();
+            if addresses.contains(&(address.clone(), output_type)) {
+                return Err(anyhow::anyhow!("duplicate output {} address: `{}`", index, output_address).into());
+            }
+            if amount == 0 {
+                return Err(anyhow::anyhow!("invalid output {} amount: must be at least 1", index).into());
+            }
+            if output_type == SIGNATURE_LOCKED_DUST_ALLOWANCE_OUTPUT_TYPE && amount < DUST_THRESHOLD {
+                return Err(anyhow::anyhow!(
+                    "invalid output {} amount: a dust allowance output must be at least {}",
+                    index,
+                    DUST_THRESHOLD
                 )
-                .unwrap()
-                .into()
-            })
-            .collect();
-        for output in outputs {
+                .into());
+            }
+            total_amount = total_amount
+                .checked_add(amount)
+                .filter(|total| *total <= IOTA_SUPPLY)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "invalid output {} amount: the outputs total exceeds the supply of {}",
+                        index,
+                        IOTA_SUPPLY
+                    )
+                })?;
+            addresses.push((address.clone(), output_type));
+            let output: Output = match output_type {
+                SIGNATURE_LOCKED_SINGLE_OUTPUT_TYPE => SignatureLockedSingleOutput::new(address, amount)?.into(),
+                _ => SignatureLockedDustAllowanceOutput::new(address, amount)?.into(),
+            };
+            outputs.push((packed(&output)?, output));
+        }
+        let mut output_order = Vec::new();
+        for (index, output) in sort_packed(outputs) {
+            output_order.push(index);
             builder = builder.add_output(output);
         }
 
         builder = match value.payload {
-            Some(indexation) => builder.with_payload(
-                (*indexation)
-                    .try_into()
-                    .expect("Invalid indexation in TransactionPayloadEssenceJson"),
-            ),
+            Some(indexation) => builder.with_payload((*indexation).try_into()?),
             _ => builder,
         };
 
-        Ok(builder.finish()?)
+        Ok(Self {
+            essence: builder.finish()?,
+            input_order,
+            output_order,
+        })
     }
 }
 
+/// Decodes a hex encoded field, with an optional `0x` prefix.
+fn decode_hex(field: &str, value: &str) -> crate::Result<Vec<u8>> {
+    let value = value.strip_prefix("0x").unwrap_or(value);
+    Ok(hex::decode(value).map_err(|e| anyhow::anyhow!("invalid `{}` hex: {}", field, e))?)
+}
+
+/// Decodes a hex encoded field, with an optional `0x` prefix, and checks its byte length.
+fn decode_hex_field(field: &str, value: &str, expected_length: usize) -> crate::Result<Vec<u8>> {
+    let bytes = decode_hex(field, value)?;
+    if bytes.len() != expected_length {
+        return Err(anyhow::anyhow!(
+            "invalid `{}` length: expected {} bytes, found {}",
+            field,
+            expected_length,
+            bytes.len()
+        )
+        .into());
+    }
+    Ok(bytes)
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct MessageSignatureUnlockDto {
     #[serde(rename = "publicKey")]
@@ -83,38 +551,83 @@ pub struct MessageSignatureUnlockDto {
     signature: String,
 }
 
+impl TryFrom<&SignatureUnlock> for MessageSignatureUnlockDto {
+    type Error = crate::Error;
+
+    fn try_from(signature: &SignatureUnlock) -> crate::Result<Self> {
+        match signature {
+            SignatureUnlock::Ed25519(signature) => Ok(Self {
+                public_key: hex::encode(signature.public_key()),
+                signature: hex::encode(signature.signature()),
+            }),
+            _ => Err(anyhow::anyhow!("unsupported signature type").into()),
+        }
+    }
+}
+
+/// Decodes the hex encoded `publicKey` and `signature` of an Ed25519 signature and checks their lengths.
+pub(crate) fn decode_ed25519_signature(public_key: &str, signature: &str) -> crate::Result<([u8; 32], Vec<u8>)> {
+    let public_key = decode_hex_field("publicKey", public_key, ED25519_PUBLIC_KEY_LENGTH)?;
+    let public_key = public_key[..].try_into().expect("public key length already validated");
+    let signature = decode_hex_field("signature", signature, ED25519_SIGNATURE_LENGTH)?;
+    Ok((public_key, signature))
+}
+
 impl TryFrom<MessageSignatureUnlockDto> for SignatureUnlock {
     type Error = crate::Error;
 
     fn try_from(value: MessageSignatureUnlockDto) -> crate::Result<Self> {
-        let mut public_key = [0u8; 32];
-        hex::decode_to_slice(value.public_key, &mut public_key)?;
-        let signature = hex::decode(value.signature)?.into_boxed_slice();
-        Ok(Ed25519Signature::new(public_key, signature).into())
+        let (public_key, signature) = decode_ed25519_signature(&value.public_key, &value.signature)?;
+        Ok(Ed25519Signature::new(public_key, signature.into_boxed_slice()).into())
     }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct MessageUnlockBlockJsonDto {
+    #[serde(skip_serializing_if = "Option::is_none")]
     signature: Option<MessageSignatureUnlockDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     reference: Option<u16>,
 }
 
+impl TryFrom<&UnlockBlock> for MessageUnlockBlockJsonDto {
+    type Error = crate::Error;
+
+    fn try_from(unlock_block: &UnlockBlock) -> crate::Result<Self> {
+        match unlock_block {
+            UnlockBlock::Signature(signature) => Ok(Self {
+                signature: Some(signature.try_into()?),
+                reference: None,
+            }),
+            UnlockBlock::Reference(reference) => Ok(Self {
+                signature: None,
+                reference: Some(reference.index()),
+            }),
+            _ => Err(anyhow::anyhow!("unsupported unlock block type").into()),
+        }
+    }
+}
+
 impl TryFrom<MessageUnlockBlockJsonDto> for UnlockBlock {
     type Error = crate::Error;
 
     fn try_from(value: MessageUnlockBlockJsonDto) -> crate::Result<Self> {
-        let type_ = if value.signature.is_some() { 0 } else { 1 };
-        match type_ {
-            0 => {
-                let sig: SignatureUnlock = value.signature.expect("Must contain signature.").try_into()?;
+        match (value.signature, value.reference) {
+            (Some(signature), None) => {
+                let sig: SignatureUnlock = signature.try_into()?;
                 Ok(sig.into())
             }
-            1 => {
-                let reference: ReferenceUnlock = value.reference.expect("Must contain reference.").try_into()?;
+            (None, Some(reference)) => {
+                let reference: ReferenceUnlock = reference.try_into()?;
                 Ok(reference.into())
             }
-            _ => unreachable!(),
+            (Some(_), Some(_)) => {
+                Err(anyhow::anyhow!("unlock block must contain either `signature` or `reference`, found both").into())
+            }
+            (None, None) => Err(anyhow::anyhow!(
+                "unlock block must contain either `signature` or `reference`, found neither"
+            )
+            .into()),
         }
     }
 }
@@ -128,24 +641,530 @@ pub struct MessageTransactionPayloadDto {
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct MessageIndexationPayloadDto {
-    index: String,
+    /// The index, as UTF-8 bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index: Option<String>,
+    /// The index, hex encoded.
+    #[serde(rename = "indexHex", skip_serializing_if = "Option::is_none")]
+    index_hex: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_data")]
     data: Vec<u8>,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-#[serde(untagged)]
+impl From<&IndexationPayload> for MessageIndexationPayloadDto {
+    fn from(indexation: &IndexationPayload) -> Self {
+        Self {
+            index: Some(indexation.index().to_string()),
+            index_hex: None,
+            data: indexation.data().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<MessageIndexationPayloadDto> for IndexationPayload {
+    type Error = crate::Error;
+
+    fn try_from(value: MessageIndexationPayloadDto) -> crate::Result<Self> {
+        let index = match (value.index, value.index_hex) {
+            (Some(index), None) => index,
+            (None, Some(index_hex)) => String::from_utf8(decode_hex("indexHex", &index_hex)?)
+                .map_err(|_| anyhow::anyhow!("invalid `indexHex`: the decoded index must be valid UTF-8"))?,
+            (Some(_), Some(_)) => {
+                return Err(
+                    anyhow::anyhow!("indexation payload must contain either `index` or `indexHex`, found both").into(),
+                )
+            }
+            (None, None) => {
+                return Err(anyhow::anyhow!(
+                    "indexation payload must contain either `index` or `indexHex`, found neither"
+                )
+                .into())
+            }
+        };
+        if !INDEXATION_INDEX_LENGTH_RANGE.contains(&index.len()) {
+            return Err(anyhow::anyhow!(
+                "invalid indexation index length {} bytes, expected between {} and {}",
+                index.len(),
+                INDEXATION_INDEX_LENGTH_RANGE.start(),
+                INDEXATION_INDEX_LENGTH_RANGE.end()
+            )
+            .into());
+        }
+        if value.data.len() > MESSAGE_LENGTH_MAX {
+            return Err(anyhow::anyhow!(
+                "invalid indexation data length {} bytes, exceeds the maximum message length of {} bytes",
+                value.data.len(),
+                MESSAGE_LENGTH_MAX
+            )
+            .into());
+        }
+        Ok(IndexationPayload::new(index, &value.data)?)
+    }
+}
+
+/// The milestone payload. Milestones are only issued by the coordinator, so it's never submitted.
+#[derive(Clone, Serialize)]
+pub struct MessageMilestonePayloadDto {
+    index: u32,
+    timestamp: u64,
+    #[serde(rename = "inclusionMerkleProof")]
+    inclusion_merkle_proof: String,
+    signatures: Vec<String>,
+}
+
+/// The payload of a message, tagged by its `type` field as in the node REST API.
+/// The untagged form (without `type`) is still accepted but deprecated.
+#[derive(Clone)]
 pub enum MessagePayloadDto {
     /// The transaction payload.
     Transaction(MessageTransactionPayloadDto),
+    /// The milestone payload, only returned by the node.
+    Milestone(MessageMilestonePayloadDto),
     /// The indexation payload.
     Indexation(MessageIndexationPayloadDto),
 }
 
+impl<'de> Deserialize<'de> for MessagePayloadDto {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let type_ = match dto_type(&value, "payload")? {
+            Some(type_) => type_,
+            // legacy untagged form
+            None => match (
+                value.get("essence").is_some(),
+                value.get("index").is_some() || value.get("indexHex").is_some(),
+            ) {
+                (true, false) => TRANSACTION_PAYLOAD_TYPE,
+                (false, true) => INDEXATION_PAYLOAD_TYPE,
+                (true, true) => {
+                    return Err(D::Error::custom(
+                        "ambiguous payload with both `essence` and `index` fields, specify its `type`",
+                    ))
+                }
+                (false, false) => return Err(D::Error::custom("missing field `type` on payload")),
+            },
+        };
+        match type_ {
+            TRANSACTION_PAYLOAD_TYPE => serde_json::from_value(value)
+                .map(Self::Transaction)
+                .map_err(|e| D::Error::custom(format!("invalid transaction payload (type 0): {}", e))),
+            MILESTONE_PAYLOAD_TYPE => Err(D::Error::custom("milestone payloads (type 1) can't be submitted")),
+            INDEXATION_PAYLOAD_TYPE => serde_json::from_value(value)
+                .map(Self::Indexation)
+                .map_err(|e| D::Error::custom(format!("invalid indexation payload (type 2): {}", e))),
+            _ => Err(D::Error::custom(format!("unknown payload type `{}`", type_))),
+        }
+    }
+}
+
+impl Serialize for MessagePayloadDto {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::Transaction(payload) => TaggedDto {
+                type_: TRANSACTION_PAYLOAD_TYPE,
+                dto: payload,
+            }
+            .serialize(serializer),
+            Self::Milestone(payload) => TaggedDto {
+                type_: MILESTONE_PAYLOAD_TYPE,
+                dto: payload,
+            }
+            .serialize(serializer),
+            Self::Indexation(payload) => TaggedDto {
+                type_: INDEXATION_PAYLOAD_TYPE,
+                dto: payload,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+impl MessagePayloadDto {
+    /// Converts a message payload, encoding the output addresses with the given bech32 HRP.
+    pub(super) fn new(payload: &Payload, bech32_hrp: &str) -> crate::Result<Self> {
+        match payload {
+            Payload::Transaction(transaction) => Ok(Self::Transaction(MessageTransactionPayloadDto {
+                essence: MessageTransactionPayloadEssenceDto::new(transaction.essence(), bech32_hrp)?,
+                unlock_blocks: transaction
+                    .unlock_blocks()
+                    .iter()
+                    .map(MessageUnlockBlockJsonDto::try_from)
+                    .collect::<crate::Result<Box<[MessageUnlockBlockJsonDto]>>>()?,
+            })),
+            Payload::Milestone(milestone) => Ok(Self::Milestone(MessageMilestonePayloadDto {
+                index: milestone.essence().index(),
+                timestamp: milestone.essence().timestamp(),
+                inclusion_merkle_proof: hex::encode(milestone.essence().merkle_proof()),
+                signatures: milestone.signatures().iter().map(hex::encode).collect(),
+            })),
+            Payload::Indexation(indexation) => Ok(Self::Indexation(indexation.as_ref().into())),
+            _ => Err(anyhow::anyhow!("unsupported payload type").into()),
+        }
+    }
+
+    /// Checks that the bech32 output addresses use the HRP of the client's network.
+    pub(super) fn check_bech32_hrp(&self, bech32_hrp: &str) -> crate::Result<()> {
+        if let MessagePayloadDto::Transaction(transaction_payload) = self {
+            for (index, output) in transaction_payload.essence.outputs.iter().enumerate() {
+                let address = match output {
+                    OutputDto::SignatureLockedSingle(output) => &output.address,
+                    OutputDto::SignatureLockedDustAllowance(output) => &output.address,
+                    OutputDto::Treasury(_) => continue,
+                };
+                // other address errors are reported by the essence conversion
+                if let Err(e @ AddressError::InvalidHrp { .. }) =
+                    crate::address::parse_address(address, Some(bech32_hrp))
+                {
+                    return Err(anyhow::anyhow!("invalid output {} address: `{}`: {}", index, address, e).into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct MessageDto {
+    #[serde(alias = "parent1MessageId")]
     pub parent1: Option<String>,
+    #[serde(alias = "parent2MessageId")]
     pub parent2: Option<String>,
     pub payload: MessagePayloadDto,
+    /// The network id, required to pack a message offline, a posted message must use the one of the client network.
+    #[serde(
+        rename = "networkId",
+        default,
+        deserialize_with = "deserialize_optional_u64",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub network_id: Option<u64>,
+    /// The proof of work nonce, only used to pack a message offline.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_u64",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub nonce: Option<u64>,
+}
+
+/// The signing data of a prepared transaction input: its address with the derivation indexes.
+#[derive(Serialize, Deserialize)]
+pub struct InputSigningDataDto {
+    #[serde(rename = "outputId")]
+    output_id: String,
+    address: String,
+    #[serde(rename = "addressIndex")]
+    address_index: usize,
+    internal: bool,
+}
+
+/// The remainder output of a transaction: its index in the essence outputs, with the derivation indexes of its
+/// address if the address belongs to the account.
+#[derive(Serialize, Deserialize)]
+pub struct RemainderDto {
+    address: String,
+    amount: u64,
+    #[serde(rename = "outputIndex")]
+    output_index: usize,
+    #[serde(rename = "addressIndex", default, skip_serializing_if = "Option::is_none")]
+    address_index: Option<usize>,
+    internal: bool,
+}
+
+impl RemainderDto {
+    pub(super) fn new(remainder: &RemainderData, bech32_hrp: &str) -> crate::Result<Self> {
+        Ok(Self {
+            address: crate::address::address_to_bech32(&remainder.address, bech32_hrp)?,
+            amount: remainder.amount,
+            output_index: remainder.output_index,
+            address_index: remainder.address_index,
+            internal: remainder.internal,
+        })
+    }
+}
+
+impl TryFrom<RemainderDto> for RemainderData {
+    type Error = crate::Error;
+    fn try_from(value: RemainderDto) -> crate::Result<Self> {
+        Ok(Self {
+            address: crate::address::parse_address(&value.address, None)
+                .map_err(|e| anyhow::anyhow!("invalid remainder address: `{}`: {}", value.address, e))?
+                .address(),
+            amount: value.amount,
+            output_index: value.output_index,
+            address_index: value.address_index,
+            internal: value.internal,
+        })
+    }
+}
+
+/// A transaction prepared by the online client, to be signed offline.
+/// Every field is plain JSON so it can be moved to the signing machine.
+#[derive(Serialize, Deserialize)]
+pub struct PreparedTransactionDataDto {
+    essence: MessageTransactionPayloadEssenceDto,
+    #[serde(rename = "accountIndex")]
+    account_index: usize,
+    inputs: Vec<InputSigningDataDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    remainder: Option<RemainderDto>,
+}
+
+impl PreparedTransactionDataDto {
+    /// Converts a prepared transaction, encoding the addresses with the given bech32 HRP.
+    pub(super) fn new(prepared: &PreparedTransactionData, bech32_hrp: &str) -> crate::Result<Self> {
+        Ok(Self {
+            essence: MessageTransactionPayloadEssenceDto::new(&prepared.essence, bech32_hrp)?,
+            account_index: prepared.account_index,
+            inputs: prepared
+                .inputs
+                .iter()
+                .map(|input| {
+                    Ok(InputSigningDataDto {
+                        output_id: input.input.output_id().to_string(),
+                        address: crate::address::address_to_bech32(&input.address, bech32_hrp)?,
+                        address_index: input.address_index,
+                        internal: input.internal,
+                    })
+                })
+                .collect::<crate::Result<Vec<InputSigningDataDto>>>()?,
+            remainder: prepared
+                .remainder
+                .as_ref()
+                .map(|remainder| RemainderDto::new(remainder, bech32_hrp))
+                .transpose()?,
+        })
+    }
+
+    /// The bech32 HRP of the input addresses, used to encode the signed payload.
+    pub(super) fn bech32_hrp(&self) -> Option<String> {
+        self.inputs
+            .iter()
+            .find_map(|input| crate::address::parse_address(&input.address, None).ok()?.hrp)
+    }
+}
+
+impl TryFrom<PreparedTransactionDataDto> for PreparedTransactionData {
+    type Error = crate::Error;
+    fn try_from(value: PreparedTransactionDataDto) -> crate::Result<Self> {
+        let inputs = value
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(index, input)| {
+                Ok(InputSigningData {
+                    input: UTXOInput::from_str(&input.output_id)
+                        .map_err(|_| anyhow::anyhow!("invalid input {} output id: `{}`", index, input.output_id))?,
+                    address: crate::address::parse_address(&input.address, None)
+                        .map_err(|e| anyhow::anyhow!("invalid input {} address: `{}`: {}", index, input.address, e))?
+                        .address(),
+                    address_index: input.address_index,
+                    internal: input.internal,
+                })
+            })
+            .collect::<crate::Result<Vec<InputSigningData>>>()?;
+        let sorted = SortedEssence::try_from(value.essence)?;
+        let mut remainder: Option<RemainderData> = value.remainder.map(TryInto::try_into).transpose()?;
+        // the remainder output moves along with the sorted outputs
+        if let Some(remainder) = &mut remainder {
+            remainder.output_index = sorted
+                .output_order
+                .iter()
+                .position(|dto_index| *dto_index == remainder.output_index)
+                .ok_or_else(|| anyhow::anyhow!("invalid remainder output index {}", remainder.output_index))?;
+        }
+        Ok(Self {
+            essence: sorted.essence,
+            account_index: value.account_index,
+            inputs,
+            remainder,
+        })
+    }
+}
+
+/// A dust output of a transaction plan.
+#[derive(Serialize, Deserialize)]
+pub struct DustWarningDto {
+    address: String,
+    amount: u64,
+    remainder: bool,
+}
+
+/// The plan of a transfer built by `MessageSender.dryRun`, submitted as is by `MessageSender.submitPlan`.
+#[derive(Serialize, Deserialize)]
+pub struct TransactionPlanDto {
+    prepared: PreparedTransactionDataDto,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    payload: Option<MessagePayloadDto>,
+    #[serde(rename = "messageLength")]
+    message_length: usize,
+    #[serde(rename = "dustWarnings")]
+    dust_warnings: Vec<DustWarningDto>,
+}
+
+impl TransactionPlanDto {
+    /// Converts a transaction plan, encoding the addresses with the given bech32 HRP.
+    pub(super) fn new(plan: &TransactionPlan, bech32_hrp: &str) -> crate::Result<Self> {
+        Ok(Self {
+            prepared: PreparedTransactionDataDto::new(&plan.prepared, bech32_hrp)?,
+            payload: plan
+                .payload
+                .as_ref()
+                .map(|payload| MessagePayloadDto::new(&Payload::Transaction(Box::new(payload.clone())), bech32_hrp))
+                .transpose()?,
+            message_length: plan.message_length,
+            dust_warnings: plan
+                .dust_warnings
+                .iter()
+                .map(|warning| DustWarningDto {
+                    address: warning.address.to_string(),
+                    amount: warning.amount,
+                    remainder: warning.remainder,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl TryFrom<TransactionPlanDto> for TransactionPlan {
+    type Error = crate::Error;
+    fn try_from(value: TransactionPlanDto) -> crate::Result<Self> {
+        let payload = match value.payload.map(TryInto::try_into).transpose()? {
+            Some(Payload::Transaction(payload)) => Some(*payload),
+            Some(_) => {
+                return Err(anyhow::anyhow!("invalid plan payload: expected a transaction payload (type 0)").into())
+            }
+            None => None,
+        };
+        Ok(Self {
+            prepared: value.prepared.try_into()?,
+            payload,
+            message_length: value.message_length,
+            dust_warnings: value
+                .dust_warnings
+                .into_iter()
+                .map(|warning| DustWarning {
+                    address: warning.address.into(),
+                    amount: warning.amount,
+                    remainder: warning.remainder,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Checks that the message packs to at most [`MESSAGE_LENGTH_MAX`] bytes, including the network id, parents,
+/// payload framing and nonce. Call it before the proof of work, which is wasted on a message the node rejects.
+pub(super) fn check_message_length(
+    network_id: u64,
+    parent1: MessageId,
+    parent2: MessageId,
+    payload: &Payload,
+) -> crate::Result<()> {
+    // the nonce has a fixed length, so a zero nonce gives the length of the final message
+    let message = MessageBuilder::<Constant>::new()
+        .with_network_id(network_id)
+        .with_parent1(parent1)
+        .with_parent2(parent2)
+        .with_payload(payload.clone())
+        .with_nonce_provider(ConstantBuilder::new().with_value(0).finish(), 4000f64)
+        .finish()?;
+    let length = message.packed_len();
+    if length > MESSAGE_LENGTH_MAX {
+        return Err(anyhow::anyhow!(
+            "invalid message length {} bytes, exceeds the maximum message length of {} bytes",
+            length,
+            MESSAGE_LENGTH_MAX
+        )
+        .into());
+    }
+    Ok(())
+}
+
+impl TryFrom<MessageTransactionPayloadDto> for TransactionPayload {
+    type Error = crate::Error;
+
+    fn try_from(value: MessageTransactionPayloadDto) -> crate::Result<Self> {
+        let sorted = SortedEssence::try_from(value.essence)?;
+        if value.unlock_blocks.len() != sorted.input_order.len() {
+            return Err(anyhow::anyhow!(
+                "{} unlock blocks for {} inputs, expected one unlock block per input",
+                value.unlock_blocks.len(),
+                sorted.input_order.len()
+            )
+            .into());
+        }
+
+        // the signature unlocking each input of the DTO
+        let mut signatures: Vec<Ed25519Signature> = Vec::new();
+        let unlock_blocks = value.unlock_blocks.into_vec();
+        for (index, unlock_block) in unlock_blocks.into_iter().enumerate() {
+            let reference = unlock_block.reference;
+            let unlock_block: UnlockBlock = unlock_block.try_into()?;
+            // a reference unlock block can only point to a previous unlock block
+            if let Some(reference) = reference {
+                let reference = reference as usize;
+                if reference >= INPUT_OUTPUT_COUNT_MAX {
+                    return Err(anyhow::anyhow!(
+                        "unlock block {} references unlock block {}, which exceeds the maximum of {} inputs",
+                        index,
+                        reference,
+                        INPUT_OUTPUT_COUNT_MAX
+                    )
+                    .into());
+                }
+                if reference >= index {
+                    return Err(anyhow::anyhow!(
+                        "unlock block {} references unlock block {}, which isn't before it",
+                        index,
+                        reference
+                    )
+                    .into());
+                }
+            }
+            let signature = match unlock_block {
+                UnlockBlock::Signature(SignatureUnlock::Ed25519(signature)) => signature,
+                UnlockBlock::Reference(reference) => signatures[reference.index() as usize].clone(),
+                _ => return Err(anyhow::anyhow!("unlock block {} isn't an Ed25519 signature", index).into()),
+            };
+            signatures.push(signature);
+        }
+
+        // the signatures sign the packed essence, which changes with the order of its inputs and outputs
+        if !sorted.kept_order() {
+            let essence = packed(&sorted.essence)?;
+            for (index, signature) in signatures.iter().enumerate() {
+                if !verify_ed25519_signature(signature.public_key(), signature.signature(), &essence)? {
+                    return Err(anyhow::anyhow!(
+                        "unlock block {} doesn't sign the essence with its inputs and outputs in the protocol order, \
+                         sign the sorted essence",
+                        index
+                    )
+                    .into());
+                }
+            }
+        }
+
+        // the unlock blocks follow the sorted inputs, an address that already signed is unlocked with a reference
+        let mut transaction = TransactionPayload::builder().with_essence(sorted.essence);
+        let mut signature_blocks: Vec<(&[u8; 32], usize)> = Vec::new();
+        for (block_index, dto_index) in sorted.input_order.iter().enumerate() {
+            let signature = &signatures[*dto_index];
+            let unlock_block: UnlockBlock = match signature_blocks
+                .iter()
+                .find(|(public_key, _)| *public_key == signature.public_key())
+            {
+                Some((_, signature_block)) => ReferenceUnlock::new(*signature_block as u16)?.into(),
+                None => {
+                    signature_blocks.push((signature.public_key(), block_index));
+                    SignatureUnlock::Ed25519(signature.clone()).into()
+                }
+            };
+            transaction = transaction.add_unlock_block(unlock_block);
+        }
+
+        Ok(transaction.finish()?)
+    }
 }
 
 impl TryFrom<MessagePayloadDto> for Payload {
@@ -153,24 +1172,118 @@ impl TryFrom<MessagePayloadDto> for Payload {
     fn try_from(payload: MessagePayloadDto) -> crate::Result<Self> {
         match payload {
             MessagePayloadDto::Transaction(transaction_payload) => {
-                let mut transaction = TransactionPayload::builder();
-                transaction = transaction.with_essence(transaction_payload.essence.try_into()?);
-
-                let unlock_blocks = transaction_payload.unlock_blocks.into_vec();
-                for unlock_block in unlock_blocks {
-                    transaction = transaction.add_unlock_block(unlock_block.try_into()?);
-                }
-
-                Ok(Payload::Transaction(Box::new(transaction.finish()?)))
+                Ok(Payload::Transaction(Box::new(transaction_payload.try_into()?)))
             }
+            MessagePayloadDto::Milestone(_) => Err(anyhow::anyhow!("milestone payloads can't be submitted").into()),
             MessagePayloadDto::Indexation(indexation_payload) => {
-                let indexation = IndexationPayload::new(indexation_payload.index, &indexation_payload.data).unwrap();
+                let indexation: IndexationPayload = indexation_payload.try_into()?;
                 Ok(Payload::Indexation(Box::new(indexation)))
             }
         }
     }
 }
 
+/// The JSON shape of the messages returned to JS.
+#[derive(Clone, Copy)]
+pub(crate) enum MessageFormat {
+    /// The [`MessageDto`] shape, also used to submit messages.
+    Dto,
+    /// The node REST API shape.
+    Rest,
+}
+
+impl FromStr for MessageFormat {
+    type Err = crate::Error;
+
+    fn from_str(format: &str) -> crate::Result<Self> {
+        match format {
+            "dto" => Ok(Self::Dto),
+            "rest" => Ok(Self::Rest),
+            _ => Err(anyhow::anyhow!("unknown message format `{}`, expected `dto` or `rest`", format).into()),
+        }
+    }
+}
+
+/// Serializes a message in the given format, encoding the DTO output addresses with the given bech32 HRP.
+pub(crate) fn serialize_message(message: &Message, format: MessageFormat, bech32_hrp: &str) -> crate::Result<String> {
+    match format {
+        MessageFormat::Dto => Ok(serde_json::to_string(&MessageResponseDto::new(message, bech32_hrp)?).unwrap()),
+        MessageFormat::Rest => Ok(serde_json::to_string(&MessageJson::from(message)).unwrap()),
+    }
+}
+
+/// A message returned by the node, with its payload in the same shape as [`MessageDto`].
+#[derive(Serialize)]
+pub(super) struct MessageResponseDto {
+    /// Network ID, serialized as string since JS numbers can't hold every u64 value
+    #[serde(rename = "networkId")]
+    network_id: String,
+    parent1: String,
+    parent2: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload: Option<MessagePayloadDto>,
+    /// Nonce, serialized as string since JS numbers can't hold every u64 value
+    nonce: String,
+}
+
+impl MessageResponseDto {
+    pub(super) fn new(message: &Message, bech32_hrp: &str) -> crate::Result<Self> {
+        Ok(Self {
+            network_id: message.network_id().to_string(),
+            parent1: message.parent1().to_string(),
+            parent2: message.parent2().to_string(),
+            payload: match message.payload() {
+                Some(payload) => Some(MessagePayloadDto::new(payload, bech32_hrp)?),
+                None => None,
+            },
+            nonce: message.nonce().to_string(),
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct MessageMetadataDto {
+    /// Message ID
+    #[serde(rename = "messageId")]
+    message_id: String,
+    /// Message ID of parent1
+    #[serde(rename = "parent1MessageId")]
+    parent1: String,
+    /// Message ID of parent2
+    #[serde(rename = "parent2MessageId")]
+    parent2: String,
+    /// Solid status
+    #[serde(rename = "isSolid")]
+    is_solid: bool,
+    /// Should promote, null if the node omits it
+    #[serde(rename = "shouldPromote")]
+    should_promote: Option<bool>,
+    /// Should reattach, null if the node omits it
+    #[serde(rename = "shouldReattach")]
+    should_reattach: Option<bool>,
+    /// Referenced by milestone index, null while the message isn't referenced
+    #[serde(rename = "referencedByMilestoneIndex")]
+    referenced_by_milestone_index: Option<u64>,
+    /// Ledger inclusion state, null while the message isn't referenced
+    #[serde(rename = "ledgerInclusionState")]
+    ledger_inclusion_state: Option<LedgerInclusionState>,
+}
+
+impl From<MessageMetadata> for MessageMetadataDto {
+    fn from(value: MessageMetadata) -> Self {
+        Self {
+            message_id: value.message_id,
+            parent1: value.parent1,
+            parent2: value.parent2,
+            is_solid: value.is_solid,
+            should_promote: value.should_promote,
+            should_reattach: value.should_reattach,
+            referenced_by_milestone_index: value.referenced_by_milestone_index,
+            ledger_inclusion_state: value.ledger_inclusion_state,
+        }
+    }
+}
+
 #[derive(Serialize)]
 pub(super) struct OutputMetadataDto {
     /// Message ID of the output
@@ -185,36 +1298,190 @@ pub(super) struct OutputMetadataDto {
     /// Spend status of the output
     #[serde(rename = "isSpent")]
     is_spent: bool,
-    /// Corresponding address
+    /// Corresponding address, bech32 encoded with the network HRP
     address: String,
+    /// Corresponding address, hex encoded
+    #[serde(rename = "addressHex")]
+    address_hex: String,
     /// Balance amount
     amount: u64,
 }
 
-impl From<OutputMetadata> for OutputMetadataDto {
-    fn from(value: OutputMetadata) -> Self {
-        Self {
+impl OutputMetadataDto {
+    pub(super) fn new(value: OutputMetadata, bech32_hrp: &str) -> crate::Result<Self> {
+        Ok(Self {
             message_id: hex::encode(value.message_id),
             transaction_id: hex::encode(value.transaction_id),
             output_index: value.output_index,
             is_spent: value.is_spent,
-            address: value.address.to_bech32(),
+            address: crate::address::address_to_bech32(&value.address, bech32_hrp)?,
+            address_hex: crate::address::address_to_hex(&value.address)?,
             amount: value.amount,
-        }
+        })
     }
 }
 
+/// An output of a monitored address created or consumed.
 #[derive(Serialize)]
-pub(super) struct AddressBalanceDto {
+pub(super) struct AddressActivityDto {
+    #[serde(rename = "outputId")]
+    output_id: String,
+    output: OutputMetadataDto,
+    consumed: bool,
+    /// The milestone of an activity caught up, `null` for a live event
+    #[serde(rename = "milestoneIndex")]
+    milestone_index: Option<u64>,
+}
+
+impl AddressActivityDto {
+    pub(super) fn new(value: AddressActivity, bech32_hrp: &str) -> crate::Result<Self> {
+        Ok(Self {
+            output_id: value.output_id,
+            output: OutputMetadataDto::new(value.output, bech32_hrp)?,
+            consumed: value.consumed,
+            milestone_index: value.milestone_index,
+        })
+    }
+}
+
+/// The outputs of an address, with the result count to detect a truncated list.
+#[derive(Serialize)]
+pub(super) struct AddressOutputsDto {
+    #[serde(rename = "outputIds")]
+    output_ids: Box<[String]>,
+    count: usize,
+    #[serde(rename = "maxResults")]
+    max_results: usize,
+    /// Whether the node capped the number of returned outputs
+    truncated: bool,
+    /// The metadata of the outputs, if they were resolved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outputs: Option<Vec<OutputMetadataDto>>,
+}
+
+impl AddressOutputsDto {
+    pub(super) fn new(
+        value: AddressOutputs,
+        outputs: Option<Vec<OutputMetadata>>,
+        bech32_hrp: &str,
+    ) -> crate::Result<Self> {
+        let outputs = match outputs {
+            Some(outputs) => Some(
+                outputs
+                    .into_iter()
+                    .map(|output| OutputMetadataDto::new(output, bech32_hrp))
+                    .collect::<crate::Result<Vec<OutputMetadataDto>>>()?,
+            ),
+            None => None,
+        };
+        Ok(Self {
+            truncated: value.is_truncated(),
+            output_ids: value.output_ids,
+            count: value.count,
+            max_results: value.max_results,
+            outputs,
+        })
+    }
+}
+
+/// The balance reached by `waitForBalance`, with the outputs holding it.
+#[derive(Serialize)]
+pub(super) struct ReachedBalanceDto {
     address: String,
     balance: u64,
+    outputs: Vec<OutputMetadataDto>,
 }
 
-impl From<AddressBalancePair> for AddressBalanceDto {
-    fn from(value: AddressBalancePair) -> Self {
-        Self {
+impl ReachedBalanceDto {
+    pub(super) fn new(value: ReachedBalance, bech32_hrp: &str) -> crate::Result<Self> {
+        Ok(Self {
             address: value.address.to_string(),
             balance: value.balance,
-        }
+            outputs: value
+                .outputs
+                .into_iter()
+                .map(|output| OutputMetadataDto::new(output, bech32_hrp))
+                .collect::<crate::Result<Vec<OutputMetadataDto>>>()?,
+        })
+    }
+}
+
+/// A failed lookup of `findOutputs`.
+#[derive(Serialize)]
+pub(super) struct OutputLookupErrorDto {
+    /// The output id that couldn't be fetched
+    #[serde(rename = "outputId", skip_serializing_if = "Option::is_none")]
+    output_id: Option<String>,
+    /// The address whose outputs couldn't be fetched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    error: String,
+}
+
+/// The outputs found by `findOutputs`, along with the failed lookups.
+#[derive(Serialize)]
+pub(super) struct FoundOutputsDto {
+    outputs: Vec<OutputMetadataDto>,
+    errors: Vec<OutputLookupErrorDto>,
+}
+
+impl FoundOutputsDto {
+    pub(super) fn new(
+        outputs: Vec<OutputMetadata>,
+        errors: Vec<(OutputLookup, iota::client::Error)>,
+        bech32_hrp: &str,
+    ) -> crate::Result<Self> {
+        let outputs = outputs
+            .into_iter()
+            .map(|output| OutputMetadataDto::new(output, bech32_hrp))
+            .collect::<crate::Result<Vec<OutputMetadataDto>>>()?;
+        let errors = errors
+            .into_iter()
+            .map(|(lookup, error)| {
+                let (output_id, address) = match lookup {
+                    OutputLookup::Output(output) => (
+                        Some(format!(
+                            "{}{}",
+                            output.output_id().transaction_id(),
+                            hex::encode(output.output_id().index().to_le_bytes())
+                        )),
+                        None,
+                    ),
+                    OutputLookup::Address(address) => (None, Some(address.to_string())),
+                };
+                OutputLookupErrorDto {
+                    output_id,
+                    address,
+                    error: error.to_string(),
+                }
+            })
+            .collect();
+        Ok(Self { outputs, errors })
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct AddressBalanceDto {
+    /// Bech32 encoded address, with the network HRP
+    address: String,
+    /// Hex encoded Ed25519 address
+    #[serde(rename = "addressHex")]
+    address_hex: String,
+    /// Balance amount, serialized as string since JS numbers can't hold every u64 value
+    balance: String,
+    /// Whether the address accepts dust outputs
+    #[serde(rename = "dustAllowed")]
+    dust_allowed: bool,
+}
+
+impl AddressBalanceDto {
+    pub(super) fn new(value: AddressBalancePair, bech32_hrp: &str) -> crate::Result<Self> {
+        let address = crate::address::parse_address(&value.address.to_string(), None)?.address();
+        Ok(Self {
+            address: crate::address::address_to_bech32(&address, bech32_hrp)?,
+            address_hex: crate::address::address_to_hex(&address)?,
+            balance: value.balance.to_string(),
+            dust_allowed: value.dust_allowed,
+        })
     }
 }
@@ -6,7 +6,7 @@ use neon::prelude::*;
 
 use std::str::FromStr;
 
-use super::{Api, ClientTask};
+use super::{Api, ClientTask, MessageFormat};
 
 pub struct MessageGetter(String);
 
@@ -36,15 +36,23 @@ declare_types! {
 
         method data(mut cx) {
             let message_id = cx.argument::<JsString>(0)?.value();
-            let message_id = MessageId::from_str(message_id.as_str()).expect("invalid message id");
-            let cb = cx.argument::<JsFunction>(1)?;
+            let message_id = match MessageId::from_str(message_id.as_str()) {
+                Ok(message_id) => message_id,
+                Err(_) => return cx.throw_error(format!("invalid message id: {}", message_id)),
+            };
+            let format = cx.argument::<JsString>(1)?.value();
+            let format = match MessageFormat::from_str(&format) {
+                Ok(format) => format,
+                Err(e) => return cx.throw_error(e.to_string()),
+            };
+            let cb = cx.argument::<JsFunction>(2)?;
             {
                 let this = cx.this();
                 let guard = cx.lock();
                 let id = &this.borrow(&guard).0;
                 let client_task = ClientTask {
                     client_id: id.clone(),
-                    api: Api::GetMessage(message_id),
+                    api: Api::GetMessage(message_id, format),
                 };
                 client_task.schedule(cb);
             }
@@ -54,7 +62,10 @@ declare_types! {
 
         method raw(mut cx) {
             let message_id = cx.argument::<JsString>(0)?.value();
-            let message_id = MessageId::from_str(message_id.as_str()).expect("invalid message id");
+            let message_id = match MessageId::from_str(message_id.as_str()) {
+                Ok(message_id) => message_id,
+                Err(_) => return cx.throw_error(format!("invalid message id: {}", message_id)),
+            };
             let cb = cx.argument::<JsFunction>(1)?;
             {
                 let this = cx.this();
@@ -72,7 +83,10 @@ declare_types! {
 
         method children(mut cx) {
             let message_id = cx.argument::<JsString>(0)?.value();
-            let message_id = MessageId::from_str(message_id.as_str()).expect("invalid message id");
+            let message_id = match MessageId::from_str(message_id.as_str()) {
+                Ok(message_id) => message_id,
+                Err(_) => return cx.throw_error(format!("invalid message id: {}", message_id)),
+            };
             let cb = cx.argument::<JsFunction>(1)?;
             {
                 let this = cx.this();
@@ -90,7 +104,10 @@ declare_types! {
 
         method metadata(mut cx) {
             let message_id = cx.argument::<JsString>(0)?.value();
-            let message_id = MessageId::from_str(message_id.as_str()).expect("invalid message id");
+            let message_id = match MessageId::from_str(message_id.as_str()) {
+                Ok(message_id) => message_id,
+                Err(_) => return cx.throw_error(format!("invalid message id: {}", message_id)),
+            };
             let cb = cx.argument::<JsFunction>(1)?;
             {
                 let this = cx.this();
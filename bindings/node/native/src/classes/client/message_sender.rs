@@ -1,23 +1,40 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use iota::{Address, MessageId, Seed, TransactionId, UTXOInput};
+use iota::{
+    client::api::{AccountAddress, InputSelectionStrategy, RemainderStrategy, TransactionPlan, MESSAGE_PARENTS_RANGE},
+    Address, MessageId, TransactionId, UTXOInput,
+};
 use neon::prelude::*;
 
-use super::{parse_address, Api, ClientTask};
+use super::{
+    offline_signing::{parse_account_addresses, AccountAddressDto},
+    parse_address, Api, ClientTask, MessagePayloadDto, TransactionPlanDto, INDEXATION_INDEX_LENGTH_RANGE,
+    MESSAGE_LENGTH_MAX,
+};
 
-use std::str::FromStr;
+use std::{convert::TryInto, str::FromStr};
 
 pub struct MessageSender {
     client_id: String,
     index: Option<String>,
     data: Option<Vec<u8>>,
     parent: Option<MessageId>,
+    parents: Option<Vec<MessageId>>,
+    parents_check: bool,
     seed: Option<String>,
+    account_addresses: Option<Vec<AccountAddress>>,
     account_index: Option<usize>,
     initial_address_index: Option<usize>,
+    input_selection: Option<InputSelectionStrategy>,
     inputs: Vec<UTXOInput>,
+    include_dust_allowance: bool,
+    remainder_strategy: Option<RemainderStrategy>,
+    dust_remainder_to_first_output: bool,
     outputs: Vec<(Address, u64)>,
+    dust_allowance_outputs: Vec<(Address, u64)>,
+    dust_check: bool,
+    address_reuse_allowed: bool,
 }
 
 declare_types! {
@@ -29,19 +46,31 @@ declare_types! {
                 index: None,
                 data: None,
                 parent: None,
+                parents: None,
+                parents_check: true,
                 seed: None,
+                account_addresses: None,
                 account_index: None,
                 initial_address_index:None,
+                input_selection: None,
                 inputs: Vec::new(),
+                include_dust_allowance: false,
+                remainder_strategy: None,
+                dust_remainder_to_first_output: false,
                 outputs: Vec::new(),
+                dust_allowance_outputs: Vec::new(),
+                dust_check: true,
+                address_reuse_allowed: false,
             })
         }
 
         method seed(mut cx) {
             let seed = cx.argument::<JsString>(0)?.value();
 
-            // validate the seed
-            Seed::from_ed25519_bytes(&hex::decode(&seed).expect("invalid seed hex")).expect("invalid seed");
+            // validate the seed or the signer options
+            if let Err(e) = crate::signer::Signer::parse(&seed) {
+                return cx.throw_error(e.to_string());
+            }
 
             {
                 let mut this = cx.this();
@@ -53,8 +82,46 @@ declare_types! {
             Ok(cx.this().upcast())
         }
 
+        method accountAddresses(mut cx) {
+            let addresses = cx.argument::<JsString>(0)?.value();
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).client_id;
+                id.clone()
+            };
+            let addresses = serde_json::from_str::<Vec<AccountAddressDto>>(&addresses)
+                .map_err(|e| anyhow::anyhow!("invalid account addresses: {}", e).into())
+                .and_then(|addresses| parse_account_addresses(&client_id, &addresses));
+            let addresses = match addresses {
+                Ok(addresses) => addresses,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let account_addresses = &mut this.borrow_mut(&guard).account_addresses;
+                account_addresses.replace(addresses);
+            }
+
+            Ok(cx.this().upcast())
+        }
+
         method index(mut cx) {
+            // the index is sent hex-encoded by the JS wrapper, it must be UTF-8 like the indexes of `findMessages`
             let index = cx.argument::<JsString>(0)?.value();
+            let index = match hex::decode(index).ok().and_then(|index| String::from_utf8(index).ok()) {
+                Some(index) => index,
+                None => return cx.throw_error("invalid index: the index must be valid UTF-8"),
+            };
+            if !INDEXATION_INDEX_LENGTH_RANGE.contains(&index.len()) {
+                return cx.throw_error(format!(
+                    "invalid index length {} bytes, expected between {} and {}",
+                    index.len(),
+                    INDEXATION_INDEX_LENGTH_RANGE.start(),
+                    INDEXATION_INDEX_LENGTH_RANGE.end()
+                ));
+            }
             {
                 let mut this = cx.this();
                 let guard = cx.lock();
@@ -73,6 +140,13 @@ declare_types! {
                 let value: Handle<JsNumber> = value.downcast_or_throw(&mut cx)?;
                 data.push(value.value() as u8);
             }
+            if data.len() > MESSAGE_LENGTH_MAX {
+                return cx.throw_error(format!(
+                    "invalid data length {} bytes, exceeds the maximum message length of {} bytes",
+                    data.len(),
+                    MESSAGE_LENGTH_MAX
+                ));
+            }
 
             {
                 let mut this = cx.this();
@@ -97,8 +171,45 @@ declare_types! {
             Ok(cx.this().upcast())
         }
 
+        method parents(mut cx) {
+            let js_parents = cx.argument::<JsArray>(0)?;
+            let js_parents: Vec<Handle<JsValue>> = js_parents.to_vec(&mut cx)?;
+            let parents_check = !cx.argument::<JsBoolean>(1)?.value();
+            let mut parents = Vec::new();
+            for js_parent in js_parents {
+                let parent: Handle<JsString> = js_parent.downcast_or_throw(&mut cx)?;
+                let parent = parent.value();
+                let parent = match MessageId::from_str(&parent) {
+                    Ok(parent) => parent,
+                    Err(_) => return cx.throw_error(format!("invalid parent message id: {}", parent)),
+                };
+                if parents.contains(&parent) {
+                    return cx.throw_error(format!("duplicate parent message id: {}", parent));
+                }
+                parents.push(parent);
+            }
+            if !MESSAGE_PARENTS_RANGE.contains(&parents.len()) {
+                return cx.throw_error(format!(
+                    "invalid parents: {} message ids, expected between {} and {}",
+                    parents.len(),
+                    MESSAGE_PARENTS_RANGE.start(),
+                    MESSAGE_PARENTS_RANGE.end()
+                ));
+            }
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let mut sender = this.borrow_mut(&guard);
+                sender.parents.replace(parents);
+                sender.parents_check = parents_check;
+            }
+
+            Ok(cx.this().upcast())
+        }
+
         method accountIndex(mut cx) {
-            let account_index = cx.argument::<JsNumber>(0)?.value() as usize;
+            let account_index = cx.argument::<JsNumber>(0)?.value();
+            let account_index = super::derivation_index(&mut cx, account_index, "account index")?;
             {
                 let mut this = cx.this();
                 let guard = cx.lock();
@@ -110,7 +221,8 @@ declare_types! {
         }
 
         method initialAddressIndex(mut cx) {
-            let index = cx.argument::<JsNumber>(0)?.value() as usize;
+            let index = cx.argument::<JsNumber>(0)?.value();
+            let index = super::derivation_index(&mut cx, index, "initial address index")?;
             {
                 let mut this = cx.this();
                 let guard = cx.lock();
@@ -123,7 +235,16 @@ declare_types! {
 
         method output(mut cx) {
             let address = cx.argument::<JsString>(0)?.value();
-            let address = parse_address(address).expect("invalid address");
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).client_id;
+                id.clone()
+            };
+            let address = match parse_address(&client_id, &address) {
+                Ok(address) => address,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
             let value = cx.argument::<JsNumber>(1)?.value() as u64;
             {
                 let mut this = cx.this();
@@ -135,6 +256,120 @@ declare_types! {
             Ok(cx.this().upcast())
         }
 
+        method dustAllowanceOutput(mut cx) {
+            let address = cx.argument::<JsString>(0)?.value();
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).client_id;
+                id.clone()
+            };
+            let address = match parse_address(&client_id, &address) {
+                Ok(address) => address,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            let value = cx.argument::<JsNumber>(1)?.value() as u64;
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let dust_allowance_outputs = &mut this.borrow_mut(&guard).dust_allowance_outputs;
+                dust_allowance_outputs.push((address, value));
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method dustCheckDisabled(mut cx) {
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).dust_check = false;
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method addressReuseAllowed(mut cx) {
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).address_reuse_allowed = true;
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method inputSelection(mut cx) {
+            let strategy = cx.argument::<JsString>(0)?.value();
+            let strategy = match super::parse_input_selection_strategy(&strategy) {
+                Ok(strategy) => strategy,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).input_selection.replace(strategy);
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method dustAllowanceInputs(mut cx) {
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).include_dust_allowance = true;
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method remainderStrategy(mut cx) {
+            let strategy = cx.argument::<JsString>(0)?.value();
+            let strategy = match super::parse_remainder_strategy(&strategy) {
+                Ok(strategy) => strategy,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).remainder_strategy.replace(strategy);
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method remainderAddress(mut cx) {
+            let address = cx.argument::<JsString>(0)?.value();
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).client_id;
+                id.clone()
+            };
+            let strategy = match super::parse_remainder_address(&client_id, &address) {
+                Ok(strategy) => strategy,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).remainder_strategy.replace(strategy);
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method dustRemainderToFirstOutput(mut cx) {
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).dust_remainder_to_first_output = true;
+            }
+
+            Ok(cx.this().upcast())
+        }
+
          method input(mut cx) {
             let transaction_id = cx.argument::<JsString>(0)?.value();
             let transaction_id = TransactionId::from_str(&transaction_id).expect("invalid transaction id");
@@ -150,7 +385,9 @@ declare_types! {
         }
 
         method submit(mut cx) {
-            let cb = cx.argument::<JsFunction>(0)?;
+            // deprecated, resolves to the message id instead of `{ messageId, message }`
+            let message_id_only = cx.argument::<JsBoolean>(0)?.value();
+            let cb = cx.argument::<JsFunction>(1)?;
             {
                 let this = cx.this();
                 let guard = cx.lock();
@@ -158,14 +395,166 @@ declare_types! {
                 let client_task = ClientTask {
                     client_id: ref_.client_id.clone(),
                     api: Api::Send {
-                        seed: ref_.seed.as_ref().map(|seed| Seed::from_ed25519_bytes(&hex::decode(&seed).expect("invalid seed hex")).expect("invalid seed")),
+                        signer: ref_
+                            .seed
+                            .as_ref()
+                            .map(|seed| crate::signer::Signer::parse(seed).expect("invalid seed")),
                         index: ref_.index.clone(),
                         data: ref_.data.clone(),
                         parent: ref_.parent,
+                        parents: ref_.parents.clone(),
+                        parents_check: ref_.parents_check,
                         account_index: ref_.account_index,
                         initial_address_index: ref_.initial_address_index,
+                        input_selection: ref_.input_selection.clone(),
                         inputs: ref_.inputs.clone(),
+                        include_dust_allowance: ref_.include_dust_allowance,
+                        remainder_strategy: ref_.remainder_strategy.clone(),
+                        dust_remainder_to_first_output: ref_.dust_remainder_to_first_output,
                         outputs: ref_.outputs.clone(),
+                        dust_allowance_outputs: ref_.dust_allowance_outputs.clone(),
+                        dust_check: ref_.dust_check,
+                        address_reuse_allowed: ref_.address_reuse_allowed,
+                        with_message: !message_id_only,
+                    },
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method prepare(mut cx) {
+            let cb = cx.argument::<JsFunction>(0)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let ref_ = &(*this.borrow(&guard));
+                let client_task = ClientTask {
+                    client_id: ref_.client_id.clone(),
+                    api: Api::PrepareTransaction {
+                        signer: ref_
+                            .seed
+                            .as_ref()
+                            .map(|seed| crate::signer::Signer::parse(seed).expect("invalid seed")),
+                        account_addresses: ref_.account_addresses.clone(),
+                        input_candidates: None,
+                        account_index: ref_.account_index,
+                        initial_address_index: ref_.initial_address_index,
+                        input_selection: ref_.input_selection.clone(),
+                        inputs: ref_.inputs.clone(),
+                        include_dust_allowance: ref_.include_dust_allowance,
+                        remainder_strategy: ref_.remainder_strategy.clone(),
+                        dust_remainder_to_first_output: ref_.dust_remainder_to_first_output,
+                        outputs: ref_.outputs.clone(),
+                        dust_allowance_outputs: ref_.dust_allowance_outputs.clone(),
+                        dust_check: ref_.dust_check,
+                        address_reuse_allowed: ref_.address_reuse_allowed,
+                        index: ref_.index.clone(),
+                        data: ref_.data.clone(),
+                        dry_run: None,
+                    },
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method dryRun(mut cx) {
+            let sign = cx.argument::<JsBoolean>(0)?.value();
+            let cb = cx.argument::<JsFunction>(1)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let ref_ = &(*this.borrow(&guard));
+                let client_task = ClientTask {
+                    client_id: ref_.client_id.clone(),
+                    api: Api::PrepareTransaction {
+                        signer: ref_
+                            .seed
+                            .as_ref()
+                            .map(|seed| crate::signer::Signer::parse(seed).expect("invalid seed")),
+                        account_addresses: ref_.account_addresses.clone(),
+                        input_candidates: None,
+                        account_index: ref_.account_index,
+                        initial_address_index: ref_.initial_address_index,
+                        input_selection: ref_.input_selection.clone(),
+                        inputs: ref_.inputs.clone(),
+                        include_dust_allowance: ref_.include_dust_allowance,
+                        remainder_strategy: ref_.remainder_strategy.clone(),
+                        dust_remainder_to_first_output: ref_.dust_remainder_to_first_output,
+                        outputs: ref_.outputs.clone(),
+                        dust_allowance_outputs: ref_.dust_allowance_outputs.clone(),
+                        dust_check: ref_.dust_check,
+                        address_reuse_allowed: ref_.address_reuse_allowed,
+                        index: ref_.index.clone(),
+                        data: ref_.data.clone(),
+                        dry_run: Some(sign),
+                    },
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method submitSigned(mut cx) {
+            let payload = cx.argument::<JsString>(0)?.value();
+            let payload: MessagePayloadDto = match serde_json::from_str(&payload) {
+                Ok(payload) => payload,
+                Err(e) => return cx.throw_error(format!("invalid transaction payload: {}", e)),
+            };
+            if !matches!(payload, MessagePayloadDto::Transaction(_)) {
+                return cx.throw_error("invalid transaction payload: expected a transaction payload (type 0)");
+            }
+            let message_id_only = cx.argument::<JsBoolean>(1)?.value();
+            let cb = cx.argument::<JsFunction>(2)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let ref_ = &(*this.borrow(&guard));
+                let client_task = ClientTask {
+                    client_id: ref_.client_id.clone(),
+                    api: Api::SubmitSignedTransaction {
+                        payload,
+                        parent: ref_.parent,
+                        parents: ref_.parents.clone(),
+                        parents_check: ref_.parents_check,
+                        with_message: !message_id_only,
+                    },
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method submitPlan(mut cx) {
+            let plan = cx.argument::<JsString>(0)?.value();
+            let plan: crate::Result<TransactionPlan> = serde_json::from_str::<TransactionPlanDto>(&plan)
+                .map_err(|e| anyhow::anyhow!("invalid transaction plan: {}", e).into())
+                .and_then(TryInto::try_into);
+            let plan = match plan {
+                Ok(plan) => plan,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            let cb = cx.argument::<JsFunction>(1)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let ref_ = &(*this.borrow(&guard));
+                let client_task = ClientTask {
+                    client_id: ref_.client_id.clone(),
+                    api: Api::SubmitPlan {
+                        signer: ref_
+                            .seed
+                            .as_ref()
+                            .map(|seed| crate::signer::Signer::parse(seed).expect("invalid seed")),
+                        plan,
+                        parent: ref_.parent,
+                        parents: ref_.parents.clone(),
+                        parents_check: ref_.parents_check,
                     },
                 };
                 client_task.schedule(cb);
@@ -0,0 +1,260 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota::{
+    client::api::{validate_milestone, verify_inclusion_proof, CoordinatorKeys, InclusionProof},
+    common::packable::Packable,
+    compute_message_id, compute_transaction_id,
+    pow::providers::{Constant, ConstantBuilder, ProviderBuilder},
+    verify_ed25519_signature, Message, MessageBuilder, MessageId, MessageJson, Payload, TransactionPayload,
+};
+use neon::prelude::*;
+use serde::Serialize;
+
+use std::{
+    convert::{TryFrom, TryInto},
+    str::FromStr,
+};
+
+use super::{
+    check_message_length, decode_ed25519_signature, serialize_message, MessageDto, MessageFormat, MessagePayloadDto,
+    MessageResponseDto, MESSAGE_LENGTH_MAX,
+};
+
+/// A message unpacked from its bytes, with its computed identifier.
+#[derive(Serialize)]
+pub(super) struct UnpackedMessageDto {
+    #[serde(rename = "messageId")]
+    pub(super) message_id: MessageId,
+    #[serde(flatten)]
+    pub(super) message: MessageResponseDto,
+}
+
+/// Unpacks a message from its binary representation, as returned by the node `/raw` endpoint.
+/// Returns the message and its identifier.
+pub(crate) fn message_from_bytes(bytes: &[u8]) -> crate::Result<(MessageId, Message)> {
+    if bytes.len() > MESSAGE_LENGTH_MAX {
+        return Err(anyhow::anyhow!(
+            "invalid message bytes: length {} exceeds the maximum message length {}",
+            bytes.len(),
+            MESSAGE_LENGTH_MAX
+        )
+        .into());
+    }
+    let mut reader = bytes;
+    let message = Message::unpack(&mut reader)
+        .map_err(|e| anyhow::anyhow!("invalid message bytes (truncated or malformed): {:?}", e))?;
+    if !reader.is_empty() {
+        return Err(anyhow::anyhow!(
+            "invalid message bytes: {} trailing bytes after the message",
+            reader.len()
+        )
+        .into());
+    }
+    let (message_id, _) = message.id();
+    Ok((message_id, message))
+}
+
+/// Builds a message from its DTO without submitting it and packs it.
+/// Returns the message identifier and bytes.
+/// Every message field must be set since neither the network nor the proof of work are used;
+/// a nonce of `0` skips the proof of work.
+pub(crate) fn message_to_bytes(message: &MessageDto, bech32_hrp: &str) -> crate::Result<(MessageId, Vec<u8>)> {
+    fn required<'a, T>(value: &'a Option<T>, field: &str) -> crate::Result<&'a T> {
+        value
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("missing `{}`, it is required to pack a message offline", field).into())
+    }
+
+    message.payload.check_bech32_hrp(bech32_hrp)?;
+    let network_id = *required(&message.network_id, "networkId")?;
+    let parent1 = MessageId::from_str(required(&message.parent1, "parent1")?)?;
+    let parent2 = MessageId::from_str(required(&message.parent2, "parent2")?)?;
+    let payload: Payload = message.payload.clone().try_into()?;
+    check_message_length(network_id, parent1, parent2, &payload)?;
+    let message = MessageBuilder::<Constant>::new()
+        .with_network_id(network_id)
+        .with_parent1(parent1)
+        .with_parent2(parent2)
+        .with_payload(payload)
+        .with_nonce_provider(
+            ConstantBuilder::new()
+                .with_value(*required(&message.nonce, "nonce")?)
+                .finish(),
+            4000f64,
+        )
+        .finish()?;
+    let mut bytes = Vec::new();
+    message.pack(&mut bytes)?;
+    let (message_id, _) = message.id();
+    Ok((message_id, bytes))
+}
+
+/// JS `parseMessageJson(json, format, bech32Hrp)`: parses a message in the node REST API shape
+/// and serializes it in the given format.
+pub fn parse_message_json(mut cx: FunctionContext) -> JsResult<JsString> {
+    let json = cx.argument::<JsString>(0)?.value();
+    let format = cx.argument::<JsString>(1)?.value();
+    let bech32_hrp = cx.argument::<JsString>(2)?.value();
+    let result = crate::convert_panics(|| {
+        let format = MessageFormat::from_str(&format)?;
+        let message: MessageJson =
+            serde_json::from_str(&json).map_err(|e| anyhow::anyhow!("invalid message JSON: {}", e))?;
+        let message = Message::try_from(message)?;
+        serialize_message(&message, format, &bech32_hrp)
+    });
+    match result {
+        Ok(message) => Ok(cx.string(message)),
+        Err(e) => crate::error_code::throw_error(&mut cx, &e),
+    }
+}
+
+/// Reads the message bytes argument, a `Buffer` or a hex string with an optional `0x` prefix.
+fn bytes_argument(cx: &mut FunctionContext, index: i32) -> NeonResult<Vec<u8>> {
+    let bytes = cx.argument::<JsValue>(index)?;
+    if let Ok(buffer) = bytes.downcast::<JsBuffer>() {
+        Ok(cx.borrow(&buffer, |data| data.as_slice::<u8>().to_vec()))
+    } else if let Ok(bytes) = bytes.downcast::<JsString>() {
+        let bytes = bytes.value();
+        match hex::decode(bytes.strip_prefix("0x").unwrap_or(&bytes)) {
+            Ok(bytes) => Ok(bytes),
+            Err(e) => cx.throw_error(format!("invalid message bytes: invalid hex: {}", e)),
+        }
+    } else {
+        cx.throw_type_error("message bytes must be a Buffer or a hex string")
+    }
+}
+
+/// JS `messageFromBytes(bytes, bech32Hrp)`: unpacks a message from a `Buffer` or a hex string
+/// and serializes it in the [`MessageDto`](super::MessageDto) shape, with its `messageId`.
+pub fn message_from_bytes_js(mut cx: FunctionContext) -> JsResult<JsString> {
+    let bytes = bytes_argument(&mut cx, 0)?;
+    let bech32_hrp = cx.argument::<JsString>(1)?.value();
+    let result = crate::convert_panics(|| {
+        let (message_id, message) = message_from_bytes(&bytes)?;
+        let message = UnpackedMessageDto {
+            message_id,
+            message: MessageResponseDto::new(&message, &bech32_hrp)?,
+        };
+        Ok(serde_json::to_string(&message).unwrap())
+    });
+    match result {
+        Ok(message) => Ok(cx.string(message)),
+        Err(e) => crate::error_code::throw_error(&mut cx, &e),
+    }
+}
+
+/// JS `messageToBytes(messageDto, bech32Hrp)`: packs a message without submitting it,
+/// returning `{ bytes: Buffer, messageId: string }`.
+pub fn message_to_bytes_js(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let message = cx.argument::<JsString>(0)?.value();
+    let bech32_hrp = cx.argument::<JsString>(1)?.value();
+    let result = crate::convert_panics(|| {
+        let message: MessageDto =
+            serde_json::from_str(&message).map_err(|e| anyhow::anyhow!("invalid message: {}", e))?;
+        message_to_bytes(&message, &bech32_hrp)
+    });
+    let (message_id, bytes) = match result {
+        Ok(result) => result,
+        Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+    };
+    let mut buffer = JsBuffer::new(&mut cx, bytes.len() as u32)?;
+    cx.borrow_mut(&mut buffer, |data| data.as_mut_slice::<u8>().copy_from_slice(&bytes));
+    let message_id = cx.string(message_id.to_string());
+    let object = JsObject::new(&mut cx);
+    object.set(&mut cx, "bytes", buffer)?;
+    object.set(&mut cx, "messageId", message_id)?;
+    Ok(object)
+}
+
+/// JS `verifyEd25519Signature(publicKeyHex, signatureHex, messageBytes)`: verifies an Ed25519 signature of the bytes,
+/// with the length rules of the signature unlock blocks.
+pub fn verify_ed25519_signature_js(mut cx: FunctionContext) -> JsResult<JsBoolean> {
+    let public_key = cx.argument::<JsString>(0)?.value();
+    let signature = cx.argument::<JsString>(1)?.value();
+    let bytes = bytes_argument(&mut cx, 2)?;
+    let result = crate::convert_panics(|| {
+        let (public_key, signature) = decode_ed25519_signature(&public_key, &signature)?;
+        Ok(verify_ed25519_signature(&public_key, &signature, &bytes)?)
+    });
+    match result {
+        Ok(valid) => Ok(cx.boolean(valid)),
+        Err(e) => crate::error_code::throw_error(&mut cx, &e),
+    }
+}
+
+/// JS `computeMessageId(messageBytes)`: the BLAKE2b-256 hash of the packed message.
+pub fn compute_message_id_js(mut cx: FunctionContext) -> JsResult<JsString> {
+    let bytes = bytes_argument(&mut cx, 0)?;
+    Ok(cx.string(compute_message_id(&bytes).to_string()))
+}
+
+/// JS `computeTransactionId(transactionPayloadDto)`: packs the transaction payload and hashes it.
+pub fn compute_transaction_id_js(mut cx: FunctionContext) -> JsResult<JsString> {
+    let payload = cx.argument::<JsString>(0)?.value();
+    let result = crate::convert_panics(|| {
+        let payload: MessagePayloadDto =
+            serde_json::from_str(&payload).map_err(|e| anyhow::anyhow!("invalid transaction payload: {}", e))?;
+        let payload: TransactionPayload = match payload {
+            MessagePayloadDto::Transaction(payload) => payload.try_into()?,
+            _ => return Err(anyhow::anyhow!("invalid transaction payload: expected a payload of type 0").into()),
+        };
+        Ok(compute_transaction_id(&payload))
+    });
+    match result {
+        Ok(transaction_id) => Ok(cx.string(transaction_id.to_string())),
+        Err(e) => crate::error_code::throw_error(&mut cx, &e),
+    }
+}
+
+/// JS `verifyInclusionProof(proofJson, coordinatorKeysJson?)`: verifies an inclusion proof offline against the
+/// coordinator keys, the mainnet ones by default, throws locating the broken link of an invalid proof.
+pub fn verify_inclusion_proof_js(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let proof = cx.argument::<JsString>(0)?.value();
+    let keys = match cx.argument_opt(1) {
+        Some(arg) if arg.is_a::<JsString>() => Some(arg.downcast_or_throw::<JsString, _>(&mut cx)?.value()),
+        _ => None,
+    };
+    let result = crate::convert_panics(|| {
+        let proof: InclusionProof =
+            serde_json::from_str(&proof).map_err(|e| anyhow::anyhow!("invalid inclusion proof: {}", e))?;
+        let keys: CoordinatorKeys = match keys {
+            Some(keys) => {
+                serde_json::from_str(&keys).map_err(|e| anyhow::anyhow!("invalid coordinator keys: {}", e))?
+            }
+            None => CoordinatorKeys::mainnet(),
+        };
+        Ok(verify_inclusion_proof(&proof, &keys)?)
+    });
+    match result {
+        Ok(()) => Ok(cx.undefined()),
+        Err(e) => crate::error_code::throw_error(&mut cx, &e),
+    }
+}
+
+/// JS `validateMilestone(messageBytes, coordinatorKeysJson?)`: validates the milestone of a packed message offline
+/// against the coordinator keys, the mainnet ones by default, throws giving the reason of an invalid milestone.
+pub fn validate_milestone_js(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let bytes = bytes_argument(&mut cx, 0)?;
+    let keys = match cx.argument_opt(1) {
+        Some(arg) if arg.is_a::<JsString>() => Some(arg.downcast_or_throw::<JsString, _>(&mut cx)?.value()),
+        _ => None,
+    };
+    let result = crate::convert_panics(|| {
+        let keys: CoordinatorKeys = match keys {
+            Some(keys) => {
+                serde_json::from_str(&keys).map_err(|e| anyhow::anyhow!("invalid coordinator keys: {}", e))?
+            }
+            None => CoordinatorKeys::mainnet(),
+        };
+        let (_, message) = message_from_bytes(&bytes)?;
+        match message.payload() {
+            Some(Payload::Milestone(milestone)) => Ok(validate_milestone(milestone, &keys)?),
+            _ => Err(anyhow::anyhow!("invalid milestone message: expected a payload of type 1").into()),
+        }
+    });
+    match result {
+        Ok(()) => Ok(cx.undefined()),
+        Err(e) => crate::error_code::throw_error(&mut cx, &e),
+    }
+}
@@ -1,14 +1,18 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use bech32::FromBase32;
 use iota::{
-    message::prelude::{Address, Ed25519Address, MessageId, UTXOInput},
-    Seed,
+    client::{
+        api::{AddressActivity, InputSelectionStrategy, RemainderStrategy},
+        signer::slip10::HARDENED,
+        TopicEvent,
+    },
+    message::prelude::{Address, MessageId, TransactionId, UTXOInput},
 };
 use neon::prelude::*;
+use serde::Deserialize;
 
-use std::{convert::TryInto, str::FromStr};
+use std::{convert::TryInto, str::FromStr, time::Duration};
 
 mod builder;
 pub use builder::*;
@@ -16,7 +20,7 @@ pub use builder::*;
 mod dto;
 use dto::*;
 mod api;
-use api::{Api, ClientTask};
+use api::{Api, ClientTask, TransactionHistoryFormat};
 
 mod message_getter;
 pub use message_getter::JsMessageGetter;
@@ -30,49 +34,1050 @@ pub use unspent_address_getter::JsUnspentAddressGetter;
 mod address_finder;
 pub use address_finder::JsAddressFinder;
 
+mod address_getter;
+pub use address_getter::JsAddressGetter;
+
 mod balance_getter;
 pub use balance_getter::JsBalanceGetter;
 
-fn parse_bech32_address(address: String) -> crate::Result<Address> {
-    let address_ed25519 = Vec::from_base32(&bech32::decode(&address)?.1)?;
-    let address = Address::Ed25519(Ed25519Address::new(
-        address_ed25519[1..]
-            .try_into()
-            .map_err(|_| anyhow::anyhow!("invalid address length"))?,
-    ));
-    Ok(address)
+mod address_outputs_getter;
+pub use address_outputs_getter::JsAddressOutputsGetter;
+
+mod offline_signing;
+use offline_signing::{
+    parse_account_addresses, parse_transfer_outputs, PrepareTransactionOptionsDto, PrepareTransactionOutputDto,
+    TransferOutputType,
+};
+pub use offline_signing::{sign_transaction_js, sign_transaction_with_signatures_js, transaction_signing_data_js};
+
+mod message_utils;
+use message_utils::UnpackedMessageDto;
+pub use message_utils::{
+    compute_message_id_js, compute_transaction_id_js, message_from_bytes_js, message_to_bytes_js, parse_message_json,
+    verify_ed25519_signature_js, verify_inclusion_proof_js,
+};
+
+/// The DTO of a watched topic event with its id, to skip the events delivered again: an address output in the
+/// `getOutput` shape, or a message of an index in the `messageFromBytes` shape.
+pub(crate) fn watched_event_dto(
+    kind: crate::WatchKind,
+    event: &TopicEvent,
+    bech32_hrp: &str,
+) -> crate::Result<(String, serde_json::Value)> {
+    match kind {
+        crate::WatchKind::Output => {
+            let output = event.output()?;
+            let output_id = format!(
+                "{}{}",
+                hex::encode(&output.transaction_id),
+                hex::encode(output.output_index.to_le_bytes())
+            );
+            let output = OutputMetadataDto::new(output, bech32_hrp)?;
+            Ok((output_id, serde_json::to_value(output).unwrap()))
+        }
+        crate::WatchKind::Message => {
+            let (message_id, message) = message_utils::message_from_bytes(&event.payload_bytes)?;
+            let message = UnpackedMessageDto {
+                message_id,
+                message: MessageResponseDto::new(&message, bech32_hrp)?,
+            };
+            Ok((message_id.to_string(), serde_json::to_value(message).unwrap()))
+        }
+    }
+}
+
+/// The JSON of an activity of an address monitor, with its output in the `getOutput` shape.
+pub(crate) fn address_activity_dto(activity: AddressActivity, bech32_hrp: &str) -> crate::Result<serde_json::Value> {
+    Ok(serde_json::to_value(AddressActivityDto::new(activity, bech32_hrp)?).unwrap())
+}
+
+/// Parses a bech32 or hex encoded Ed25519 address string, a bech32 address must use the network HRP of the client.
+fn parse_address(client_id: &str, address: &str) -> crate::Result<Address> {
+    let bech32_hrp = crate::get_client(client_id)
+        .read()
+        .unwrap()
+        .get_network_info()
+        .bech32_hrp;
+    Ok(crate::address::parse_address(address, Some(&bech32_hrp))
+        .map_err(|e| crate::Error::InvalidAddress(address.to_string(), e))?
+        .address())
+}
+
+/// Parses an output id, the hex-encoded transaction id followed by the little endian output index.
+fn parse_output_id(output_id: &str) -> crate::Result<UTXOInput> {
+    if output_id.len() != 68 || hex::decode(output_id).is_err() {
+        return Err(anyhow::anyhow!(
+            "invalid output id `{}`: expected 68 hex characters, the transaction id followed by the output index",
+            output_id
+        )
+        .into());
+    }
+    Ok(UTXOInput::from_str(output_id)
+        .map_err(|_| anyhow::anyhow!("invalid output id `{}`: invalid output index", output_id))?)
+}
+
+/// Parses the name of an input selection strategy, the manual strategy is set with the explicit inputs.
+fn parse_input_selection_strategy(strategy: &str) -> crate::Result<InputSelectionStrategy> {
+    match strategy {
+        "LargestFirst" => Ok(InputSelectionStrategy::LargestFirst),
+        "SmallestFirst" => Ok(InputSelectionStrategy::SmallestFirst),
+        _ => Err(anyhow::anyhow!(
+            "invalid input selection strategy `{}`: expected `LargestFirst` or `SmallestFirst`",
+            strategy
+        )
+        .into()),
+    }
 }
 
-/// Parses a bech32 address string.
-fn parse_address(address: String) -> crate::Result<Address> {
-    match parse_bech32_address(address.clone()) {
-        Ok(address) => Ok(address),
-        Err(_) => Ok(Address::Ed25519(Ed25519Address::new(
-            hex::decode(address)?.try_into().expect("invalid address length"),
-        ))),
+/// Parses the name of a remainder strategy, the custom strategy is set with the remainder address.
+fn parse_remainder_strategy(strategy: &str) -> crate::Result<RemainderStrategy> {
+    match strategy {
+        "ChangeAddress" => Ok(RemainderStrategy::ChangeAddress),
+        "ReuseFirstInputAddress" => Ok(RemainderStrategy::ReuseFirstInputAddress),
+        _ => Err(anyhow::anyhow!(
+            "invalid remainder strategy `{}`: expected `ChangeAddress` or `ReuseFirstInputAddress`",
+            strategy
+        )
+        .into()),
     }
 }
 
+/// Parses the custom remainder address, a bech32 address must use the network HRP of the client.
+fn parse_remainder_address(client_id: &str, address: &str) -> crate::Result<RemainderStrategy> {
+    let address = parse_address(client_id, address)?;
+    let bech32_hrp = crate::get_client(client_id)
+        .read()
+        .unwrap()
+        .get_network_info()
+        .bech32_hrp;
+    Ok(RemainderStrategy::Custom(
+        crate::address::address_to_bech32(&address, &bech32_hrp)?.into(),
+    ))
+}
+
+/// Checks a derivation index argument, a non-negative integer below 2^31 as the segments of the BIP44 path are
+/// hardened.
+fn derivation_index<'a, C: Context<'a>>(cx: &mut C, index: f64, name: &str) -> NeonResult<usize> {
+    if index < 0.0 || index.fract() != 0.0 || index >= HARDENED as f64 {
+        return cx.throw_error(format!(
+            "invalid {} {}, expected a non-negative integer below 2^31",
+            name, index
+        ));
+    }
+    Ok(index as usize)
+}
+
+/// Parses a hex-encoded transaction id.
+fn parse_transaction_id(transaction_id: &str) -> crate::Result<TransactionId> {
+    if transaction_id.len() != 64 || hex::decode(transaction_id).is_err() {
+        return Err(anyhow::anyhow!(
+            "invalid transaction id `{}`: expected 64 hex characters",
+            transaction_id
+        )
+        .into());
+    }
+    Ok(TransactionId::from_str(transaction_id)
+        .map_err(|e| anyhow::anyhow!("invalid transaction id `{}`: {:?}", transaction_id, e))?)
+}
+
+/// A range of address indexes, the end is excluded.
+#[derive(Deserialize)]
+struct AddressRangeDto {
+    start: usize,
+    end: usize,
+}
+
+/// The options of `consolidateOutputs`.
+#[derive(Deserialize)]
+struct ConsolidationOptionsDto {
+    #[serde(rename = "accountIndex")]
+    account_index: Option<usize>,
+    #[serde(rename = "addressRange")]
+    address_range: Option<AddressRangeDto>,
+    #[serde(rename = "includeDustAllowance", default)]
+    include_dust_allowance: bool,
+    #[serde(rename = "waitForConfirmation", default)]
+    wait_for_confirmation: bool,
+}
+
+/// A transfer of `sendBatch`: an output, or the outputs sent in the same transaction.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BatchTransferDto {
+    Output(BatchOutputDto),
+    Outputs(Vec<BatchOutputDto>),
+}
+
+#[derive(Deserialize)]
+struct BatchOutputDto {
+    address: String,
+    amount: u64,
+}
+
+/// The options of `sendBatch`.
+#[derive(Deserialize)]
+struct SendBatchOptionsDto {
+    #[serde(rename = "accountIndex")]
+    account_index: Option<usize>,
+    #[serde(rename = "initialAddressIndex")]
+    initial_address_index: Option<usize>,
+    parallelism: Option<usize>,
+    #[serde(default)]
+    packing: bool,
+}
+
+/// The options of `sendData`.
+#[derive(Deserialize)]
+struct SendDataOptionsDto {
+    #[serde(default)]
+    chaining: bool,
+    #[serde(rename = "fragmentLength")]
+    fragment_length: Option<usize>,
+}
+
+/// The options of `getTransactionHistory`.
+#[derive(Deserialize)]
+struct TransactionHistoryOptionsDto {
+    #[serde(rename = "accountIndex")]
+    account_index: Option<usize>,
+    #[serde(rename = "initialAddressIndex")]
+    initial_address_index: Option<usize>,
+    #[serde(rename = "gapLimit")]
+    gap_limit: Option<usize>,
+    #[serde(default)]
+    format: TransactionHistoryFormat,
+}
+
 pub struct ClientWrapper(String);
 
-impl Drop for ClientWrapper {
-    fn drop(&mut self) {
-        crate::remove_client(&self.0);
-    }
-}
+impl Drop for ClientWrapper {
+    fn drop(&mut self) {
+        crate::remove_client(&self.0);
+        crate::remove_request_listeners(&self.0);
+        crate::remove_mqtt_event_listeners(&self.0);
+    }
+}
+
+declare_types! {
+    pub class JsClient for ClientWrapper {
+        init(mut cx) {
+            let client_id = cx.argument::<JsString>(0)?.value();
+            Ok(ClientWrapper(client_id))
+        }
+
+        ///////////////////////////////////////////////////////////////////////
+        // High level API
+        ///////////////////////////////////////////////////////////////////////
+
+        method send(mut cx) {
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
+            let client_id = cx.string(client_id).upcast::<JsValue>();
+
+            Ok(JsMessageSender::new(&mut cx, vec![client_id])?.upcast())
+        }
+
+        method message(mut cx) {
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
+            let client_id = cx.string(client_id).upcast::<JsValue>();
+
+            Ok(JsMessageSender::new(&mut cx, vec![client_id])?.upcast())
+        }
+
+        method getUnspentAddress(mut cx) {
+            let seed = cx.argument::<JsString>(0)?;
+            // validate the seed or the signer options
+            if let Err(e) = crate::signer::Signer::parse(&seed.value()) {
+                return cx.throw_error(e.to_string());
+            }
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
+            let client_id = cx.string(client_id);
+
+            Ok(JsUnspentAddressGetter::new(&mut cx, vec![client_id, seed])?.upcast())
+        }
+
+        method findAddresses(mut cx) {
+            let seed = cx.argument::<JsString>(0)?;
+            // validate the seed or the signer options
+            if let Err(e) = crate::signer::Signer::parse(&seed.value()) {
+                return cx.throw_error(e.to_string());
+            }
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
+            let client_id = cx.string(client_id);
+
+            Ok(JsAddressFinder::new(&mut cx, vec![client_id, seed])?.upcast())
+        }
+
+        method getAddresses(mut cx) {
+            let seed = cx.argument::<JsString>(0)?;
+            // validate the seed or the signer options
+            if let Err(e) = crate::signer::Signer::parse(&seed.value()) {
+                return cx.throw_error(e.to_string());
+            }
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
+            let client_id = cx.string(client_id);
+
+            Ok(JsAddressGetter::new(&mut cx, vec![client_id, seed])?.upcast())
+        }
+
+        method findMessages(mut cx) {
+            // the indexes are hex-encoded so binary indexes can be passed
+            let js_indexation_keys: Vec<Handle<JsValue>> = cx.argument::<JsArray>(0)?.to_vec(&mut cx)?;
+            let mut indexation_keys = vec![];
+            for js_indexation_key in js_indexation_keys {
+                let indexation_key: Handle<JsString> = js_indexation_key.downcast_or_throw(&mut cx)?;
+                let indexation_key = hex::decode(indexation_key.value())
+                    .ok()
+                    .and_then(|index| String::from_utf8(index).ok());
+                match indexation_key {
+                    Some(indexation_key) => indexation_keys.push(indexation_key),
+                    None => return cx.throw_error("invalid index: the indexation keys must be valid UTF-8"),
+                }
+            }
+
+            let js_message_ids: Vec<Handle<JsValue>> = cx.argument::<JsArray>(1)?.to_vec(&mut cx)?;
+            let mut message_ids = vec![];
+            for js_message_id in js_message_ids {
+                let message_id: Handle<JsString> = js_message_id.downcast_or_throw(&mut cx)?;
+                match MessageId::from_str(message_id.value().as_str()) {
+                    Ok(id) => message_ids.push(id),
+                    Err(_) => return cx.throw_error(format!("invalid message id: {}", message_id.value())),
+                }
+            }
+
+            // 0 doesn't limit the number of messages
+            let limit = cx.argument::<JsNumber>(2)?.value() as usize;
+            let limit = if limit == 0 { None } else { Some(limit) };
+
+            let cb = cx.argument::<JsFunction>(3)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::FindMessages { indexation_keys, message_ids, limit },
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method getBalance(mut cx) {
+            let seed = cx.argument::<JsString>(0)?;
+            // validate the seed or the signer options
+            if let Err(e) = crate::signer::Signer::parse(&seed.value()) {
+                return cx.throw_error(e.to_string());
+            }
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
+            let client_id = cx.string(client_id);
+
+            Ok(JsBalanceGetter::new(&mut cx, vec![client_id, seed])?.upcast())
+        }
+
+        method findMessagePage(mut cx) {
+            // the index and the data prefix are hex-encoded like the indexes of findMessages
+            let index = hex::decode(cx.argument::<JsString>(0)?.value())
+                .ok()
+                .and_then(|index| String::from_utf8(index).ok());
+            let index = match index {
+                Some(index) => index,
+                None => return cx.throw_error("invalid index: the indexation key must be valid UTF-8"),
+            };
+            let page_size = cx.argument::<JsNumber>(1)?.value() as usize;
+            // `null` skips the cursor and the filters
+            let cursor = match cx.argument_opt(2) {
+                Some(arg) if arg.is_a::<JsString>() => Some(arg.downcast_or_throw::<JsString, _>(&mut cx)?.value()),
+                _ => None,
+            };
+            let referenced_after = match cx.argument_opt(3) {
+                Some(arg) if arg.is_a::<JsNumber>() => {
+                    Some(arg.downcast_or_throw::<JsNumber, _>(&mut cx)?.value() as u64)
+                }
+                _ => None,
+            };
+            let data_prefix = match cx.argument_opt(4) {
+                Some(arg) if arg.is_a::<JsString>() => {
+                    match hex::decode(arg.downcast_or_throw::<JsString, _>(&mut cx)?.value()) {
+                        Ok(prefix) => Some(prefix),
+                        Err(_) => return cx.throw_error("invalid data prefix"),
+                    }
+                }
+                _ => None,
+            };
+
+            let cb = cx.argument::<JsFunction>(5)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::FindMessagePage { index, page_size, cursor, referenced_after, data_prefix },
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method getMessages(mut cx) {
+            let js_message_ids: Vec<Handle<JsValue>> = cx.argument::<JsArray>(0)?.to_vec(&mut cx)?;
+            let mut message_ids = vec![];
+            for js_message_id in js_message_ids {
+                let message_id: Handle<JsString> = js_message_id.downcast_or_throw(&mut cx)?;
+                match MessageId::from_str(message_id.value().as_str()) {
+                    Ok(id) => message_ids.push(id),
+                    Err(_) => return cx.throw_error(format!("invalid message id: {}", message_id.value())),
+                }
+            }
+
+            let cb = cx.argument::<JsFunction>(1)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::GetMessages(message_ids),
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method getAddressBalances(mut cx) {
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
+            let js_addresses: Vec<Handle<JsValue>> = cx.argument::<JsArray>(0)?.to_vec(&mut cx)?;
+            let mut addresses = vec![];
+            for js_address in js_addresses {
+                let address: Handle<JsString> = js_address.downcast_or_throw(&mut cx)?;
+                match parse_address(&client_id, &address.value()) {
+                    Ok(address) => addresses.push(address),
+                    Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+                }
+            }
+
+            let cb = cx.argument::<JsFunction>(1)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::GetAddressBalances(addresses),
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method getTransactionHistory(mut cx) {
+            let seed = cx.argument::<JsString>(0)?.value();
+            let options = cx.argument::<JsString>(1)?.value();
+            let api = crate::convert_panics(|| {
+                let signer = crate::signer::Signer::parse(&seed).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+                let options: TransactionHistoryOptionsDto =
+                    serde_json::from_str(&options).map_err(|e| anyhow::anyhow!("invalid options: {}", e))?;
+                Ok(Api::GetTransactionHistory {
+                    signer,
+                    account_index: options.account_index,
+                    initial_address_index: options.initial_address_index,
+                    gap_limit: options.gap_limit,
+                    format: options.format,
+                })
+            });
+            let api = match api {
+                Ok(api) => api,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            let cb = cx.argument::<JsFunction>(2)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api,
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method getAddressTransactionHistory(mut cx) {
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
+            let js_addresses: Vec<Handle<JsValue>> = cx.argument::<JsArray>(0)?.to_vec(&mut cx)?;
+            let mut addresses = vec![];
+            for js_address in js_addresses {
+                let address: Handle<JsString> = js_address.downcast_or_throw(&mut cx)?;
+                match parse_address(&client_id, &address.value()) {
+                    Ok(address) => addresses.push(address),
+                    Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+                }
+            }
+            let format = cx.argument::<JsString>(1)?.value();
+            let format: TransactionHistoryFormat = match serde_json::from_value(serde_json::Value::String(format)) {
+                Ok(format) => format,
+                Err(e) => return cx.throw_error(format!("invalid format: {}", e)),
+            };
+
+            let cb = cx.argument::<JsFunction>(2)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::GetAddressTransactionHistory { addresses, format },
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method retry(mut cx) {
+            let message_id = cx.argument::<JsString>(0)?.value();
+            let message_id = match MessageId::from_str(message_id.as_str()) {
+                Ok(message_id) => message_id,
+                Err(_) => return cx.throw_error(format!("invalid message id: {}", message_id)),
+            };
+            let cb = cx.argument::<JsFunction>(1)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::Retry(message_id),
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method retryUntilIncluded(mut cx) {
+            let message_id = cx.argument::<JsString>(0)?.value();
+            let message_id = match MessageId::from_str(message_id.as_str()) {
+                Ok(message_id) => message_id,
+                Err(_) => return cx.throw_error(format!("invalid message id: {}", message_id)),
+            };
+            // 0 uses the client defaults
+            let interval = match cx.argument::<JsNumber>(1)?.value() as u64 {
+                0 => None,
+                interval => Some(Duration::from_millis(interval)),
+            };
+            let max_attempts = match cx.argument::<JsNumber>(2)?.value() as u64 {
+                0 => None,
+                max_attempts => Some(max_attempts),
+            };
+            let cb = cx.argument::<JsFunction>(3)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::RetryUntilIncluded {
+                        message_id,
+                        interval,
+                        max_attempts,
+                    },
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method waitForBalance(mut cx) {
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
+            let address = cx.argument::<JsString>(0)?.value();
+            let address = match parse_address(&client_id, &address) {
+                Ok(address) => address,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            let min_amount = cx.argument::<JsNumber>(1)?.value() as u64;
+            // 0 uses the client defaults
+            let interval = match cx.argument::<JsNumber>(2)?.value() as u64 {
+                0 => None,
+                interval => Some(Duration::from_millis(interval)),
+            };
+            let timeout = match cx.argument::<JsNumber>(3)?.value() as u64 {
+                0 => None,
+                timeout => Some(Duration::from_millis(timeout)),
+            };
+            let cb = cx.argument::<JsFunction>(4)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::WaitForBalance {
+                        address,
+                        min_amount,
+                        interval,
+                        timeout,
+                    },
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method consolidateOutputs(mut cx) {
+            let seed = cx.argument::<JsString>(0)?.value();
+            let options = cx.argument::<JsString>(1)?.value();
+            let api = crate::convert_panics(|| {
+                let signer = crate::signer::Signer::parse(&seed).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+                let options: ConsolidationOptionsDto =
+                    serde_json::from_str(&options).map_err(|e| anyhow::anyhow!("invalid options: {}", e))?;
+                Ok(Api::ConsolidateOutputs {
+                    signer,
+                    account_index: options.account_index,
+                    range: options.address_range.map(|range| range.start..range.end),
+                    include_dust_allowance: options.include_dust_allowance,
+                    wait_for_confirmation: options.wait_for_confirmation,
+                })
+            });
+            let api = match api {
+                Ok(api) => api,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            let cb = cx.argument::<JsFunction>(2)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api,
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method sendBatch(mut cx) {
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
+            let seed = cx.argument::<JsString>(0)?.value();
+            let transfers = cx.argument::<JsString>(1)?.value();
+            let options = cx.argument::<JsString>(2)?.value();
+            let api = crate::convert_panics(|| {
+                let signer = crate::signer::Signer::parse(&seed).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+                let transfers: Vec<BatchTransferDto> =
+                    serde_json::from_str(&transfers).map_err(|e| anyhow::anyhow!("invalid transfers: {}", e))?;
+                let options: SendBatchOptionsDto =
+                    serde_json::from_str(&options).map_err(|e| anyhow::anyhow!("invalid options: {}", e))?;
+                let transfers = transfers
+                    .into_iter()
+                    .map(|transfer| {
+                        let outputs = match transfer {
+                            BatchTransferDto::Output(output) => vec![output],
+                            BatchTransferDto::Outputs(outputs) => outputs,
+                        };
+                        outputs
+                            .iter()
+                            .map(|output| Ok((parse_address(&client_id, &output.address)?, output.amount)))
+                            .collect::<crate::Result<Vec<(Address, u64)>>>()
+                    })
+                    .collect::<crate::Result<Vec<Vec<(Address, u64)>>>>()?;
+                Ok(Api::SendBatch {
+                    signer,
+                    transfers,
+                    account_index: options.account_index,
+                    initial_address_index: options.initial_address_index,
+                    parallelism: options.parallelism,
+                    packing: options.packing,
+                })
+            });
+            let api = match api {
+                Ok(api) => api,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            let cb = cx.argument::<JsFunction>(3)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api,
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method prepareTransaction(mut cx) {
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
+            let outputs = cx.argument::<JsString>(0)?.value();
+            let options = cx.argument::<JsString>(1)?.value();
+            let api = crate::convert_panics(|| {
+                let outputs: Vec<PrepareTransactionOutputDto> =
+                    serde_json::from_str(&outputs).map_err(|e| anyhow::anyhow!("invalid outputs: {}", e))?;
+                let options: PrepareTransactionOptionsDto =
+                    serde_json::from_str(&options).map_err(|e| anyhow::anyhow!("invalid options: {}", e))?;
+                if options.seed.is_none() && options.addresses.is_none() && options.input_candidates.is_none() {
+                    return Err(anyhow::anyhow!(
+                        "invalid options: either the `seed`, the account `addresses` or the `inputCandidates` is \
+                         required"
+                    )
+                    .into());
+                }
+                let signer = match &options.seed {
+                    Some(seed) => {
+                        Some(crate::signer::Signer::parse(seed).map_err(|e| anyhow::anyhow!(e.to_string()))?)
+                    }
+                    None => None,
+                };
+                let account_addresses = match &options.addresses {
+                    Some(addresses) => Some(parse_account_addresses(&client_id, addresses)?),
+                    None => None,
+                };
+                Ok(Api::PrepareTransaction {
+                    signer,
+                    account_addresses,
+                    input_candidates: options.input_candidates,
+                    account_index: options.account_index,
+                    initial_address_index: options.initial_address_index,
+                    input_selection: options
+                        .input_selection
+                        .as_deref()
+                        .map(parse_input_selection_strategy)
+                        .transpose()?,
+                    inputs: options
+                        .inputs
+                        .iter()
+                        .map(|output_id| parse_output_id(output_id))
+                        .collect::<crate::Result<Vec<UTXOInput>>>()?,
+                    include_dust_allowance: options.include_dust_allowance_inputs,
+                    remainder_strategy: match (&options.remainder_address, &options.remainder_strategy) {
+                        (Some(_), Some(_)) => {
+                            return Err(anyhow::anyhow!(
+                                "invalid options: the `remainderAddress` sets the remainder strategy, it can't be \
+                                 combined with the `remainderStrategy`"
+                            )
+                            .into())
+                        }
+                        (Some(address), None) => Some(parse_remainder_address(&client_id, address)?),
+                        (None, Some(strategy)) => Some(parse_remainder_strategy(strategy)?),
+                        (None, None) => None,
+                    },
+                    dust_remainder_to_first_output: options.dust_remainder_to_first_output,
+                    outputs: parse_transfer_outputs(&client_id, &outputs, TransferOutputType::SignatureLockedSingle)?,
+                    dust_allowance_outputs: parse_transfer_outputs(
+                        &client_id,
+                        &outputs,
+                        TransferOutputType::DustAllowance,
+                    )?,
+                    dust_check: !options.disable_dust_check,
+                    address_reuse_allowed: options.allow_address_reuse,
+                    index: options.index,
+                    data: options.data,
+                    dry_run: None,
+                })
+            });
+            let api = match api {
+                Ok(api) => api,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            let cb = cx.argument::<JsFunction>(2)?;
+            let client_task = ClientTask { client_id, api };
+            client_task.schedule(cb);
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method getInputCandidates(mut cx) {
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
+            let options = cx.argument::<JsString>(0)?.value();
+            let api = crate::convert_panics(|| {
+                let options: PrepareTransactionOptionsDto =
+                    serde_json::from_str(&options).map_err(|e| anyhow::anyhow!("invalid options: {}", e))?;
+                if options.seed.is_none() && options.addresses.is_none() {
+                    return Err(
+                        anyhow::anyhow!("invalid options: either the `seed` or the account `addresses` is required")
+                            .into(),
+                    );
+                }
+                Ok(Api::GetInputCandidates {
+                    signer: match &options.seed {
+                        Some(seed) => {
+                            Some(crate::signer::Signer::parse(seed).map_err(|e| anyhow::anyhow!(e.to_string()))?)
+                        }
+                        None => None,
+                    },
+                    account_addresses: match &options.addresses {
+                        Some(addresses) => Some(parse_account_addresses(&client_id, addresses)?),
+                        None => None,
+                    },
+                    account_index: options.account_index,
+                    initial_address_index: options.initial_address_index,
+                    inputs: options
+                        .inputs
+                        .iter()
+                        .map(|output_id| parse_output_id(output_id))
+                        .collect::<crate::Result<Vec<UTXOInput>>>()?,
+                    include_dust_allowance: options.include_dust_allowance_inputs,
+                })
+            });
+            let api = match api {
+                Ok(api) => api,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            let cb = cx.argument::<JsFunction>(1)?;
+            let client_task = ClientTask { client_id, api };
+            client_task.schedule(cb);
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method submitSignedTransaction(mut cx) {
+            let payload = cx.argument::<JsString>(0)?.value();
+            let payload: MessagePayloadDto = match serde_json::from_str(&payload) {
+                Ok(payload) => payload,
+                Err(e) => return cx.throw_error(format!("invalid transaction payload: {}", e)),
+            };
+            if !matches!(payload, MessagePayloadDto::Transaction(_)) {
+                return cx.throw_error("invalid transaction payload: expected a transaction payload (type 0)");
+            }
+            let cb = cx.argument::<JsFunction>(1)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::SubmitSignedTransaction {
+                        payload,
+                        parent: None,
+                        parents: None,
+                        parents_check: true,
+                        with_message: true,
+                    },
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method networkInfo(mut cx) {
+            let network_info = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client = crate::get_client(&id);
+                let client = client.read().unwrap();
+                let info = client.get_network_info();
+                serde_json::to_string(&info).unwrap()
+            };
+            Ok(cx.string(network_info).upcast())
+        }
+
+        method getMetrics(mut cx) {
+            let metrics = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client = crate::get_client(&id);
+                let client = client.read().unwrap();
+                let metrics = client.get_metrics();
+                serde_json::to_string(&metrics).unwrap()
+            };
+            Ok(cx.string(metrics).upcast())
+        }
+
+        method getPrometheusMetrics(mut cx) {
+            let metrics = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client = crate::get_client(&id);
+                let client = client.read().unwrap();
+                client.get_metrics().to_prometheus()
+            };
+            Ok(cx.string(metrics).upcast())
+        }
+
+        method refreshNetworkInfo(mut cx) {
+            let cb = cx.argument::<JsFunction>(0)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::RefreshNetworkInfo,
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method refreshNodeInfo(mut cx) {
+            let cb = cx.argument::<JsFunction>(0)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::RefreshNodeInfo,
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method currentNode(mut cx) {
+            let node = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client = crate::get_client(&id);
+                let client = client.read().unwrap();
+                client.get_current_node()
+            };
+            match node {
+                Some(node) => Ok(cx.string(node.as_str()).upcast()),
+                None => Ok(cx.null().upcast()),
+            }
+        }
+
+        method syncedNodes(mut cx) {
+            let nodes = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client = crate::get_client(&id);
+                let client = client.read().unwrap();
+                let nodes: Vec<String> = client.get_synced_nodes().iter().map(|node| node.to_string()).collect();
+                serde_json::to_string(&nodes).unwrap()
+            };
+            Ok(cx.string(nodes).upcast())
+        }
+
+        method nodeSyncStatus(mut cx) {
+            let status = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client = crate::get_client(&id);
+                let client = client.read().unwrap();
+                serde_json::to_string(&client.get_node_sync_status()).unwrap()
+            };
+            Ok(cx.string(status).upcast())
+        }
+
+        method pinNode(mut cx) {
+            let node_url = cx.argument::<JsString>(0)?.value();
+            let result = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client = crate::get_client(&id);
+                let client = client.read().unwrap();
+                client.pin_node(&node_url)
+            };
+            match result {
+                Ok(()) => Ok(cx.this().upcast()),
+                Err(e) => {
+                    let error = crate::error_code::js_error(&mut cx, e.to_string(), &crate::Error::Client(e))?;
+                    cx.throw(error)
+                }
+            }
+        }
 
-declare_types! {
-    pub class JsClient for ClientWrapper {
-        init(mut cx) {
-            let client_id = cx.argument::<JsString>(0)?.value();
-            Ok(ClientWrapper(client_id))
+        method unpinNode(mut cx) {
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client = crate::get_client(&id);
+                let client = client.read().unwrap();
+                client.unpin_node();
+            }
+            Ok(cx.this().upcast())
         }
 
         ///////////////////////////////////////////////////////////////////////
-        // High level API
+        // Node API
         ///////////////////////////////////////////////////////////////////////
 
-        method send(mut cx) {
+        method subscriber(mut cx) {
             let client_id = {
                 let this = cx.this();
                 let guard = cx.lock();
@@ -80,14 +1085,10 @@ declare_types! {
                 id.to_string()
             };
             let client_id = cx.string(client_id);
-
-            Ok(JsMessageSender::new(&mut cx, vec![client_id])?.upcast())
+            Ok(crate::JsTopicSubscriber::new(&mut cx, vec![client_id])?.upcast())
         }
 
-        method getUnspentAddress(mut cx) {
-            let seed = cx.argument::<JsString>(0)?;
-            // validate the seed
-            Seed::from_ed25519_bytes(&hex::decode(seed.value()).expect("invalid seed hex")).expect("invalid seed");
+        method confirmationListener(mut cx) {
             let client_id = {
                 let this = cx.this();
                 let guard = cx.lock();
@@ -95,14 +1096,34 @@ declare_types! {
                 id.to_string()
             };
             let client_id = cx.string(client_id);
+            Ok(crate::JsConfirmationListener::new(&mut cx, vec![client_id])?.upcast())
+        }
 
-            Ok(JsUnspentAddressGetter::new(&mut cx, vec![client_id, seed])?.upcast())
+        method addressMonitor(mut cx) {
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
+            let address = cx.argument::<JsString>(0)?.value();
+            let address = match parse_address(&client_id, &address).and_then(|address| {
+                let bech32_hrp = crate::get_client(&client_id).read().unwrap().get_network_info().bech32_hrp;
+                crate::address::address_to_bech32(&address, &bech32_hrp)
+            }) {
+                Ok(address) => address,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            let mut args: Vec<Handle<JsValue>> = vec![cx.string(client_id).upcast(), cx.string(address).upcast()];
+            if let Some(since_milestone_index) = cx.argument_opt(1) {
+                if since_milestone_index.is_a::<JsNumber>() {
+                    args.push(since_milestone_index);
+                }
+            }
+            Ok(crate::JsAddressMonitor::new(&mut cx, args)?.upcast())
         }
 
-        method findAddresses(mut cx) {
-            let seed = cx.argument::<JsString>(0)?;
-            // validate the seed
-            Seed::from_ed25519_bytes(&hex::decode(seed.value()).expect("invalid seed hex")).expect("invalid seed");
+        method requestListener(mut cx) {
             let client_id = {
                 let this = cx.this();
                 let guard = cx.lock();
@@ -110,26 +1131,18 @@ declare_types! {
                 id.to_string()
             };
             let client_id = cx.string(client_id);
-
-            Ok(JsAddressFinder::new(&mut cx, vec![client_id, seed])?.upcast())
+            Ok(crate::JsRequestListener::new(&mut cx, vec![client_id])?.upcast())
         }
 
-        method findMessages(mut cx) {
-            let js_message_ids: Vec<Handle<JsValue>> = cx.argument::<JsArray>(0)?.to_vec(&mut cx)?;
-            let mut message_ids = vec![];
-            for js_message_id in js_message_ids {
-                let message_id: Handle<JsString> = js_message_id.downcast_or_throw(&mut cx)?;
-                message_ids.push(MessageId::from_str(message_id.value().as_str()).unwrap_or_else(|_| panic!("invalid message id: {}", message_id.value())));
-            }
-
-            let cb = cx.argument::<JsFunction>(2)?;
+        method getInfo(mut cx) {
+            let cb = cx.argument::<JsFunction>(0)?;
             {
                 let this = cx.this();
                 let guard = cx.lock();
                 let id = &this.borrow(&guard).0;
                 let client_task = ClientTask {
                     client_id: id.clone(),
-                    api: Api::FindMessages { message_ids },
+                    api: Api::GetInfo,
                 };
                 client_task.schedule(cb);
             }
@@ -137,37 +1150,15 @@ declare_types! {
             Ok(cx.undefined().upcast())
         }
 
-        method getBalance(mut cx) {
-            let seed = cx.argument::<JsString>(0)?;
-            // validate the seed
-            Seed::from_ed25519_bytes(&hex::decode(seed.value()).expect("invalid seed hex")).expect("invalid seed");
-            let client_id = {
-                let this = cx.this();
-                let guard = cx.lock();
-                let id = &this.borrow(&guard).0;
-                id.to_string()
-            };
-            let client_id = cx.string(client_id);
-
-            Ok(JsBalanceGetter::new(&mut cx, vec![client_id, seed])?.upcast())
-        }
-
-        method getAddressBalances(mut cx) {
-            let js_addresses: Vec<Handle<JsValue>> = cx.argument::<JsArray>(0)?.to_vec(&mut cx)?;
-            let mut addresses = vec![];
-            for js_address in js_addresses {
-                let address: Handle<JsString> = js_address.downcast_or_throw(&mut cx)?;
-                addresses.push(parse_address(address.value()).unwrap_or_else(|_| panic!("invalid address: {}", address.value())));
-            }
-
-            let cb = cx.argument::<JsFunction>(1)?;
+        method getTips(mut cx) {
+            let cb = cx.argument::<JsFunction>(0)?;
             {
                 let this = cx.this();
                 let guard = cx.lock();
                 let id = &this.borrow(&guard).0;
                 let client_task = ClientTask {
                     client_id: id.clone(),
-                    api: Api::GetAddressBalances(addresses),
+                    api: Api::GetTips,
                 };
                 client_task.schedule(cb);
             }
@@ -175,17 +1166,40 @@ declare_types! {
             Ok(cx.undefined().upcast())
         }
 
-        method retry(mut cx) {
-            let message_id = cx.argument::<JsString>(0)?.value();
-            let message_id = MessageId::from_str(message_id.as_str()).expect("invalid message id");
-            let cb = cx.argument::<JsFunction>(1)?;
+        method sendData(mut cx) {
+            // the index and the data are hex-encoded like the indexes of findMessages
+            let index = hex::decode(cx.argument::<JsString>(0)?.value())
+                .ok()
+                .and_then(|index| String::from_utf8(index).ok());
+            let index = match index {
+                Some(index) => index,
+                None => return cx.throw_error("invalid index: the indexation key must be valid UTF-8"),
+            };
+            let data = cx.argument::<JsString>(1)?.value();
+            let options = cx.argument::<JsString>(2)?.value();
+            let api = crate::convert_panics(|| {
+                let data = hex::decode(data).map_err(|e| anyhow::anyhow!("invalid data: {}", e))?;
+                let options: SendDataOptionsDto =
+                    serde_json::from_str(&options).map_err(|e| anyhow::anyhow!("invalid options: {}", e))?;
+                Ok(Api::SendData {
+                    index,
+                    data,
+                    chaining: options.chaining,
+                    fragment_length: options.fragment_length,
+                })
+            });
+            let api = match api {
+                Ok(api) => api,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            let cb = cx.argument::<JsFunction>(3)?;
             {
                 let this = cx.this();
                 let guard = cx.lock();
                 let id = &this.borrow(&guard).0;
                 let client_task = ClientTask {
                     client_id: id.clone(),
-                    api: Api::Retry(message_id),
+                    api,
                 };
                 client_task.schedule(cb);
             }
@@ -193,35 +1207,45 @@ declare_types! {
             Ok(cx.undefined().upcast())
         }
 
-        method networkInfo(mut cx) {
-            let network_info = {
-                let this = cx.this();
-                let guard = cx.lock();
-                let id = &this.borrow(&guard).0;
-                let client = crate::get_client(&id);
-                let client = client.read().unwrap();
-                let info = client.get_network_info();
-                serde_json::to_string(&info).unwrap()
+        method reassembleData(mut cx) {
+            // the index is hex-encoded like the indexes of findMessages
+            let index = hex::decode(cx.argument::<JsString>(0)?.value())
+                .ok()
+                .and_then(|index| String::from_utf8(index).ok());
+            let index = match index {
+                Some(index) => index,
+                None => return cx.throw_error("invalid index: the indexation key must be valid UTF-8"),
             };
-            Ok(cx.string(network_info).upcast())
-        }
-
-        ///////////////////////////////////////////////////////////////////////
-        // Node API
-        ///////////////////////////////////////////////////////////////////////
-
-        method subscriber(mut cx) {
-            let client_id = {
+            let content_hash = cx.argument::<JsString>(1)?.value();
+            let api = crate::convert_panics(|| {
+                let content_hash = hex::decode(&content_hash)
+                    .ok()
+                    .and_then(|content_hash| content_hash.as_slice().try_into().ok())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("invalid content hash: {}, expected 32 hex-encoded bytes", content_hash)
+                    })?;
+                Ok(Api::ReassembleData { index, content_hash })
+            });
+            let api = match api {
+                Ok(api) => api,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            let cb = cx.argument::<JsFunction>(2)?;
+            {
                 let this = cx.this();
                 let guard = cx.lock();
                 let id = &this.borrow(&guard).0;
-                id.to_string()
-            };
-            let client_id = cx.string(client_id);
-            Ok(crate::JsTopicSubscriber::new(&mut cx, vec![client_id])?.upcast())
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api,
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
         }
 
-        method getInfo(mut cx) {
+        method getPeers(mut cx) {
             let cb = cx.argument::<JsFunction>(0)?;
             {
                 let this = cx.this();
@@ -229,7 +1253,7 @@ declare_types! {
                 let id = &this.borrow(&guard).0;
                 let client_task = ClientTask {
                     client_id: id.clone(),
-                    api: Api::GetInfo,
+                    api: Api::GetPeers,
                 };
                 client_task.schedule(cb);
             }
@@ -237,15 +1261,37 @@ declare_types! {
             Ok(cx.undefined().upcast())
         }
 
-        method getTips(mut cx) {
-            let cb = cx.argument::<JsFunction>(0)?;
+        method callNodeApi(mut cx) {
+            let method = cx.argument::<JsString>(0)?.value();
+            let path = cx.argument::<JsString>(1)?.value();
+            let query = cx.argument::<JsString>(2)?.value();
+            let body = cx.argument::<JsString>(3)?.value();
+            let api = crate::convert_panics(|| {
+                let method = iota::Method::from_str(&method.to_uppercase())
+                    .map_err(|_| anyhow::anyhow!("invalid method: {}", method))?;
+                let query: Vec<(String, String)> =
+                    serde_json::from_str(&query).map_err(|e| anyhow::anyhow!("invalid query: {}", e))?;
+                let body: Option<serde_json::Value> =
+                    serde_json::from_str(&body).map_err(|e| anyhow::anyhow!("invalid body: {}", e))?;
+                Ok(Api::CallNodeApi {
+                    method,
+                    path,
+                    query,
+                    body,
+                })
+            });
+            let api = match api {
+                Ok(api) => api,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
+            let cb = cx.argument::<JsFunction>(4)?;
             {
                 let this = cx.this();
                 let guard = cx.lock();
                 let id = &this.borrow(&guard).0;
                 let client_task = ClientTask {
                     client_id: id.clone(),
-                    api: Api::GetTips,
+                    api,
                 };
                 client_task.schedule(cb);
             }
@@ -255,15 +1301,23 @@ declare_types! {
 
         method postMessage(mut cx) {
             let message = cx.argument::<JsString>(0)?.value();
-            let message: MessageDto = serde_json::from_str(&message).expect("invalid message argument");
-            let cb = cx.argument::<JsFunction>(1)?;
+            let message: MessageDto = match serde_json::from_str(&message) {
+                Ok(message) => message,
+                Err(e) => return cx.throw_error(format!("invalid message argument: {}", e)),
+            };
+            // deprecated, resolves to the message id instead of `{ messageId, message }`
+            let message_id_only = cx.argument::<JsBoolean>(1)?.value();
+            let cb = cx.argument::<JsFunction>(2)?;
             {
                 let this = cx.this();
                 let guard = cx.lock();
                 let id = &this.borrow(&guard).0;
                 let client_task = ClientTask {
                     client_id: id.clone(),
-                    api: Api::PostMessage(message),
+                    api: Api::PostMessage {
+                        message,
+                        with_message: !message_id_only,
+                    },
                 };
                 client_task.schedule(cb);
             }
@@ -285,7 +1339,10 @@ declare_types! {
 
         method getOutput(mut cx) {
             let output_id = cx.argument::<JsString>(0)?.value();
-            let output_id = UTXOInput::from_str(output_id.as_str()).expect("invalid output id");
+            let output_id = match parse_output_id(&output_id) {
+                Ok(output_id) => output_id,
+                Err(e) => return cx.throw_error(e.to_string()),
+            };
             let cb = cx.argument::<JsFunction>(1)?;
             {
                 let this = cx.this();
@@ -301,26 +1358,60 @@ declare_types! {
             Ok(cx.undefined().upcast())
         }
 
+        method getIncludedMessage(mut cx) {
+            let transaction_id = cx.argument::<JsString>(0)?.value();
+            let transaction_id = match parse_transaction_id(&transaction_id) {
+                Ok(transaction_id) => transaction_id,
+                Err(e) => return cx.throw_error(e.to_string()),
+            };
+            let cb = cx.argument::<JsFunction>(1)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::GetIncludedMessage(transaction_id),
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
         method findOutputs(mut cx) {
             let js_output_ids = cx.argument::<JsArray>(0)?;
             let js_output_ids: Vec<Handle<JsValue>> = js_output_ids.to_vec(&mut cx)?;
             let mut outputs = vec![];
             for js_output_id in js_output_ids {
                 let output_id: Handle<JsString> = js_output_id.downcast_or_throw(&mut cx)?;
-                let output_id = UTXOInput::from_str(output_id.value().as_str()).expect("invalid output id");
-                outputs.push(output_id);
+                match parse_output_id(&output_id.value()) {
+                    Ok(output_id) => outputs.push(output_id),
+                    Err(e) => return cx.throw_error(e.to_string()),
+                }
             }
 
             let js_addresses = cx.argument::<JsArray>(1)?;
             let js_addresses: Vec<Handle<JsValue>> = js_addresses.to_vec(&mut cx)?;
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
             let mut addresses = vec![];
             for js_address in js_addresses {
                 let address: Handle<JsString> = js_address.downcast_or_throw(&mut cx)?;
-                let address = parse_address(address.value()).expect("invalid address");
-                addresses.push(address);
+                match parse_address(&client_id, &address.value()) {
+                    Ok(address) => addresses.push(address),
+                    Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+                }
             }
 
-            let cb = cx.argument::<JsFunction>(2)?;
+            // fails with the first failed lookup instead of returning the errors along with the found outputs
+            let atomic = cx.argument::<JsBoolean>(2)?.value();
+
+            let cb = cx.argument::<JsFunction>(3)?;
             {
                 let this = cx.this();
                 let guard = cx.lock();
@@ -329,7 +1420,8 @@ declare_types! {
                     client_id: id.clone(),
                     api: Api::FindOutputs {
                         outputs,
-                        addresses
+                        addresses,
+                        atomic,
                     },
                 };
                 client_task.schedule(cb);
@@ -339,8 +1431,34 @@ declare_types! {
         }
 
         method getAddressOutputs(mut cx) {
+            let address = cx.argument::<JsString>(0)?;
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
+            // validate the address
+            if let Err(e) = parse_address(&client_id, &address.value()) {
+                return crate::error_code::throw_error(&mut cx, &e);
+            }
+            let client_id = cx.string(client_id);
+
+            Ok(JsAddressOutputsGetter::new(&mut cx, vec![client_id, address])?.upcast())
+        }
+
+        method getAddressBalance(mut cx) {
+            let client_id = {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                id.to_string()
+            };
             let address = cx.argument::<JsString>(0)?.value();
-            let address = parse_address(address).expect("invalid output id");
+            let address = match parse_address(&client_id, &address) {
+                Ok(address) => address,
+                Err(e) => return crate::error_code::throw_error(&mut cx, &e),
+            };
 
             let cb = cx.argument::<JsFunction>(1)?;
             {
@@ -349,7 +1467,7 @@ declare_types! {
                 let id = &this.borrow(&guard).0;
                 let client_task = ClientTask {
                     client_id: id.clone(),
-                    api: Api::GetAddressOutputs(address),
+                    api: Api::GetAddressBalance(address),
                 };
                 client_task.schedule(cb);
             }
@@ -357,9 +1475,8 @@ declare_types! {
             Ok(cx.undefined().upcast())
         }
 
-        method getAddressBalance(mut cx) {
-            let address = cx.argument::<JsString>(0)?.value();
-            let address = parse_address(address).expect("invalid output id");
+        method getMilestone(mut cx) {
+            let milestone_index = cx.argument::<JsNumber>(0)?.value() as u64;
 
             let cb = cx.argument::<JsFunction>(1)?;
             {
@@ -368,7 +1485,7 @@ declare_types! {
                 let id = &this.borrow(&guard).0;
                 let client_task = ClientTask {
                     client_id: id.clone(),
-                    api: Api::GetAddressBalance(address),
+                    api: Api::GetMilestone(milestone_index),
                 };
                 client_task.schedule(cb);
             }
@@ -376,7 +1493,7 @@ declare_types! {
             Ok(cx.undefined().upcast())
         }
 
-        method getMilestone(mut cx) {
+        method getValidatedMilestone(mut cx) {
             let milestone_index = cx.argument::<JsNumber>(0)?.value() as u64;
 
             let cb = cx.argument::<JsFunction>(1)?;
@@ -386,7 +1503,62 @@ declare_types! {
                 let id = &this.borrow(&guard).0;
                 let client_task = ClientTask {
                     client_id: id.clone(),
-                    api: Api::GetMilestone(milestone_index),
+                    api: Api::GetValidatedMilestone(milestone_index),
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method getMilestoneUtxoChanges(mut cx) {
+            let milestone_index = cx.argument::<JsNumber>(0)?.value() as u64;
+
+            let cb = cx.argument::<JsFunction>(1)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::GetMilestoneUtxoChanges(milestone_index),
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method createInclusionProof(mut cx) {
+            let message_id = cx.argument::<JsString>(0)?.value();
+            let message_id = match MessageId::from_str(message_id.as_str()) {
+                Ok(message_id) => message_id,
+                Err(_) => return cx.throw_error(format!("invalid message id: {}", message_id)),
+            };
+            let cb = cx.argument::<JsFunction>(1)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::CreateInclusionProof(message_id),
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+
+        method getPendingMessages(mut cx) {
+            let cb = cx.argument::<JsFunction>(0)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::GetPendingMessages,
                 };
                 client_task.schedule(cb);
             }
@@ -396,7 +1568,10 @@ declare_types! {
 
         method reattach(mut cx) {
             let message_id = cx.argument::<JsString>(0)?.value();
-            let message_id = MessageId::from_str(message_id.as_str()).expect("invalid message id");
+            let message_id = match MessageId::from_str(message_id.as_str()) {
+                Ok(message_id) => message_id,
+                Err(_) => return cx.throw_error(format!("invalid message id: {}", message_id)),
+            };
             let cb = cx.argument::<JsFunction>(1)?;
             {
                 let this = cx.this();
@@ -414,7 +1589,10 @@ declare_types! {
 
         method promote(mut cx) {
             let message_id = cx.argument::<JsString>(0)?.value();
-            let message_id = MessageId::from_str(message_id.as_str()).expect("invalid message id");
+            let message_id = match MessageId::from_str(message_id.as_str()) {
+                Ok(message_id) => message_id,
+                Err(_) => return cx.throw_error(format!("invalid message id: {}", message_id)),
+            };
             let cb = cx.argument::<JsFunction>(1)?;
             {
                 let this = cx.this();
@@ -429,5 +1607,26 @@ declare_types! {
 
             Ok(cx.undefined().upcast())
         }
+
+        method rebroadcast(mut cx) {
+            let message_id = cx.argument::<JsString>(0)?.value();
+            let message_id = match MessageId::from_str(message_id.as_str()) {
+                Ok(message_id) => message_id,
+                Err(_) => return cx.throw_error(format!("invalid message id: {}", message_id)),
+            };
+            let cb = cx.argument::<JsFunction>(1)?;
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let id = &this.borrow(&guard).0;
+                let client_task = ClientTask {
+                    client_id: id.clone(),
+                    api: Api::Rebroadcast(message_id),
+                };
+                client_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
     }
 }
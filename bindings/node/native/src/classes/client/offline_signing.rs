@@ -0,0 +1,222 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota::{
+    client::api::{AccountAddress, InputCandidate, PreparedTransactionData},
+    Address, Ed25519Signature, Payload, SignatureUnlock,
+};
+use neon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use std::convert::{TryFrom, TryInto};
+
+use super::{parse_address, MessagePayloadDto, MessageSignatureUnlockDto, PreparedTransactionDataDto};
+
+const DEFAULT_BECH32_HRP: &str = "iot";
+
+/// The type of a transfer output, a signature locked single output by default.
+#[derive(Deserialize, PartialEq)]
+pub(super) enum TransferOutputType {
+    SignatureLockedSingle,
+    DustAllowance,
+}
+
+/// An output of `prepareTransaction`.
+#[derive(Deserialize)]
+pub(super) struct PrepareTransactionOutputDto {
+    pub(super) address: String,
+    pub(super) amount: u64,
+    #[serde(rename = "type")]
+    pub(super) output_type: Option<TransferOutputType>,
+}
+
+/// Parses the addresses and amounts of the outputs of the given type.
+pub(super) fn parse_transfer_outputs(
+    client_id: &str,
+    outputs: &[PrepareTransactionOutputDto],
+    output_type: TransferOutputType,
+) -> crate::Result<Vec<(Address, u64)>> {
+    outputs
+        .iter()
+        .filter(|output| {
+            output
+                .output_type
+                .as_ref()
+                .unwrap_or(&TransferOutputType::SignatureLockedSingle)
+                == &output_type
+        })
+        .map(|output| Ok((parse_address(client_id, &output.address)?, output.amount)))
+        .collect()
+}
+
+/// An address of the account, lets the online client select the inputs without the seed.
+#[derive(Deserialize)]
+pub(super) struct AccountAddressDto {
+    pub(super) address: String,
+    #[serde(rename = "addressIndex")]
+    pub(super) address_index: usize,
+    #[serde(default)]
+    pub(super) internal: bool,
+}
+
+/// Parses the account addresses, a bech32 address must use the network HRP of the client.
+pub(super) fn parse_account_addresses(
+    client_id: &str,
+    addresses: &[AccountAddressDto],
+) -> crate::Result<Vec<AccountAddress>> {
+    addresses
+        .iter()
+        .map(|address| {
+            Ok(AccountAddress {
+                address: parse_address(client_id, &address.address)?,
+                address_index: address.address_index,
+                internal: address.internal,
+            })
+        })
+        .collect()
+}
+
+/// The options of `prepareTransaction` and `getInputCandidates`, either the seed, the account addresses or the input
+/// candidates is required.
+#[derive(Deserialize)]
+pub(super) struct PrepareTransactionOptionsDto {
+    pub(super) seed: Option<String>,
+    pub(super) addresses: Option<Vec<AccountAddressDto>>,
+    /// The unspent outputs of the account exported by `getInputCandidates`, in the JSON form of the client.
+    #[serde(rename = "inputCandidates")]
+    pub(super) input_candidates: Option<Vec<InputCandidate>>,
+    #[serde(rename = "accountIndex")]
+    pub(super) account_index: Option<usize>,
+    #[serde(rename = "initialAddressIndex")]
+    pub(super) initial_address_index: Option<usize>,
+    #[serde(rename = "inputSelection")]
+    pub(super) input_selection: Option<String>,
+    #[serde(default)]
+    pub(super) inputs: Vec<String>,
+    #[serde(rename = "includeDustAllowanceInputs", default)]
+    pub(super) include_dust_allowance_inputs: bool,
+    #[serde(rename = "remainderStrategy")]
+    pub(super) remainder_strategy: Option<String>,
+    #[serde(rename = "remainderAddress")]
+    pub(super) remainder_address: Option<String>,
+    #[serde(rename = "dustRemainderToFirstOutput", default)]
+    pub(super) dust_remainder_to_first_output: bool,
+    pub(super) index: Option<String>,
+    pub(super) data: Option<Vec<u8>>,
+    #[serde(rename = "disableDustCheck", default)]
+    pub(super) disable_dust_check: bool,
+    #[serde(rename = "allowAddressReuse", default)]
+    pub(super) allow_address_reuse: bool,
+}
+
+/// The essence an external signer must sign, with the addresses signing it.
+#[derive(Serialize)]
+struct TransactionSigningDataDto {
+    /// The packed essence, hex encoded.
+    essence: String,
+    signers: Vec<SignerDto>,
+}
+
+/// An address signing the essence, with its derivation indexes.
+#[derive(Serialize)]
+struct SignerDto {
+    address: String,
+    #[serde(rename = "accountIndex")]
+    account_index: usize,
+    #[serde(rename = "addressIndex")]
+    address_index: usize,
+    internal: bool,
+}
+
+/// Parses a prepared transaction, along with the bech32 HRP of its input addresses.
+fn parse_prepared_transaction(prepared: &str) -> crate::Result<(PreparedTransactionData, String)> {
+    let prepared: PreparedTransactionDataDto =
+        serde_json::from_str(prepared).map_err(|e| anyhow::anyhow!("invalid prepared transaction: {}", e))?;
+    let bech32_hrp = prepared.bech32_hrp().unwrap_or_else(|| DEFAULT_BECH32_HRP.to_string());
+    Ok((prepared.try_into()?, bech32_hrp))
+}
+
+/// Signs a prepared transaction with the seed or the signer, returning the transaction payload DTO.
+/// The payload addresses are encoded with the HRP of the input addresses.
+fn sign_transaction(prepared: &str, seed: &str) -> crate::Result<String> {
+    let signer = crate::signer::Signer::parse(seed).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    let (prepared, bech32_hrp) = parse_prepared_transaction(prepared)?;
+    let payload = Payload::Transaction(Box::new(prepared.sign(signer.load()?.signer_type())?));
+    Ok(serde_json::to_string(&MessagePayloadDto::new(&payload, &bech32_hrp)?).unwrap())
+}
+
+/// Gets the essence to sign and the signing addresses of a prepared transaction.
+fn transaction_signing_data(prepared: &str) -> crate::Result<String> {
+    let (prepared, bech32_hrp) = parse_prepared_transaction(prepared)?;
+    let signers = prepared
+        .signers()?
+        .into_iter()
+        .map(|signer| {
+            Ok(SignerDto {
+                address: crate::address::address_to_bech32(&signer.address, &bech32_hrp)?,
+                account_index: prepared.account_index,
+                address_index: signer.address_index,
+                internal: signer.internal,
+            })
+        })
+        .collect::<crate::Result<Vec<SignerDto>>>()?;
+    let signing_data = TransactionSigningDataDto {
+        essence: hex::encode(prepared.essence_bytes()?),
+        signers,
+    };
+    Ok(serde_json::to_string(&signing_data).unwrap())
+}
+
+/// Builds the transaction payload DTO of a prepared transaction from the `{ publicKey, signature }` pairs of the
+/// signers, the signatures are verified before the unlock blocks are built.
+fn sign_transaction_with_signatures(prepared: &str, signatures: &str) -> crate::Result<String> {
+    let (prepared, bech32_hrp) = parse_prepared_transaction(prepared)?;
+    let signatures: Vec<MessageSignatureUnlockDto> =
+        serde_json::from_str(signatures).map_err(|e| anyhow::anyhow!("invalid signatures: {}", e))?;
+    let signatures = signatures
+        .into_iter()
+        .enumerate()
+        .map(|(index, signature)| {
+            match SignatureUnlock::try_from(signature)
+                .map_err(|e| anyhow::anyhow!("invalid signature {}: {}", index, e))?
+            {
+                SignatureUnlock::Ed25519(signature) => Ok(signature),
+                _ => unreachable!(),
+            }
+        })
+        .collect::<crate::Result<Vec<Ed25519Signature>>>()?;
+    let payload = Payload::Transaction(Box::new(prepared.with_signatures(signatures)?));
+    Ok(serde_json::to_string(&MessagePayloadDto::new(&payload, &bech32_hrp)?).unwrap())
+}
+
+/// JS `signTransaction(preparedData, seed)`: signs a transaction prepared by `Client.prepareTransaction`
+/// without a node, returning the transaction payload to submit with `Client.submitSignedTransaction`.
+pub fn sign_transaction_js(mut cx: FunctionContext) -> JsResult<JsString> {
+    let prepared = cx.argument::<JsString>(0)?.value();
+    let seed = cx.argument::<JsString>(1)?.value();
+    match crate::convert_panics(|| sign_transaction(&prepared, &seed)) {
+        Ok(payload) => Ok(cx.string(payload)),
+        Err(e) => crate::error_code::throw_error(&mut cx, &e),
+    }
+}
+
+/// JS `transactionSigningData(preparedData)`: the hex encoded essence and the signers of a prepared transaction,
+/// passed to the external signer callback of `signTransaction`.
+pub fn transaction_signing_data_js(mut cx: FunctionContext) -> JsResult<JsString> {
+    let prepared = cx.argument::<JsString>(0)?.value();
+    match crate::convert_panics(|| transaction_signing_data(&prepared)) {
+        Ok(signing_data) => Ok(cx.string(signing_data)),
+        Err(e) => crate::error_code::throw_error(&mut cx, &e),
+    }
+}
+
+/// JS `signTransactionWithSignatures(preparedData, signatures)`: builds the transaction payload from the signatures
+/// returned by the external signer callback of `signTransaction`.
+pub fn sign_transaction_with_signatures_js(mut cx: FunctionContext) -> JsResult<JsString> {
+    let prepared = cx.argument::<JsString>(0)?.value();
+    let signatures = cx.argument::<JsString>(1)?.value();
+    match crate::convert_panics(|| sign_transaction_with_signatures(&prepared, &signatures)) {
+        Ok(payload) => Ok(cx.string(payload)),
+        Err(e) => crate::error_code::throw_error(&mut cx, &e),
+    }
+}
@@ -1,7 +1,6 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use iota::Seed;
 use neon::prelude::*;
 
 use super::{Api, ClientTask};
@@ -11,6 +10,7 @@ pub struct UnspentAddressGetter {
     seed: String,
     account_index: Option<usize>,
     initial_address_index: Option<usize>,
+    include_funded: bool,
 }
 
 declare_types! {
@@ -23,11 +23,13 @@ declare_types! {
                 seed,
                 account_index: None,
                 initial_address_index: None,
+                include_funded: false,
             })
         }
 
         method accountIndex(mut cx) {
-            let account_index = cx.argument::<JsNumber>(0)?.value() as usize;
+            let account_index = cx.argument::<JsNumber>(0)?.value();
+            let account_index = super::derivation_index(&mut cx, account_index, "account index")?;
             {
                 let mut this = cx.this();
                 let guard = cx.lock();
@@ -39,7 +41,8 @@ declare_types! {
         }
 
         method initialAddressIndex(mut cx) {
-            let index = cx.argument::<JsNumber>(0)?.value() as usize;
+            let index = cx.argument::<JsNumber>(0)?.value();
+            let index = super::derivation_index(&mut cx, index, "initial address index")?;
             {
                 let mut this = cx.this();
                 let guard = cx.lock();
@@ -50,6 +53,16 @@ declare_types! {
             Ok(cx.this().upcast())
         }
 
+        method includeFunded(mut cx) {
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).include_funded = true;
+            }
+
+            Ok(cx.this().upcast())
+        }
+
         method get(mut cx) {
             let cb = cx.argument::<JsFunction>(0)?;
             {
@@ -59,9 +72,10 @@ declare_types! {
                 let client_task = ClientTask {
                     client_id: ref_.client_id.clone(),
                     api: Api::GetUnspentAddress {
-                        seed: Seed::from_ed25519_bytes(&hex::decode(&ref_.seed).expect("invalid seed hex")).expect("invalid seed"),
+                        signer: crate::signer::Signer::parse(&ref_.seed).expect("invalid seed"),
                         account_index: ref_.account_index,
                         initial_address_index: ref_.initial_address_index,
+                        include_funded: ref_.include_funded,
                     },
                 };
                 client_task.schedule(cb);
@@ -0,0 +1,238 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex, Weak,
+    },
+    thread,
+    time::Duration,
+};
+
+use iota::{client::api::ConfirmationTracker, message::prelude::MessageId};
+use neon::prelude::*;
+use once_cell::sync::Lazy;
+
+use super::topic_subscriber::WaitForMessageTask;
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
+// The trackers listening to the messages submitted through each client.
+type SubmittedTrackers = Mutex<HashMap<String, Vec<Weak<ConfirmationTracker>>>>;
+
+fn submitted_trackers() -> &'static SubmittedTrackers {
+    static TRACKERS: Lazy<SubmittedTrackers> = Lazy::new(Default::default);
+    &TRACKERS
+}
+
+/// Watches the confirmation of a message submitted through the client on the listeners of its submitted messages.
+pub(crate) fn watch_submitted_message(client_id: &str, message_id: MessageId) {
+    let mut trackers = submitted_trackers().lock().unwrap();
+    if let Some(client_trackers) = trackers.get_mut(client_id) {
+        // the trackers of the dropped listeners are cleaned up here
+        client_trackers.retain(|tracker| match tracker.upgrade() {
+            Some(tracker) => {
+                tracker.watch(message_id);
+                true
+            }
+            None => false,
+        });
+        if client_trackers.is_empty() {
+            trackers.remove(client_id);
+        }
+    }
+}
+
+pub struct ConfirmationListener {
+    client_id: String,
+    tracker: Arc<ConfirmationTracker>,
+    interval: Duration,
+    // `None` is sent when the listener stops, ending the polling.
+    tx: Sender<Option<String>>,
+    rx: Arc<Mutex<Receiver<Option<String>>>>,
+    stop: Arc<AtomicBool>,
+    listening: bool,
+}
+
+impl Drop for ConfirmationListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+fn parse_message_id(message_id: &str) -> Option<MessageId> {
+    MessageId::from_str(message_id).ok()
+}
+
+declare_types! {
+    pub class JsConfirmationListener for ConfirmationListener {
+        init(mut cx) {
+            let client_id = cx.argument::<JsString>(0)?.value();
+            let (tx, rx) = channel();
+
+            Ok(ConfirmationListener {
+                client_id,
+                tracker: Arc::new(ConfirmationTracker::new()),
+                interval: DEFAULT_POLL_INTERVAL,
+                tx,
+                rx: Arc::new(Mutex::new(rx)),
+                stop: Arc::new(AtomicBool::new(false)),
+                listening: false,
+            })
+        }
+
+        method messageId(mut cx) {
+            let message_id = cx.argument::<JsString>(0)?.value();
+            let message_id = match parse_message_id(&message_id) {
+                Some(message_id) => message_id,
+                None => return cx.throw_error(format!("invalid message id: {}", message_id)),
+            };
+
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                this.borrow(&guard).tracker.watch(message_id);
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method messageIds(mut cx) {
+            let mut message_ids = Vec::new();
+            let js_message_ids = cx.argument::<JsArray>(0)?.to_vec(&mut cx)?;
+            for js_message_id in js_message_ids {
+                let message_id: Handle<JsString> = js_message_id.downcast_or_throw(&mut cx)?;
+                match parse_message_id(&message_id.value()) {
+                    Some(message_id) => message_ids.push(message_id),
+                    None => return cx.throw_error(format!("invalid message id: {}", message_id.value())),
+                }
+            }
+
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let tracker = &this.borrow(&guard).tracker;
+                for message_id in message_ids {
+                    tracker.watch(message_id);
+                }
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method unwatch(mut cx) {
+            let message_id = cx.argument::<JsString>(0)?.value();
+            let message_id = match parse_message_id(&message_id) {
+                Some(message_id) => message_id,
+                None => return cx.throw_error(format!("invalid message id: {}", message_id)),
+            };
+
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                this.borrow(&guard).tracker.unwatch(&message_id);
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method submitted(mut cx) {
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let instance = this.borrow(&guard);
+                submitted_trackers()
+                    .lock()
+                    .unwrap()
+                    .entry(instance.client_id.clone())
+                    .or_default()
+                    .push(Arc::downgrade(&instance.tracker));
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method interval(mut cx) {
+            let interval = cx.argument::<JsNumber>(0)?.value() as u64;
+            if interval == 0 {
+                return cx.throw_error("invalid interval, expected a positive number of milliseconds");
+            }
+
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).interval = Duration::from_millis(interval);
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method listen(mut cx) {
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let instance = &mut this.borrow_mut(&guard);
+                if instance.listening {
+                    return Ok(cx.this().upcast());
+                }
+                instance.listening = true;
+                instance.stop = Arc::new(AtomicBool::new(false));
+
+                let client_id = instance.client_id.clone();
+                let tracker = instance.tracker.clone();
+                let interval = instance.interval;
+                let tx = instance.tx.clone();
+                let stop = instance.stop.clone();
+                // the watched messages are polled with the current client node, so they survive node switches
+                thread::spawn(move || {
+                    loop {
+                        thread::sleep(interval);
+                        if stop.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        let client = match crate::find_client(&client_id) {
+                            Some(client) => client,
+                            None => break,
+                        };
+                        let confirmations = crate::block_on(tracker.poll(&client.read().unwrap()));
+                        for confirmation in confirmations {
+                            let _ = tx.send(Some(serde_json::to_string(&confirmation).unwrap()));
+                        }
+                    }
+                    let _ = tx.send(None);
+                });
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method stop(mut cx) {
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let instance = &mut this.borrow_mut(&guard);
+                instance.stop.store(true, Ordering::SeqCst);
+                instance.listening = false;
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method poll(mut cx) {
+            let cb = cx.argument::<JsFunction>(0)?;
+
+            {
+                let this = cx.this();
+                let rx = cx.borrow(&this, |listener| listener.rx.clone());
+                let receive_task = WaitForMessageTask(rx);
+                receive_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+    }
+}
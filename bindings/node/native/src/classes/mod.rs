@@ -1,8 +1,18 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+mod address_monitor;
+mod cancellation;
 mod client;
+mod confirmation_listener;
+mod nonce_provider;
+mod request_listener;
 mod topic_subscriber;
 
+pub use address_monitor::*;
+pub use cancellation::*;
 pub use client::*;
+pub use confirmation_listener::*;
+pub use nonce_provider::*;
+pub use request_listener::*;
 pub use topic_subscriber::*;
@@ -0,0 +1,161 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{channel, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use iota::client::{CancellationToken, Error as ClientError, NonceProvider};
+use neon::prelude::*;
+use once_cell::sync::Lazy;
+
+// The answer of a JS nonce provider: the nonce, or the error message of the provider.
+type NonceAnswer = std::result::Result<u64, String>;
+
+// The interval the cancellation of a call is checked at while it waits for the nonce.
+const CANCELLATION_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+// The nonce requests of every client, polled by JS, and the senders of the nonces of the pending requests by id.
+struct NonceRequests {
+    tx: Mutex<Sender<String>>,
+    rx: Arc<Mutex<Receiver<String>>>,
+    pending: Mutex<HashMap<u64, Sender<NonceAnswer>>>,
+    next_id: AtomicU64,
+}
+
+fn nonce_requests() -> &'static NonceRequests {
+    static REQUESTS: Lazy<NonceRequests> = Lazy::new(|| {
+        let (tx, rx) = channel();
+        NonceRequests {
+            tx: Mutex::new(tx),
+            rx: Arc::new(Mutex::new(rx)),
+            pending: Default::default(),
+            next_id: AtomicU64::new(0),
+        }
+    });
+    &REQUESTS
+}
+
+/// The nonce provider of the clients built with a JS nonce provider: the nonce requests are sent as JSON to the JS
+/// poller, which calls the provider registered with `provider_id` and answers the nonce. The thread building the
+/// message waits for the answer until the timeout, or until its call is cancelled.
+pub(crate) struct JsNonceProvider {
+    pub(crate) provider_id: u64,
+    pub(crate) timeout: Duration,
+}
+
+impl NonceProvider for JsNonceProvider {
+    fn nonce(&self, bytes: &[u8], target_score: f64) -> iota::client::Result<u64> {
+        let requests = nonce_requests();
+        let id = requests.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = channel();
+        requests.pending.lock().unwrap().insert(id, tx);
+        let request = serde_json::json!({
+            "id": id,
+            "provider": self.provider_id,
+            "bytes": hex::encode(bytes),
+            "targetScore": target_score,
+        });
+        let _ = requests.tx.lock().unwrap().send(request.to_string());
+        let answer = wait_for_answer(&rx, self.timeout, CancellationToken::current());
+        requests.pending.lock().unwrap().remove(&id);
+        match answer {
+            Ok(answer) => answer.map_err(ClientError::NonceProvider),
+            Err(error @ ClientError::Cancelled) => Err(error),
+            Err(_) => Err(ClientError::NonceProvider(format!(
+                "no nonce after {} ms",
+                self.timeout.as_millis()
+            ))),
+        }
+    }
+}
+
+// Waits for the answer until the timeout, `Error::Cancelled` once the token is cancelled.
+fn wait_for_answer(
+    rx: &Receiver<NonceAnswer>,
+    timeout: Duration,
+    token: Option<CancellationToken>,
+) -> iota::client::Result<NonceAnswer> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let wait = match &token {
+            Some(_) => remaining.min(CANCELLATION_CHECK_INTERVAL),
+            None => remaining,
+        };
+        match rx.recv_timeout(wait) {
+            Ok(answer) => return Ok(answer),
+            Err(RecvTimeoutError::Timeout) if wait < remaining => {
+                if let Some(token) = &token {
+                    token.check()?;
+                }
+            }
+            Err(_) => return Err(ClientError::NonceProvider("no nonce".to_string())),
+        }
+    }
+}
+
+/// Waits for the next nonce request until the timeout, `undefined` if none came.
+struct PollNonceRequestsTask(Duration);
+
+impl Task for PollNonceRequestsTask {
+    type Output = Option<String>;
+    type Error = crate::Error;
+    type JsEvent = JsValue;
+
+    fn perform(&self) -> Result<Self::Output, Self::Error> {
+        crate::convert_panics(|| {
+            let rx = nonce_requests()
+                .rx
+                .lock()
+                .map_err(|_| anyhow::anyhow!("Could not obtain lock on receiver"))?;
+            match rx.recv_timeout(self.0) {
+                Ok(request) => Ok(Some(request)),
+                Err(RecvTimeoutError::Timeout) => Ok(None),
+                Err(e) => Err(anyhow::anyhow!(e.to_string()).into()),
+            }
+        })
+    }
+
+    fn complete(self, mut cx: TaskContext, result: Result<Self::Output, Self::Error>) -> JsResult<Self::JsEvent> {
+        match result {
+            Ok(Some(s)) => Ok(cx.string(s).upcast()),
+            Ok(None) => Ok(cx.undefined().upcast()),
+            Err(e) => cx.throw_error(format!("PollNonceRequestsTask error: {:?}", e)),
+        }
+    }
+}
+
+/// `pollNonceRequests(timeout, cb)`: calls back with the next nonce request as JSON, or `undefined` after `timeout`
+/// milliseconds.
+pub fn poll_nonce_requests_js(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let timeout = cx.argument::<JsNumber>(0)?.value() as u64;
+    let cb = cx.argument::<JsFunction>(1)?;
+    PollNonceRequestsTask(Duration::from_millis(timeout)).schedule(cb);
+    Ok(cx.undefined())
+}
+
+/// `answerNonceRequest(id, nonce, error)`: answers the nonce of a request as a decimal string, or the error of the
+/// provider if the nonce is `null`. The answer of a request that timed out is ignored.
+pub fn answer_nonce_request_js(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let id = cx.argument::<JsNumber>(0)?.value() as u64;
+    let nonce = cx.argument::<JsValue>(1)?;
+    let answer = if let Ok(nonce) = nonce.downcast::<JsString>() {
+        let nonce = nonce.value();
+        nonce
+            .parse::<u64>()
+            .map_err(|_| format!("invalid nonce `{}`, expected an integer below 2^64", nonce))
+    } else {
+        Err(cx.argument::<JsString>(2)?.value())
+    };
+    if let Some(tx) = nonce_requests().pending.lock().unwrap().remove(&id) {
+        let _ = tx.send(answer);
+    }
+    Ok(cx.undefined())
+}
@@ -0,0 +1,160 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+};
+
+use iota::client::{RequestInfo, RequestMiddleware, ResponseInfo};
+use neon::prelude::*;
+use once_cell::sync::Lazy;
+
+use super::topic_subscriber::WaitForMessageTask;
+
+// The listeners of the requests of a client: the request events are sent as JSON while the flag is set.
+pub(crate) type RequestListeners = Arc<Mutex<Vec<(Arc<AtomicBool>, Sender<Option<String>>)>>>;
+
+fn client_request_listeners() -> &'static Mutex<HashMap<String, RequestListeners>> {
+    static LISTENERS: Lazy<Mutex<HashMap<String, RequestListeners>>> = Lazy::new(Default::default);
+    &LISTENERS
+}
+
+/// Registers the request listeners of a stored client, the JS listeners of the client are added to them.
+pub(crate) fn register_request_listeners(client_id: &str, listeners: RequestListeners) {
+    client_request_listeners()
+        .lock()
+        .unwrap()
+        .insert(client_id.to_string(), listeners);
+}
+
+/// Removes the request listeners of a dropped client.
+pub(crate) fn remove_request_listeners(client_id: &str) {
+    client_request_listeners().lock().unwrap().remove(client_id);
+}
+
+/// The middleware of the clients built from JS: adds the configured headers to every request and forwards the
+/// requests and the responses to the JS request listeners. The listeners are notified asynchronously, after the
+/// request is sent, so they can't change it.
+pub(crate) struct RequestEventForwarder {
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) listeners: RequestListeners,
+}
+
+impl RequestEventForwarder {
+    fn emit(&self, event: impl FnOnce() -> serde_json::Value) {
+        let mut listeners = self.listeners.lock().unwrap();
+        if listeners.is_empty() {
+            return;
+        }
+        let event = event().to_string();
+        listeners.retain(|(listening, tx)| listening.load(Ordering::SeqCst) && tx.send(Some(event.clone())).is_ok());
+    }
+}
+
+fn request_json(request: &RequestInfo) -> serde_json::Value {
+    serde_json::json!({
+        "method": request.method,
+        "path": request.path,
+        "bodySize": request.body_size,
+        "node": request.node,
+    })
+}
+
+impl RequestMiddleware for RequestEventForwarder {
+    fn before_request(&self, request: &RequestInfo) -> Vec<(String, String)> {
+        self.emit(|| serde_json::json!({ "type": "request", "request": request_json(request) }));
+        self.headers.clone()
+    }
+
+    fn after_response(&self, request: &RequestInfo, response: &ResponseInfo) {
+        self.emit(|| {
+            serde_json::json!({
+                "type": "response",
+                "request": request_json(request),
+                "response": {
+                    "status": response.status,
+                    "elapsed": response.elapsed.as_millis() as u64,
+                    "error": response.error,
+                },
+            })
+        });
+    }
+}
+
+pub struct RequestListener {
+    client_id: String,
+    // `None` is sent when the listener stops, ending the polling.
+    tx: Sender<Option<String>>,
+    rx: Arc<Mutex<Receiver<Option<String>>>>,
+    listening: Arc<AtomicBool>,
+}
+
+impl Drop for RequestListener {
+    fn drop(&mut self) {
+        self.listening.store(false, Ordering::SeqCst);
+    }
+}
+
+declare_types! {
+    pub class JsRequestListener for RequestListener {
+        init(mut cx) {
+            let client_id = cx.argument::<JsString>(0)?.value();
+            let (tx, rx) = channel();
+
+            Ok(RequestListener {
+                client_id,
+                tx,
+                rx: Arc::new(Mutex::new(rx)),
+                listening: Arc::new(AtomicBool::new(false)),
+            })
+        }
+
+        method listen(mut cx) {
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                let instance = &mut this.borrow_mut(&guard);
+                if instance.listening.load(Ordering::SeqCst) {
+                    return Ok(cx.this().upcast());
+                }
+                instance.listening = Arc::new(AtomicBool::new(true));
+                if let Some(listeners) = client_request_listeners().lock().unwrap().get(&instance.client_id) {
+                    listeners.lock().unwrap().push((instance.listening.clone(), instance.tx.clone()));
+                }
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method stop(mut cx) {
+            {
+                let this = cx.this();
+                let guard = cx.lock();
+                let instance = this.borrow(&guard);
+                if instance.listening.swap(false, Ordering::SeqCst) {
+                    let _ = instance.tx.send(None);
+                }
+            }
+
+            Ok(cx.this().upcast())
+        }
+
+        method poll(mut cx) {
+            let cb = cx.argument::<JsFunction>(0)?;
+
+            {
+                let this = cx.this();
+                let rx = cx.borrow(&this, |listener| listener.rx.clone());
+                let receive_task = WaitForMessageTask(rx);
+                receive_task.schedule(cb);
+            }
+
+            Ok(cx.undefined().upcast())
+        }
+    }
+}
@@ -2,22 +2,118 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
+    collections::{HashMap, HashSet, VecDeque},
     convert::TryInto,
     sync::{
+        atomic::{AtomicBool, Ordering},
         mpsc::{channel, Receiver, Sender},
         Arc, Mutex,
     },
 };
 
-use iota::Topic;
+use iota::{
+    client::{Client, MqttEvent},
+    Topic,
+};
 use neon::prelude::*;
+use once_cell::sync::Lazy;
+
+// `None` is sent when the subscriber isn't subscribed to any topic anymore, ending the polling.
+type TopicEventSender = Sender<Option<String>>;
+
+// The number of event ids a watcher keeps to skip the events delivered again.
+const DELIVERED_IDS_CAPACITY: usize = 1000;
+
+/// The typed events of a watching subscriber, parsed from the topic payloads.
+#[derive(Clone, Copy)]
+pub(crate) enum WatchKind {
+    /// The outputs of an address, in the `getOutput` shape.
+    Output,
+    /// The messages of an index, in the `messageFromBytes` shape.
+    Message,
+}
+
+// The ids of the last events delivered by a watcher, the broker occasionally delivers an event again.
+#[derive(Default)]
+struct DeliveredIds {
+    ids: HashSet<String>,
+    order: VecDeque<String>,
+}
 
-struct WaitForMessageTask(Arc<Mutex<Receiver<String>>>);
+impl DeliveredIds {
+    // `false` if the id was already delivered
+    fn insert(&mut self, id: String) -> bool {
+        if !self.ids.insert(id.clone()) {
+            return false;
+        }
+        self.order.push_back(id);
+        if self.order.len() > DELIVERED_IDS_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+// The subscribers of a client getting its MQTT connection events: the events are sent as JSON while the flag is set.
+pub(crate) type MqttEventListeners = Arc<Mutex<Vec<(Arc<AtomicBool>, TopicEventSender)>>>;
+
+fn client_mqtt_event_listeners() -> &'static Mutex<HashMap<String, MqttEventListeners>> {
+    static LISTENERS: Lazy<Mutex<HashMap<String, MqttEventListeners>>> = Lazy::new(Default::default);
+    &LISTENERS
+}
+
+/// Forwards the MQTT connection events of a client to its subscribers, to register once the client is stored.
+pub(crate) fn forward_mqtt_events(client: &mut Client) -> MqttEventListeners {
+    let listeners = MqttEventListeners::default();
+    let listeners_ = listeners.clone();
+    client.subscriber().on_event(move |event| {
+        let event = match event {
+            MqttEvent::Connected => serde_json::json!({ "type": "connected" }),
+            MqttEvent::Disconnected => serde_json::json!({ "type": "disconnected" }),
+            MqttEvent::Reconnecting { attempt, delay } => serde_json::json!({
+                "type": "reconnecting",
+                "attempt": attempt,
+                "delay": delay.as_millis() as u64,
+            }),
+            MqttEvent::Error(error) => serde_json::json!({ "type": "error", "error": error }),
+        }
+        .to_string();
+        listeners_
+            .lock()
+            .unwrap()
+            .retain(|(listening, tx)| listening.load(Ordering::SeqCst) && tx.send(Some(event.clone())).is_ok());
+    });
+    listeners
+}
+
+/// Registers the MQTT event listeners of a stored client, its subscribers are added to them.
+pub(crate) fn register_mqtt_event_listeners(client_id: &str, listeners: MqttEventListeners) {
+    client_mqtt_event_listeners()
+        .lock()
+        .unwrap()
+        .insert(client_id.to_string(), listeners);
+}
+
+/// Removes the MQTT event listeners of a dropped client.
+pub(crate) fn remove_mqtt_event_listeners(client_id: &str) {
+    client_mqtt_event_listeners().lock().unwrap().remove(client_id);
+}
+
+/// Sends the MQTT connection events of a client as JSON while the flag is set.
+pub(crate) fn listen_mqtt_events(client_id: &str, listening: Arc<AtomicBool>, tx: TopicEventSender) {
+    if let Some(listeners) = client_mqtt_event_listeners().lock().unwrap().get(client_id) {
+        listeners.lock().unwrap().push((listening, tx));
+    }
+}
+
+pub(crate) struct WaitForMessageTask(pub(crate) Arc<Mutex<Receiver<Option<String>>>>);
 
 impl Task for WaitForMessageTask {
-    type Output = String;
+    type Output = Option<String>;
     type Error = crate::Error;
-    type JsEvent = JsString;
+    type JsEvent = JsValue;
 
     fn perform(&self) -> Result<Self::Output, Self::Error> {
         crate::convert_panics(|| {
@@ -32,7 +128,8 @@ impl Task for WaitForMessageTask {
 
     fn complete(self, mut cx: TaskContext, result: Result<Self::Output, Self::Error>) -> JsResult<Self::JsEvent> {
         match result {
-            Ok(s) => Ok(cx.string(s)),
+            Ok(Some(s)) => Ok(cx.string(s).upcast()),
+            Ok(None) => Ok(cx.undefined().upcast()),
             Err(e) => cx.throw_error(format!("WaitForMessageTask error: {:?}", e)),
         }
     }
@@ -40,14 +137,16 @@ impl Task for WaitForMessageTask {
 
 enum TopicAction {
     Subscribe,
-    Unsubscribe,
+    // `true` if the subscriber has no subscribed topic left after it
+    Unsubscribe(bool),
 }
 
 struct TopicTask {
     client_id: String,
     topics: Vec<Topic>,
     action: TopicAction,
-    sender: Sender<String>,
+    sender: TopicEventSender,
+    watch: Option<WatchKind>,
 }
 
 impl Task for TopicTask {
@@ -57,26 +156,50 @@ impl Task for TopicTask {
 
     fn perform(&self) -> Result<Self::Output, Self::Error> {
         crate::convert_panics(|| {
-            let sender = Arc::new(Mutex::new(self.sender.clone()));
+            // the topics are already subscribed by this instance
+            if self.topics.is_empty() {
+                return Ok(());
+            }
             let client = crate::get_client(&self.client_id);
             let mut client = client.write().unwrap();
             match self.action {
                 TopicAction::Subscribe => {
+                    let sender = Arc::new(Mutex::new(self.sender.clone()));
+                    let watch = self.watch;
+                    let bech32_hrp = client.get_network_info().bech32_hrp;
+                    let delivered_ids = Mutex::new(DeliveredIds::default());
                     client
                         .subscriber()
                         .with_topics(self.topics.clone())
                         .subscribe(move |event| {
+                            let event = match watch {
+                                Some(kind) => match crate::watched_event_dto(kind, event, &bech32_hrp) {
+                                    Ok((id, dto)) => {
+                                        if !delivered_ids.lock().unwrap().insert(id) {
+                                            return;
+                                        }
+                                        serde_json::json!({ "topic": event.topic, "payload": dto })
+                                    }
+                                    Err(e) => serde_json::json!({ "topic": event.topic, "error": e.to_string() }),
+                                },
+                                None => {
+                                    let payload = serde_json::from_str(&event.payload)
+                                        .unwrap_or_else(|_| serde_json::Value::String(event.payload.clone()));
+                                    serde_json::json!({
+                                        "topic": event.topic,
+                                        "payload": payload,
+                                    })
+                                }
+                            };
                             let s = sender.lock().unwrap();
-                            let _ = s.send(serde_json::to_string(&event).unwrap());
-                        })
-                        .expect("failed to subscribe to topics");
+                            let _ = s.send(Some(event.to_string()));
+                        })?;
                 }
-                TopicAction::Unsubscribe => {
-                    client
-                        .subscriber()
-                        .with_topics(self.topics.clone())
-                        .unsubscribe()
-                        .expect("failed to unsbuscribe from topics");
+                TopicAction::Unsubscribe(unsubscribed_all) => {
+                    client.subscriber().with_topics(self.topics.clone()).unsubscribe()?;
+                    if unsubscribed_all {
+                        let _ = self.sender.send(None);
+                    }
                 }
             }
             Ok(())
@@ -92,10 +215,33 @@ impl Task for TopicTask {
 }
 
 pub struct TopicSubscriber {
-    tx: Sender<String>,
-    rx: Arc<Mutex<Receiver<String>>>,
+    tx: TopicEventSender,
+    rx: Arc<Mutex<Receiver<Option<String>>>>,
     client_id: String,
     topics: Vec<Topic>,
+    subscribed_topics: HashSet<Topic>,
+    // set while the subscriber gets the MQTT connection events
+    listening: Arc<AtomicBool>,
+    // the typed events of a watcher, instead of the raw payloads
+    watch: Option<WatchKind>,
+}
+
+impl Drop for TopicSubscriber {
+    fn drop(&mut self) {
+        self.listening.store(false, Ordering::SeqCst);
+        if self.subscribed_topics.is_empty() {
+            return;
+        }
+        // the client might have been dropped first, which already closes the MQTT connection
+        if let Some(client) = crate::find_client(&self.client_id) {
+            if let Ok(mut client) = client.write() {
+                let _ = client
+                    .subscriber()
+                    .with_topics(self.subscribed_topics.drain().collect())
+                    .unsubscribe();
+            }
+        }
+    }
 }
 
 declare_types! {
@@ -109,12 +255,18 @@ declare_types! {
                 rx: Arc::new(Mutex::new(rx)),
                 client_id,
                 topics: Vec::new(),
+                subscribed_topics: HashSet::new(),
+                listening: Arc::new(AtomicBool::new(false)),
+                watch: None,
             })
         }
 
         method topic(mut cx) {
-            let js_topic = cx.argument::<JsString>(0)?;
-            let topic = js_topic.value().as_str().try_into().unwrap_or_else(|_| panic!("invalid topic: {}", js_topic.value()));
+            let js_topic = cx.argument::<JsString>(0)?.value();
+            let topic: Topic = match js_topic.as_str().try_into() {
+                Ok(topic) => topic,
+                Err(_) => return cx.throw_error(format!("invalid topic: {}", js_topic)),
+            };
 
             {
                 let mut this = cx.this();
@@ -133,7 +285,10 @@ declare_types! {
             let js_topics: Vec<Handle<JsValue>> = topic_js_array.to_vec(&mut cx)?;
             for js_topic in js_topics {
                 let topic: Handle<JsString> = js_topic.downcast_or_throw(&mut cx)?;
-                topics.push(topic.value().as_str().try_into().unwrap_or_else(|_| panic!("invalid topic: {}", topic.value())));
+                match topic.value().as_str().try_into() {
+                    Ok(topic) => topics.push(topic),
+                    Err(_) => return cx.throw_error(format!("invalid topic: {}", topic.value())),
+                }
             }
 
             {
@@ -146,17 +301,48 @@ declare_types! {
             Ok(cx.this().upcast())
         }
 
+        method watch(mut cx) {
+            let kind = match cx.argument::<JsString>(0)?.value().as_str() {
+                "output" => WatchKind::Output,
+                "message" => WatchKind::Message,
+                kind => return cx.throw_error(format!("unknown watch kind: {}, expected output or message", kind)),
+            };
+            {
+                let mut this = cx.this();
+                let guard = cx.lock();
+                this.borrow_mut(&guard).watch.replace(kind);
+            }
+
+            Ok(cx.this().upcast())
+        }
+
         method subscribe(mut cx) {
             let cb = cx.argument::<JsFunction>(0)?;
             {
-                let this = cx.this();
+                let mut this = cx.this();
                 let guard = cx.lock();
-                let instance = &this.borrow(&guard);
+                let instance = &mut this.borrow_mut(&guard);
+                // subscribing twice to a topic would deliver its events twice
+                let topics: Vec<Topic> = instance
+                    .topics
+                    .iter()
+                    .filter(|topic| !instance.subscribed_topics.contains(topic))
+                    .cloned()
+                    .collect::<HashSet<Topic>>()
+                    .into_iter()
+                    .collect();
+                instance.subscribed_topics.extend(topics.iter().cloned());
+                // listening before the connection, for its first event
+                if !instance.listening.load(Ordering::SeqCst) {
+                    instance.listening = Arc::new(AtomicBool::new(true));
+                    listen_mqtt_events(&instance.client_id, instance.listening.clone(), instance.tx.clone());
+                }
                 let topic_task = TopicTask {
                     client_id: instance.client_id.clone(),
-                    topics: instance.topics.clone(),
+                    topics,
                     action: TopicAction::Subscribe,
                     sender: instance.tx.clone(),
+                    watch: instance.watch,
                 };
                 topic_task.schedule(cb);
             }
@@ -167,14 +353,26 @@ declare_types! {
         method unsubscribe(mut cx) {
             let cb = cx.argument::<JsFunction>(0)?;
             {
-                let this = cx.this();
+                let mut this = cx.this();
                 let guard = cx.lock();
-                let instance = &this.borrow(&guard);
+                let instance = &mut this.borrow_mut(&guard);
+                let topics: Vec<Topic> = if instance.topics.is_empty() {
+                    instance.subscribed_topics.drain().collect()
+                } else {
+                    instance.topics.clone()
+                };
+                for topic in &topics {
+                    instance.subscribed_topics.remove(topic);
+                }
+                if instance.subscribed_topics.is_empty() {
+                    instance.listening.store(false, Ordering::SeqCst);
+                }
                 let topic_task = TopicTask {
                     client_id: instance.client_id.clone(),
-                    topics: instance.topics.clone(),
-                    action: TopicAction::Unsubscribe,
+                    topics,
+                    action: TopicAction::Unsubscribe(instance.subscribed_topics.is_empty()),
                     sender: instance.tx.clone(),
+                    watch: instance.watch,
                 };
                 topic_task.schedule(cb);
             }
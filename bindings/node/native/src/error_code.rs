@@ -0,0 +1,83 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The errors thrown to JS, with the `code` and the `details` of `error_codes`.
+
+use iota::client::Error as ClientError;
+use neon::prelude::*;
+
+use crate::{
+    error_codes::{error_code, Detail},
+    Error,
+};
+
+/// Creates the JS error with the message and the `code` and `details` of the error.
+pub(crate) fn js_error<'a, C: Context<'a>>(cx: &mut C, message: String, error: &Error) -> JsResult<'a, JsError> {
+    let js_error = JsError::error(cx, message)?;
+    if let Some((code, details)) = error_code(error) {
+        let js_code = cx.string(code);
+        js_error.set(cx, "code", js_code)?;
+        if !details.is_empty() {
+            let js_details = JsObject::new(cx);
+            for (key, detail) in details {
+                let value = match detail {
+                    Detail::String(value) => cx.string(value).upcast::<JsValue>(),
+                    Detail::Number(value) => cx.number(value).upcast::<JsValue>(),
+                    Detail::Strings(values) => {
+                        let js_values = JsArray::new(cx, values.len() as u32);
+                        for (index, value) in values.into_iter().enumerate() {
+                            let value = cx.string(value);
+                            js_values.set(cx, index as u32, value)?;
+                        }
+                        js_values.upcast::<JsValue>()
+                    }
+                    Detail::Numbers(values) => {
+                        let js_values = JsArray::new(cx, values.len() as u32);
+                        for (index, value) in values.into_iter().enumerate() {
+                            let value = cx.number(value);
+                            js_values.set(cx, index as u32, value)?;
+                        }
+                        js_values.upcast::<JsValue>()
+                    }
+                    Detail::Answers(answers) => {
+                        let js_answers = JsArray::new(cx, answers.len() as u32);
+                        for (index, (node, answer)) in answers.into_iter().enumerate() {
+                            let js_answer = JsObject::new(cx);
+                            let node = cx.string(node);
+                            js_answer.set(cx, "node", node)?;
+                            let answer = cx.string(answer);
+                            js_answer.set(cx, "answer", answer)?;
+                            js_answers.set(cx, index as u32, js_answer)?;
+                        }
+                        js_answers.upcast::<JsValue>()
+                    }
+                };
+                js_details.set(cx, key, value)?;
+            }
+            js_error.set(cx, "details", js_details)?;
+        }
+    }
+    // the last known states of the message and its reattachments
+    if let Error::Client(ClientError::NotIncluded(_, _, states)) = error {
+        let js_states = JsArray::new(cx, states.len() as u32);
+        for (index, state) in states.iter().enumerate() {
+            let js_state = JsObject::new(cx);
+            let message_id = cx.string(&state.message_id);
+            js_state.set(cx, "messageId", message_id)?;
+            let inclusion_state = match state.inclusion_state {
+                Some(inclusion_state) => cx.string(inclusion_state.to_string()).upcast::<JsValue>(),
+                None => cx.null().upcast::<JsValue>(),
+            };
+            js_state.set(cx, "inclusionState", inclusion_state)?;
+            js_states.set(cx, index as u32, js_state)?;
+        }
+        js_error.set(cx, "states", js_states)?;
+    }
+    Ok(js_error)
+}
+
+/// Throws the error with its message, `code` and `details`.
+pub(crate) fn throw_error<'a, C: Context<'a>, T>(cx: &mut C, error: &Error) -> NeonResult<T> {
+    let js_error = js_error(cx, error.to_string(), error)?;
+    cx.throw(js_error)
+}
@@ -0,0 +1,297 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The stable `code` and the structured `details` of the errors, so they can be handled without matching the
+//! messages. There's no JS engine API here: the JS errors are created in `error_code`. The variants of the optional
+//! iota-core features are matched with the same features of the binding crate.
+
+use iota::client::{Error as ClientError, PrunedRequest, RequestContext};
+
+use crate::Error;
+
+/// A value of the `details` of an error.
+pub(crate) enum Detail {
+    String(String),
+    Number(f64),
+    Strings(Vec<String>),
+    Numbers(Vec<f64>),
+    /// The `{ node, answer }` pairs of the quorum nodes.
+    Answers(Vec<(String, String)>),
+}
+
+fn string(value: impl ToString) -> Detail {
+    Detail::String(value.to_string())
+}
+
+fn number(value: impl Into<f64>) -> Detail {
+    Detail::Number(value.into())
+}
+
+/// The `url`, the `node`, the `endpoint`, the `elapsedMs` and the `attempt` of the node request of an error.
+fn request_details(context: &RequestContext) -> Vec<(&'static str, Detail)> {
+    let mut details = vec![
+        ("url", string(&context.url)),
+        ("node", string(&context.node)),
+        ("endpoint", string(&context.endpoint)),
+        ("elapsedMs", number(context.elapsed.as_millis() as f64)),
+    ];
+    if let Some(attempt) = context.attempt {
+        details.push(("attempt", number(attempt as f64)));
+    }
+    details
+}
+
+pub(crate) type ErrorCode = (&'static str, Vec<(&'static str, Detail)>);
+
+/// The code and the details of an error, `None` for the errors without a stable code.
+pub(crate) fn error_code(error: &Error) -> Option<ErrorCode> {
+    match error {
+        Error::Client(error) => client_error_code(error),
+        Error::InvalidAddress(address, _) => Some(("EINVALIDADDRESS", vec![("address", string(address))])),
+        Error::Address(_) => Some(("EINVALIDADDRESS", Vec::new())),
+        Error::Bech32(_) => Some(("EINVALIDPARAMETER", Vec::new())),
+        Error::Panic(_) => Some(("EINTERNAL", Vec::new())),
+        _ => None,
+    }
+}
+
+/// The `{ message, code, details }` JSON of an error returned among the results of a call instead of being thrown,
+/// such as the error of a transfer of `sendBatch`.
+pub(crate) fn error_json(error: &Error) -> serde_json::Value {
+    let mut json = serde_json::json!({ "message": error.to_string() });
+    if let Some((code, details)) = error_code(error) {
+        json["code"] = code.into();
+        if !details.is_empty() {
+            let details: serde_json::Map<String, serde_json::Value> = details
+                .into_iter()
+                .map(|(key, detail)| {
+                    let value = match detail {
+                        Detail::String(value) => value.into(),
+                        Detail::Number(value) => value.into(),
+                        Detail::Strings(values) => values.into(),
+                        Detail::Numbers(values) => values.into(),
+                        Detail::Answers(answers) => answers
+                            .into_iter()
+                            .map(|(node, answer)| serde_json::json!({ "node": node, "answer": answer }))
+                            .collect(),
+                    };
+                    (key.to_string(), value)
+                })
+                .collect();
+            json["details"] = details.into();
+        }
+    }
+    json
+}
+
+fn client_error_code(error: &ClientError) -> Option<ErrorCode> {
+    let code = match error {
+        ClientError::Timeout(context) => ("ETIMEDOUT", context.as_deref().map(request_details).unwrap_or_default()),
+        ClientError::ReqwestError(error, context) if error.is_connect() => (
+            "ENODEUNREACHABLE",
+            match context {
+                Some(context) => request_details(context),
+                None => error.url().map(|url| vec![("url", string(url))]).unwrap_or_default(),
+            },
+        ),
+        ClientError::SyncedNodePoolEmpty | ClientError::SubmitNodePoolEmpty => ("ENODEUNREACHABLE", Vec::new()),
+        ClientError::OfflineClient => ("EOFFLINE", Vec::new()),
+        ClientError::Cancelled => ("ECANCELLED", Vec::new()),
+        ClientError::ResponseError(status, _, context) => {
+            let mut details = vec![("status", number(*status))];
+            details.extend(request_details(context));
+            ("ERESPONSE", details)
+        }
+        ClientError::RateLimited(status, retry_after, context) => {
+            let mut details = vec![("status", number(*status))];
+            if let Some(retry_after) = retry_after {
+                details.push(("retryAfterMs", number(retry_after.as_millis() as f64)));
+            }
+            details.extend(request_details(context));
+            ("ERATELIMITED", details)
+        }
+        ClientError::Unauthorized(url) => ("EUNAUTHORIZED", vec![("url", string(url))]),
+        ClientError::Pow(_) | ClientError::PowNodePoolEmpty => ("EPOW", Vec::new()),
+        ClientError::RemotePowRefused(url, _) => ("EPOW", vec![("url", string(url))]),
+        ClientError::NonceProvider(_) => ("ENONCEPROVIDER", Vec::new()),
+        ClientError::PrunedData {
+            requested,
+            pruning_index,
+        } => {
+            let requested = match requested {
+                PrunedRequest::Milestone(index) | PrunedRequest::MilestoneUtxoChanges(index) => {
+                    ("milestoneIndex", number(*index as f64))
+                }
+                PrunedRequest::Message(message_id) => ("messageId", string(message_id)),
+            };
+            (
+                "EPRUNED",
+                vec![requested, ("pruningIndex", number(*pruning_index as f64))],
+            )
+        }
+        ClientError::NotEnoughBalance(available, required) => (
+            "ENOTENOUGHBALANCE",
+            vec![
+                ("availableAmount", number(*available as f64)),
+                ("requiredAmount", number(*required as f64)),
+            ],
+        ),
+        ClientError::ValueOverflow(amount) => ("EVALUEOVERFLOW", vec![("amount", number(*amount as f64))]),
+        ClientError::TooManyInputs(count, max) => (
+            "ETOOMANYINPUTS",
+            vec![
+                ("inputCount", number(*count as f64)),
+                ("maxInputCount", number(*max as f64)),
+            ],
+        ),
+        // a dust output the destination address doesn't allow, caught before the PoW
+        ClientError::DustProtection(address, amount, available)
+        | ClientError::DustRemainder(address, amount, available) => (
+            "EDUST",
+            vec![
+                ("address", string(address)),
+                ("amount", number(*amount as f64)),
+                ("availableDustOutputs", number(*available as f64)),
+            ],
+        ),
+        // dust allowance inputs leaving more dust outputs than the remaining allowance allows
+        ClientError::DustAllowanceInput(address, dust_outputs, allowed) => (
+            "EDUST",
+            vec![
+                ("address", string(address)),
+                ("dustOutputs", number(*dust_outputs as f64)),
+                ("availableDustOutputs", number(*allowed as f64)),
+            ],
+        ),
+        ClientError::DustError(address, amount, required) => (
+            "EDUST",
+            vec![
+                ("address", string(address)),
+                ("amount", number(*amount as f64)),
+                ("requiredAmount", number(*required as f64)),
+            ],
+        ),
+        ClientError::MessageNotReferenced(message_id) => ("ENOTREFERENCED", vec![("messageId", string(message_id))]),
+        ClientError::InvalidInclusionProof(link, reason) => (
+            "EINVALIDPROOF",
+            vec![("link", number(*link as f64)), ("reason", string(reason))],
+        ),
+        ClientError::InvalidMilestone(index, reason) => (
+            "EINVALIDMILESTONE",
+            vec![("milestoneIndex", number(*index as f64)), ("reason", string(reason))],
+        ),
+        ClientError::ConflictingTransaction(message_id) => ("ECONFLICTING", vec![("messageId", string(message_id))]),
+        ClientError::NotIncluded(message_id, attempts, _) => (
+            "ENOTINCLUDED",
+            vec![
+                ("messageId", string(message_id)),
+                ("attempts", number(*attempts as f64)),
+            ],
+        ),
+        ClientError::BalanceTimeout(address, min_amount, balance) => (
+            "EBALANCETIMEOUT",
+            vec![
+                ("address", string(address)),
+                ("minAmount", number(*min_amount as f64)),
+                ("balance", number(*balance as f64)),
+            ],
+        ),
+        ClientError::AddressReuse(addresses) => {
+            ("EADDRESSREUSE", vec![("addresses", Detail::Strings(addresses.clone()))])
+        }
+        ClientError::StalePlan(output_id) => ("ESTALEPLAN", vec![("outputId", string(output_id))]),
+        ClientError::BatchMessageFailed(transfer, _) => {
+            ("EBATCHMESSAGE", vec![("transferIndex", number(*transfer as f64))])
+        }
+        ClientError::TransactionNotIncluded(transaction_id) => {
+            ("ENOTINCLUDED", vec![("transactionId", string(transaction_id))])
+        }
+        ClientError::MessageAlreadyReferenced(message_id, milestone_index) => (
+            "EREFERENCED",
+            vec![
+                ("messageId", string(message_id)),
+                ("milestoneIndex", number(*milestone_index as f64)),
+            ],
+        ),
+        ClientError::UnknownParents(parents) => {
+            ("EUNKNOWNPARENTS", vec![("parents", Detail::Strings(parents.clone()))])
+        }
+        ClientError::ParentsRejected(parents, answer) => (
+            "EUNKNOWNPARENTS",
+            vec![
+                ("parents", Detail::Strings(parents.clone())),
+                ("answer", string(answer)),
+            ],
+        ),
+        ClientError::QuorumError(answers) => ("EQUORUM", vec![("answers", Detail::Answers(answers.clone()))]),
+        ClientError::DataNotFound(content_hash) => ("EDATANOTFOUND", vec![("contentHash", string(content_hash))]),
+        ClientError::MissingDataFragments {
+            content_hash,
+            missing,
+            fragment_count,
+        } => (
+            "EMISSINGFRAGMENTS",
+            vec![
+                ("contentHash", string(content_hash)),
+                (
+                    "missing",
+                    Detail::Numbers(missing.iter().map(|sequence| *sequence as f64).collect()),
+                ),
+                ("fragmentCount", number(*fragment_count)),
+            ],
+        ),
+        ClientError::InvalidDataFragments(content_hash, _) => {
+            ("EINVALIDFRAGMENTS", vec![("contentHash", string(content_hash))])
+        }
+        ClientError::QuorumPoolSizeError(synced, size) => (
+            "EQUORUM",
+            vec![
+                ("syncedNodes", number(*synced as f64)),
+                ("quorumSize", number(*size as f64)),
+            ],
+        ),
+        ClientError::SigningError(_) => ("ESIGNING", Vec::new()),
+        #[cfg(feature = "stronghold")]
+        ClientError::StrongholdSnapshotNotFound(path) => ("ESNAPSHOTNOTFOUND", vec![("snapshotPath", string(path))]),
+        #[cfg(feature = "stronghold")]
+        ClientError::StrongholdInvalidPassword(path) => ("ESTRONGHOLDPASSWORD", vec![("snapshotPath", string(path))]),
+        #[cfg(feature = "ledger")]
+        ClientError::LedgerDeviceNotFound => ("ELEDGERNOTFOUND", Vec::new()),
+        #[cfg(feature = "ledger")]
+        ClientError::LedgerAppNotOpen(_) => ("ELEDGERAPPNOTOPEN", Vec::new()),
+        #[cfg(feature = "ledger")]
+        ClientError::LedgerDeniedByUser => ("ELEDGERDENIED", Vec::new()),
+        ClientError::InvalidDerivationPath(_) => ("EINVALIDPARAMETER", Vec::new()),
+        ClientError::MissingParameter(parameter) => ("EINVALIDPARAMETER", vec![("parameter", string(parameter))]),
+        ClientError::TooManyOutputs(count, max) => (
+            "EINVALIDPARAMETER",
+            vec![
+                ("outputCount", number(*count as f64)),
+                ("maxOutputCount", number(*max as f64)),
+            ],
+        ),
+        ClientError::InvalidParameter(_) | ClientError::IndexationError(_) => ("EINVALIDPARAMETER", Vec::new()),
+        ClientError::UnknownNode(url) => ("EINVALIDPARAMETER", vec![("url", string(url))]),
+        #[cfg(feature = "mqtt")]
+        ClientError::MqttSchemeMismatch(url, _) => ("ESCHEMEMISMATCH", vec![("url", string(url))]),
+        // the network ids are strings, they don't fit in a JS number
+        ClientError::NetworkIdMismatch(message_network_id, network, network_id) => (
+            "ENETWORKMISMATCH",
+            vec![
+                ("messageNetworkId", string(message_network_id)),
+                ("network", string(network)),
+                ("networkId", string(network_id)),
+            ],
+        ),
+        ClientError::Bech32HrpMismatch(address, _, _) => ("EINVALIDADDRESS", vec![("address", string(address))]),
+        ClientError::InvalidMessage(message_id, _) => ("EINVALIDMESSAGE", vec![("messageId", string(message_id))]),
+        // the balance of an address failed with the inner error
+        ClientError::AddressBalanceError(address, error) => {
+            let (code, mut details) = client_error_code(error)?;
+            details.push(("address", string(address)));
+            (code, details)
+        }
+        _ => return None,
+    };
+    Some(code)
+}
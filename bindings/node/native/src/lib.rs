@@ -13,10 +13,16 @@ use std::{
     any::Any,
     collections::HashMap,
     panic::{catch_unwind, AssertUnwindSafe},
-    sync::{Arc, Mutex, RwLock},
+    sync::{Arc, RwLock},
 };
 
+mod address;
+mod address_js;
 mod classes;
+mod error_code;
+mod error_codes;
+mod mnemonic;
+mod signer;
 use classes::*;
 
 type ClientInstanceMap = Arc<RwLock<HashMap<String, Arc<RwLock<Client>>>>>;
@@ -30,7 +36,11 @@ pub enum Error {
     #[error("`{0}`")]
     Client(#[from] iota::client::Error),
     #[error("`{0}`")]
-    Address(#[from] bech32::Error),
+    Address(#[from] address::AddressError),
+    #[error("`{0}`")]
+    Bech32(#[from] address::Bech32Error),
+    #[error("`invalid address `{0}`: {1}`")]
+    InvalidAddress(String, address::AddressError),
     #[error("`{0}`")]
     Hex(#[from] hex::FromHexError),
     #[error("`{0}`")]
@@ -45,10 +55,12 @@ impl From<iota::message::Error> for Error {
     }
 }
 
+/// Runs the future on the shared runtime, it can be entered from several threads at once so the tasks of concurrent
+/// calls don't wait on each other.
 pub(crate) fn block_on<C: futures::Future>(cb: C) -> C::Output {
-    static INSTANCE: OnceCell<Mutex<Runtime>> = OnceCell::new();
-    let runtime = INSTANCE.get_or_init(|| Mutex::new(Runtime::new().unwrap()));
-    runtime.lock().unwrap().block_on(cb)
+    static INSTANCE: OnceCell<Runtime> = OnceCell::new();
+    let runtime = INSTANCE.get_or_init(|| Runtime::new().unwrap());
+    runtime.block_on(cb)
 }
 
 /// Gets the client instances map.
@@ -64,6 +76,13 @@ pub(crate) fn get_client(id: &str) -> Arc<RwLock<Client>> {
     map.get(id).expect("client dropped or not initialised").clone()
 }
 
+pub(crate) fn find_client(id: &str) -> Option<Arc<RwLock<Client>>> {
+    let map = instances()
+        .read()
+        .expect("failed to lock client instances: find_client()");
+    map.get(id).cloned()
+}
+
 pub(crate) fn store_client(client: Client) -> String {
     let mut map = instances()
         .write()
@@ -110,10 +129,41 @@ register_module!(mut cx, {
     cx.export_class::<JsClientBuilder>("ClientBuilder")?;
     cx.export_class::<JsClient>("Client")?;
     cx.export_class::<JsTopicSubscriber>("TopicSubscriber")?;
+    cx.export_class::<JsConfirmationListener>("ConfirmationListener")?;
+    cx.export_class::<JsRequestListener>("RequestListener")?;
+    cx.export_class::<JsAddressMonitor>("AddressMonitor")?;
     cx.export_class::<JsMessageGetter>("MessageGetter")?;
     cx.export_class::<JsMessageSender>("MessageSender")?;
     cx.export_class::<JsUnspentAddressGetter>("UnspentAddressGetter")?;
     cx.export_class::<JsAddressFinder>("AddressFinder")?;
+    cx.export_class::<JsAddressGetter>("AddressGetter")?;
     cx.export_class::<JsBalanceGetter>("BalanceGetter")?;
+    cx.export_class::<JsAddressOutputsGetter>("AddressOutputsGetter")?;
+    cx.export_function("isAddressValid", address_js::is_address_valid)?;
+    cx.export_function("parseAddress", address_js::parse_address_js)?;
+    cx.export_function("hexToBech32", address_js::hex_to_bech32_js)?;
+    cx.export_function("bech32ToHex", address_js::bech32_to_hex_js)?;
+    cx.export_function("bech32Encode", address_js::bech32_encode_js)?;
+    cx.export_function("bech32Decode", address_js::bech32_decode_js)?;
+    cx.export_function("generateMnemonic", mnemonic::generate_mnemonic_js)?;
+    cx.export_function("mnemonicToSeed", mnemonic::mnemonic_to_seed_js)?;
+    cx.export_function("createStrongholdSnapshot", signer::create_stronghold_snapshot_js)?;
+    cx.export_function("signTransaction", sign_transaction_js)?;
+    cx.export_function("transactionSigningData", transaction_signing_data_js)?;
+    cx.export_function("signTransactionWithSignatures", sign_transaction_with_signatures_js)?;
+    cx.export_function("parseMessageJson", parse_message_json)?;
+    cx.export_function("messageFromBytes", message_from_bytes_js)?;
+    cx.export_function("messageToBytes", message_to_bytes_js)?;
+    cx.export_function("verifyEd25519Signature", verify_ed25519_signature_js)?;
+    cx.export_function("computeMessageId", compute_message_id_js)?;
+    cx.export_function("computeTransactionId", compute_transaction_id_js)?;
+    cx.export_function("verifyInclusionProof", verify_inclusion_proof_js)?;
+    cx.export_function("validateMilestone", validate_milestone_js)?;
+    cx.export_function("pollNonceRequests", poll_nonce_requests_js)?;
+    cx.export_function("answerNonceRequest", answer_nonce_request_js)?;
+    cx.export_function("beginCancellation", begin_cancellation_js)?;
+    cx.export_function("endCancellation", end_cancellation_js)?;
+    cx.export_function("cancel", cancel_js)?;
+    cx.export_function("releaseCancellation", release_cancellation_js)?;
     Ok(())
 });
@@ -0,0 +1,107 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! BIP39 mnemonic utilities and the seed parsing shared by the bindings.
+
+use bip39::{ErrorKind, Language, Mnemonic};
+use neon::prelude::*;
+use rand::{rngs::OsRng, RngCore};
+
+const DEFAULT_WORD_COUNT: usize = 24;
+const WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// Mnemonic parsing errors.
+#[derive(Debug, thiserror::Error)]
+pub enum MnemonicError {
+    /// The mnemonic doesn't have a BIP39 number of words.
+    #[error("invalid mnemonic word count {0}, expected 12, 15, 18, 21 or 24 words")]
+    InvalidWordCount(usize),
+    /// A word of the mnemonic isn't in the word list.
+    #[error("invalid mnemonic word: every word must be in the BIP39 English word list")]
+    InvalidWord,
+    /// The checksum encoded in the last word doesn't match the mnemonic.
+    #[error("invalid mnemonic checksum")]
+    InvalidChecksum,
+}
+
+/// Errors of the seeds given to the builders.
+#[derive(Debug, thiserror::Error)]
+pub enum SeedError {
+    /// The seed is neither a hex seed nor a mnemonic.
+    #[error("invalid seed")]
+    InvalidSeed,
+    /// The seed mnemonic is invalid.
+    #[error("invalid seed mnemonic: {0}")]
+    Mnemonic(#[from] MnemonicError),
+    /// The `{ signer }` options are invalid.
+    #[error("invalid signer: {0}")]
+    InvalidSigner(String),
+}
+
+impl From<anyhow::Error> for MnemonicError {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast_ref::<ErrorKind>() {
+            Some(ErrorKind::InvalidChecksum) => Self::InvalidChecksum,
+            Some(ErrorKind::InvalidWordLength(count)) => Self::InvalidWordCount(*count),
+            _ => Self::InvalidWord,
+        }
+    }
+}
+
+/// Generates an English mnemonic of `word_count` words from OS entropy.
+pub fn generate_mnemonic(word_count: usize) -> Result<String, MnemonicError> {
+    if !WORD_COUNTS.contains(&word_count) {
+        return Err(MnemonicError::InvalidWordCount(word_count));
+    }
+    // every 3 words encode 32 bits of entropy
+    let mut entropy = vec![0u8; word_count / 3 * 4];
+    OsRng.fill_bytes(&mut entropy);
+    let mnemonic = Mnemonic::from_entropy(&entropy, Language::English)?;
+    Ok(mnemonic.phrase().to_string())
+}
+
+/// Validates an English mnemonic and gets its BIP39 seed.
+pub fn mnemonic_to_seed(mnemonic: &str, passphrase: &str) -> Result<Vec<u8>, MnemonicError> {
+    let words: Vec<&str> = mnemonic.split_whitespace().collect();
+    if !WORD_COUNTS.contains(&words.len()) {
+        return Err(MnemonicError::InvalidWordCount(words.len()));
+    }
+    let mnemonic = Mnemonic::from_phrase(&words.join(" "), Language::English)?;
+    Ok(bip39::Seed::new(&mnemonic, passphrase).as_bytes().to_vec())
+}
+
+/// Parses the seed given to the builders, a hex Ed25519 seed, a hex BIP39 seed or a mnemonic without passphrase,
+/// into the bytes of its SLIP-10 seed. The BIP39 seed is the SLIP-10 seed as is, not its BLAKE2b-256 hash anymore.
+pub(crate) fn parse_seed_bytes(seed: &str) -> Result<Vec<u8>, SeedError> {
+    let word_count = seed.split_whitespace().count();
+    if WORD_COUNTS.contains(&word_count) {
+        Ok(mnemonic_to_seed(seed, "")?)
+    } else {
+        hex::decode(seed).map_err(|_| SeedError::InvalidSeed)
+    }
+}
+
+/// JS `generateMnemonic(wordCount)`.
+pub fn generate_mnemonic_js(mut cx: FunctionContext) -> JsResult<JsString> {
+    let word_count = match cx.argument_opt(0) {
+        Some(arg) if arg.is_a::<JsNumber>() => arg.downcast_or_throw::<JsNumber, _>(&mut cx)?.value() as usize,
+        _ => DEFAULT_WORD_COUNT,
+    };
+    match generate_mnemonic(word_count) {
+        Ok(mnemonic) => Ok(cx.string(mnemonic)),
+        Err(e) => cx.throw_error(e.to_string()),
+    }
+}
+
+/// JS `mnemonicToSeed(mnemonic, passphrase?)`, returning the hex encoded BIP39 seed.
+pub fn mnemonic_to_seed_js(mut cx: FunctionContext) -> JsResult<JsString> {
+    let mnemonic = cx.argument::<JsString>(0)?.value();
+    let passphrase = match cx.argument_opt(1) {
+        Some(arg) if arg.is_a::<JsString>() => arg.downcast_or_throw::<JsString, _>(&mut cx)?.value(),
+        _ => String::new(),
+    };
+    match mnemonic_to_seed(&mnemonic, &passphrase) {
+        Ok(seed) => Ok(cx.string(hex::encode(seed))),
+        Err(e) => cx.throw_error(e.to_string()),
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The signers given to the builders in place of a seed: a seed, a mnemonic or the JSON `{ signer }` options of a
+//! Stronghold or a Ledger Nano signer.
+
+use iota::{
+    client::{Bip39Seed, LedgerSigner, SignerType, StrongholdSigner},
+    Seed,
+};
+use neon::prelude::*;
+use serde::Deserialize;
+
+use crate::mnemonic::{parse_seed_bytes, SeedError};
+
+const BIP39_SEED_LENGTH: usize = 64;
+
+/// The JSON `{ signer }` options.
+#[derive(Deserialize)]
+struct SignerOptionsDto {
+    signer: SignerDto,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum SignerDto {
+    Stronghold {
+        #[serde(rename = "snapshotPath")]
+        snapshot_path: String,
+        password: String,
+    },
+    LedgerNano {
+        #[serde(default)]
+        simulator: bool,
+    },
+}
+
+/// A parsed signer, the Stronghold snapshot is only loaded and the Ledger Nano only opened to derive the addresses or
+/// to sign.
+pub(crate) enum Signer {
+    Seed(Seed),
+    Bip39Seed(Bip39Seed),
+    Stronghold { snapshot_path: String, password: String },
+    LedgerNano { simulator: bool },
+}
+
+/// A signer ready to derive the addresses and to sign.
+pub(crate) enum LoadedSigner<'a> {
+    Seed(&'a Seed),
+    Bip39Seed(&'a Bip39Seed),
+    Stronghold(StrongholdSigner),
+    LedgerNano(LedgerSigner),
+}
+
+impl Signer {
+    /// Parses a seed, a mnemonic or the JSON `{ signer }` options.
+    pub(crate) fn parse(signer: &str) -> Result<Self, SeedError> {
+        if !signer.trim_start().starts_with('{') {
+            let seed = parse_seed_bytes(signer)?;
+            return match seed.len() {
+                BIP39_SEED_LENGTH => Bip39Seed::from_bytes(&seed).map(Self::Bip39Seed),
+                _ => Seed::from_ed25519_bytes(&seed).map(Self::Seed),
+            }
+            .map_err(|_| SeedError::InvalidSeed);
+        }
+        let options: SignerOptionsDto =
+            serde_json::from_str(signer).map_err(|e| SeedError::InvalidSigner(e.to_string()))?;
+        match options.signer {
+            SignerDto::Stronghold {
+                snapshot_path,
+                password,
+            } => Ok(Self::Stronghold {
+                snapshot_path,
+                password,
+            }),
+            SignerDto::LedgerNano { simulator } => Ok(Self::LedgerNano { simulator }),
+        }
+    }
+
+    /// Loads the Stronghold snapshot, failing if it's missing or if the password is wrong, or opens the Ledger Nano,
+    /// failing if it isn't found or if the IOTA app isn't open.
+    pub(crate) fn load(&self) -> crate::Result<LoadedSigner<'_>> {
+        match self {
+            Self::Seed(seed) => Ok(LoadedSigner::Seed(seed)),
+            Self::Bip39Seed(seed) => Ok(LoadedSigner::Bip39Seed(seed)),
+            Self::Stronghold {
+                snapshot_path,
+                password,
+            } => Ok(LoadedSigner::Stronghold(StrongholdSigner::load(
+                snapshot_path,
+                password,
+            )?)),
+            Self::LedgerNano { simulator } => Ok(LoadedSigner::LedgerNano(LedgerSigner::new(*simulator)?)),
+        }
+    }
+}
+
+impl LoadedSigner<'_> {
+    pub(crate) fn signer_type(&self) -> SignerType<'_> {
+        match self {
+            Self::Seed(seed) => SignerType::Seed(seed),
+            Self::Bip39Seed(seed) => SignerType::Bip39Seed(seed),
+            Self::Stronghold(signer) => SignerType::Stronghold(signer),
+            Self::LedgerNano(signer) => SignerType::LedgerNano(signer),
+        }
+    }
+}
+
+/// JS `createStrongholdSnapshot(snapshotPath, password, seed)`: creates a Stronghold snapshot holding the SLIP-10 seed
+/// of a seed or a mnemonic, it derives the same addresses as the seed.
+pub fn create_stronghold_snapshot_js(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let snapshot_path = cx.argument::<JsString>(0)?.value();
+    let password = cx.argument::<JsString>(1)?.value();
+    let seed = cx.argument::<JsString>(2)?.value();
+    let seed = match parse_seed_bytes(&seed) {
+        Ok(seed) => seed,
+        Err(e) => return cx.throw_error(e.to_string()),
+    };
+    match crate::convert_panics(|| Ok(StrongholdSigner::create(&snapshot_path, &password, &seed)?)) {
+        Ok(_) => Ok(cx.undefined()),
+        Err(e) => crate::error_code::throw_error(&mut cx, &e),
+    }
+}
@@ -0,0 +1,55 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+// the first address accepts dust outputs, the second one has no balance and the third one accepts dust outputs
+// without a balance
+const dustAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+const dustAddressHex = '644eb5594b880f7da82f35902296199a0eb9186999797f661b324f8d754a3a09'
+const emptyAddress = 'iot1q95jpvtk7cf7c7l9ne50c684jl4n8ya0srm5clpak7qes9ratu0eyf5eyz5'
+const emptyAddressHex = '6920b176f613ec7be59e68fc68f597eb3393af80f74c7c3db78198147d5f1f92'
+const emptyDustAddress = 'iot1qx6rfxzrgqrj8mpwprzzgrexq9p6v9mvw8q2s8r54gdzqs5dcs8xydrfqz9'
+const emptyDustAddressHex = 'b4349843400723ec2e08c4240f260143a6176c71c0a81c74aa1a20428dc40e62'
+const balances = {
+  [dustAddress]: { balance: 1000000, dustAllowed: true },
+  [emptyAddress]: { balance: 0, dustAllowed: false },
+  [emptyDustAddress]: { balance: 0, dustAllowed: true }
+}
+
+function startNode () {
+  const node = {}
+  node.server = http.createServer((req, res) => {
+    const [, address] = req.url.match(/^\/api\/v1\/addresses\/(iot1[0-9a-z]+)$/) || []
+    res.setHeader('content-type', 'application/json')
+    if (req.method === 'GET' && address in balances) {
+      res.end(JSON.stringify({ data: { addressType: 1, address, count: 1, ...balances[address] } }))
+    } else {
+      res.statusCode = 404
+      res.end()
+    }
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Address balances', () => {
+  let node
+
+  before(async () => {
+    node = await startNode()
+  })
+
+  after(() => node.server.close())
+
+  it('serializes the address balances', async () => {
+    const client = new ClientBuilder().node(node.url).disableNodeSync().build()
+    const addressBalances = await client.getAddressBalances([dustAddress, emptyAddress, emptyDustAddress])
+    assert.deepStrictEqual(addressBalances, [
+      { address: dustAddress, addressHex: dustAddressHex, balance: '1000000', dustAllowed: true },
+      { address: emptyAddress, addressHex: emptyAddressHex, balance: '0', dustAllowed: false },
+      { address: emptyDustAddress, addressHex: emptyDustAddressHex, balance: '0', dustAllowed: true }
+    ])
+  })
+})
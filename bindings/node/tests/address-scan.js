@@ -0,0 +1,69 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const seed = 'b3a9bf35521157aa9c4508ab3a9266e210ae297ff5a4584234c4d9e7d01712e3'
+
+// a node answering every address as unused after 20 milliseconds, without any spent output, counting the balance
+// and outputs requests and the most requests it handled at once
+function startNode () {
+  const node = { requests: 0, outputsRequests: 0, inFlight: 0, maxInFlight: 0 }
+  node.server = http.createServer((req, res) => {
+    const balance = req.method === 'GET' && req.url.match(/^\/api\/v1\/addresses\/(\w+)$/)
+    const outputs = req.method === 'GET' && req.url.match(/^\/api\/v1\/addresses\/(\w+)\/outputs\?include-spent=true$/)
+    if (balance) {
+      node.requests++
+    } else if (outputs) {
+      node.outputsRequests++
+    }
+    if (balance || outputs) {
+      node.inFlight++
+      node.maxInFlight = Math.max(node.maxInFlight, node.inFlight)
+    }
+    setTimeout(() => {
+      res.setHeader('content-type', 'application/json')
+      if (balance) {
+        node.inFlight--
+        res.end(JSON.stringify({ data: { addressType: 1, address: balance[1], balance: 0, count: 0, dustAllowed: false } }))
+      } else if (outputs) {
+        node.inFlight--
+        res.end(JSON.stringify({ data: { address: outputs[1], maxResults: 1000, count: 0, outputIds: [] } }))
+      } else {
+        res.statusCode = 404
+        res.end()
+      }
+    }, 20)
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Address scan', () => {
+  it('queries the addresses of a window concurrently', async () => {
+    for (const parallelism of [1, 8]) {
+      const node = await startNode()
+      const client = new ClientBuilder()
+        .node(node.url)
+        .disableNodeSync()
+        .addressScanWindow(10)
+        .addressScanParallelism(parallelism)
+        .build()
+      // the first window of 10 indexes is unused, so the search stops after its public and internal addresses
+      const balance = await client.getBalance(seed).accountIndex(0).get()
+      assert.strictEqual(balance, '0')
+      assert.strictEqual(node.requests, 20)
+      // the addresses without a balance are checked for spent outputs
+      assert.strictEqual(node.outputsRequests, 20)
+      assert.strictEqual(node.maxInFlight, parallelism)
+      node.server.close()
+    }
+  })
+
+  it('validates the address scan options', () => {
+    assert.throws(() => new ClientBuilder().addressScanWindow(0), /invalid address scan window 0/)
+    assert.throws(() => new ClientBuilder().addressScanWindow(1001), /expected an integer from 1 to 1000/)
+    assert.throws(() => new ClientBuilder().addressScanParallelism(1.5), /invalid address scan parallelism 1.5/)
+  })
+})
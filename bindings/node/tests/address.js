@@ -0,0 +1,217 @@
+const { ClientBuilder, isAddressValid, parseAddress, hexToBech32, bech32ToHex, bech32Encode, bech32Decode } = require('../lib')
+const assert = require('assert')
+
+const bech32Address = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+const hexAddress = '644eb5594b880f7da82f35902296199a0eb9186999797f661b324f8d754a3a09'
+const bytes = Array.from(Buffer.from(hexAddress, 'hex'))
+
+describe('Address utilities', () => {
+  it('parses a bech32 address', () => {
+    assert.strictEqual(isAddressValid(bech32Address), true)
+    assert.strictEqual(isAddressValid(bech32Address, 'iot'), true)
+    assert.deepStrictEqual(parseAddress(bech32Address), { encoding: 'bech32', hrp: 'iot', bytes })
+  })
+
+  it('parses a hex address', () => {
+    assert.strictEqual(isAddressValid(hexAddress), true)
+    assert.deepStrictEqual(parseAddress(hexAddress), { encoding: 'hex', hrp: null, bytes })
+    assert.deepStrictEqual(parseAddress(`0x${hexAddress}`, 'iot'), { encoding: 'hex', hrp: null, bytes })
+  })
+
+  it('rejects a bad checksum', () => {
+    const address = bech32Address.slice(0, -1) + 'q'
+    assert.strictEqual(isAddressValid(address), false)
+    assert.throws(() => parseAddress(address), /invalid address checksum/)
+  })
+
+  it('rejects an unexpected HRP', () => {
+    assert.strictEqual(isAddressValid(bech32Address, 'iota'), false)
+    assert.throws(() => parseAddress(bech32Address, 'iota'), /invalid address HRP `iot`, expected `iota`/)
+  })
+
+  it('rejects a bad length', () => {
+    assert.strictEqual(isAddressValid(hexAddress.slice(2)), false)
+    assert.throws(() => parseAddress(hexAddress.slice(2)), /invalid address length: expected 64 hex characters, found 62/)
+  })
+
+  it('rejects bad characters', () => {
+    assert.throws(() => parseAddress(`0x${'g'.repeat(64)}`), /invalid address character `g`/)
+    assert.strictEqual(isAddressValid(bech32Address.replace('q9', 'b9')), false)
+    assert.throws(() => parseAddress(bech32Address.replace('q9', 'b9')), /invalid address character `b`/)
+  })
+})
+
+describe('Address conversion', () => {
+  // the same Ed25519 address on the testnet and on the default network
+  const testnetAddress = 'atoi1qxt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupxtmtev5'
+  const iotAddress = 'iot1qxt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupxgecea4'
+
+  it('converts between hex and bech32', () => {
+    assert.strictEqual(hexToBech32(hexAddress, 'iot'), bech32Address)
+    assert.deepStrictEqual(bech32ToHex(bech32Address), { hex: hexAddress, hrp: 'iot' })
+    const { hex, hrp } = bech32ToHex(testnetAddress)
+    assert.strictEqual(hrp, 'atoi')
+    assert.strictEqual(hexToBech32(hex, 'atoi'), testnetAddress)
+    assert.strictEqual(hexToBech32(hex, 'iot'), iotAddress)
+  })
+
+  it('converts mainnet addresses', () => {
+    const mainnetAddress = hexToBech32(hexAddress, 'iota')
+    assert.strictEqual(mainnetAddress.startsWith('iota1q'), true)
+    assert.deepStrictEqual(bech32ToHex(mainnetAddress), { hex: hexAddress, hrp: 'iota' })
+  })
+
+  it('defaults to the client HRP', () => {
+    const client = new ClientBuilder().node('http://localhost:14265').disableNodeSync().build()
+    assert.strictEqual(client.hexToBech32(hexAddress), bech32Address)
+    assert.strictEqual(client.hexToBech32(hexAddress, 'atoi'), hexToBech32(hexAddress, 'atoi'))
+    assert.deepStrictEqual(client.bech32ToHex(testnetAddress), bech32ToHex(testnetAddress))
+    assert.throws(() => hexToBech32(hexAddress), { name: 'TypeError' })
+  })
+
+  it('rejects corrupted checksums', () => {
+    assert.throws(() => bech32ToHex(bech32Address.slice(0, -1) + 'q'), /invalid address checksum/)
+    assert.throws(() => bech32ToHex(testnetAddress.replace('atoi1qxt0', 'atoi1qxt2')), /invalid address checksum/)
+    assert.throws(() => bech32ToHex(hexAddress), /invalid address `644eb/)
+  })
+
+  it('rejects hex addresses that are not 64 hex characters', () => {
+    assert.throws(() => hexToBech32(hexAddress.slice(2), 'iot'), /expected 64 hex characters, found 62/)
+    assert.throws(() => hexToBech32(`${hexAddress}00`, 'iot'), /expected 64 hex characters, found 66/)
+    assert.throws(() => hexToBech32(`0x${hexAddress}`, 'iot'), /invalid address character `x`/)
+    assert.throws(() => hexToBech32(bech32Address, 'iot'), /invalid address character/)
+  })
+})
+
+describe('Bech32 utilities', () => {
+  const testnetAddress = 'atoi1qxt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupxtmtev5'
+
+  // the valid strings of BIP-173
+  const validStrings = [
+    'A12UEL5L',
+    'a12uel5l',
+    'an83characterlonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1tt5tgs',
+    'abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw',
+    `11${'q'.repeat(82)}c8247j`,
+    'split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w',
+    '?1ezyfcl'
+  ]
+
+  for (const value of validStrings) {
+    it(`decodes and encodes back the BIP-173 string ${value.slice(0, 16)}`, () => {
+      const { hrp, version, payload } = bech32Decode(value)
+      const data = version === null ? '' : version.toString(16).padStart(2, '0') + payload
+      assert.strictEqual(bech32Encode(hrp, data), value.toLowerCase())
+    })
+  }
+
+  it('decodes the version and the payload', () => {
+    assert.deepStrictEqual(bech32Decode('abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw'), {
+      hrp: 'abcdef',
+      version: 0,
+      payload: '443214c74254b635cf84653a56d7c675be77df'
+    })
+    assert.deepStrictEqual(bech32Decode('A12UEL5L'), { hrp: 'a', version: null, payload: '' })
+  })
+
+  // the invalid strings of BIP-173, with the failed check
+  const invalidStrings = [
+    ['\x201nwldj5', /invalid bech32 character ` `/],
+    ['\x7f1axkwrx', /invalid bech32 character `\x7f`/],
+    ['\x801eym55h', /invalid bech32 character `\x80`/],
+    [
+      'an84characterslonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1569pvx',
+      /invalid bech32 length: expected between 8 and 90 characters, found 91/
+    ],
+    ['pzry9x0s0muk', /invalid bech32 string: missing the `1` separator/],
+    ['1pzry9x0s0muk', /invalid bech32 HRP: empty HRP/],
+    ['x1b4n0q5v', /invalid bech32 character `b`/],
+    ['li1dgmt3', /invalid bech32 length: expected at least 6 data characters for the checksum, found 5/],
+    ['de1lg7wt\xff', /invalid bech32 character `\xff`/],
+    ['A1G7SGD8', /invalid bech32 checksum/],
+    ['10a06t8', /invalid bech32 HRP: empty HRP/],
+    ['1qzzfhee', /invalid bech32 HRP: empty HRP/],
+    ['A12uEL5L', /invalid bech32 string: mixed case characters/]
+  ]
+
+  for (const [value, error] of invalidStrings) {
+    it(`rejects the BIP-173 string ${JSON.stringify(value)}`, () => {
+      assert.throws(() => bech32Decode(value), { code: 'EINVALIDPARAMETER', message: error })
+    })
+  }
+
+  it('decodes the IOTA addresses', () => {
+    assert.deepStrictEqual(bech32Decode(bech32Address), { hrp: 'iot', version: 1, payload: hexAddress })
+    assert.deepStrictEqual(bech32Decode(bech32Address.toUpperCase()), { hrp: 'iot', version: 1, payload: hexAddress })
+    assert.deepStrictEqual(bech32Decode(testnetAddress), {
+      hrp: 'atoi',
+      version: 1,
+      payload: '96f9de0989e77d0e150e850a5a600e83045fa57419eaf3b20225b763d4e23813'
+    })
+  })
+
+  it('encodes the addresses of any HRP', () => {
+    assert.strictEqual(bech32Encode('iot', `01${hexAddress}`), bech32Address)
+    assert.strictEqual(bech32Encode('IOT', `0x01${hexAddress}`), bech32Address)
+    const privateTangleAddress = bech32Encode('dev', `01${hexAddress}`)
+    assert.strictEqual(privateTangleAddress, 'dev1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqjxhnrfp')
+    assert.strictEqual(privateTangleAddress, hexToBech32(hexAddress, 'dev'))
+    assert.deepStrictEqual(parseAddress(privateTangleAddress, 'dev'), { encoding: 'bech32', hrp: 'dev', bytes })
+  })
+
+  it('rejects an invalid HRP or data', () => {
+    assert.throws(() => bech32Encode('', '01'), /invalid bech32 HRP: empty HRP/)
+    assert.throws(() => bech32Encode('a'.repeat(84), ''), /invalid bech32 HRP: expected at most 83 characters, found 84/)
+    assert.throws(() => bech32Encode('Iot', '01'), /invalid bech32 string: mixed case characters/)
+    assert.throws(() => bech32Encode('i t', '01'), /invalid bech32 character ` `/)
+    assert.throws(
+      () => bech32Encode('iota', `01${hexAddress}${hexAddress}`),
+      /invalid bech32 length: the encoded string would have 115 characters, more than 90/
+    )
+    assert.throws(() => bech32Encode('iot', '0'), /Odd number of digits/)
+  })
+})
+
+describe('Address derivation', () => {
+  const client = new ClientBuilder().node('http://localhost:14265').disableNodeSync().build()
+  const seed = '256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2'
+
+  it('derives the reference testnet addresses', () => {
+    const addresses = client.getAddresses(seed).accountIndex(0).range(0, 3).bech32Hrp('atoi').get()
+    assert.deepStrictEqual(addresses, [
+      'atoi1qxt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupxtmtev5',
+      'atoi1q9nrumvaex24dy0duulp4q07lpa00w20ze6jfd0xly422kdcjxzakc0ht47',
+      'atoi1qx4sfmp605vnj6fxt0sf0cwclffw5hpxjqkf6fthyd74r9nmmu337pw23ua'
+    ])
+  })
+
+  it('includes the internal addresses', () => {
+    const addresses = client.getAddresses(seed).range(0, 1).bech32Hrp('atoi').includeInternal().get()
+    assert.deepStrictEqual(addresses, [
+      ['atoi1qxt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupxtmtev5', false],
+      ['atoi1q9rxpfvaz2peggq6f8k9cj8zfsxuw69e4nszjyv5kuf8yt70t28472g5d7p', true]
+    ])
+  })
+
+  it('defaults to the client HRP', () => {
+    const addresses = client.getAddresses(seed).range(0, 1).get()
+    assert.deepStrictEqual(addresses, ['iot1qxt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupxgecea4'])
+  })
+
+  it('rejects invalid ranges', () => {
+    assert.throws(() => client.getAddresses(seed).range(5, 2).get(), /address range 5\.\.2/)
+    assert.throws(() => client.getAddresses(seed).range(0, 100000).get(), /address range 0\.\.100000/)
+    assert.throws(() => client.getAddresses(seed).range(-1, 2), /invalid address range/)
+  })
+
+  it('rejects the indexes of non-hardened segments', () => {
+    assert.throws(() => client.getAddresses(seed).accountIndex(2 ** 31), /invalid account index 2147483648, expected a non-negative integer below 2\^31/)
+    assert.throws(() => client.getAddresses(seed).accountIndex(-1), /invalid account index -1/)
+    assert.throws(() => client.getBalance(seed).accountIndex(0.5), /invalid account index 0.5/)
+    assert.throws(() => client.message().initialAddressIndex(2 ** 31), /invalid initial address index 2147483648/)
+  })
+
+  it('rejects an invalid seed', () => {
+    assert.throws(() => client.getAddresses('not a seed'), { message: 'invalid seed' })
+  })
+})
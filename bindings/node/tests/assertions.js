@@ -19,8 +19,26 @@ function assertAddress(address) {
   assert.strictEqual(address.startsWith('iot1'), true)
 }
 
+// asserts that `actual` has the same fields and value types as `expected`, recursively
+// arrays are compared by their first item
+function assertSameShape(actual, expected, path = 'value') {
+  if (Array.isArray(expected)) {
+    assert.strictEqual(Array.isArray(actual), true, `${path} should be an array`)
+    if (expected.length > 0 && actual.length > 0) {
+      assertSameShape(actual[0], expected[0], `${path}[0]`)
+    }
+  } else if (expected !== null && typeof expected === 'object') {
+    assert.strictEqual(typeof actual, 'object', `${path} should be an object`)
+    assert.deepStrictEqual(Object.keys(actual).sort(), Object.keys(expected).sort(), `${path} fields`)
+    Object.keys(expected).forEach(key => assertSameShape(actual[key], expected[key], `${path}.${key}`))
+  } else {
+    assert.strictEqual(typeof actual, typeof expected, `${path} type`)
+  }
+}
+
 module.exports = {
   assertMessage,
   assertMessageId,
-  assertAddress
+  assertAddress,
+  assertSameShape
 }
\ No newline at end of file
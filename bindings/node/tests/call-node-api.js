@@ -0,0 +1,81 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const jwt = 'eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiJob3JuZXQifQ.signature'
+
+// a node serving a plugin route to authenticated requests and echoing the posted bodies, recording the requests
+function startNode () {
+  const node = { requests: [] }
+  node.server = http.createServer((req, res) => {
+    let body = ''
+    req.on('data', chunk => { body += chunk })
+    req.on('end', () => {
+      node.requests.push({ method: req.method, url: req.url, authorization: req.headers.authorization })
+      res.setHeader('content-type', 'application/json')
+      const [path] = req.url.split('?')
+      if (req.method === 'GET' && path === '/api/plugins/participation/events') {
+        if (req.headers.authorization !== `Bearer ${jwt}`) {
+          res.statusCode = 401
+          res.end()
+          return
+        }
+        res.end(JSON.stringify({ data: { eventIds: ['event1', 'event2'] } }))
+      } else if (req.method === 'POST' && path === '/api/v1/echo') {
+        res.statusCode = 201
+        res.end(JSON.stringify({ data: { echo: JSON.parse(body) } }))
+      } else {
+        res.statusCode = 404
+        res.end(JSON.stringify({ error: { code: '404', message: 'not found' } }))
+      }
+    })
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Node API passthrough', () => {
+  let node
+  let client
+
+  before(async () => {
+    node = await startNode()
+    client = new ClientBuilder().node(node.url).disableNodeSync().nodeAuth({ jwt }).build()
+  })
+
+  after(() => node.server.close())
+
+  beforeEach(() => {
+    node.requests = []
+  })
+
+  it('gets a route with query parameters and the credentials', async () => {
+    const events = await client.callNodeApi('GET', '/api/plugins/participation/events', { type: 0, limit: 2 })
+    assert.deepStrictEqual(events, { eventIds: ['event1', 'event2'] })
+    assert.deepStrictEqual(node.requests, [{
+      method: 'GET',
+      url: '/api/plugins/participation/events?type=0&limit=2',
+      authorization: `Bearer ${jwt}`
+    }])
+  })
+
+  it('posts a body', async () => {
+    const body = { value: 1, tags: ['a', 'b'] }
+    assert.deepStrictEqual(await client.callNodeApi('POST', '/api/v1/echo', {}, body), { echo: body })
+  })
+
+  it('rejects with EUNAUTHORIZED without the credentials', async () => {
+    const anonymous = new ClientBuilder().node(node.url).disableNodeSync().build()
+    await assert.rejects(anonymous.callNodeApi('GET', '/api/plugins/participation/events'), { code: 'EUNAUTHORIZED' })
+  })
+
+  it('rejects the paths escaping the API', async () => {
+    for (const path of ['/health', '/api/v1/../../health', '/api/v1/%2e%2e/info', '/api/v1/info?limit=1']) {
+      await assert.rejects(client.callNodeApi('GET', path), { code: 'EINVALIDPARAMETER' })
+    }
+    await assert.rejects(client.callNodeApi('FETCH ME', '/api/v1/info'))
+    assert.deepStrictEqual(node.requests, [])
+  })
+})
@@ -0,0 +1,95 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const tipId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+const pendingId = '0000000000000000000000000000000000000000000000000000000000000004'
+// the delay before the abort, and the bound of the rejection after it
+const ABORT_AFTER = 300
+const ABORT_DELAY = 700
+
+// a node where the message stays pending, with a PoW score out of reach of the local PoW
+function startPendingNode () {
+  const node = { metadataRequests: 0 }
+  node.server = http.createServer((req, res) => {
+    res.setHeader('content-type', 'application/json')
+    if (req.url === '/api/v1/info') {
+      res.end(JSON.stringify({
+        data: {
+          name: 'HORNET',
+          version: '0.6.0',
+          isHealthy: true,
+          networkId: 'testnet',
+          bech32HRP: 'iot',
+          minPowScore: 1e12,
+          latestMilestoneIndex: 1,
+          solidMilestoneIndex: 1,
+          pruningIndex: 0,
+          features: []
+        }
+      }))
+    } else if (req.url === '/api/v1/tips') {
+      res.end(JSON.stringify({ data: { tip1MessageId: tipId, tip2MessageId: tipId } }))
+    } else if (req.url === `/api/v1/messages/${pendingId}/metadata`) {
+      node.metadataRequests++
+      res.end(JSON.stringify({
+        data: {
+          messageId: pendingId,
+          parent1MessageId: tipId,
+          parent2MessageId: tipId,
+          isSolid: true,
+          shouldPromote: false,
+          shouldReattach: false
+        }
+      }))
+    } else {
+      res.statusCode = 404
+      res.end()
+    }
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+function abortLater () {
+  const controller = new AbortController()
+  setTimeout(() => controller.abort(), ABORT_AFTER)
+  return { signal: controller.signal, started: Date.now() }
+}
+
+describe('Cancellation', () => {
+  let node
+  let client
+
+  beforeEach(async () => {
+    node = await startPendingNode()
+    client = new ClientBuilder().node(node.url).disableNodeSync().localPow(true).powWorkerCount(2).build()
+  })
+
+  afterEach(() => node.server.close())
+
+  it('cancels a polling loop', async () => {
+    const { signal, started } = abortLater()
+    await assert.rejects(
+      client.retryUntilIncluded(pendingId, { interval: 60000, signal }),
+      { code: 'ECANCELLED' }
+    )
+    assert.ok(Date.now() - started < ABORT_AFTER + ABORT_DELAY)
+    assert.strictEqual(node.metadataRequests, 1)
+
+    // an aborted signal rejects right away
+    await assert.rejects(client.retryUntilIncluded(pendingId, { signal }), { code: 'ECANCELLED' })
+    assert.strictEqual(node.metadataRequests, 1)
+  })
+
+  it('cancels the local PoW', async () => {
+    const { signal, started } = abortLater()
+    await assert.rejects(
+      client.message().index('iota.rs cancellation').data('unreachable PoW score').signal(signal).submit(),
+      { code: 'ECANCELLED' }
+    )
+    assert.ok(Date.now() - started < ABORT_AFTER + ABORT_DELAY)
+  })
+})
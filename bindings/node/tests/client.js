@@ -1,5 +1,5 @@
-const { ClientBuilder } = require('../lib')
-const { assertAddress, assertMessageId, assertMessage } = require('./assertions')
+const { ClientBuilder, generateMnemonic, messageFromBytes, messageToBytes, signTransaction } = require('../lib')
+const { assertAddress, assertMessageId, assertMessage, assertSameShape } = require('./assertions')
 const assert = require('assert')
 
 const seed = 'b3a9bf35521157aa9c4508ab3a9266e210ae297ff5a4584234c4d9e7d01712e3'
@@ -11,13 +11,63 @@ const client = new ClientBuilder()
   .build()
 
 describe('Client', () => {
-  it('gets network info', () => {
+  it('gets network info', async () => {
+    const nodeInfo = await client.getInfo()
     const info = client.networkInfo()
     assert.strictEqual(typeof info, 'object')
     assert.strictEqual(info.localPow, false)
     assert.deepStrictEqual(info.network, { type: 'Testnet' })
-    assert.strictEqual(info.networkId, 'alphanet1')
-    assert.strictEqual(info.minPowScore, 4000)
+    assert.strictEqual(info.networkId, nodeInfo.networkId)
+    assert.strictEqual(info.minPowScore, nodeInfo.minPowScore)
+    assert.strictEqual(info.bech32HRP, nodeInfo.bech32HRP || 'iot')
+    assert.strictEqual(info.powWorkerCount, null)
+
+    assert.strictEqual(client.getNetworkId(), info.networkId)
+    assert.strictEqual(client.getBech32Hrp(), info.bech32HRP)
+  })
+
+  it('refreshes the network info', async () => {
+    const unsyncedClient = new ClientBuilder().node('http://localhost:14265').disableNodeSync().build()
+    // the network info isn't taken from the node without the node syncing
+    assert.strictEqual(unsyncedClient.getNetworkId(), 'alphanet1')
+
+    const nodeInfo = await unsyncedClient.getInfo()
+    const info = await unsyncedClient.refreshNetworkInfo()
+    assert.strictEqual(info.networkId, nodeInfo.networkId)
+    assert.strictEqual(unsyncedClient.getNetworkId(), nodeInfo.networkId)
+  })
+
+  it('sets the PoW worker count', () => {
+    const powClient = new ClientBuilder().node('http://localhost:14265').powWorkerCount(2).build()
+    assert.strictEqual(powClient.networkInfo().powWorkerCount, 2)
+    assert.throws(() => new ClientBuilder().powWorkerCount(0), /the PoW worker count can't be zero/)
+  })
+
+  describe('node pool', () => {
+    const unreachableNode = 'http://localhost:14266/'
+    const poolClient = new ClientBuilder()
+      .nodes(['http://localhost:14265', unreachableNode])
+      .localPow(false)
+      .build()
+
+    it('only keeps the healthy nodes in the pool', async () => {
+      assert.deepStrictEqual(poolClient.syncedNodes(), ['http://localhost:14265/'])
+      await poolClient.getInfo()
+      assert.strictEqual(poolClient.currentNode(), 'http://localhost:14265/')
+    })
+
+    it('pins a node', async () => {
+      poolClient.pinNode(unreachableNode)
+      assert.strictEqual(poolClient.currentNode(), unreachableNode)
+      await assert.rejects(poolClient.getInfo())
+      poolClient.unpinNode()
+      await poolClient.getInfo()
+      assert.strictEqual(poolClient.currentNode(), 'http://localhost:14265/')
+    })
+
+    it('rejects pinning an unknown node', () => {
+      assert.throws(() => poolClient.pinNode('http://localhost:14267'), /isn't one of the client nodes/)
+    })
   })
 
   it('gets tips', async () => {
@@ -28,6 +78,31 @@ describe('Client', () => {
     assertMessageId(tips[1])
   })
 
+  it('gets peers with the node auth', async () => {
+    const unauthorizedClient = new ClientBuilder()
+      .node('http://localhost:14265')
+      .nodeAuth({ jwt: 'invalid' })
+      .build()
+    try {
+      const peers = await unauthorizedClient.getPeers()
+      // the node doesn't protect the peers route
+      assert.strictEqual(Array.isArray(peers), true)
+      for (const peer of peers) {
+        assert.strictEqual(typeof peer.id, 'string')
+        assert.strictEqual(Array.isArray(peer.multiAddresses), true)
+        assert.strictEqual(['known', 'unknown', 'autopeered'].includes(peer.relation), true)
+        assert.strictEqual(typeof peer.connected, 'boolean')
+      }
+    } catch (error) {
+      assert.strictEqual(error.code, 'EUNAUTHORIZED')
+      assert.match(error.message, /configure the node auth/)
+    }
+
+    assert.throws(() => new ClientBuilder().nodeAuth({ username: 'admin' }), {
+      message: 'invalid node auth, expected `{ jwt }` or `{ username, password }`'
+    })
+  })
+
   it('finds addresses', () => {
     const addresses = client.findAddresses(seed)
       .accountIndex(0)
@@ -39,21 +114,36 @@ describe('Client', () => {
   })
 
   it('sends an indexation message with the high level API', async () => {
-    const messageId = await client
+    const { messageId, message } = await client
       .send()
       .index('IOTA.RS TEST')
       .data(new TextEncoder().encode('MESSAGE'))
       .submit()
     assertMessageId(messageId)
+    // the submitted message, with the parents and the nonce chosen by the client
+    assertMessage(message)
+    assert.strictEqual(typeof message.nonce, 'string')
+    assert.deepStrictEqual(message.payload, {
+      type: 2,
+      index: 'IOTA.RS TEST',
+      data: Array.from(new TextEncoder().encode('MESSAGE'))
+    })
+  })
+
+  it('resolves to the message id with the deprecated messageIdOnly option', async () => {
+    const messageId = await client.send().index('IOTA.RS TEST').submit({ messageIdOnly: true })
+    assertMessageId(messageId)
+    const postedMessageId = await client.postMessage({ payload: { type: 2, index: 'IOTA.RS TEST' } }, { messageIdOnly: true })
+    assertMessageId(postedMessageId)
   })
 
   it('sends a value transaction and checks output balance', async () => {
     const depositAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
-    const messageId = await client
+    const { messageId } = await client
       .send()
       .seed(seed)
       .accountIndex(0)
-      .output(depositAddress, 2)
+      .output(depositAddress, 1000000)
       .submit()
     assertMessageId(messageId)
 
@@ -68,7 +158,211 @@ describe('Client', () => {
     }
 
     const depositBalance = await client.getAddressBalance(depositAddress)
-    assert.strictEqual(depositBalance >= 2, true)
+    assert.strictEqual(depositBalance >= 1000000, true)
+
+    const message = await client.getMessage().data(messageId)
+    // the fixture is a transaction message fetched from a node
+    assertSameShape(message, require('./fixtures/transaction-message.json'))
+    assert.strictEqual(message.payload.essence.outputs.some(output => output.address === depositAddress && output.amount === 1000000), true)
+  })
+
+  it('sends a value transfer and gets the submitted message', async () => {
+    const depositAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+    const { messageId, message } = await client
+      .message()
+      .seed(seed)
+      .outputs([{ address: depositAddress, amount: 1000000 }])
+      .indexation({ index: 'IOTA.RS BINDING - NODE.JS', data: 'value transfer' })
+      .submit()
+    assertMessageId(messageId)
+    const essence = message.payload.essence
+    assert.strictEqual(essence.inputs.length > 0, true)
+    assert.strictEqual(essence.outputs.some(output => output.address === depositAddress && output.amount === 1000000), true)
+    // the remainder can't be dust
+    assert.strictEqual(essence.outputs.every(output => output.amount >= 1000000), true)
+    assert.strictEqual(essence.payload.index, 'IOTA.RS BINDING - NODE.JS')
+    // an unlock block per input, the first input of each address is signed and the others reference it
+    assert.strictEqual(message.payload.unlockBlocks.length, essence.inputs.length)
+    assert.strictEqual('signature' in message.payload.unlockBlocks[0], true)
+  })
+
+  it('rejects an unknown transfer output type', () => {
+    const depositAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+    assert.throws(
+      () => client.message().outputs([{ address: depositAddress, amount: 1000000, type: 'Treasury' }]),
+      { name: 'TypeError', message: 'invalid output type: Treasury, expected SignatureLockedSingle or DustAllowance' }
+    )
+  })
+
+  it('finds a value transfer by the index nested in its essence', async () => {
+    const depositAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+    const index = Buffer.from(`IOTA.RS BINDING - ORDER ${Date.now()}`)
+    const data = Buffer.from('paid')
+    const { messageId } = await client
+      .message()
+      .seed(seed)
+      .output(depositAddress, 1000000)
+      .index(index)
+      .data(data)
+      .submit()
+
+    const message = await client.getMessage().data(messageId)
+    const indexation = message.payload.essence.payload
+    assert.strictEqual(indexation.type, 2)
+    assert.strictEqual(indexation.index, index.toString())
+    assert.deepStrictEqual(indexation.data, Array.from(data))
+
+    const messages = await client.findMessages([index])
+    assert(messages.map(message => message.messageId).includes(messageId))
+  })
+
+  it('rejects an invalid value transfer indexation', () => {
+    assert.throws(() => client.message().index('i'.repeat(65)), { message: 'invalid index length 65 bytes, expected between 1 and 64' })
+    assert.throws(() => client.message().index(''), { message: 'invalid index length 0 bytes, expected between 1 and 64' })
+    assert.throws(() => client.message().index(Buffer.from([0xff])), { message: 'invalid index: the index must be valid UTF-8' })
+    assert.throws(() => client.message().data(Buffer.alloc(32769)), {
+      message: 'invalid data length 32769 bytes, exceeds the maximum message length of 32768 bytes'
+    })
+  })
+
+  it('sends a value transfer signed offline', async () => {
+    const depositAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+    // the online machine only knows the account addresses
+    const addresses = client.findAddresses(seed).accountIndex(0).range(0, 20).get()
+      .map(([address, internal], index) => ({ address, addressIndex: Math.floor(index / 2), internal }))
+    const prepared = await client.prepareTransaction([{ address: depositAddress, amount: 1000000 }], { addresses })
+    // the prepared data must survive a round trip through a file
+    const preparedData = JSON.parse(JSON.stringify(prepared))
+    assert.strictEqual(preparedData.accountIndex, 0)
+    assert.strictEqual(preparedData.inputs.length, preparedData.essence.inputs.length)
+    preparedData.inputs.forEach(input => {
+      assertAddress(input.address)
+      assert.strictEqual(addresses.some(({ address }) => address === input.address), true)
+    })
+
+    const otherSeed = '0000000000000000000000000000000000000000000000000000000000000000'
+    assert.throws(() => signTransaction(preparedData, otherSeed), /Failed to sign the transaction/)
+    const payload = signTransaction(preparedData, seed)
+    assert.strictEqual(payload.type, 0)
+    assert.strictEqual(payload.unlockBlocks.length, preparedData.inputs.length)
+
+    const { messageId, message } = await client.submitSignedTransaction(payload)
+    assertMessageId(messageId)
+    assert.deepStrictEqual(message.payload, payload)
+    const fetched = await client.getMessage().data(messageId)
+    assert.deepStrictEqual(fetched.payload, payload)
+  })
+
+  it('has nothing to consolidate on a new seed', async () => {
+    const messageIds = await client.consolidateOutputs(generateMnemonic(), { addressRange: { start: 0, end: 2 } })
+    assert.deepStrictEqual(messageIds, [])
+    await assert.rejects(client.consolidateOutputs('not a seed'), { message: 'invalid seed' })
+  })
+
+  it('rejects preparing a transaction without the seed or the addresses', async () => {
+    const depositAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+    await assert.rejects(
+      client.prepareTransaction([{ address: depositAddress, amount: 1000000 }]),
+      { message: 'invalid options: either the `seed`, the account `addresses` or the `inputCandidates` is required' }
+    )
+    await assert.rejects(
+      client.submitSignedTransaction({ type: 2, index: 'IOTA.RS' }),
+      { message: 'invalid transaction payload: expected a transaction payload (type 0)' }
+    )
+  })
+
+  it('refuses to send a dust output', async () => {
+    const depositAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+    await assert.rejects(
+      client.message().seed(seed).output(depositAddress, 999999).submit(),
+      error => {
+        assert.strictEqual(error.code, 'EDUST')
+        assert(error.message.includes(`DustProtection("${depositAddress}", 999999, 0)`), error.message)
+        assert.deepStrictEqual(error.details, { address: depositAddress, amount: 999999, availableDustOutputs: 0 })
+        return true
+      }
+    )
+  })
+
+  it('rejects a transfer above the balance of the seed', async () => {
+    const depositAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+    await assert.rejects(
+      client.message().seed(generateMnemonic()).output(depositAddress, 1000000).submit(),
+      { code: 'ENOTENOUGHBALANCE', details: { availableAmount: 0, requiredAmount: 1000000 } }
+    )
+  })
+
+  it('selects the inputs with the strategy', async () => {
+    const depositAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+    const message = 'invalid input selection strategy `RandomFirst`: expected `LargestFirst` or `SmallestFirst`'
+    assert.throws(() => client.message().inputSelection('RandomFirst'), { message })
+    await assert.rejects(
+      client.prepareTransaction([{ address: depositAddress, amount: 1000000 }], { seed, inputSelection: 'RandomFirst' }),
+      { message }
+    )
+    await assert.rejects(
+      client.message().seed(generateMnemonic()).inputSelection('SmallestFirst').dustAllowanceInputs()
+        .output(depositAddress, 1000000).submit(),
+      { code: 'ENOTENOUGHBALANCE', details: { availableAmount: 0, requiredAmount: 1000000 } }
+    )
+  })
+
+  it('sets the remainder strategy', async () => {
+    const depositAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+    const message = 'invalid remainder strategy `NewAddress`: expected `ChangeAddress` or `ReuseFirstInputAddress`'
+    assert.throws(() => client.message().remainderStrategy('NewAddress'), { message })
+    // the remainder address must be an address of the client network
+    const testnetAddress = 'atoi1qxt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupxtmtev5'
+    assert.throws(() => client.message().remainderAddress(testnetAddress), { code: 'EINVALIDADDRESS' })
+    await assert.rejects(
+      client.prepareTransaction([{ address: depositAddress, amount: 1000000 }], { seed, remainderAddress: testnetAddress }),
+      { code: 'EINVALIDADDRESS' }
+    )
+    await assert.rejects(
+      client.prepareTransaction([{ address: depositAddress, amount: 1000000 }], {
+        seed,
+        remainderStrategy: 'ReuseFirstInputAddress',
+        remainderAddress: depositAddress
+      }),
+      /the `remainderAddress` sets the remainder strategy/
+    )
+    await assert.rejects(
+      client.message().seed(generateMnemonic()).remainderStrategy('ReuseFirstInputAddress').remainderAddress(depositAddress)
+        .dustRemainderToFirstOutput().output(depositAddress, 1000000).submit(),
+      { code: 'ENOTENOUGHBALANCE' }
+    )
+  })
+
+  it('gets address balances', async () => {
+    const addresses = [
+      'iot1q95jpvtk7cf7c7l9ne50c684jl4n8ya0srm5clpak7qes9ratu0eyf5eyz5',
+      (await client.getUnspentAddress(seed).accountIndex(0).initialAddressIndex(50000).get())[0]
+    ]
+    const balances = await client.getAddressBalances(addresses)
+    assert.strictEqual(Array.isArray(balances), true)
+    assert.strictEqual(balances.length, 2)
+    balances.forEach((balance, index) => {
+      assertAddress(balance.address)
+      assert.strictEqual(balance.address, addresses[index])
+      assert.strictEqual(typeof balance.addressHex, 'string')
+      assert.strictEqual(balance.addressHex.length, 64)
+      assert.strictEqual(typeof balance.balance, 'string')
+      assert.strictEqual(typeof balance.dustAllowed, 'boolean')
+    })
+    assert.strictEqual(balances[1].balance, '0')
+    assert.strictEqual(balances[1].dustAllowed, false)
+  })
+
+  it('dedupes the addresses of a balance request', async () => {
+    const addresses = [
+      'iot1q95jpvtk7cf7c7l9ne50c684jl4n8ya0srm5clpak7qes9ratu0eyf5eyz5',
+      'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj',
+      'iot1q95jpvtk7cf7c7l9ne50c684jl4n8ya0srm5clpak7qes9ratu0eyf5eyz5'
+    ]
+    const balances = await client.getAddressBalances(addresses)
+    assert.deepStrictEqual(balances.map(balance => balance.address), addresses.slice(0, 2))
+
+    await assert.rejects(client.getAddressBalances([addresses[0], 'invalid']))
   })
 
   it('gets an unspent address', async () => {
@@ -80,9 +374,33 @@ describe('Client', () => {
     assert.strictEqual(index, 5)
   })
 
+  it('gets an unspent address skipping the funded addresses', async () => {
+    const [address, index] = await client.getUnspentAddress(seed).accountIndex(0).get()
+    assertAddress(address)
+    const [fundedAddress, fundedIndex] = await client.getUnspentAddress(seed).accountIndex(0).includeFunded().get()
+    assertAddress(fundedAddress)
+    assert(fundedIndex <= index)
+  })
+
+  it('rejects an invalid seed for an unspent address', () => {
+    assert.throws(() => client.getUnspentAddress('invalid'), { message: 'invalid seed' })
+  })
+
   it('gets seed balance', async () => {
     const balance = await client.getBalance(seed).accountIndex(0).initialAddressIndex(50000).get()
-    assert.strictEqual(balance, 0)
+    assert.strictEqual(balance, '0')
+  })
+
+  it('gets seed balance with the used addresses', async () => {
+    const total = await client.getBalance(seed).accountIndex(0).gapLimit(10).get()
+    const { balance, addresses } = await client.getBalance(seed).accountIndex(0).gapLimit(10).includeAddresses().get()
+    assert.strictEqual(balance, total)
+    const sum = addresses.reduce((sum, address) => sum + BigInt(address.balance), BigInt(0))
+    assert.strictEqual(sum.toString(), balance)
+  })
+
+  it('rejects a zero gap limit', () => {
+    assert.throws(() => client.getBalance(seed).gapLimit(0), { message: "the gap limit can't be zero" })
   })
 
   it('get milestone and message', async () => {
@@ -103,14 +421,103 @@ describe('Client', () => {
     assert.strictEqual('messageId' in metadata, true)
     assertMessageId(metadata.messageId)
     assert.strictEqual(metadata.messageId, milestone.messageId)
+    assert.strictEqual(metadata.referencedByMilestoneIndex, 1)
+    assert.strictEqual(metadata.ledgerInclusionState, 'noTransaction')
 
     const raw = await client.getMessage().raw(milestone.messageId)
-    assert.strictEqual(typeof raw, 'string')
+    assert.strictEqual(Buffer.isBuffer(raw), true)
+    assert.strictEqual(messageFromBytes(raw).messageId, milestone.messageId)
+  })
+
+  it('gets the metadata of a new message', async () => {
+    const { messageId } = await client.send().index('IOTA.RS BINDING - METADATA').submit()
+    const metadata = await client.getMessage().metadata(messageId)
+    assert.strictEqual(metadata.messageId, messageId)
+    // the fields the node omits are null
+    for (const field of ['shouldPromote', 'shouldReattach', 'referencedByMilestoneIndex', 'ledgerInclusionState']) {
+      assert.notStrictEqual(metadata[field], undefined)
+    }
+    await assert.rejects(client.getMessage().metadata('invalid'), { message: 'invalid message id: invalid' })
+  })
+
+  it('get milestone utxo changes', async () => {
+    const changes = await client.getMilestoneUtxoChanges(1)
+    assert.strictEqual(changes.index, 1)
+    for (const outputIds of [changes.createdOutputs, changes.consumedOutputs]) {
+      assert.strictEqual(Array.isArray(outputIds), true)
+      assert.strictEqual(outputIds.every(outputId => outputId.length === 68), true)
+    }
+  })
+
+  it('rejects pruned milestones', async () => {
+    const { pruningIndex } = await client.getInfo()
+    if (pruningIndex > 0) {
+      await assert.rejects(client.getMilestone(pruningIndex), {
+        code: 'EPRUNED',
+        details: { milestoneIndex: pruningIndex, pruningIndex }
+      })
+      await assert.rejects(client.getMilestoneUtxoChanges(pruningIndex), { code: 'EPRUNED' })
+    }
+  })
+
+  it('refuses to retry a message referenced by a milestone', async () => {
+    const milestone = await client.getMilestone(1)
+    for (const retry of ['retry', 'promote', 'reattach']) {
+      await assert.rejects(client[retry](milestone.messageId), /is already referenced by the milestone/)
+    }
+    await assert.rejects(client.retry('invalid'), { message: 'invalid message id: invalid' })
+  })
+
+  it('retries a message until it is included', async () => {
+    const milestone = await client.getMilestone(1)
+    // a milestone message is already referenced, so it resolves on the first poll
+    const states = await client.retryUntilIncluded(milestone.messageId, { interval: 100, maxAttempts: 3 })
+    assert.deepStrictEqual(states, [{ messageId: milestone.messageId, inclusionState: 'noTransaction' }])
+    await assert.rejects(client.retryUntilIncluded(milestone.messageId, { maxAttempts: 0 }), /invalid maxAttempts: 0/)
+    await assert.rejects(client.retryUntilIncluded(milestone.messageId, { interval: 0.5 }), /invalid interval: 0.5/)
+    await assert.rejects(client.retryUntilIncluded('invalid'), { message: 'invalid message id: invalid' })
+  })
+
+  it('delivers the confirmation of a referenced message once', async () => {
+    const milestone = await client.getMilestone(1)
+    const confirmations = []
+    const cb = (err, confirmation) => {
+      assert.strictEqual(err, null)
+      confirmations.push(confirmation)
+    }
+    client.on('confirmed', cb, { messageIds: [milestone.messageId, milestone.messageId], interval: 50 })
+    await new Promise(resolve => setTimeout(resolve, 500))
+    client.off('confirmed', cb)
+
+    assert.strictEqual(confirmations.length, 1)
+    assert.strictEqual(confirmations[0].messageId, milestone.messageId)
+    assert.strictEqual(confirmations[0].referencedByMilestoneIndex, 1)
+    assert.notStrictEqual(confirmations[0].ledgerInclusionState, undefined)
+  })
+
+  it('delivers the confirmation of a submitted message', async function () {
+    this.timeout(60000)
+    const listener = client.confirmationListener().submitted().interval(500)
+    const confirmation = new Promise((resolve, reject) => listener.listen((err, confirmation) => {
+      listener.stop()
+      err ? reject(err) : resolve(confirmation)
+    }))
+    const { messageId } = await client.send().index('IOTA.RS BINDING - CONFIRMATION').submit()
+    assert.strictEqual((await confirmation).messageId, messageId)
+  })
+
+  it('rejects invalid confirmation listeners', () => {
+    assert.throws(() => client.on('referenced', () => {}), /unknown event: referenced/)
+    assert.throws(() => client.on('confirmed', () => {}, { messageIds: ['invalid'] }), { message: 'invalid message id: invalid' })
+    assert.throws(() => client.confirmationListener().interval(0), /invalid interval/)
   })
 
   it('get address outputs', async () => {
-    const outputs = await client.getAddressOutputs('iot1q95jpvtk7cf7c7l9ne50c684jl4n8ya0srm5clpak7qes9ratu0eyf5eyz5')
+    const { outputIds: outputs, count, maxResults, truncated } = await client.getAddressOutputs('iot1q95jpvtk7cf7c7l9ne50c684jl4n8ya0srm5clpak7qes9ratu0eyf5eyz5').get()
     assert.strictEqual(Array.isArray(outputs), true)
+    assert.strictEqual(count, outputs.length)
+    assert.strictEqual(typeof maxResults, 'number')
+    assert.strictEqual(truncated, count >= maxResults)
     assert.strictEqual(outputs.length > 0, true)
     assert.strictEqual(typeof outputs[0], 'string')
     assert.strictEqual(outputs[0].length, 68)
@@ -119,23 +526,431 @@ describe('Client', () => {
     assert.strictEqual(typeof output, 'object')
     assert.strict('messageId' in output, true)
     assertMessageId(output.messageId)
+    assertAddress(output.address)
+    assert.strictEqual(output.addressHex.length, 64)
+  })
+
+  it('get address outputs with filters', async () => {
+    const address = 'iot1q95jpvtk7cf7c7l9ne50c684jl4n8ya0srm5clpak7qes9ratu0eyf5eyz5'
+    const { outputIds: unspentOutputIds } = await client.getAddressOutputs(address).get()
+    const { outputIds } = await client.getAddressOutputs(address).includeSpent(true).get()
+    assert.strictEqual(unspentOutputIds.every(outputId => outputIds.includes(outputId)), true)
+
+    const { outputIds: singleOutputIds } = await client.getAddressOutputs(address).outputType('SignatureLockedSingle').get()
+    assert.strictEqual(Array.isArray(singleOutputIds), true)
+
+    const { outputs } = await client.getAddressOutputs(address).resolve().get()
+    assert.strictEqual(outputs.length, unspentOutputIds.length)
+    assert.strictEqual(outputs.every(output => output.address === address && !output.isSpent), true)
+
+    assert.throws(() => client.getAddressOutputs(address).outputType('Treasury'), {
+      message: 'invalid output type: Treasury, expected SignatureLockedSingle or SignatureLockedDustAllowance'
+    })
+    assert.throws(() => client.getAddressOutputs('invalid'))
+  })
+
+  it('finds outputs by id and address', async () => {
+    const address = 'iot1q95jpvtk7cf7c7l9ne50c684jl4n8ya0srm5clpak7qes9ratu0eyf5eyz5'
+    const { outputIds } = await client.getAddressOutputs(address).get()
+    // the output of a transaction that doesn't exist
+    const unknownOutputId = '0'.repeat(64) + '0000'
+
+    const { outputs, errors } = await client.findOutputs({ outputIds: [outputIds[0], unknownOutputId], addresses: [address] })
+    assert.strictEqual(outputs.length, outputIds.length)
+    assert.strictEqual(outputs.every(output => output.address === address), true)
+    assert.strictEqual(errors.length, 1)
+    assert.strictEqual(errors[0].outputId, unknownOutputId)
+    assert.strictEqual(typeof errors[0].error, 'string')
+
+    await assert.rejects(client.findOutputs({ outputIds: [unknownOutputId] }, { atomic: true }))
+  })
+
+  it('rejects invalid output ids', async () => {
+    for (const outputId of ['0'.repeat(64), '0'.repeat(66) + 'zz', '0'.repeat(68) + '00']) {
+      const message = `invalid output id \`${outputId}\`: expected 68 hex characters, the transaction id followed by the output index`
+      await assert.rejects(client.getOutput(outputId), { message })
+      await assert.rejects(client.findOutputs({ outputIds: [outputId] }), { message })
+    }
   })
 
   it('submits an indexation message and reads it', async () => {
-    const indexation = {
+    const payload = {
+      type: 2,
       index: 'IOTA.RS BINDING - NODE.JS',
-      data: new TextEncoder().encode('INDEXATION DATA')
+      data: Array.from(new TextEncoder().encode('INDEXATION DATA'))
     }
-    const messageId = await client.postMessage({
-      payload: indexation
-    })
+    const { messageId, message: submitted } = await client.postMessage({ payload: { ...payload } })
     assertMessageId(messageId)
+    assert.deepStrictEqual(submitted.payload, payload)
 
+    // the fetched payload has the same shape as the submitted DTO
     const message = await client.getMessage().data(messageId)
     assertMessage(message)
-    assert.strictEqual(message.payload.type, 'Indexation')
-    assert.strictEqual(typeof message.payload.data, 'object')
-    assert.deepStrictEqual(message.payload.data, indexation)
+    assert.strictEqual(typeof message.networkId, 'string')
+    assert.strictEqual(typeof message.nonce, 'string')
+    assert.deepStrictEqual(message.payload, payload)
+    // the message resolved by the submit is the one the node serves
+    assert.deepStrictEqual(submitted, message)
+  })
+
+  it('finds messages by indexation key', async () => {
+    const index = 'IOTA.RS BINDING - FIND MESSAGES'
+    const { messageId } = await client.send().index(index).submit()
+
+    const messages = await client.findMessages([index, Buffer.from(index)], [messageId], { limit: 5 })
+    assert(messages.length >= 1 && messages.length <= 5)
+    const ids = messages.map(message => message.messageId)
+    assert(ids.includes(messageId))
+    assert.strictEqual(new Set(ids).size, ids.length)
+    messages.forEach(assertMessage)
+  })
+
+  it('rejects an invalid find messages limit', async () => {
+    await assert.rejects(client.findMessages(['IOTA.RS'], [], { limit: 0 }), { message: 'invalid limit: 0, expected a positive integer' })
+  })
+
+  it('submits a REST-shaped indexation message and reads it in the REST format', async () => {
+    const payload = {
+      type: 2,
+      index: 'IOTA.RS BINDING - NODE.JS',
+      data: Buffer.from('REST INDEXATION DATA').toString('hex')
+    }
+    const { messageId } = await client.postMessage({ payload: { ...payload } })
+    assertMessageId(messageId)
+
+    const message = await client.getMessage().data(messageId, { format: 'rest' })
+    assert.strictEqual(typeof message.parent1MessageId, 'string')
+    assert.strictEqual(typeof message.parent2MessageId, 'string')
+    assert.deepStrictEqual(message.payload, payload)
+  })
+
+  it('packs a fetched message to the bytes of the submitted message', async () => {
+    const { messageId } = await client.postMessage({
+      payload: {
+        type: 2,
+        index: 'IOTA.RS BINDING - NODE.JS',
+        data: Array.from(new TextEncoder().encode('PACKED DATA'))
+      }
+    })
+    const message = await client.getMessage().data(messageId)
+    // the message id is the hash of the bytes served by the node's raw endpoint
+    assert.strictEqual(messageToBytes(message).messageId, messageId)
+  })
+
+  it('submits an indexation message with the legacy untagged payload', async () => {
+    const { messageId } = await client.postMessage({
+      payload: {
+        index: 'IOTA.RS BINDING - NODE.JS',
+        data: new TextEncoder().encode('LEGACY INDEXATION DATA')
+      }
+    })
+    assertMessageId(messageId)
+  })
+
+  it('rejects a message exceeding the maximum message length before the PoW', async () => {
+    await assert.rejects(
+      client.postMessage({ payload: { type: 2, index: 'IOTA.RS BINDING - NODE.JS', data: Array(32700).fill(0) } }),
+      /invalid message length 32819 bytes, exceeds the maximum message length of 32768 bytes/
+    )
+  })
+
+  it('rejects a payload with an unknown type', async () => {
+    await assert.rejects(
+      client.postMessage({ payload: { type: 5, index: 'IOTA.RS BINDING - NODE.JS' } }),
+      /unknown payload type `5`/
+    )
+  })
+
+  it('rejects a transaction payload without unlock blocks', async () => {
+    await assert.rejects(
+      client.postMessage({ payload: { type: 0, essence: { inputs: [], outputs: [] } } }),
+      /invalid transaction payload \(type 0\): missing field `unlockBlocks`/
+    )
+  })
+
+  describe('unlock block validation', () => {
+    const publicKey = 'a'.repeat(64)
+    const signature = 'b'.repeat(128)
+    const transactionWithUnlockBlocks = (unlockBlocks) => ({
+      payload: {
+        type: 0,
+        essence: {
+          inputs: ['0'.repeat(68)],
+          outputs: [{ address: 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj', amount: 1 }]
+        },
+        unlockBlocks
+      }
+    })
+    const transaction = (unlock) => transactionWithUnlockBlocks([{ signature: unlock }])
+
+    it('rejects a short signature', async () => {
+      await assert.rejects(
+        client.postMessage(transaction({ publicKey, signature: signature.slice(2) })),
+        /invalid `signature` length: expected 64 bytes, found 63/
+      )
+    })
+
+    it('rejects a long public key', async () => {
+      await assert.rejects(
+        client.postMessage(transaction({ publicKey: publicKey + 'aa', signature })),
+        /invalid `publicKey` length: expected 32 bytes, found 33/
+      )
+    })
+
+    it('rejects an odd-length hex signature', async () => {
+      await assert.rejects(
+        client.postMessage(transaction({ publicKey, signature: signature.slice(1) })),
+        /invalid `signature` hex/
+      )
+    })
+
+    it('accepts 0x-prefixed hex values', async () => {
+      await assert.rejects(
+        client.postMessage(transaction({ publicKey: '0x' + publicKey, signature: '0x' + signature })),
+        err => !/invalid `(publicKey|signature)`/.test(err.message)
+      )
+    })
+
+    it('rejects an unlock block with both signature and reference', async () => {
+      await assert.rejects(
+        client.postMessage(transactionWithUnlockBlocks([{ signature: { publicKey, signature }, reference: 0 }])),
+        /found both/
+      )
+    })
+
+    it('rejects an unlock block with neither signature nor reference', async () => {
+      await assert.rejects(
+        client.postMessage(transactionWithUnlockBlocks([{}])),
+        /found neither/
+      )
+    })
+
+    it('rejects a forward-referencing unlock block', async () => {
+      await assert.rejects(
+        client.postMessage(transactionWithUnlockBlocks([{ reference: 1 }, { signature: { publicKey, signature } }])),
+        /unlock block 0 references unlock block 1, which isn't before it/
+      )
+    })
+  })
+
+  describe('essence validation', () => {
+    const input = '0'.repeat(68)
+    const address = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+    const otherAddress = 'iot1q95jpvtk7cf7c7l9ne50c684jl4n8ya0srm5clpak7qes9ratu0eyf5eyz5'
+    const transaction = (inputs, outputs) => ({
+      payload: {
+        type: 0,
+        essence: { inputs, outputs },
+        unlockBlocks: []
+      }
+    })
+
+    it('rejects an essence without inputs', async () => {
+      await assert.rejects(
+        client.postMessage(transaction([], [{ address, amount: 1 }])),
+        /invalid inputs count 0, expected between 1 and 126/
+      )
+    })
+
+    it('rejects an essence with too many outputs', async () => {
+      await assert.rejects(
+        client.postMessage(transaction([input], new Array(127).fill({ address, amount: 1 }))),
+        /invalid outputs count 127, expected between 1 and 126/
+      )
+    })
+
+    it('rejects duplicate inputs', async () => {
+      await assert.rejects(
+        client.postMessage(transaction([input, input], [{ address, amount: 1 }])),
+        /duplicate input 1/
+      )
+    })
+
+    it('rejects duplicate output addresses', async () => {
+      await assert.rejects(
+        client.postMessage(transaction([input], [{ address, amount: 1 }, { address, amount: 2 }])),
+        new RegExp(`duplicate output 1 address: \`${address}\``)
+      )
+    })
+
+    it('rejects a zero amount output', async () => {
+      await assert.rejects(
+        client.postMessage(transaction([input], [{ address, amount: 0 }])),
+        /invalid output 0 amount: must be at least 1/
+      )
+    })
+
+    it('rejects a dust allowance output below the dust allowance minimum', async () => {
+      await assert.rejects(
+        client.postMessage(transaction([input], [{ type: 1, address, amount: 999999 }])),
+        /invalid output 0 amount: a dust allowance output must be at least 1000000/
+      )
+      // the address can get a single output besides its dust allowance output
+      await assert.rejects(
+        client.postMessage(transaction([input], [{ type: 1, address, amount: 1000000 }, { address, amount: 1 }, { address, amount: 2 }])),
+        new RegExp(`duplicate output 2 address: \`${address}\``)
+      )
+    })
+
+    it('rejects outputs exceeding the total supply', async () => {
+      await assert.rejects(
+        client.postMessage(transaction([input], [{ address, amount: 2779530283277761 }, { address: otherAddress, amount: 1 }])),
+        /invalid output 1 amount: the outputs total exceeds the supply/
+      )
+    })
+
+    it('rejects outputs overflowing the amount sum', async () => {
+      await assert.rejects(
+        client.postMessage(transaction([input], [{ address, amount: 18000000000000000000 }, { address: otherAddress, amount: 18000000000000000000 }])),
+        /invalid output 0 amount: the outputs total exceeds the supply/
+      )
+    })
+  })
+
+  describe('input validation', () => {
+    const transactionId = 'a'.repeat(64)
+    const address = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+    const transaction = (input) => ({
+      payload: {
+        type: 0,
+        essence: { inputs: [input], outputs: [{ address, amount: 1 }] },
+        unlockBlocks: []
+      }
+    })
+    const notAnInputError = (err) => !/invalid input/.test(err.message)
+
+    it('accepts a structured UTXO input', async () => {
+      await assert.rejects(
+        client.postMessage(transaction({ type: 0, transactionId, transactionOutputIndex: 126 })),
+        notAnInputError
+      )
+      await assert.rejects(
+        client.postMessage(transaction({ transactionId: `0x${transactionId}`, transactionOutputIndex: 0 })),
+        notAnInputError
+      )
+    })
+
+    it('rejects an output index of 127', async () => {
+      await assert.rejects(
+        client.postMessage(transaction({ transactionId, transactionOutputIndex: 127 })),
+        /invalid input 0: .*invalid `transactionOutputIndex` 127, expected at most 126/
+      )
+    })
+
+    it('rejects an invalid transaction id', async () => {
+      await assert.rejects(
+        client.postMessage(transaction({ transactionId: 'z'.repeat(64), transactionOutputIndex: 0 })),
+        /invalid input 0: .*invalid `transactionId` hex/
+      )
+      await assert.rejects(
+        client.postMessage(transaction({ transactionId: 'a'.repeat(62), transactionOutputIndex: 0 })),
+        /invalid input 0: .*invalid `transactionId` length: expected 32 bytes, found 31/
+      )
+    })
+
+    it('accepts the legacy output id string', async () => {
+      await assert.rejects(client.postMessage(transaction(`${transactionId}0000`)), notAnInputError)
+      await assert.rejects(client.postMessage(transaction('not an output id')), /invalid input 0: `not an output id`/)
+    })
+  })
+
+  describe('output address validation', () => {
+    const input = '0'.repeat(68)
+    const testnetAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+    const mainnetAddress = 'iota1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj4rjyt7'
+    const hexAddress = '644eb5594b880f7da82f35902296199a0eb9186999797f661b324f8d754a3a09'
+    const mainnetClient = new ClientBuilder()
+      .node('http://localhost:14265')
+      .bech32Hrp('iota')
+      .localPow(false)
+      .build()
+    const transaction = (address) => ({
+      payload: {
+        type: 0,
+        essence: { inputs: [input], outputs: [{ address, amount: 1 }] },
+        unlockBlocks: []
+      }
+    })
+    const notAnAddressError = (err) => !/address/.test(err.message)
+
+    it('accepts an address with the network HRP', async () => {
+      await assert.rejects(client.postMessage(transaction(testnetAddress)), notAnAddressError)
+      await assert.rejects(mainnetClient.postMessage(transaction(mainnetAddress)), notAnAddressError)
+    })
+
+    it('rejects an address with another network HRP', async () => {
+      await assert.rejects(
+        client.postMessage(transaction(mainnetAddress)),
+        /invalid output 0 address: `iota1.*`: invalid address HRP `iota`, expected `iot`/
+      )
+      await assert.rejects(
+        mainnetClient.postMessage(transaction(testnetAddress)),
+        /invalid output 0 address: `iot1.*`: invalid address HRP `iot`, expected `iota`/
+      )
+    })
+
+    it('accepts a hex address', async () => {
+      await assert.rejects(client.postMessage(transaction(hexAddress)), notAnAddressError)
+      await assert.rejects(mainnetClient.postMessage(transaction(`0x${hexAddress}`)), notAnAddressError)
+    })
+
+    it('rejects an address with a corrupted checksum', async () => {
+      await assert.rejects(
+        client.postMessage(transaction(testnetAddress.slice(0, -1) + 'q')),
+        /invalid output 0 address/
+      )
+    })
+  })
+
+  describe('indexation validation', () => {
+    it('rejects an empty index', async () => {
+      await assert.rejects(
+        client.postMessage({ payload: { type: 2, index: '' } }),
+        /invalid indexation index length 0 bytes, expected between 1 and 64/
+      )
+    })
+
+    it('accepts a 64 bytes index', async () => {
+      const { messageId } = await client.postMessage({ payload: { type: 2, index: 'I'.repeat(64) } })
+      assertMessageId(messageId)
+    })
+
+    it('rejects a 65 bytes index', async () => {
+      await assert.rejects(
+        client.postMessage({ payload: { type: 2, index: 'I'.repeat(65) } }),
+        /invalid indexation index length 65 bytes, expected between 1 and 64/
+      )
+    })
+
+    it('accepts a hex encoded index', async () => {
+      const indexHex = Buffer.from('IOTA.RS BINDING - HEX INDEX').toString('hex')
+      const { messageId } = await client.postMessage({ payload: { type: 2, indexHex } })
+      assertMessageId(messageId)
+    })
+
+    it('rejects a hex encoded index that is not UTF-8', async () => {
+      await assert.rejects(
+        client.postMessage({ payload: { type: 2, indexHex: 'ff00ff' } }),
+        /the decoded index must be valid UTF-8/
+      )
+    })
+  })
+
+  it('rejects transactions spending the treasury', async () => {
+    const message = require('./fixtures/treasury-transaction.json')
+    await assert.rejects(
+      client.postMessage(message),
+      /input 0 is a treasury input, which can't be used in client-submitted transactions/
+    )
+  })
+
+  it('rejects transactions with a treasury output', async () => {
+    const message = JSON.parse(JSON.stringify(require('./fixtures/treasury-transaction.json')))
+    message.payload.essence.inputs = ['0'.repeat(68)]
+    await assert.rejects(
+      client.postMessage(message),
+      /output 1 is a treasury output, which can't be used in client-submitted transactions/
+    )
   })
 
   it('gets info', async () => {
@@ -0,0 +1,111 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const tipId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+
+// a node keeping the posted messages, looked up by their index
+function startTangleNode () {
+  const node = { messages: [], hidden: new Set() }
+  node.server = http.createServer((req, res) => {
+    let body = ''
+    req.on('data', chunk => { body += chunk })
+    req.on('end', () => {
+      res.setHeader('content-type', 'application/json')
+      const url = new URL(req.url, 'http://localhost')
+      const message = url.pathname.match(/^\/api\/v1\/messages\/(\w+)$/)
+      if (url.pathname === '/api/v1/info') {
+        res.end(JSON.stringify({
+          data: {
+            name: 'HORNET',
+            version: '0.6.0',
+            isHealthy: true,
+            networkId: 'testnet',
+            bech32HRP: 'iot',
+            minPowScore: 10,
+            latestMilestoneIndex: 1,
+            solidMilestoneIndex: 1,
+            pruningIndex: 0,
+            features: []
+          }
+        }))
+      } else if (url.pathname === '/api/v1/tips') {
+        res.end(JSON.stringify({ data: { tip1MessageId: tipId, tip2MessageId: tipId } }))
+      } else if (req.method === 'POST' && url.pathname === '/api/v1/messages') {
+        const messageId = (node.messages.length + 1).toString(16).padStart(64, '0')
+        node.messages.push({ messageId, message: JSON.parse(body) })
+        res.statusCode = 201
+        res.end(JSON.stringify({ data: { messageId } }))
+      } else if (url.pathname === '/api/v1/messages') {
+        const index = url.searchParams.get('index')
+        const messageIds = node.messages
+          .filter(({ messageId, message }) => message.payload.index === index && !node.hidden.has(messageId))
+          .map(({ messageId }) => messageId)
+        res.end(JSON.stringify({ data: { index, maxResults: 1000, count: messageIds.length, messageIds } }))
+      } else if (message && node.messages.some(({ messageId }) => messageId === message[1])) {
+        res.end(JSON.stringify({ data: node.messages.find(({ messageId }) => messageId === message[1]).message }))
+      } else {
+        res.statusCode = 404
+        res.end()
+      }
+    })
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+// 100 KB of data
+function data () {
+  return Buffer.from(Array.from({ length: 100000 }, (_, i) => (i * 7 + Math.floor(i / 256)) % 256))
+}
+
+describe('Data messages', () => {
+  let node
+  let client
+
+  beforeEach(async () => {
+    node = await startTangleNode()
+    client = new ClientBuilder().node(node.url).disableNodeSync().localPow(true).powWorkerCount(1).build()
+  })
+
+  afterEach(() => node.server.close())
+
+  it('splits the data into fragment messages and reassembles it', async () => {
+    const { messageIds, contentHash } = await client.sendData('data', data())
+    assert.strictEqual(messageIds.length, 4)
+    assert.strictEqual(contentHash.length, 64)
+    assert.deepStrictEqual(node.messages.map(({ messageId }) => messageId), messageIds)
+    node.messages.forEach(({ message }, sequence) => {
+      const fragment = Buffer.from(message.payload.data, 'hex')
+      assert.strictEqual(fragment.readUInt32LE(1), sequence)
+      assert.strictEqual(fragment.readUInt32LE(5), 4)
+      assert.strictEqual(fragment.slice(9, 41).toString('hex'), contentHash)
+    })
+    // the fragments are found in any order
+    node.messages.reverse()
+    assert.deepStrictEqual(await client.reassembleData('data', contentHash), data())
+  })
+
+  it('chains the fragment messages', async () => {
+    const { messageIds, contentHash } = await client.sendData('chained', data(), { chaining: true, fragmentLength: 25000 })
+    assert.strictEqual(messageIds.length, 4)
+    assert.deepStrictEqual(
+      node.messages.map(({ message }) => message.parent1MessageId),
+      [tipId, ...messageIds.slice(0, 3)]
+    )
+    assert.deepStrictEqual(await client.reassembleData('chained', contentHash), data())
+  })
+
+  it('rejects the missing fragments', async () => {
+    const { messageIds, contentHash } = await client.sendData('data', data(), { fragmentLength: 10000 })
+    await assert.rejects(client.reassembleData('data', '00'.repeat(32)), { code: 'EDATANOTFOUND' })
+    node.hidden.add(messageIds[3])
+    await assert.rejects(client.reassembleData('data', contentHash), {
+      code: 'EMISSINGFRAGMENTS',
+      details: { contentHash, missing: [3], fragmentCount: 10 }
+    })
+    await assert.rejects(client.sendData('data', data(), { fragmentLength: 0 }), { code: 'EINVALIDPARAMETER' })
+  })
+})
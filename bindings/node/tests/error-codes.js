@@ -0,0 +1,79 @@
+const { ClientBuilder, parseAddress, hexToBech32 } = require('../lib')
+const assert = require('assert')
+const net = require('net')
+
+// a local port nothing listens on
+function unusedPort () {
+  return new Promise(resolve => {
+    const server = net.createServer()
+    server.listen(0, '127.0.0.1', () => {
+      const { port } = server.address()
+      server.close(() => resolve(port))
+    })
+  })
+}
+
+describe('Error codes', () => {
+  const client = new ClientBuilder().node('http://localhost:14265').disableNodeSync().localPow(false).build()
+
+  it('sets EINVALIDADDRESS on the thrown address errors', () => {
+    assert.throws(() => client.message().output('iot1invalid', 1000000), {
+      code: 'EINVALIDADDRESS',
+      details: { address: 'iot1invalid' }
+    })
+    assert.throws(() => client.getAddressOutputs('iot1invalid'), {
+      code: 'EINVALIDADDRESS',
+      details: { address: 'iot1invalid' }
+    })
+    assert.throws(() => parseAddress('0x1234'), {
+      code: 'EINVALIDADDRESS',
+      details: { address: '0x1234' },
+      message: /invalid address `0x1234`: invalid address length/
+    })
+    assert.throws(() => hexToBech32('0xinvalid', 'iot'), { code: 'EINVALIDADDRESS' })
+  })
+
+  it('sets EINVALIDPARAMETER with the missing parameter', async () => {
+    await assert.rejects(client.message().data('data').submit(), error => {
+      assert.strictEqual(error.code, 'EINVALIDPARAMETER')
+      assert.deepStrictEqual(error.details, { parameter: 'index' })
+      // the message is unchanged
+      assert(error.message.startsWith('ClientTask error: Client(MissingParameter("index"))'), error.message)
+      return true
+    })
+  })
+
+  it('sets ENODEUNREACHABLE when the node refuses the connection', async () => {
+    const nodeUrl = `http://127.0.0.1:${await unusedPort()}`
+    const unreachableClient = new ClientBuilder().node(nodeUrl).disableNodeSync().build()
+    await assert.rejects(unreachableClient.getInfo(), error => {
+      assert.strictEqual(error.code, 'ENODEUNREACHABLE')
+      assert(error.details.url.startsWith(nodeUrl), error.details.url)
+      return true
+    })
+  })
+
+  it('sets ENODEUNREACHABLE when no node is synced', async () => {
+    const nodeUrl = `http://127.0.0.1:${await unusedPort()}`
+    const unsyncedClient = await new ClientBuilder().node(nodeUrl).buildAsync()
+    await assert.rejects(unsyncedClient.getTips(), { code: 'ENODEUNREACHABLE' })
+  })
+
+  it('sets EVALUEOVERFLOW with the amount beyond the supply', async () => {
+    const seed = '256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2'
+    const address = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+    // the amounts are checked before any node request
+    await assert.rejects(client.message().seed(seed).output(address, 2779530283277762).submit(), {
+      code: 'EVALUEOVERFLOW',
+      details: { amount: 2779530283277762 }
+    })
+  })
+
+  it('leaves the errors without a stable code unchanged', async () => {
+    await assert.rejects(client.getMessage().data('invalid'), error => {
+      assert.strictEqual(error.code, undefined)
+      assert.strictEqual(error.details, undefined)
+      return true
+    })
+  })
+})
@@ -0,0 +1,98 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const delay = 200
+const tipId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+
+// a node that answers the info and tips requests after `delay` milliseconds
+function startSlowNode () {
+  const server = http.createServer((req, res) => {
+    setTimeout(() => {
+      res.setHeader('content-type', 'application/json')
+      if (req.url === '/api/v1/info') {
+        res.end(JSON.stringify({
+          data: {
+            name: 'slow',
+            version: '0.0.0',
+            isHealthy: true,
+            networkId: 'testnet',
+            bech32HRP: 'atoi',
+            minPowScore: 4000,
+            latestMilestoneIndex: 1,
+            solidMilestoneIndex: 1,
+            pruningIndex: 0,
+            features: []
+          }
+        }))
+      } else if (req.url === '/api/v1/tips') {
+        res.end(JSON.stringify({ data: { tip1MessageId: tipId, tip2MessageId: tipId } }))
+      } else {
+        res.statusCode = 404
+        res.end()
+      }
+    }, delay)
+  })
+  return new Promise(resolve => server.listen(0, '127.0.0.1', () => resolve(server)))
+}
+
+// runs `fn` while a timer measures the longest gap between two ticks of the event loop
+async function withTickMonitor (fn) {
+  let last = Date.now()
+  let maxGap = 0
+  const timer = setInterval(() => {
+    const now = Date.now()
+    maxGap = Math.max(maxGap, now - last)
+    last = now
+  }, 5)
+  try {
+    const result = await fn()
+    return { result, maxGap }
+  } finally {
+    clearInterval(timer)
+  }
+}
+
+describe('Event loop', () => {
+  let server
+  let nodeUrl
+
+  before(async () => {
+    server = await startSlowNode()
+    nodeUrl = `http://127.0.0.1:${server.address().port}`
+  })
+
+  after(() => server.close())
+
+  it('keeps ticking while 50 concurrent calls run', async () => {
+    const client = new ClientBuilder().node(nodeUrl).disableNodeSync().build()
+    const start = Date.now()
+    const calls = () => Promise.all(Array.from({ length: 50 }, () => client.getTips()))
+    const { result, maxGap } = await withTickMonitor(calls)
+    const elapsed = Date.now() - start
+    assert.strictEqual(result.length, 50)
+    result.forEach(tips => assert.deepStrictEqual(tips, [tipId, tipId]))
+    // a call blocking the event loop would delay the timer for a whole request
+    assert(maxGap < delay / 2, `the event loop was blocked for ${maxGap}ms`)
+    // the calls run concurrently on the thread pool instead of one after the other
+    assert(elapsed < 50 * delay / 2, `the 50 calls took ${elapsed}ms`)
+  })
+
+  it('syncs the nodes of a client built asynchronously in the background', async () => {
+    const { result: client, maxGap } = await withTickMonitor(() => new ClientBuilder().node(nodeUrl).buildAsync())
+    assert(maxGap < delay / 2, `the event loop was blocked for ${maxGap}ms`)
+    assert.deepStrictEqual(client.syncedNodes(), [`${nodeUrl}/`])
+    assert.strictEqual(client.getBech32Hrp(), 'atoi')
+  })
+
+  it('completes the calls of a garbage collected client', async () => {
+    // the clients are only referenced by the pending calls
+    const calls = Array.from({ length: 10 }, () => new ClientBuilder().node(nodeUrl).disableNodeSync().build())
+      .map(client => client.getTips())
+    if (global.gc) {
+      global.gc()
+    }
+    const results = await Promise.all(calls)
+    results.forEach(tips => assert.deepStrictEqual(tips, [tipId, tipId]))
+  })
+})
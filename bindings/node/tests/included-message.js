@@ -0,0 +1,54 @@
+const { ClientBuilder, parseMessageJson, messageToBytes } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const message = require('./fixtures/rest/transaction.json')
+const includedTransactionId = message.payload.essence.inputs[0].transactionId
+const pendingTransactionId = '0000000000000000000000000000000000000000000000000000000000000001'
+
+// a node serving the fixture as the included message of `includedTransactionId`, 404 for the other transactions
+function startNode () {
+  const server = http.createServer((req, res) => {
+    res.setHeader('content-type', 'application/json')
+    if (req.url === `/api/v1/transactions/${includedTransactionId}/included-message`) {
+      res.end(JSON.stringify({ data: message }))
+    } else {
+      res.statusCode = 404
+      res.end(JSON.stringify({ error: { code: '404', message: 'included message not found' } }))
+    }
+  })
+  return new Promise(resolve => server.listen(0, '127.0.0.1', () => resolve(server)))
+}
+
+describe('Included message', () => {
+  let server
+  let client
+
+  before(async () => {
+    server = await startNode()
+    client = new ClientBuilder().node(`http://127.0.0.1:${server.address().port}`).disableNodeSync().build()
+  })
+
+  after(() => server.close())
+
+  it('gets the message including the transaction', async () => {
+    const included = await client.getIncludedMessage(includedTransactionId)
+    const dto = parseMessageJson(message)
+    assert.deepStrictEqual(included, { messageId: messageToBytes(dto).messageId, ...dto })
+  })
+
+  it('rejects with ENOTINCLUDED if the node has no included message', async () => {
+    await assert.rejects(client.getIncludedMessage(pendingTransactionId), {
+      code: 'ENOTINCLUDED',
+      details: { transactionId: pendingTransactionId }
+    })
+  })
+
+  it('rejects an invalid transaction id', async () => {
+    await assert.rejects(
+      client.getIncludedMessage(includedTransactionId.slice(2)),
+      { message: `invalid transaction id \`${includedTransactionId.slice(2)}\`: expected 64 hex characters` }
+    )
+    await assert.rejects(client.getIncludedMessage('g'.repeat(64)), /expected 64 hex characters/)
+  })
+})
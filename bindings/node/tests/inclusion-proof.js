@@ -0,0 +1,163 @@
+const { ClientBuilder, computeMessageId, messageToBytes, verifyInclusionProof } = require('../lib')
+const assert = require('assert')
+const crypto = require('crypto')
+const http = require('http')
+
+const oldMessageId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+const milestoneIndex = 5
+
+function u32 (value) {
+  const bytes = Buffer.alloc(4)
+  bytes.writeUInt32LE(value)
+  return bytes
+}
+
+function u64 (value) {
+  const bytes = Buffer.alloc(8)
+  bytes.writeBigUInt64LE(BigInt(value))
+  return bytes
+}
+
+// a test coordinator key, the raw public key is the end of its DER encoding
+function coordinatorKey () {
+  const { privateKey, publicKey } = crypto.generateKeyPairSync('ed25519')
+  return { privateKey, publicKey: publicKey.export({ format: 'der', type: 'spki' }).slice(-32).toString('hex') }
+}
+
+function indexationMessage (data, parent1, parent2) {
+  return messageToBytes({ networkId: '1', parent1, parent2, payload: { type: 2, index: 'IOTA.RS', data: Array.from(Buffer.from(data)) }, nonce: '0' })
+}
+
+// packs a milestone message signed by the keys by hand, the DTOs can't build milestone payloads
+function milestoneMessage (parent1, parent2, keys) {
+  const parents = Buffer.concat([Buffer.from(parent1, 'hex'), Buffer.from(parent2, 'hex')])
+  const publicKeys = keys.map(key => Buffer.from(key.publicKey, 'hex'))
+  const essence = Buffer.concat([u32(milestoneIndex), u64(1000), parents, Buffer.alloc(32), Buffer.from([keys.length]), ...publicKeys])
+  // the keys sign the BLAKE2b-256 hash of the essence, computed as a message id
+  const essenceHash = Buffer.from(computeMessageId(essence), 'hex')
+  const signatures = keys.map(key => crypto.sign(null, essenceHash, key.privateKey))
+  const payload = Buffer.concat([u32(1), essence, Buffer.from([keys.length]), ...signatures])
+  const bytes = Buffer.concat([u64(1), parents, u32(payload.length), payload, u64(0)])
+  return { bytes, messageId: computeMessageId(bytes) }
+}
+
+// a node with the milestone 5 referencing the message `deep` through the message `top`
+function startNode (keys) {
+  const node = {}
+  const deep = indexationMessage('deep', oldMessageId, oldMessageId)
+  const top = indexationMessage('top', oldMessageId, deep.messageId)
+  const milestone = milestoneMessage(top.messageId, oldMessageId, keys)
+  const unreferenced = indexationMessage('unreferenced', milestone.messageId, oldMessageId)
+  const messages = {
+    [oldMessageId]: [oldMessageId, oldMessageId, 4],
+    [deep.messageId]: [oldMessageId, oldMessageId, milestoneIndex],
+    [top.messageId]: [oldMessageId, deep.messageId, milestoneIndex],
+    [milestone.messageId]: [top.messageId, oldMessageId, milestoneIndex],
+    [unreferenced.messageId]: [milestone.messageId, oldMessageId, null]
+  }
+  const packed = { [deep.messageId]: deep.bytes, [top.messageId]: top.bytes, [milestone.messageId]: milestone.bytes }
+  node.deep = deep.messageId
+  node.unreferenced = unreferenced.messageId
+  node.server = http.createServer((req, res) => {
+    const [, messageId, endpoint] = req.url.match(/^\/api\/v1\/messages\/([0-9a-f]{64})\/(metadata|raw)$/) || []
+    if (endpoint === 'raw' && packed[messageId]) {
+      res.setHeader('content-type', 'application/octet-stream')
+      res.end(packed[messageId])
+      return
+    }
+    res.setHeader('content-type', 'application/json')
+    let data
+    if (endpoint === 'metadata' && messages[messageId]) {
+      const [parent1MessageId, parent2MessageId, referencedByMilestoneIndex] = messages[messageId]
+      data = { messageId, parent1MessageId, parent2MessageId, isSolid: true, referencedByMilestoneIndex }
+    } else if (req.url === `/api/v1/milestones/${milestoneIndex}`) {
+      data = { index: milestoneIndex, messageId: milestone.messageId, timestamp: 1000 }
+    } else {
+      res.statusCode = 404
+      res.end()
+      return
+    }
+    res.end(JSON.stringify({ data }))
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Inclusion proof', () => {
+  const keys = [coordinatorKey(), coordinatorKey()]
+  // both keys sign every milestone
+  const coordinatorKeys = { publicKeys: keys.map(key => ({ publicKey: key.publicKey, start: 0 })), signatureCount: 2 }
+  let node
+  let client
+
+  before(async () => {
+    node = await startNode(keys)
+    client = new ClientBuilder().node(node.url).disableNodeSync().build()
+  })
+
+  after(() => node.server.close())
+
+  it('proves the inclusion of a message through its parents', async () => {
+    const proof = await client.createInclusionProof(node.deep)
+    assert.strictEqual(proof.messageId, node.deep)
+    assert.strictEqual(proof.milestoneIndex, milestoneIndex)
+    assert.strictEqual(proof.chain.length, 3)
+    verifyInclusionProof(proof, coordinatorKeys)
+    verifyInclusionProof(JSON.stringify(proof), coordinatorKeys)
+  })
+
+  it('locates the broken link of a tampered proof', async () => {
+    const proof = await client.createInclusionProof(node.deep)
+    const tampered = { ...proof, chain: [proof.chain[0], proof.chain[1], indexationMessage('forged', oldMessageId, oldMessageId).bytes.toString('hex')] }
+    assert.throws(() => verifyInclusionProof(tampered, coordinatorKeys), error => {
+      assert.strictEqual(error.code, 'EINVALIDPROOF')
+      assert.strictEqual(error.details.link, 2)
+      assert.match(error.details.reason, /isn't a parent of the previous message/)
+      return true
+    })
+  })
+
+  it('validates the milestone against the coordinator keys', async () => {
+    const proof = await client.createInclusionProof(node.deep)
+    const invalidMilestone = reason => error => {
+      assert.strictEqual(error.code, 'EINVALIDPROOF')
+      assert.strictEqual(error.details.link, 0)
+      assert.match(error.details.reason, reason)
+      return true
+    }
+    // the milestone isn't signed by the mainnet coordinator
+    assert.throws(() => verifyInclusionProof(proof), invalidMilestone(/doesn't sign the milestone 5/))
+    // the second key doesn't sign the milestone 5 anymore
+    const expired = { ...coordinatorKeys, publicKeys: [coordinatorKeys.publicKeys[0], { ...coordinatorKeys.publicKeys[1], end: 5 }] }
+    assert.throws(() => verifyInclusionProof(proof, expired), invalidMilestone(/doesn't sign the milestone 5/))
+    assert.throws(() => verifyInclusionProof(proof, { ...coordinatorKeys, signatureCount: 3 }), invalidMilestone(/2 signatures/))
+  })
+
+  describe('fixture', () => {
+    // the proof of a message referenced by the milestone 600001, packed and signed apart from the bindings
+    const proof = require('./fixtures/inclusion-proof.json')
+    const { coordinatorKeys } = require('./fixtures/milestone-message.json')
+
+    it('verifies the fixture proof', () => {
+      verifyInclusionProof(proof, coordinatorKeys)
+    })
+
+    it('rejects a flipped signature byte of the fixture milestone', () => {
+      const milestone = Buffer.from(proof.chain[0], 'hex')
+      // the last byte of the last signature, before the nonce
+      milestone[milestone.length - 9] ^= 1
+      const tampered = { ...proof, chain: [milestone.toString('hex'), proof.chain[1]] }
+      assert.throws(() => verifyInclusionProof(tampered, coordinatorKeys), error => {
+        assert.strictEqual(error.details.link, 0)
+        assert.match(error.details.reason, /doesn't sign the essence/)
+        return true
+      })
+    })
+  })
+
+  it('rejects a message not referenced by a milestone', async () => {
+    await assert.rejects(client.createInclusionProof(node.unreferenced), { code: 'ENOTREFERENCED', details: { messageId: node.unreferenced } })
+  })
+})
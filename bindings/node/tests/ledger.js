@@ -0,0 +1,31 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+
+// the smoke test runs against the Speculos simulator with the IOTA app and the automatic approval of the prompts,
+// enabled with `LEDGER_SIMULATOR=1`
+const simulator = Boolean(process.env.LEDGER_SIMULATOR)
+const signer = { type: 'LedgerNano', simulator: true }
+
+describe('Ledger Nano signer', () => {
+  const client = new ClientBuilder().node('http://localhost:14265').disableNodeSync().build()
+
+  it('derives the addresses on the simulator', function () {
+    if (!simulator) {
+      this.skip()
+    }
+    const addresses = client.getAddresses({ signer }).accountIndex(0).range(0, 2).includeInternal().get()
+    assert.strictEqual(addresses.length, 4)
+    assert.deepStrictEqual(client.getAddresses({ signer }).accountIndex(0).range(0, 1).get(), [addresses[0][0]])
+  })
+
+  it('rejects a missing simulator', function () {
+    if (simulator) {
+      this.skip()
+    }
+    assert.throws(() => client.getAddresses({ signer }).range(0, 1).get(), { code: 'ELEDGERNOTFOUND' })
+  })
+
+  it('accepts the default options', () => {
+    assert.doesNotThrow(() => client.getAddresses({ signer: { type: 'LedgerNano' } }))
+  })
+})
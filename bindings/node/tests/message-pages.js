@@ -0,0 +1,91 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const messageCount = 250
+const messageId = i => i.toString(16).padStart(64, '0')
+
+// a node holding 250 messages under the `iota.rs` index, the data of the message `i` is `i` as 3 digits
+function startNode () {
+  const node = { fetched: 0 }
+  node.server = http.createServer((req, res) => {
+    res.setHeader('content-type', 'application/json')
+    const fetchedId = req.url.startsWith('/api/v1/messages/') && req.url.slice('/api/v1/messages/'.length)
+    const index = Array.from({ length: messageCount }, (_, i) => i).find(i => messageId(i) === fetchedId)
+    if (req.url === '/api/v1/messages?index=iota.rs') {
+      const messageIds = Array.from({ length: messageCount }, (_, i) => messageId(i))
+      res.end(JSON.stringify({ data: { index: '', maxResults: 1000, count: messageCount, messageIds } }))
+    } else if (index !== undefined) {
+      node.fetched++
+      res.end(JSON.stringify({
+        data: {
+          networkId: '1',
+          parent1MessageId: messageId(0),
+          parent2MessageId: messageId(0),
+          payload: { type: 2, index: 'iota.rs', data: Buffer.from(index.toString().padStart(3, '0')).toString('hex') },
+          nonce: '0'
+        }
+      }))
+    } else {
+      res.statusCode = 404
+      res.end()
+    }
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Message pages', () => {
+  let node
+  let client
+
+  before(async () => {
+    node = await startNode()
+    client = new ClientBuilder().node(node.url).disableNodeSync().build()
+  })
+
+  beforeEach(() => {
+    node.fetched = 0
+  })
+
+  after(() => node.server.close())
+
+  const pageData = page => page.messages.map(message => Buffer.from(message.payload.data).toString())
+
+  it('yields the messages page by page', async () => {
+    const pages = []
+    for await (const page of client.findMessagePages('iota.rs')) {
+      pages.push(page)
+    }
+    assert.deepStrictEqual(pages.map(page => page.nextCursor), ['100', '200', null])
+    assert.deepStrictEqual(pages.map(page => page.hasMore), [true, true, false])
+    assert.deepStrictEqual(pages.map(page => page.messages.length), [100, 100, 50])
+    assert.strictEqual(pageData(pages[2])[0], '200')
+    assert.strictEqual(node.fetched, messageCount)
+  })
+
+  it('stops fetching once the loop breaks', async () => {
+    for await (const page of client.findMessagePages('iota.rs', { pageSize: 100 })) {
+      assert.strictEqual(page.messages.length, 100)
+      break
+    }
+    assert.strictEqual(node.fetched, 100)
+  })
+
+  it('resumes from a cursor and filters the data', async () => {
+    const pages = []
+    for await (const page of client.findMessagePages('iota.rs', { cursor: '100', dataPrefix: '19' })) {
+      pages.push(page)
+    }
+    assert.strictEqual(pages.length, 2)
+    assert.deepStrictEqual(pageData(pages[0]), ['190', '191', '192', '193', '194', '195', '196', '197', '198', '199'])
+    assert.deepStrictEqual(pages[1].messages, [])
+  })
+
+  it('validates the options', async () => {
+    await assert.rejects(client.findMessagePages('iota.rs', { pageSize: 0 }).next(), { message: 'invalid page size: 0, expected a positive integer' })
+    await assert.rejects(client.findMessagePages('iota.rs', { cursor: 'next' }).next(), { code: 'EINVALIDPARAMETER' })
+  })
+})
@@ -0,0 +1,189 @@
+const {
+  parseMessageJson,
+  messageFromBytes,
+  messageToBytes,
+  verifyEd25519Signature,
+  computeMessageId,
+  computeTransactionId
+} = require('../lib')
+const assert = require('assert')
+const fs = require('fs')
+const path = require('path')
+
+// an indexation message packed from `fixtures/rest/indexation.json`
+const indexationHex = fs.readFileSync(path.join(__dirname, 'fixtures/raw/indexation.hex'), 'utf8').trim()
+const indexationId = '0db692542a11a45ef6c96d4774c9ed404bdb2ff13916821afc0fc0c8f294c1d4'
+// the transaction message of `fixtures/rest/transaction.json`, packed
+const transactionHex = fs.readFileSync(path.join(__dirname, 'fixtures/raw/transaction.hex'), 'utf8').trim()
+const transactionMessageId = '3e448717ec15c96bdcdf76b503db93596bfefcb1edeaa92ce9f0a6c8f9075007'
+const transactionId = '7bf5fbf72b73f12e7324cfe8cbe79b22b8631ee9dbcdc0f374e4fd4f9267f8f9'
+
+const fixtures = ['transaction', 'indexation', 'milestone']
+
+describe('Message utilities', () => {
+  for (const name of fixtures) {
+    it(`round-trips the REST ${name} message`, () => {
+      const message = require(`./fixtures/rest/${name}.json`)
+      assert.deepStrictEqual(parseMessageJson(message, { format: 'rest' }), message)
+      assert.deepStrictEqual(parseMessageJson(JSON.stringify(message), { format: 'rest' }), message)
+    })
+  }
+
+  it('converts a REST message to the DTO shape', () => {
+    const message = require('./fixtures/rest/transaction.json')
+    const dto = parseMessageJson(message)
+    assert.strictEqual(dto.parent1, message.parent1MessageId)
+    assert.strictEqual(dto.parent2, message.parent2MessageId)
+    assert.strictEqual(dto.payload.type, 0)
+    assert.strictEqual(dto.payload.essence.outputs[0].address, 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj')
+    assert.strictEqual(
+      parseMessageJson(message, { bech32Hrp: 'iota' }).payload.essence.outputs[0].address,
+      'iota1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj4rjyt7'
+    )
+  })
+
+  it('rejects an unknown format', () => {
+    assert.throws(
+      () => parseMessageJson(require('./fixtures/rest/indexation.json'), { format: 'bytes' }),
+      /unknown message format `bytes`, expected `dto` or `rest`/
+    )
+  })
+
+  it('rejects an invalid message', () => {
+    assert.throws(() => parseMessageJson({ payload: null }), /invalid message JSON/)
+  })
+
+  describe('messageFromBytes', () => {
+    const expected = { messageId: indexationId, ...parseMessageJson(require('./fixtures/rest/indexation.json')) }
+
+    it('unpacks a message from a Buffer', () => {
+      assert.deepStrictEqual(messageFromBytes(Buffer.from(indexationHex, 'hex')), expected)
+      assert.deepStrictEqual(messageFromBytes(new Uint8Array(Buffer.from(indexationHex, 'hex'))), expected)
+    })
+
+    it('unpacks a message from a hex string', () => {
+      assert.deepStrictEqual(messageFromBytes(indexationHex), expected)
+      assert.deepStrictEqual(messageFromBytes(`0x${indexationHex}`), expected)
+    })
+
+    it('rejects truncated bytes', () => {
+      assert.throws(() => messageFromBytes(indexationHex.slice(0, -2)), /invalid message bytes \(truncated or malformed\)/)
+      assert.throws(() => messageFromBytes(Buffer.alloc(0)), /invalid message bytes \(truncated or malformed\)/)
+    })
+
+    it('rejects trailing bytes', () => {
+      assert.throws(() => messageFromBytes(`${indexationHex}00`), /invalid message bytes: 1 trailing bytes after the message/)
+    })
+
+    it('rejects bytes longer than the maximum message length', () => {
+      assert.throws(() => messageFromBytes(Buffer.alloc(32769)), /length 32769 exceeds the maximum message length 32768/)
+    })
+
+    it('rejects invalid hex', () => {
+      assert.throws(() => messageFromBytes('zz'), /invalid message bytes: invalid hex/)
+    })
+  })
+
+  describe('messageToBytes', () => {
+    const message = parseMessageJson(require('./fixtures/rest/indexation.json'))
+
+    it('packs a message', () => {
+      const { bytes, messageId } = messageToBytes({ ...message })
+      assert.ok(Buffer.isBuffer(bytes))
+      assert.strictEqual(bytes.toString('hex'), indexationHex)
+      assert.strictEqual(messageId, indexationId)
+    })
+
+    it('round-trips with messageFromBytes', () => {
+      for (const name of ['transaction', 'indexation']) {
+        const message = parseMessageJson(require(`./fixtures/rest/${name}.json`))
+        const { bytes, messageId } = messageToBytes({ ...message })
+        assert.deepStrictEqual(messageFromBytes(bytes), { messageId, ...message })
+      }
+    })
+
+    it('accepts a numeric zero nonce', () => {
+      const { bytes } = messageToBytes({ ...message, nonce: 0 })
+      assert.strictEqual(bytes.readBigUInt64LE(bytes.length - 8), 0n)
+    })
+
+    for (const field of ['networkId', 'parent1', 'parent2', 'nonce']) {
+      it(`requires the ${field} field`, () => {
+        const incomplete = { ...message }
+        delete incomplete[field]
+        assert.throws(() => messageToBytes(incomplete), new RegExp(`missing \`${field}\``))
+      })
+    }
+
+    describe('message length', () => {
+      const index = 'IOTA.RS BINDING - NODE.JS'
+      // network id, parents, payload length, nonce and payload type, index and data length prefixes
+      const overhead = 8 + 32 + 32 + 4 + 8 + 4 + 2 + index.length + 4
+      const withData = length => ({ ...message, payload: { type: 2, index, data: Array(length).fill(0) } })
+
+      it('accepts a message of exactly 32768 bytes', () => {
+        assert.strictEqual(messageToBytes(withData(32768 - overhead)).bytes.length, 32768)
+      })
+
+      it('rejects a message of 32769 bytes', () => {
+        assert.throws(
+          () => messageToBytes(withData(32769 - overhead)),
+          /invalid message length 32769 bytes, exceeds the maximum message length of 32768 bytes/
+        )
+      })
+    })
+
+    it('fails with the DTO validation errors', () => {
+      assert.throws(
+        () => messageToBytes({ ...message, payload: { type: 2, index: '', data: [] } }),
+        /invalid indexation index length 0 bytes, expected between 1 and 64/
+      )
+      assert.throws(() => messageToBytes({ ...message, nonce: 'nonce' }), /invalid unsigned 64-bit integer `nonce`/)
+    })
+  })
+
+  describe('hashing and signature utilities', () => {
+    // the packed essence of `fixtures/rest/transaction.json`, signed by the key of the secret key bytes 0 to 31
+    const { essence, publicKey, signature } = require('./fixtures/essence-signature.json')
+
+    it('computes the message id of the packed message', () => {
+      assert.strictEqual(computeMessageId(Buffer.from(indexationHex, 'hex')), indexationId)
+      assert.strictEqual(computeMessageId(`0x${transactionHex}`), transactionMessageId)
+      assert.strictEqual(messageFromBytes(transactionHex).messageId, transactionMessageId)
+    })
+
+    it('computes the transaction id of the payload', () => {
+      const { payload } = parseMessageJson(require('./fixtures/rest/transaction.json'))
+      assert.strictEqual(computeTransactionId(payload), transactionId)
+      assert.strictEqual(computeTransactionId(JSON.stringify(payload)), transactionId)
+    })
+
+    it('rejects a payload that is not a transaction', () => {
+      assert.throws(
+        () => computeTransactionId({ type: 2, index: 'IOTA.RS', data: [] }),
+        /invalid transaction payload: expected a payload of type 0/
+      )
+    })
+
+    it('verifies an Ed25519 signature', () => {
+      assert.strictEqual(verifyEd25519Signature(publicKey, signature, Buffer.from(essence, 'hex')), true)
+      assert.strictEqual(verifyEd25519Signature(`0x${publicKey}`, signature, essence), true)
+      // a signature of other bytes or by another key
+      assert.strictEqual(verifyEd25519Signature(publicKey, signature, essence.slice(2)), false)
+      const { unlockBlocks } = require('./fixtures/rest/transaction.json').payload
+      assert.strictEqual(verifyEd25519Signature(unlockBlocks[0].signature.publicKey, signature, essence), false)
+    })
+
+    it('checks the public key and signature lengths', () => {
+      assert.throws(
+        () => verifyEd25519Signature(publicKey.slice(2), signature, essence),
+        /invalid `publicKey` length: expected 32 bytes, found 31/
+      )
+      assert.throws(
+        () => verifyEd25519Signature(publicKey, signature.slice(0, 64), essence),
+        /invalid `signature` length: expected 64 bytes, found 32/
+      )
+      assert.throws(() => verifyEd25519Signature('zz', signature, essence), /invalid `publicKey` hex/)
+    })
+  })
+})
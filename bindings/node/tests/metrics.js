@@ -0,0 +1,71 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const tipId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+
+// a node accepting the messages, its milestones route fails
+function startNode () {
+  const node = {}
+  node.server = http.createServer((req, res) => {
+    req.on('data', () => {})
+    req.on('end', () => {
+      res.setHeader('content-type', 'application/json')
+      if (req.url === '/api/v1/tips') {
+        res.end(JSON.stringify({ data: { tip1MessageId: tipId, tip2MessageId: tipId } }))
+      } else if (req.method === 'POST' && req.url === '/api/v1/messages') {
+        res.statusCode = 201
+        res.end(JSON.stringify({ data: { messageId: tipId } }))
+      } else if (req.url.startsWith('/api/v1/milestones/')) {
+        res.statusCode = 500
+        res.end()
+      } else {
+        res.statusCode = 404
+        res.end()
+      }
+    })
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Metrics', () => {
+  it('counts the requests and the messages', async () => {
+    const node = await startNode()
+    const client = new ClientBuilder()
+      .node(node.url)
+      .disableNodeSync()
+      .networkId('testnet')
+      .minPowScore(10)
+      .bech32Hrp('iot')
+      .localPow(true)
+      .powWorkerCount(1)
+      .build()
+    assert.deepStrictEqual(client.getMetrics().endpoints, [])
+
+    await client.send().index('IOTA.RS METRICS').submit()
+    await client.send().index('IOTA.RS METRICS').submit()
+    await assert.rejects(client.getMilestone(1))
+
+    const metrics = client.getMetrics()
+    assert.deepStrictEqual(
+      metrics.endpoints.map(({ node, method, endpoint, requests }) => ({ node, method, endpoint, requests })),
+      [
+        { node: `${node.url}/`, method: 'GET', endpoint: '/api/v1/milestones/:id', requests: 1 },
+        { node: `${node.url}/`, method: 'GET', endpoint: '/api/v1/tips', requests: 2 },
+        { node: `${node.url}/`, method: 'POST', endpoint: '/api/v1/messages', requests: 2 }
+      ]
+    )
+    metrics.endpoints.forEach(({ requests, latency }) => assert.strictEqual(latency.count, requests))
+    assert.deepStrictEqual(metrics.errors, { status_5xx: 1 })
+    assert.strictEqual(metrics.pow.count, 2)
+    assert.strictEqual(metrics.messagesSubmitted, 2)
+
+    const text = client.getPrometheusMetrics()
+    assert(text.includes('iota_client_messages_submitted_total 2\n'))
+    assert(text.includes('iota_client_request_errors_total{category="status_5xx"} 1\n'))
+    node.server.close()
+  })
+})
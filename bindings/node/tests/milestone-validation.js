@@ -0,0 +1,135 @@
+const { ClientBuilder, computeMessageId, validateMilestone } = require('../lib')
+const assert = require('assert')
+const crypto = require('crypto')
+const http = require('http')
+
+const parentMessageId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+const milestoneIndex = 1
+
+function u32 (value) {
+  const bytes = Buffer.alloc(4)
+  bytes.writeUInt32LE(value)
+  return bytes
+}
+
+function u64 (value) {
+  const bytes = Buffer.alloc(8)
+  bytes.writeBigUInt64LE(BigInt(value))
+  return bytes
+}
+
+// a test coordinator key, the raw public key is the end of its DER encoding
+function coordinatorKey () {
+  const { privateKey, publicKey } = crypto.generateKeyPairSync('ed25519')
+  return { privateKey, publicKey: publicKey.export({ format: 'der', type: 'spki' }).slice(-32).toString('hex') }
+}
+
+// packs a milestone message signed by the keys by hand, the DTOs can't build milestone payloads
+function milestoneMessage (index, keys, { flipped = false } = {}) {
+  const parents = Buffer.concat([Buffer.from(parentMessageId, 'hex'), Buffer.from(parentMessageId, 'hex')])
+  const publicKeys = keys.map(key => Buffer.from(key.publicKey, 'hex'))
+  const essence = Buffer.concat([u32(index), u64(1000), parents, Buffer.alloc(32), Buffer.from([keys.length]), ...publicKeys])
+  // the keys sign the BLAKE2b-256 hash of the essence, computed as a message id
+  const essenceHash = Buffer.from(computeMessageId(essence), 'hex')
+  const signatures = keys.map(key => crypto.sign(null, essenceHash, key.privateKey))
+  if (flipped) {
+    signatures[0][0] ^= 1
+  }
+  const payload = Buffer.concat([u32(1), essence, Buffer.from([keys.length]), ...signatures])
+  const bytes = Buffer.concat([u64(1), parents, u32(payload.length), payload, u64(0)])
+  return { bytes, messageId: computeMessageId(bytes) }
+}
+
+// a node answering the milestone 1 and its message
+function startNode (milestone) {
+  const node = {}
+  node.server = http.createServer((req, res) => {
+    if (req.url === `/api/v1/messages/${milestone.messageId}/raw`) {
+      res.setHeader('content-type', 'application/octet-stream')
+      res.end(milestone.bytes)
+    } else if (req.url === `/api/v1/milestones/${milestoneIndex}`) {
+      res.setHeader('content-type', 'application/json')
+      res.end(JSON.stringify({ data: { index: milestoneIndex, messageId: milestone.messageId, timestamp: 1000 } }))
+    } else {
+      res.statusCode = 404
+      res.end()
+    }
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Milestone validation', () => {
+  const keys = [coordinatorKey(), coordinatorKey()]
+  // the first key signs every milestone, the second one up to the index 10
+  const coordinatorKeys = {
+    publicKeys: [{ publicKey: keys[0].publicKey, start: 0 }, { publicKey: keys[1].publicKey, start: 0, end: 10 }],
+    signatureCount: 2
+  }
+  const invalid = reason => error => {
+    assert.strictEqual(error.code, 'EINVALIDMILESTONE')
+    assert.match(error.details.reason, reason)
+    return true
+  }
+
+  it('validates a milestone signed by the coordinator', () => {
+    const milestone = milestoneMessage(milestoneIndex, keys)
+    validateMilestone(milestone.bytes, coordinatorKeys)
+    validateMilestone(milestone.bytes.toString('hex'), coordinatorKeys)
+    // the test coordinator doesn't sign for the mainnet
+    assert.throws(() => validateMilestone(milestone.bytes), invalid(/doesn't sign the milestone 1/))
+  })
+
+  it('rejects a flipped signature byte', () => {
+    const milestone = milestoneMessage(milestoneIndex, keys, { flipped: true })
+    assert.throws(() => validateMilestone(milestone.bytes, coordinatorKeys), error => {
+      assert.strictEqual(error.details.milestoneIndex, milestoneIndex)
+      return invalid(/doesn't sign the essence/)(error)
+    })
+  })
+
+  describe('fixture', () => {
+    // the milestone 600000 of a coordinator of the mainnet key ranges, packed and signed apart from the bindings
+    const fixture = require('./fixtures/milestone-message.json')
+
+    it('validates the fixture milestone', () => {
+      validateMilestone(fixture.bytes, fixture.coordinatorKeys)
+      assert.strictEqual(computeMessageId(Buffer.from(fixture.bytes, 'hex')), fixture.metadata.messageId)
+      // the keys don't sign for the mainnet
+      assert.throws(() => validateMilestone(fixture.bytes), invalid(/doesn't sign the milestone 600000/))
+    })
+
+    it('rejects a flipped signature byte of the fixture milestone', () => {
+      const bytes = Buffer.from(fixture.bytes, 'hex')
+      // the last byte of the last signature, before the nonce
+      bytes[bytes.length - 9] ^= 1
+      assert.throws(() => validateMilestone(bytes, fixture.coordinatorKeys), invalid(/doesn't sign the essence/))
+    })
+  })
+
+  it('checks the key ranges and the signature count', () => {
+    assert.throws(() => validateMilestone(milestoneMessage(10, keys).bytes, coordinatorKeys), invalid(/doesn't sign the milestone 10/))
+    assert.throws(() => validateMilestone(milestoneMessage(milestoneIndex, [keys[0]]).bytes, coordinatorKeys), invalid(/1 signatures/))
+  })
+
+  it('validates the milestone answered by the node', async () => {
+    const genuine = await startNode(milestoneMessage(milestoneIndex, keys))
+    const flipped = await startNode(milestoneMessage(milestoneIndex, keys, { flipped: true }))
+    const client = url => new ClientBuilder().node(url).disableNodeSync().coordinatorKeys(coordinatorKeys).build()
+    try {
+      const milestone = await client(genuine.url).getValidatedMilestone(milestoneIndex)
+      assert.strictEqual(milestone.index, milestoneIndex)
+      await assert.rejects(client(flipped.url).getValidatedMilestone(milestoneIndex), invalid(/doesn't sign the essence/))
+    } finally {
+      genuine.server.close()
+      flipped.server.close()
+    }
+  })
+
+  it('validates the coordinator keys', () => {
+    assert.throws(() => new ClientBuilder().coordinatorKeys({ publicKeys: [] }), /invalid coordinator keys/)
+    assert.throws(() => new ClientBuilder().coordinatorKeys({ ...coordinatorKeys, signatureCount: 0 }), /invalid coordinator keys/)
+  })
+})
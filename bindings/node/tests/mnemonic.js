@@ -0,0 +1,75 @@
+const { ClientBuilder, generateMnemonic, mnemonicToSeed } = require('../lib')
+const assert = require('assert')
+
+// BIP39 reference vectors, with the `TREZOR` passphrase
+const vectors = [
+  [
+    'abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about',
+    'c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04'
+  ],
+  [
+    'legal winner thank year wave sausage worth useful legal winner thank yellow',
+    '2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607'
+  ],
+  [
+    'letter advice cage absurd amount doctor acoustic avoid letter advice cage above',
+    'd71de856f81a8acc65e6fc851a38d4d7ec216fd0796d0a6827a3ad6ed5511a30fa280f12eb2e47ed2ac03b5c462a0358d18d69fe4f985ec81778c1b370b652a8'
+  ],
+  [
+    'zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong',
+    'ac27495480225222079d7be181583751e86f571027b0497b5b5d11218e0a8a13332572917f0f8e5a589620c6f15b11c61dee327651a14c34e18231052e48c069'
+  ],
+  [
+    `${'abandon '.repeat(23)}art`,
+    'bda85446c68413707090a52022edd26a1c9462295029f2e60cd7c4f2bbd3097170af7a4d73245cafa9c3cca8d561a7c3de6f5d4a10be8ed2a5e608d68f92fcc8'
+  ]
+]
+
+describe('Mnemonic utilities', () => {
+  it('passes the BIP39 vectors', () => {
+    for (const [mnemonic, seed] of vectors) {
+      assert.strictEqual(mnemonicToSeed(mnemonic, 'TREZOR'), seed)
+    }
+  })
+
+  it('generates valid mnemonics', () => {
+    const mnemonic = generateMnemonic()
+    assert.strictEqual(mnemonic.split(' ').length, 24)
+    assert.strictEqual(mnemonicToSeed(mnemonic).length, 128)
+    assert.notStrictEqual(generateMnemonic(), mnemonic)
+    assert.strictEqual(generateMnemonic(12).split(' ').length, 12)
+    assert.throws(() => generateMnemonic(13), /invalid mnemonic word count 13/)
+  })
+
+  it('rejects invalid mnemonics', () => {
+    assert.throws(() => mnemonicToSeed('abandon '.repeat(11) + 'foo'), /invalid mnemonic word/)
+    assert.throws(() => mnemonicToSeed('abandon '.repeat(11) + 'abandon'), { message: 'invalid mnemonic checksum' })
+    assert.throws(() => mnemonicToSeed('abandon about'), /invalid mnemonic word count 2/)
+  })
+
+  it('derives the addresses of a mnemonic', () => {
+    const client = new ClientBuilder().node('http://localhost:14265').disableNodeSync().build()
+    const [mnemonic] = vectors[0]
+    const addresses = client.getAddresses(mnemonic).range(0, 2).get()
+    assert.deepStrictEqual(client.getAddresses(mnemonicToSeed(mnemonic)).range(0, 2).get(), addresses)
+    // the passphrase changes the seed
+    assert.notDeepStrictEqual(client.getAddresses(mnemonicToSeed(mnemonic, 'TREZOR')).range(0, 2).get(), addresses)
+    assert.throws(() => client.getAddresses('abandon '.repeat(11) + 'abandon'), { message: 'invalid seed mnemonic: invalid mnemonic checksum' })
+  })
+
+  // no published IOTA vector covers a BIP39 seed, the addresses are pinned as derived with SLIP-10 from the BIP39 seed
+  // without passphrase of the first mnemonic, the mnemonic of the BIP84 test vectors
+  it('derives the addresses of the BIP39 seed as the SLIP-10 seed', () => {
+    const client = new ClientBuilder().node('http://localhost:14265').disableNodeSync().build()
+    const [mnemonic] = vectors[0]
+    assert.deepStrictEqual(client.getAddresses(mnemonic).range(0, 2).includeInternal().get(), [
+      ['iot1qym9ka8j0jnud47wqxwhxpp0shxyvflp4mkzk7pzn98pvqgzxnjhvu4uya0', false],
+      ['iot1q8m26fc7sxg8zg0qa4ksp76f8wc756wzz607d4avf6hqgrrgpyyesdpyapv', true],
+      ['iot1q8vz77h2tjdu2c5e2l75k587mwfz3fggjznngxj7m5y8sj38czwmcdfvmw0', false],
+      ['iot1qys0tcnpw3tkllvqx7w2zrxgjpm3cmvfp2t0e6v9da2q0zvjhqrqqgfjn8u', true]
+    ])
+    assert.deepStrictEqual(client.getAddresses(mnemonic).accountIndex(1).range(0, 1).get(), [
+      'iot1qxnu43j3pn84p3h6ag3wx6dja80g426c2q4rw7s2yxmkkatctl567uq78za'
+    ])
+  })
+})
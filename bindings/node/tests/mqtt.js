@@ -1,24 +1,378 @@
-const { ClientBuilder } = require('../lib')
+const { ClientBuilder, messageFromBytes } = require('../lib')
 const assert = require('assert')
+const fs = require('fs')
+const http = require('http')
+const net = require('net')
+const path = require('path')
 
 const client = new ClientBuilder().node('http://localhost:14265').build()
+const offlineClient = new ClientBuilder().node('http://localhost:14265').disableNodeSync().build()
+
+const bech32Address = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+const hexAddress = '644eb5594b880f7da82f35902296199a0eb9186999797f661b324f8d754a3a09'
+const messageId = '0db692542a11a45ef6c96d4774c9ed404bdb2ff13916821afc0fc0c8f294c1d4'
+
+describe('MQTT topics', () => {
+  it('accepts valid topics', () => {
+    offlineClient.subscriber()
+      .topic('milestones/latest')
+      .topic(`messages/${messageId}/metadata`)
+      .topic(`outputs/${messageId}0000`)
+      .topic(`addresses/${bech32Address}/outputs`)
+      .topic(`addresses/ed25519/${hexAddress}/outputs`)
+      .topics(['messages/indexation/iota.rs', 'messages/referenced'])
+  })
+
+  it('rejects invalid topics', () => {
+    const invalidTopics = [
+      'milestones',
+      `prefix/messages/${messageId}/metadata`,
+      `messages/${messageId}/metadata/suffix`,
+      `outputs/${messageId}`,
+      `addresses/${bech32Address.slice(0, -1)}q/outputs`,
+      `addresses/${hexAddress}/outputs`,
+      'messages/indexation/'
+    ]
+    for (const topic of invalidTopics) {
+      assert.throws(() => offlineClient.subscriber().topic(topic), { message: `invalid topic: ${topic}` })
+      assert.throws(() => offlineClient.subscriber().topics(['milestones/latest', topic]), { message: `invalid topic: ${topic}` })
+    }
+  })
+})
 
 describe('MQTT', () => {
-  // TODO: test not exiting
-  /* it('subscribes and unsubscribes to the milestones topic', () => {
-    return new Promise(resolve => {
-      client.subscriber().topic('milestones/solid').subscribe((err, data) => {
-        assert.strictEqual(err, null)
-        assert.strictEqual(typeof data, 'object')
-        assert.strictEqual('topic' in data, true)
-        assert.strictEqual(typeof data.topic, 'string')
-        assert.strictEqual('payload' in data, true)
-        assert.strictEqual(typeof data.payload, 'string')
-        client.subscriber().topic('milestones/solid').unsubscribe((err) => {
-          assert.strictEqual(err, null)
-          resolve()
+  it('subscribes and unsubscribes to the milestones topic', () => {
+    return new Promise((resolve, reject) => {
+      const subscriber = client.subscriber().topic('milestones/solid')
+      let events = 0
+      subscriber
+        .subscribe((err, data) => {
+          try {
+            assert.strictEqual(err, null)
+            assert.strictEqual(typeof data, 'object')
+            assert.strictEqual(data.topic, 'milestones/solid')
+            assert.strictEqual(typeof data.payload, 'object')
+            assert.strictEqual(typeof data.payload.index, 'number')
+          } catch (e) {
+            reject(e)
+          }
+          events++
+          subscriber.unsubscribe((err) => {
+            if (err) {
+              reject(err)
+            } else {
+              assert.strictEqual(events, 1)
+              resolve()
+            }
+          })
         })
+        // subscribing twice doesn't duplicate the events
+        .subscribe(() => {})
+    })
+  })
+})
+
+// a minimal MQTT 3.1.1 broker on the default port, which the TCP connections of the client use
+function startBroker () {
+  const sockets = []
+  const server = net.createServer(socket => {
+    socket.topics = []
+    sockets.push(socket)
+    let buffer = Buffer.alloc(0)
+    socket.on('error', () => {})
+    socket.on('data', data => {
+      buffer = Buffer.concat([buffer, data])
+      while (buffer.length >= 2) {
+        let length = 0
+        let position = 1
+        let multiplier = 1
+        let byte
+        do {
+          byte = buffer[position++]
+          length += (byte & 0x7f) * multiplier
+          multiplier *= 128
+        } while (byte & 0x80 && position < buffer.length)
+        if (buffer.length < position + length) {
+          return
+        }
+        const type = buffer[0] >> 4
+        const body = buffer.slice(position, position + length)
+        buffer = buffer.slice(position + length)
+        if (type === 1) {
+          // CONNECT
+          socket.write(Buffer.from([0x20, 2, 0, 0]))
+        } else if (type === 8) {
+          // SUBSCRIBE
+          const codes = []
+          for (let i = 2; i < body.length; i += body.readUInt16BE(i) + 3) {
+            socket.topics.push(body.slice(i + 2, i + 2 + body.readUInt16BE(i)).toString())
+            codes.push(0)
+          }
+          socket.write(Buffer.from([0x90, 2 + codes.length, body[0], body[1], ...codes]))
+        } else if (type === 10) {
+          // UNSUBSCRIBE
+          socket.write(Buffer.from([0xb0, 2, body[0], body[1]]))
+        } else if (type === 12) {
+          // PINGREQ
+          socket.write(Buffer.from([0xd0, 0]))
+        }
+      }
+    })
+  })
+  return new Promise(resolve => server.listen(1883, '127.0.0.1', () => resolve({
+    publish (topic, payload) {
+      const body = Buffer.concat([Buffer.from([topic.length >> 8, topic.length & 0xff]), Buffer.from(topic), Buffer.from(payload)])
+      const length = []
+      let remaining = body.length
+      do {
+        length.push((remaining % 128) | (remaining >= 128 ? 0x80 : 0))
+        remaining = Math.floor(remaining / 128)
+      } while (remaining > 0)
+      for (const socket of sockets.filter(socket => socket.topics.includes(topic))) {
+        socket.write(Buffer.concat([Buffer.from([0x30, ...length]), body]))
+      }
+    },
+    isSubscribed (topic) {
+      return sockets.some(socket => socket.topics.includes(topic))
+    },
+    stop () {
+      sockets.forEach(socket => socket.destroy())
+      return new Promise(resolve => server.close(resolve))
+    }
+  })))
+}
+
+async function waitFor (condition) {
+  while (!condition()) {
+    await new Promise(resolve => setTimeout(resolve, 20))
+  }
+}
+
+describe('MQTT reconnection', () => {
+  it('resubscribes after a broker restart', async () => {
+    let broker = await startBroker()
+    const brokerClient = new ClientBuilder()
+      .node('http://127.0.0.1:14265')
+      .disableNodeSync()
+      .brokerOptions({ use_ws: false })
+      .build()
+    const events = []
+    const payloads = []
+    const subscriber = brokerClient.subscriber()
+      .topic('milestones/latest')
+      .on('connected', () => events.push('connected'))
+      .on('disconnected', () => events.push('disconnected'))
+      .on('reconnecting', ({ attempt, delay }) => {
+        assert.strictEqual(typeof delay, 'number')
+        events.push(`reconnecting ${attempt}`)
       })
+      .subscribe((err, event) => {
+        assert.strictEqual(err, null)
+        payloads.push(event.payload)
+      })
+
+    await waitFor(() => broker.isSubscribed('milestones/latest'))
+    broker.publish('milestones/latest', '{"index":1}')
+    await waitFor(() => payloads.length === 1)
+    assert.deepStrictEqual(payloads, [{ index: 1 }])
+
+    await broker.stop()
+    await waitFor(() => events.includes('reconnecting 1'))
+    broker = await startBroker()
+    await waitFor(() => broker.isSubscribed('milestones/latest'))
+    await waitFor(() => events.lastIndexOf('connected') > 0)
+    assert.deepStrictEqual(events.slice(0, 3), ['connected', 'disconnected', 'reconnecting 1'])
+    broker.publish('milestones/latest', '{"index":2}')
+    await waitFor(() => payloads.length === 2)
+    assert.deepStrictEqual(payloads[1], { index: 2 })
+
+    await new Promise(resolve => subscriber.unsubscribe(resolve))
+    await broker.stop()
+  })
+})
+
+describe('MQTT watchers', () => {
+  const transactionId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+  const output = outputIndex => ({
+    messageId,
+    transactionId,
+    outputIndex,
+    isSpent: false,
+    output: { type: 0, address: { type: 1, address: hexAddress }, amount: 1000000 }
+  })
+  // an indexation message packed from `fixtures/rest/indexation.json`
+  const indexationBytes = Buffer.from(fs.readFileSync(path.join(__dirname, 'fixtures/raw/indexation.hex'), 'utf8').trim(), 'hex')
+  const index = 'IOTA.RS BINDING - NODE.JS'
+
+  let broker
+  let watchClient
+  beforeEach(async () => {
+    broker = await startBroker()
+    watchClient = new ClientBuilder()
+      .node('http://127.0.0.1:14265')
+      .disableNodeSync()
+      .brokerOptions({ use_ws: false })
+      .build()
+  })
+  afterEach(() => broker.stop())
+
+  it('delivers the deduplicated outputs of an address', async () => {
+    const outputs = []
+    const watcher = watchClient.watchAddress(bech32Address, (err, output) => {
+      assert.strictEqual(err, null)
+      outputs.push(output)
+    })
+    const topic = `addresses/ed25519/${hexAddress}/outputs`
+    await waitFor(() => broker.isSubscribed(topic))
+    broker.publish(topic, JSON.stringify(output(0)))
+    // delivered again by the broker, then spent
+    broker.publish(topic, JSON.stringify(output(0)))
+    broker.publish(topic, JSON.stringify({ ...output(0), isSpent: true }))
+    broker.publish(topic, JSON.stringify(output(1)))
+    await waitFor(() => outputs.length === 2)
+    // the events are delivered in order, a duplicate would come before the second output
+    assert.deepStrictEqual(outputs, [0, 1].map(outputIndex => ({
+      messageId,
+      transactionId,
+      outputIndex,
+      isSpent: false,
+      address: bech32Address,
+      addressHex: hexAddress,
+      amount: 1000000
+    })))
+
+    await new Promise(resolve => watcher.unsubscribe(resolve))
+  })
+
+  it('delivers the deduplicated messages of an index', async () => {
+    const messages = []
+    const errors = []
+    const watcher = watchClient.watchIndex(index, (err, message) => {
+      if (err) {
+        errors.push(err)
+      } else {
+        messages.push(message)
+      }
+    })
+    const topic = `messages/indexation/${Buffer.from(index).toString('hex')}`
+    await waitFor(() => broker.isSubscribed(topic))
+    broker.publish(topic, indexationBytes)
+    broker.publish(topic, indexationBytes)
+    broker.publish(topic, Buffer.from([1, 2, 3]))
+    await waitFor(() => errors.length === 1)
+    assert.deepStrictEqual(messages, [messageFromBytes(indexationBytes)])
+    assert.match(errors[0].message, /^invalid message event on messages\/indexation\/[0-9a-f]+: invalid message bytes/)
+
+    await new Promise(resolve => watcher.unsubscribe(resolve))
+  })
+
+  it('rejects an invalid address or index', () => {
+    assert.throws(() => watchClient.watchAddress(`${bech32Address.slice(0, -1)}q`, () => {}))
+    assert.throws(() => watchClient.watchIndex('', () => {}), /invalid index: 0 bytes, expected 1 to 64 bytes/)
+    assert.throws(() => watchClient.watchIndex('i'.repeat(65), () => {}), /invalid index: 65 bytes/)
+  })
+})
+
+describe('Address monitor', () => {
+  const transactionId = messageId
+  const outputId = outputIndex => `${transactionId}${Buffer.from([outputIndex, 0]).toString('hex')}`
+  const output = (outputIndex, isSpent) => ({
+    messageId,
+    transactionId,
+    outputIndex,
+    isSpent,
+    output: { type: 0, address: { type: 1, address: hexAddress }, amount: 1000000 }
+  })
+
+  // a node answering from the `[milestoneIndex, outputIndex, consumed]` changes of the address up to the solid milestone
+  function startLedgerNode (ledger) {
+    const server = http.createServer((req, res) => {
+      res.setHeader('content-type', 'application/json')
+      const changes = ledger.changes.filter(([index]) => index <= ledger.solidIndex)
+      const utxoChanges = req.url.match(/^\/api\/v1\/milestones\/(\d+)\/utxo-changes$/)
+      const outputMatch = req.url.match(/^\/api\/v1\/outputs\/(\w+)$/)
+      let data
+      if (req.url === '/api/v1/info') {
+        data = {
+          name: 'HORNET',
+          version: '0.6.0',
+          isHealthy: true,
+          networkId: 'testnet',
+          bech32HRP: 'iot',
+          minPowScore: 10,
+          latestMilestoneIndex: ledger.solidIndex,
+          solidMilestoneIndex: ledger.solidIndex,
+          pruningIndex: 0,
+          features: []
+        }
+      } else if (req.url === `/api/v1/addresses/${bech32Address}/outputs?include-spent=true`) {
+        const outputIds = changes.filter(([, , consumed]) => !consumed).map(([, outputIndex]) => outputId(outputIndex))
+        data = { address: bech32Address, maxResults: 1000, count: outputIds.length, outputIds }
+      } else if (utxoChanges) {
+        const ids = consumed => ledger.changes
+          .filter(([index, , spent]) => index === parseInt(utxoChanges[1]) && spent === consumed)
+          .map(([, outputIndex]) => outputId(outputIndex))
+        data = { index: parseInt(utxoChanges[1]), createdOutputs: ids(false), consumedOutputs: ids(true) }
+      } else if (outputMatch && changes.some(([, outputIndex]) => outputId(outputIndex) === outputMatch[1])) {
+        const outputIndex = changes.find(([, index]) => outputId(index) === outputMatch[1])[1]
+        data = output(outputIndex, changes.some(([, index, consumed]) => index === outputIndex && consumed))
+      } else {
+        res.statusCode = 404
+        res.end()
+        return
+      }
+      res.end(JSON.stringify({ data }))
     })
-  }) */
+    return new Promise(resolve => server.listen(0, '127.0.0.1', () => resolve(server)))
+  }
+
+  it('catches up the activity missed while disconnected', async () => {
+    const ledger = { solidIndex: 2, changes: [[2, 0, false]] }
+    const node = await startLedgerNode(ledger)
+    let broker = await startBroker()
+    const monitorClient = new ClientBuilder()
+      .node(`http://127.0.0.1:${node.address().port}`)
+      .disableNodeSync()
+      .brokerOptions({ use_ws: false })
+      .build()
+    const activities = []
+    const monitor = monitorClient.monitorAddress(bech32Address, 1, (err, activity) => {
+      assert.strictEqual(err, null)
+      activities.push([activity.output.outputIndex, activity.consumed, activity.milestoneIndex])
+    })
+    const topic = `addresses/${bech32Address}/outputs`
+
+    await waitFor(() => activities.length === 1)
+    assert.deepStrictEqual(activities, [[0, false, 2]])
+    assert.strictEqual(monitor.lastMilestoneIndex(), 2)
+    await waitFor(() => broker.isSubscribed(topic))
+    broker.publish(topic, JSON.stringify(output(1, false)))
+    await waitFor(() => activities.length === 2)
+    assert.deepStrictEqual(activities[1], [1, false, null])
+
+    await broker.stop()
+    ledger.changes.push([3, 1, false], [3, 0, true], [4, 2, false])
+    ledger.solidIndex = 4
+    broker = await startBroker()
+    await waitFor(() => activities.length === 4)
+    // the live output 1 isn't delivered again by the catch-up
+    assert.deepStrictEqual(activities.slice(2), [[0, true, 3], [2, false, 4]])
+    assert.strictEqual(monitor.lastMilestoneIndex(), 4)
+
+    await waitFor(() => broker.isSubscribed(topic))
+    broker.publish(topic, JSON.stringify(output(2, false)))
+    broker.publish(topic, JSON.stringify(output(3, false)))
+    await waitFor(() => activities.length === 5)
+    assert.deepStrictEqual(activities[4], [3, false, null])
+
+    monitor.stop()
+    await waitFor(() => !broker.isSubscribed(topic))
+    await broker.stop()
+    node.close()
+  })
+
+  it('rejects an invalid address', () => {
+    const monitorClient = new ClientBuilder().node('http://127.0.0.1:14265').disableNodeSync().build()
+    assert.throws(() => monitorClient.monitorAddress(`${bech32Address.slice(0, -1)}q`, () => {}))
+  })
 })
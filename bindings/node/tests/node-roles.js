@@ -0,0 +1,81 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const tipId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+
+// a node recording the method and the path of its requests
+function startNode () {
+  const node = { requests: [] }
+  node.server = http.createServer((req, res) => {
+    req.resume()
+    req.on('end', () => {
+      node.requests.push(`${req.method} ${req.url}`)
+      res.setHeader('content-type', 'application/json')
+      if (req.url === '/api/v1/tips') {
+        res.end(JSON.stringify({ data: { tip1MessageId: tipId, tip2MessageId: tipId } }))
+      } else if (req.method === 'POST' && req.url === '/api/v1/messages') {
+        res.statusCode = 201
+        res.end(JSON.stringify({ data: { messageId: tipId } }))
+      } else {
+        res.statusCode = 404
+        res.end()
+      }
+    })
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Node roles', () => {
+  let publicNode
+  let privateNode
+
+  beforeEach(async () => {
+    publicNode = await startNode()
+    privateNode = await startNode()
+  })
+
+  afterEach(() => {
+    publicNode.server.close()
+    privateNode.server.close()
+  })
+
+  const client = (publicOptions, privateOptions = {}) => new ClientBuilder()
+    .node(publicNode.url, publicOptions)
+    .node(privateNode.url, privateOptions)
+    .disableNodeSync()
+    .networkId('testnet')
+    .minPowScore(10)
+    .bech32Hrp('iot')
+    .localPow(true)
+    .powWorkerCount(1)
+    .build()
+
+  it('submits the messages to the nodes allowing it', async () => {
+    const readOnly = client({ allowSubmit: false, remotePow: false, mqtt: false })
+    for (let i = 0; i < 2; i++) {
+      await readOnly.send().index('IOTA.RS ROLES').submit()
+    }
+    const posts = node => node.requests.filter(request => request === 'POST /api/v1/messages').length
+    assert.strictEqual(posts(publicNode), 0)
+    assert.strictEqual(posts(privateNode), 2)
+    // the tips are read from both nodes
+    assert(publicNode.requests.includes('GET /api/v1/tips'))
+    assert(privateNode.requests.includes('GET /api/v1/tips'))
+  })
+
+  it('fails without a node allowing the submission', async () => {
+    const readOnly = client({ allowSubmit: false }, { allowSubmit: false })
+    await assert.rejects(readOnly.send().index('IOTA.RS ROLES').submit(), error => {
+      assert.strictEqual(error.code, 'ENODEUNREACHABLE')
+      return true
+    })
+  })
+
+  it('throws on invalid node options', () => {
+    assert.throws(() => new ClientBuilder().node(publicNode.url, { primary: true }), /invalid node options/)
+  })
+})
@@ -0,0 +1,104 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const syncInterval = 200
+
+// a node at the milestone 100, or 10 milestones behind while `node.stale` is set
+function startNode () {
+  const node = { stale: false, infoRequests: 0 }
+  node.server = http.createServer((req, res) => {
+    res.setHeader('content-type', 'application/json')
+    if (req.url === '/api/v1/info') {
+      node.infoRequests++
+      const milestoneIndex = node.stale ? 90 : 100
+      res.end(JSON.stringify({
+        data: {
+          name: 'mock',
+          version: '0.0.0',
+          isHealthy: true,
+          networkId: 'testnet',
+          minPowScore: 4000,
+          latestMilestoneIndex: milestoneIndex,
+          solidMilestoneIndex: milestoneIndex,
+          pruningIndex: 0,
+          features: []
+        }
+      }))
+    } else {
+      res.statusCode = 404
+      res.end()
+    }
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}/`
+    resolve(node)
+  }))
+}
+
+const sleep = ms => new Promise(resolve => setTimeout(resolve, ms))
+
+describe('Node sync', () => {
+  let nodes
+
+  before(async () => {
+    nodes = await Promise.all([startNode(), startNode()])
+  })
+
+  after(() => nodes.forEach(node => node.server.close()))
+
+  it('evicts a stale node and readmits it once it caught up', async () => {
+    const [freshNode, staleNode] = nodes
+    const client = await new ClientBuilder()
+      .nodes(nodes.map(node => node.url))
+      .nodeSyncInterval(syncInterval)
+      .buildAsync()
+    assert.strictEqual(client.syncedNodes().length, 2)
+
+    staleNode.stale = true
+    await sleep(syncInterval * 3)
+    assert.deepStrictEqual(client.syncedNodes(), [freshNode.url])
+    const status = client.nodeSyncStatus().find(status => status.url === staleNode.url)
+    assert.strictEqual(status.synced, false)
+    assert.strictEqual(status.latestMilestoneIndex, 90)
+    assert.strictEqual(status.solidMilestoneIndex, 90)
+    assert(status.lastCheck > Date.now() - syncInterval * 3, `outdated last check ${status.lastCheck}`)
+
+    staleNode.stale = false
+    await sleep(syncInterval * 3)
+    assert.strictEqual(client.syncedNodes().length, 2)
+    assert(client.nodeSyncStatus().every(status => status.synced))
+  })
+
+  it('caches the node info shared with the node syncing', async () => {
+    const [firstNode, secondNode] = await Promise.all([startNode(), startNode()])
+    try {
+      const client = await new ClientBuilder()
+        .nodes([firstNode.url, secondNode.url])
+        .buildAsync()
+      for (let i = 0; i < 50; i++) {
+        const info = await client.getInfo()
+        assert.strictEqual(info.name, 'mock')
+      }
+      assert.strictEqual(firstNode.infoRequests, 1)
+      assert.strictEqual(secondNode.infoRequests, 1)
+
+      await client.refreshNodeInfo()
+      assert.strictEqual(firstNode.infoRequests + secondNode.infoRequests, 3)
+    } finally {
+      firstNode.server.close()
+      secondNode.server.close()
+    }
+  })
+
+  it('reports every node synced with the node syncing disabled', () => {
+    const client = new ClientBuilder().node(nodes[0].url).disableNodeSync().build()
+    assert.deepStrictEqual(client.nodeSyncStatus(), [{
+      url: nodes[0].url,
+      synced: true,
+      latestMilestoneIndex: null,
+      solidMilestoneIndex: null,
+      lastCheck: null
+    }])
+  })
+})
@@ -0,0 +1,114 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const tipId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+
+// a node without remote PoW recording the posted messages
+function startNode () {
+  const node = { messages: [] }
+  node.server = http.createServer((req, res) => {
+    let body = ''
+    req.on('data', chunk => { body += chunk })
+    req.on('end', () => {
+      res.setHeader('content-type', 'application/json')
+      if (req.url === '/api/v1/info') {
+        res.end(JSON.stringify({
+          data: {
+            name: 'HORNET',
+            version: '0.6.0',
+            isHealthy: true,
+            networkId: 'testnet',
+            bech32HRP: 'atoi',
+            minPowScore: 10,
+            latestMilestoneIndex: 1,
+            solidMilestoneIndex: 1,
+            pruningIndex: 0,
+            features: []
+          }
+        }))
+      } else if (req.url === '/api/v1/tips') {
+        res.end(JSON.stringify({ data: { tip1MessageId: tipId, tip2MessageId: tipId } }))
+      } else if (req.method === 'POST' && req.url === '/api/v1/messages') {
+        node.messages.push(JSON.parse(body))
+        res.statusCode = 201
+        res.end(JSON.stringify({ data: { messageId: tipId } }))
+      } else {
+        res.statusCode = 404
+        res.end()
+      }
+    })
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Nonce provider', () => {
+  let node
+
+  before(async () => {
+    node = await startNode()
+  })
+
+  after(() => node.server.close())
+
+  function client (provider, { timeout, fallback = false } = {}) {
+    return new ClientBuilder()
+      .node(node.url)
+      .disableNodeSync()
+      .localPow(false)
+      .powWorkerCount(1)
+      .nonceProvider(provider, { timeout })
+      .localPowFallback(fallback)
+      .build()
+  }
+
+  it('submits the messages with the nonce of the provider', async () => {
+    const requests = []
+    const provider = async (bytes, targetScore) => {
+      requests.push({ bytes, targetScore })
+      return BigInt('18446744073709551615')
+    }
+    const { message } = await client(provider).send().index('IOTA.RS NONCE PROVIDER').submit()
+    assert.strictEqual(requests.length, 1)
+    assert(Buffer.isBuffer(requests[0].bytes))
+    assert.strictEqual(requests[0].targetScore, 10)
+    assert.strictEqual(node.messages.pop().nonce, '18446744073709551615')
+    assert.strictEqual(message.nonce, '18446744073709551615')
+  })
+
+  it('rejects the failures of the provider', async () => {
+    const failing = client(() => Promise.reject(new Error('the PoW service is down')))
+    await assert.rejects(failing.send().index('IOTA.RS NONCE PROVIDER').submit(), {
+      code: 'ENONCEPROVIDER',
+      message: /the PoW service is down/
+    })
+    const invalid = client(() => '-1')
+    await assert.rejects(invalid.send().index('IOTA.RS NONCE PROVIDER').submit(), {
+      code: 'ENONCEPROVIDER',
+      message: /invalid nonce `-1`/
+    })
+  })
+
+  it('rejects a provider timing out', async () => {
+    const timingOut = client(() => new Promise(() => {}), { timeout: 200 })
+    await assert.rejects(timingOut.send().index('IOTA.RS NONCE PROVIDER').submit(), {
+      code: 'ENONCEPROVIDER',
+      message: /no nonce after 200 ms/
+    })
+  })
+
+  it('falls back to the local PoW', async () => {
+    const failing = client(() => { throw new Error('the PoW service is down') }, { fallback: true })
+    await failing.send().index('IOTA.RS NONCE PROVIDER').submit()
+    assert.strictEqual(node.messages.length, 1)
+    node.messages.pop()
+  })
+
+  it('validates the provider', () => {
+    assert.throws(() => new ClientBuilder().nonceProvider('http://localhost:8080'), /the nonce provider must be a function/)
+    assert.throws(() => new ClientBuilder().nonceProvider(() => 0, { timeout: 0 }), /the nonce provider timeout can't be zero/)
+  })
+})
@@ -0,0 +1,159 @@
+const {
+  ClientBuilder,
+  bech32ToHex,
+  isAddressValid,
+  messageFromBytes,
+  messageToBytes,
+  signTransaction
+} = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const seed = '256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2'
+const tipId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+const testnetId = '8342982141227064571'
+const depositAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+
+// a node holding the unspent outputs `{ outputId, address, amount }`, recording the posted messages
+function startUtxoNode (outputs) {
+  const node = { messages: [] }
+  node.server = http.createServer((req, res) => {
+    let body = ''
+    req.on('data', chunk => { body += chunk })
+    req.on('end', () => {
+      res.setHeader('content-type', 'application/json')
+      const [path, query] = req.url.split('?')
+      const addressOutputs = path.match(/^\/api\/v1\/addresses\/(\w+)\/outputs$/)
+      const addressBalance = path.match(/^\/api\/v1\/addresses\/(\w+)$/)
+      const output = path.match(/^\/api\/v1\/outputs\/(\w+)$/)
+      if (path === '/api/v1/info') {
+        res.end(JSON.stringify({
+          data: {
+            name: 'HORNET',
+            version: '0.6.0',
+            isHealthy: true,
+            networkId: 'testnet',
+            bech32HRP: 'iot',
+            minPowScore: 10,
+            latestMilestoneIndex: 1,
+            solidMilestoneIndex: 1,
+            pruningIndex: 0,
+            features: []
+          }
+        }))
+      } else if (path === '/api/v1/tips') {
+        res.end(JSON.stringify({ data: { tip1MessageId: tipId, tip2MessageId: tipId } }))
+      } else if (addressOutputs) {
+        // all the outputs are signature locked single outputs
+        const outputIds = query === 'type=1'
+          ? []
+          : outputs.filter(({ address }) => address === addressOutputs[1]).map(({ outputId }) => outputId)
+        res.end(JSON.stringify({
+          data: { address: addressOutputs[1], maxResults: 1000, count: outputIds.length, outputIds }
+        }))
+      } else if (addressBalance) {
+        const held = outputs.filter(({ address }) => address === addressBalance[1])
+        res.end(JSON.stringify({
+          data: {
+            addressType: 1,
+            address: addressBalance[1],
+            maxResults: 1000,
+            count: held.length,
+            balance: held.reduce((balance, { amount }) => balance + amount, 0),
+            dustAllowed: false
+          }
+        }))
+      } else if (output && outputs.some(({ outputId }) => outputId === output[1])) {
+        const { outputId, address, amount } = outputs.find(({ outputId }) => outputId === output[1])
+        res.end(JSON.stringify({
+          data: {
+            messageId: tipId,
+            transactionId: outputId.slice(0, 64),
+            outputIndex: parseInt(outputId.slice(64), 16),
+            isSpent: false,
+            output: { type: 0, address: { type: 1, address: bech32ToHex(address).hex }, amount }
+          }
+        }))
+      } else if (req.method === 'POST' && path === '/api/v1/messages') {
+        node.messages.push(JSON.parse(body))
+        res.statusCode = 201
+        res.end(JSON.stringify({ data: { messageId: tipId } }))
+      } else {
+        res.statusCode = 404
+        res.end()
+      }
+    })
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+function offlineClient () {
+  return new ClientBuilder().offline().networkId('testnet').bech32Hrp('iot').build()
+}
+
+describe('Offline client', () => {
+  let node
+  let onlineClient
+  let addresses
+
+  before(async () => {
+    addresses = offlineClient().getAddresses(seed).accountIndex(0).range(0, 2).get()
+    node = await startUtxoNode([
+      { outputId: `${'01'.repeat(32)}0000`, address: addresses[0], amount: 2000000 },
+      { outputId: `${'02'.repeat(32)}0000`, address: addresses[1], amount: 3000000 }
+    ])
+    onlineClient = new ClientBuilder().node(node.url).localPow(true).powWorkerCount(1).build()
+  })
+
+  after(() => node.server.close())
+
+  it('prepares and signs a transaction offline', async () => {
+    // the online machine exports the unspent outputs of the account addresses
+    const inputCandidates = await onlineClient.getInputCandidates({
+      addresses: addresses.map((address, addressIndex) => ({ address, addressIndex }))
+    })
+    assert.strictEqual(inputCandidates.length, 2)
+    const exported = JSON.stringify(inputCandidates)
+
+    // the offline machine derives and validates the addresses, prepares and signs the transaction
+    const client = offlineClient()
+    assert.strictEqual(isAddressValid(depositAddress, 'iot'), true)
+    const prepared = await client.prepareTransaction([{ address: depositAddress, amount: 4000000 }], {
+      inputCandidates: JSON.parse(exported),
+      remainderStrategy: 'ReuseFirstInputAddress'
+    })
+    assert.strictEqual(prepared.inputs.length, 2)
+    const payload = signTransaction(JSON.parse(JSON.stringify(prepared)), seed)
+    assert.strictEqual(payload.unlockBlocks.length, 2)
+
+    // the online machine submits the signed payload
+    const { message } = await onlineClient.submitSignedTransaction(payload)
+    assert.deepStrictEqual(message.payload, payload)
+    assert.strictEqual(node.messages.length, 1)
+    assert.strictEqual(node.messages[0].networkId, testnetId)
+
+    // the message id and the bytes of the message don't need a node either
+    const { bytes, messageId } = messageToBytes(message)
+    const unpacked = messageFromBytes(bytes)
+    assert.strictEqual(unpacked.messageId, messageId)
+    assert.deepStrictEqual(unpacked.payload, payload)
+  })
+
+  it('fails the network operations', async () => {
+    const client = offlineClient()
+    const error = { code: 'EOFFLINE', message: /offline client: network operation not available/ }
+    await assert.rejects(client.getInfo(), error)
+    await assert.rejects(client.getTips(), error)
+    await assert.rejects(client.send().index('IOTA.RS OFFLINE').submit(), error)
+    await assert.rejects(client.prepareTransaction([{ address: depositAddress, amount: 1000000 }], { seed }), error)
+  })
+
+  it('validates the offline client options', () => {
+    assert.throws(() => new ClientBuilder().offline().node('http://localhost:14265').bech32Hrp('iot').build(),
+      /nodes of an offline client/)
+    assert.throws(() => new ClientBuilder().offline().build(), /bech32 HRP of the offline client/)
+  })
+})
@@ -0,0 +1,102 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const tipId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+// the known messages, in the protocol order
+const parent1 = '1111111111111111111111111111111111111111111111111111111111111111'
+const parent2 = '2222222222222222222222222222222222222222222222222222222222222222'
+const unknownParent = '3333333333333333333333333333333333333333333333333333333333333333'
+
+// a node knowing the parents, recording the posted messages and counting the tips requests
+function startNode () {
+  const node = { tipsRequests: 0, messages: [] }
+  const known = messageId => messageId === parent1 || messageId === parent2
+  node.server = http.createServer((req, res) => {
+    let body = ''
+    req.on('data', chunk => { body += chunk })
+    req.on('end', () => {
+      res.setHeader('content-type', 'application/json')
+      const metadata = req.url.match(/^\/api\/v1\/messages\/([0-9a-f]+)\/metadata$/)
+      if (req.url === '/api/v1/tips') {
+        node.tipsRequests++
+        res.end(JSON.stringify({ data: { tip1MessageId: tipId, tip2MessageId: tipId } }))
+      } else if (metadata && known(metadata[1])) {
+        res.end(JSON.stringify({
+          data: { messageId: metadata[1], parent1MessageId: tipId, parent2MessageId: tipId, isSolid: true }
+        }))
+      } else if (req.method === 'POST' && req.url === '/api/v1/messages') {
+        const message = JSON.parse(body)
+        if (known(message.parent1MessageId) && known(message.parent2MessageId)) {
+          node.messages.push(message)
+          res.statusCode = 201
+          res.end(JSON.stringify({ data: { messageId: tipId } }))
+        } else {
+          res.statusCode = 400
+          res.end(JSON.stringify({ error: { code: '400', message: 'invalid parameter, error: parents not found' } }))
+        }
+      } else {
+        res.statusCode = 404
+        res.end()
+      }
+    })
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Custom parents', () => {
+  let node
+  let client
+
+  beforeEach(async () => {
+    node = await startNode()
+    client = new ClientBuilder()
+      .node(node.url)
+      .disableNodeSync()
+      .networkId('testnet')
+      .minPowScore(10)
+      .bech32Hrp('iot')
+      .localPow(true)
+      .powWorkerCount(1)
+      .build()
+  })
+
+  afterEach(() => node.server.close())
+
+  it('submits the message with the parents in the protocol order', async () => {
+    await client.send().index('IOTA.RS PARENTS').parents([parent2, parent1]).submit()
+    assert.strictEqual(node.tipsRequests, 0)
+    assert.strictEqual(node.messages.length, 1)
+    assert.strictEqual(node.messages[0].parent1MessageId, parent1)
+    assert.strictEqual(node.messages[0].parent2MessageId, parent2)
+  })
+
+  it('rejects the parents unknown to the node', async () => {
+    await assert.rejects(client.send().index('IOTA.RS PARENTS').parents([unknownParent, parent1]).submit(), error => {
+      assert.strictEqual(error.code, 'EUNKNOWNPARENTS')
+      assert.deepStrictEqual(error.details, { parents: [unknownParent] })
+      return true
+    })
+    assert.strictEqual(node.messages.length, 0)
+  })
+
+  it('rejects the message the node refuses without the verification', async () => {
+    const sender = client.send().index('IOTA.RS PARENTS').parents([unknownParent, parent1], { skipVerification: true })
+    await assert.rejects(sender.submit(), error => {
+      assert.strictEqual(error.code, 'EUNKNOWNPARENTS')
+      assert.deepStrictEqual(error.details.parents, [parent1, unknownParent])
+      assert(error.details.answer.includes('parents not found'))
+      return true
+    })
+  })
+
+  it('throws on invalid parents', () => {
+    assert.throws(() => client.send().parents([]), /invalid parents: 0 message ids, expected between 1 and 2/)
+    assert.throws(() => client.send().parents([parent1, parent2, tipId]), /invalid parents: 3 message ids/)
+    assert.throws(() => client.send().parents([parent1, parent1]), /duplicate parent message id/)
+    assert.throws(() => client.send().parents(['0x11']), /invalid parent message id: 0x11/)
+  })
+})
@@ -0,0 +1,108 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const seed = '256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2'
+const tipId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+// the network ids of the messages, the hashes of `my-devnet` and `testnet`
+const devnetId = '2066401064894887889'
+const testnetId = '8342982141227064571'
+const mainnetAddress = 'iota1qyenxvenxvenxvenxvenxvenxvenxvenxvenxvenxvenxvenxvenxu7jn4m'
+
+// a node of a private tangle with its own network id, HRP and minimum PoW score, recording the posted messages
+function startDevnetNode () {
+  const node = { messages: [] }
+  node.server = http.createServer((req, res) => {
+    let body = ''
+    req.on('data', chunk => { body += chunk })
+    req.on('end', () => {
+      res.setHeader('content-type', 'application/json')
+      if (req.url === '/api/v1/info') {
+        res.end(JSON.stringify({
+          data: {
+            name: 'HORNET',
+            version: '0.6.0',
+            isHealthy: true,
+            networkId: 'my-devnet',
+            bech32HRP: 'dev',
+            minPowScore: 10,
+            latestMilestoneIndex: 1,
+            solidMilestoneIndex: 1,
+            pruningIndex: 0,
+            features: []
+          }
+        }))
+      } else if (req.url === '/api/v1/tips') {
+        res.end(JSON.stringify({ data: { tip1MessageId: tipId, tip2MessageId: tipId } }))
+      } else if (req.method === 'POST' && req.url === '/api/v1/messages') {
+        node.messages.push(JSON.parse(body))
+        res.statusCode = 201
+        res.end(JSON.stringify({ data: { messageId: tipId } }))
+      } else {
+        res.statusCode = 404
+        res.end()
+      }
+    })
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Private tangle', () => {
+  let node
+  let client
+
+  before(async () => {
+    node = await startDevnetNode()
+    client = new ClientBuilder().node(node.url).localPow(true).powWorkerCount(1).build()
+  })
+
+  after(() => node.server.close())
+
+  it('uses the network parameters of the node', async () => {
+    const info = client.networkInfo()
+    assert.strictEqual(info.networkId, 'my-devnet')
+    assert.strictEqual(info.bech32HRP, 'dev')
+    assert.strictEqual(info.minPowScore, 10)
+    const addresses = client.getAddresses(seed).accountIndex(0).range(0, 1).get()
+    assert.deepStrictEqual(addresses, ['dev1qxt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupxlpml5x'])
+
+    await client.send().index('IOTA.RS PRIVATE TANGLE').submit()
+    assert.strictEqual(node.messages.pop().networkId, devnetId)
+  })
+
+  it('rejects the messages and the addresses of another network', async () => {
+    await assert.rejects(
+      client.postMessage({ networkId: testnetId, payload: { type: 2, index: 'IOTA.RS PRIVATE TANGLE' } }),
+      { code: 'ENETWORKMISMATCH', details: { messageNetworkId: testnetId, network: 'my-devnet', networkId: devnetId } }
+    )
+    const transaction = {
+      payload: {
+        type: 0,
+        essence: { inputs: [`${tipId}0000`], outputs: [{ address: mainnetAddress, amount: 1000000 }] },
+        unlockBlocks: []
+      }
+    }
+    await assert.rejects(client.postMessage(transaction), /invalid address HRP `iota`, expected `dev`/)
+    assert.strictEqual(node.messages.length, 0)
+  })
+
+  it('sets the network parameters for an offline use', () => {
+    const offlineClient = new ClientBuilder()
+      .node('http://localhost:14265')
+      .disableNodeSync()
+      .networkId('my-devnet')
+      .minPowScore(10)
+      .bech32Hrp('dev')
+      .build()
+    const info = offlineClient.networkInfo()
+    assert.strictEqual(info.networkId, 'my-devnet')
+    assert.strictEqual(info.minPowScore, 10)
+    assert(offlineClient.getAddresses(seed).range(0, 1).get()[0].startsWith('dev1'))
+
+    assert.throws(() => new ClientBuilder().networkId(''), /the network id can't be empty/)
+    assert.throws(() => new ClientBuilder().minPowScore(-1), /invalid minimum PoW score -1/)
+  })
+})
@@ -0,0 +1,75 @@
+const { ClientBuilder, messageToBytes, signTransaction } = require('../lib')
+const assert = require('assert')
+
+const seed = '256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2'
+const tipId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+const depositAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+
+// the output ids in the protocol order: the order of their bytes, the output index is little endian
+const outputIds = [`${'01'.repeat(32)}0000`, `${'01'.repeat(32)}0100`, `${'02'.repeat(32)}0000`]
+
+describe('Protocol order', () => {
+  let addresses
+
+  before(() => {
+    const client = new ClientBuilder().offline().networkId('testnet').bech32Hrp('iot').build()
+    addresses = client.getAddresses(seed).accountIndex(0).range(0, 2).get()
+  })
+
+  // the first and the last inputs are on the address 0, the second one on the address 1
+  const prepared = (order, outputs) => ({
+    essence: { inputs: order.map(index => outputIds[index]), outputs },
+    accountIndex: 0,
+    inputs: outputIds.map((outputId, index) => ({
+      outputId,
+      address: addresses[index % 2],
+      addressIndex: index % 2,
+      internal: false
+    }))
+  })
+  const outputs = () => [
+    { type: 0, address: depositAddress, amount: 4000000 },
+    { type: 0, address: addresses[1], amount: 2000000 }
+  ]
+  const message = payload => ({ networkId: '1', parent1: tipId, parent2: tipId, payload, nonce: '0' })
+
+  it('signs the essence in the protocol order', () => {
+    const sorted = signTransaction(prepared([0, 1, 2], outputs()), seed)
+    assert.deepStrictEqual(sorted.essence.inputs, outputIds)
+    assert.deepStrictEqual(sorted.unlockBlocks[2], { reference: 0 })
+    const shuffled = signTransaction(prepared([2, 0, 1], outputs().reverse()), seed)
+    assert.deepStrictEqual(shuffled, sorted)
+  })
+
+  it('packs the same message from a payload in any order', () => {
+    const payload = signTransaction(prepared([0, 1, 2], outputs()), seed)
+    const [signature0, signature1] = payload.unlockBlocks
+    // the unlock blocks in the order of the shuffled inputs, the address 0 signs twice instead of a reference
+    const shuffled = {
+      ...payload,
+      essence: { inputs: [outputIds[2], outputIds[1], outputIds[0]], outputs: [...payload.essence.outputs].reverse() },
+      unlockBlocks: [signature0, signature1, signature0]
+    }
+    const expected = messageToBytes(message(payload))
+    const { bytes, messageId } = messageToBytes(message(shuffled))
+    assert.strictEqual(messageId, expected.messageId)
+    assert.deepStrictEqual(bytes, expected.bytes)
+  })
+
+  it('rejects a shuffled payload signing another essence', () => {
+    const payload = signTransaction(prepared([0, 1, 2], outputs()), seed)
+    const [signature0, signature1] = payload.unlockBlocks
+    // the public keys keep their signatures of the other key
+    const forged = [
+      { signature: { ...signature0.signature, signature: signature1.signature.signature } },
+      { signature: { ...signature1.signature, signature: signature0.signature.signature } },
+      { reference: 0 }
+    ]
+    const shuffled = {
+      ...payload,
+      essence: { ...payload.essence, outputs: [...payload.essence.outputs].reverse() },
+      unlockBlocks: forged
+    }
+    assert.throws(() => messageToBytes(message(shuffled)), /unlock block 0 doesn't sign the essence .* protocol order/)
+  })
+})
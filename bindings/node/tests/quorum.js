@@ -0,0 +1,135 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const address = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+const messageId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+
+// a node answering the balance of `address` with `balance` after `delay` milliseconds, counting the submitted messages
+function startNode (balance, delay = 0) {
+  const node = { posts: 0 }
+  node.server = http.createServer((req, res) => {
+    if (req.method === 'POST') {
+      node.posts++
+    }
+    setTimeout(() => {
+      res.setHeader('content-type', 'application/json')
+      if (req.url === '/api/v1/info') {
+        res.end(JSON.stringify({
+          data: {
+            name: 'quorum',
+            version: '0.0.0',
+            isHealthy: true,
+            networkId: 'testnet',
+            bech32HRP: 'iot',
+            minPowScore: 4000,
+            latestMilestoneIndex: 1,
+            solidMilestoneIndex: 1,
+            pruningIndex: 0,
+            features: []
+          }
+        }))
+      } else if (req.method === 'GET' && req.url === `/api/v1/addresses/${address}`) {
+        res.end(JSON.stringify({ data: { addressType: 1, address, balance, count: 1, dustAllowed: false } }))
+      } else if (req.method === 'POST' && req.url === '/api/v1/messages') {
+        res.statusCode = 201
+        res.end(JSON.stringify({ data: { messageId } }))
+      } else {
+        res.statusCode = 404
+        res.end()
+      }
+    }, delay)
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+function quorumClient (nodes, threshold, requestTimeout = 5000) {
+  return new ClientBuilder()
+    .nodes(nodes.map(node => node.url))
+    .disableNodeSync()
+    .localPow(false)
+    .quorum(true)
+    .quorumThreshold(threshold)
+    .requestTimeout(requestTimeout)
+    .build()
+}
+
+describe('Quorum', () => {
+  let nodes
+
+  before(async () => {
+    nodes = await Promise.all([startNode(1000000), startNode(1000000), startNode(2000000)])
+  })
+
+  after(() => nodes.forEach(node => node.server.close()))
+
+  it('accepts the balance the threshold of the nodes agree on', async () => {
+    const balances = await quorumClient(nodes, 66).getAddressBalances([address])
+    assert.strictEqual(balances[0].balance, '1000000')
+  })
+
+  it('rejects with the answer of each node if they disagree', async () => {
+    await assert.rejects(quorumClient(nodes, 100).getAddressBalances([address]), error => {
+      assert.strictEqual(error.code, 'EQUORUM')
+      assert.strictEqual(error.details.address, address)
+      const { answers } = error.details
+      assert.strictEqual(answers.length, 3)
+      for (const node of nodes) {
+        assert(answers.some(answer => answer.node.startsWith(node.url)), `no answer of ${node.url}`)
+      }
+      assert.strictEqual(answers.filter(({ answer }) => answer.includes('2000000')).length, 1)
+      return true
+    })
+  })
+
+  it("doesn't wait for a slow node past the request timeout", async () => {
+    const slowNode = await startNode(1000000, 2000)
+    try {
+      const start = Date.now()
+      const balances = await quorumClient([nodes[0], nodes[1], slowNode], 66).getAddressBalances([address])
+      assert.strictEqual(balances[0].balance, '1000000')
+      assert(Date.now() - start < 1000, 'the quorum waited for the slow node')
+
+      await assert.rejects(
+        quorumClient([nodes[0], nodes[1], slowNode], 100, 300).getAddressBalances([address]),
+        error => error.code === 'EQUORUM' && error.details.answers.some(({ answer }) => answer.includes('timed out'))
+      )
+    } finally {
+      slowNode.server.close()
+    }
+  })
+
+  it('rejects with EQUORUM if fewer nodes than the quorum size are synced', async () => {
+    const client = new ClientBuilder()
+      .nodes(nodes.slice(0, 2).map(node => node.url))
+      .disableNodeSync()
+      .quorum(true)
+      .build()
+    await assert.rejects(client.getAddressBalances([address]), {
+      code: 'EQUORUM',
+      details: { syncedNodes: 2, quorumSize: 3, address }
+    })
+  })
+
+  it('submits the messages to a single node', async () => {
+    const posts = nodes.reduce((sum, node) => sum + node.posts, 0)
+    const client = quorumClient(nodes, 66)
+    const message = {
+      networkId: '6530425480034647824',
+      parent1: '2e071ee19dc58d250e0e084a1ac890a9769896cd4c5689fd7f202bfc6c8d574c',
+      parent2: '4375fb2a9d6b0b5a6c529bde678f227192d409b75cf87f7245ceeed8ed611664',
+      payload: { type: 2, index: 'QUORUM', data: [] },
+      nonce: 0
+    }
+    await client.postMessage(message)
+    assert.strictEqual(nodes.reduce((sum, node) => sum + node.posts, 0), posts + 1)
+  })
+
+  it('validates the quorum options', () => {
+    assert.throws(() => new ClientBuilder().quorumSize(0), /the quorum size can't be zero/)
+    assert.throws(() => new ClientBuilder().quorumThreshold(101), /between 1 and 100/)
+  })
+})
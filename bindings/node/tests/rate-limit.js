@@ -0,0 +1,74 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const tipId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+
+// a node answering 429 with `Retry-After: 1` to the first `limited` tips requests
+function startNode () {
+  const node = { limited: 0, requests: 0 }
+  node.server = http.createServer((req, res) => {
+    res.setHeader('content-type', 'application/json')
+    if (req.url !== '/api/v1/tips') {
+      res.statusCode = 404
+      res.end()
+      return
+    }
+    node.requests++
+    if (node.limited > 0) {
+      node.limited--
+      res.statusCode = 429
+      res.setHeader('retry-after', '1')
+      res.end()
+      return
+    }
+    res.end(JSON.stringify({ data: { tip1MessageId: tipId, tip2MessageId: tipId } }))
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Rate limits', () => {
+  let node
+
+  before(async () => {
+    node = await startNode()
+  })
+
+  after(() => node.server.close())
+
+  beforeEach(() => {
+    node.requests = 0
+  })
+
+  it('retries after the delay the node asks for', async () => {
+    node.limited = 2
+    const client = new ClientBuilder().node(node.url).disableNodeSync().build()
+    const start = Date.now()
+    assert.deepStrictEqual(await client.getTips(), [tipId, tipId])
+    assert(Date.now() - start >= 2000, `elapsed ${Date.now() - start} ms`)
+    assert.strictEqual(node.requests, 3)
+  })
+
+  it('rejects with ERATELIMITED once the retries are exhausted', async () => {
+    node.limited = 2
+    const client = new ClientBuilder().node(node.url).disableNodeSync().rateLimitRetries(1).build()
+    await assert.rejects(client.getTips(), error => {
+      assert.strictEqual(error.code, 'ERATELIMITED')
+      assert.strictEqual(error.details.status, 429)
+      assert.strictEqual(error.details.retryAfterMs, 1000)
+      assert.strictEqual(error.details.endpoint, 'GET /api/v1/tips')
+      return true
+    })
+    assert.strictEqual(node.requests, 2)
+  })
+
+  it('rejects at once if the node asks to wait longer than the max delay', async () => {
+    node.limited = 1
+    const client = new ClientBuilder().node(node.url).disableNodeSync().rateLimitMaxDelay(500).build()
+    await assert.rejects(client.getTips(), { code: 'ERATELIMITED' })
+    assert.strictEqual(node.requests, 1)
+  })
+})
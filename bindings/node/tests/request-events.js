@@ -0,0 +1,120 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const messageId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+const nodeInfo = {
+  name: 'mock',
+  version: '0.0.0',
+  isHealthy: true,
+  networkId: 'testnet',
+  minPowScore: 4000,
+  latestMilestoneIndex: 1,
+  solidMilestoneIndex: 1,
+  pruningIndex: 0,
+  features: []
+}
+
+// a node answering the info, 404 for the messages and the milestone 1 after a second, recording the trace ids
+function startNode () {
+  const node = { traceIds: [] }
+  node.server = http.createServer((req, res) => {
+    node.traceIds.push(req.headers['x-trace-id'])
+    res.setHeader('content-type', 'application/json')
+    if (req.url === '/api/v1/info') {
+      res.end(JSON.stringify({ data: nodeInfo }))
+    } else if (req.url === '/api/v1/milestones/1') {
+      setTimeout(() => {
+        res.statusCode = 404
+        res.end()
+      }, 1000)
+    } else {
+      res.statusCode = 404
+      res.end()
+    }
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}/`
+    resolve(node)
+  }))
+}
+
+const sleep = ms => new Promise(resolve => setTimeout(resolve, ms))
+
+describe('Request events', () => {
+  let node
+  let client
+
+  before(async () => {
+    node = await startNode()
+    client = new ClientBuilder()
+      .node(node.url)
+      .disableNodeSync()
+      .requestTimeout(300)
+      .requestHeaders({ 'x-trace-id': 'trace-1' })
+      .build()
+  })
+
+  after(() => node.server.close())
+
+  it('observes a success, a 4xx and a timeout', async () => {
+    const requests = []
+    const responses = []
+    const onRequest = (err, event) => requests.push(err || event)
+    const onResponse = (err, event) => responses.push(err || event)
+    client.on('request', onRequest)
+    client.on('response', onResponse)
+    try {
+      await client.getInfo()
+      await assert.rejects(client.getMessage().data(messageId))
+      await assert.rejects(client.getMilestone(1), { code: 'ETIMEDOUT' })
+      await sleep(100)
+    } finally {
+      client.off('request', onRequest)
+      client.off('response', onResponse)
+    }
+
+    const paths = ['/api/v1/info', `/api/v1/messages/${messageId}`, '/api/v1/milestones/1']
+    assert.deepStrictEqual(requests.map(event => event.type), ['request', 'request', 'request'])
+    assert.deepStrictEqual(requests.map(event => event.request.path), paths)
+    assert.deepStrictEqual(requests[0].request, { method: 'GET', path: paths[0], bodySize: 0, node: node.url })
+
+    assert.deepStrictEqual(responses.map(event => event.type), ['response', 'response', 'response'])
+    assert.deepStrictEqual(responses.map(event => event.request.path), paths)
+    assert.strictEqual(responses[0].response.status, 200)
+    assert.strictEqual(responses[0].response.error, null)
+    assert.strictEqual(responses[1].response.status, 404)
+    assert.strictEqual(responses[2].response.status, null)
+    assert(responses[2].response.error.includes('timed out'), responses[2].response.error)
+    assert(responses[2].response.elapsed >= 300, `elapsed ${responses[2].response.elapsed}`)
+  })
+
+  it('adds the request headers', async () => {
+    node.traceIds = []
+    await client.getInfo()
+    assert.deepStrictEqual(node.traceIds, ['trace-1'])
+  })
+
+  it("doesn't break the requests if a callback throws", async () => {
+    const warnings = []
+    const onWarning = warning => warnings.push(warning)
+    const onRequest = () => {
+      throw new Error('callback failure')
+    }
+    process.on('warning', onWarning)
+    client.on('request', onRequest)
+    try {
+      const info = await client.getInfo()
+      assert.strictEqual(info.name, 'mock')
+      await sleep(100)
+    } finally {
+      client.off('request', onRequest)
+      process.off('warning', onWarning)
+    }
+    assert(warnings.some(warning => warning.message === 'callback failure'), 'the callback error was not emitted')
+  })
+
+  it('rejects invalid request headers', () => {
+    assert.throws(() => new ClientBuilder().requestHeaders({ 'x-trace-id': 1 }), /invalid request headers/)
+  })
+})
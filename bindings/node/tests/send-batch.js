@@ -0,0 +1,142 @@
+const { ClientBuilder, bech32ToHex } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const seed = '256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2'
+const tipId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+const depositAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+
+// a node holding the unspent outputs `{ outputId, address, amount }`, recording the posted messages
+function startUtxoNode (outputs) {
+  const node = { messages: [] }
+  node.server = http.createServer((req, res) => {
+    let body = ''
+    req.on('data', chunk => { body += chunk })
+    req.on('end', () => {
+      res.setHeader('content-type', 'application/json')
+      const [path, query] = req.url.split('?')
+      const addressOutputs = path.match(/^\/api\/v1\/addresses\/(\w+)\/outputs$/)
+      const addressBalance = path.match(/^\/api\/v1\/addresses\/(\w+)$/)
+      const output = path.match(/^\/api\/v1\/outputs\/(\w+)$/)
+      if (path === '/api/v1/info') {
+        res.end(JSON.stringify({
+          data: {
+            name: 'HORNET',
+            version: '0.6.0',
+            isHealthy: true,
+            networkId: 'testnet',
+            bech32HRP: 'iot',
+            minPowScore: 10,
+            latestMilestoneIndex: 1,
+            solidMilestoneIndex: 1,
+            pruningIndex: 0,
+            features: []
+          }
+        }))
+      } else if (path === '/api/v1/tips') {
+        res.end(JSON.stringify({ data: { tip1MessageId: tipId, tip2MessageId: tipId } }))
+      } else if (addressOutputs) {
+        // all the outputs are signature locked single outputs
+        const outputIds = query === 'type=1'
+          ? []
+          : outputs.filter(({ address }) => address === addressOutputs[1]).map(({ outputId }) => outputId)
+        res.end(JSON.stringify({
+          data: { address: addressOutputs[1], maxResults: 1000, count: outputIds.length, outputIds }
+        }))
+      } else if (addressBalance) {
+        const held = outputs.filter(({ address }) => address === addressBalance[1])
+        res.end(JSON.stringify({
+          data: {
+            addressType: 1,
+            address: addressBalance[1],
+            maxResults: 1000,
+            count: held.length,
+            balance: held.reduce((balance, { amount }) => balance + amount, 0),
+            dustAllowed: false
+          }
+        }))
+      } else if (output && outputs.some(({ outputId }) => outputId === output[1])) {
+        const { outputId, address, amount } = outputs.find(({ outputId }) => outputId === output[1])
+        res.end(JSON.stringify({
+          data: {
+            messageId: tipId,
+            transactionId: outputId.slice(0, 64),
+            outputIndex: parseInt(outputId.slice(64), 16),
+            isSpent: false,
+            output: { type: 0, address: { type: 1, address: bech32ToHex(address).hex }, amount }
+          }
+        }))
+      } else if (req.method === 'POST' && path === '/api/v1/messages') {
+        node.messages.push(JSON.parse(body))
+        res.statusCode = 201
+        res.end(JSON.stringify({ data: { messageId: tipId } }))
+      } else {
+        res.statusCode = 404
+        res.end()
+      }
+    })
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+// the output ids spent by the posted messages
+function spentOutputs (messages) {
+  return messages.flatMap(message => message.payload.essence.inputs.map(input =>
+    `${input.transactionId}${input.transactionOutputIndex}`))
+}
+
+describe('Batch transfers', () => {
+  let node
+  let client
+
+  // 5 outputs of 1 Mi on the first 3 addresses of the seed
+  beforeEach(async () => {
+    const addressClient = new ClientBuilder().offline().networkId('testnet').bech32Hrp('iot').build()
+    const addresses = addressClient.getAddresses(seed).accountIndex(0).range(0, 3).get()
+    const outputs = [0, 1, 2, 3, 4].map(index => ({
+      outputId: `${`0${index + 1}`.repeat(32)}0000`,
+      address: addresses[index % addresses.length],
+      amount: 1000000
+    }))
+    node = await startUtxoNode(outputs)
+    client = new ClientBuilder().node(node.url).disableNodeSync().localPow(true).powWorkerCount(1).build()
+  })
+
+  afterEach(() => node.server.close())
+
+  it('sends the transfers without double spent inputs', async () => {
+    const transfers = [1000000, 1000000, 10000000, 1000000, 1000000].map(amount => ({ address: depositAddress, amount }))
+    const results = await client.sendBatch(seed, transfers, { parallelism: 2 })
+    assert.strictEqual(results.length, 5)
+    results.forEach((result, index) => {
+      if (index === 2) {
+        // the outputs of the first transfers are reserved
+        assert.strictEqual(result.error.code, 'ENOTENOUGHBALANCE')
+        assert.strictEqual(result.error.details.availableAmount, 3000000)
+      } else {
+        assert.strictEqual(result.messageId, tipId)
+      }
+    })
+    const spent = spentOutputs(node.messages)
+    assert.strictEqual(node.messages.length, 4)
+    assert.strictEqual(new Set(spent).size, 4)
+  })
+
+  it('packs the transfers into a transaction', async () => {
+    const results = await client.sendBatch(seed, [
+      { address: depositAddress, amount: 1000000 },
+      [{ address: depositAddress, amount: 1000000 }, { address: depositAddress, amount: 1000000 }]
+    ], { packing: true })
+    assert.deepStrictEqual(results, [{ messageId: tipId }, { messageId: tipId }])
+    assert.strictEqual(node.messages.length, 1)
+    assert.strictEqual(new Set(spentOutputs(node.messages)).size, 3)
+  })
+
+  it('rejects an invalid address', async () => {
+    await assert.rejects(client.sendBatch(seed, [{ address: 'iot1invalid', amount: 1000000 }]), { code: 'EINVALIDADDRESS' })
+    assert.strictEqual(node.messages.length, 0)
+  })
+})
@@ -0,0 +1,85 @@
+const { ClientBuilder, signTransaction } = require('../lib')
+const assert = require('assert')
+const crypto = require('crypto')
+
+const seed = '256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2'
+const depositAddress = 'iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj'
+const PKCS8_ED25519_PREFIX = Buffer.from('302e020100300506032b657004220420', 'hex')
+
+// SLIP-10 derivation of the address key on m/44'/4218'/account'/internal'/index', as a hardware signer would do it
+function privateKey (seed, { accountIndex, addressIndex, internal }) {
+  let node = crypto.createHmac('sha512', 'ed25519 seed').update(Buffer.from(seed, 'hex')).digest()
+  for (const index of [44, 4218, accountIndex, internal ? 1 : 0, addressIndex]) {
+    const data = Buffer.alloc(37)
+    node.copy(data, 1, 0, 32)
+    data.writeUInt32BE((index | 0x80000000) >>> 0, 33)
+    node = crypto.createHmac('sha512', node.slice(32)).update(data).digest()
+  }
+  const key = Buffer.concat([PKCS8_ED25519_PREFIX, node.slice(0, 32)])
+  return crypto.createPrivateKey({ key, format: 'der', type: 'pkcs8' })
+}
+
+function ed25519Signer (seed) {
+  return async (essence, signers) => signers.map(signer => {
+    const key = privateKey(seed, signer)
+    const publicKey = crypto.createPublicKey(key).export({ format: 'der', type: 'spki' }).slice(-32)
+    return {
+      publicKey: publicKey.toString('hex'),
+      signature: crypto.sign(null, essence, key).toString('hex')
+    }
+  })
+}
+
+describe('External signer', () => {
+  const client = new ClientBuilder().node('http://localhost:14265').disableNodeSync().build()
+  const [[address0], , [address1]] = client.findAddresses(seed).accountIndex(0).range(0, 2).get()
+  const transactionId = index => index.toString(16).padStart(64, '0')
+  // two inputs on the address 0, which signs once, and one on the address 1
+  const preparedData = {
+    essence: {
+      inputs: [1, 2, 3].map(index => ({ type: 0, transactionId: transactionId(index), transactionOutputIndex: 0 })),
+      outputs: [{ address: depositAddress, amount: 3000000 }]
+    },
+    accountIndex: 0,
+    inputs: [
+      { outputId: `${transactionId(1)}0000`, address: address0, addressIndex: 0, internal: false },
+      { outputId: `${transactionId(2)}0000`, address: address0, addressIndex: 0, internal: false },
+      { outputId: `${transactionId(3)}0000`, address: address1, addressIndex: 1, internal: false }
+    ]
+  }
+
+  it('signs a transaction with an ed25519 signer', async () => {
+    const signers = []
+    const signer = ed25519Signer(seed)
+    const payload = await signTransaction(preparedData, (essence, addresses) => {
+      assert.strictEqual(Buffer.isBuffer(essence), true)
+      signers.push(...addresses)
+      return signer(essence, addresses)
+    })
+    assert.deepStrictEqual(signers.map(({ address }) => address), [address0, address1])
+    assert.deepStrictEqual(payload, signTransaction(preparedData, seed))
+    assert.strictEqual(payload.unlockBlocks.length, 3)
+    assert.deepStrictEqual(payload.unlockBlocks[1], { reference: 0 })
+  })
+
+  it('rejects with the signer error', async () => {
+    const error = new Error('the device is locked')
+    await assert.rejects(signTransaction(preparedData, async () => { throw error }), error)
+    await assert.rejects(signTransaction(preparedData, () => { throw error }), error)
+  })
+
+  it('rejects invalid signatures', async () => {
+    const otherSeed = '0000000000000000000000000000000000000000000000000000000000000000'
+    await assert.rejects(signTransaction(preparedData, ed25519Signer(otherSeed)), /Failed to sign the transaction/)
+    const signer = ed25519Signer(seed)
+    const forged = async (essence, signers) => (await signer(essence, signers))
+      .map(({ publicKey }) => ({ publicKey, signature: '00'.repeat(64) }))
+    await assert.rejects(signTransaction(preparedData, forged), /Failed to sign the transaction/)
+    await assert.rejects(signTransaction(preparedData, async (_, signers) => (await signer(_, signers)).slice(1)),
+      /Failed to sign the transaction/)
+  })
+
+  it('requires a function', () => {
+    assert.throws(() => client.send().signer(seed), { name: 'TypeError', message: 'the signer must be a function' })
+  })
+})
@@ -0,0 +1,75 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const fs = require('fs')
+const http = require('http')
+const os = require('os')
+const path = require('path')
+
+const seed = '256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2'
+
+// a node where the first three addresses of the seed hold an unspent output, counting the output requests, with and
+// without the spent outputs
+function startNode (usedAddresses) {
+  const node = { requests: 0 }
+  node.server = http.createServer((req, res) => {
+    const [, address] = req.url.match(/^\/api\/v1\/addresses\/(\w+)\/outputs(\?include-spent=true)?$/) || []
+    res.setHeader('content-type', 'application/json')
+    if (!address) {
+      res.statusCode = 404
+      res.end()
+      return
+    }
+    node.requests++
+    const index = usedAddresses.indexOf(address)
+    const outputIds = index === -1 ? [] : [`${(index + 1).toString(16).padStart(2, '0').repeat(32)}0000`]
+    res.end(JSON.stringify({ data: { address, maxResults: 1000, count: outputIds.length, outputIds } }))
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Storage', () => {
+  let node
+  let storagePath
+
+  before(async () => {
+    const offline = new ClientBuilder().offline().networkId('testnet').bech32Hrp('iot').build()
+    node = await startNode(offline.getAddresses(seed).accountIndex(0).range(0, 3).get())
+    storagePath = fs.mkdtempSync(path.join(os.tmpdir(), 'iota-client-storage-'))
+  })
+
+  after(() => {
+    node.server.close()
+    fs.rmSync(storagePath, { recursive: true, force: true })
+  })
+
+  const client = networkId => new ClientBuilder()
+    .node(node.url)
+    .disableNodeSync()
+    .networkId(networkId)
+    .addressScanWindow(1)
+    .storage({ storagePath })
+    .build()
+  const unspentAddressIndex = async networkId => {
+    node.requests = 0
+    const [, index] = await client(networkId).getUnspentAddress(seed).accountIndex(0).get()
+    return index
+  }
+
+  it('skips the addresses discovered by a client of the same storage', async () => {
+    assert.strictEqual(await unspentAddressIndex('testnet'), 3)
+    assert.strictEqual(node.requests, 2 * 4)
+    assert.strictEqual(await unspentAddressIndex('testnet'), 3)
+    assert.strictEqual(node.requests, 2)
+    // the state of another network is dropped
+    assert.strictEqual(await unspentAddressIndex('othernet'), 3)
+    assert.strictEqual(node.requests, 2 * 4)
+    assert.deepStrictEqual(await client('othernet').getPendingMessages(), [])
+  })
+
+  it('validates the storage options', () => {
+    assert.throws(() => new ClientBuilder().storage({ path: storagePath }), /invalid storage options/)
+  })
+})
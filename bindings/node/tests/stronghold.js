@@ -0,0 +1,52 @@
+const { ClientBuilder, createStrongholdSnapshot, generateMnemonic } = require('../lib')
+const assert = require('assert')
+const fs = require('fs')
+const os = require('os')
+const path = require('path')
+
+const password = 'password'
+
+describe('Stronghold signer', () => {
+  const client = new ClientBuilder().node('http://localhost:14265').disableNodeSync().build()
+  const mnemonic = generateMnemonic()
+  const snapshotPath = path.join(os.tmpdir(), `iota-client-${process.pid}-${Date.now()}.stronghold`)
+  const signer = { type: 'Stronghold', snapshotPath, password }
+
+  before(() => createStrongholdSnapshot(snapshotPath, password, mnemonic))
+
+  after(() => fs.rmSync(snapshotPath, { force: true }))
+
+  it('derives the addresses of the mnemonic', () => {
+    for (const accountIndex of [0, 1]) {
+      const addresses = client.getAddresses({ signer }).accountIndex(accountIndex).range(0, 5).includeInternal().get()
+      assert.deepStrictEqual(
+        addresses,
+        client.getAddresses(mnemonic).accountIndex(accountIndex).range(0, 5).includeInternal().get()
+      )
+    }
+  })
+
+  it("doesn't overwrite a snapshot", () => {
+    assert.throws(() => createStrongholdSnapshot(snapshotPath, password, generateMnemonic()), /already exists/)
+    assert.deepStrictEqual(client.getAddresses({ signer }).range(0, 1).get(), client.getAddresses(mnemonic).range(0, 1).get())
+  })
+
+  it('rejects a wrong password', () => {
+    assert.throws(() => client.getAddresses({ signer: { ...signer, password: 'wrong password' } }).range(0, 1).get(), {
+      code: 'ESTRONGHOLDPASSWORD',
+      details: { snapshotPath }
+    })
+  })
+
+  it('rejects a missing snapshot', () => {
+    const missingPath = path.join(os.tmpdir(), 'iota-client-missing.stronghold')
+    assert.throws(() => client.getAddresses({ signer: { ...signer, snapshotPath: missingPath } }).range(0, 1).get(), {
+      code: 'ESNAPSHOTNOTFOUND',
+      details: { snapshotPath: missingPath }
+    })
+  })
+
+  it('rejects invalid signer options', () => {
+    assert.throws(() => client.getAddresses({ signer: { type: 'Ledger' } }), /invalid signer/)
+  })
+})
@@ -0,0 +1,101 @@
+const { ClientBuilder } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const delay = 500
+
+// a node that answers every request after `delay` milliseconds
+function startSlowNode () {
+  const server = http.createServer((req, res) => {
+    setTimeout(() => {
+      res.setHeader('content-type', 'application/json')
+      if (req.url === '/api/v1/tips') {
+        res.end(JSON.stringify({
+          data: {
+            tip1MessageId: '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649',
+            tip2MessageId: '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+          }
+        }))
+      } else {
+        res.statusCode = 404
+        res.end()
+      }
+    }, delay)
+  })
+  return new Promise(resolve => server.listen(0, '127.0.0.1', () => resolve(server)))
+}
+
+describe('Request timeouts', () => {
+  let server
+  let nodeUrl
+
+  before(async () => {
+    server = await startSlowNode()
+    nodeUrl = `http://127.0.0.1:${server.address().port}`
+  })
+
+  after(() => server.close())
+
+  it('rejects with ETIMEDOUT after the request timeout', async () => {
+    const client = new ClientBuilder().node(nodeUrl).disableNodeSync().requestTimeout(100).build()
+    await assert.rejects(
+      client.getTips(),
+      err => err.code === 'ETIMEDOUT' && err.details.url === `${nodeUrl}/api/v1/tips`
+    )
+  })
+
+  it('sets the node request details on the timeout and the node errors', async () => {
+    const messageId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+    const client = new ClientBuilder().node(nodeUrl).disableNodeSync().requestTimeout(100).build()
+    await assert.rejects(client.getTips(), error => {
+      const { elapsedMs, ...details } = error.details
+      assert.deepStrictEqual(details, {
+        url: `${nodeUrl}/api/v1/tips`,
+        node: `${nodeUrl}/`,
+        endpoint: 'GET /api/v1/tips'
+      })
+      assert(elapsedMs >= 100 && elapsedMs < delay, `elapsed ${elapsedMs} ms`)
+      return true
+    })
+
+    const patientClient = new ClientBuilder().node(nodeUrl).disableNodeSync().requestTimeout(5000).build()
+    await assert.rejects(patientClient.getMessage().metadata(messageId), error => {
+      assert.strictEqual(error.code, 'ERESPONSE')
+      const { elapsedMs, ...details } = error.details
+      assert.deepStrictEqual(details, {
+        status: 404,
+        url: `${nodeUrl}/api/v1/messages/${messageId}/metadata`,
+        node: `${nodeUrl}/`,
+        endpoint: `GET /api/v1/messages/${messageId}/metadata`
+      })
+      assert(elapsedMs >= delay, `elapsed ${elapsedMs} ms`)
+      return true
+    })
+  })
+
+  it('uses the API timeout over the request timeout', async () => {
+    const client = new ClientBuilder()
+      .node(nodeUrl)
+      .disableNodeSync()
+      .requestTimeout(100)
+      .apiTimeout('GetTips', 5000)
+      .apiTimeout('GetMessage', 100)
+      .build()
+    assert.strictEqual((await client.getTips()).length, 2)
+    await assert.rejects(
+      client.getMessage().metadata('52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'),
+      err => err.code === 'ETIMEDOUT'
+    )
+  })
+
+  it('applies the timeout to the node syncing', () => {
+    const start = Date.now()
+    const client = new ClientBuilder().node(nodeUrl).apiTimeout('GetInfo', 100).build()
+    assert.ok(Date.now() - start < delay)
+    assert.deepStrictEqual(client.syncedNodes(), [])
+  })
+
+  it('rejects an unknown API', () => {
+    assert.throws(() => new ClientBuilder().apiTimeout('GetEverything', 100), /unknown api kind `GetEverything`/)
+  })
+})
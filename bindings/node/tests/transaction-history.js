@@ -0,0 +1,124 @@
+const { ClientBuilder, hexToBech32 } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const ownAddress = '1'.repeat(64)
+const otherAddress = '2'.repeat(64)
+const fundingTransactionId = '1'.padStart(64, '0')
+const depositTransactionId = '2'.padStart(64, '0')
+// the signatures aren't checked by the client
+const publicKey = 'dda7a4395bd2035f6fee2d060ce844101efb612f3bcf7f49e749683f124014f8'
+const signature = '1a6ddcc49f0715395f0c8dcfd106a1833624abd85679370b4c9603c1e9c6d80a' +
+  '3ecc63c1ba1ffe1ffa98b64b2cf610ce3df86c6b8470e4006e7fef189df1ebde'
+
+// a node holding a deposit of 3 Mi to the own address, spending a 5 Mi output of the other address, referenced by the
+// milestone 5
+function startNode () {
+  const node = {}
+  const outputs = {
+    [`${depositTransactionId}0000`]: [ownAddress, 3000000],
+    [`${fundingTransactionId}0000`]: [otherAddress, 5000000]
+  }
+  node.server = http.createServer((req, res) => {
+    res.setHeader('content-type', 'application/json')
+    const outputId = req.url.startsWith('/api/v1/outputs/') && req.url.slice('/api/v1/outputs/'.length)
+    const own = hexToBech32(ownAddress, 'iot')
+    let data
+    const [, address] = req.url.match(/^\/api\/v1\/addresses\/(\w+)\/outputs(\?include-spent=true)?$/) || []
+    if (address) {
+      // the deposit output is unspent, it's listed with and without the spent outputs
+      const outputIds = address === own ? [`${depositTransactionId}0000`] : []
+      data = { address, maxResults: 1000, count: outputIds.length, outputIds }
+    } else if (outputs[outputId]) {
+      const [address, amount] = outputs[outputId]
+      data = {
+        messageId: '0'.repeat(64),
+        transactionId: outputId.slice(0, 64),
+        outputIndex: 0,
+        isSpent: false,
+        output: { type: 0, address: { type: 1, address }, amount }
+      }
+    } else if (req.url === `/api/v1/transactions/${depositTransactionId}/included-message`) {
+      data = {
+        networkId: '1',
+        parent1MessageId: '0'.repeat(64),
+        parent2MessageId: '0'.repeat(64),
+        payload: {
+          type: 0,
+          essence: {
+            type: 0,
+            inputs: [{ type: 0, transactionId: fundingTransactionId, transactionOutputIndex: 0 }],
+            outputs: [
+              { type: 0, address: { type: 1, address: ownAddress }, amount: 3000000 },
+              { type: 0, address: { type: 1, address: otherAddress }, amount: 2000000 }
+            ]
+          },
+          unlockBlocks: [{ type: 0, signature: { type: 1, publicKey, signature } }]
+        },
+        nonce: '0'
+      }
+    } else if (req.url.startsWith('/api/v1/messages/') && req.url.endsWith('/metadata')) {
+      data = {
+        messageId: req.url.slice('/api/v1/messages/'.length, -'/metadata'.length),
+        parent1MessageId: '0'.repeat(64),
+        parent2MessageId: '0'.repeat(64),
+        isSolid: true,
+        referencedByMilestoneIndex: 5,
+        ledgerInclusionState: 'included'
+      }
+    } else if (req.url === '/api/v1/milestones/5') {
+      data = { index: 5, messageId: '0'.repeat(64), timestamp: 1000 }
+    } else {
+      res.statusCode = 404
+      res.end()
+      return
+    }
+    res.end(JSON.stringify({ data }))
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Transaction history', () => {
+  let node
+  let client
+  const own = hexToBech32(ownAddress, 'iot')
+  const other = hexToBech32(otherAddress, 'iot')
+
+  before(async () => {
+    node = await startNode()
+    client = new ClientBuilder().node(node.url).disableNodeSync().build()
+  })
+
+  after(() => node.server.close())
+
+  it('lists the transactions of the addresses', async () => {
+    const entries = await client.getTransactionHistory([own])
+    assert.strictEqual(entries.length, 1)
+    const [deposit] = entries
+    assert.strictEqual(deposit.transactionId, depositTransactionId)
+    assert.match(deposit.messageId, /^[0-9a-f]{64}$/)
+    assert.strictEqual(deposit.milestoneIndex, 5)
+    assert.strictEqual(deposit.timestamp, 1000)
+    assert.strictEqual(deposit.inclusionState, 'included')
+    assert.deepStrictEqual(deposit.deltas, [{ address: own, delta: '3000000' }])
+    assert.deepStrictEqual(deposit.counterparts, [other])
+  })
+
+  it('exports the history as CSV and JSON lines', async () => {
+    const csv = await client.getTransactionHistory([own], { format: 'csv' })
+    const [header, row] = csv.trim().split('\n')
+    assert.strictEqual(header, 'milestoneIndex,timestamp,messageId,transactionId,address,delta,counterparts,inclusionState')
+    assert.ok(row.startsWith('5,1000,'))
+    assert.ok(row.endsWith(`,${depositTransactionId},${own},3000000,${other},included`))
+
+    const lines = await client.getTransactionHistory([own], { format: 'jsonl' })
+    assert.deepStrictEqual(JSON.parse(lines).deltas, [{ address: own, delta: 3000000 }])
+  })
+
+  it('rejects an unknown format', async () => {
+    await assert.rejects(client.getTransactionHistory([own], { format: 'xml' }), /invalid format/)
+  })
+})
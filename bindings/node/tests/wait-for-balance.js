@@ -0,0 +1,112 @@
+const { ClientBuilder, hexToBech32 } = require('../lib')
+const assert = require('assert')
+const http = require('http')
+
+const addressHex = '644eb5594b880f7da82f35902296199a0eb9186999797f661b324f8d754a3a09'
+const messageId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+const transactionId = '0000000000000000000000000000000000000000000000000000000000000001'
+const outputId = `${transactionId}0100`
+const amount = 1000000
+
+// a node where the output of the address appears on the third balance request, failing while `node.failing` is set
+function startNode () {
+  const node = { balanceRequests: 0, failing: false }
+  node.server = http.createServer((req, res) => {
+    res.setHeader('content-type', 'application/json')
+    const paid = node.balanceRequests >= 3
+    if (node.failing) {
+      res.statusCode = 500
+      res.end()
+    } else if (/^\/api\/v1\/addresses\/[^/]+$/.test(req.url)) {
+      const balance = ++node.balanceRequests >= 3 ? amount : 0
+      res.end(JSON.stringify({
+        data: {
+          addressType: 1,
+          address: addressHex,
+          maxResults: 1000,
+          count: balance ? 1 : 0,
+          balance,
+          dustAllowed: false
+        }
+      }))
+    } else if (/^\/api\/v1\/addresses\/[^/]+\/outputs$/.test(req.url)) {
+      const outputIds = paid ? [outputId] : []
+      res.end(JSON.stringify({
+        data: { address: addressHex, maxResults: 1000, count: outputIds.length, outputIds }
+      }))
+    } else if (paid && req.url === `/api/v1/outputs/${outputId}`) {
+      res.end(JSON.stringify({
+        data: {
+          messageId,
+          transactionId,
+          outputIndex: 1,
+          isSpent: false,
+          output: { type: 0, address: { type: 1, address: addressHex }, amount }
+        }
+      }))
+    } else {
+      res.statusCode = 404
+      res.end()
+    }
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('Balance wait', () => {
+  let node
+  let client
+
+  beforeEach(async () => {
+    node = await startNode()
+    client = new ClientBuilder().node(node.url).disableNodeSync().build()
+  })
+
+  afterEach(() => node.server.close())
+
+  it('resolves once the balance reaches the minimum amount', async () => {
+    const reached = await client.waitForBalance(addressHex, amount, { interval: 50, timeout: 10000 })
+    assert.strictEqual(node.balanceRequests, 3)
+    const address = hexToBech32(addressHex, client.getBech32Hrp())
+    assert.deepStrictEqual(reached, {
+      address,
+      balance: amount,
+      outputs: [{ messageId, transactionId, outputIndex: 1, isSpent: false, address, addressHex, amount }]
+    })
+  })
+
+  it('resolves a zero amount on the first query', async () => {
+    const reached = await client.waitForBalance(addressHex, 0)
+    assert.strictEqual(node.balanceRequests, 1)
+    assert.strictEqual(reached.balance, 0)
+    assert.deepStrictEqual(reached.outputs, [])
+  })
+
+  it('rejects with EBALANCETIMEOUT after the timeout', async () => {
+    await assert.rejects(client.waitForBalance(addressHex, amount + 1, { interval: 20, timeout: 200 }), error => {
+      assert.strictEqual(error.code, 'EBALANCETIMEOUT')
+      assert.deepStrictEqual(error.details, {
+        address: hexToBech32(addressHex, client.getBech32Hrp()),
+        minAmount: amount + 1,
+        balance: amount
+      })
+      return true
+    })
+  })
+
+  it('rejects with the node error', async () => {
+    node.failing = true
+    await assert.rejects(client.waitForBalance(addressHex, amount, { timeout: 10000 }), error => {
+      assert.strictEqual(error.code, 'ERESPONSE')
+      assert.strictEqual(error.details.status, 500)
+      return true
+    })
+  })
+
+  it('rejects the invalid options', async () => {
+    await assert.rejects(client.waitForBalance(addressHex, -1), /invalid minAmount: -1/)
+    await assert.rejects(client.waitForBalance(addressHex, 1, { timeout: 0.5 }), /invalid timeout: 0.5/)
+  })
+})
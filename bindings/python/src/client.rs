@@ -0,0 +1,523 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The Python `Client`. Its requests release the GIL while they wait on the nodes and return the DTOs as dicts.
+
+use crate::{
+    address::{address_to_bech32, parse_address, AddressEncoding},
+    block_on,
+    dto::{MessageMetadataDto, MessageResponseDto, OutputMetadataDto, ReachedBalanceDto},
+    json_to_py, Error,
+};
+
+use iota::{
+    client::{types::Bech32Address, Bip39Seed, Method, SignerType},
+    MessageId, Seed, UTXOInput,
+};
+use pyo3::{
+    prelude::*,
+    types::{PyBytes, PyDict},
+};
+use serde::Serialize;
+
+use std::{convert::TryInto, str::FromStr, time::Duration};
+
+const BIP39_SEED_LENGTH: usize = 64;
+
+/// A hex encoded Ed25519 seed or BIP39 seed, as the seeds of the Node.js binding.
+enum ParsedSeed {
+    Seed(Seed),
+    Bip39Seed(Bip39Seed),
+}
+
+impl ParsedSeed {
+    fn parse(seed: &str) -> crate::Result<Self> {
+        let bytes = hex::decode(seed)?;
+        match bytes.len() {
+            BIP39_SEED_LENGTH => Bip39Seed::from_bytes(&bytes).map(Self::Bip39Seed).ok(),
+            _ => Seed::from_ed25519_bytes(&bytes).map(Self::Seed).ok(),
+        }
+        .ok_or_else(|| anyhow::anyhow!("invalid seed").into())
+    }
+
+    fn signer_type(&self) -> SignerType<'_> {
+        match self {
+            Self::Seed(seed) => SignerType::Seed(seed),
+            Self::Bip39Seed(seed) => SignerType::Bip39Seed(seed),
+        }
+    }
+}
+
+fn parse_message_id(message_id: &str) -> crate::Result<MessageId> {
+    MessageId::from_str(message_id).map_err(|_| anyhow::anyhow!("invalid message id `{}`", message_id).into())
+}
+
+#[derive(Serialize)]
+struct SentMessageDto {
+    #[serde(rename = "messageId")]
+    message_id: String,
+    message: MessageResponseDto,
+}
+
+/// `Client(nodes, network_id=None, bech32_hrp=None, min_pow_score=None, request_timeout=None, node_sync=True,
+/// local_pow=True, rate_limit_retries=None)`, the request timeout in seconds. The network parameters not set are the
+/// ones of the nodes.
+#[pyclass(module = "iota_client")]
+pub struct Client {
+    client: iota::Client,
+}
+
+impl Client {
+    // An address of the client network: a bech32 address as is, a hex address encoded with the network HRP.
+    fn bech32_address(&self, address: &str) -> crate::Result<String> {
+        let parsed = parse_address(address, None).map_err(|e| Error::InvalidAddress(address.to_string(), e))?;
+        match parsed.encoding {
+            AddressEncoding::Bech32 => Ok(address.to_string()),
+            AddressEncoding::Hex => address_to_bech32(&parsed.address(), &self.client.get_network_info().bech32_hrp),
+        }
+    }
+}
+
+#[pymethods]
+impl Client {
+    #[new]
+    #[args(
+        network_id = "None",
+        bech32_hrp = "None",
+        min_pow_score = "None",
+        request_timeout = "None",
+        node_sync = "true",
+        local_pow = "true",
+        rate_limit_retries = "None"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        py: Python<'_>,
+        nodes: Vec<String>,
+        network_id: Option<&str>,
+        bech32_hrp: Option<&str>,
+        min_pow_score: Option<f64>,
+        request_timeout: Option<f64>,
+        node_sync: bool,
+        local_pow: bool,
+        rate_limit_retries: Option<usize>,
+    ) -> PyResult<Self> {
+        let nodes: Vec<&str> = nodes.iter().map(String::as_str).collect();
+        let mut builder = iota::Client::build()
+            .with_nodes(&nodes)
+            .map_err(Error::from)?
+            .with_local_pow(local_pow);
+        if let Some(network_id) = network_id {
+            builder = builder.with_network_id(network_id);
+        }
+        if let Some(bech32_hrp) = bech32_hrp {
+            builder = builder.with_bech32_hrp(bech32_hrp);
+        }
+        if let Some(min_pow_score) = min_pow_score {
+            builder = builder.with_min_pow_score(min_pow_score);
+        }
+        if let Some(request_timeout) = request_timeout {
+            builder = builder.with_request_timeout(Duration::from_secs_f64(request_timeout));
+        }
+        if let Some(rate_limit_retries) = rate_limit_retries {
+            builder = builder.with_rate_limit_retries(rate_limit_retries);
+        }
+        if !node_sync {
+            builder = builder.with_node_sync_disabled();
+        }
+        // the node syncing waits on the nodes
+        let client = py.allow_threads(|| builder.finish()).map_err(Error::from)?;
+        Ok(Self { client })
+    }
+
+    /// The cached network parameters.
+    fn network_info(&self, py: Python<'_>) -> PyResult<PyObject> {
+        json_to_py(py, &serde_json::to_string(&self.client.get_network_info()).unwrap())
+    }
+
+    fn get_info(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let client = &self.client;
+        let info = py.allow_threads(|| block_on(client.get_info())).map_err(Error::from)?;
+        json_to_py(py, &serde_json::to_string(&info).unwrap())
+    }
+
+    /// The two tips `[message_id, message_id]`.
+    fn get_tips(&self, py: Python<'_>) -> PyResult<Vec<String>> {
+        let client = &self.client;
+        let (tip1, tip2) = py.allow_threads(|| block_on(client.get_tips())).map_err(Error::from)?;
+        Ok(vec![tip1.to_string(), tip2.to_string()])
+    }
+
+    /// Calls a route of the node API under `/api/` the client doesn't cover, such as the route of a node plugin,
+    /// returning the `data` of the JSON answer. The query values are converted with `str` and the body is any object
+    /// `json.dumps` takes.
+    #[args(query = "None", body = "None")]
+    fn call_node_api(
+        &self,
+        py: Python<'_>,
+        method: &str,
+        path: &str,
+        query: Option<&PyDict>,
+        body: Option<&PyAny>,
+    ) -> PyResult<PyObject> {
+        let query = match query {
+            Some(query) => query
+                .iter()
+                .map(|(name, value)| Ok((name.str()?.to_str()?.to_string(), value.str()?.to_str()?.to_string())))
+                .collect::<PyResult<Vec<(String, String)>>>()?,
+            None => Vec::new(),
+        };
+        let body = match body {
+            Some(body) => Some(py.import("json")?.call_method1("dumps", (body,))?.extract::<String>()?),
+            None => None,
+        };
+        let client = &self.client;
+        let json = py.allow_threads(|| -> crate::Result<String> {
+            let method =
+                Method::from_str(&method.to_uppercase()).map_err(|_| anyhow::anyhow!("invalid method `{}`", method))?;
+            let query: Vec<(&str, &str)> = query
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect();
+            let body = match body {
+                Some(body) => Some(
+                    serde_json::from_str::<serde_json::Value>(&body)
+                        .map_err(|e| anyhow::anyhow!("invalid body: {}", e))?,
+                ),
+                None => None,
+            };
+            let answer = block_on(client.call_node_api(method, path, &query, body.as_ref()))?;
+            Ok(serde_json::to_string(&answer).unwrap())
+        })?;
+        json_to_py(py, &json)
+    }
+
+    /// The message in the shape of the messages of the Node.js binding.
+    fn get_message_data(&self, py: Python<'_>, message_id: &str) -> PyResult<PyObject> {
+        let client = &self.client;
+        let json = py.allow_threads(|| -> crate::Result<String> {
+            let message = block_on(client.get_message().data(&parse_message_id(message_id)?))?;
+            let bech32_hrp = client.get_network_info().bech32_hrp;
+            Ok(serde_json::to_string(&MessageResponseDto::new(&message, &bech32_hrp)?).unwrap())
+        })?;
+        json_to_py(py, &json)
+    }
+
+    fn get_message_metadata(&self, py: Python<'_>, message_id: &str) -> PyResult<PyObject> {
+        let client = &self.client;
+        let metadata = py.allow_threads(|| -> crate::Result<_> {
+            Ok(block_on(client.get_message().metadata(&parse_message_id(message_id)?))?)
+        })?;
+        json_to_py(py, &serde_json::to_string(&MessageMetadataDto::from(metadata)).unwrap())
+    }
+
+    /// The packed bytes of the message.
+    fn get_message_raw(&self, py: Python<'_>, message_id: &str) -> PyResult<PyObject> {
+        let client = &self.client;
+        let bytes = py.allow_threads(|| -> crate::Result<_> {
+            Ok(block_on(client.get_message().raw(&parse_message_id(message_id)?))?)
+        })?;
+        Ok(PyBytes::new(py, &bytes).into())
+    }
+
+    /// The ids of the messages approving the message.
+    fn get_message_children(&self, py: Python<'_>, message_id: &str) -> PyResult<Vec<String>> {
+        let client = &self.client;
+        let message_ids = py.allow_threads(|| -> crate::Result<_> {
+            Ok(block_on(client.get_message().children(&parse_message_id(message_id)?))?)
+        })?;
+        Ok(message_ids.iter().map(ToString::to_string).collect())
+    }
+
+    /// The ids of the messages with the index.
+    fn find_messages_by_index(&self, py: Python<'_>, index: &str) -> PyResult<Vec<String>> {
+        let client = &self.client;
+        let message_ids = py
+            .allow_threads(|| block_on(client.get_message().index(index)))
+            .map_err(Error::from)?;
+        Ok(message_ids.iter().map(ToString::to_string).collect())
+    }
+
+    /// The output metadata, its address bech32 encoded with the network HRP.
+    fn get_output(&self, py: Python<'_>, output_id: &str) -> PyResult<PyObject> {
+        let client = &self.client;
+        let json = py.allow_threads(|| -> crate::Result<String> {
+            let output_id =
+                UTXOInput::from_str(output_id).map_err(|_| anyhow::anyhow!("invalid output id `{}`", output_id))?;
+            let output = block_on(client.get_output(&output_id))?;
+            let bech32_hrp = client.get_network_info().bech32_hrp;
+            Ok(serde_json::to_string(&OutputMetadataDto::new(output, &bech32_hrp)?).unwrap())
+        })?;
+        json_to_py(py, &json)
+    }
+
+    /// The ids of the unspent outputs of a bech32 or hex address.
+    fn get_address_outputs(&self, py: Python<'_>, address: &str) -> PyResult<Vec<String>> {
+        let outputs = py.allow_threads(|| -> crate::Result<_> {
+            let address = self.bech32_address(address)?;
+            Ok(block_on(self.client.get_address().outputs(&address.into()))?)
+        })?;
+        Ok(outputs.iter().map(ToString::to_string).collect())
+    }
+
+    /// The balance of a bech32 or hex address.
+    fn get_address_balance(&self, py: Python<'_>, address: &str) -> PyResult<u64> {
+        Ok(py.allow_threads(|| -> crate::Result<_> {
+            let address = self.bech32_address(address)?;
+            Ok(block_on(self.client.get_address().balance(&address.into()))?)
+        })?)
+    }
+
+    /// Waits until the confirmed balance of a bech32 or hex address reaches `min_amount`, returning the
+    /// `{ address, balance, outputs }` dict. The interval and the timeout are in seconds, an expired timeout raises a
+    /// `BalanceTimeoutError`.
+    #[args(interval = "None", timeout = "None")]
+    fn wait_for_balance(
+        &self,
+        py: Python<'_>,
+        address: &str,
+        min_amount: u64,
+        interval: Option<f64>,
+        timeout: Option<f64>,
+    ) -> PyResult<PyObject> {
+        let client = &self.client;
+        let json = py.allow_threads(|| -> crate::Result<String> {
+            let address = self.bech32_address(address)?;
+            let reached = block_on(client.wait_for_balance(
+                &address.into(),
+                min_amount,
+                interval.map(Duration::from_secs_f64),
+                timeout.map(Duration::from_secs_f64),
+            ))?;
+            let bech32_hrp = client.get_network_info().bech32_hrp;
+            Ok(serde_json::to_string(&ReachedBalanceDto::new(reached, &bech32_hrp)?).unwrap())
+        })?;
+        json_to_py(py, &json)
+    }
+
+    /// The public addresses of an account of a hex seed, bech32 encoded with the network HRP unless `bech32_hrp` is
+    /// set.
+    #[args(account_index = "0", start = "0", end = "20", bech32_hrp = "None")]
+    fn get_addresses(
+        &self,
+        seed: &str,
+        account_index: usize,
+        start: usize,
+        end: usize,
+        bech32_hrp: Option<String>,
+    ) -> PyResult<Vec<String>> {
+        let seed = ParsedSeed::parse(seed)?;
+        let bech32_hrp = bech32_hrp.unwrap_or_else(|| self.client.get_network_info().bech32_hrp);
+        let addresses = self
+            .client
+            .find_addresses(seed.signer_type())
+            .with_account_index(account_index)
+            .with_range(start..end)
+            .get_all()
+            .map_err(Error::from)?;
+        let mut bech32_addresses = Vec::new();
+        for (address, internal) in addresses {
+            if !internal {
+                let address = parse_address(&address.to_string(), None)
+                    .map_err(Error::from)?
+                    .address();
+                bech32_addresses.push(address_to_bech32(&address, &bech32_hrp)?);
+            }
+        }
+        Ok(bech32_addresses)
+    }
+
+    /// The balance of an account of a hex seed, only counting the outputs whose message is included in the ledger if
+    /// `confirmed_only` is set.
+    #[args(
+        account_index = "None",
+        initial_address_index = "None",
+        gap_limit = "None",
+        confirmed_only = "true"
+    )]
+    fn get_balance(
+        &self,
+        py: Python<'_>,
+        seed: &str,
+        account_index: Option<usize>,
+        initial_address_index: Option<usize>,
+        gap_limit: Option<usize>,
+        confirmed_only: bool,
+    ) -> PyResult<u64> {
+        let seed = ParsedSeed::parse(seed)?;
+        let client = &self.client;
+        let balance = py.allow_threads(|| {
+            let mut getter = client
+                .get_balance(seed.signer_type())
+                .with_confirmed_only(confirmed_only);
+            if let Some(account_index) = account_index {
+                getter = getter.with_account_index(account_index);
+            }
+            if let Some(initial_address_index) = initial_address_index {
+                getter = getter.with_initial_address_index(initial_address_index);
+            }
+            if let Some(gap_limit) = gap_limit {
+                getter = getter.with_gap_limit(gap_limit);
+            }
+            block_on(getter.finish())
+        });
+        Ok(balance.map_err(Error::from)?)
+    }
+
+    /// The transactions of the used addresses of an account of a hex seed, or of a list of bech32 or hex addresses,
+    /// sorted by the milestone referencing them. The `format` is `"entries"` for a list of dicts, `"jsonl"` or `"csv"`
+    /// for the exports of the history as a `str`.
+    #[args(
+        seed = "None",
+        addresses = "None",
+        account_index = "None",
+        initial_address_index = "None",
+        gap_limit = "None",
+        format = "\"entries\""
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn get_transaction_history(
+        &self,
+        py: Python<'_>,
+        seed: Option<&str>,
+        addresses: Option<Vec<String>>,
+        account_index: Option<usize>,
+        initial_address_index: Option<usize>,
+        gap_limit: Option<usize>,
+        format: &str,
+    ) -> PyResult<PyObject> {
+        if !["entries", "jsonl", "csv"].contains(&format) {
+            let error = anyhow::anyhow!("invalid format `{}`, expected entries, jsonl or csv", format);
+            return Err(Error::from(error).into());
+        }
+        let seed = seed.map(ParsedSeed::parse).transpose()?;
+        let client = &self.client;
+        let history = py.allow_threads(|| -> crate::Result<_> {
+            match (seed, addresses) {
+                (Some(seed), None) => {
+                    let mut getter = client.get_transaction_history(seed.signer_type());
+                    if let Some(account_index) = account_index {
+                        getter = getter.with_account_index(account_index);
+                    }
+                    if let Some(initial_address_index) = initial_address_index {
+                        getter = getter.with_initial_address_index(initial_address_index);
+                    }
+                    if let Some(gap_limit) = gap_limit {
+                        getter = getter.with_gap_limit(gap_limit);
+                    }
+                    Ok(block_on(getter.finish())?)
+                }
+                (None, Some(addresses)) => {
+                    let addresses = addresses
+                        .iter()
+                        .map(|address| self.bech32_address(address).map(Bech32Address))
+                        .collect::<crate::Result<Vec<Bech32Address>>>()?;
+                    Ok(block_on(client.get_address_transaction_history(&addresses))?)
+                }
+                _ => Err(anyhow::anyhow!("expected either a seed or a list of addresses").into()),
+            }
+        })?;
+        match format {
+            "jsonl" => Ok(history.to_json_lines().map_err(Error::from)?.into_py(py)),
+            "csv" => Ok(history.to_csv().into_py(py)),
+            _ => json_to_py(py, &serde_json::to_string(&history.entries).unwrap()),
+        }
+    }
+
+    /// Submits an indexation message, returning the `{ messageId, message }` dict of the message accepted by the
+    /// node. The data is `bytes` or a `str`. The `parents` are used instead of the tips of the node, the node must know
+    /// them unless `skip_verification` is set.
+    #[args(data = "None", parent = "None", parents = "None", skip_verification = "false")]
+    fn send_indexation(
+        &self,
+        py: Python<'_>,
+        index: &str,
+        data: Option<&PyAny>,
+        parent: Option<&str>,
+        parents: Option<Vec<&str>>,
+        skip_verification: bool,
+    ) -> PyResult<PyObject> {
+        let data = match data {
+            Some(data) => match data.extract::<&str>() {
+                Ok(data) => Some(data.as_bytes().to_vec()),
+                Err(_) => Some(data.downcast::<PyBytes>()?.as_bytes().to_vec()),
+            },
+            None => None,
+        };
+        let client = &self.client;
+        let json = py.allow_threads(|| -> crate::Result<String> {
+            let mut sender = client.send().with_index(index);
+            if let Some(data) = data {
+                sender = sender.with_data(data);
+            }
+            if let Some(parent) = parent {
+                sender = sender.with_parent(parse_message_id(parent)?);
+            }
+            if let Some(parents) = parents {
+                let parents = parents
+                    .into_iter()
+                    .map(parse_message_id)
+                    .collect::<crate::Result<Vec<MessageId>>>()?;
+                sender = sender.with_parents(parents)?;
+            }
+            if skip_verification {
+                sender = sender.with_parents_check_disabled();
+            }
+            let (message_id, message) = block_on(sender.finish_with_message())?;
+            let bech32_hrp = client.get_network_info().bech32_hrp;
+            Ok(serde_json::to_string(&SentMessageDto {
+                message_id: message_id.to_string(),
+                message: MessageResponseDto::new(&message, &bech32_hrp)?,
+            })
+            .unwrap())
+        })?;
+        json_to_py(py, &json)
+    }
+
+    /// Submits data of any length in indexation messages with the index, split into fragments filling a message,
+    /// returning the `{ messageIds, contentHash }` dict of the fragment messages in their order and the hex-encoded
+    /// BLAKE2b-256 hash of the data. The data is `bytes` or a `str`. With `chaining`, each fragment message is a parent
+    /// of the next one.
+    #[args(chaining = "false", fragment_length = "None")]
+    fn send_data(
+        &self,
+        py: Python<'_>,
+        index: &str,
+        data: &PyAny,
+        chaining: bool,
+        fragment_length: Option<usize>,
+    ) -> PyResult<PyObject> {
+        let data = match data.extract::<&str>() {
+            Ok(data) => data.as_bytes().to_vec(),
+            Err(_) => data.downcast::<PyBytes>()?.as_bytes().to_vec(),
+        };
+        let client = &self.client;
+        let json = py.allow_threads(|| -> crate::Result<String> {
+            let mut builder = client.data_message(index, data).with_chaining(chaining);
+            if let Some(fragment_length) = fragment_length {
+                builder = builder.with_fragment_length(fragment_length);
+            }
+            let data_message = block_on(builder.finish())?;
+            Ok(serde_json::json!({
+                "messageIds": data_message.message_ids.iter().map(|id| id.to_string()).collect::<Vec<String>>(),
+                "contentHash": hex::encode(data_message.content_hash),
+            })
+            .to_string())
+        })?;
+        json_to_py(py, &json)
+    }
+
+    /// The data of `send_data` reassembled from its fragments among the messages of the index, with the hex-encoded
+    /// content hash of the data.
+    fn reassemble_data(&self, py: Python<'_>, index: &str, content_hash: &str) -> PyResult<PyObject> {
+        let client = &self.client;
+        let data = py.allow_threads(|| -> crate::Result<_> {
+            let hash = hex::decode(content_hash)
+                .ok()
+                .and_then(|hash| hash.as_slice().try_into().ok())
+                .ok_or_else(|| anyhow::anyhow!("invalid content hash `{}`", content_hash))?;
+            Ok(block_on(client.reassemble_data(index, &hash))?)
+        })?;
+        Ok(PyBytes::new(py, &data).into())
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The exceptions raised to Python, a `ClientError` subclass for the main error codes of the Node.js binding:
+//!
+//! ```text
+//! ClientError
+//! ├── BalanceTimeoutError      EBALANCETIMEOUT
+//! ├── InvalidAddressError      EINVALIDADDRESS
+//! ├── InvalidParameterError    EINVALIDPARAMETER
+//! ├── NetworkMismatchError     ENETWORKMISMATCH
+//! ├── NotEnoughBalanceError    ENOTENOUGHBALANCE
+//! ├── PowError                 EPOW
+//! └── NodeError
+//!     ├── NodeUnreachableError ENODEUNREACHABLE
+//!     ├── RateLimitedError     ERATELIMITED
+//!     ├── RequestTimeoutError  ETIMEDOUT
+//!     ├── ResponseError        ERESPONSE
+//!     └── UnauthorizedError    EUNAUTHORIZED
+//! ```
+//!
+//! Every exception has the `code` and the `details` dict of the JS errors, the errors of the other codes and without
+//! a code raise a `ClientError`.
+
+use pyo3::{
+    create_exception,
+    exceptions::PyException,
+    prelude::*,
+    types::{PyDict, PyList, PyModule},
+};
+
+use crate::{
+    error_codes::{error_code, Detail},
+    Error,
+};
+
+create_exception!(iota_client, ClientError, PyException);
+create_exception!(iota_client, BalanceTimeoutError, ClientError);
+create_exception!(iota_client, InvalidAddressError, ClientError);
+create_exception!(iota_client, InvalidParameterError, ClientError);
+create_exception!(iota_client, NetworkMismatchError, ClientError);
+create_exception!(iota_client, NotEnoughBalanceError, ClientError);
+create_exception!(iota_client, PowError, ClientError);
+create_exception!(iota_client, NodeError, ClientError);
+create_exception!(iota_client, NodeUnreachableError, NodeError);
+create_exception!(iota_client, RateLimitedError, NodeError);
+create_exception!(iota_client, RequestTimeoutError, NodeError);
+create_exception!(iota_client, ResponseError, NodeError);
+create_exception!(iota_client, UnauthorizedError, NodeError);
+
+pub(crate) fn register(py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add("ClientError", py.get_type::<ClientError>())?;
+    m.add("BalanceTimeoutError", py.get_type::<BalanceTimeoutError>())?;
+    m.add("InvalidAddressError", py.get_type::<InvalidAddressError>())?;
+    m.add("InvalidParameterError", py.get_type::<InvalidParameterError>())?;
+    m.add("NetworkMismatchError", py.get_type::<NetworkMismatchError>())?;
+    m.add("NotEnoughBalanceError", py.get_type::<NotEnoughBalanceError>())?;
+    m.add("PowError", py.get_type::<PowError>())?;
+    m.add("NodeError", py.get_type::<NodeError>())?;
+    m.add("NodeUnreachableError", py.get_type::<NodeUnreachableError>())?;
+    m.add("RateLimitedError", py.get_type::<RateLimitedError>())?;
+    m.add("RequestTimeoutError", py.get_type::<RequestTimeoutError>())?;
+    m.add("ResponseError", py.get_type::<ResponseError>())?;
+    m.add("UnauthorizedError", py.get_type::<UnauthorizedError>())?;
+    Ok(())
+}
+
+fn detail_to_py(py: Python<'_>, detail: Detail) -> PyResult<PyObject> {
+    Ok(match detail {
+        Detail::String(value) => value.into_py(py),
+        // the details are JS numbers, the integers are given back as `int`
+        Detail::Number(value) if value.fract() == 0.0 && value.abs() < i64::MAX as f64 => (value as i64).into_py(py),
+        Detail::Number(value) => value.into_py(py),
+        Detail::Strings(values) => values.into_py(py),
+        Detail::Numbers(values) => values
+            .into_iter()
+            .map(|value| detail_to_py(py, Detail::Number(value)))
+            .collect::<PyResult<Vec<PyObject>>>()?
+            .into_py(py),
+        Detail::Answers(answers) => {
+            let py_answers = PyList::empty(py);
+            for (node, answer) in answers {
+                let py_answer = PyDict::new(py);
+                py_answer.set_item("node", node)?;
+                py_answer.set_item("answer", answer)?;
+                py_answers.append(py_answer)?;
+            }
+            py_answers.into()
+        }
+    })
+}
+
+impl From<Error> for PyErr {
+    fn from(error: Error) -> Self {
+        Python::with_gil(|py| {
+            let message = error.to_string();
+            let (code, details) = match error_code(&error) {
+                Some((code, details)) => (Some(code), details),
+                None => (None, Vec::new()),
+            };
+            let err = match code {
+                Some("EBALANCETIMEOUT") => BalanceTimeoutError::new_err(message),
+                Some("EINVALIDADDRESS") => InvalidAddressError::new_err(message),
+                Some("EINVALIDPARAMETER") => InvalidParameterError::new_err(message),
+                Some("ENETWORKMISMATCH") => NetworkMismatchError::new_err(message),
+                Some("ENOTENOUGHBALANCE") => NotEnoughBalanceError::new_err(message),
+                Some("EPOW") => PowError::new_err(message),
+                Some("ENODEUNREACHABLE") => NodeUnreachableError::new_err(message),
+                Some("ERATELIMITED") => RateLimitedError::new_err(message),
+                Some("ETIMEDOUT") => RequestTimeoutError::new_err(message),
+                Some("ERESPONSE") => ResponseError::new_err(message),
+                Some("EUNAUTHORIZED") => UnauthorizedError::new_err(message),
+                _ => ClientError::new_err(message),
+            };
+            let py_details = PyDict::new(py);
+            for (key, detail) in details {
+                let value = detail_to_py(py, detail).expect("failed to convert the error details");
+                py_details
+                    .set_item(key, value)
+                    .expect("failed to set the error details");
+            }
+            let instance = err.instance(py);
+            instance.setattr("code", code).expect("failed to set the error code");
+            instance
+                .setattr("details", py_details)
+                .expect("failed to set the error details");
+            err
+        })
+    }
+}
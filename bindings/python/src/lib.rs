@@ -0,0 +1,121 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Python binding to the client library. The address utilities, the DTOs and the error codes are the ones of the
+//! Node.js binding, the DTOs are returned as dicts with the same keys as the JS objects.
+
+#[path = "../../node/native/src/address.rs"]
+mod address;
+mod client;
+// the DTOs of the Node.js binding, only a part of them is used here
+#[allow(dead_code)]
+#[path = "../../node/native/src/classes/client/dto.rs"]
+mod dto;
+#[path = "../../node/native/src/error_codes.rs"]
+mod error_codes;
+mod exceptions;
+
+use address::{AddressError, Bech32Error};
+use once_cell::sync::OnceCell;
+use pyo3::{prelude::*, types::PyModule, wrap_pyfunction};
+use tokio::runtime::Runtime;
+
+use std::future::Future;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0}")]
+    Anyhow(#[from] anyhow::Error),
+    #[error("{0}")]
+    Client(#[from] iota::client::Error),
+    #[error("{0}")]
+    Address(#[from] AddressError),
+    #[error("{0}")]
+    Bech32(#[from] Bech32Error),
+    #[error("invalid address `{0}`: {1}")]
+    InvalidAddress(String, AddressError),
+    #[error("{0}")]
+    Hex(#[from] hex::FromHexError),
+    /// A panic of the binding, not constructed here since PyO3 raises the panics as `PanicException`.
+    #[error("{0}")]
+    Panic(String),
+    #[error("{0}")]
+    Message(iota::message::Error),
+}
+
+impl From<iota::message::Error> for Error {
+    fn from(error: iota::message::Error) -> Self {
+        Self::Message(error)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Runs the future on the shared runtime. Call it in `Python::allow_threads` so the other Python threads run while
+/// the requests wait on the nodes.
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    static INSTANCE: OnceCell<Runtime> = OnceCell::new();
+    let runtime = INSTANCE.get_or_init(|| Runtime::new().unwrap());
+    runtime.block_on(future)
+}
+
+/// Converts the JSON of a DTO to the Python dicts and lists, with the `json` module.
+pub(crate) fn json_to_py(py: Python<'_>, json: &str) -> PyResult<PyObject> {
+    Ok(py.import("json")?.call_method1("loads", (json,))?.into())
+}
+
+/// `is_address_valid(address, expected_hrp=None)`
+#[pyfunction(expected_hrp = "None")]
+fn is_address_valid(address: &str, expected_hrp: Option<&str>) -> bool {
+    address::parse_address(address, expected_hrp).is_ok()
+}
+
+/// `parse_address(address, expected_hrp=None)`, returning the `{ encoding, hrp, bytes }` dict of the address.
+#[pyfunction(expected_hrp = "None")]
+fn parse_address(py: Python<'_>, address: &str, expected_hrp: Option<&str>) -> PyResult<PyObject> {
+    let parsed =
+        address::parse_address(address, expected_hrp).map_err(|e| Error::InvalidAddress(address.to_string(), e))?;
+    json_to_py(py, &serde_json::to_string(&parsed).unwrap())
+}
+
+/// `hex_to_bech32(address, bech32_hrp)`
+#[pyfunction]
+fn hex_to_bech32(address: &str, bech32_hrp: &str) -> PyResult<String> {
+    address::hex_to_bech32(address, bech32_hrp).map_err(|e| match e {
+        Error::Address(e) => Error::InvalidAddress(address.to_string(), e).into(),
+        e => e.into(),
+    })
+}
+
+/// `bech32_to_hex(address)`, returning the `{ hex, hrp }` dict of the address.
+#[pyfunction]
+fn bech32_to_hex(py: Python<'_>, address: &str) -> PyResult<PyObject> {
+    let address = address::bech32_to_hex(address).map_err(|e| Error::InvalidAddress(address.to_string(), e))?;
+    json_to_py(py, &serde_json::to_string(&address).unwrap())
+}
+
+/// `bech32_encode(hrp, data_hex)`, the data starting with the version or address type byte.
+#[pyfunction]
+fn bech32_encode(hrp: &str, data_hex: &str) -> PyResult<String> {
+    let data = hex::decode(data_hex.strip_prefix("0x").unwrap_or(data_hex)).map_err(Error::from)?;
+    Ok(address::bech32_encode(hrp, &data).map_err(Error::from)?)
+}
+
+/// `bech32_decode(bech32_string)`, returning the `{ hrp, version, payload }` dict of the string.
+#[pyfunction]
+fn bech32_decode(py: Python<'_>, bech32_string: &str) -> PyResult<PyObject> {
+    let decoded = address::bech32_decode(bech32_string).map_err(Error::from)?;
+    json_to_py(py, &serde_json::to_string(&decoded).unwrap())
+}
+
+#[pymodule]
+fn iota_client(py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<client::Client>()?;
+    m.add_function(wrap_pyfunction!(is_address_valid, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_address, m)?)?;
+    m.add_function(wrap_pyfunction!(hex_to_bech32, m)?)?;
+    m.add_function(wrap_pyfunction!(bech32_to_hex, m)?)?;
+    m.add_function(wrap_pyfunction!(bech32_encode, m)?)?;
+    m.add_function(wrap_pyfunction!(bech32_decode, m)?)?;
+    exceptions::register(py, m)
+}
@@ -0,0 +1,110 @@
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+from urllib.parse import parse_qs, urlparse
+
+import pytest
+
+TIP_ID = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+
+
+class MockNode(ThreadingHTTPServer):
+    """A node recording the posted messages and serving them back under the tip id, or under their own id with
+    `distinct_ids`."""
+
+    def __init__(self):
+        super().__init__(('127.0.0.1', 0), MockNodeHandler)
+        self.messages = []
+        self.message_ids = []
+        self.distinct_ids = False
+        # the messages are posted concurrently
+        self.lock = threading.Lock()
+        # the tips requests answered with 429 and `Retry-After: 1` before the tips
+        self.rate_limited = 0
+        self.url = 'http://127.0.0.1:{}'.format(self.server_address[1])
+
+
+class MockNodeHandler(BaseHTTPRequestHandler):
+    def log_message(self, *args):
+        pass
+
+    def respond(self, status, data=None, headers=()):
+        body = json.dumps({'data': data}).encode() if data is not None else b''
+        self.send_response(status)
+        for name, value in headers:
+            self.send_header(name, value)
+        self.send_header('content-type', 'application/json')
+        self.send_header('content-length', str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+    def do_GET(self):
+        url = urlparse(self.path)
+        query = parse_qs(url.query)
+        messages = self.server.messages
+        message_ids = self.server.message_ids
+        message_id = url.path[len('/api/v1/messages/'):] if url.path.startswith('/api/v1/messages/') else None
+        if url.path == '/api/v1/info':
+            self.respond(200, {
+                'name': 'HORNET',
+                'version': '0.6.0',
+                'isHealthy': True,
+                'networkId': 'testnet',
+                'bech32HRP': 'atoi',
+                'minPowScore': 10,
+                'latestMilestoneIndex': 1,
+                'solidMilestoneIndex': 1,
+                'pruningIndex': 0,
+                'features': [],
+            })
+        elif url.path == '/api/v1/tips' and self.server.rate_limited > 0:
+            self.server.rate_limited -= 1
+            self.respond(429, headers=[('retry-after', '1')])
+        elif url.path == '/api/v1/tips':
+            self.respond(200, {'tip1MessageId': TIP_ID, 'tip2MessageId': TIP_ID})
+        elif url.path == '/api/v1/messages' and 'index' in query:
+            index = query['index'][0]
+            # the messages of the tip id are listed once
+            indexed = [i for i, m in zip(message_ids, messages) if m['payload'].get('index') == index]
+            indexed = list(dict.fromkeys(indexed))
+            self.respond(200, {
+                'index': index,
+                'maxResults': 1000,
+                'count': len(indexed),
+                'messageIds': indexed,
+            })
+        elif message_id in message_ids:
+            # the last message posted under the id
+            self.respond(200, messages[len(message_ids) - 1 - message_ids[::-1].index(message_id)])
+        elif url.path == '/api/plugins/echo':
+            # a plugin route echoing the query
+            self.respond(200, {'query': {name: values[0] for name, values in query.items()}})
+        elif url.path.startswith('/api/v1/addresses/') and url.path.endswith('/outputs'):
+            # the addresses don't have outputs
+            address = url.path[len('/api/v1/addresses/'):-len('/outputs')]
+            self.respond(200, {'address': address, 'maxResults': 1000, 'count': 0, 'outputIds': []})
+        else:
+            self.respond(404)
+
+    def do_POST(self):
+        body = self.rfile.read(int(self.headers['content-length']))
+        if self.path == '/api/v1/messages':
+            with self.server.lock:
+                self.server.messages.append(json.loads(body))
+                message_id = '{:064x}'.format(len(self.server.messages)) if self.server.distinct_ids else TIP_ID
+                self.server.message_ids.append(message_id)
+            self.respond(201, {'messageId': message_id})
+        elif self.path == '/api/plugins/echo':
+            self.respond(200, {'body': json.loads(body)})
+        else:
+            self.respond(404)
+
+
+@pytest.fixture
+def node():
+    node = MockNode()
+    thread = threading.Thread(target=node.serve_forever, daemon=True)
+    thread.start()
+    yield node
+    node.shutdown()
+    node.server_close()
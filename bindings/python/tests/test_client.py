@@ -0,0 +1,173 @@
+import threading
+import time
+
+import pytest
+
+import iota_client
+from conftest import TIP_ID
+
+SEED = '256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2'
+INDEX = 'IOTA.RS PYTHON'
+
+
+def client(node):
+    return iota_client.Client([node.url], node_sync=False, local_pow=True)
+
+
+def test_submit_indexation(node):
+    sent = client(node).send_indexation(INDEX, data=b'python round trip')
+    assert sent['messageId'] == TIP_ID
+    assert sent['message']['payload']['index'] == INDEX
+    assert bytes(sent['message']['payload']['data']) == b'python round trip'
+    # the network parameters are the ones of the node
+    assert node.messages[0]['networkId'] == sent['message']['networkId']
+
+
+def test_fetch_message(node):
+    c = client(node)
+    sent = c.send_indexation(INDEX, data='python round trip')
+    message = c.get_message_data(TIP_ID)
+    assert message['parent1'] == TIP_ID
+    assert message['payload'] == sent['message']['payload']
+
+
+def test_find_by_index(node):
+    c = client(node)
+    assert c.find_messages_by_index(INDEX) == []
+    c.send_indexation(INDEX)
+    assert c.find_messages_by_index(INDEX) == [TIP_ID]
+
+
+def test_send_indexation_parents(node):
+    c = client(node)
+    # the node doesn't know the metadata of the tip
+    with pytest.raises(iota_client.ClientError) as error:
+        c.send_indexation(INDEX, parents=[TIP_ID])
+    assert error.value.code == 'EUNKNOWNPARENTS'
+    assert error.value.details == {'parents': [TIP_ID]}
+    assert node.messages == []
+    c.send_indexation(INDEX, parents=[TIP_ID], skip_verification=True)
+    assert node.messages[0]['parent1MessageId'] == TIP_ID
+    assert node.messages[0]['parent2MessageId'] == TIP_ID
+
+
+def test_release_the_gil(node):
+    # the requests of several threads run at once
+    c = client(node)
+    tips = []
+    threads = [threading.Thread(target=lambda: tips.append(c.get_tips())) for _ in range(4)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+    assert tips == [[TIP_ID, TIP_ID]] * 4
+
+
+def test_errors(node):
+    c = client(node)
+    with pytest.raises(iota_client.ResponseError) as error:
+        c.get_message_metadata(TIP_ID)
+    assert isinstance(error.value, iota_client.NodeError)
+    assert error.value.code == 'ERESPONSE'
+    assert error.value.details['status'] == 404
+    assert error.value.details['endpoint'] == f'GET /api/v1/messages/{TIP_ID}/metadata'
+    assert error.value.details['node'] == node.url + '/'
+
+    with pytest.raises(iota_client.InvalidAddressError) as error:
+        c.get_address_balance('atoi1invalid')
+    assert error.value.code == 'EINVALIDADDRESS'
+    assert isinstance(error.value, iota_client.ClientError)
+
+
+
+def test_call_node_api(node):
+    c = client(node)
+    assert c.call_node_api('GET', '/api/plugins/echo', query={'type': 0, 'limit': 2}) == {
+        'query': {'type': '0', 'limit': '2'},
+    }
+    body = {'value': 1, 'tags': ['a', 'b']}
+    assert c.call_node_api('POST', '/api/plugins/echo', body=body) == {'body': body}
+
+    with pytest.raises(iota_client.InvalidParameterError):
+        c.call_node_api('GET', '/api/v1/../../health')
+    with pytest.raises(iota_client.ResponseError) as error:
+        c.call_node_api('GET', '/api/v1/unknown')
+    assert error.value.details['status'] == 404
+
+
+def test_data_round_trip(node):
+    node.distinct_ids = True
+    c = client(node)
+    data = bytes((i * 7 + i // 256) % 256 for i in range(100000))
+    sent = c.send_data(INDEX, data)
+    assert len(sent['messageIds']) == 4
+    assert sent['messageIds'] == node.message_ids
+    assert c.reassemble_data(INDEX, sent['contentHash']) == data
+    # a fragment missing from the index lookup
+    node.messages[2]['payload']['index'] = 'elsewhere'
+    with pytest.raises(iota_client.ClientError) as error:
+        c.reassemble_data(INDEX, sent['contentHash'])
+    assert error.value.code == 'EMISSINGFRAGMENTS'
+    assert error.value.details == {'contentHash': sent['contentHash'], 'missing': [2], 'fragmentCount': 4}
+
+
+def test_rate_limit(node):
+    node.rate_limited = 2
+    start = time.monotonic()
+    assert client(node).get_tips() == [TIP_ID, TIP_ID]
+    assert time.monotonic() - start >= 2
+
+    node.rate_limited = 2
+    c = iota_client.Client([node.url], node_sync=False, rate_limit_retries=1)
+    with pytest.raises(iota_client.RateLimitedError) as error:
+        c.get_tips()
+    assert isinstance(error.value, iota_client.NodeError)
+    assert error.value.code == 'ERATELIMITED'
+    assert error.value.details['status'] == 429
+    assert error.value.details['retryAfterMs'] == 1000
+
+def test_transaction_history_unused_address(node):
+    c = client(node)
+    [address] = c.get_addresses(SEED, account_index=0, start=0, end=1, bech32_hrp='atoi')
+    assert c.get_transaction_history(addresses=[address]) == []
+    assert c.get_transaction_history(addresses=[address], format='jsonl') == ''
+    assert c.get_transaction_history(addresses=[address], format='csv') == (
+        'milestoneIndex,timestamp,messageId,transactionId,address,delta,counterparts,inclusionState\n'
+    )
+
+    with pytest.raises(iota_client.ClientError, match='invalid format'):
+        c.get_transaction_history(addresses=[address], format='xml')
+    with pytest.raises(iota_client.ClientError, match='either a seed or a list of addresses'):
+        c.get_transaction_history(seed=SEED, addresses=[address])
+
+
+def test_wait_for_balance_node_error(node):
+    # the mock node doesn't know the address, the wait fails with the node error instead of timing out
+    c = client(node)
+    [address] = c.get_addresses(SEED, account_index=0, start=0, end=1, bech32_hrp='atoi')
+    with pytest.raises(iota_client.ResponseError) as error:
+        c.wait_for_balance(address, 1000000, interval=0.05, timeout=5)
+    assert error.value.code == 'ERESPONSE'
+    assert not isinstance(error.value, iota_client.BalanceTimeoutError)
+
+
+def test_addresses(node):
+    c = client(node)
+    [address] = c.get_addresses(SEED, account_index=0, start=0, end=1, bech32_hrp='atoi')
+    assert iota_client.is_address_valid(address, 'atoi')
+    assert not iota_client.is_address_valid(address, 'iota')
+    converted = iota_client.bech32_to_hex(address)
+    assert converted['hrp'] == 'atoi'
+    assert iota_client.hex_to_bech32(converted['hex'], 'atoi') == address
+    assert iota_client.parse_address(converted['hex'])['encoding'] == 'hex'
+
+
+def test_bech32():
+    hex_address = '644eb5594b880f7da82f35902296199a0eb9186999797f661b324f8d754a3a09'
+    address = iota_client.bech32_encode('dev', '01' + hex_address)
+    assert address == iota_client.hex_to_bech32(hex_address, 'dev')
+    assert iota_client.bech32_decode(address) == {'hrp': 'dev', 'version': 1, 'payload': hex_address}
+    with pytest.raises(iota_client.InvalidParameterError) as error:
+        iota_client.bech32_decode('A1G7SGD8')
+    assert error.value.code == 'EINVALIDPARAMETER'
+    assert 'invalid bech32 checksum' in str(error.value)
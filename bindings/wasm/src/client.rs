@@ -0,0 +1,467 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The JS `ClientBuilder`, the `Client` and the builders of its requests. The builders are consumed by their methods,
+//! as in `new ClientBuilder().node(url).build()`.
+
+use crate::{
+    address::{address_to_bech32, parse_address, AddressEncoding},
+    dto::{MessageMetadataDto, MessageResponseDto, OutputMetadataDto},
+    js_error, to_js, Error,
+};
+
+use iota::{
+    client::{Bip39Seed, SignerType},
+    MessageId, Seed, UTXOInput,
+};
+use js_sys::{Promise, Uint8Array};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+
+use std::{ops::Range, rc::Rc, str::FromStr, time::Duration};
+
+type JsResult<T> = std::result::Result<T, JsValue>;
+
+const BIP39_SEED_LENGTH: usize = 64;
+
+/// A hex encoded Ed25519 seed or BIP39 seed, as the seeds of the Node.js binding.
+enum ParsedSeed {
+    Seed(Seed),
+    Bip39Seed(Bip39Seed),
+}
+
+impl ParsedSeed {
+    fn parse(seed: &str) -> crate::Result<Self> {
+        let bytes = hex::decode(seed)?;
+        match bytes.len() {
+            BIP39_SEED_LENGTH => Bip39Seed::from_bytes(&bytes).map(Self::Bip39Seed).ok(),
+            _ => Seed::from_ed25519_bytes(&bytes).map(Self::Seed).ok(),
+        }
+        .ok_or_else(|| anyhow::anyhow!("invalid seed").into())
+    }
+
+    fn signer_type(&self) -> SignerType<'_> {
+        match self {
+            Self::Seed(seed) => SignerType::Seed(seed),
+            Self::Bip39Seed(seed) => SignerType::Bip39Seed(seed),
+        }
+    }
+}
+
+fn parse_message_id(message_id: &str) -> crate::Result<MessageId> {
+    MessageId::from_str(message_id).map_err(|_| anyhow::anyhow!("invalid message id `{}`", message_id).into())
+}
+
+/// Resolves the promise with the value of the future or rejects it with its error.
+fn promise<F, T>(future: F) -> Promise
+where
+    F: std::future::Future<Output = crate::Result<T>> + 'static,
+    T: Into<JsValue>,
+{
+    future_to_promise(async move { future.await.map(Into::into).map_err(js_error) })
+}
+
+/// JS `new ClientBuilder()`. The nodes aren't synced, a browser has no thread to sync them in the background, and the
+/// network parameters not set are fetched from a node on the first request needing them.
+#[wasm_bindgen]
+pub struct ClientBuilder {
+    builder: iota::ClientBuilder,
+}
+
+#[wasm_bindgen]
+impl ClientBuilder {
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            builder: iota::Client::build().with_node_sync_disabled(),
+        }
+    }
+
+    pub fn node(self, url: &str) -> JsResult<ClientBuilder> {
+        let builder = self.builder.with_node(url).map_err(js_error)?;
+        Ok(Self { builder })
+    }
+
+    pub fn nodes(self, urls: Box<[JsValue]>) -> JsResult<ClientBuilder> {
+        let urls = urls
+            .iter()
+            .map(|url| {
+                url.as_string()
+                    .ok_or_else(|| js_error(anyhow::anyhow!("the node URLs must be strings")))
+            })
+            .collect::<JsResult<Vec<String>>>()?;
+        let urls: Vec<&str> = urls.iter().map(String::as_str).collect();
+        let builder = self.builder.with_nodes(&urls).map_err(js_error)?;
+        Ok(Self { builder })
+    }
+
+    #[wasm_bindgen(js_name = networkId)]
+    pub fn network_id(self, network_id: &str) -> ClientBuilder {
+        Self {
+            builder: self.builder.with_network_id(network_id),
+        }
+    }
+
+    #[wasm_bindgen(js_name = bech32Hrp)]
+    pub fn bech32_hrp(self, hrp: &str) -> ClientBuilder {
+        Self {
+            builder: self.builder.with_bech32_hrp(hrp),
+        }
+    }
+
+    #[wasm_bindgen(js_name = minPowScore)]
+    pub fn min_pow_score(self, score: f64) -> ClientBuilder {
+        Self {
+            builder: self.builder.with_min_pow_score(score),
+        }
+    }
+
+    #[wasm_bindgen(js_name = requestTimeout)]
+    pub fn request_timeout(self, timeout_ms: u32) -> ClientBuilder {
+        Self {
+            builder: self
+                .builder
+                .with_request_timeout(Duration::from_millis(timeout_ms as u64)),
+        }
+    }
+
+    pub fn build(self) -> JsResult<Client> {
+        let client = self.builder.finish().map_err(js_error)?;
+        Ok(Client {
+            client: Rc::new(client),
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct SentMessageDto {
+    #[serde(rename = "messageId")]
+    message_id: String,
+    message: MessageResponseDto,
+}
+
+/// JS `Client`, created with the `ClientBuilder`. The requests return promises.
+#[wasm_bindgen]
+pub struct Client {
+    client: Rc<iota::Client>,
+}
+
+#[wasm_bindgen]
+impl Client {
+    /// The cached network parameters, as `networkInfo()` of the Node.js binding.
+    #[wasm_bindgen(js_name = networkInfo)]
+    pub fn network_info(&self) -> JsValue {
+        to_js(&self.client.get_network_info())
+    }
+
+    /// Fetches the network parameters from a node, before deriving the addresses of a network only known by the nodes.
+    #[wasm_bindgen(js_name = refreshNetworkInfo)]
+    pub fn refresh_network_info(&self) -> Promise {
+        let client = self.client.clone();
+        promise(async move { Ok(to_js(&client.refresh_network_info().await?)) })
+    }
+
+    #[wasm_bindgen(js_name = getInfo)]
+    pub fn get_info(&self) -> Promise {
+        let client = self.client.clone();
+        promise(async move { Ok(to_js(&client.get_info().await?)) })
+    }
+
+    /// Resolves to the two tips `[messageId, messageId]`.
+    #[wasm_bindgen(js_name = getTips)]
+    pub fn get_tips(&self) -> Promise {
+        let client = self.client.clone();
+        promise(async move {
+            let (tip1, tip2) = client.get_tips().await?;
+            Ok(to_js(&[tip1.to_string(), tip2.to_string()]))
+        })
+    }
+
+    #[wasm_bindgen(js_name = getAddresses)]
+    pub fn get_addresses(&self, seed: String) -> AddressGetter {
+        AddressGetter {
+            client: self.client.clone(),
+            seed,
+            account_index: 0,
+            range: None,
+            bech32_hrp: None,
+        }
+    }
+
+    #[wasm_bindgen(js_name = getBalance)]
+    pub fn get_balance(&self, seed: String) -> BalanceGetter {
+        BalanceGetter {
+            client: self.client.clone(),
+            seed,
+            account_index: None,
+            initial_address_index: None,
+            gap_limit: None,
+        }
+    }
+
+    pub fn send(&self) -> MessageSender {
+        MessageSender {
+            client: self.client.clone(),
+            index: None,
+            data: None,
+            parent: None,
+        }
+    }
+
+    #[wasm_bindgen(js_name = getMessage)]
+    pub fn get_message(&self) -> MessageGetter {
+        MessageGetter {
+            client: self.client.clone(),
+        }
+    }
+
+    /// Resolves to the output metadata, its address bech32 encoded with the network HRP.
+    #[wasm_bindgen(js_name = getOutput)]
+    pub fn get_output(&self, output_id: String) -> Promise {
+        let client = self.client.clone();
+        promise(async move {
+            let output_id =
+                UTXOInput::from_str(&output_id).map_err(|_| anyhow::anyhow!("invalid output id `{}`", output_id))?;
+            let output = client.get_output(&output_id).await?;
+            let bech32_hrp = client.get_network_info().bech32_hrp;
+            Ok(to_js(&OutputMetadataDto::new(output, &bech32_hrp)?))
+        })
+    }
+
+    /// Resolves to the ids of the unspent outputs of a bech32 or hex address.
+    #[wasm_bindgen(js_name = getAddressOutputs)]
+    pub fn get_address_outputs(&self, address: String) -> Promise {
+        let client = self.client.clone();
+        promise(async move {
+            let parsed = parse_address(&address, None).map_err(|e| Error::InvalidAddress(address.clone(), e))?;
+            let address = match parsed.encoding {
+                AddressEncoding::Bech32 => address,
+                AddressEncoding::Hex => address_to_bech32(&parsed.address(), &client.get_network_info().bech32_hrp)?,
+            };
+            let outputs = client.get_address().outputs(&address.into()).await?;
+            Ok(to_js(&outputs.iter().map(ToString::to_string).collect::<Vec<String>>()))
+        })
+    }
+}
+
+/// JS `client.getAddresses(seed)`, the public addresses of an account.
+#[wasm_bindgen]
+pub struct AddressGetter {
+    client: Rc<iota::Client>,
+    seed: String,
+    account_index: usize,
+    range: Option<Range<usize>>,
+    bech32_hrp: Option<String>,
+}
+
+#[wasm_bindgen]
+impl AddressGetter {
+    #[wasm_bindgen(js_name = accountIndex)]
+    pub fn account_index(mut self, index: usize) -> AddressGetter {
+        self.account_index = index;
+        self
+    }
+
+    pub fn range(mut self, start: usize, end: usize) -> AddressGetter {
+        self.range.replace(start..end);
+        self
+    }
+
+    /// The HRP of the addresses, the network HRP by default.
+    #[wasm_bindgen(js_name = bech32Hrp)]
+    pub fn bech32_hrp(mut self, hrp: String) -> AddressGetter {
+        self.bech32_hrp.replace(hrp);
+        self
+    }
+
+    /// Derives the addresses, returning an array of bech32 addresses.
+    pub fn get(self) -> JsResult<JsValue> {
+        self.addresses().map(|addresses| to_js(&addresses)).map_err(js_error)
+    }
+
+    fn addresses(&self) -> crate::Result<Vec<String>> {
+        let seed = ParsedSeed::parse(&self.seed)?;
+        let bech32_hrp = self
+            .bech32_hrp
+            .clone()
+            .unwrap_or_else(|| self.client.get_network_info().bech32_hrp);
+        let mut getter = self
+            .client
+            .find_addresses(seed.signer_type())
+            .with_account_index(self.account_index);
+        if let Some(range) = &self.range {
+            getter = getter.with_range(range.clone());
+        }
+        let mut addresses = Vec::new();
+        for (address, internal) in getter.get_all()? {
+            if !internal {
+                let address = parse_address(&address.to_string(), None)?.address();
+                addresses.push(address_to_bech32(&address, &bech32_hrp)?);
+            }
+        }
+        Ok(addresses)
+    }
+}
+
+/// JS `client.getBalance(seed)`, the balance of an account.
+#[wasm_bindgen]
+pub struct BalanceGetter {
+    client: Rc<iota::Client>,
+    seed: String,
+    account_index: Option<usize>,
+    initial_address_index: Option<usize>,
+    gap_limit: Option<usize>,
+}
+
+#[wasm_bindgen]
+impl BalanceGetter {
+    #[wasm_bindgen(js_name = accountIndex)]
+    pub fn account_index(mut self, index: usize) -> BalanceGetter {
+        self.account_index.replace(index);
+        self
+    }
+
+    #[wasm_bindgen(js_name = initialAddressIndex)]
+    pub fn initial_address_index(mut self, index: usize) -> BalanceGetter {
+        self.initial_address_index.replace(index);
+        self
+    }
+
+    #[wasm_bindgen(js_name = gapLimit)]
+    pub fn gap_limit(mut self, limit: usize) -> BalanceGetter {
+        self.gap_limit.replace(limit);
+        self
+    }
+
+    /// Resolves to the balance as a string, JS numbers can't hold every balance.
+    pub fn get(self) -> Promise {
+        promise(async move {
+            let seed = ParsedSeed::parse(&self.seed)?;
+            let mut getter = self.client.get_balance(seed.signer_type());
+            if let Some(account_index) = self.account_index {
+                getter = getter.with_account_index(account_index);
+            }
+            if let Some(initial_address_index) = self.initial_address_index {
+                getter = getter.with_initial_address_index(initial_address_index);
+            }
+            if let Some(gap_limit) = self.gap_limit {
+                getter = getter.with_gap_limit(gap_limit);
+            }
+            Ok(getter.finish().await?.to_string())
+        })
+    }
+}
+
+/// JS `client.send()`, an indexation message.
+#[wasm_bindgen]
+pub struct MessageSender {
+    client: Rc<iota::Client>,
+    index: Option<String>,
+    data: Option<Vec<u8>>,
+    parent: Option<String>,
+}
+
+#[wasm_bindgen]
+impl MessageSender {
+    pub fn index(mut self, index: String) -> MessageSender {
+        self.index.replace(index);
+        self
+    }
+
+    /// The data of the indexation payload, a string or a `Uint8Array`.
+    pub fn data(mut self, data: JsValue) -> MessageSender {
+        let data = match data.as_string() {
+            Some(data) => data.into_bytes(),
+            None => Uint8Array::new(&data).to_vec(),
+        };
+        self.data.replace(data);
+        self
+    }
+
+    pub fn parent(mut self, message_id: String) -> MessageSender {
+        self.parent.replace(message_id);
+        self
+    }
+
+    /// Resolves to the `{ messageId, message }` of the message accepted by the node.
+    pub fn submit(self) -> Promise {
+        promise(async move {
+            let mut sender = self.client.send();
+            if let Some(index) = &self.index {
+                sender = sender.with_index(index);
+            }
+            if let Some(data) = self.data {
+                sender = sender.with_data(data);
+            }
+            if let Some(parent) = &self.parent {
+                sender = sender.with_parent(parse_message_id(parent)?);
+            }
+            let (message_id, message) = sender.finish_with_message().await?;
+            let bech32_hrp = self.client.get_network_info().bech32_hrp;
+            Ok(to_js(&SentMessageDto {
+                message_id: message_id.to_string(),
+                message: MessageResponseDto::new(&message, &bech32_hrp)?,
+            }))
+        })
+    }
+}
+
+/// JS `client.getMessage()`, the messages of the node.
+#[wasm_bindgen]
+pub struct MessageGetter {
+    client: Rc<iota::Client>,
+}
+
+#[wasm_bindgen]
+impl MessageGetter {
+    /// Resolves to the ids of the messages with the index.
+    pub fn index(&self, index: String) -> Promise {
+        let client = self.client.clone();
+        promise(async move {
+            let message_ids = client.get_message().index(&index).await?;
+            Ok(to_js(
+                &message_ids.iter().map(ToString::to_string).collect::<Vec<String>>(),
+            ))
+        })
+    }
+
+    /// Resolves to the message, in the shape of the messages of the Node.js binding.
+    pub fn data(&self, message_id: String) -> Promise {
+        let client = self.client.clone();
+        promise(async move {
+            let message = client.get_message().data(&parse_message_id(&message_id)?).await?;
+            let bech32_hrp = client.get_network_info().bech32_hrp;
+            Ok(to_js(&MessageResponseDto::new(&message, &bech32_hrp)?))
+        })
+    }
+
+    pub fn metadata(&self, message_id: String) -> Promise {
+        let client = self.client.clone();
+        promise(async move {
+            let metadata = client.get_message().metadata(&parse_message_id(&message_id)?).await?;
+            Ok(to_js(&MessageMetadataDto::from(metadata)))
+        })
+    }
+
+    /// Resolves to the packed bytes of the message as a `Uint8Array`.
+    pub fn raw(&self, message_id: String) -> Promise {
+        let client = self.client.clone();
+        promise(async move {
+            let bytes = client.get_message().raw(&parse_message_id(&message_id)?).await?;
+            Ok(Uint8Array::from(&bytes[..]))
+        })
+    }
+
+    /// Resolves to the ids of the messages approving the message.
+    pub fn children(&self, message_id: String) -> Promise {
+        let client = self.client.clone();
+        promise(async move {
+            let message_ids = client.get_message().children(&parse_message_id(&message_id)?).await?;
+            Ok(to_js(
+                &message_ids.iter().map(ToString::to_string).collect::<Vec<String>>(),
+            ))
+        })
+    }
+}
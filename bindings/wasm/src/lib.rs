@@ -0,0 +1,95 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! WebAssembly binding to the client library, for the browsers and Node.js. The requests are sent with `fetch` and the
+//! PoW is done by the nodes. The address utilities and the DTOs are the ones of the Node.js binding, so both bindings
+//! return the same objects.
+
+#[path = "../../node/native/src/address.rs"]
+mod address;
+mod client;
+// the DTOs of the Node.js binding, only a part of them is used here
+#[allow(dead_code)]
+#[path = "../../node/native/src/classes/client/dto.rs"]
+mod dto;
+
+use address::AddressError;
+use wasm_bindgen::prelude::*;
+
+pub use client::{AddressGetter, BalanceGetter, Client, ClientBuilder, MessageGetter, MessageSender};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0}")]
+    Anyhow(#[from] anyhow::Error),
+    #[error("{0}")]
+    Client(#[from] iota::client::Error),
+    #[error("{0}")]
+    Address(#[from] AddressError),
+    #[error("invalid address `{0}`: {1}")]
+    InvalidAddress(String, AddressError),
+    #[error("{0}")]
+    Hex(#[from] hex::FromHexError),
+    #[error("{0}")]
+    Message(iota::message::Error),
+}
+
+impl From<iota::message::Error> for Error {
+    fn from(error: iota::message::Error) -> Self {
+        Self::Message(error)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The JS `Error` thrown or rejecting a promise, with the `code` of the Node.js binding for the invalid addresses and
+/// the error responses of the nodes.
+pub(crate) fn js_error(error: impl Into<Error>) -> JsValue {
+    let error = error.into();
+    let js_error = js_sys::Error::new(&error.to_string());
+    let code = match &error {
+        Error::Address(_) | Error::InvalidAddress(_, _) => Some("EINVALIDADDRESS"),
+        Error::Client(iota::client::Error::ResponseError(_, _, _)) => Some("ERESPONSE"),
+        _ => None,
+    };
+    if let Some(code) = code {
+        js_sys::Reflect::set(&js_error, &"code".into(), &code.into()).expect("failed to set the error code");
+    }
+    js_error.into()
+}
+
+/// Converts a serializable value to a plain JS object.
+pub(crate) fn to_js<T: serde::Serialize>(value: &T) -> JsValue {
+    JsValue::from_serde(value).expect("failed to convert the value to JS")
+}
+
+/// JS `isAddressValid(address, expectedHrp?)`.
+#[wasm_bindgen(js_name = isAddressValid)]
+pub fn is_address_valid(address: &str, expected_hrp: Option<String>) -> bool {
+    address::parse_address(address, expected_hrp.as_deref()).is_ok()
+}
+
+/// JS `parseAddress(address, expectedHrp?)`, returning the `{ encoding, hrp, bytes }` of the address.
+#[wasm_bindgen(js_name = parseAddress)]
+pub fn parse_address(address: &str, expected_hrp: Option<String>) -> std::result::Result<JsValue, JsValue> {
+    address::parse_address(address, expected_hrp.as_deref())
+        .map(|parsed| to_js(&parsed))
+        .map_err(|e| js_error(Error::InvalidAddress(address.to_string(), e)))
+}
+
+/// JS `hexToBech32(address, bech32Hrp)`.
+#[wasm_bindgen(js_name = hexToBech32)]
+pub fn hex_to_bech32(address: &str, bech32_hrp: &str) -> std::result::Result<String, JsValue> {
+    address::hex_to_bech32(address, bech32_hrp).map_err(|e| match e {
+        Error::Address(e) => js_error(Error::InvalidAddress(address.to_string(), e)),
+        e => js_error(e),
+    })
+}
+
+/// JS `bech32ToHex(address)`, returning the `{ hex, hrp }` of the address.
+#[wasm_bindgen(js_name = bech32ToHex)]
+pub fn bech32_to_hex(address: &str) -> std::result::Result<JsValue, JsValue> {
+    address::bech32_to_hex(address)
+        .map(|address| to_js(&address))
+        .map_err(|e| js_error(Error::InvalidAddress(address.to_string(), e)))
+}
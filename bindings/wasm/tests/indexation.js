@@ -0,0 +1,98 @@
+const { ClientBuilder, bech32ToHex, isAddressValid } = require('../pkg')
+const assert = require('assert')
+const http = require('http')
+
+const seed = '256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2'
+const tipId = '52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649'
+const index = 'IOTA.RS WASM'
+
+// a node doing the PoW of the posted messages, serving them back under the tip id
+function startNode () {
+  const node = { messages: [] }
+  node.server = http.createServer((req, res) => {
+    let body = ''
+    req.on('data', chunk => { body += chunk })
+    req.on('end', () => {
+      res.setHeader('content-type', 'application/json')
+      const url = new URL(req.url, 'http://localhost')
+      if (url.pathname === '/api/v1/info') {
+        res.end(JSON.stringify({
+          data: {
+            name: 'HORNET',
+            version: '0.6.0',
+            isHealthy: true,
+            networkId: 'testnet',
+            bech32HRP: 'atoi',
+            minPowScore: 4000,
+            latestMilestoneIndex: 1,
+            solidMilestoneIndex: 1,
+            pruningIndex: 0,
+            features: ['PoW']
+          }
+        }))
+      } else if (url.pathname === '/api/v1/tips') {
+        res.end(JSON.stringify({ data: { tip1MessageId: tipId, tip2MessageId: tipId } }))
+      } else if (req.method === 'POST' && url.pathname === '/api/v1/messages') {
+        node.messages.push({ nonce: '0', ...JSON.parse(body) })
+        res.statusCode = 201
+        res.end(JSON.stringify({ data: { messageId: tipId } }))
+      } else if (url.pathname === '/api/v1/messages' && url.searchParams.has('index')) {
+        const messageIds = url.searchParams.get('index') === index && node.messages.length ? [tipId] : []
+        res.end(JSON.stringify({
+          data: { index: url.searchParams.get('index'), maxResults: 1000, count: messageIds.length, messageIds }
+        }))
+      } else if (url.pathname === `/api/v1/messages/${tipId}` && node.messages.length) {
+        res.end(JSON.stringify({ data: node.messages[node.messages.length - 1] }))
+      } else {
+        res.statusCode = 404
+        res.end()
+      }
+    })
+  })
+  return new Promise(resolve => node.server.listen(0, '127.0.0.1', () => {
+    node.url = `http://127.0.0.1:${node.server.address().port}`
+    resolve(node)
+  }))
+}
+
+describe('WebAssembly client', () => {
+  let node
+  let client
+
+  before(async () => {
+    node = await startNode()
+    client = new ClientBuilder().node(node.url).build()
+  })
+
+  after(() => node.server.close())
+
+  it('submits and fetches an indexation message', async () => {
+    const sent = await client.send().index(index).data('wasm round trip').submit()
+    assert.strictEqual(sent.messageId, tipId)
+    assert.strictEqual(node.messages.length, 1)
+    // the nodes do the PoW of the browser clients
+    assert.strictEqual(client.networkInfo().localPow, false)
+
+    assert.deepStrictEqual(await client.getMessage().index(index), [tipId])
+    const message = await client.getMessage().data(tipId)
+    assert.strictEqual(message.networkId, sent.message.networkId)
+    assert.strictEqual(message.parent1, tipId)
+    assert.deepStrictEqual(message.payload, sent.message.payload)
+    assert.strictEqual(message.payload.index, index)
+    assert.strictEqual(Buffer.from(message.payload.data).toString(), 'wasm round trip')
+  })
+
+  it('fails on the error responses', async () => {
+    await assert.rejects(client.getMessage().metadata(tipId), { code: 'ERESPONSE' })
+  })
+
+  it('derives and converts the addresses', async () => {
+    await client.refreshNetworkInfo()
+    const [address] = client.getAddresses(seed).accountIndex(0).range(0, 1).get()
+    assert(address.startsWith('atoi1'))
+    assert.strictEqual(isAddressValid(address, 'atoi'), true)
+    assert.strictEqual(isAddressValid(address, 'iota'), false)
+    assert.strictEqual(bech32ToHex(address).hrp, 'atoi')
+    assert.throws(() => bech32ToHex('atoi1invalid'), { code: 'EINVALIDADDRESS' })
+  })
+})
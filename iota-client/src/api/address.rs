@@ -1,36 +1,37 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{types::Bech32Address, Client, Error, Result};
+#[cfg(feature = "client")]
+use crate::{signer::slip10::HARDENED, types::Bech32Address, Client, Error, Result, SignerType};
 
 use bee_message::prelude::{Address, Ed25519Address};
-use bee_signing_ext::{
-    binary::{BIP32Path, Ed25519PrivateKey, Ed25519Seed},
-    Seed,
-};
 use blake2::{
     digest::{Update, VariableOutput},
     VarBlake2b,
 };
 use core::convert::TryInto;
+#[cfg(feature = "client")]
 use std::ops::Range;
 
-const HARDEND: u32 = 1 << 31;
+/// The maximum number of address indexes derived at once.
+pub const ADDRESS_RANGE_MAX: usize = 1000;
 
 /// Builder of find_addresses API
+#[cfg(feature = "client")]
 pub struct GetAddressesBuilder<'a> {
-    _client: &'a Client,
-    seed: &'a Seed,
+    client: &'a Client,
+    signer: SignerType<'a>,
     account_index: Option<usize>,
     range: Option<Range<usize>>,
 }
 
+#[cfg(feature = "client")]
 impl<'a> GetAddressesBuilder<'a> {
-    /// Create find_addresses builder
-    pub fn new(_client: &'a Client, seed: &'a Seed) -> Self {
+    /// Create find_addresses builder, the signer is a [`Seed`](crate::Seed) or a [`SignerType`]
+    pub fn new<S: Into<SignerType<'a>>>(client: &'a Client, signer: S) -> Self {
         Self {
-            _client,
-            seed,
+            client,
+            signer: signer.into(),
             account_index: None,
             range: None,
         }
@@ -58,44 +59,38 @@ impl<'a> GetAddressesBuilder<'a> {
             .collect::<Vec<Bech32Address>>())
     }
 
-    /// Consume the builder and get the vector of Bech32Address
+    /// Consume the builder and get the vector of Bech32Address, with the HRP of the client network
     pub fn get_all(self) -> Result<Vec<(Bech32Address, bool)>> {
-        let mut path = self
+        let account_index = self
             .account_index
-            .map(|i| BIP32Path::from_str(&crate::account_path!(i)).expect("invalid account index"))
             .ok_or_else(|| Error::MissingParameter(String::from("account index")))?;
 
         let range = match self.range {
             Some(r) => r,
             None => 0..20,
         };
-
-        let seed = match self.seed {
-            Seed::Ed25519(s) => s,
-            _ => panic!("Other seed scheme isn't supported yet."),
-        };
+        // the indexes are hardened, so they must be below 2^31
+        if range.start > range.end || range.end - range.start > ADDRESS_RANGE_MAX || range.end > HARDENED as usize {
+            return Err(Error::InvalidParameter(format!(
+                "address range {}..{}, expected at most {} increasing indexes below 2^31",
+                range.start, range.end, ADDRESS_RANGE_MAX
+            )));
+        }
 
         let mut addresses = Vec::new();
         for i in range {
-            let address = generate_address(&seed, &mut path, i, false);
-            let internal_address = generate_address(&seed, &mut path, i, true);
-            addresses.push((Bech32Address(address.to_bech32()), false));
-            addresses.push((Bech32Address(internal_address.to_bech32()), true));
+            let address = self.signer.generate_address(account_index, i, false)?;
+            let internal_address = self.signer.generate_address(account_index, i, true)?;
+            addresses.push((self.client.address_to_bech32(&address)?, false));
+            addresses.push((self.client.address_to_bech32(&internal_address)?, true));
         }
 
         Ok(addresses)
     }
 }
 
-fn generate_address(seed: &Ed25519Seed, path: &mut BIP32Path, index: usize, internal: bool) -> Address {
-    path.push(internal as u32 + HARDEND);
-    path.push(index as u32 + HARDEND);
-
-    let public_key = Ed25519PrivateKey::generate_from_seed(seed, &path)
-        .expect("Invalid Seed & BIP32Path. Probably because the index of path is not hardened.")
-        .generate_public_key()
-        .to_bytes();
-    // Hash the public key to get the address
+/// Gets the Ed25519 address of a public key, the BLAKE2b-256 hash of the key.
+pub(crate) fn public_key_address(public_key: &[u8]) -> Address {
     let mut hasher = VarBlake2b::new(32).unwrap();
     hasher.update(public_key);
     let mut result: [u8; 32] = [0; 32];
@@ -103,28 +98,28 @@ fn generate_address(seed: &Ed25519Seed, path: &mut BIP32Path, index: usize, inte
         result = res.try_into().expect("Invalid Length of Public Key");
     });
 
-    path.pop();
-    path.pop();
-
     Address::Ed25519(Ed25519Address::new(result))
 }
 
-/// Function to find the index and public or internal type of an Bech32 encoded address
-pub fn search_address(
-    seed: &Seed,
+/// Function to find the index and public or internal type of an Bech32 encoded address, whatever its HRP
+#[cfg(feature = "client")]
+pub fn search_address<'a, S: Into<SignerType<'a>>>(
+    signer: S,
     account_index: usize,
     range: Range<usize>,
     address: &Bech32Address,
 ) -> Result<(usize, bool)> {
+    let signer = signer.into();
     let iota = Client::build().with_node("http://0.0.0.0:14265")?.finish()?;
     let addresses = iota
-        .find_addresses(&seed)
+        .find_addresses(signer)
         .with_account_index(account_index)
         .with_range(range)
         .get_all()?;
+    let address = Address::try_from_bech32(address)?;
     let mut index_counter = 0;
     for address_internal in addresses {
-        if address_internal.0 == *address {
+        if Address::try_from_bech32(&address_internal.0)? == address {
             return Ok((index_counter, address_internal.1));
         }
         if !address_internal.1 {
@@ -0,0 +1,232 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{types::Bech32Address, Client, OutputMetadata, OutputType, OutputsOptions, Result};
+#[cfg(feature = "mqtt")]
+use crate::{Topic, TopicEvent};
+
+use bee_message::prelude::{Address, TransactionId, UTXOInput};
+use futures::{StreamExt, TryStreamExt};
+use serde::Serialize;
+
+use std::{
+    collections::{HashSet, VecDeque},
+    convert::TryInto,
+    str::FromStr,
+    sync::Mutex,
+};
+
+/// The number of activities an [`AddressMonitor`] remembers to skip the ones delivered again.
+pub const ADDRESS_MONITOR_DELIVERED_CAPACITY: usize = 10_000;
+
+/// An output of a monitored address created or consumed, see [`AddressMonitor`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AddressActivity {
+    /// The output id, the hex-encoded transaction id followed by the little endian output index.
+    #[serde(rename = "outputId")]
+    pub output_id: String,
+    /// The output.
+    pub output: OutputMetadata,
+    /// Whether the output was consumed, or created.
+    pub consumed: bool,
+    /// The milestone of the activity found by a catch-up, `None` for a live event.
+    #[serde(rename = "milestoneIndex")]
+    pub milestone_index: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct MonitorState {
+    last_milestone_index: Option<u64>,
+    // the (output id, consumed) activities delivered last
+    delivered: HashSet<(String, bool)>,
+    order: VecDeque<(String, bool)>,
+}
+
+impl MonitorState {
+    // `false` if the activity was already delivered
+    fn deliver(&mut self, key: (String, bool)) -> bool {
+        if !self.delivered.insert(key.clone()) {
+            return false;
+        }
+        self.order.push_back(key);
+        if self.order.len() > ADDRESS_MONITOR_DELIVERED_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.delivered.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    fn processed(&mut self, milestone_index: u64) {
+        if self.last_milestone_index.map_or(true, |last| last < milestone_index) {
+            self.last_milestone_index.replace(milestone_index);
+        }
+    }
+}
+
+/// Monitor of the outputs created and consumed on an address, across the gaps of the live MQTT events.
+/// A catch-up goes through the UTXO changes of the milestones solidified since the last processed one, so the
+/// activity of a disconnection isn't lost; an activity seen by both the catch-up and the live events is only delivered
+/// once. The monitor doesn't depend on a node connection, so it survives reconnects, see
+/// [`Client::monitor_address`].
+#[derive(Debug)]
+pub struct AddressMonitor {
+    address: Bech32Address,
+    state: Mutex<MonitorState>,
+}
+
+impl AddressMonitor {
+    /// Create the monitor of an address, catching up from the milestone after `since_milestone_index`, or from the
+    /// current solid milestone without it.
+    pub fn new(address: Bech32Address, since_milestone_index: Option<u64>) -> Self {
+        Self {
+            address,
+            state: Mutex::new(MonitorState {
+                last_milestone_index: since_milestone_index,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Gets the monitored address.
+    pub fn address(&self) -> &Bech32Address {
+        &self.address
+    }
+
+    /// Gets the index of the last milestone whose activity was delivered, `None` before the first catch-up without
+    /// a starting milestone.
+    pub fn last_milestone_index(&self) -> Option<u64> {
+        self.state.lock().unwrap().last_milestone_index
+    }
+
+    /// Delivers the activities of the milestones solidified since the last processed one to the callback, in the
+    /// order of the milestones. Stops with `false` once the callback returns `false`, the milestone being processed
+    /// is then caught up again by the next catch-up. A node request failing fails the catch-up, e.g. with
+    /// [`Error::PrunedData`](crate::Error::PrunedData) for the milestones neither the node nor the permanode keeps.
+    pub async fn catch_up<C: FnMut(AddressActivity) -> bool>(&self, client: &Client, mut callback: C) -> Result<bool> {
+        let solid_index = client.get_info().await?.solid_milestone_index as u64;
+        let last_index = match self.last_milestone_index() {
+            Some(last_index) => last_index,
+            None => {
+                self.state.lock().unwrap().processed(solid_index);
+                return Ok(true);
+            }
+        };
+        if last_index >= solid_index {
+            return Ok(true);
+        }
+        let address = Address::try_from_bech32(&self.address)?;
+        let options = OutputsOptions {
+            include_spent: true,
+            ..Default::default()
+        };
+        let outputs = client.get_address().outputs_response(&self.address, options).await?;
+        // without the full list of the address, every changed output is fetched to find its own
+        let truncated = outputs.is_truncated();
+        let address_outputs: HashSet<String> = outputs
+            .utxo_inputs()?
+            .iter()
+            .map(|id| id.output_id().to_string())
+            .collect();
+
+        for milestone_index in last_index + 1..=solid_index {
+            let changes = client.get_milestone_utxo_changes(milestone_index).await?;
+            let mut changed = Vec::new();
+            for (ids, consumed) in &[(&changes.created_outputs, false), (&changes.consumed_outputs, true)] {
+                for id in ids.iter() {
+                    let input = UTXOInput::from_str(id)
+                        .map_err(|_| crate::Error::InvalidParameter(format!("output id {}", id)))?;
+                    if truncated || address_outputs.contains(&input.output_id().to_string()) {
+                        changed.push((input, *consumed));
+                    }
+                }
+            }
+            let activities: Vec<AddressActivity> = futures::stream::iter(changed)
+                .map(|(input, consumed)| async move {
+                    let output = client
+                        .get_output_of_types(
+                            &input,
+                            &[
+                                OutputType::SignatureLockedSingle,
+                                OutputType::SignatureLockedDustAllowance,
+                            ],
+                        )
+                        .await?;
+                    Ok::<_, crate::Error>(AddressActivity {
+                        output_id: input.output_id().to_string(),
+                        output,
+                        consumed,
+                        milestone_index: Some(milestone_index),
+                    })
+                })
+                .buffered(client.request_parallelism)
+                .try_collect()
+                .await?;
+            for activity in activities {
+                if activity.output.address != address {
+                    continue;
+                }
+                let new = self
+                    .state
+                    .lock()
+                    .unwrap()
+                    .deliver((activity.output_id.clone(), activity.consumed));
+                if new && !callback(activity) {
+                    return Ok(false);
+                }
+            }
+            self.state.lock().unwrap().processed(milestone_index);
+        }
+        Ok(true)
+    }
+
+    /// The MQTT topics of the live activity: the outputs of the address and the solid milestones.
+    #[cfg(feature = "mqtt")]
+    pub fn topics(&self) -> Result<Vec<Topic>> {
+        Ok(vec![
+            Topic::new(format!("addresses/{}/outputs", self.address))?,
+            Topic::new("milestones/solid")?,
+        ])
+    }
+
+    /// Gets the activity of an event of the [`topics`](Self::topics), `None` for a milestone or an activity already
+    /// delivered. A solid milestone marks the one before it as processed once the milestones before are, so a gap is
+    /// still caught up after a reconnection.
+    #[cfg(feature = "mqtt")]
+    pub fn live_activity(&self, event: &TopicEvent) -> Result<Option<AddressActivity>> {
+        if event.topic == "milestones/solid" {
+            #[derive(serde::Deserialize)]
+            struct Milestone {
+                index: u64,
+            }
+            let milestone: Milestone = serde_json::from_str(&event.payload)?;
+            let mut state = self.state.lock().unwrap();
+            // the outputs of the milestone might still be on their way
+            if let Some(last_index) = state.last_milestone_index {
+                if milestone.index >= 2 && milestone.index - 2 == last_index {
+                    state.processed(milestone.index - 1);
+                }
+            }
+            return Ok(None);
+        }
+        let output = event.output()?;
+        let transaction_id: [u8; 32] = output
+            .transaction_id
+            .as_slice()
+            .try_into()
+            .map_err(|_| crate::Error::InvalidParameter("transaction id".to_string()))?;
+        let output_id = UTXOInput::new(TransactionId::new(transaction_id), output.output_index)?
+            .output_id()
+            .to_string();
+        let consumed = output.is_spent;
+        if !self.state.lock().unwrap().deliver((output_id.clone(), consumed)) {
+            return Ok(None);
+        }
+        Ok(Some(AddressActivity {
+            output_id,
+            output,
+            consumed,
+            milestone_index: None,
+        }))
+    }
+}
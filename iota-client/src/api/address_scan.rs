@@ -0,0 +1,107 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{types::Bech32Address, Client, Result, SignerType};
+
+use futures::{stream, Future, StreamExt, TryStreamExt};
+
+/// The addresses of each index queried by an address scan.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ScanAddresses {
+    Public,
+    Internal,
+    All,
+}
+
+/// An address of a scan window with the result of its query.
+pub(crate) struct ScannedAddress<T> {
+    pub(crate) address: Bech32Address,
+    pub(crate) address_index: usize,
+    pub(crate) internal: bool,
+    pub(crate) result: T,
+}
+
+/// A windowed scan of the addresses of an account. Each window derives the addresses of the next `window_size`
+/// indexes and queries them concurrently, at most `address_scan_parallelism` of the client at a time. The results
+/// are in the order of the addresses, the public address of an index first, so the callers see the results of a
+/// serial scan and stop at the same address.
+pub(crate) struct AddressScan<'a> {
+    client: &'a Client,
+    signer: SignerType<'a>,
+    account_index: usize,
+    addresses: ScanAddresses,
+    next_index: usize,
+    window_size: usize,
+}
+
+impl<'a> AddressScan<'a> {
+    /// Starts a scan at the initial address index, with the address scan window of the client.
+    pub(crate) fn new(
+        client: &'a Client,
+        signer: SignerType<'a>,
+        account_index: usize,
+        initial_address_index: usize,
+        addresses: ScanAddresses,
+    ) -> Self {
+        Self {
+            client,
+            signer,
+            account_index,
+            addresses,
+            next_index: initial_address_index,
+            window_size: client.address_scan_window,
+        }
+    }
+
+    /// Sets the number of address indexes of each window.
+    pub(crate) fn with_window_size(mut self, window_size: usize) -> Self {
+        self.window_size = window_size;
+        self
+    }
+
+    /// Derives the addresses of the next window and queries them with their address index and internal flag, the
+    /// results are in the order of the addresses. Fails with the first failed query, the remaining queries of the
+    /// window are dropped.
+    pub(crate) async fn next_window<T, Q, F>(&mut self, query: Q) -> Result<Vec<ScannedAddress<T>>>
+    where
+        Q: Fn(Bech32Address, usize, bool) -> F,
+        F: Future<Output = Result<T>>,
+    {
+        let start = self.next_index;
+        let addresses = self
+            .client
+            .find_addresses(self.signer)
+            .with_account_index(self.account_index)
+            .with_range(start..start + self.window_size)
+            .get_all()?;
+        self.next_index += self.window_size;
+
+        let kind = self.addresses;
+        let addresses: Vec<(Bech32Address, usize, bool)> = addresses
+            .into_iter()
+            .enumerate()
+            // the addresses are (public, internal) pairs for each index
+            .map(|(position, (address, internal))| (address, start + position / 2, internal))
+            .filter(|(_, _, internal)| match kind {
+                ScanAddresses::Public => !internal,
+                ScanAddresses::Internal => *internal,
+                ScanAddresses::All => true,
+            })
+            .collect();
+        stream::iter(addresses)
+            .map(|(address, address_index, internal)| {
+                let result = query(address.clone(), address_index, internal);
+                async move {
+                    Ok(ScannedAddress {
+                        address,
+                        address_index,
+                        internal,
+                        result: result.await?,
+                    })
+                }
+            })
+            .buffered(self.client.address_scan_parallelism)
+            .try_collect()
+            .await
+    }
+}
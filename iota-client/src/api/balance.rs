@@ -1,26 +1,40 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{Client, Error, Result};
+use crate::{
+    api::{checked_amount_sum, AddressScan, ScanAddresses, ScannedAddress},
+    builder::DEFAULT_ADDRESS_SCAN_WINDOW,
+    types::AddressBalance,
+    AddressBalancePair, BalanceSplit, Bech32Address, Client, Error, OutputsOptions, Result, SignerType,
+};
 
-use bee_signing_ext::Seed;
+use bee_message::prelude::UTXOInput;
+use futures::{StreamExt, TryStreamExt};
+
+/// The default number of consecutive unused address indexes after which the balance search stops, with the default
+/// address scan window of the client.
+pub const DEFAULT_GAP_LIMIT: usize = DEFAULT_ADDRESS_SCAN_WINDOW;
 
 /// Builder of get_balance API
 pub struct GetBalanceBuilder<'a> {
     client: &'a Client,
-    seed: &'a Seed,
+    signer: SignerType<'a>,
     account_index: Option<usize>,
     initial_address_index: Option<usize>,
+    gap_limit: Option<usize>,
+    confirmed_only: bool,
 }
 
 impl<'a> GetBalanceBuilder<'a> {
     /// Create get_balance builder
-    pub fn new(client: &'a Client, seed: &'a Seed) -> Self {
+    pub fn new<S: Into<SignerType<'a>>>(client: &'a Client, signer: S) -> Self {
         Self {
             client,
-            seed,
+            signer: signer.into(),
             account_index: None,
             initial_address_index: None,
+            gap_limit: None,
+            confirmed_only: true,
         }
     }
 
@@ -36,43 +50,266 @@ impl<'a> GetBalanceBuilder<'a> {
         self
     }
 
+    /// Sets the number of consecutive unused address indexes after which the search stops, the address scan window
+    /// of the client by default. The addresses are queried in windows of `gap_limit` indexes.
+    pub fn with_gap_limit(mut self, gap_limit: usize) -> Self {
+        self.gap_limit = Some(gap_limit);
+        self
+    }
+
+    /// Sets whether only the outputs whose message is included in the ledger are counted, true by default. The used
+    /// addresses then need the metadata of the messages of their outputs, see
+    /// [`Client::get_address_balance_split`]. Without it, the balances answered by the node are summed.
+    pub fn with_confirmed_only(mut self, confirmed_only: bool) -> Self {
+        self.confirmed_only = confirmed_only;
+        self
+    }
+
     /// Consume the builder and get the API result
     pub async fn finish(self) -> Result<u64> {
+        Ok(self.finish_with_addresses().await?.0)
+    }
+
+    /// Consume the builder and get the balance along with the balance of every used address.
+    /// An address is used if it holds a balance or outputs, the spent ones included, and the search stops after
+    /// `gap_limit` consecutive address indexes whose public and internal addresses are both unused.
+    pub async fn finish_with_addresses(self) -> Result<(u64, Vec<AddressBalancePair>)> {
+        let confirmed_only = self.confirmed_only;
+        let mut balance = 0;
+        let mut address_balances = Vec::new();
+        for (mut address_balance, split) in self.used_addresses(confirmed_only).await? {
+            if let Some(split) = split {
+                address_balance.balance = split.confirmed;
+            }
+            balance = checked_amount_sum(vec![balance, address_balance.balance])?;
+            address_balances.push(address_balance);
+        }
+        Ok((balance, address_balances))
+    }
+
+    /// Consume the builder and get the balance split into the confirmed amount, of the outputs whose message is
+    /// included in the ledger, and the pending amount of the other outputs, whatever
+    /// [`with_confirmed_only`](Self::with_confirmed_only).
+    pub async fn finish_split(self) -> Result<BalanceSplit> {
+        let mut balance = BalanceSplit::default();
+        for (_, split) in self.used_addresses(true).await? {
+            if let Some(split) = split {
+                balance.confirmed = checked_amount_sum(vec![balance.confirmed, split.confirmed])?;
+                balance.pending = checked_amount_sum(vec![balance.pending, split.pending])?;
+            }
+        }
+        Ok(balance)
+    }
+
+    // Searches the used addresses with their balance, split by the inclusion state of their outputs if `split` is set
+    async fn used_addresses(self, split: bool) -> Result<Vec<(AddressBalancePair, Option<BalanceSplit>)>> {
+        let client = self.client;
+        let mut pages = self.finish_pages()?;
+        let mut used_addresses = Vec::new();
+        while let Some(window) = pages.next_balances().await? {
+            let splits: Vec<Option<BalanceSplit>> = futures::stream::iter(&window)
+                .map(|scanned| async move {
+                    if split {
+                        client.get_address_balance_split(&scanned.address).await.map(Some)
+                    } else {
+                        Ok(None)
+                    }
+                })
+                .buffered(client.request_parallelism)
+                .try_collect()
+                .await?;
+            for (scanned, split) in window.into_iter().zip(splits) {
+                let address_balance = AddressBalancePair {
+                    address: scanned.address,
+                    balance: scanned.result.balance,
+                    dust_allowed: scanned.result.dust_allowed,
+                };
+                used_addresses.push((address_balance, split));
+            }
+        }
+        Ok(used_addresses)
+    }
+
+    /// Consume the builder and search the used addresses one window of `gap_limit` address indexes at a time, see
+    /// [`BalancePages::next_page`]: only the current page is held in memory and the search stops with the pages.
+    pub fn finish_pages(self) -> Result<BalancePages<'a>> {
         let account_index = self
             .account_index
             .ok_or_else(|| Error::MissingParameter(String::from("account index")))?;
+        let gap_limit = self.gap_limit.unwrap_or(self.client.address_scan_window);
+        if gap_limit == 0 {
+            return Err(Error::InvalidParameter(String::from("gap limit")));
+        }
+        let next_address_index = self.initial_address_index.unwrap_or(0);
+        Ok(BalancePages {
+            client: self.client,
+            scan: AddressScan::new(
+                self.client,
+                self.signer,
+                account_index,
+                next_address_index,
+                ScanAddresses::All,
+            )
+            .with_window_size(gap_limit),
+            gap_limit,
+            unused_count: 0,
+            next_address_index,
+            done: false,
+        })
+    }
+}
 
-        let mut index = self.initial_address_index.unwrap_or(0);
+/// A used address of a balance page, with its balance and its outputs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UsedAddress {
+    /// The bech32 address.
+    pub address: Bech32Address,
+    /// The address index of the address.
+    pub address_index: usize,
+    /// Whether the address is the internal address of its index.
+    pub internal: bool,
+    /// The balance of the address.
+    pub balance: u64,
+    /// Whether the address allows dust outputs.
+    pub dust_allowed: bool,
+    /// The unspent outputs of the address.
+    pub outputs: Vec<UTXOInput>,
+    /// The spent outputs of the address known to the node.
+    pub spent_outputs: Vec<UTXOInput>,
+}
 
-        // get account balance and check with value
-        let mut balance = 0;
-        loop {
-            let addresses = self
-                .client
-                .find_addresses(self.seed)
-                .with_account_index(account_index)
-                .with_range(index..index + 20)
-                .get_all()?;
-
-            // TODO we assume all addresses are unspent and valid if balance > 0
-            let mut found_zero_balance = false;
-            for (address, _) in addresses {
-                let address_balance = self.client.get_address().balance(&address).await?;
-                match address_balance {
-                    0 => {
-                        found_zero_balance = true;
-                        break;
-                    }
-                    _ => balance += address_balance,
+/// The used addresses of a window of address indexes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BalancePage {
+    /// The used addresses of the window, in the order of the address indexes with the public address first.
+    pub addresses: Vec<UsedAddress>,
+    /// The address index after the window, the cursor to resume the search with
+    /// [`GetBalanceBuilder::with_initial_address_index`] once the page is processed.
+    pub next_address_index: usize,
+}
+
+/// A balance search yielding the used addresses page by page, see [`GetBalanceBuilder::finish_pages`]. No request is
+/// sent before a page is asked for, so dropping the pages stops the search.
+pub struct BalancePages<'a> {
+    client: &'a Client,
+    scan: AddressScan<'a>,
+    gap_limit: usize,
+    unused_count: usize,
+    next_address_index: usize,
+    done: bool,
+}
+
+impl<'a> BalancePages<'a> {
+    /// The page of the next window of address indexes, `None` once the search stopped after `gap_limit` consecutive
+    /// unused address indexes. The outputs of the used addresses are queried concurrently, see
+    /// [`ClientBuilder::with_request_parallelism`](crate::ClientBuilder::with_request_parallelism).
+    /// A search resumed from a cursor counts the unused address indexes from the cursor again, so it stops at the
+    /// same used addresses or scans a few more unused ones.
+    pub async fn next_page(&mut self) -> Result<Option<BalancePage>> {
+        let window = match self.next_balances().await? {
+            Some(window) => window,
+            None => return Ok(None),
+        };
+        let client = self.client;
+        let addresses = futures::stream::iter(window)
+            .map(|scanned| async move {
+                let outputs = client.get_address().outputs(&scanned.address).await?.to_vec();
+                let spent_outputs = client
+                    .get_address()
+                    .outputs_response(
+                        &scanned.address,
+                        OutputsOptions {
+                            include_spent: true,
+                            ..Default::default()
+                        },
+                    )
+                    .await?
+                    .utxo_inputs()?
+                    .iter()
+                    .filter(|output| !outputs.contains(output))
+                    .cloned()
+                    .collect();
+                Ok::<_, Error>(UsedAddress {
+                    address: scanned.address,
+                    address_index: scanned.address_index,
+                    internal: scanned.internal,
+                    balance: scanned.result.balance,
+                    dust_allowed: scanned.result.dust_allowed,
+                    outputs,
+                    spent_outputs,
+                })
+            })
+            .buffered(client.request_parallelism)
+            .try_collect()
+            .await?;
+        Ok(Some(BalancePage {
+            addresses,
+            next_address_index: self.next_address_index,
+        }))
+    }
+
+    /// The address index the next page starts at.
+    pub fn next_address_index(&self) -> usize {
+        self.next_address_index
+    }
+
+    // The used addresses of the next window with their balance, `None` once the search stopped.
+    pub(crate) async fn next_balances(&mut self) -> Result<Option<Vec<ScannedAddress<AddressBalance>>>> {
+        if self.done {
+            return Ok(None);
+        }
+        let client = self.client;
+        let window = self
+            .scan
+            .next_window(|address, _, _| async move {
+                let balance = client.get_address().balance_response(&address).await?;
+                // the count is the one of the unspent outputs, an address without a balance may have spent outputs
+                let used = balance.balance > 0
+                    || balance.count > 0
+                    || !client
+                        .get_address()
+                        .outputs_response(
+                            &address,
+                            OutputsOptions {
+                                include_spent: true,
+                                ..Default::default()
+                            },
+                        )
+                        .await?
+                        .output_ids
+                        .is_empty();
+                Ok((balance, used))
+            })
+            .await?;
+
+        let mut used_addresses = Vec::new();
+        // the addresses are (public, internal) pairs for each index
+        let mut window = window.into_iter();
+        while let (Some(public), Some(internal)) = (window.next(), window.next()) {
+            self.next_address_index = public.address_index + 1;
+            let mut used = false;
+            for scanned in std::iter::once(public).chain(std::iter::once(internal)) {
+                let (balance, address_used) = scanned.result;
+                if address_used {
+                    used = true;
+                    used_addresses.push(ScannedAddress {
+                        address: scanned.address,
+                        address_index: scanned.address_index,
+                        internal: scanned.internal,
+                        result: balance,
+                    });
                 }
             }
-
-            match found_zero_balance {
-                true => break,
-                false => index += 20,
+            if used {
+                self.unused_count = 0;
+            } else {
+                self.unused_count += 1;
+                if self.unused_count == self.gap_limit {
+                    self.done = true;
+                    break;
+                }
             }
         }
-
-        Ok(balance)
+        Ok(Some(used_addresses))
     }
 }
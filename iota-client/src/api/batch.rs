@@ -0,0 +1,209 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    api::{InputCandidate, PreparedTransactionData, SendBuilder, OUTPUT_COUNT_MAX},
+    types::Bech32Address,
+    Client, Error, Result, SignerType,
+};
+
+use bee_message::prelude::MessageId;
+use futures::StreamExt;
+
+/// Builder of the send_batch API
+pub struct BatchSendBuilder<'a> {
+    client: &'a Client,
+    signer: SignerType<'a>,
+    account_index: Option<usize>,
+    initial_address_index: Option<usize>,
+    transfers: Vec<Vec<(Bech32Address, u64)>>,
+    parallelism: Option<usize>,
+    packing: bool,
+}
+
+impl<'a> BatchSendBuilder<'a> {
+    /// Create send_batch builder
+    pub fn new<S: Into<SignerType<'a>>>(client: &'a Client, signer: S) -> Self {
+        Self {
+            client,
+            signer: signer.into(),
+            account_index: None,
+            initial_address_index: None,
+            transfers: Vec::new(),
+            parallelism: None,
+            packing: false,
+        }
+    }
+
+    /// Sets the account index.
+    pub fn with_account_index(mut self, account_index: usize) -> Self {
+        self.account_index = Some(account_index);
+        self
+    }
+
+    /// Sets the index of the address to start looking for balance.
+    pub fn with_initial_address_index(mut self, initial_address_index: usize) -> Self {
+        self.initial_address_index = Some(initial_address_index);
+        self
+    }
+
+    /// Adds a transfer to the batch, its outputs to bech32 addresses are sent in the same transaction.
+    pub fn with_transfer(mut self, outputs: Vec<(Bech32Address, u64)>) -> Self {
+        self.transfers.push(outputs);
+        self
+    }
+
+    /// Sets the number of transactions signed, with their PoW, and submitted at once, the request parallelism of the
+    /// client by default, see
+    /// [`ClientBuilder::with_request_parallelism`](crate::ClientBuilder::with_request_parallelism).
+    pub fn with_parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = Some(parallelism);
+        self
+    }
+
+    /// Packs the consecutive transfers into a single transaction while their outputs fit under [`OUTPUT_COUNT_MAX`],
+    /// keeping an output for the remainder. The transfers of a packed message get its id, and if the message fails
+    /// the first of them gets the error and the others [`Error::BatchMessageFailed`].
+    pub fn with_packing(mut self, packing: bool) -> Self {
+        self.packing = packing;
+        self
+    }
+
+    /// Consume the builder and get the message id or the error of each transfer, in the order of the transfers.
+    /// The inputs of every transfer are selected in turn from a single snapshot of the unspent outputs of the account,
+    /// each transfer reserving its inputs, then the transactions are signed and submitted concurrently. A transfer
+    /// failing doesn't fail the others: a packed transaction the inputs can't fund is split into its transfers. The
+    /// batch only fails if the unspent outputs can't be fetched.
+    pub async fn finish(self) -> Result<Vec<Result<MessageId>>> {
+        let parallelism = self.parallelism.unwrap_or(self.client.request_parallelism);
+        if parallelism == 0 {
+            return Err(Error::InvalidParameter(String::from(
+                "parallelism: at least one transaction is submitted at once",
+            )));
+        }
+        if self.transfers.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut available = self.sender().get_input_candidates().await?;
+
+        // The selection is sequential so no two transfers spend the same output
+        let mut prepared = Vec::new();
+        for pack in self.packs() {
+            match self.prepare(&pack, &available).await {
+                Ok(transaction) => {
+                    reserve_inputs(&mut available, &transaction);
+                    prepared.push((pack, Ok(transaction)));
+                }
+                Err(_) if pack.len() > 1 => {
+                    for transfer in pack {
+                        let result = self.prepare(&[transfer], &available).await;
+                        if let Ok(transaction) = &result {
+                            reserve_inputs(&mut available, transaction);
+                        }
+                        prepared.push((vec![transfer], result));
+                    }
+                }
+                Err(e) => prepared.push((pack, Err(e))),
+            }
+        }
+
+        let client = self.client;
+        let signer = self.signer;
+        let submitted: Vec<(Vec<usize>, Result<MessageId>)> = futures::stream::iter(prepared)
+            .map(move |(pack, transaction)| async move {
+                let result = match transaction {
+                    Ok(transaction) => submit_transaction(client, signer, transaction).await,
+                    Err(e) => Err(e),
+                };
+                (pack, result)
+            })
+            .buffered(parallelism)
+            .collect()
+            .await;
+
+        let mut results: Vec<Option<Result<MessageId>>> = self.transfers.iter().map(|_| None).collect();
+        for (pack, result) in submitted {
+            match result {
+                Ok(message_id) => {
+                    for transfer in pack {
+                        results[transfer] = Some(Ok(message_id));
+                    }
+                }
+                Err(e) => {
+                    for transfer in &pack[1..] {
+                        results[*transfer] = Some(Err(Error::BatchMessageFailed(pack[0], e.to_string())));
+                    }
+                    results[pack[0]] = Some(Err(e));
+                }
+            }
+        }
+        Ok(results
+            .into_iter()
+            .map(|result| result.expect("every transfer is in a pack"))
+            .collect())
+    }
+
+    // The send builder of the account
+    fn sender(&self) -> SendBuilder<'a> {
+        let mut sender = self
+            .client
+            .send()
+            .with_signer(self.signer)
+            .with_account_index(self.account_index.unwrap_or(0));
+        if let Some(initial_address_index) = self.initial_address_index {
+            sender = sender.with_initial_address_index(initial_address_index);
+        }
+        sender
+    }
+
+    // Groups the indexes of the transfers sent in the same transaction, a single transfer without the packing
+    fn packs(&self) -> Vec<Vec<usize>> {
+        let mut packs: Vec<Vec<usize>> = Vec::new();
+        let mut pack_outputs = 0;
+        for (index, outputs) in self.transfers.iter().enumerate() {
+            match packs.last_mut() {
+                Some(pack) if self.packing && pack_outputs + outputs.len() < OUTPUT_COUNT_MAX => {
+                    pack.push(index);
+                    pack_outputs += outputs.len();
+                }
+                _ => {
+                    packs.push(vec![index]);
+                    pack_outputs = outputs.len();
+                }
+            }
+        }
+        packs
+    }
+
+    // Selects the inputs of the transfers of the pack among the available outputs and computes the remainder
+    async fn prepare(&self, pack: &[usize], available: &[InputCandidate]) -> Result<PreparedTransactionData> {
+        let mut sender = self.sender().with_input_candidates(available.to_vec());
+        for transfer in pack {
+            for (address, amount) in &self.transfers[*transfer] {
+                sender = sender.with_output(address, *amount)?;
+            }
+        }
+        sender.prepare_transaction().await
+    }
+}
+
+// Removes the inputs of the transaction from the available outputs
+fn reserve_inputs(available: &mut Vec<InputCandidate>, transaction: &PreparedTransactionData) {
+    available.retain(|candidate| {
+        !transaction
+            .inputs
+            .iter()
+            .any(|input| input.input == candidate.signing_data.input)
+    });
+}
+
+// Signs the transaction, does its PoW and submits it
+async fn submit_transaction(
+    client: &Client,
+    signer: SignerType<'_>,
+    transaction: PreparedTransactionData,
+) -> Result<MessageId> {
+    let payload = transaction.sign(signer)?;
+    let (message_id, _) = client.send().finish_signed_transaction(payload).await?;
+    Ok(message_id)
+}
@@ -0,0 +1,78 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Client, MessageConfirmation};
+
+use bee_message::prelude::MessageId;
+
+use std::{collections::HashSet, sync::Mutex};
+
+/// The number of message metadata fetched in parallel by [`ConfirmationTracker::poll`].
+pub const CONFIRMATION_POLL_CONCURRENCY: usize = 10;
+
+/// Registry of the messages waiting for a milestone to reference them.
+/// The registry doesn't depend on a node connection, so it can be polled with any client and survives reconnects;
+/// every message confirmation is only reported once.
+#[derive(Debug, Default)]
+pub struct ConfirmationTracker {
+    watched: Mutex<HashSet<MessageId>>,
+    confirmed: Mutex<HashSet<MessageId>>,
+}
+
+impl ConfirmationTracker {
+    /// Create an empty confirmation tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watches the confirmation of a message. Returns false if the message is already watched or confirmed.
+    pub fn watch(&self, message_id: MessageId) -> bool {
+        if self.confirmed.lock().unwrap().contains(&message_id) {
+            return false;
+        }
+        self.watched.lock().unwrap().insert(message_id)
+    }
+
+    /// Stops watching the confirmation of a message. Returns false if the message isn't watched.
+    pub fn unwatch(&self, message_id: &MessageId) -> bool {
+        self.watched.lock().unwrap().remove(message_id)
+    }
+
+    /// Gets the messages waiting for their confirmation.
+    pub fn watched(&self) -> Vec<MessageId> {
+        self.watched.lock().unwrap().iter().copied().collect()
+    }
+
+    /// Marks a watched message as confirmed. Returns true only the first time the message is confirmed.
+    pub fn confirm(&self, message_id: &MessageId) -> bool {
+        if !self.watched.lock().unwrap().remove(message_id) {
+            return false;
+        }
+        self.confirmed.lock().unwrap().insert(*message_id)
+    }
+
+    /// Fetches the metadata of the watched messages and returns the new confirmations.
+    /// The metadata is fetched in batches of [`CONFIRMATION_POLL_CONCURRENCY`] parallel requests, a message whose
+    /// metadata can't be fetched is kept and queried again on the next poll.
+    pub async fn poll(&self, client: &Client) -> Vec<MessageConfirmation> {
+        let message_ids = self.watched();
+        let mut confirmations = Vec::new();
+        for message_ids in message_ids.chunks(CONFIRMATION_POLL_CONCURRENCY) {
+            let results = futures::future::join_all(
+                message_ids
+                    .iter()
+                    .map(|message_id| client.get_message_confirmation(message_id)),
+            )
+            .await;
+            for (message_id, result) in message_ids.iter().zip(results) {
+                if let Ok(Some(confirmation)) = result {
+                    // the message may have been unwatched or confirmed by another poll in the meantime
+                    if self.confirm(message_id) {
+                        confirmations.push(confirmation);
+                    }
+                }
+            }
+        }
+        confirmations
+    }
+}
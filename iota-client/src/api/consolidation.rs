@@ -0,0 +1,203 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    api::{
+        check_dust_allowance_inputs, checked_amount_sum, send::sort_inputs, InputCandidate, InputSigningData,
+        PreparedTransactionData, TransferOutput, DUST_THRESHOLD,
+    },
+    Client, Error, OutputType, OutputsOptions, Result, SignerType,
+};
+
+use bee_message::prelude::*;
+use std::ops::Range;
+
+/// The maximum number of inputs of a transaction, the size of the consolidation batches.
+pub const INPUT_COUNT_MAX: usize = 127;
+/// The default range of address indexes scanned for the outputs to consolidate.
+pub const DEFAULT_CONSOLIDATION_RANGE: Range<usize> = 0..20;
+
+/// Builder of the consolidate_outputs API
+pub struct ConsolidationBuilder<'a> {
+    client: &'a Client,
+    signer: SignerType<'a>,
+    account_index: Option<usize>,
+    range: Option<Range<usize>>,
+    include_dust_allowance: bool,
+    wait_for_confirmation: bool,
+}
+
+impl<'a> ConsolidationBuilder<'a> {
+    /// Create consolidate_outputs builder
+    pub fn new<S: Into<SignerType<'a>>>(client: &'a Client, signer: S) -> Self {
+        Self {
+            client,
+            signer: signer.into(),
+            account_index: None,
+            range: None,
+            include_dust_allowance: false,
+            wait_for_confirmation: false,
+        }
+    }
+
+    /// Sets the account index.
+    pub fn with_account_index(mut self, account_index: usize) -> Self {
+        self.account_index = Some(account_index);
+        self
+    }
+
+    /// Sets the range of address indexes to scan, both the public and the internal addresses are scanned.
+    pub fn with_range(mut self, range: Range<usize>) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// Also consolidates the dust allowance outputs, which are skipped by default. Their amount is sent to a dust
+    /// allowance output on the consolidation address, and a batch fails with [`Error::DustAllowanceInput`] if it
+    /// takes the allowance of an address still holding dust outputs.
+    pub fn with_dust_allowance_outputs(mut self, include_dust_allowance: bool) -> Self {
+        self.include_dust_allowance = include_dust_allowance;
+        self
+    }
+
+    /// Waits until each consolidation message is referenced by a milestone before sending the next batch, retrying
+    /// it with [`Client::retry_until_included`].
+    pub fn with_wait_for_confirmation(mut self, wait_for_confirmation: bool) -> Self {
+        self.wait_for_confirmation = wait_for_confirmation;
+        self
+    }
+
+    /// Consume the builder and get the ids of the consolidation messages.
+    /// The unspent outputs of the scanned addresses are sent in batches of at most [`INPUT_COUNT_MAX`] inputs to the
+    /// public address at the start of the range, nothing is sent if there is nothing to consolidate.
+    pub async fn finish(self) -> Result<Vec<MessageId>> {
+        let account_index = self.account_index.unwrap_or(0);
+        let range = self.range.clone().unwrap_or(DEFAULT_CONSOLIDATION_RANGE);
+        let addresses = self
+            .client
+            .find_addresses(self.signer)
+            .with_account_index(account_index)
+            .with_range(range.clone())
+            .get_all()?;
+        if addresses.is_empty() {
+            return Ok(Vec::new());
+        }
+        let consolidation_address = Address::try_from_bech32(&addresses[0].0.to_string())?;
+
+        let mut output_types = vec![OutputType::SignatureLockedSingle];
+        if self.include_dust_allowance {
+            output_types.push(OutputType::SignatureLockedDustAllowance);
+        }
+        // the addresses are (public, internal) pairs for each index
+        let mut outputs = Vec::new();
+        for (index, (address, internal)) in addresses.iter().enumerate() {
+            for output_type in &output_types {
+                let options = OutputsOptions {
+                    include_spent: false,
+                    output_type: Some(*output_type),
+                };
+                let output_ids = self
+                    .client
+                    .get_address()
+                    .outputs_response(address, options)
+                    .await?
+                    .utxo_inputs()?;
+                for output_id in output_ids.iter() {
+                    let output = self.client.get_output_of_type(output_id, *output_type).await?;
+                    if output.is_spent || output.amount == 0 {
+                        continue;
+                    }
+                    outputs.push(InputCandidate {
+                        signing_data: InputSigningData {
+                            input: output_id.clone(),
+                            address: output.address,
+                            address_index: range.start + index / 2,
+                            internal: *internal,
+                        },
+                        amount: output.amount,
+                        output_type: *output_type,
+                    });
+                }
+            }
+        }
+
+        let mut message_ids = Vec::new();
+        for batch in outputs.chunks(INPUT_COUNT_MAX) {
+            // a single output already on the consolidation address doesn't need to move
+            if batch.len() == 1 && batch[0].signing_data.address == consolidation_address {
+                continue;
+            }
+            let message_id = self.send_batch(batch, account_index, &consolidation_address).await?;
+            if self.wait_for_confirmation {
+                self.client.retry_until_included(&message_id, None, None).await?;
+            }
+            message_ids.push(message_id);
+        }
+        Ok(message_ids)
+    }
+
+    // Sends the outputs of the batch to the consolidation address, the dust allowance outputs to a dust allowance
+    // output
+    async fn send_batch(
+        &self,
+        batch: &[InputCandidate],
+        account_index: usize,
+        consolidation_address: &Address,
+    ) -> Result<MessageId> {
+        let amount = checked_amount_sum(batch.iter().map(|candidate| candidate.amount))?;
+        if amount < DUST_THRESHOLD {
+            return Err(Error::DustError(
+                self.client.address_to_bech32(consolidation_address)?.0,
+                amount,
+                DUST_THRESHOLD,
+            ));
+        }
+        let mut allowance: u64 = batch
+            .iter()
+            .filter(|candidate| candidate.output_type == OutputType::SignatureLockedDustAllowance)
+            .map(|candidate| candidate.amount)
+            .sum();
+        let mut single = amount - allowance;
+        // the rest would be a dust output, it joins the allowance
+        if allowance > 0 && single < DUST_THRESHOLD {
+            allowance += single;
+            single = 0;
+        }
+        // the outputs are in the lexicographic order of their serialized form, which starts with the output type
+        let mut outputs: Vec<Output> = Vec::new();
+        if single > 0 {
+            outputs.push(
+                SignatureLockedSingleOutput::new(consolidation_address.clone(), single)
+                    .map_err(|_| Error::TransactionError)?
+                    .into(),
+            );
+        }
+        if allowance > 0 {
+            outputs.push(
+                SignatureLockedDustAllowanceOutput::new(consolidation_address.clone(), allowance)
+                    .map_err(|_| Error::TransactionError)?
+                    .into(),
+            );
+            let transfers = outputs.iter().map(TransferOutput::new).collect::<Result<Vec<_>>>()?;
+            check_dust_allowance_inputs(self.client, batch, &transfers).await?;
+        }
+
+        let inputs = sort_inputs(batch.iter().map(|candidate| candidate.signing_data.clone()).collect())?;
+        let mut essence = TransactionPayloadEssence::builder();
+        for input in &inputs {
+            essence = essence.add_input(Input::UTXO(input.input.clone()));
+        }
+        for output in outputs {
+            essence = essence.add_output(output);
+        }
+        let prepared = PreparedTransactionData {
+            essence: essence.finish()?,
+            account_index,
+            inputs,
+            remainder: None,
+        };
+        let payload = prepared.sign(self.signer)?;
+        let (message_id, _) = self.client.send().finish_signed_transaction(payload).await?;
+        Ok(message_id)
+    }
+}
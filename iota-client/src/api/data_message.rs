@@ -0,0 +1,243 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    api::{INDEXATION_INDEX_LENGTH_RANGE, MESSAGE_LENGTH_MAX},
+    client::blake2b_256,
+    Client, Error, Result,
+};
+
+use bee_common::packable::Packable;
+use bee_message::prelude::{IndexationPayload, MessageBuilder, MessageId, Payload};
+use bee_pow::providers::{Constant, ConstantBuilder, ProviderBuilder};
+use futures::{StreamExt, TryStreamExt};
+
+use std::{collections::BTreeMap, convert::TryInto};
+
+/// The version of the framing header of the data fragments.
+pub const DATA_FRAGMENT_VERSION: u8 = 1;
+/// The length of the framing header of a data fragment: the version, the sequence number and the fragment count as
+/// little endian `u32`, then the BLAKE2b-256 hash of the whole data.
+pub const DATA_FRAGMENT_HEADER_LENGTH: usize = 41;
+// the missing sequence numbers listed by `Error::MissingDataFragments`
+const MISSING_FRAGMENTS_LISTED: usize = 100;
+
+/// The fragment messages of data submitted with [`DataMessageBuilder`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataMessage {
+    /// The ids of the fragment messages, in the order of the fragments.
+    pub message_ids: Vec<MessageId>,
+    /// The BLAKE2b-256 hash of the data, to reassemble it with [`Client::reassemble_data`].
+    pub content_hash: [u8; 32],
+}
+
+// A fragment of data, the indexation data of a fragment message.
+struct DataFragment<'a> {
+    sequence: u32,
+    count: u32,
+    content_hash: [u8; 32],
+    data: &'a [u8],
+}
+
+impl<'a> DataFragment<'a> {
+    fn pack(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(DATA_FRAGMENT_HEADER_LENGTH + self.data.len());
+        bytes.push(DATA_FRAGMENT_VERSION);
+        bytes.extend_from_slice(&self.sequence.to_le_bytes());
+        bytes.extend_from_slice(&self.count.to_le_bytes());
+        bytes.extend_from_slice(&self.content_hash);
+        bytes.extend_from_slice(self.data);
+        bytes
+    }
+
+    // `None` for the data of another message of the index
+    fn unpack(bytes: &'a [u8]) -> Option<Self> {
+        if bytes.len() < DATA_FRAGMENT_HEADER_LENGTH || bytes[0] != DATA_FRAGMENT_VERSION {
+            return None;
+        }
+        Some(Self {
+            sequence: u32::from_le_bytes(bytes[1..5].try_into().ok()?),
+            count: u32::from_le_bytes(bytes[5..9].try_into().ok()?),
+            content_hash: bytes[9..DATA_FRAGMENT_HEADER_LENGTH].try_into().ok()?,
+            data: &bytes[DATA_FRAGMENT_HEADER_LENGTH..],
+        })
+    }
+}
+
+/// Builder of the indexation messages of data of any length, see [`Client::data_message`]. The data is split into
+/// fragments fitting in a message, each prefixed by a framing header of [`DATA_FRAGMENT_HEADER_LENGTH`] bytes.
+pub struct DataMessageBuilder<'a> {
+    client: &'a Client,
+    index: String,
+    data: Vec<u8>,
+    fragment_length: Option<usize>,
+    chaining: bool,
+}
+
+impl<'a> DataMessageBuilder<'a> {
+    /// Create the builder of the fragment messages of `data` with the index
+    pub fn new(client: &'a Client, index: &str, data: Vec<u8>) -> Self {
+        Self {
+            client,
+            index: index.to_string(),
+            data,
+            fragment_length: None,
+            chaining: false,
+        }
+    }
+
+    /// Sets the length of the data of a fragment, without its header. By default, and at most, a fragment fills a
+    /// message of [`MESSAGE_LENGTH_MAX`] bytes.
+    pub fn with_fragment_length(mut self, fragment_length: usize) -> Self {
+        self.fragment_length = Some(fragment_length);
+        self
+    }
+
+    /// Chains the fragment messages, the message of a fragment being a parent of the message of the next one. The
+    /// messages are then submitted one after the other, instead of concurrently.
+    pub fn with_chaining(mut self, chaining: bool) -> Self {
+        self.chaining = chaining;
+        self
+    }
+
+    /// Consume the builder and submit the fragment messages, the first failed submit fails the data. An empty data is
+    /// a single empty fragment.
+    pub async fn finish(self) -> Result<DataMessage> {
+        let fragment_length_max = fragment_length_max(&self.index)?;
+        let fragment_length = self.fragment_length.unwrap_or(fragment_length_max);
+        if fragment_length == 0 || fragment_length > fragment_length_max {
+            return Err(Error::InvalidParameter(format!(
+                "fragment length {}, expected between 1 and {} bytes with the index",
+                fragment_length, fragment_length_max
+            )));
+        }
+        let content_hash = blake2b_256(&self.data);
+        let chunks: Vec<&[u8]> = if self.data.is_empty() {
+            vec![&self.data[..]]
+        } else {
+            self.data.chunks(fragment_length).collect()
+        };
+        let count: u32 = chunks
+            .len()
+            .try_into()
+            .map_err(|_| Error::InvalidParameter(format!("data of {} fragments", chunks.len())))?;
+        let fragments = chunks.into_iter().enumerate().map(|(sequence, data)| {
+            DataFragment {
+                sequence: sequence as u32,
+                count,
+                content_hash,
+                data,
+            }
+            .pack()
+        });
+
+        let client = self.client;
+        let index = &self.index;
+        let message_ids = if self.chaining {
+            let mut message_ids: Vec<MessageId> = Vec::new();
+            for fragment in fragments {
+                let mut sender = client.send().with_index(index).with_data(fragment);
+                if let Some(previous) = message_ids.last() {
+                    sender = sender.with_parent(*previous);
+                }
+                message_ids.push(sender.finish().await?);
+            }
+            message_ids
+        } else {
+            futures::stream::iter(fragments)
+                .map(|fragment| client.send().with_index(index).with_data(fragment).finish())
+                .buffered(client.request_parallelism)
+                .try_collect()
+                .await?
+        };
+        Ok(DataMessage {
+            message_ids,
+            content_hash,
+        })
+    }
+}
+
+// The length of the data of a fragment filling a message with the index.
+fn fragment_length_max(index: &str) -> Result<usize> {
+    if !INDEXATION_INDEX_LENGTH_RANGE.contains(&index.len()) {
+        return Err(Error::IndexationError(format!(
+            "invalid index length {} bytes, expected between {} and {}",
+            index.len(),
+            INDEXATION_INDEX_LENGTH_RANGE.start(),
+            INDEXATION_INDEX_LENGTH_RANGE.end()
+        )));
+    }
+    // The network id, the parents and the nonce have a fixed length
+    let indexation = IndexationPayload::new(index.to_string(), &[0u8; DATA_FRAGMENT_HEADER_LENGTH])
+        .map_err(|e| Error::IndexationError(e.to_string()))?;
+    let message = MessageBuilder::<Constant>::new()
+        .with_network_id(0)
+        .with_parent1(MessageId::new([0u8; 32]))
+        .with_parent2(MessageId::new([0u8; 32]))
+        .with_payload(Payload::Indexation(Box::new(indexation)))
+        .with_nonce_provider(ConstantBuilder::new().with_value(0).finish(), 0f64)
+        .finish()
+        .map_err(Error::MessageError)?;
+    Ok(MESSAGE_LENGTH_MAX.saturating_sub(message.packed_len()))
+}
+
+// Finds the fragments of the data among the messages of the index, see `Client::reassemble_data`.
+pub(crate) async fn reassemble_data(client: &Client, index: &str, content_hash: &[u8; 32]) -> Result<Vec<u8>> {
+    let hash = hex::encode(content_hash);
+    let message_ids = client.get_message().index(index).await?;
+    let messages = client.get_messages(&message_ids).await?;
+    let mut count = None;
+    let mut fragments: BTreeMap<u32, &[u8]> = BTreeMap::new();
+    for message in messages.iter().flatten() {
+        let fragment = match message.payload() {
+            Some(Payload::Indexation(indexation)) => match DataFragment::unpack(indexation.data()) {
+                Some(fragment) if fragment.content_hash == *content_hash => fragment,
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let expected_count = *count.get_or_insert(fragment.count);
+        if expected_count != fragment.count {
+            return Err(Error::InvalidDataFragments(
+                hash,
+                format!("fragments of {} and {} fragments", expected_count, fragment.count),
+            ));
+        }
+        if fragment.sequence >= fragment.count {
+            return Err(Error::InvalidDataFragments(
+                hash,
+                format!("fragment {} of {} fragments", fragment.sequence, fragment.count),
+            ));
+        }
+        // a fragment submitted again is the same
+        match fragments.insert(fragment.sequence, fragment.data) {
+            Some(data) if data != fragment.data => {
+                return Err(Error::InvalidDataFragments(
+                    hash,
+                    format!("conflicting fragments {}", fragment.sequence),
+                ));
+            }
+            _ => {}
+        }
+    }
+    let count = count.ok_or_else(|| Error::DataNotFound(hash.clone()))?;
+    if fragments.len() < count as usize {
+        let missing = (0..count)
+            .filter(|sequence| !fragments.contains_key(sequence))
+            .take(MISSING_FRAGMENTS_LISTED)
+            .collect();
+        return Err(Error::MissingDataFragments {
+            content_hash: hash,
+            missing,
+            fragment_count: count,
+        });
+    }
+    let data: Vec<u8> = fragments.values().flat_map(|data| data.iter().copied()).collect();
+    if blake2b_256(&data) != *content_hash {
+        return Err(Error::InvalidDataFragments(
+            hash,
+            "the reassembled data doesn't match the content hash".to_string(),
+        ));
+    }
+    Ok(data)
+}
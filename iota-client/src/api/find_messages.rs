@@ -0,0 +1,153 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Client, Error, Result};
+
+use bee_message::prelude::{Message, MessageId, Payload};
+use futures::{StreamExt, TryStreamExt};
+
+/// The default number of message ids of a page of an index lookup.
+pub const DEFAULT_MESSAGE_PAGE_SIZE: usize = 100;
+
+/// Builder of the paged lookup of the messages of an indexation key, see [`Client::find_message_pages`].
+pub struct FindMessagesBuilder<'a> {
+    client: &'a Client,
+    index: String,
+    page_size: usize,
+    cursor: Option<String>,
+    referenced_after: Option<u64>,
+    data_prefix: Option<Vec<u8>>,
+}
+
+impl<'a> FindMessagesBuilder<'a> {
+    /// Create the builder of the lookup of the messages of `index`
+    pub fn new(client: &'a Client, index: &str) -> Self {
+        Self {
+            client,
+            index: index.to_string(),
+            page_size: DEFAULT_MESSAGE_PAGE_SIZE,
+            cursor: None,
+            referenced_after: None,
+            data_prefix: None,
+        }
+    }
+
+    /// Sets the number of message ids of a page, [`DEFAULT_MESSAGE_PAGE_SIZE`] by default. A filtered page holds
+    /// fewer messages.
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Sets the cursor to resume the lookup from, the [`MessagePage::next_cursor`] of the last processed page.
+    pub fn with_cursor(mut self, cursor: &str) -> Self {
+        self.cursor = Some(cursor.to_string());
+        self
+    }
+
+    /// Only keeps the messages referenced by a milestone after `milestone_index`. The metadata of each message is
+    /// then fetched along with the message.
+    pub fn with_referenced_after(mut self, milestone_index: u64) -> Self {
+        self.referenced_after = Some(milestone_index);
+        self
+    }
+
+    /// Only keeps the messages whose indexation payload data starts with `prefix`.
+    pub fn with_data_prefix(mut self, prefix: &[u8]) -> Self {
+        self.data_prefix = Some(prefix.to_vec());
+        self
+    }
+
+    /// Consume the builder and look the messages up page by page, see [`MessagePages::next_page`]. The node has no
+    /// paging of the index lookup, so the pages are windows of the message ids it answers.
+    pub fn finish_pages(self) -> Result<MessagePages<'a>> {
+        if self.page_size == 0 {
+            return Err(Error::InvalidParameter(String::from("page size")));
+        }
+        let offset = match &self.cursor {
+            Some(cursor) => cursor
+                .parse()
+                .map_err(|_| Error::InvalidParameter(String::from("cursor")))?,
+            None => 0,
+        };
+        Ok(MessagePages {
+            client: self.client,
+            index: self.index,
+            page_size: self.page_size,
+            referenced_after: self.referenced_after,
+            data_prefix: self.data_prefix,
+            message_ids: None,
+            offset,
+        })
+    }
+}
+
+/// The messages of a window of the message ids of an index.
+#[derive(Clone, Debug)]
+pub struct MessagePage {
+    /// The messages of the window passing the filters, in the order of the ids answered by the node.
+    pub messages: Vec<Message>,
+    /// The cursor to resume the lookup with [`FindMessagesBuilder::with_cursor`] once the page is processed, `None`
+    /// after the last page.
+    pub next_cursor: Option<String>,
+    /// Whether the index has message ids after the page.
+    pub has_more: bool,
+}
+
+/// An index lookup yielding the messages page by page, see [`FindMessagesBuilder::finish_pages`]. No request is sent
+/// before a page is asked for, so dropping the pages stops the lookup.
+pub struct MessagePages<'a> {
+    client: &'a Client,
+    index: String,
+    page_size: usize,
+    referenced_after: Option<u64>,
+    data_prefix: Option<Vec<u8>>,
+    // the message ids of the index, fetched with the first page
+    message_ids: Option<Box<[MessageId]>>,
+    offset: usize,
+}
+
+impl<'a> MessagePages<'a> {
+    /// The page of the next `page_size` message ids, `None` after the last page. The messages are fetched
+    /// concurrently, see [`ClientBuilder::with_request_parallelism`](crate::ClientBuilder::with_request_parallelism),
+    /// and the first failed request fails the page, a message the client can't parse with [`Error::InvalidMessage`].
+    pub async fn next_page(&mut self) -> Result<Option<MessagePage>> {
+        if self.message_ids.is_none() {
+            self.message_ids = Some(self.client.get_message().index(&self.index).await?);
+        }
+        let message_ids = self.message_ids.as_deref().unwrap_or_default();
+        if self.offset >= message_ids.len() {
+            return Ok(None);
+        }
+        let end = message_ids.len().min(self.offset + self.page_size);
+        let client = self.client;
+        let (referenced_after, data_prefix) = (self.referenced_after, &self.data_prefix);
+        let messages: Vec<Option<Message>> = futures::stream::iter(&message_ids[self.offset..end])
+            .map(|message_id| async move {
+                let message = client.get_message().data(message_id).await?;
+                if let Some(prefix) = data_prefix {
+                    match message.payload() {
+                        Some(Payload::Indexation(indexation)) if indexation.data().starts_with(prefix) => {}
+                        _ => return Ok(None),
+                    }
+                }
+                if let Some(milestone_index) = referenced_after {
+                    let metadata = client.get_message().metadata(message_id).await?;
+                    if !matches!(metadata.referenced_by_milestone_index, Some(index) if index > milestone_index) {
+                        return Ok(None);
+                    }
+                }
+                Ok::<_, Error>(Some(message))
+            })
+            .buffered(client.request_parallelism)
+            .try_collect()
+            .await?;
+        self.offset = end;
+        let has_more = end < message_ids.len();
+        Ok(Some(MessagePage {
+            messages: messages.into_iter().flatten().collect(),
+            next_cursor: if has_more { Some(end.to_string()) } else { None },
+            has_more,
+        }))
+    }
+}
@@ -0,0 +1,380 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    api::GetBalanceBuilder, types::Bech32Address, Client, Error, LedgerInclusionState, OutputType, Result, SignerType,
+};
+
+use bee_message::prelude::{Input, MessageId, Output, Payload, TransactionId, TransactionPayloadEssence, UTXOInput};
+use futures::{StreamExt, TryStreamExt};
+use serde::Serialize;
+
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+/// Builder of the get_transaction_history API, the history of the used addresses of a seed, see
+/// [`Client::get_transaction_history`].
+pub struct GetTransactionHistoryBuilder<'a> {
+    client: &'a Client,
+    balance: GetBalanceBuilder<'a>,
+}
+
+impl<'a> GetTransactionHistoryBuilder<'a> {
+    /// Create get_transaction_history builder
+    pub fn new<S: Into<SignerType<'a>>>(client: &'a Client, signer: S) -> Self {
+        Self {
+            client,
+            balance: GetBalanceBuilder::new(client, signer),
+        }
+    }
+
+    /// Sets the account index.
+    pub fn with_account_index(mut self, account_index: usize) -> Self {
+        self.balance = self.balance.with_account_index(account_index);
+        self
+    }
+
+    /// Sets the index of the address to start looking for used addresses.
+    pub fn with_initial_address_index(mut self, initial_address_index: usize) -> Self {
+        self.balance = self.balance.with_initial_address_index(initial_address_index);
+        self
+    }
+
+    /// Sets the number of consecutive unused address indexes after which the search of the used addresses stops, see
+    /// [`GetBalanceBuilder::with_gap_limit`].
+    pub fn with_gap_limit(mut self, gap_limit: usize) -> Self {
+        self.balance = self.balance.with_gap_limit(gap_limit);
+        self
+    }
+
+    /// Consume the builder and get the history of the used addresses, see
+    /// [`Client::get_address_transaction_history`].
+    pub async fn finish(self) -> Result<TransactionHistory> {
+        let mut pages = self.balance.finish_pages()?;
+        let mut addresses = Vec::new();
+        let mut output_ids = Vec::new();
+        let mut spent_output_ids = Vec::new();
+        while let Some(page) = pages.next_page().await? {
+            for address in page.addresses {
+                addresses.push(address.address);
+                output_ids.extend(address.outputs);
+                spent_output_ids.extend(address.spent_outputs);
+            }
+        }
+        transaction_history(self.client, &addresses, output_ids, spent_output_ids).await
+    }
+}
+
+/// The value moved from or to an address by a transaction.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AddressDelta {
+    /// The bech32 address.
+    pub address: Bech32Address,
+    /// The amount of the outputs of the transaction to the address minus the amount of its inputs from the address.
+    pub delta: i64,
+}
+
+/// A transaction moving value from or to the addresses of a history.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TransactionHistoryEntry {
+    /// The transaction id.
+    #[serde(rename = "transactionId")]
+    pub transaction_id: String,
+    /// The id of the message that got the transaction included in the ledger, the reattachments are left out.
+    #[serde(rename = "messageId")]
+    pub message_id: String,
+    /// The index of the milestone referencing the message.
+    #[serde(rename = "milestoneIndex")]
+    pub milestone_index: Option<u64>,
+    /// The timestamp of the milestone referencing the message, in seconds.
+    pub timestamp: Option<u64>,
+    /// The ledger inclusion state of the message.
+    #[serde(rename = "inclusionState")]
+    pub inclusion_state: Option<LedgerInclusionState>,
+    /// The value delta of each address of the history the transaction spends from or sends to, in the order of the
+    /// inputs then the outputs.
+    pub deltas: Vec<AddressDelta>,
+    /// The other addresses of the inputs and the outputs of the transaction: the senders of a deposit, the recipients
+    /// of a spend.
+    pub counterparts: Vec<Bech32Address>,
+}
+
+impl TransactionHistoryEntry {
+    /// The value delta of the transaction for all the addresses of the history, negative for a spend.
+    pub fn delta(&self) -> i64 {
+        self.deltas.iter().map(|delta| delta.delta).sum()
+    }
+}
+
+/// The transactions of a set of addresses, sorted by the milestone referencing them.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TransactionHistory {
+    /// The transactions, the ones not referenced by a milestone last.
+    pub entries: Vec<TransactionHistoryEntry>,
+}
+
+// The columns of the CSV export.
+const CSV_HEADER: &str = "milestoneIndex,timestamp,messageId,transactionId,address,delta,counterparts,inclusionState";
+
+impl TransactionHistory {
+    /// The history as JSON lines, one line per transaction.
+    pub fn to_json_lines(&self) -> Result<String> {
+        let mut lines = String::new();
+        for entry in &self.entries {
+            lines.push_str(&serde_json::to_string(entry)?);
+            lines.push('\n');
+        }
+        Ok(lines)
+    }
+
+    /// The history as CSV with a header, one row per address delta of each transaction. The counterparts of a
+    /// transaction are separated by spaces, the missing values are empty.
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("{}\n", CSV_HEADER);
+        let optional = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+        for entry in &self.entries {
+            let counterparts: Vec<&str> = entry.counterparts.iter().map(|address| address.as_str()).collect();
+            for delta in &entry.deltas {
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{},{},{}\n",
+                    optional(entry.milestone_index),
+                    optional(entry.timestamp),
+                    entry.message_id,
+                    entry.transaction_id,
+                    delta.address,
+                    delta.delta,
+                    counterparts.join(" "),
+                    entry.inclusion_state.map(|state| state.to_string()).unwrap_or_default(),
+                ));
+            }
+        }
+        csv
+    }
+}
+
+// The history of the addresses from the ids of their unspent and spent outputs: the transactions creating the
+// outputs, then the transactions consuming the spent outputs that aren't inputs of the transactions found.
+pub(crate) async fn transaction_history(
+    client: &Client,
+    addresses: &[Bech32Address],
+    output_ids: Vec<UTXOInput>,
+    spent_output_ids: Vec<UTXOInput>,
+) -> Result<TransactionHistory> {
+    // the addresses are encoded again so they compare with the addresses of the transactions
+    let own_addresses = addresses
+        .iter()
+        .map(|address| Ok(client.address_to_bech32(&client.parse_bech32_address(address)?)?.0))
+        .collect::<Result<HashSet<String>>>()?;
+    // an output id holds the id of the transaction creating it, the reattachments of a transaction create the same
+    // outputs so each transaction is only listed once
+    let mut seen = HashSet::new();
+    let transaction_ids: Vec<TransactionId> = output_ids
+        .iter()
+        .chain(&spent_output_ids)
+        .map(|output_id| *output_id.output_id().transaction_id())
+        .filter(|transaction_id| seen.insert(*transaction_id))
+        .collect();
+    let transactions = included_transactions(client, transaction_ids).await?;
+    let mut entries = history_entries(client, &own_addresses, &transactions).await?;
+
+    // a spent output is consumed from the milestone referencing its transaction on, the outputs of the transactions
+    // left out can't be followed
+    let milestone_indexes: HashMap<TransactionId, u64> = transactions
+        .iter()
+        .zip(&entries)
+        .filter_map(|(transaction, entry)| Some((transaction.transaction_id, entry.milestone_index?)))
+        .collect();
+    let consumed: HashSet<&String> = transactions
+        .iter()
+        .flat_map(|transaction| &transaction.inputs)
+        .collect();
+    let mut pending = HashSet::new();
+    let mut first_milestone_index: Option<u64> = None;
+    for output_id in &spent_output_ids {
+        let milestone_index = match milestone_indexes.get(output_id.output_id().transaction_id()) {
+            Some(milestone_index) => *milestone_index,
+            None => continue,
+        };
+        let output_id = output_id.output_id().to_string();
+        if !consumed.contains(&output_id) {
+            pending.insert(output_id);
+            first_milestone_index =
+                Some(first_milestone_index.map_or(milestone_index, |first| first.min(milestone_index)));
+        }
+    }
+    if let Some(first_milestone_index) = first_milestone_index {
+        let solid_milestone_index = client.get_info().await?.solid_milestone_index as u64;
+        let mut milestones = futures::stream::iter(first_milestone_index..=solid_milestone_index)
+            .map(|milestone_index| client.get_milestone_utxo_changes(milestone_index))
+            .buffered(client.request_parallelism);
+        while !pending.is_empty() {
+            let changes = match milestones.try_next().await? {
+                Some(changes) => changes,
+                None => break,
+            };
+            let consumes_pending = changes.consumed_outputs.iter().any(|output_id| {
+                UTXOInput::from_str(output_id)
+                    .map_or(false, |output_id| pending.contains(&output_id.output_id().to_string()))
+            });
+            if !consumes_pending {
+                continue;
+            }
+            // the transactions consuming outputs in the milestone create outputs in it too
+            let mut candidates = Vec::new();
+            for output_id in &changes.created_outputs {
+                let output_id = UTXOInput::from_str(output_id)
+                    .map_err(|_| Error::InvalidParameter(format!("output id {}", output_id)))?;
+                let transaction_id = *output_id.output_id().transaction_id();
+                if seen.insert(transaction_id) {
+                    candidates.push(transaction_id);
+                }
+            }
+            let spends: Vec<IncludedTransaction> = included_transactions(client, candidates)
+                .await?
+                .into_iter()
+                .filter(|transaction| transaction.inputs.iter().any(|input| pending.contains(input)))
+                .collect();
+            for transaction in &spends {
+                for input in &transaction.inputs {
+                    pending.remove(input);
+                }
+            }
+            entries.extend(history_entries(client, &own_addresses, &spends).await?);
+        }
+    }
+
+    entries.sort_by(|a, b| {
+        (a.milestone_index.is_none(), a.milestone_index, &a.transaction_id).cmp(&(
+            b.milestone_index.is_none(),
+            b.milestone_index,
+            &b.transaction_id,
+        ))
+    });
+    Ok(TransactionHistory { entries })
+}
+
+// A transaction included in the ledger, with the ids of the outputs it consumes.
+struct IncludedTransaction {
+    transaction_id: TransactionId,
+    message_id: MessageId,
+    essence: TransactionPayloadEssence,
+    inputs: Vec<String>,
+}
+
+// The included transactions, the ones not included in the ledger or pruned by the node are left out.
+async fn included_transactions(
+    client: &Client,
+    transaction_ids: Vec<TransactionId>,
+) -> Result<Vec<IncludedTransaction>> {
+    let transactions: Vec<Option<IncludedTransaction>> = futures::stream::iter(transaction_ids)
+        .map(|transaction_id| async move {
+            let message = match client.get_included_message(&transaction_id).await {
+                Err(Error::TransactionNotIncluded(_)) => return Ok(None),
+                message => message?,
+            };
+            let essence = match message.payload() {
+                Some(Payload::Transaction(transaction)) => transaction.essence().clone(),
+                _ => return Ok(None),
+            };
+            let inputs = essence
+                .inputs()
+                .iter()
+                .map(|input| match input {
+                    Input::UTXO(input) => Ok(input.output_id().to_string()),
+                    _ => Err(Error::TransactionError),
+                })
+                .collect::<Result<Vec<String>>>()?;
+            Ok(Some(IncludedTransaction {
+                transaction_id,
+                message_id: message.id().0,
+                essence,
+                inputs,
+            }))
+        })
+        .buffered(client.request_parallelism)
+        .try_collect()
+        .await?;
+    Ok(transactions.into_iter().flatten().collect())
+}
+
+// The entries of the transactions, in their order.
+async fn history_entries(
+    client: &Client,
+    own_addresses: &HashSet<String>,
+    transactions: &[IncludedTransaction],
+) -> Result<Vec<TransactionHistoryEntry>> {
+    futures::stream::iter(transactions)
+        .map(|transaction| history_entry(client, own_addresses, transaction))
+        .buffered(client.request_parallelism)
+        .try_collect()
+        .await
+}
+
+// The entry of an included transaction.
+async fn history_entry(
+    client: &Client,
+    own_addresses: &HashSet<String>,
+    transaction: &IncludedTransaction,
+) -> Result<TransactionHistoryEntry> {
+    let essence = &transaction.essence;
+    // the (address, amount) flows of the inputs, negative, then of the outputs
+    let mut flows: Vec<(Bech32Address, i64)> = futures::stream::iter(essence.inputs())
+        .map(|input| async move {
+            let input = match input {
+                Input::UTXO(input) => input,
+                _ => return Err(Error::TransactionError),
+            };
+            let output = client
+                .get_output_of_types(
+                    input,
+                    &[
+                        OutputType::SignatureLockedSingle,
+                        OutputType::SignatureLockedDustAllowance,
+                    ],
+                )
+                .await?;
+            Ok((client.address_to_bech32(&output.address)?, -(output.amount as i64)))
+        })
+        .buffered(client.request_parallelism)
+        .try_collect()
+        .await?;
+    for output in essence.outputs() {
+        let (address, amount) = match output {
+            Output::SignatureLockedSingle(output) => (output.address(), output.amount()),
+            Output::SignatureLockedDustAllowance(output) => (output.address(), output.amount()),
+            _ => return Err(Error::TransactionError),
+        };
+        flows.push((client.address_to_bech32(address)?, amount as i64));
+    }
+
+    let mut deltas: Vec<AddressDelta> = Vec::new();
+    let mut counterparts: Vec<Bech32Address> = Vec::new();
+    for (address, amount) in flows {
+        if own_addresses.contains(&address.0) {
+            match deltas.iter_mut().find(|delta| delta.address == address) {
+                Some(delta) => delta.delta += amount,
+                None => deltas.push(AddressDelta { address, delta: amount }),
+            }
+        } else if !counterparts.contains(&address) {
+            counterparts.push(address);
+        }
+    }
+
+    let message_id = transaction.message_id;
+    let metadata = client.get_message().metadata(&message_id).await?;
+    let timestamp = match metadata.referenced_by_milestone_index {
+        Some(index) => Some(client.get_milestone(index).await?.timestamp),
+        None => None,
+    };
+    Ok(TransactionHistoryEntry {
+        transaction_id: transaction.transaction_id.to_string(),
+        message_id: message_id.to_string(),
+        milestone_index: metadata.referenced_by_milestone_index,
+        timestamp,
+        inclusion_state: metadata.ledger_inclusion_state,
+        deltas,
+        counterparts,
+    })
+}
@@ -0,0 +1,154 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    api::{validate_milestone, CoordinatorKeys},
+    compute_message_id, Client, Error, Result,
+};
+
+use bee_common::packable::Packable;
+use bee_message::prelude::{Message, MessageId, Payload};
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+
+use std::{collections::HashMap, str::FromStr};
+
+/// The proof that a milestone references a message: the packed messages of a chain of parent links from the milestone
+/// message down to the message. It is verified offline with [`verify_inclusion_proof`], a verifier only needs the
+/// keys of the coordinator.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    /// The id of the message proven to be included.
+    #[serde(rename = "messageId")]
+    pub message_id: String,
+    /// The index of the milestone referencing the message.
+    #[serde(rename = "milestoneIndex")]
+    pub milestone_index: u32,
+    /// The hex encoded packed messages of the chain: the milestone message first, then each message is a parent of
+    /// the previous one, the message last.
+    pub chain: Vec<String>,
+}
+
+// The proof from the metadata of the node: a breadth first search of the message from the milestone message through
+// the parents, in the past cone of the milestone only, then the packed messages of the path found.
+pub(crate) async fn create_inclusion_proof(client: &Client, message_id: &MessageId) -> Result<InclusionProof> {
+    let milestone_index = client
+        .get_message()
+        .metadata(message_id)
+        .await?
+        .referenced_by_milestone_index
+        .ok_or_else(|| Error::MessageNotReferenced(message_id.to_string()))?;
+    let milestone_message_id = MessageId::from_str(&client.get_milestone(milestone_index).await?.message_id)?;
+
+    // the child each visited message was reached from
+    let mut children: HashMap<MessageId, MessageId> = HashMap::new();
+    let mut level = vec![milestone_message_id];
+    while *message_id != milestone_message_id && !children.contains_key(message_id) {
+        if level.is_empty() {
+            return Err(Error::InclusionPathNotFound(message_id.to_string(), milestone_index));
+        }
+        let metadata: Vec<_> = futures::stream::iter(&level)
+            .map(|id| client.get_message().metadata(id))
+            .buffered(client.request_parallelism)
+            .try_collect()
+            .await?;
+        let mut next = Vec::new();
+        for (child, metadata) in level.iter().zip(metadata) {
+            // the messages referenced by an older milestone are outside of the past cone
+            if *child != milestone_message_id && metadata.referenced_by_milestone_index != Some(milestone_index) {
+                continue;
+            }
+            for parent in &[metadata.parent1, metadata.parent2] {
+                let parent = MessageId::from_str(parent)?;
+                if parent != milestone_message_id && !children.contains_key(&parent) {
+                    children.insert(parent, *child);
+                    next.push(parent);
+                }
+            }
+        }
+        level = next;
+    }
+
+    let mut path = vec![*message_id];
+    while let Some(child) = children.get(&path[path.len() - 1]) {
+        path.push(*child);
+    }
+    path.reverse();
+    let chain = futures::stream::iter(&path)
+        .map(|id| async move { Ok(hex::encode(client.get_message().raw(id).await?)) })
+        .buffered(client.request_parallelism)
+        .try_collect::<Vec<String>>()
+        .await?;
+    Ok(InclusionProof {
+        message_id: message_id.to_string(),
+        milestone_index: milestone_index as u32,
+        chain,
+    })
+}
+
+/// Verifies an inclusion proof offline: the milestone payload of the first message of the chain is valid for the
+/// coordinator keys, see [`validate_milestone`], each message id is computed from the packed message and each message
+/// is a parent of the previous one, down to the proven message. Fails with [`Error::InvalidInclusionProof`] locating
+/// the first broken link.
+pub fn verify_inclusion_proof(proof: &InclusionProof, keys: &CoordinatorKeys) -> Result<()> {
+    let invalid = |link: usize, reason: String| Error::InvalidInclusionProof(link, reason);
+    if proof.chain.is_empty() {
+        return Err(invalid(0, "the chain is empty".to_string()));
+    }
+    let mut parents: Option<(MessageId, MessageId)> = None;
+    let mut message_id = None;
+    for (link, packed) in proof.chain.iter().enumerate() {
+        let bytes = hex::decode(packed).map_err(|e| invalid(link, format!("invalid hex: {}", e)))?;
+        let message =
+            Message::unpack(&mut bytes.as_slice()).map_err(|e| invalid(link, format!("invalid message: {:?}", e)))?;
+        // bytes after the message would be left out of the message id
+        if message.pack_new() != bytes {
+            return Err(invalid(link, "the bytes aren't a packed message".to_string()));
+        }
+        let id = compute_message_id(&bytes);
+        match parents {
+            None => verify_milestone(proof.milestone_index, &message, keys).map_err(|e| invalid(link, e))?,
+            Some((parent1, parent2)) if id != parent1 && id != parent2 => {
+                return Err(invalid(
+                    link,
+                    format!("the message {} isn't a parent of the previous message", id),
+                ));
+            }
+            Some(_) => {}
+        }
+        parents = Some((*message.parent1(), *message.parent2()));
+        message_id = Some(id);
+    }
+    match message_id {
+        Some(id) if id.to_string() == proof.message_id => Ok(()),
+        Some(id) => Err(invalid(
+            proof.chain.len() - 1,
+            format!("the chain ends at the message {}, not {}", id, proof.message_id),
+        )),
+        None => unreachable!("the chain isn't empty"),
+    }
+}
+
+// Checks the milestone message of a proof, with the reason of the failure.
+fn verify_milestone(
+    milestone_index: u32,
+    message: &Message,
+    keys: &CoordinatorKeys,
+) -> std::result::Result<(), String> {
+    let milestone = match message.payload() {
+        Some(Payload::Milestone(milestone)) => milestone,
+        _ => return Err("the first message isn't a milestone".to_string()),
+    };
+    let essence = milestone.essence();
+    if essence.index() != milestone_index {
+        return Err(format!(
+            "the milestone has the index {}, not {}",
+            essence.index(),
+            milestone_index
+        ));
+    }
+    validate_milestone(milestone, keys).map_err(|e| match e {
+        Error::InvalidMilestone(_, reason) => reason,
+        e => e.to_string(),
+    })
+}
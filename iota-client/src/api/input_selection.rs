@@ -0,0 +1,205 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    api::{checked_amount, checked_amount_sum, InputSigningData, INPUT_COUNT_MAX},
+    AddressJson, Error, OutputMetadata, OutputType, Result,
+};
+
+use bee_message::prelude::{Address, TransactionId, UTXOInput};
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::{TryFrom, TryInto},
+    str::FromStr,
+};
+
+const TRANSACTION_ID_LENGTH: usize = 32;
+
+/// The strategy selecting the inputs of a transaction among the unspent outputs of the account.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputSelectionStrategy {
+    /// Selects the largest outputs first, minimizing the number of inputs.
+    LargestFirst,
+    /// Selects the smallest outputs first, consolidating the small outputs while sending.
+    SmallestFirst,
+    /// Selects the given outputs in their order until the amount is covered, the spent or unknown outputs are
+    /// skipped.
+    Manual(Vec<UTXOInput>),
+}
+
+impl Default for InputSelectionStrategy {
+    fn default() -> Self {
+        Self::LargestFirst
+    }
+}
+
+/// An unspent output of the account that can fund a transaction.
+/// The JSON form lets an online client export the unspent outputs of the account to an offline client, see
+/// [`SendBuilder::get_input_candidates`](crate::api::SendBuilder::get_input_candidates).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "InputCandidateJson", into = "InputCandidateJson")]
+pub struct InputCandidate {
+    /// The output as input, with the derivation indexes of its address.
+    pub signing_data: InputSigningData,
+    /// The amount of the output.
+    pub amount: u64,
+    /// The type of the output.
+    pub output_type: OutputType,
+}
+
+impl InputCandidate {
+    pub(crate) fn new(
+        output: &OutputMetadata,
+        output_type: OutputType,
+        address_index: usize,
+        internal: bool,
+    ) -> Result<Self> {
+        let transaction_id: [u8; TRANSACTION_ID_LENGTH] = output.transaction_id[..]
+            .try_into()
+            .map_err(|_| Error::TransactionError)?;
+        let input = UTXOInput::new(TransactionId::from(transaction_id), output.output_index)
+            .map_err(|_| Error::TransactionError)?;
+        Ok(Self {
+            // Note that we need to sign the original address, i.e., `path/index`,
+            // instead of `path/index/_offset` or `path/_offset`.
+            signing_data: InputSigningData {
+                input,
+                address: output.address.clone(),
+                address_index,
+                internal,
+            },
+            amount: output.amount,
+            output_type,
+        })
+    }
+
+    // The sort key of the candidates, the output id breaks the ties so the order only depends on the UTXO set
+    fn sort_key(&self) -> (u64, String) {
+        (self.amount, self.signing_data.input.output_id().to_string())
+    }
+}
+
+// The JSON form of an input candidate, the output id is hex encoded like in the node API
+#[derive(Serialize, Deserialize)]
+struct InputCandidateJson {
+    #[serde(rename = "outputId")]
+    output_id: String,
+    address: AddressJson,
+    #[serde(rename = "addressIndex")]
+    address_index: usize,
+    internal: bool,
+    amount: u64,
+    #[serde(rename = "outputType")]
+    output_type: OutputType,
+}
+
+impl From<InputCandidate> for InputCandidateJson {
+    fn from(candidate: InputCandidate) -> Self {
+        Self {
+            output_id: candidate.signing_data.input.output_id().to_string(),
+            address: (&candidate.signing_data.address).into(),
+            address_index: candidate.signing_data.address_index,
+            internal: candidate.signing_data.internal,
+            amount: candidate.amount,
+            output_type: candidate.output_type,
+        }
+    }
+}
+
+impl TryFrom<InputCandidateJson> for InputCandidate {
+    type Error = Error;
+
+    fn try_from(value: InputCandidateJson) -> Result<Self> {
+        let input = UTXOInput::from_str(&value.output_id)
+            .map_err(|_| Error::InvalidParameter(format!("output id {}", value.output_id)))?;
+        Ok(Self {
+            signing_data: InputSigningData {
+                input,
+                address: Address::try_from(value.address)?,
+                address_index: value.address_index,
+                internal: value.internal,
+            },
+            amount: value.amount,
+            output_type: value.output_type,
+        })
+    }
+}
+
+/// Selects the candidates funding the amount with the strategy, in the order of the transaction inputs.
+///
+/// The dust allowance outputs are only selected with `include_dust_allowance`, as spending them can make the dust
+/// outputs of their address violate the dust protection. Fails with [`Error::NotEnoughBalance`] if the candidates
+/// don't cover the amount, with [`Error::TooManyInputs`] if they only cover it with more than
+/// [`INPUT_COUNT_MAX`] inputs, and with [`Error::ValueOverflow`] if the amount or the candidates total exceeds the
+/// [`IOTA_SUPPLY`](crate::api::IOTA_SUPPLY).
+pub fn select_inputs(
+    candidates: &[InputCandidate],
+    amount: u64,
+    strategy: &InputSelectionStrategy,
+    include_dust_allowance: bool,
+) -> Result<Vec<InputCandidate>> {
+    let candidates: Vec<&InputCandidate> = candidates
+        .iter()
+        .filter(|candidate| {
+            candidate.amount > 0
+                && (include_dust_allowance || candidate.output_type == OutputType::SignatureLockedSingle)
+        })
+        .collect();
+    checked_amount(amount)?;
+    // the partial sums of the selection are within the candidates total
+    let available = checked_amount_sum(candidates.iter().map(|candidate| candidate.amount))?;
+    if available < amount {
+        return Err(Error::NotEnoughBalance(available, amount));
+    }
+
+    if let InputSelectionStrategy::Manual(_) = strategy {
+        let count = covering_count(candidates.iter().copied(), amount);
+        if count > INPUT_COUNT_MAX {
+            return Err(Error::TooManyInputs(count, INPUT_COUNT_MAX));
+        }
+        return Ok(candidates[..count]
+            .iter()
+            .map(|candidate| (*candidate).clone())
+            .collect());
+    }
+
+    let mut sorted = candidates;
+    sorted.sort_by_key(|candidate| candidate.sort_key());
+    // The largest outputs cover the amount with the fewest inputs
+    let count = covering_count(sorted.iter().rev().copied(), amount);
+    if count > INPUT_COUNT_MAX {
+        return Err(Error::TooManyInputs(count, INPUT_COUNT_MAX));
+    }
+    let selected = match strategy {
+        InputSelectionStrategy::SmallestFirst => {
+            // The window of the smallest outputs slides to the larger ones while it's full and doesn't cover the
+            // amount, at the latest it ends on the largest outputs which cover it
+            let (mut start, mut end, mut total) = (0, 0, 0);
+            while total < amount {
+                if end - start == INPUT_COUNT_MAX {
+                    total -= sorted[start].amount;
+                    start += 1;
+                }
+                total += sorted[end].amount;
+                end += 1;
+            }
+            sorted[start..end].to_vec()
+        }
+        _ => sorted.iter().rev().take(count).copied().collect(),
+    };
+    Ok(selected.into_iter().cloned().collect())
+}
+
+// Counts the candidates needed to cover the amount, taking them in order
+fn covering_count<'a>(candidates: impl Iterator<Item = &'a InputCandidate>, amount: u64) -> usize {
+    let mut total = 0;
+    let mut count = 0;
+    for candidate in candidates {
+        if total >= amount {
+            break;
+        }
+        total += candidate.amount;
+        count += 1;
+    }
+    count
+}
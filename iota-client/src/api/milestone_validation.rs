@@ -0,0 +1,197 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{client::blake2b_256, compute_message_id, verify_ed25519_signature, Error, MilestoneMetadata, Result};
+
+use bee_common::packable::Packable;
+use bee_message::{
+    payload::milestone::MilestonePayloadEssence,
+    prelude::{Message, MilestonePayload, Payload},
+};
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashSet;
+
+/// A public key of the coordinator with the milestone indexes it signs: from `start` up to `end` excluded, every
+/// index from `start` without an end.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoordinatorPublicKey {
+    /// The hex encoded Ed25519 public key.
+    #[serde(rename = "publicKey")]
+    pub public_key: String,
+    /// The first milestone index signed by the key.
+    pub start: u32,
+    /// The first milestone index not signed by the key anymore.
+    #[serde(default)]
+    pub end: Option<u32>,
+}
+
+impl CoordinatorPublicKey {
+    fn signs(&self, index: u32) -> bool {
+        self.start <= index && !matches!(self.end, Some(end) if index >= end)
+    }
+}
+
+/// The public keys of the coordinator and the number of them signing each milestone, set with
+/// [`ClientBuilder::with_coordinator_keys`](crate::ClientBuilder::with_coordinator_keys). The default is the
+/// coordinator of the mainnet, see [`CoordinatorKeys::mainnet`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoordinatorKeys {
+    /// The public keys with their milestone index ranges.
+    #[serde(rename = "publicKeys")]
+    pub public_keys: Vec<CoordinatorPublicKey>,
+    /// The number of signatures a milestone needs at least, by distinct keys of its index.
+    #[serde(rename = "signatureCount")]
+    pub signature_count: usize,
+}
+
+/// The public keys of the mainnet coordinator with their milestone index ranges, two of them sign each milestone.
+pub const MAINNET_COORDINATOR_PUBLIC_KEYS: [(&str, u32, u32); 3] = [
+    (
+        "a9b46fe743df783dedd00c954612428b34241f5913cf249d75bed3aafd65e4cd",
+        0,
+        777600,
+    ),
+    (
+        "365fb85e7568b9b32f7359d6cbafa9814472ad0ecbad32d77beaf5dd9e84c6ba",
+        0,
+        1555200,
+    ),
+    (
+        "ba6d07d1a1aea969e7e435f9f7d1b736ea9e0fcb8de400bf855dba7f2a57e947",
+        552960,
+        2108160,
+    ),
+];
+
+impl CoordinatorKeys {
+    /// The coordinator keys of the mainnet, see [`MAINNET_COORDINATOR_PUBLIC_KEYS`].
+    pub fn mainnet() -> Self {
+        Self {
+            public_keys: MAINNET_COORDINATOR_PUBLIC_KEYS
+                .iter()
+                .map(|(public_key, start, end)| CoordinatorPublicKey {
+                    public_key: public_key.to_string(),
+                    start: *start,
+                    end: Some(*end),
+                })
+                .collect(),
+            signature_count: 2,
+        }
+    }
+
+    // The public keys signing the milestone index.
+    fn applicable_public_keys(&self, index: u32) -> Result<HashSet<[u8; 32]>> {
+        let mut public_keys = HashSet::new();
+        for key in self.public_keys.iter().filter(|key| key.signs(index)) {
+            let mut public_key = [0u8; 32];
+            hex::decode_to_slice(&key.public_key, &mut public_key).map_err(|_| {
+                Error::InvalidParameter(format!(
+                    "coordinator public key {}: expected 32 hex encoded bytes",
+                    key.public_key
+                ))
+            })?;
+            public_keys.insert(public_key);
+        }
+        Ok(public_keys)
+    }
+}
+
+impl Default for CoordinatorKeys {
+    fn default() -> Self {
+        Self::mainnet()
+    }
+}
+
+/// The bytes the coordinator signs for a milestone, the BLAKE2b-256 hash of its packed essence (RFC-0019).
+pub fn milestone_essence_hash(essence: &MilestonePayloadEssence) -> [u8; 32] {
+    blake2b_256(&essence.pack_new())
+}
+
+/// Validates a milestone payload against the coordinator keys: it has an index and a timestamp, at least
+/// `signature_count` signatures, one per public key of the essence, each public key signs the index of the milestone
+/// and each signature signs the essence hash, see [`milestone_essence_hash`]. Fails with [`Error::InvalidMilestone`]
+/// giving the reason.
+pub fn validate_milestone(milestone: &MilestonePayload, keys: &CoordinatorKeys) -> Result<()> {
+    let essence = milestone.essence();
+    let index = essence.index();
+    let invalid = |reason: String| Error::InvalidMilestone(index, reason);
+    if index == 0 {
+        return Err(invalid("the index is 0".to_string()));
+    }
+    if essence.timestamp() == 0 {
+        return Err(invalid("the timestamp is 0".to_string()));
+    }
+    let public_keys = essence.public_keys();
+    let signatures = milestone.signatures();
+    if public_keys.len() != signatures.len() {
+        return Err(invalid(format!(
+            "{} public keys for {} signatures",
+            public_keys.len(),
+            signatures.len()
+        )));
+    }
+    if signatures.len() < keys.signature_count {
+        return Err(invalid(format!(
+            "{} signatures, the coordinator signs with {} keys",
+            signatures.len(),
+            keys.signature_count
+        )));
+    }
+    let applicable_public_keys = keys.applicable_public_keys(index)?;
+    let mut signing_public_keys = HashSet::new();
+    let essence_hash = milestone_essence_hash(essence);
+    for (public_key, signature) in public_keys.iter().zip(signatures) {
+        if !applicable_public_keys.contains(public_key) {
+            return Err(invalid(format!(
+                "the public key {} doesn't sign the milestone {}",
+                hex::encode(public_key),
+                index
+            )));
+        }
+        if !signing_public_keys.insert(*public_key) {
+            return Err(invalid(format!(
+                "the public key {} signs twice",
+                hex::encode(public_key)
+            )));
+        }
+        if !verify_ed25519_signature(public_key, signature, &essence_hash).map_err(|e| invalid(e.to_string()))? {
+            return Err(invalid(format!(
+                "the signature of the public key {} doesn't sign the essence",
+                hex::encode(public_key)
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Validates the packed milestone message the node answered for the milestone metadata: its message id is the id of
+/// the metadata, it holds a valid milestone payload of the same index and timestamp, see [`validate_milestone`].
+pub fn validate_milestone_message(bytes: &[u8], metadata: &MilestoneMetadata, keys: &CoordinatorKeys) -> Result<()> {
+    let index = metadata.index as u32;
+    let invalid = |reason: String| Error::InvalidMilestone(index, reason);
+    let message_id = compute_message_id(bytes);
+    if message_id.to_string() != metadata.message_id {
+        return Err(invalid(format!(
+            "the message {} isn't the milestone message {}",
+            message_id, metadata.message_id
+        )));
+    }
+    let message =
+        Message::unpack(&mut &bytes[..]).map_err(|e| invalid(format!("invalid milestone message: {:?}", e)))?;
+    let milestone = match message.payload() {
+        Some(Payload::Milestone(milestone)) => milestone,
+        _ => return Err(invalid("the message doesn't hold a milestone".to_string())),
+    };
+    let essence = milestone.essence();
+    if u64::from(essence.index()) != metadata.index || essence.timestamp() != metadata.timestamp {
+        return Err(invalid(format!(
+            "the milestone has the index {} and the timestamp {}, the node declares the index {} and the timestamp {}",
+            essence.index(),
+            essence.timestamp(),
+            metadata.index,
+            metadata.timestamp
+        )));
+    }
+    validate_milestone(milestone, keys)
+}
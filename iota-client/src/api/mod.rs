@@ -4,11 +4,64 @@
 //! High level APIs
 
 mod address;
+#[cfg(feature = "client")]
+mod address_monitor;
+#[cfg(feature = "client")]
+mod address_scan;
+#[cfg(feature = "client")]
 mod balance;
+#[cfg(feature = "client")]
+mod batch;
+#[cfg(feature = "client")]
+mod confirmation;
+#[cfg(feature = "client")]
+mod consolidation;
+#[cfg(feature = "client")]
+mod data_message;
+#[cfg(feature = "client")]
+mod find_messages;
+#[cfg(feature = "client")]
+mod history;
+#[cfg(feature = "client")]
+mod inclusion_proof;
+#[cfg(feature = "client")]
+mod input_selection;
+#[cfg(feature = "client")]
+mod milestone_validation;
+#[cfg(feature = "client")]
 mod send;
+#[cfg(feature = "client")]
 mod unspent;
 
 pub use address::*;
+#[cfg(feature = "client")]
+pub use address_monitor::*;
+#[cfg(feature = "client")]
+pub(crate) use address_scan::*;
+#[cfg(feature = "client")]
 pub use balance::*;
+#[cfg(feature = "client")]
+pub use batch::*;
+#[cfg(feature = "client")]
+pub use confirmation::*;
+#[cfg(feature = "client")]
+pub use consolidation::*;
+#[cfg(feature = "client")]
+pub use data_message::*;
+#[cfg(feature = "client")]
+pub use find_messages::*;
+#[cfg(feature = "client")]
+pub use history::*;
+#[cfg(feature = "client")]
+pub use inclusion_proof::*;
+#[cfg(feature = "client")]
+pub use input_selection::*;
+#[cfg(feature = "client")]
+pub use milestone_validation::*;
+#[cfg(feature = "client")]
 pub use send::*;
+#[cfg(feature = "client")]
 pub use unspent::*;
+
+/// The total supply of IOTA tokens, the ceiling of every amount, balance and sum of amounts.
+pub const IOTA_SUPPLY: u64 = 2_779_530_283_277_761;
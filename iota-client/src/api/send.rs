@@ -1,40 +1,585 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{api::address::search_address, types::Bech32Address, Client, ClientMiner, Error, Result};
+use crate::{
+    api::{
+        address::{public_key_address, search_address},
+        select_inputs, AddressScan, InputCandidate, InputSelectionStrategy, ScanAddresses, IOTA_SUPPLY,
+    },
+    types::Bech32Address,
+    AddressOutputs, Client, Error, OutputType, OutputsOptions, Result, SignerType,
+};
 
 use bee_common::packable::Packable;
 use bee_message::prelude::*;
+use bee_pow::providers::{Constant, ConstantBuilder, ProviderBuilder as PowProviderBuilder};
 use bee_signing_ext::{
-    binary::{BIP32Path, Ed25519PrivateKey},
-    Seed, Signer,
+    binary::{Ed25519PublicKey, Ed25519Signature as Ed25519EssenceSignature},
+    Seed, Verifier,
 };
-use std::{collections::HashMap, convert::TryInto};
-
-const HARDEND: u32 = 1 << 31;
-const TRANSACTION_ID_LENGTH: usize = 32;
-
-/// Structure for sorting of UnlockBlocks
-// TODO: move the sorting process to the `Message` crate
-struct AddressIndexRecorder {
-    input: Input,
-    address_index: usize,
-    address_path: BIP32Path,
-    internal: bool,
+use futures::{StreamExt, TryStreamExt};
+use std::ops::RangeInclusive;
+
+/// The minimum amount of an output, smaller outputs are dust.
+pub const DUST_THRESHOLD: u64 = 1_000_000;
+/// The dust allowance amount allowing one dust output on an address.
+pub const DUST_ALLOWANCE_DIVISOR: u64 = 100_000;
+/// The maximum number of dust outputs an address can hold, whatever its dust allowance.
+pub const DUST_OUTPUTS_MAX: usize = 100;
+/// The maximum number of outputs of a transaction, the remainder included.
+pub const OUTPUT_COUNT_MAX: usize = 127;
+/// The length range of an indexation index, in bytes.
+pub const INDEXATION_INDEX_LENGTH_RANGE: RangeInclusive<usize> = 1..=64;
+/// The maximum length of a message, in bytes, which bounds the indexation data.
+pub const MESSAGE_LENGTH_MAX: usize = 32768;
+/// The number of custom parents of a message, the message has the parent1 and parent2 fields and a single parent
+/// fills both.
+pub const MESSAGE_PARENTS_RANGE: RangeInclusive<usize> = 1..=2;
+
+/// Sums the amounts, failing with [`Error::ValueOverflow`] if the sum exceeds [`IOTA_SUPPLY`].
+pub(crate) fn checked_amount_sum<I: IntoIterator<Item = u64>>(amounts: I) -> Result<u64> {
+    // a u128 sum of u64 amounts can't overflow
+    let sum: u128 = amounts.into_iter().map(u128::from).sum();
+    if sum > u128::from(IOTA_SUPPLY) {
+        return Err(Error::ValueOverflow(sum));
+    }
+    Ok(sum as u64)
+}
+
+/// Checks an amount of a node answer, failing with [`Error::ValueOverflow`] if it exceeds [`IOTA_SUPPLY`].
+pub(crate) fn checked_amount(amount: u64) -> Result<u64> {
+    checked_amount_sum(std::iter::once(amount))
+}
+
+/// An address of the spending account with its derivation indexes.
+/// Lets a client without the seed select the inputs of a transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountAddress {
+    /// The address.
+    pub address: Address,
+    /// The index of the address in the account.
+    pub address_index: usize,
+    /// Whether the address is an internal (remainder) address.
+    pub internal: bool,
+}
+
+/// The address receiving the remainder of a transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemainderStrategy {
+    /// Sends the remainder to the first internal address of the account without any output, from the initial address
+    /// index. With the account addresses, it's the first of their internal addresses without any output.
+    ChangeAddress,
+    /// Sends the remainder back to the address of the first input.
+    ReuseFirstInputAddress,
+    /// Sends the remainder to the address, which must use the bech32 HRP of the client network.
+    Custom(Bech32Address),
+}
+
+impl Default for RemainderStrategy {
+    fn default() -> Self {
+        Self::ChangeAddress
+    }
+}
+
+/// The remainder output of a prepared transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemainderData {
+    /// The remainder address.
+    pub address: Address,
+    /// The remainder amount, the output also holds the amount sent to the address if there is one.
+    pub amount: u64,
+    /// The index of the remainder output in the essence outputs.
+    pub output_index: usize,
+    /// The index of the address in the account, unknown for a custom remainder address.
+    pub address_index: Option<usize>,
+    /// Whether the address is an internal (remainder) address.
+    pub internal: bool,
+}
+
+/// An input of a prepared transaction with the derivation indexes of its address, needed to sign it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputSigningData {
+    /// The input.
+    pub input: UTXOInput,
+    /// The address of the consumed output.
+    pub address: Address,
+    /// The index of the address in the account.
+    pub address_index: usize,
+    /// Whether the address is an internal (remainder) address.
+    pub internal: bool,
+}
+
+/// A transaction with its selected inputs and remainder, ready to be signed offline.
+#[derive(Debug, Clone)]
+pub struct PreparedTransactionData {
+    /// The unsigned transaction essence.
+    pub essence: TransactionPayloadEssence,
+    /// The account index of the inputs.
+    pub account_index: usize,
+    /// The signing data of the essence inputs.
+    pub inputs: Vec<InputSigningData>,
+    /// The remainder output, if the inputs hold more than the outputs need.
+    pub remainder: Option<RemainderData>,
+}
+
+impl PreparedTransactionData {
+    /// Gets the packed transaction essence, the message signed by the input addresses.
+    pub fn essence_bytes(&self) -> Result<Vec<u8>> {
+        let mut serialized_essence = Vec::new();
+        self.essence
+            .pack(&mut serialized_essence)
+            .map_err(|_| Error::InvalidParameter("inputs".to_string()))?;
+        Ok(serialized_essence)
+    }
+
+    /// Gets the signing data of the addresses that must sign the essence, in the order of their signature unlock
+    /// blocks. An address unlocking several inputs only signs once.
+    pub fn signers(&self) -> Result<Vec<&InputSigningData>> {
+        let mut signers: Vec<&InputSigningData> = Vec::new();
+        for input in self.essence.inputs() {
+            let signing_data = self.signing_data(input)?;
+            if !signers.iter().any(|signer| signer.address == signing_data.address) {
+                signers.push(signing_data);
+            }
+        }
+        Ok(signers)
+    }
+
+    /// Signs the transaction with the seed or the signer of the account, no node is needed.
+    /// The addresses derived by the signer must be the input addresses.
+    pub fn sign<'a, S: Into<SignerType<'a>>>(&self, signer: S) -> Result<TransactionPayload> {
+        let account_signer = signer.into();
+        // The device signs the whole essence at once, confirmed by the user
+        #[cfg(feature = "ledger")]
+        if let SignerType::LedgerNano(ledger) = account_signer {
+            return self.with_signatures(ledger.sign_essence(self)?);
+        }
+        let serialized_essence = self.essence_bytes()?;
+
+        let mut signatures = Vec::new();
+        for signer in self.signers()? {
+            let address = account_signer.generate_address(self.account_index, signer.address_index, signer.internal)?;
+            if address != signer.address {
+                return Err(Error::SigningError(format!(
+                    "the seed doesn't derive the address of the input {}",
+                    signer.input.output_id()
+                )));
+            }
+            // The block should sign the entire transaction essence part of the transaction payload
+            signatures.push(account_signer.sign(
+                self.account_index,
+                signer.address_index,
+                signer.internal,
+                &serialized_essence,
+            )?);
+        }
+
+        self.with_signatures(signatures)
+    }
+
+    /// Builds the transaction payload from the essence signatures of an external signer, one for each of the
+    /// [`signers`](Self::signers) in the same order. The signatures are verified against the input addresses and the
+    /// essence, and the inputs of an address that already signed are unlocked with a reference.
+    pub fn with_signatures(&self, signatures: Vec<Ed25519Signature>) -> Result<TransactionPayload> {
+        let serialized_essence = self.essence_bytes()?;
+        let signers = self.signers()?;
+        if signatures.len() != signers.len() {
+            return Err(Error::SigningError(format!(
+                "{} signatures for {} signing addresses",
+                signatures.len(),
+                signers.len()
+            )));
+        }
+        for (index, (signer, signature)) in signers.iter().zip(signatures.iter()).enumerate() {
+            if public_key_address(signature.public_key()) != signer.address {
+                return Err(Error::SigningError(format!(
+                    "the public key of the signature {} isn't the key of the input {} address",
+                    index,
+                    signer.input.output_id()
+                )));
+            }
+            let public_key = Ed25519PublicKey::from_bytes(signature.public_key())
+                .map_err(|_| Error::SigningError(format!("invalid public key of the signature {}", index)))?;
+            let essence_signature = Ed25519EssenceSignature::from_bytes(signature.signature())
+                .map_err(|_| Error::SigningError(format!("invalid signature {}", index)))?;
+            public_key
+                .verify(&serialized_essence, &essence_signature)
+                .map_err(|_| Error::SigningError(format!("the signature {} doesn't sign the essence", index)))?;
+        }
+        self.payload_with_signatures(signatures)
+    }
+
+    // Builds the transaction payload from the signatures of the signers without verifying them
+    fn payload_with_signatures(&self, signatures: Vec<Ed25519Signature>) -> Result<TransactionPayload> {
+        let mut unlock_blocks = Vec::new();
+        let mut signature_indexes: Vec<(&Address, usize)> = Vec::new();
+        // The unlock blocks follow the order of the essence inputs
+        for input in self.essence.inputs() {
+            let address = &self.signing_data(input)?.address;
+            // An input of an address that already has a signature unlock block is unlocked with a reference
+            if let Some((_, block_index)) = signature_indexes.iter().find(|(signed, _)| *signed == address) {
+                unlock_blocks.push(UnlockBlock::Reference(ReferenceUnlock::new(*block_index as u16)?));
+                continue;
+            }
+            let signature = signatures[signature_indexes.len()].clone();
+            signature_indexes.push((address, unlock_blocks.len()));
+            unlock_blocks.push(UnlockBlock::Signature(SignatureUnlock::Ed25519(signature)));
+        }
+
+        // TODO overflow check
+        let mut payload_builder = TransactionPayloadBuilder::new().with_essence(self.essence.clone());
+        for unlock in unlock_blocks {
+            payload_builder = payload_builder.add_unlock_block(unlock);
+        }
+        payload_builder.finish().map_err(|_| Error::TransactionError)
+    }
+
+    // Finds the signing data of an essence input
+    pub(crate) fn signing_data(&self, input: &Input) -> Result<&InputSigningData> {
+        let input = match input {
+            Input::UTXO(input) => input,
+            _ => return Err(Error::SigningError("only UTXO inputs can be signed".to_string())),
+        };
+        self.inputs
+            .iter()
+            .find(|signing_data| signing_data.input == *input)
+            .ok_or_else(|| Error::SigningError(format!("missing the signing data of the input {}", input.output_id())))
+    }
+}
+
+/// A dust output of a planned transaction, the node only accepts it if its address allows one more dust output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DustWarning {
+    /// The address of the dust output.
+    pub address: Bech32Address,
+    /// The amount of the dust output, below [`DUST_THRESHOLD`].
+    pub amount: u64,
+    /// Whether the dust output is the remainder output.
+    pub remainder: bool,
+}
+
+/// The plan of a transaction built by [`SendBuilder::dry_run`] without any PoW or submit: its selected inputs, its
+/// remainder and its essence. The plan is submitted as is with [`SendBuilder::finish_plan`].
+#[derive(Debug, Clone)]
+pub struct TransactionPlan {
+    /// The prepared transaction, with the essence, the signing data of the inputs and the remainder output.
+    pub prepared: PreparedTransactionData,
+    /// The signed transaction payload, if the dry run signed the essence.
+    pub payload: Option<TransactionPayload>,
+    /// The length of the packed message of the transaction, in bytes. The unlock blocks of an unsigned plan are
+    /// counted with the length of their signatures, so the length is the length of the submitted message.
+    pub message_length: usize,
+    /// The dust outputs of the transaction, in the order of the essence outputs. Unless the dust check of the builder
+    /// is disabled, their addresses were checked to allow them.
+    pub dust_warnings: Vec<DustWarning>,
+}
+
+impl TransactionPlan {
+    fn new(client: &Client, prepared: PreparedTransactionData, payload: Option<TransactionPayload>) -> Result<Self> {
+        let remainder_index = prepared.remainder.as_ref().map(|remainder| remainder.output_index);
+        let mut dust_warnings = Vec::new();
+        for (index, output) in prepared.essence.outputs().iter().enumerate() {
+            if let Output::SignatureLockedSingle(output) = output {
+                if output.amount() < DUST_THRESHOLD {
+                    dust_warnings.push(DustWarning {
+                        address: client.address_to_bech32(output.address())?,
+                        amount: output.amount(),
+                        remainder: remainder_index == Some(index),
+                    });
+                }
+            }
+        }
+        // An unsigned essence gets placeholder signatures of the same length
+        let transaction = match &payload {
+            Some(payload) => payload.clone(),
+            None => {
+                let signatures = prepared
+                    .signers()?
+                    .iter()
+                    .map(|_| Ed25519Signature::new([0u8; 32], Box::new([0u8; 64])))
+                    .collect();
+                prepared.payload_with_signatures(signatures)?
+            }
+        };
+        // The network id, the parents and the nonce have a fixed length
+        let message = MessageBuilder::<Constant>::new()
+            .with_network_id(0)
+            .with_parent1(MessageId::new([0u8; 32]))
+            .with_parent2(MessageId::new([0u8; 32]))
+            .with_payload(Payload::Transaction(Box::new(transaction)))
+            .with_nonce_provider(ConstantBuilder::new().with_value(0).finish(), 0f64)
+            .finish()
+            .map_err(Error::MessageError)?;
+        Ok(Self {
+            prepared,
+            payload,
+            message_length: message.packed_len(),
+            dust_warnings,
+        })
+    }
+}
+
+// Sorts the inputs in the protocol order, the lexicographic order of their serialized form. The unlock blocks follow
+// the order of the essence inputs, so they get sorted along.
+pub(crate) fn sort_inputs(inputs: Vec<InputSigningData>) -> Result<Vec<InputSigningData>> {
+    let mut inputs = inputs
+        .into_iter()
+        .map(|input| {
+            let mut serialized_input = Vec::new();
+            Input::UTXO(input.input.clone())
+                .pack(&mut serialized_input)
+                .map_err(|_| Error::TransactionError)?;
+            Ok((serialized_input, input))
+        })
+        .collect::<Result<Vec<(Vec<u8>, InputSigningData)>>>()?;
+    inputs.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(inputs.into_iter().map(|(_, input)| input).collect())
+}
+
+// Gets the number of dust outputs the address can still receive. Following the dust protection of the node, an
+// address with dust allowance outputs can hold one dust output per DUST_ALLOWANCE_DIVISOR of allowance, up to
+// DUST_OUTPUTS_MAX. The new allowance is the amount of the dust allowance outputs the transaction sends to the address.
+async fn available_dust_outputs(client: &Client, address: &Address, new_allowance: u64) -> Result<usize> {
+    let (allowance, dust_outputs) = dust_state(client, address).await?;
+    Ok(allowed_dust_outputs(allowance + new_allowance).saturating_sub(dust_outputs))
+}
+
+// The number of dust outputs an allowance allows
+fn allowed_dust_outputs(allowance: u64) -> usize {
+    ((allowance / DUST_ALLOWANCE_DIVISOR) as usize).min(DUST_OUTPUTS_MAX)
+}
+
+// Gets the dust allowance of the address, the sum of its unspent dust allowance outputs, and its number of unspent
+// dust outputs.
+async fn dust_state(client: &Client, address: &Address) -> Result<(u64, usize)> {
+    let address = client.address_to_bech32(address)?;
+    let mut allowance = 0;
+    let mut dust_outputs = 0;
+    if !client.get_address().balance_response(&address).await?.dust_allowed {
+        return Ok((allowance, dust_outputs));
+    }
+    for output_type in &[
+        OutputType::SignatureLockedDustAllowance,
+        OutputType::SignatureLockedSingle,
+    ] {
+        let options = OutputsOptions {
+            include_spent: false,
+            output_type: Some(*output_type),
+        };
+        let output_ids = client
+            .get_address()
+            .outputs_response(&address, options)
+            .await?
+            .utxo_inputs()?;
+        for output_id in output_ids.iter() {
+            let output = client.get_output_of_type(output_id, *output_type).await?;
+            if output.is_spent {
+                continue;
+            }
+            match output_type {
+                OutputType::SignatureLockedDustAllowance => {
+                    allowance = checked_amount_sum(vec![allowance, output.amount])?
+                }
+                OutputType::SignatureLockedSingle if output.amount < DUST_THRESHOLD => dust_outputs += 1,
+                OutputType::SignatureLockedSingle => {}
+            }
+        }
+    }
+    Ok((allowance, dust_outputs))
+}
+
+// Checks the dust allowance outputs selected as inputs leave enough allowance on their address for the dust outputs
+// it keeps, counting the dust outputs and the allowance the transaction spends from and sends to the address.
+pub(crate) async fn check_dust_allowance_inputs(
+    client: &Client,
+    selected: &[InputCandidate],
+    transfers: &[TransferOutput],
+) -> Result<()> {
+    let mut addresses: Vec<&Address> = Vec::new();
+    for candidate in selected {
+        let address = &candidate.signing_data.address;
+        if candidate.output_type == OutputType::SignatureLockedDustAllowance && !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+    for address in addresses {
+        let (allowance, dust_outputs) = dust_state(client, address).await?;
+        let inputs = selected
+            .iter()
+            .filter(|candidate| candidate.signing_data.address == *address);
+        let spent_allowance: u64 = inputs
+            .clone()
+            .filter(|candidate| candidate.output_type == OutputType::SignatureLockedDustAllowance)
+            .map(|candidate| candidate.amount)
+            .sum();
+        let spent_dust_outputs = inputs
+            .filter(|candidate| {
+                candidate.output_type == OutputType::SignatureLockedSingle && candidate.amount < DUST_THRESHOLD
+            })
+            .count();
+        let new_dust_outputs = transfers
+            .iter()
+            .filter(|transfer| transfer.is_dust() && transfer.address == *address)
+            .count();
+        let remaining_allowance = allowance.saturating_sub(spent_allowance) + new_dust_allowance(transfers, address);
+        let remaining_dust_outputs = dust_outputs.saturating_sub(spent_dust_outputs) + new_dust_outputs;
+        let allowed = allowed_dust_outputs(remaining_allowance);
+        if remaining_dust_outputs > allowed {
+            return Err(Error::DustAllowanceInput(
+                client.address_to_bech32(address)?.0,
+                remaining_dust_outputs,
+                allowed,
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Gets the input addresses that already spent outputs, in the order of the inputs. Every address is only checked
+// once, the addresses are checked concurrently and the first failed check fails without waiting for the others.
+async fn reused_addresses(client: &Client, inputs: &[InputSigningData]) -> Result<Vec<Bech32Address>> {
+    let mut addresses = Vec::new();
+    for input in inputs {
+        let address = client.address_to_bech32(&input.address)?;
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+    let spent: Vec<bool> = futures::stream::iter(&addresses)
+        .map(|address| spent_from(client, address))
+        .buffered(client.request_parallelism)
+        .try_collect()
+        .await?;
+    Ok(addresses
+        .into_iter()
+        .zip(spent)
+        .filter(|(_, spent)| *spent)
+        .map(|(address, _)| address)
+        .collect())
+}
+
+// Checks if the address spent an output, see `address_outputs`. A stored spent address isn't queried.
+async fn spent_from(client: &Client, address: &Bech32Address) -> Result<bool> {
+    if client.is_stored_spent(address).await? {
+        return Ok(true);
+    }
+    Ok(address_outputs(client, address).await?.1)
+}
+
+// The outputs of the address, the spent ones included, and whether it spent an output: one of its outputs isn't
+// unspent. A spent address is stored.
+pub(crate) async fn address_outputs(client: &Client, address: &Bech32Address) -> Result<(AddressOutputs, bool)> {
+    let outputs = |include_spent| {
+        client.get_address().outputs_response(
+            address,
+            OutputsOptions {
+                include_spent,
+                output_type: None,
+            },
+        )
+    };
+    let (all_outputs, unspent_outputs) = futures::future::try_join(outputs(true), outputs(false)).await?;
+    let spent = all_outputs
+        .output_ids
+        .iter()
+        .any(|output_id| !unspent_outputs.output_ids.contains(output_id));
+    if spent {
+        client.store_spent(address).await;
+    }
+    Ok((all_outputs, spent))
+}
+
+// A value output of the transaction
+pub(crate) struct TransferOutput {
+    address: Address,
+    amount: u64,
+    output_type: OutputType,
+}
+
+impl TransferOutput {
+    pub(crate) fn new(output: &Output) -> Result<Self> {
+        let (address, amount, output_type) = match output {
+            Output::SignatureLockedSingle(x) => (x.address(), x.amount(), OutputType::SignatureLockedSingle),
+            Output::SignatureLockedDustAllowance(x) => {
+                (x.address(), x.amount(), OutputType::SignatureLockedDustAllowance)
+            }
+            _ => return Err(Error::TransactionError),
+        };
+        Ok(Self {
+            address: address.clone(),
+            amount,
+            output_type,
+        })
+    }
+
+    fn is_dust(&self) -> bool {
+        self.output_type == OutputType::SignatureLockedSingle && self.amount < DUST_THRESHOLD
+    }
+
+    fn to_output(&self) -> Result<Output> {
+        Ok(match self.output_type {
+            OutputType::SignatureLockedSingle => SignatureLockedSingleOutput::new(self.address.clone(), self.amount)
+                .map_err(|_| Error::TransactionError)?
+                .into(),
+            OutputType::SignatureLockedDustAllowance => {
+                SignatureLockedDustAllowanceOutput::new(self.address.clone(), self.amount)
+                    .map_err(|_| Error::TransactionError)?
+                    .into()
+            }
+        })
+    }
+}
+
+// Merges the outputs of the same type to the same address, summing their amounts. Fails with `Error::ValueOverflow`
+// if the outputs total exceeds the supply, the merged amounts are then within the supply too.
+fn merge_outputs(outputs: &[Output]) -> Result<Vec<TransferOutput>> {
+    let outputs = outputs.iter().map(TransferOutput::new).collect::<Result<Vec<_>>>()?;
+    checked_amount_sum(outputs.iter().map(|output| output.amount))?;
+    let mut transfers: Vec<TransferOutput> = Vec::new();
+    for output in outputs {
+        match transfers
+            .iter_mut()
+            .find(|transfer| transfer.address == output.address && transfer.output_type == output.output_type)
+        {
+            Some(transfer) => transfer.amount += output.amount,
+            None => transfers.push(output),
+        }
+    }
+    Ok(transfers)
+}
+
+// Gets the amount of the dust allowance outputs the transaction sends to the address
+fn new_dust_allowance(transfers: &[TransferOutput], address: &Address) -> u64 {
+    transfers
+        .iter()
+        .filter(|transfer| {
+            transfer.output_type == OutputType::SignatureLockedDustAllowance && transfer.address == *address
+        })
+        .map(|transfer| transfer.amount)
+        .sum()
 }
 
 /// Builder of send API
 pub struct SendBuilder<'a> {
     client: &'a Client,
-    seed: Option<&'a Seed>,
+    signer: Option<SignerType<'a>>,
     account_index: Option<usize>,
     initial_address_index: Option<usize>,
-    inputs: Option<Vec<UTXOInput>>,
+    input_selection: InputSelectionStrategy,
+    include_dust_allowance: bool,
+    remainder_strategy: RemainderStrategy,
+    dust_remainder_to_first_output: bool,
     outputs: Vec<Output>,
     index: Option<String>,
     data: Option<Vec<u8>>,
     parent: Option<MessageId>,
+    parents: Option<Vec<MessageId>>,
+    parents_check: bool,
     network_id: Option<u64>,
+    account_addresses: Option<Vec<AccountAddress>>,
+    input_candidates: Option<Vec<InputCandidate>>,
+    dust_check: bool,
+    address_reuse_allowed: bool,
 }
 
 impl<'a> SendBuilder<'a> {
@@ -42,21 +587,36 @@ impl<'a> SendBuilder<'a> {
     pub fn new(client: &'a Client) -> Self {
         Self {
             client,
-            seed: None,
+            signer: None,
             account_index: None,
             initial_address_index: None,
-            inputs: None,
+            input_selection: InputSelectionStrategy::default(),
+            include_dust_allowance: false,
+            remainder_strategy: RemainderStrategy::default(),
+            dust_remainder_to_first_output: false,
             outputs: Vec::new(),
             index: None,
             data: None,
             parent: None,
+            parents: None,
+            parents_check: true,
             network_id: None,
+            account_addresses: None,
+            input_candidates: None,
+            dust_check: true,
+            address_reuse_allowed: false,
         }
     }
 
     /// Sets the seed.
     pub fn with_seed(mut self, seed: &'a Seed) -> Self {
-        self.seed = Some(seed);
+        self.signer = Some(seed.into());
+        self
+    }
+
+    /// Sets the signer deriving the addresses and signing the transaction, instead of the seed.
+    pub fn with_signer<S: Into<SignerType<'a>>>(mut self, signer: S) -> Self {
+        self.signer = Some(signer.into());
         self
     }
 
@@ -72,36 +632,95 @@ impl<'a> SendBuilder<'a> {
         self
     }
 
-    /// Set a custom input(transaction output)
+    /// Sets the account addresses to select the inputs from, the seed isn't needed to prepare the transaction.
+    pub fn with_account_addresses(mut self, account_addresses: Vec<AccountAddress>) -> Self {
+        self.account_addresses = Some(account_addresses);
+        self
+    }
+
+    /// Sets the unspent outputs of the account to select the inputs from instead of querying the node, as exported by
+    /// [`get_input_candidates`](Self::get_input_candidates). Neither the seed nor a node is needed to prepare the
+    /// transaction, so an offline client can prepare it with a remainder strategy that doesn't look for an unused
+    /// address and the dust check disabled.
+    pub fn with_input_candidates(mut self, candidates: Vec<InputCandidate>) -> Self {
+        self.input_candidates = Some(candidates);
+        self
+    }
+
+    /// Set a custom input(transaction output), the inputs are then selected with
+    /// [`InputSelectionStrategy::Manual`].
     pub fn with_input(mut self, input: UTXOInput) -> Self {
-        self.inputs = match self.inputs {
-            Some(mut inputs) => {
+        self.input_selection = match self.input_selection {
+            InputSelectionStrategy::Manual(mut inputs) => {
                 inputs.push(input);
-                Some(inputs)
+                InputSelectionStrategy::Manual(inputs)
             }
-            None => Some(vec![input]),
+            _ => InputSelectionStrategy::Manual(vec![input]),
         };
         self
     }
 
-    /// Set a transfer to the builder, address needs to be Bech32 encoded
+    /// Sets the strategy selecting the inputs among the unspent outputs of the account,
+    /// [`InputSelectionStrategy::LargestFirst`] by default.
+    pub fn with_input_selection_strategy(mut self, strategy: InputSelectionStrategy) -> Self {
+        self.input_selection = strategy;
+        self
+    }
+
+    /// Also selects the dust allowance outputs of the account as inputs, which are skipped by default since spending
+    /// them can leave the dust outputs of their address without allowance. With the dust check, a transaction
+    /// spending the allowance that the dust outputs kept on its address need fails with
+    /// [`Error::DustAllowanceInput`], see [`SendBuilder::with_dust_check_disabled`].
+    pub fn with_dust_allowance_inputs(mut self, include_dust_allowance: bool) -> Self {
+        self.include_dust_allowance = include_dust_allowance;
+        self
+    }
+
+    /// Sets the address receiving the remainder, [`RemainderStrategy::ChangeAddress`] by default.
+    pub fn with_remainder_strategy(mut self, strategy: RemainderStrategy) -> Self {
+        self.remainder_strategy = strategy;
+        self
+    }
+
+    /// Adds a remainder below [`DUST_THRESHOLD`] to the first output of the builder instead of sending it to the
+    /// remainder address, so the transaction never fails with [`Error::DustRemainder`]. The first output then receives
+    /// more than its amount.
+    pub fn with_dust_remainder_to_first_output(mut self, dust_remainder_to_first_output: bool) -> Self {
+        self.dust_remainder_to_first_output = dust_remainder_to_first_output;
+        self
+    }
+
+    /// Set a transfer to the builder, address needs to be Bech32 encoded with the HRP of the client network
     pub fn with_output(mut self, address: &Bech32Address, amount: u64) -> Result<Self> {
-        let address = Address::try_from_bech32(&address.to_string())?;
-        let output = SignatureLockedSingleOutput::new(address, amount).unwrap().into();
+        checked_amount(amount)?;
+        let address = self.client.parse_bech32_address(address)?;
+        let output = SignatureLockedSingleOutput::new(address, amount)?.into();
+        self.outputs.push(output);
+        Ok(self)
+    }
+
+    /// Set a dust allowance output to the builder, address needs to be Bech32 encoded.
+    /// The address can then hold one dust output per [`DUST_ALLOWANCE_DIVISOR`] of allowance.
+    pub fn with_dust_allowance_output(mut self, address: &Bech32Address, amount: u64) -> Result<Self> {
+        if amount < DUST_THRESHOLD {
+            return Err(Error::DustError(address.to_string(), amount, DUST_THRESHOLD));
+        }
+        checked_amount(amount)?;
+        let address = self.client.parse_bech32_address(address)?;
+        let output = SignatureLockedDustAllowanceOutput::new(address, amount)?.into();
         self.outputs.push(output);
         Ok(self)
     }
 
     /// Set a transfer to the builder, address needs to be hex encoded
     pub fn with_output_hex(mut self, address: &str, amount: u64) -> Result<Self> {
-        let output = SignatureLockedSingleOutput::new(address.parse::<Ed25519Address>()?.into(), amount)
-            .unwrap()
-            .into();
+        checked_amount(amount)?;
+        let output = SignatureLockedSingleOutput::new(address.parse::<Ed25519Address>()?.into(), amount)?.into();
         self.outputs.push(output);
         Ok(self)
     }
 
-    /// Set indexation string to the builder
+    /// Set indexation string to the builder, with outputs the indexation payload is nested in the transaction essence
     pub fn with_index(mut self, index: &str) -> Self {
         self.index = Some(index.to_string());
         self
@@ -119,311 +738,661 @@ impl<'a> SendBuilder<'a> {
         self
     }
 
+    /// Sets the parents of the message instead of the tips of the node, the custom [`parent`](Self::with_parent) is
+    /// then ignored. The distinct parents are sorted in the protocol order, the lexicographic order of their bytes,
+    /// and the node must know them, which is checked before the PoW unless
+    /// [`with_parents_check_disabled`](Self::with_parents_check_disabled) is set.
+    pub fn with_parents(mut self, mut parents: Vec<MessageId>) -> Result<Self> {
+        if !MESSAGE_PARENTS_RANGE.contains(&parents.len()) {
+            return Err(Error::InvalidParameter(format!(
+                "{} parents, expected between {} and {}",
+                parents.len(),
+                MESSAGE_PARENTS_RANGE.start(),
+                MESSAGE_PARENTS_RANGE.end()
+            )));
+        }
+        parents.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+        if let Some(parent) = parents.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(Error::InvalidParameter(format!("duplicate parent {}", parent[0])));
+        }
+        self.parents = Some(parents);
+        Ok(self)
+    }
+
+    /// Skips checking that the node knows the custom parents, a message with unknown parents is then only refused by
+    /// the node.
+    pub fn with_parents_check_disabled(mut self) -> Self {
+        self.parents_check = false;
+        self
+    }
+
     /// Set the network id
     pub fn with_network_id(mut self, network_id: u64) -> Self {
         self.network_id = Some(network_id);
         self
     }
 
+    /// Disables the dust protection check of the outputs below [`DUST_THRESHOLD`], they are then only validated by
+    /// the node after the PoW is done.
+    pub fn with_dust_check_disabled(mut self) -> Self {
+        self.dust_check = false;
+        self
+    }
+
+    /// Allows the inputs to spend again from addresses that already spent outputs, overriding the address reuse check
+    /// of the client, see [`ClientBuilder::with_address_reuse_check`](crate::ClientBuilder::with_address_reuse_check).
+    pub fn with_address_reuse_allowed(mut self, address_reuse_allowed: bool) -> Self {
+        self.address_reuse_allowed = address_reuse_allowed;
+        self
+    }
+
     /// Consume the builder and get the API result
     pub async fn finish(self) -> Result<MessageId> {
+        Ok(self.finish_with_message().await?.0)
+    }
+
+    /// Consume the builder and get the id of the submitted message along with the message
+    pub async fn finish_with_message(self) -> Result<(MessageId, Message)> {
+        let (message_id, message, _) = self.finish_with_remainder().await?;
+        Ok((message_id, message))
+    }
+
+    /// Consume the builder and get the message id, the message and the remainder output of its transaction, if it
+    /// has one.
+    pub async fn finish_with_remainder(self) -> Result<(MessageId, Message, Option<RemainderData>)> {
         // Indexation payload requires an indexation tag
         if self.data.is_some() && self.index.is_none() {
             return Err(Error::MissingParameter(String::from("index")));
         }
-        if self.inputs.is_some() && self.outputs.is_empty() {
+        if matches!(self.input_selection, InputSelectionStrategy::Manual(_)) && self.outputs.is_empty() {
             return Err(Error::MissingParameter(String::from("output")));
         }
-        if !self.outputs.is_empty() {
-            if self.seed.is_none() {
+        let (payload, remainder) = if !self.outputs.is_empty() {
+            if self.signer.is_none() {
                 return Err(Error::MissingParameter(String::from("Seed")));
             }
             // Send message with transaction
-            self.finish_transaction().await
+            let (payload, remainder) = self.transaction_payload().await?;
+            (Some(payload), remainder)
         } else if self.index.is_some() {
             // Send message with indexation payload
-            self.finish_indexation().await
+            (Some(self.indexation_payload()?), None)
         } else {
             // Send message without payload
-            self.finish_message(None).await
-        }
+            (None, None)
+        };
+        let (message_id, message) = self.submit_message(payload).await?;
+        Ok((message_id, message, remainder))
     }
 
     /// Consume the builder and get the API result
     pub async fn finish_transaction(self) -> Result<MessageId> {
-        let account_index = self.account_index.unwrap_or(0);
-        let path = BIP32Path::from_str(&crate::account_path!(account_index)).expect("invalid account index");
+        let (payload, _) = self.transaction_payload().await?;
+        self.finish_message(Some(payload)).await
+    }
 
-        let mut index = self.initial_address_index.unwrap_or(0);
+    // Selects the inputs, computes the remainder and signs the transaction
+    async fn transaction_payload(&self) -> Result<(Payload, Option<RemainderData>)> {
+        let signer = self
+            .signer
+            .ok_or_else(|| Error::MissingParameter(String::from("Seed")))?;
+        let prepared = self.prepare_transaction().await?;
+        let payload = prepared.sign(signer)?;
+        Ok((Payload::Transaction(Box::new(payload)), prepared.remainder))
+    }
+
+    /// Builds the plan of the transaction without any PoW or submit: selects the inputs and computes the remainder
+    /// like [`prepare_transaction`](Self::prepare_transaction), and signs the essence if `sign` is set, which needs the
+    /// seed or the signer. The node is only asked for the outputs of the account and the checks of the builder.
+    pub async fn dry_run(&self, sign: bool) -> Result<TransactionPlan> {
+        let prepared = self.prepare_transaction().await?;
+        let payload = if sign {
+            let signer = self
+                .signer
+                .ok_or_else(|| Error::MissingParameter(String::from("Seed")))?;
+            Some(prepared.sign(signer)?)
+        } else {
+            None
+        };
+        TransactionPlan::new(self.client, prepared, payload)
+    }
+
+    /// Consume the builder and submit the transaction of a plan with the essence of the plan, an unsigned plan is
+    /// signed with the seed or the signer of the builder. The transaction isn't submitted if an input of the plan was
+    /// spent since the dry run, the plan is then stale and a new dry run selects other inputs.
+    pub async fn finish_plan(self, plan: TransactionPlan) -> Result<(MessageId, Message)> {
+        let prepared = &plan.prepared;
+        let client = self.client;
+        let spent: Vec<bool> = futures::stream::iter(&prepared.inputs)
+            .map(|signing_data| async move {
+                let output_types = [
+                    OutputType::SignatureLockedSingle,
+                    OutputType::SignatureLockedDustAllowance,
+                ];
+                match client.get_output_of_types(&signing_data.input, &output_types).await {
+                    Ok(output) => Ok(output.is_spent),
+                    Err(Error::ResponseError(404, _, _)) => Ok(true),
+                    Err(e) => Err(e),
+                }
+            })
+            .buffered(client.request_parallelism)
+            .try_collect()
+            .await?;
+        if let Some((signing_data, _)) = prepared.inputs.iter().zip(spent).find(|(_, spent)| *spent) {
+            return Err(Error::StalePlan(signing_data.input.output_id().to_string()));
+        }
+        let payload = match plan.payload {
+            Some(payload) => {
+                let mut serialized_essence = Vec::new();
+                payload
+                    .essence()
+                    .pack(&mut serialized_essence)
+                    .map_err(|_| Error::TransactionError)?;
+                if serialized_essence != prepared.essence_bytes()? {
+                    return Err(Error::InvalidParameter(String::from(
+                        "plan: the payload doesn't sign the essence of the plan",
+                    )));
+                }
+                payload
+            }
+            None => prepared.sign(
+                self.signer
+                    .ok_or_else(|| Error::MissingParameter(String::from("Seed")))?,
+            )?,
+        };
+        self.finish_signed_transaction(payload).await
+    }
+
+    /// Selects the inputs and computes the remainder of the transaction without signing it.
+    /// The inputs are selected from the account addresses if they are set, so the seed isn't needed, and from the
+    /// seed addresses up to the gap limit otherwise, with the [`InputSelectionStrategy`] of the builder.
+    pub async fn prepare_transaction(&self) -> Result<PreparedTransactionData> {
+        let account_index = self.account_index.unwrap_or(0);
 
         if self.outputs.is_empty() {
             return Err(Error::MissingParameter(String::from("Outputs")));
         }
+        if self.signer.is_none() && self.account_addresses.is_none() && self.input_candidates.is_none() {
+            return Err(Error::MissingParameter(String::from("Seed")));
+        }
+        if self.data.is_some() && self.index.is_none() {
+            return Err(Error::MissingParameter(String::from("index")));
+        }
+
+        let custom_remainder_address = match &self.remainder_strategy {
+            RemainderStrategy::Custom(address) => Some(self.client.parse_bech32_address(address)?),
+            _ => None,
+        };
 
+        // The outputs of the same type to the same address are merged into one output
+        let mut transfers = merge_outputs(&self.outputs)?;
+        if transfers.len() > OUTPUT_COUNT_MAX {
+            return Err(Error::TooManyOutputs(transfers.len(), OUTPUT_COUNT_MAX));
+        }
         // Calculate the total tokens to spend
-        let mut total_to_spend = 0;
-        let mut total_already_spent = 0;
-        for output in &self.outputs {
-            if let Output::SignatureLockedSingle(x) = &output {
-                total_to_spend += x.amount();
+        let total_to_spend = checked_amount_sum(transfers.iter().map(|transfer| transfer.amount))?;
+        // The node only rejects the dust outputs an address doesn't allow after the PoW is done
+        if self.dust_check {
+            for transfer in transfers.iter().filter(|transfer| transfer.is_dust()) {
+                let new_allowance = new_dust_allowance(&transfers, &transfer.address);
+                let available = available_dust_outputs(self.client, &transfer.address, new_allowance).await?;
+                if available == 0 {
+                    return Err(Error::DustProtection(
+                        self.client.address_to_bech32(&transfer.address)?.0,
+                        transfer.amount,
+                        available,
+                    ));
+                }
+            }
+        }
+        let candidates = self.get_input_candidates().await?;
+        // The whole UTXO set of the account is known, so the selection only depends on it
+        let selected = select_inputs(
+            &candidates,
+            total_to_spend,
+            &self.input_selection,
+            self.include_dust_allowance,
+        )?;
+        if self.include_dust_allowance && self.dust_check && !self.client.is_offline() {
+            check_dust_allowance_inputs(self.client, &selected, &transfers).await?;
+        }
+        let total_already_spent = checked_amount_sum(selected.iter().map(|candidate| candidate.amount))?;
+        let inputs = sort_inputs(selected.into_iter().map(|candidate| candidate.signing_data).collect())?;
+        if self.client.address_reuse_check && !self.address_reuse_allowed && !self.client.is_offline() {
+            let reused = reused_addresses(self.client, &inputs).await?;
+            if !reused.is_empty() {
+                return Err(Error::AddressReuse(
+                    reused.into_iter().map(|address| address.0).collect(),
+                ));
             }
         }
 
-        let mut paths = Vec::new();
         let mut essence = TransactionPayloadEssence::builder();
-        let mut address_index_recorders = Vec::new();
-
-        match self.inputs.clone() {
-            Some(inputs) => {
-                for input in inputs {
-                    // Only add unspent outputs
-                    if let Ok(output) = self.client.get_output(&input).await {
-                        if !output.is_spent {
-                            total_already_spent += output.amount;
-                            let mut address_path = path.clone();
-                            // Note that we need to sign the original address, i.e., `path/index`,
-                            // instead of `path/index/_offset` or `path/_offset`.
-                            // Todo: Make the range 0..100 configurable
-                            let (address_index, internal) = search_address(
-                                &self.seed.expect("No seed"),
-                                account_index,
-                                0..100,
-                                &output.address.to_bech32().into(),
-                            )?;
-                            address_path.push(internal as u32 + HARDEND);
-                            address_path.push(address_index as u32 + HARDEND);
-                            paths.push(address_path.clone());
-                            let transaction_id: [u8; TRANSACTION_ID_LENGTH] = output.transaction_id[..]
-                                .try_into()
-                                .map_err(|_| Error::TransactionError)?;
-                            let input = Input::UTXO(
-                                UTXOInput::new(TransactionId::from(transaction_id), output.output_index)
-                                    .map_err(|_| Error::TransactionError)?,
-                            );
-                            essence = essence.add_input(input.clone());
-                            address_index_recorders.push(AddressIndexRecorder {
-                                input,
-                                address_index,
-                                address_path,
-                                internal,
-                            });
-                            // Output the remaining tokens back to the original address
-                            if total_already_spent > total_to_spend {
-                                essence = essence.add_output(
-                                    SignatureLockedSingleOutput::new(
-                                        output.address.clone(),
-                                        total_already_spent - total_to_spend,
-                                    )
-                                    .unwrap()
-                                    .into(),
-                                );
-                            }
-                        }
-                    }
+        for input in &inputs {
+            essence = essence.add_input(Input::UTXO(input.input.clone()));
+        }
+        // Output the remaining tokens to the remainder address, in the output to the address if there is one. The
+        // outputs then total the inputs amount, within the supply.
+        let remainder = total_already_spent
+            .checked_sub(total_to_spend)
+            .ok_or(Error::NotEnoughBalance(total_already_spent, total_to_spend))?;
+        let mut remainder_address = None;
+        if remainder > 0 {
+            let (address, derivation) = match custom_remainder_address {
+                Some(address) => (address, None),
+                None => self.account_remainder_address(account_index, &inputs).await?,
+            };
+            let new_allowance = new_dust_allowance(&transfers, &address);
+            match transfers.iter().position(|transfer| {
+                transfer.output_type == OutputType::SignatureLockedSingle && transfer.address == address
+            }) {
+                Some(position) => {
+                    transfers[position].amount += remainder;
+                    remainder_address = Some((address, derivation));
                 }
-            }
-            None => {
-                'input_selection: loop {
-                    // Reset the empty_address_count for each run of output address searching
-                    let mut empty_address_count = 0;
-                    // Get the addresses in the BIP path/index ~ path/index+20
-                    let addresses = self
-                        .client
-                        .find_addresses(self.seed.expect("No seed"))
-                        .with_account_index(account_index)
-                        .with_range(index..index + 20)
-                        .get_all()?;
-                    // For each address, get the address outputs
-                    let mut address_index = 0;
-                    for (index, (address, internal)) in addresses.iter().enumerate() {
-                        let address_outputs = self.client.get_address().outputs(&address).await?;
-                        let mut outputs = vec![];
-                        for output_id in address_outputs.iter() {
-                            let curr_outputs = self.client.get_output(output_id).await?;
-                            outputs.push(curr_outputs);
-                        }
-                        // If there are more than 20 (gap limit) consecutive empty addresses, then we stop looking
-                        // up the addresses belonging to the seed. Note that we don't really count the exact 20
-                        // consecutive empty addresses, which is uncessary. We just need to check the address range,
-                        // [k*20, k*20 + 20), where k is natural number, and to see if the outpus are all empty.
-                        if outputs.is_empty() {
-                            // Accumulate the empty_address_count for each run of output address searching
-                            empty_address_count += 1;
-                        }
-                        for (_offset, output) in outputs.into_iter().enumerate() {
-                            match output.is_spent {
-                                true => {
-                                    if output.amount != 0 {
-                                        return Err(Error::SpentAddress);
-                                    }
-                                }
-                                false => {
-                                    if output.amount != 0 && total_already_spent < total_to_spend {
-                                        total_already_spent += output.amount;
-                                        let mut address_path = path.clone();
-                                        // Note that we need to sign the original address, i.e., `path/index`,
-                                        // instead of `path/index/_offset` or `path/_offset`.
-                                        address_path.push(*internal as u32 + HARDEND);
-                                        address_path.push(address_index as u32 + HARDEND);
-                                        paths.push(address_path.clone());
-                                        let transaction_id: [u8; TRANSACTION_ID_LENGTH] = output.transaction_id[..]
-                                            .try_into()
-                                            .map_err(|_| Error::TransactionError)?;
-                                        let input = Input::UTXO(
-                                            UTXOInput::new(TransactionId::from(transaction_id), output.output_index)
-                                                .map_err(|_| Error::TransactionError)?,
-                                        );
-                                        essence = essence.add_input(input.clone());
-                                        address_index_recorders.push(AddressIndexRecorder {
-                                            input,
-                                            address_index,
-                                            address_path,
-                                            internal: *internal,
-                                        });
-                                        // Output the remaining tokens back to the original address
-                                        if total_already_spent > total_to_spend {
-                                            essence = essence.add_output(
-                                                SignatureLockedSingleOutput::new(
-                                                    Address::try_from_bech32(address)?,
-                                                    total_already_spent - total_to_spend,
-                                                )
-                                                .unwrap()
-                                                .into(),
-                                            );
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        if total_already_spent > total_to_spend {
-                            break 'input_selection;
-                        }
-                        // if we just processed an even index, increase the address index
-                        // (because the list has public and internal addresses)
-                        if index % 2 == 1 {
-                            address_index += 1;
+                // The first output can be dust itself, it then only gets closer to the threshold
+                None if remainder < DUST_THRESHOLD && self.dust_remainder_to_first_output => {
+                    transfers[0].amount += remainder;
+                }
+                None => {
+                    if remainder < DUST_THRESHOLD && self.dust_check {
+                        let available = available_dust_outputs(self.client, &address, new_allowance).await?;
+                        if available == 0 {
+                            return Err(Error::DustRemainder(
+                                self.client.address_to_bech32(&address)?.0,
+                                remainder,
+                                available,
+                            ));
                         }
                     }
-                    index += 20;
-                    // The gap limit is 20 and use reference 40 here because there's public and internal addresses
-                    if empty_address_count == 40 {
-                        break;
-                    }
+                    transfers.push(TransferOutput {
+                        address: address.clone(),
+                        amount: remainder,
+                        output_type: OutputType::SignatureLockedSingle,
+                    });
+                    remainder_address = Some((address, derivation));
                 }
             }
         }
-
-        if total_already_spent < total_to_spend {
-            return Err(Error::NotEnoughBalance(total_to_spend));
+        if transfers.len() > OUTPUT_COUNT_MAX {
+            return Err(Error::TooManyOutputs(transfers.len(), OUTPUT_COUNT_MAX));
         }
 
-        // Build signed transaction payload
-        for output in self.outputs.clone() {
+        // The outputs are sorted in the lexicographic order of their serialized form
+        let mut outputs = transfers
+            .iter()
+            .map(|transfer| {
+                let output = transfer.to_output()?;
+                let mut serialized_output = Vec::new();
+                output
+                    .pack(&mut serialized_output)
+                    .map_err(|_| Error::TransactionError)?;
+                Ok((serialized_output, output))
+            })
+            .collect::<Result<Vec<(Vec<u8>, Output)>>>()?;
+        outputs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let remainder = match remainder_address {
+            Some((address, derivation)) => Some(RemainderData {
+                output_index: outputs
+                    .iter()
+                    .position(|(_, output)| {
+                        matches!(output, Output::SignatureLockedSingle(output) if *output.address() == address)
+                    })
+                    .ok_or(Error::TransactionError)?,
+                address,
+                amount: remainder,
+                address_index: derivation.map(|(address_index, _)| address_index),
+                internal: derivation.map_or(false, |(_, internal)| internal),
+            }),
+            None => None,
+        };
+        for (_, output) in outputs {
             essence = essence.add_output(output);
         }
-        // Add indexation_payload if index set
-        if let Some(index) = self.index.clone() {
-            let indexation_payload = IndexationPayload::new(index, &self.data.clone().unwrap_or_default())?;
-            essence = essence.with_payload(Payload::Indexation(Box::new(indexation_payload)))
+        // Add indexation_payload if index set, it's nested in the essence so the transaction can be found by its index
+        if self.index.is_some() {
+            essence = essence.with_payload(self.indexation_payload()?)
         }
-        let essence = essence.finish()?;
-        let mut serialized_essence = Vec::new();
-        essence
-            .pack(&mut serialized_essence)
-            .map_err(|_| Error::InvalidParameter("inputs".to_string()))?;
 
-        let mut unlock_blocks = Vec::new();
-        let mut current_block_index: usize = 0;
-        let mut signature_indexes = HashMap::<String, usize>::new();
-        address_index_recorders.sort_by(|a, b| a.input.cmp(&b.input));
-
-        for recorder in address_index_recorders.iter() {
-            // Check if current path is same as previous path
-            // If so, add a reference unlock block
+        Ok(PreparedTransactionData {
+            essence: essence.finish()?,
+            account_index,
+            inputs,
+            remainder,
+        })
+    }
 
-            // Format to differentiate between public and private addresses
-            let index = format!("{}{}", recorder.address_index, recorder.internal);
-            if let Some(block_index) = signature_indexes.get(&index) {
-                unlock_blocks.push(UnlockBlock::Reference(ReferenceUnlock::new(*block_index as u16)?));
-            } else {
-                // If not, we should create a signature unlock block
-                match &self.seed.expect("No seed") {
-                    Seed::Ed25519(s) => {
-                        let private_key = Ed25519PrivateKey::generate_from_seed(s, &recorder.address_path)
-                            .map_err(|_| Error::InvalidParameter("seed inputs".to_string()))?;
-                        let public_key = private_key.generate_public_key().to_bytes();
-                        // The block should sign the entire transaction essence part of the transaction payload
-                        let signature = Box::new(private_key.sign(&serialized_essence).to_bytes());
-                        unlock_blocks.push(UnlockBlock::Signature(SignatureUnlock::Ed25519(Ed25519Signature::new(
-                            public_key, signature,
-                        ))));
+    /// Gets the unspent outputs of the account the inputs are selected from: the given inputs with
+    /// [`InputSelectionStrategy::Manual`], the outputs of the account addresses if they are set, and the outputs of the
+    /// seed addresses up to the gap limit otherwise. They can be exported as JSON to prepare the transaction with an
+    /// offline client, see [`with_input_candidates`](Self::with_input_candidates).
+    pub async fn get_input_candidates(&self) -> Result<Vec<InputCandidate>> {
+        let account_index = self.account_index.unwrap_or(0);
+        let initial_address_index = self.initial_address_index.unwrap_or(0);
+        if let Some(candidates) = &self.input_candidates {
+            return Ok(match &self.input_selection {
+                // The given inputs in their order, the ones that aren't candidates are skipped like spent outputs
+                InputSelectionStrategy::Manual(inputs) => inputs
+                    .iter()
+                    .filter_map(|input| {
+                        candidates
+                            .iter()
+                            .find(|candidate| candidate.signing_data.input == *input)
+                            .cloned()
+                    })
+                    .collect(),
+                _ => candidates.clone(),
+            });
+        }
+        if self.signer.is_none() && self.account_addresses.is_none() {
+            return Err(Error::MissingParameter(String::from("Seed")));
+        }
+        let candidates = match (&self.input_selection, &self.account_addresses) {
+            (InputSelectionStrategy::Manual(inputs), _) => self.manual_candidates(account_index, inputs).await?,
+            (_, Some(account_addresses)) => {
+                let mut candidates = Vec::new();
+                for account_address in account_addresses {
+                    candidates.extend(
+                        self.address_candidates(
+                            &self.client.address_to_bech32(&account_address.address)?,
+                            account_address.address_index,
+                            account_address.internal,
+                        )
+                        .await?,
+                    );
+                }
+                candidates
+            }
+            (_, None) => {
+                let mut candidates = Vec::new();
+                let mut scan = AddressScan::new(
+                    self.client,
+                    self.signer.expect("No seed"),
+                    account_index,
+                    initial_address_index,
+                    ScanAddresses::All,
+                );
+                loop {
+                    // The unspent outputs of the public and internal addresses of the next window of indexes
+                    let window = scan
+                        .next_window(|address, address_index, internal| async move {
+                            self.address_candidates(&address, address_index, internal).await
+                        })
+                        .await?;
+                    // We stop looking up the addresses belonging to the seed after a window of indexes without any
+                    // unspent output, the window size is the gap limit. Note that we don't count the exact
+                    // consecutive empty addresses, we just check that the outputs of the window are all empty.
+                    let mut empty_window = true;
+                    for scanned in window {
+                        empty_window &= scanned.result.is_empty();
+                        candidates.extend(scanned.result);
+                    }
+                    if empty_window {
+                        break;
                     }
-                    Seed::Wots(_) => panic!("Wots signing scheme isn't supported."),
                 }
-                signature_indexes.insert(index, current_block_index);
+                candidates
+            }
+        };
+        Ok(candidates)
+    }
 
-                // Update current block index
-                current_block_index += 1;
+    // Gets the remainder address of the strategy for an address of the account, with its derivation indexes
+    async fn account_remainder_address(
+        &self,
+        account_index: usize,
+        inputs: &[InputSigningData],
+    ) -> Result<(Address, Option<(usize, bool)>)> {
+        if let RemainderStrategy::ReuseFirstInputAddress = self.remainder_strategy {
+            let input = inputs.first().ok_or(Error::TransactionError)?;
+            return Ok((input.address.clone(), Some((input.address_index, input.internal))));
+        }
+        // The change address is the first internal address without any output, spent or unspent
+        if let Some(account_addresses) = &self.account_addresses {
+            for account_address in account_addresses
+                .iter()
+                .filter(|account_address| account_address.internal)
+            {
+                if self
+                    .is_unused_address(&self.client.address_to_bech32(&account_address.address)?)
+                    .await?
+                {
+                    return Ok((
+                        account_address.address.clone(),
+                        Some((account_address.address_index, true)),
+                    ));
+                }
             }
+            return Err(Error::MissingParameter(String::from(
+                "unused internal account address for the remainder",
+            )));
         }
-        // TODO overflow check
-        let mut payload_builder = TransactionPayloadBuilder::new().with_essence(essence);
-        for unlock in unlock_blocks {
-            payload_builder = payload_builder.add_unlock_block(unlock);
+        // The inputs can be given without the seed or the account addresses
+        let signer = self
+            .signer
+            .ok_or_else(|| Error::MissingParameter(String::from("Seed")))?;
+        let mut scan = AddressScan::new(
+            self.client,
+            signer,
+            account_index,
+            self.initial_address_index.unwrap_or(0),
+            ScanAddresses::Internal,
+        );
+        loop {
+            let window = scan
+                .next_window(|address, _, _| async move { self.is_unused_address(&address).await })
+                .await?;
+            if let Some(scanned) = window.into_iter().find(|scanned| scanned.result) {
+                return Ok((
+                    Address::try_from_bech32(&scanned.address.0)?,
+                    Some((scanned.address_index, true)),
+                ));
+            }
         }
+    }
 
-        let payload = payload_builder.finish().map_err(|_| Error::TransactionError)?;
+    // Gets the unspent outputs of the address that can fund the transaction, the dust allowance outputs only if they
+    // can be selected
+    async fn address_candidates(
+        &self,
+        address: &Bech32Address,
+        address_index: usize,
+        internal: bool,
+    ) -> Result<Vec<InputCandidate>> {
+        let mut output_types = vec![OutputType::SignatureLockedSingle];
+        if self.include_dust_allowance {
+            output_types.push(OutputType::SignatureLockedDustAllowance);
+        }
+        let mut candidates = Vec::new();
+        for output_type in output_types {
+            let options = OutputsOptions {
+                include_spent: false,
+                output_type: Some(output_type),
+            };
+            let output_ids = self
+                .client
+                .get_address()
+                .outputs_response(address, options)
+                .await?
+                .utxo_inputs()?;
+            for output_id in output_ids.iter() {
+                let output = self.client.get_output_of_type(output_id, output_type).await?;
+                if output.is_spent {
+                    if output.amount != 0 {
+                        return Err(Error::SpentAddress);
+                    }
+                    continue;
+                }
+                candidates.push(InputCandidate::new(&output, output_type, address_index, internal)?);
+            }
+        }
+        Ok(candidates)
+    }
 
-        // building message
-        let payload = Payload::Transaction(Box::new(payload));
+    // Gets the given outputs that are unspent, in their order
+    async fn manual_candidates(&self, account_index: usize, inputs: &[UTXOInput]) -> Result<Vec<InputCandidate>> {
+        let mut candidates = Vec::new();
+        for input in inputs {
+            let mut output = self
+                .client
+                .get_output(input)
+                .await
+                .map(|output| (output, OutputType::SignatureLockedSingle));
+            if output.is_err() && self.include_dust_allowance {
+                output = self
+                    .client
+                    .get_output_of_type(input, OutputType::SignatureLockedDustAllowance)
+                    .await
+                    .map(|output| (output, OutputType::SignatureLockedDustAllowance));
+            }
+            // Only add unspent outputs
+            if let Ok((output, output_type)) = output {
+                if !output.is_spent {
+                    let (address_index, internal) = self.find_account_address(account_index, &output.address)?;
+                    candidates.push(InputCandidate::new(&output, output_type, address_index, internal)?);
+                }
+            }
+        }
+        Ok(candidates)
+    }
 
-        self.finish_message(Some(payload)).await
+    // Checks that the address never held an output
+    async fn is_unused_address(&self, address: &Bech32Address) -> Result<bool> {
+        let options = OutputsOptions {
+            include_spent: true,
+            output_type: None,
+        };
+        let outputs = self.client.get_address().outputs_response(address, options).await?;
+        Ok(outputs.utxo_inputs()?.is_empty())
     }
 
-    /// Consume the builder and get the API result
-    pub async fn finish_indexation(self) -> Result<MessageId> {
-        let payload: Payload;
-        {
-            let index = &self.index.as_ref();
-            let empty_slice = &vec![];
-            let data = &self.data.as_ref().unwrap_or(empty_slice);
+    // Finds the index and public or internal type of an account address
+    fn find_account_address(&self, account_index: usize, address: &Address) -> Result<(usize, bool)> {
+        match &self.account_addresses {
+            Some(account_addresses) => account_addresses
+                .iter()
+                .find(|account_address| account_address.address == *address)
+                .map(|account_address| (account_address.address_index, account_address.internal))
+                .ok_or(Error::AddressNotFound),
+            // Todo: Make the range 0..100 configurable
+            None => search_address(
+                self.signer.expect("No seed"),
+                account_index,
+                0..100,
+                &address.to_bech32().into(),
+            ),
+        }
+    }
 
-            // build indexation
-            let index = IndexationPayload::new(index.expect("No indexation tag").to_string(), data)
-                .map_err(|e| Error::IndexationError(e.to_string()))?;
-            payload = Payload::Indexation(Box::new(index));
+    /// Consume the builder and submit a transaction signed offline, the message is built with the builder parent
+    /// and network id.
+    pub async fn finish_signed_transaction(self, payload: TransactionPayload) -> Result<(MessageId, Message)> {
+        if payload.unlock_blocks().len() != payload.essence().inputs().len() {
+            return Err(Error::InvalidParameter(format!(
+                "{} unlock blocks for {} inputs",
+                payload.unlock_blocks().len(),
+                payload.essence().inputs().len()
+            )));
         }
+        self.submit_message(Some(Payload::Transaction(Box::new(payload)))).await
+    }
+
+    /// Consume the builder and get the API result
+    pub async fn finish_indexation(self) -> Result<MessageId> {
+        let payload = self.indexation_payload()?;
 
         // building message
         self.finish_message(Some(payload)).await
     }
 
+    fn indexation_payload(&self) -> Result<Payload> {
+        let index = &self.index.as_ref();
+        let empty_slice = &vec![];
+        let data = &self.data.as_ref().unwrap_or(empty_slice);
+        let index = index.expect("No indexation tag");
+        if !INDEXATION_INDEX_LENGTH_RANGE.contains(&index.len()) {
+            return Err(Error::IndexationError(format!(
+                "invalid index length {} bytes, expected between {} and {}",
+                index.len(),
+                INDEXATION_INDEX_LENGTH_RANGE.start(),
+                INDEXATION_INDEX_LENGTH_RANGE.end()
+            )));
+        }
+        if data.len() > MESSAGE_LENGTH_MAX {
+            return Err(Error::IndexationError(format!(
+                "invalid data length {} bytes, exceeds the maximum message length of {} bytes",
+                data.len(),
+                MESSAGE_LENGTH_MAX
+            )));
+        }
+
+        // build indexation
+        let index =
+            IndexationPayload::new(index.to_string(), data).map_err(|e| Error::IndexationError(e.to_string()))?;
+        Ok(Payload::Indexation(Box::new(index)))
+    }
+
     /// Builds the final message and posts it to the node
     pub async fn finish_message(self, payload: Option<Payload>) -> Result<MessageId> {
-        // get tips
-        let tips = self.client.get_tips().await?;
+        Ok(self.submit_message(payload).await?.0)
+    }
+
+    // Builds the final message, posts it to the node and returns it with its id
+    async fn submit_message(&self, payload: Option<Payload>) -> Result<(MessageId, Message)> {
+        let (parent1, parent2) = match &self.parents {
+            Some(parents) => {
+                if self.parents_check {
+                    self.check_parents(parents).await?;
+                }
+                // the parents are sorted, a single parent is both parents
+                (parents[0], parents[parents.len() - 1])
+            }
+            None => {
+                // get tips
+                let tips = self.client.get_tips().await?;
+                (self.parent.unwrap_or(tips.0), tips.1)
+            }
+        };
 
         // building message
-        let mut message = MessageBuilder::<ClientMiner>::new();
+        let network_id = match self.network_id {
+            Some(id) => id,
+            _ => self.client.get_network_id().await?,
+        };
+        let final_message = self
+            .client
+            .build_message(network_id, (parent1, parent2), payload)
+            .await?;
 
-        match self.network_id {
-            Some(id) => message = message.with_network_id(id),
-            _ => message = message.with_network_id(self.client.get_network_id().await?),
-        }
+        let message_id = match (self.client.post_message(&final_message).await, &self.parents) {
+            (Ok(message_id), _) => message_id,
+            // the node refuses a message whose parents it doesn't know
+            (Err(Error::ResponseError(400, text, _)), Some(parents)) if text.to_lowercase().contains("parent") => {
+                return Err(Error::ParentsRejected(
+                    parents.iter().map(MessageId::to_string).collect(),
+                    text,
+                ));
+            }
+            (Err(e), _) => return Err(e),
+        };
+        Ok((message_id, final_message))
+    }
 
-        match self.parent {
-            Some(p) => message = message.with_parent1(p),
-            _ => message = message.with_parent1(tips.0),
+    // Checks that the node knows the custom parents
+    async fn check_parents(&self, parents: &[MessageId]) -> Result<()> {
+        let results =
+            futures::future::join_all(parents.iter().map(|parent| self.client.get_message().metadata(parent))).await;
+        let mut unknown = Vec::new();
+        for (parent, result) in parents.iter().zip(results) {
+            match result {
+                Ok(_) => {}
+                Err(Error::ResponseError(404, _, _)) => unknown.push(parent.to_string()),
+                Err(e) => return Err(e),
+            }
         }
-        if let Some(p) = payload {
-            message = message.with_payload(p);
+        if !unknown.is_empty() {
+            return Err(Error::UnknownParents(unknown));
         }
-        let final_message = message
-            .with_parent2(tips.1)
-            .with_nonce_provider(self.client.get_pow_provider(), 4000f64)
-            .finish()
-            .map_err(Error::MessageError)?;
-
-        self.client.post_message(&final_message).await
+        Ok(())
     }
 }
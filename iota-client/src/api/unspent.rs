@@ -1,26 +1,33 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{types::Bech32Address, Client, Error, Result};
+use crate::{
+    api::{address_outputs, AddressScan, ScanAddresses},
+    storage,
+    types::Bech32Address,
+    Client, Error, Result, SignerType,
+};
 
-use bee_signing_ext::Seed;
+use std::collections::HashMap;
 
 /// Builder of get_unspent_address API
 pub struct GetUnspentAddressBuilder<'a> {
     client: &'a Client,
-    seed: &'a Seed,
+    signer: SignerType<'a>,
     account_index: Option<usize>,
     initial_address_index: Option<usize>,
+    include_funded: bool,
 }
 
 impl<'a> GetUnspentAddressBuilder<'a> {
     /// Create get_unspent_address builder
-    pub fn new(client: &'a Client, seed: &'a Seed) -> Self {
+    pub fn new<S: Into<SignerType<'a>>>(client: &'a Client, signer: S) -> Self {
         Self {
             client,
-            seed,
+            signer: signer.into(),
             account_index: None,
             initial_address_index: None,
+            include_funded: false,
         }
     }
 
@@ -36,40 +43,88 @@ impl<'a> GetUnspentAddressBuilder<'a> {
         self
     }
 
-    /// Consume the builder and get the API result
+    /// Sets whether an address holding a balance that was never spent from can be returned.
+    /// By default only addresses without any output are returned.
+    pub fn with_include_funded(mut self, include_funded: bool) -> Self {
+        self.include_funded = include_funded;
+        self
+    }
+
+    /// Consume the builder and get the first unspent address with its index.
+    /// An address is skipped if one of its outputs was spent, or if it holds a balance and
+    /// funded addresses aren't included. The public addresses are checked in windows of the address scan window of
+    /// the client.
+    /// Without an initial address index and funded addresses, the address found is stored and the next scans of the
+    /// account start at its index, the addresses before it already hold outputs, see
+    /// [`ClientBuilder::with_storage`](crate::ClientBuilder::with_storage).
     pub async fn get(self) -> Result<(Bech32Address, usize)> {
         let account_index = self
             .account_index
             .ok_or_else(|| Error::MissingParameter(String::from("account index")))?;
 
-        let mut index = self.initial_address_index.unwrap_or(0);
-
-        let result = loop {
-            let addresses = self
-                .client
-                .find_addresses(self.seed)
-                .with_account_index(account_index)
-                .with_range(index..index + 20)
-                .finish()?;
-
-            // TODO we assume all addresses are unspent and valid if balance > 0
-            let mut address = None;
-            for a in addresses {
-                let address_balance = self.client.get_address().balance(&a).await?;
-                match address_balance {
-                    0 => {
-                        address = Some(a);
-                        break;
-                    }
-                    _ => index += 1,
+        let account = match self.initial_address_index {
+            None if !self.include_funded => Some(self.account_key(account_index)?),
+            _ => None,
+        };
+        let initial_address_index = match &account {
+            Some(account) => {
+                let indexes: HashMap<String, usize> = self.client.load_state(storage::UNUSED_ADDRESS_INDEXES).await?;
+                indexes.get(account).copied().unwrap_or(0)
+            }
+            None => self.initial_address_index.unwrap_or(0),
+        };
+        let mut scan = AddressScan::new(
+            self.client,
+            self.signer,
+            account_index,
+            initial_address_index,
+            ScanAddresses::Public,
+        );
+        let this = &self;
+        loop {
+            let window = scan
+                .next_window(|address, _, _| async move { this.is_unspent(&address).await })
+                .await?;
+            if let Some(scanned) = window.into_iter().find(|scanned| scanned.result) {
+                if let Some(account) = account {
+                    let address_index = scanned.address_index;
+                    // the address is found again by a scan from the start
+                    let _ = self
+                        .client
+                        .update_state(
+                            storage::UNUSED_ADDRESS_INDEXES,
+                            |indexes: &mut HashMap<String, usize>| {
+                                let index = indexes.entry(account).or_default();
+                                *index = address_index.max(*index);
+                            },
+                        )
+                        .await;
                 }
+                return Ok((scanned.address, scanned.address_index));
             }
+        }
+    }
 
-            if let Some(a) = address {
-                break (a, index);
-            }
-        };
+    // The key of the account in the stored indexes: the address at the index 0 of the seed and the account index, the
+    // seed itself isn't stored.
+    fn account_key(&self, account_index: usize) -> Result<String> {
+        let addresses = self
+            .client
+            .find_addresses(self.signer)
+            .with_account_index(account_index)
+            .with_range(0..1)
+            .get_all()?;
+        Ok(format!("{}/{}", addresses[0].0, account_index))
+    }
 
-        Ok(result)
+    // Checks the outputs of the address, the spent ones included, and whether it spent one of them, see
+    // `address_outputs`, the node only lists the unspent outputs by default. A spent address is stored even if funded
+    // addresses aren't included, a stored spent address isn't queried.
+    async fn is_unspent(&self, address: &Bech32Address) -> Result<bool> {
+        if self.client.is_stored_spent(address).await? {
+            return Ok(false);
+        }
+        let (outputs, spent) = address_outputs(self.client, address).await?;
+        Ok(!spent && (outputs.output_ids.is_empty() || self.include_funded))
     }
 }
@@ -3,11 +3,22 @@
 
 //! Builder of the client instance
 
-use crate::{client::*, error::*};
+use crate::{
+    api::{CoordinatorKeys, ADDRESS_RANGE_MAX},
+    client::*,
+    error::*,
+    middleware::RequestMiddleware,
+    pow::NonceProvider,
+    storage::{ClientStorage, MemoryStorage, Storage},
+    types::NodeSyncStatus,
+};
 
 use reqwest::Url;
+#[cfg(not(target_arch = "wasm32"))]
 use tokio::{runtime::Runtime, sync::broadcast::channel};
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
 use std::{
     collections::{HashMap, HashSet},
     sync::{Arc, RwLock},
@@ -15,6 +26,22 @@ use std::{
 };
 
 const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// The default number of nodes queried by a quorum request.
+pub const DEFAULT_QUORUM_SIZE: usize = 3;
+/// The default percentage of the quorum nodes that must agree on the answer.
+pub const DEFAULT_QUORUM_THRESHOLD: usize = 66;
+/// The default number of address indexes derived and queried at once by the address scans.
+pub const DEFAULT_ADDRESS_SCAN_WINDOW: usize = 20;
+/// The default number of concurrent node requests of the address scans.
+pub const DEFAULT_ADDRESS_SCAN_PARALLELISM: usize = 10;
+/// The default number of concurrent node requests of the batched lookups, like the outputs or the address balances.
+pub const DEFAULT_REQUEST_PARALLELISM: usize = 10;
+/// The default time the info of a node is cached.
+pub const DEFAULT_NODE_INFO_TTL: Duration = Duration::from_secs(60);
+/// The default number of retries of a rate limited request.
+pub const DEFAULT_RATE_LIMIT_RETRIES: usize = 3;
+/// The default longest delay a rate limited request is retried after.
+pub const DEFAULT_RATE_LIMIT_MAX_DELAY: Duration = Duration::from_secs(60);
 
 /// Network of the Iota nodes belong to
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Hash, Eq)]
@@ -40,26 +67,130 @@ pub struct NetworkInfo {
     /// Local proof of work
     #[serde(rename = "localPow")]
     pub local_pow: bool,
+    /// Bech32 human readable part of the network addresses
+    #[serde(rename = "bech32HRP")]
+    pub bech32_hrp: String,
+    /// Number of threads used by the local proof of work, defaults to the number of CPUs
+    #[serde(rename = "powWorkerCount")]
+    pub pow_worker_count: Option<usize>,
+}
+
+/// The network parameters set with the builder, they take precedence over the ones reported by the nodes
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct NetworkOverrides {
+    pub(crate) network_id: bool,
+    pub(crate) min_pow_score: bool,
+    pub(crate) bech32_hrp: bool,
+}
+
+/// The cached network info of a client, with the parameters set with the builder and whether a node reported it
+#[derive(Clone, Debug)]
+pub(crate) struct NetworkState {
+    pub(crate) info: NetworkInfo,
+    pub(crate) overrides: NetworkOverrides,
+    pub(crate) fetched: bool,
+}
+
+impl NetworkState {
+    /// Whether the network of the client is known, reported by a node or set with the builder, so the messages can be
+    /// built and checked without asking a node.
+    pub(crate) fn is_known(&self) -> bool {
+        self.fetched || self.overrides.network_id
+    }
+}
+
+/// Credentials of the node routes that require authentication, like the peers API
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum NodeAuth {
+    /// JSON Web Token sent as bearer token
+    Jwt {
+        /// The token
+        jwt: String,
+    },
+    /// Basic authentication credentials
+    BasicAuth {
+        /// The user name
+        username: String,
+        /// The password
+        password: String,
+    },
+}
+
+/// The credentials and the roles of a node added with [`ClientBuilder::with_node_options`]. Every synced node serves
+/// the reads, round-robin, the roles restrict the submitted messages, the remote PoW and the MQTT connection to the
+/// nodes allowing them. A node is used for everything by default, and a pinned node whatever its roles.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeOptions {
+    /// The credentials of the node routes that require authentication, instead of the ones of
+    /// [`ClientBuilder::with_node_auth`]
+    pub auth: Option<NodeAuth>,
+    /// Whether the messages are submitted to the node
+    pub submit: bool,
+    /// Whether the node does the remote PoW of the submitted messages, if it has the PoW feature
+    pub remote_pow: bool,
+    /// Whether the client connects to the MQTT broker of the node
+    pub mqtt: bool,
+}
+
+impl Default for NodeOptions {
+    fn default() -> Self {
+        Self {
+            auth: None,
+            submit: true,
+            remote_pow: true,
+            mqtt: true,
+        }
+    }
 }
 
 /// Builder to construct client instance with sensible default values
 pub struct ClientBuilder {
     nodes: HashSet<Url>,
+    node_options: HashMap<Url, NodeOptions>,
     node_sync_interval: Duration,
     node_sync_enabled: bool,
+    offline: bool,
     #[cfg(feature = "mqtt")]
     broker_options: BrokerOptions,
     network_info: NetworkInfo,
+    network_overrides: NetworkOverrides,
     request_timeout: Duration,
     api_timeout: HashMap<Api, Duration>,
+    rate_limit_retries: usize,
+    rate_limit_max_delay: Duration,
+    node_auth: Option<NodeAuth>,
+    #[cfg(not(target_arch = "wasm32"))]
+    ca_certificate: Option<PathBuf>,
+    quorum: bool,
+    quorum_size: usize,
+    quorum_threshold: usize,
+    quorum_milestone_validation: bool,
+    coordinator_keys: CoordinatorKeys,
+    address_scan_window: usize,
+    address_scan_parallelism: usize,
+    request_parallelism: usize,
+    middlewares: Vec<Box<dyn RequestMiddleware>>,
+    nonce_provider: Option<Arc<dyn NonceProvider>>,
+    local_pow_fallback: bool,
+    permanode: Option<Url>,
+    permanode_only: bool,
+    node_info_ttl: Duration,
+    address_reuse_check: bool,
+    output_cache: Option<Duration>,
+    storage: Option<Arc<dyn Storage>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    storage_path: Option<PathBuf>,
 }
 
 impl Default for ClientBuilder {
     fn default() -> Self {
         Self {
             nodes: HashSet::new(),
+            node_options: HashMap::new(),
             node_sync_interval: Duration::from_millis(60000),
             node_sync_enabled: true,
+            offline: false,
             #[cfg(feature = "mqtt")]
             broker_options: Default::default(),
             network_info: NetworkInfo {
@@ -67,9 +198,36 @@ impl Default for ClientBuilder {
                 network_id: "alphanet1".into(),
                 min_pow_score: 4000f64,
                 local_pow: true,
+                bech32_hrp: "iot".into(),
+                pow_worker_count: None,
             },
+            network_overrides: Default::default(),
             request_timeout: DEFAULT_REQUEST_TIMEOUT,
             api_timeout: Default::default(),
+            rate_limit_retries: DEFAULT_RATE_LIMIT_RETRIES,
+            rate_limit_max_delay: DEFAULT_RATE_LIMIT_MAX_DELAY,
+            node_auth: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            ca_certificate: None,
+            quorum: false,
+            quorum_size: DEFAULT_QUORUM_SIZE,
+            quorum_threshold: DEFAULT_QUORUM_THRESHOLD,
+            quorum_milestone_validation: false,
+            coordinator_keys: CoordinatorKeys::mainnet(),
+            address_scan_window: DEFAULT_ADDRESS_SCAN_WINDOW,
+            address_scan_parallelism: DEFAULT_ADDRESS_SCAN_PARALLELISM,
+            request_parallelism: DEFAULT_REQUEST_PARALLELISM,
+            middlewares: Vec::new(),
+            nonce_provider: None,
+            local_pow_fallback: false,
+            permanode: None,
+            permanode_only: false,
+            node_info_ttl: DEFAULT_NODE_INFO_TTL,
+            address_reuse_check: false,
+            output_cache: None,
+            storage: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            storage_path: None,
         }
     }
 }
@@ -80,6 +238,19 @@ impl ClientBuilder {
         Default::default()
     }
 
+    /// Creates the builder of an offline client, without any node. The client derives and validates the addresses,
+    /// prepares and signs the transactions from the unspent outputs given with
+    /// [`SendBuilder::with_input_candidates`](crate::api::SendBuilder::with_input_candidates), and every operation
+    /// that needs a node fails with [`Error::OfflineClient`]. The bech32 HRP must be set, and the network id to
+    /// build messages.
+    pub fn offline() -> Self {
+        Self {
+            offline: true,
+            node_sync_enabled: false,
+            ..Default::default()
+        }
+    }
+
     /// Adds an IOTA node by its URL.
     pub fn with_node(mut self, url: &str) -> Result<Self> {
         let url = Url::parse(url).map_err(|_| Error::UrlError)?;
@@ -96,7 +267,80 @@ impl ClientBuilder {
         Ok(self)
     }
 
-    /// Set the node sync interval
+    /// Adds an IOTA node by its URL with its credentials and its roles, e.g. a read-only public node or a private node
+    /// only used to submit the messages and do their remote PoW.
+    pub fn with_node_options(mut self, url: &str, options: NodeOptions) -> Result<Self> {
+        let url = Url::parse(url).map_err(|_| Error::UrlError)?;
+        self.nodes.insert(url.clone());
+        self.node_options.insert(url, options);
+        Ok(self)
+    }
+
+    /// Sets a Chronicle-style permanode by its URL, the routes of the node API are relative to it, e.g.
+    /// `https://chronicle.example.com/api/mainnet/`. The messages, the address outputs, the index searches and the
+    /// milestones the nodes don't know, pruned or older than their snapshot, are then searched in the permanode.
+    pub fn with_permanode(mut self, url: &str) -> Result<Self> {
+        let mut url = Url::parse(url).map_err(|_| Error::UrlError)?;
+        // the routes are joined to the URL, which would replace its last segment without the trailing slash
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+        self.permanode.replace(url);
+        Ok(self)
+    }
+
+    /// Sends the historical queries only to the permanode set with [`ClientBuilder::with_permanode`], instead of
+    /// asking a node first.
+    pub fn with_permanode_only(mut self, permanode_only: bool) -> Self {
+        self.permanode_only = permanode_only;
+        self
+    }
+
+    /// Checks before every transfer that its input addresses never spent an output, a transfer spending again from an
+    /// address then fails with [`Error::AddressReuse`] unless
+    /// [`SendBuilder::with_address_reuse_allowed`](crate::api::SendBuilder::with_address_reuse_allowed) is set.
+    /// The transactions prepared by an offline client aren't checked.
+    pub fn with_address_reuse_check(mut self, address_reuse_check: bool) -> Self {
+        self.address_reuse_check = address_reuse_check;
+        self
+    }
+
+    /// Sets the time the info of a node is cached, [`DEFAULT_NODE_INFO_TTL`] by default. The info of each node is
+    /// fetched again once it expires, by [`Client::get_info`](crate::Client::get_info) or by the node syncing, which
+    /// share it. A zero TTL disables the cache, see also [`Client::refresh_node_info`](crate::Client::refresh_node_info).
+    pub fn with_node_info_ttl(mut self, ttl: Duration) -> Self {
+        self.node_info_ttl = ttl;
+        self
+    }
+
+    /// Caches the outputs found by [`Client::find_outputs`](crate::Client::find_outputs), the lookups of the cached
+    /// outputs aren't sent to the node again. A spent output stays spent so it's cached for the lifetime of the
+    /// client, an unspent output for `unspent_ttl`: a zero TTL only caches the spent outputs.
+    /// See [`Client::get_output_cache_stats`](crate::Client::get_output_cache_stats).
+    pub fn with_output_cache(mut self, unspent_ttl: Duration) -> Self {
+        self.output_cache = Some(unspent_ttl);
+        self
+    }
+
+    /// Stores the client state in the storage instead of the memory of the process, so it's kept across restarts: the
+    /// first unused address index of the accounts, the addresses known to have spent an output, the node infos and
+    /// the submitted messages not referenced by a milestone yet, see [`Client::get_pending_messages`]. The state
+    /// belongs to the network id of the client, it's deleted when the client uses the storage with another network id.
+    pub fn with_storage<S: Storage + 'static>(mut self, storage: S) -> Self {
+        self.storage.replace(Arc::new(storage));
+        self
+    }
+
+    /// Stores the client state in the directory at `path`, created if it doesn't exist, see
+    /// [`ClientBuilder::with_storage`] and [`FileStorage`](crate::FileStorage).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_storage_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.storage_path.replace(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set the node sync interval, 60 seconds by default. The nodes are checked again on every interval: a node out of
+    /// sync leaves the synced node pool and comes back once it caught up.
     pub fn with_node_sync_interval(mut self, node_sync_interval: Duration) -> Self {
         self.node_sync_interval = node_sync_interval;
         self
@@ -109,8 +353,6 @@ impl ClientBuilder {
         self
     }
 
-    // TODO node pool
-
     /// Selects the type of network the added nodes belong to.
     pub fn with_network(mut self, network: Network) -> Self {
         self.network_info.network = network;
@@ -124,12 +366,56 @@ impl ClientBuilder {
         self
     }
 
-    /// Sets whether the PoW should be done locally or remotely.
+    /// Sets whether the PoW should be done locally or remotely. In a browser (`wasm32`) the PoW is always done by the
+    /// nodes, the local PoW needs threads, and without the `pow` feature too.
     pub fn with_local_pow(mut self, local: bool) -> Self {
         self.network_info.local_pow = local;
         self
     }
 
+    /// Sets the number of threads used by the local PoW, defaults to the number of CPUs.
+    pub fn with_pow_worker_count(mut self, worker_count: usize) -> Self {
+        self.network_info.pow_worker_count.replace(worker_count);
+        self
+    }
+
+    /// Outsources the PoW to the nonce provider, like a dedicated PoW service: the messages are built with the nonce
+    /// it returns instead of the local or the remote PoW, and submitted to any node with the submit role.
+    pub fn with_nonce_provider<P: NonceProvider + 'static>(mut self, nonce_provider: P) -> Self {
+        self.nonce_provider.replace(Arc::new(nonce_provider));
+        self
+    }
+
+    /// Sets whether the local PoW is done when the nonce provider fails or times out, false by default: the message
+    /// then fails with [`Error::NonceProvider`].
+    pub fn with_local_pow_fallback(mut self, local_pow_fallback: bool) -> Self {
+        self.local_pow_fallback = local_pow_fallback;
+        self
+    }
+
+    /// Sets the network id, the nodes reporting another network id are left out of the synced node pool. The network
+    /// parameters are otherwise reported by the nodes: set the network id, the minimum PoW score and the bech32 HRP to
+    /// build the messages without any node info, for a fully offline use.
+    pub fn with_network_id(mut self, network_id: &str) -> Self {
+        self.network_info.network_id = network_id.into();
+        self.network_overrides.network_id = true;
+        self
+    }
+
+    /// Sets the minimum PoW score of the messages, instead of the one reported by the nodes.
+    pub fn with_min_pow_score(mut self, min_pow_score: f64) -> Self {
+        self.network_info.min_pow_score = min_pow_score;
+        self.network_overrides.min_pow_score = true;
+        self
+    }
+
+    /// Sets the bech32 human readable part of the network addresses, instead of the one reported by the nodes.
+    pub fn with_bech32_hrp(mut self, hrp: &str) -> Self {
+        self.network_info.bech32_hrp = hrp.into();
+        self.network_overrides.bech32_hrp = true;
+        self
+    }
+
     /// Sets the request timeout.
     pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
         self.request_timeout = timeout;
@@ -142,9 +428,137 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the number of retries of a rate limited request, [`DEFAULT_RATE_LIMIT_RETRIES`] by default. A request
+    /// answered with 429 or 503 is retried after the `Retry-After` delay of the node, in seconds or until an HTTP date,
+    /// or after [`RATE_LIMIT_BACKOFF`](crate::RATE_LIMIT_BACKOFF) doubled by every retry without it. Once the
+    /// retries are exhausted the request fails with [`Error::RateLimited`]. 0 disables the retries.
+    pub fn with_rate_limit_retries(mut self, retries: usize) -> Self {
+        self.rate_limit_retries = retries;
+        self
+    }
+
+    /// Sets the longest delay a rate limited request is retried after, [`DEFAULT_RATE_LIMIT_MAX_DELAY`] by default. A
+    /// node asking to wait longer fails the request at once with [`Error::RateLimited`].
+    pub fn with_rate_limit_max_delay(mut self, max_delay: Duration) -> Self {
+        self.rate_limit_max_delay = max_delay;
+        self
+    }
+
+    /// Sets the credentials of the node routes that require authentication, like the peers API.
+    pub fn with_node_auth(mut self, auth: NodeAuth) -> Self {
+        self.node_auth.replace(auth);
+        self
+    }
+
+    /// Trusts the PEM encoded CA certificate at `path`, on top of the system ones, for the node requests and the TLS
+    /// connections of the MQTT broker. For the nodes and the brokers with a self-signed certificate.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_ca_certificate<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.ca_certificate.replace(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Cross-checks the balance and the output queries: the same request is sent to `quorum_size` synced nodes
+    /// concurrently and the answer is only accepted if `quorum_threshold` percent of them agree on it.
+    /// The submissions and the PoW always go to a single node.
+    pub fn with_quorum(mut self, quorum: bool) -> Self {
+        self.quorum = quorum;
+        self
+    }
+
+    /// Sets the number of nodes queried by a quorum request, [`DEFAULT_QUORUM_SIZE`] by default.
+    pub fn with_quorum_size(mut self, quorum_size: usize) -> Self {
+        self.quorum_size = quorum_size;
+        self
+    }
+
+    /// Sets the percentage of the quorum nodes that must agree on the answer, [`DEFAULT_QUORUM_THRESHOLD`] by default.
+    pub fn with_quorum_threshold(mut self, quorum_threshold: usize) -> Self {
+        self.quorum_threshold = quorum_threshold;
+        self
+    }
+
+    /// Only accepts the quorum answers of the nodes whose latest milestone is valid for the coordinator keys, see
+    /// [`ClientBuilder::with_coordinator_keys`]. The latest milestone of a node is validated again when it changes.
+    pub fn with_quorum_milestone_validation(mut self, validation: bool) -> Self {
+        self.quorum_milestone_validation = validation;
+        self
+    }
+
+    /// Sets the public keys of the coordinator the milestones are validated against, the mainnet ones by default.
+    pub fn with_coordinator_keys(mut self, coordinator_keys: CoordinatorKeys) -> Self {
+        self.coordinator_keys = coordinator_keys;
+        self
+    }
+
+    /// Sets the number of address indexes derived and queried at once by the balance, unspent address and input
+    /// scans of a seed, [`DEFAULT_ADDRESS_SCAN_WINDOW`] by default. The scans stop after a window without any used
+    /// address, so the window is also the gap limit of the input search; the balance search has its own gap limit.
+    pub fn with_address_scan_window(mut self, window_size: usize) -> Self {
+        self.address_scan_window = window_size;
+        self
+    }
+
+    /// Sets the number of concurrent node requests of the address scans, [`DEFAULT_ADDRESS_SCAN_PARALLELISM`] by
+    /// default. The results don't depend on it, they are the ones of a serial scan.
+    pub fn with_address_scan_parallelism(mut self, parallelism: usize) -> Self {
+        self.address_scan_parallelism = parallelism;
+        self
+    }
+
+    /// Sets the number of concurrent node requests of the batched lookups, [`DEFAULT_REQUEST_PARALLELISM`] by
+    /// default: the outputs and the messages of the find methods, the address balances and the address reuse check.
+    /// A request starts as soon as another one ends and the results keep the order of the lookups.
+    pub fn with_request_parallelism(mut self, parallelism: usize) -> Self {
+        self.request_parallelism = parallelism;
+        self
+    }
+
+    /// Adds a middleware invoked around every node request, to log or trace them. The middlewares are invoked in the
+    /// order they're added.
+    pub fn with_middleware<M: RequestMiddleware + 'static>(mut self, middleware: M) -> Self {
+        self.middlewares.push(Box::new(middleware));
+        self
+    }
+
     /// Build the Client instance.
     pub fn finish(mut self) -> Result<Client> {
-        if self.nodes.is_empty() {
+        if self.quorum_size == 0 {
+            return Err(Error::InvalidParameter("quorum size".to_string()));
+        }
+        if self.quorum_threshold == 0 || self.quorum_threshold > 100 {
+            return Err(Error::InvalidParameter("quorum threshold".to_string()));
+        }
+        if self.coordinator_keys.signature_count == 0 {
+            return Err(Error::InvalidParameter("coordinator signature count".to_string()));
+        }
+        if self.address_scan_window == 0 || self.address_scan_window > ADDRESS_RANGE_MAX {
+            return Err(Error::InvalidParameter("address scan window".to_string()));
+        }
+        if self.address_scan_parallelism == 0 {
+            return Err(Error::InvalidParameter("address scan parallelism".to_string()));
+        }
+        if self.request_parallelism == 0 {
+            return Err(Error::InvalidParameter("request parallelism".to_string()));
+        }
+        if self.permanode_only && self.permanode.is_none() {
+            return Err(Error::InvalidParameter(
+                "permanode only without a permanode".to_string(),
+            ));
+        }
+        if self.offline {
+            if self.permanode.is_some() {
+                return Err(Error::InvalidParameter("permanode of an offline client".to_string()));
+            }
+            if !self.nodes.is_empty() {
+                return Err(Error::InvalidParameter("nodes of an offline client".to_string()));
+            }
+            if !self.network_overrides.bech32_hrp {
+                return Err(Error::MissingParameter(String::from(
+                    "bech32 HRP of the offline client",
+                )));
+            }
+        } else if self.nodes.is_empty() {
             match self.network_info.network {
                 Network::Testnet => {
                     let default_nodes = vec![
@@ -162,49 +576,168 @@ impl ClientBuilder {
             }
         }
 
-        let network_info = Arc::new(RwLock::new(self.network_info));
+        // the local PoW needs threads, see `with_local_pow`
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.network_info.local_pow = false;
+        }
+        // without the `pow` feature the PoW is done by the nodes or the nonce provider
+        #[cfg(not(feature = "pow"))]
+        {
+            self.network_info.local_pow = false;
+            self.local_pow_fallback = false;
+        }
+
+        let node_info_cache = Arc::new(NodeInfoCache::new(self.node_info_ttl));
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = &self.storage_path {
+            self.storage.replace(Arc::new(crate::FileStorage::new(path)?));
+        }
+        let storage = ClientStorage::new(self.storage.unwrap_or_else(|| Arc::new(MemoryStorage::new())));
+        let network_info = Arc::new(RwLock::new(NetworkState {
+            info: self.network_info,
+            overrides: self.network_overrides,
+            fetched: false,
+        }));
+        #[cfg(not(target_arch = "wasm32"))]
+        let http_client = match &self.ca_certificate {
+            Some(path) => reqwest::Client::builder()
+                .add_root_certificate(reqwest::Certificate::from_pem(&std::fs::read(path)?)?)
+                .build()?,
+            None => reqwest::Client::new(),
+        };
+        #[cfg(target_arch = "wasm32")]
+        let http_client = reqwest::Client::new();
         let nodes = self.nodes;
         let node_sync_interval = self.node_sync_interval;
+        // the node syncing uses the `get_info` API timeout so the client creation can't hang on a node
+        let node_info_timeout = *self.api_timeout.get(&Api::GetInfo).unwrap_or(&self.request_timeout);
 
-        let (runtime, sync, sync_kill_sender, network_info) = if self.node_sync_enabled {
-            let sync = Arc::new(RwLock::new(HashSet::new()));
-            let sync_ = sync.clone();
-            let network_info_ = network_info.clone();
-            let (sync_kill_sender, sync_kill_receiver) = channel(1);
-            let runtime = std::thread::spawn(move || {
-                let runtime = Runtime::new().unwrap();
-                runtime.block_on(Client::sync_nodes(&sync_, &nodes, &network_info_));
-                Client::start_sync_process(
-                    &runtime,
-                    sync_,
-                    nodes,
-                    node_sync_interval,
-                    network_info_,
-                    sync_kill_receiver,
-                );
-                runtime
-            })
-            .join()
-            .expect("failed to init node syncing process");
-            (Some(runtime), sync, Some(sync_kill_sender), network_info)
-        } else {
-            (None, Arc::new(RwLock::new(nodes)), None, network_info)
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut runtime = None;
+        // a browser has no thread to sync the nodes in the background, its nodes are used as with the syncing disabled
+        let (sync, pow_sync, sync_status, sync_kill_sender, network_info) = match self.node_sync_enabled {
+            #[cfg(not(target_arch = "wasm32"))]
+            true => {
+                let sync = Arc::new(RwLock::new(HashSet::new()));
+                let sync_ = sync.clone();
+                let pow_sync = Arc::new(RwLock::new(HashSet::new()));
+                let pow_sync_ = pow_sync.clone();
+                let sync_status = Arc::new(RwLock::new(HashMap::new()));
+                let sync_status_ = sync_status.clone();
+                let nodes_ = nodes.clone();
+                let network_info_ = network_info.clone();
+                let node_info_cache_ = node_info_cache.clone();
+                let http_client_ = http_client.clone();
+                let (sync_kill_sender, sync_kill_receiver) = channel(1);
+                let sync_runtime = std::thread::spawn(move || {
+                    let runtime = Runtime::new().unwrap();
+                    runtime.block_on(Client::sync_nodes(
+                        &http_client_,
+                        &sync_,
+                        &pow_sync_,
+                        &sync_status_,
+                        &nodes_,
+                        node_info_timeout,
+                        &network_info_,
+                        &node_info_cache_,
+                        node_sync_interval,
+                    ));
+                    Client::start_sync_process(
+                        &runtime,
+                        http_client_,
+                        sync_,
+                        pow_sync_,
+                        sync_status_,
+                        nodes_,
+                        node_sync_interval,
+                        node_info_timeout,
+                        network_info_,
+                        node_info_cache_,
+                        sync_kill_receiver,
+                    );
+                    runtime
+                })
+                .join()
+                .expect("failed to init node syncing process");
+                runtime.replace(sync_runtime);
+                (sync, pow_sync, sync_status, Some(sync_kill_sender), network_info)
+            }
+            _ => {
+                // every node is considered healthy and able to do remote PoW
+                let sync_status = nodes
+                    .iter()
+                    .map(|node| {
+                        let status = NodeSyncStatus {
+                            url: node.to_string(),
+                            synced: true,
+                            latest_milestone_index: None,
+                            solid_milestone_index: None,
+                            last_check: None,
+                        };
+                        (node.clone(), status)
+                    })
+                    .collect();
+                (
+                    Arc::new(RwLock::new(nodes.clone())),
+                    Arc::new(RwLock::new(nodes.clone())),
+                    Arc::new(RwLock::new(sync_status)),
+                    None,
+                    network_info,
+                )
+            }
         };
 
         let client = Client {
+            #[cfg(not(target_arch = "wasm32"))]
             runtime,
+            nodes,
+            node_options: self.node_options,
+            offline: self.offline,
             sync,
+            pow_sync,
+            sync_status,
+            node_index: Default::default(),
+            current_node: Default::default(),
+            pinned_node: Default::default(),
             sync_kill_sender: sync_kill_sender.map(Arc::new),
-            client: reqwest::Client::new(),
+            client: http_client,
             #[cfg(feature = "mqtt")]
             mqtt_client: None,
             #[cfg(feature = "mqtt")]
             mqtt_topic_handlers: Default::default(),
             #[cfg(feature = "mqtt")]
+            mqtt_event_handlers: Default::default(),
+            #[cfg(feature = "mqtt")]
             broker_options: self.broker_options,
+            #[cfg(all(feature = "mqtt", not(target_arch = "wasm32")))]
+            ca_certificate: self.ca_certificate,
             network_info,
             request_timeout: self.request_timeout,
             api_timeout: self.api_timeout,
+            rate_limit_retries: self.rate_limit_retries,
+            rate_limit_max_delay: self.rate_limit_max_delay,
+            node_auth: self.node_auth,
+            quorum: self.quorum,
+            quorum_size: self.quorum_size,
+            quorum_threshold: self.quorum_threshold,
+            quorum_milestone_validation: self.quorum_milestone_validation,
+            coordinator_keys: self.coordinator_keys,
+            validated_milestones: Default::default(),
+            address_scan_window: self.address_scan_window,
+            address_scan_parallelism: self.address_scan_parallelism,
+            request_parallelism: self.request_parallelism,
+            middlewares: self.middlewares,
+            nonce_provider: self.nonce_provider,
+            local_pow_fallback: self.local_pow_fallback,
+            permanode: self.permanode,
+            permanode_only: self.permanode_only,
+            node_info_cache,
+            address_reuse_check: self.address_reuse_check,
+            output_cache: self.output_cache.map(OutputCache::new),
+            storage,
+            #[cfg(feature = "metrics")]
+            metrics: Default::default(),
         };
 
         Ok(client)
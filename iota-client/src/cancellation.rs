@@ -0,0 +1,259 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The cancellation of the long-running operations, like a shutdown stopping the PoW or a `retry_until_included` loop.
+//!
+//! Any operation of the client is cancelled by running its future with [`CancellationToken::run`]: on
+//! [`CancellationToken::cancel`] the future is dropped, which aborts its in-flight requests and its sleeps, and the
+//! operation returns [`Error::Cancelled`]. The local PoW done while the future is polled observes the token too, its
+//! worker threads stop at their next batch of nonces. The MQTT subscriptions are cancelled with
+//! [`MqttTopicManager::with_cancellation`](crate::node::MqttTopicManager::with_cancellation).
+
+use crate::{Error, Result};
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+
+thread_local! {
+    // the tokens of the futures being polled on this thread, the innermost last
+    static CURRENT_TOKENS: RefCell<Vec<CancellationToken>> = RefCell::new(Vec::new());
+}
+
+type CancelHandler = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct Handlers {
+    next_id: u64,
+    // the wakers of the futures run with the token, by future
+    wakers: HashMap<u64, Waker>,
+    callbacks: HashMap<u64, CancelHandler>,
+}
+
+#[derive(Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    // the flag is set with the handlers locked, so a handler added concurrently is either run by `cancel` or sees it
+    handlers: Mutex<Handlers>,
+}
+
+/// A token cancelling the operations run with it, cloned to be cancelled from another task or thread, like a signal
+/// handler. A cancelled token stays cancelled.
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<TokenState>);
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl CancellationToken {
+    /// Creates a token which isn't cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the operations run with the token, they return [`Error::Cancelled`] once they are polled again.
+    pub fn cancel(&self) {
+        let (wakers, callbacks) = {
+            let mut handlers = self.0.handlers.lock().unwrap();
+            if self.0.cancelled.swap(true, Ordering::SeqCst) {
+                return;
+            }
+            (
+                std::mem::take(&mut handlers.wakers),
+                std::mem::take(&mut handlers.callbacks),
+            )
+        };
+        for (_, callback) in callbacks {
+            callback();
+        }
+        for (_, waker) in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Whether the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Returns [`Error::Cancelled`] if the token was cancelled, to check it between the steps of a blocking operation.
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Runs the future until it completes or the token is cancelled. A cancelled future is dropped and
+    /// [`Error::Cancelled`] is returned, a future run with an already cancelled token isn't polled.
+    pub fn run<F>(&self, future: F) -> Cancellable<F> {
+        Cancellable {
+            token: self.clone(),
+            future: Some(Box::pin(future)),
+            waker_id: None,
+        }
+    }
+
+    /// The token of the innermost [`CancellationToken::run`] polling a future on this thread, for a
+    /// [`NonceProvider`](crate::NonceProvider) to stop its own blocking PoW.
+    pub fn current() -> Option<Self> {
+        CURRENT_TOKENS.with(|tokens| tokens.borrow().last().cloned())
+    }
+
+    // Every token running a future polled on this thread, an outer one cancels the inner futures too.
+    pub(crate) fn current_tokens() -> Vec<Self> {
+        CURRENT_TOKENS.with(|tokens| tokens.borrow().clone())
+    }
+
+    // Returns `Error::Cancelled` if a token running a future polled on this thread was cancelled.
+    pub(crate) fn check_current() -> Result<()> {
+        CURRENT_TOKENS.with(|tokens| tokens.borrow().iter().try_for_each(CancellationToken::check))
+    }
+
+    // Calls the handler on cancellation, right away if the token is already cancelled. The handler is removed with the
+    // returned guard.
+    pub(crate) fn on_cancel<C: FnOnce() + Send + 'static>(&self, callback: C) -> CancelGuard {
+        let id = {
+            let mut handlers = self.0.handlers.lock().unwrap();
+            if self.is_cancelled() {
+                None
+            } else {
+                let id = handlers.next_id;
+                handlers.next_id += 1;
+                handlers.callbacks.insert(id, Box::new(callback));
+                Some(id)
+            }
+        };
+        match id {
+            Some(id) => CancelGuard {
+                token: self.clone(),
+                id: Some(id),
+            },
+            None => {
+                callback();
+                CancelGuard {
+                    token: self.clone(),
+                    id: None,
+                }
+            }
+        }
+    }
+
+    // Stores the waker of a future run with the token, false if the token is cancelled.
+    fn register_waker(&self, waker_id: &mut Option<u64>, waker: &Waker) -> bool {
+        let mut handlers = self.0.handlers.lock().unwrap();
+        if self.is_cancelled() {
+            return false;
+        }
+        let id = match waker_id {
+            Some(id) => *id,
+            None => {
+                let id = handlers.next_id;
+                handlers.next_id += 1;
+                *waker_id = Some(id);
+                id
+            }
+        };
+        match handlers.wakers.get(&id) {
+            Some(registered) if registered.will_wake(waker) => {}
+            _ => {
+                handlers.wakers.insert(id, waker.clone());
+            }
+        }
+        true
+    }
+
+    fn remove_waker(&self, id: u64) {
+        self.0.handlers.lock().unwrap().wakers.remove(&id);
+    }
+}
+
+/// Removes a cancellation handler on drop, see `CancellationToken::on_cancel`.
+pub(crate) struct CancelGuard {
+    token: CancellationToken,
+    id: Option<u64>,
+}
+
+impl CancelGuard {
+    // Keeps the handler for the lifetime of the token.
+    pub(crate) fn keep(mut self) {
+        self.id = None;
+    }
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.token.0.handlers.lock().unwrap().callbacks.remove(&id);
+        }
+    }
+}
+
+/// A future run with a [`CancellationToken`], see [`CancellationToken::run`].
+pub struct Cancellable<F> {
+    token: CancellationToken,
+    // dropped on cancellation
+    future: Option<Pin<Box<F>>>,
+    waker_id: Option<u64>,
+}
+
+impl<F, T, E> Future for Cancellable<F>
+where
+    F: Future<Output = std::result::Result<T, E>>,
+    E: From<Error>,
+{
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        if !this.token.register_waker(&mut this.waker_id, cx.waker()) {
+            this.future = None;
+            return Poll::Ready(Err(Error::Cancelled.into()));
+        }
+        let future = match this.future.as_mut() {
+            Some(future) => future,
+            None => return Poll::Ready(Err(Error::Cancelled.into())),
+        };
+        CURRENT_TOKENS.with(|tokens| tokens.borrow_mut().push(this.token.clone()));
+        // popped on unwind too, so a panicking future doesn't leave its token to the next one
+        let _current = CurrentToken;
+        // a token cancelled while the future is polled, like during a blocking PoW, wakes it to be dropped
+        let poll = future.as_mut().poll(cx);
+        if poll.is_ready() {
+            this.future = None;
+        }
+        poll
+    }
+}
+
+impl<F> Drop for Cancellable<F> {
+    fn drop(&mut self) {
+        if let Some(id) = self.waker_id {
+            self.token.remove_waker(id);
+        }
+    }
+}
+
+// Pops the token of the polled future.
+struct CurrentToken;
+
+impl Drop for CurrentToken {
+    fn drop(&mut self) {
+        CURRENT_TOKENS.with(|tokens| tokens.borrow_mut().pop());
+    }
+}
@@ -2,29 +2,48 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! The Client module to connect through HORNET or Bee with API usages
+#[cfg(feature = "metrics")]
+use crate::metrics::{self, Metrics, MetricsRecorder};
+#[cfg(feature = "pow")]
+use crate::pow::LocalPow;
 use crate::{
     api::*,
-    builder::{ClientBuilder, Network, NetworkInfo},
+    builder::{ClientBuilder, Network, NetworkInfo, NetworkState, NodeAuth, NodeOptions},
+    cancellation::CancellationToken,
     error::*,
+    middleware::{self, RequestInfo, RequestMiddleware, ResponseInfo},
     node::*,
     parse_response,
+    pow::{finish_pow, NonceProvider, RemotePow},
+    signer::SignerType,
+    storage::{self, ClientStorage, StateKey, StoredNodeInfo},
     types::*,
 };
 
-use bee_message::prelude::{Address, Ed25519Address, Message, MessageId, UTXOInput};
-use bee_pow::providers::{MinerBuilder, Provider as PowProvider, ProviderBuilder as PowProviderBuilder};
-use bee_signing_ext::Seed;
+use bee_common::packable::Packable;
+use bee_message::prelude::{
+    Address, Ed25519Address, Message, MessageId, MilestonePayload, Payload, TransactionId, TransactionPayload,
+    UTXOInput,
+};
+use bee_pow::providers::{Provider as PowProvider, ProviderBuilder as PowProviderBuilder};
+use bee_signing_ext::binary::{Ed25519PublicKey, Ed25519Signature as Ed25519EssenceSignature};
 
+use bech32::ToBase32;
 use blake2::{
     digest::{Update, VariableOutput},
     VarBlake2b,
 };
-#[cfg(feature = "mqtt")]
-use paho_mqtt::Client as MqttClient;
-use reqwest::{IntoUrl, Url};
+use futures::{stream::FuturesUnordered, Future, StreamExt, TryStreamExt};
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    IntoUrl, Method, Request, RequestBuilder, StatusCode, Url,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::broadcast::Sender;
+#[cfg(not(target_arch = "wasm32"))]
 use tokio::{
     runtime::Runtime,
-    sync::broadcast::{Receiver, Sender},
+    sync::broadcast::Receiver,
     time::{sleep, Duration as TokioDuration},
 };
 
@@ -33,16 +52,243 @@ use std::{
     convert::TryInto,
     hash::Hash,
     str::FromStr,
-    sync::{Arc, RwLock},
-    time::Duration,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+// `std::time::Instant` isn't available in a browser
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
 const ADDRESS_LENGTH: usize = 32;
+const ED25519_PUBLIC_KEY_LENGTH: usize = 32;
+const ED25519_SIGNATURE_LENGTH: usize = 64;
+/// Node feature required to submit messages without local PoW.
+const POW_FEATURE: &str = "PoW";
+/// The number of milestones a synced node may lag behind: its solid milestone behind its latest one, and its latest
+/// milestone behind the most recent one of the client nodes.
+pub const NODE_SYNC_MILESTONE_TOLERANCE: usize = 2;
+/// The maximum age of the latest milestone of a synced node, checked if the node reports the milestone timestamp.
+pub const NODE_SYNC_MAX_MILESTONE_AGE: Duration = Duration::from_secs(300);
+/// The default interval between the metadata polls of [`Client::retry_until_included`].
+pub const DEFAULT_RETRY_UNTIL_INCLUDED_INTERVAL: Duration = Duration::from_secs(5);
+/// The default number of metadata polls of [`Client::retry_until_included`].
+pub const DEFAULT_RETRY_UNTIL_INCLUDED_MAX_ATTEMPTS: u64 = 40;
+/// The default interval between the balance polls of [`Client::wait_for_balance`].
+pub const DEFAULT_WAIT_FOR_BALANCE_INTERVAL: Duration = Duration::from_secs(5);
+/// The default time [`Client::wait_for_balance`] waits for the balance.
+pub const DEFAULT_WAIT_FOR_BALANCE_TIMEOUT: Duration = Duration::from_secs(600);
+/// The delay before the first retry of a rate limited request without a `Retry-After` header, doubled by every retry.
+pub const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(1);
+/// The delay before the first reconnection attempt to a lost MQTT broker, doubled by every failed attempt.
+#[cfg(feature = "mqtt")]
+pub const MQTT_RECONNECT_MIN_DELAY: Duration = Duration::from_secs(1);
+/// The maximum delay between two reconnection attempts to a lost MQTT broker.
+#[cfg(feature = "mqtt")]
+pub const MQTT_RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
 
 #[cfg(feature = "mqtt")]
-type TopicHandler = Box<dyn Fn(&TopicEvent) + Send + Sync>;
+pub(crate) type TopicHandler = Box<dyn Fn(&TopicEvent) + Send + Sync>;
 #[cfg(feature = "mqtt")]
 pub(crate) type TopicHandlerMap = HashMap<Topic, Vec<Arc<TopicHandler>>>;
+#[cfg(feature = "mqtt")]
+pub(crate) type MqttEventHandler = Box<dyn Fn(&MqttEvent) + Send + Sync>;
+
+/// The info of the nodes with the time each was fetched, an info older than the TTL is expired.
+#[derive(Debug)]
+pub(crate) struct NodeInfoCache {
+    ttl: Duration,
+    infos: RwLock<HashMap<Url, (Instant, NodeInfo)>>,
+}
+
+impl NodeInfoCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            infos: Default::default(),
+        }
+    }
+
+    pub(crate) fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// The info of a node, if it's not expired.
+    pub(crate) fn get(&self, node: &Url) -> Option<NodeInfo> {
+        self.get_max_age(node, self.ttl)
+    }
+
+    /// The info of a node, if it's not expired and was fetched less than `max_age` ago.
+    pub(crate) fn get_max_age(&self, node: &Url, max_age: Duration) -> Option<NodeInfo> {
+        match self.infos.read().unwrap().get(node) {
+            Some((fetched_at, info)) if fetched_at.elapsed() < self.ttl.min(max_age) => Some(info.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, node: &Url, info: &NodeInfo) {
+        if self.ttl > Duration::from_secs(0) {
+            self.infos
+                .write()
+                .unwrap()
+                .insert(node.clone(), (Instant::now(), info.clone()));
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        self.infos.write().unwrap().clear();
+    }
+}
+
+/// The outputs found by the output lookups with the time each was fetched. The spent outputs never expire, the
+/// unspent outputs expire after the TTL.
+#[derive(Debug)]
+pub(crate) struct OutputCache {
+    unspent_ttl: Duration,
+    outputs: RwLock<HashMap<UTXOInput, (Instant, OutputMetadata)>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl OutputCache {
+    pub(crate) fn new(unspent_ttl: Duration) -> Self {
+        Self {
+            unspent_ttl,
+            outputs: Default::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The output, if it's spent or not expired. Counts the hit or the miss.
+    pub(crate) fn get(&self, output_id: &UTXOInput) -> Option<OutputMetadata> {
+        let output = match self.outputs.read().unwrap().get(output_id) {
+            Some((_, output)) if output.is_spent => Some(output.clone()),
+            Some((fetched_at, output)) if fetched_at.elapsed() < self.unspent_ttl => Some(output.clone()),
+            _ => None,
+        };
+        let counter = if output.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        output
+    }
+
+    pub(crate) fn insert(&self, output_id: &UTXOInput, output: &OutputMetadata) {
+        if output.is_spent || self.unspent_ttl > Duration::from_secs(0) {
+            self.outputs
+                .write()
+                .unwrap()
+                .insert(output_id.clone(), (Instant::now(), output.clone()));
+        }
+    }
+
+    pub(crate) fn stats(&self) -> OutputCacheStats {
+        let outputs = self.outputs.read().unwrap();
+        let spent = outputs.values().filter(|(_, output)| output.is_spent).count();
+        OutputCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            spent,
+            unspent: outputs.len() - spent,
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        self.outputs.write().unwrap().clear();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+}
+
+/// The statistics of the output cache, see [`ClientBuilder::with_output_cache`].
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct OutputCacheStats {
+    /// The output lookups answered by the cache
+    pub hits: u64,
+    /// The output lookups sent to the node
+    pub misses: u64,
+    /// The cached spent outputs
+    pub spent: usize,
+    /// The cached unspent outputs, including the expired ones
+    pub unspent: usize,
+}
+
+/// The response of a node request, its errors carry the request, see [`RequestContext`].
+pub(crate) struct NodeResponse {
+    response: reqwest::Response,
+    method: Method,
+    url: Url,
+    start: Instant,
+}
+
+impl NodeResponse {
+    // Sends a request without the middlewares and the metrics, as the node syncing.
+    pub(crate) async fn send(http_client: &reqwest::Client, request: RequestBuilder) -> Result<Self> {
+        Self::execute(http_client, request.build()?).await
+    }
+
+    async fn execute(http_client: &reqwest::Client, request: Request) -> Result<Self> {
+        let (method, url) = (request.method().clone(), request.url().clone());
+        let start = Instant::now();
+        match http_client.execute(request).await {
+            Ok(response) => Ok(Self {
+                response,
+                method,
+                url,
+                start,
+            }),
+            Err(error) => Err(Error::request(
+                error,
+                RequestContext::new(&method, &url, start.elapsed()),
+            )),
+        }
+    }
+
+    pub(crate) fn status(&self) -> StatusCode {
+        self.response.status()
+    }
+
+    /// The request of the response, the time elapsed until now.
+    pub(crate) fn context(&self) -> RequestContext {
+        RequestContext::new(&self.method, &self.url, self.start.elapsed())
+    }
+
+    pub(crate) async fn json<T: DeserializeOwned>(self) -> Result<T> {
+        let context = self.context();
+        self.response
+            .json()
+            .await
+            .map_err(|error| Error::request(error, context))
+    }
+
+    pub(crate) async fn text(self) -> Result<String> {
+        let context = self.context();
+        self.response
+            .text()
+            .await
+            .map_err(|error| Error::request(error, context))
+    }
+
+    // The message of a message answer, a message the client can't parse fails with its id.
+    pub(crate) async fn message(self, message_id: &MessageId) -> Result<Message> {
+        let text = self.text().await?;
+        let invalid = |error: Error| Error::InvalidMessage(message_id.to_string(), Box::new(error));
+        let message = serde_json::from_str::<Response<MessageJson>>(&text).map_err(|error| invalid(error.into()))?;
+        message.data.try_into().map_err(invalid)
+    }
+
+    pub(crate) async fn bytes(self) -> Result<Vec<u8>> {
+        let context = self.context();
+        match self.response.bytes().await {
+            Ok(bytes) => Ok(bytes.to_vec()),
+            Err(error) => Err(Error::request(error, context)),
+        }
+    }
+}
 
 /// An event from a MQTT topic.
 #[cfg(feature = "mqtt")]
@@ -52,6 +298,149 @@ pub struct TopicEvent {
     pub topic: String,
     /// The MQTT event payload.
     pub payload: String,
+    /// The MQTT event payload bytes, the packed message of the `messages` topics isn't valid UTF-8.
+    #[serde(skip)]
+    pub payload_bytes: Vec<u8>,
+}
+
+#[cfg(feature = "mqtt")]
+impl TopicEvent {
+    /// The output of an `addresses/.../outputs` or `outputs/...` event, a signature locked single or dust allowance
+    /// output.
+    pub fn output(&self) -> Result<OutputMetadata> {
+        let raw: RawOutput = serde_json::from_str(&self.payload)?;
+        output_metadata(
+            raw,
+            &[
+                OutputType::SignatureLockedSingle,
+                OutputType::SignatureLockedDustAllowance,
+            ],
+        )
+    }
+
+    /// The message of a `messages` or `messages/indexation/...` event.
+    pub fn message(&self) -> Result<Message> {
+        let mut reader = self.payload_bytes.as_slice();
+        Ok(Message::unpack(&mut reader)?)
+    }
+}
+
+// The addresses without the repeated ones, in the order of their first occurrence.
+fn unique_addresses(addresses: &[Bech32Address]) -> Vec<&Bech32Address> {
+    let mut seen_addresses = HashSet::new();
+    addresses
+        .iter()
+        .filter(|address| seen_addresses.insert(address.0.as_str()))
+        .collect()
+}
+
+// The delay a rate limited node asks for with its `Retry-After` header, in seconds or until an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+// The URL of a route of the node API, its path must stay under `/api/`
+fn node_api_url(node: &Url, path: &str) -> Result<Url> {
+    let invalid = |reason: &str| Error::InvalidParameter(format!("path: {} {}", path, reason));
+    let route = path.strip_prefix("/api/").ok_or_else(|| invalid("isn't under /api/"))?;
+    if route.contains(|c| c == '?' || c == '#' || c == '\\') {
+        return Err(invalid(
+            "has a query, a fragment or a backslash, the query is given apart",
+        ));
+    }
+    // the percent-encoded dots are decoded by the nodes
+    if route.split('/').any(|segment| {
+        let segment = segment.to_lowercase().replace("%2e", ".");
+        segment.is_empty() || segment == "." || segment == ".."
+    }) {
+        return Err(invalid("has an empty, `.` or `..` segment"));
+    }
+    let mut url = node.clone();
+    url.set_path(path);
+    Ok(url)
+}
+
+// The pruned data of a route of the node API, a not found answer then maps to `Error::PrunedData` like the typed calls
+fn pruned_request(path: &str) -> Option<PrunedRequest> {
+    let segments: Vec<&str> = path.strip_prefix("/api/v1/")?.split('/').collect();
+    match segments.as_slice() {
+        ["milestones", index] => index.parse().ok().map(PrunedRequest::Milestone),
+        ["milestones", index, "utxo-changes"] => index.parse().ok().map(PrunedRequest::MilestoneUtxoChanges),
+        ["messages", message_id, ..] if MessageId::from_str(message_id).is_ok() => {
+            Some(PrunedRequest::Message(message_id.to_string()))
+        }
+        _ => None,
+    }
+}
+
+// The unix time in seconds.
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+// The output metadata of a node output, failing for the other output types.
+fn output_metadata(raw: RawOutput, output_types: &[OutputType]) -> Result<OutputMetadata> {
+    let is_output_type = output_types
+        .iter()
+        .any(|output_type| output_type.kind() == raw.output.type_);
+    if !is_output_type || raw.output.address.type_ != 1 {
+        return Err(Error::InvalidParameter("address type".to_string()));
+    }
+    let mut address = [0u8; ADDRESS_LENGTH];
+    hex::decode_to_slice(raw.output.address.address, &mut address)?;
+    Ok(OutputMetadata {
+        message_id: hex::decode(raw.message_id)?,
+        transaction_id: hex::decode(raw.transaction_id)?,
+        output_index: raw.output_index,
+        is_spent: raw.is_spent,
+        amount: checked_amount(raw.output.amount)?,
+        address: Address::from(Ed25519Address::from(address)),
+    })
+}
+
+/// A change of the state of the MQTT broker connection.
+///
+/// The events published by the broker while the connection is lost are missed, but the subscribed topics are
+/// subscribed again once the client is reconnected.
+#[cfg(feature = "mqtt")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MqttEvent {
+    /// The client is connected to the broker, sent on the first connection and after every reconnection.
+    Connected,
+    /// The connection to the broker is lost.
+    Disconnected,
+    /// The client waits `delay` before its reconnection attempt number `attempt`, starting at 1.
+    Reconnecting {
+        /// The number of the attempt.
+        attempt: u32,
+        /// The delay before the attempt.
+        delay: Duration,
+    },
+    /// The client couldn't reconnect within the reconnection window of the broker options, it's the last event of
+    /// the connection.
+    Error(String),
+}
+
+/// The credentials of an MQTT broker requiring authentication.
+#[cfg(feature = "mqtt")]
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct BrokerAuth {
+    /// The user name
+    pub username: String,
+    /// The password
+    pub password: String,
 }
 
 /// The MQTT broker options.
@@ -64,6 +453,14 @@ pub struct BrokerOptions {
     pub(crate) timeout: Duration,
     #[serde(default = "default_use_ws")]
     pub(crate) use_ws: bool,
+    #[serde(default = "default_broker_automatic_reconnect", rename = "automaticReconnect")]
+    pub(crate) automatic_reconnect: bool,
+    #[serde(default = "default_broker_reconnect_window", rename = "reconnectWindow")]
+    pub(crate) reconnect_window: Duration,
+    #[serde(default, rename = "brokerUrl")]
+    pub(crate) broker_url: Option<String>,
+    #[serde(default)]
+    pub(crate) auth: Option<BrokerAuth>,
 }
 
 #[cfg(feature = "mqtt")]
@@ -81,6 +478,16 @@ fn default_use_ws() -> bool {
     true
 }
 
+#[cfg(feature = "mqtt")]
+fn default_broker_automatic_reconnect() -> bool {
+    true
+}
+
+#[cfg(feature = "mqtt")]
+fn default_broker_reconnect_window() -> Duration {
+    Duration::from_secs(300)
+}
+
 #[cfg(feature = "mqtt")]
 impl Default for BrokerOptions {
     fn default() -> Self {
@@ -88,6 +495,10 @@ impl Default for BrokerOptions {
             automatic_disconnect: default_broker_automatic_disconnect(),
             timeout: default_broker_timeout(),
             use_ws: default_use_ws(),
+            automatic_reconnect: default_broker_automatic_reconnect(),
+            reconnect_window: default_broker_reconnect_window(),
+            broker_url: None,
+            auth: None,
         }
     }
 }
@@ -116,12 +527,47 @@ impl BrokerOptions {
         self.use_ws = use_ws;
         self
     }
+
+    /// Whether a lost broker connection is reconnected, with an exponential backoff, or not. Enabled by default.
+    pub fn automatic_reconnect(mut self, automatic_reconnect: bool) -> Self {
+        self.automatic_reconnect = automatic_reconnect;
+        self
+    }
+
+    /// Sets how long the client tries to reconnect a lost broker connection before giving up with an
+    /// [`MqttEvent::Error`], 5 minutes by default.
+    pub fn reconnect_window(mut self, reconnect_window: Duration) -> Self {
+        self.reconnect_window = reconnect_window;
+        self
+    }
+
+    /// Sets the URL of the broker, used instead of the brokers derived from the node URLs. Its scheme is `mqtt`,
+    /// `mqtts` (TLS), `ws` or `wss` (TLS), the default ports are 1883, 8883, 80 and 443, and the default websocket path
+    /// is `/mqtt`.
+    pub fn broker_url(mut self, url: &str) -> Self {
+        self.broker_url.replace(url.to_string());
+        self
+    }
+
+    /// Sets the credentials of a broker requiring authentication.
+    pub fn auth(mut self, username: &str, password: &str) -> Self {
+        self.auth.replace(BrokerAuth {
+            username: username.to_string(),
+            password: password.to_string(),
+        });
+        self
+    }
 }
 
 /// The miner builder.
 #[derive(Default)]
 pub struct ClientMinerBuilder {
     local_pow: bool,
+    worker_count: Option<usize>,
+    nonce_provider: Option<Arc<dyn NonceProvider>>,
+    local_pow_fallback: bool,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<MetricsRecorder>>,
 }
 
 impl ClientMinerBuilder {
@@ -130,6 +576,31 @@ impl ClientMinerBuilder {
         self.local_pow = value;
         self
     }
+
+    /// Sets the number of threads used by the local PoW, defaults to the number of CPUs.
+    pub fn with_worker_count(mut self, worker_count: usize) -> Self {
+        self.worker_count.replace(worker_count);
+        self
+    }
+
+    /// Sets the provider the PoW is outsourced to, used instead of the local or the remote PoW.
+    pub fn with_nonce_provider(mut self, nonce_provider: Arc<dyn NonceProvider>) -> Self {
+        self.nonce_provider.replace(nonce_provider);
+        self
+    }
+
+    /// Sets whether the local PoW is done when the outsourced provider fails, false by default.
+    pub fn with_local_pow_fallback(mut self, local_pow_fallback: bool) -> Self {
+        self.local_pow_fallback = local_pow_fallback;
+        self
+    }
+
+    // Records the duration of the local PoW in the metrics of a client.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn with_metrics(mut self, metrics: Arc<MetricsRecorder>) -> Self {
+        self.metrics.replace(metrics);
+        self
+    }
 }
 
 impl PowProviderBuilder for ClientMinerBuilder {
@@ -142,13 +613,64 @@ impl PowProviderBuilder for ClientMinerBuilder {
     fn finish(self) -> ClientMiner {
         ClientMiner {
             local_pow: self.local_pow,
+            worker_count: self.worker_count,
+            nonce_provider: self.nonce_provider,
+            local_pow_fallback: self.local_pow_fallback,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
         }
     }
 }
 
-/// The miner used for PoW
+/// The miner used for PoW: the outsourced nonce provider if set, otherwise the local or the remote PoW.
 pub struct ClientMiner {
     local_pow: bool,
+    worker_count: Option<usize>,
+    nonce_provider: Option<Arc<dyn NonceProvider>>,
+    local_pow_fallback: bool,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<MetricsRecorder>>,
+}
+
+impl ClientMiner {
+    fn local_nonce(&self, bytes: &[u8], target_score: f64) -> Result<u64> {
+        #[cfg(feature = "metrics")]
+        let start = Instant::now();
+        let nonce = local_pow(self.worker_count, bytes, target_score);
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.record_pow(start.elapsed());
+        }
+        nonce
+    }
+}
+
+#[cfg(feature = "pow")]
+fn local_pow(worker_count: Option<usize>, bytes: &[u8], target_score: f64) -> Result<u64> {
+    LocalPow::new(worker_count.unwrap_or_else(num_cpus::get)).nonce(bytes, target_score)
+}
+
+// Without the `pow` feature the client is built without the local PoW and its fallback, see `ClientBuilder::finish`.
+#[cfg(not(feature = "pow"))]
+fn local_pow(_worker_count: Option<usize>, bytes: &[u8], target_score: f64) -> Result<u64> {
+    RemotePow.nonce(bytes, target_score)
+}
+
+impl NonceProvider for ClientMiner {
+    fn nonce(&self, bytes: &[u8], target_score: f64) -> Result<u64> {
+        match &self.nonce_provider {
+            // a provider returning once its operation is cancelled doesn't fall back to the local PoW
+            Some(nonce_provider) => match nonce_provider.nonce(bytes, target_score) {
+                Ok(nonce) => CancellationToken::check_current().map(|_| nonce),
+                Err(_) if CancellationToken::check_current().is_err() => Err(Error::Cancelled),
+                Err(_) if self.local_pow_fallback => self.local_nonce(bytes, target_score),
+                Err(Error::NonceProvider(error)) => Err(Error::NonceProvider(error)),
+                Err(error) => Err(Error::NonceProvider(error.to_string())),
+            },
+            None if self.local_pow => self.local_nonce(bytes, target_score),
+            None => RemotePow.nonce(bytes, target_score),
+        }
+    }
 }
 
 impl PowProvider for ClientMiner {
@@ -156,20 +678,12 @@ impl PowProvider for ClientMiner {
     type Error = crate::Error;
 
     fn nonce(&self, bytes: &[u8], target_score: f64) -> std::result::Result<u64, Self::Error> {
-        if self.local_pow {
-            MinerBuilder::new()
-                .with_num_workers(num_cpus::get())
-                .finish()
-                .nonce(bytes, target_score)
-                .map_err(|e| crate::Error::Pow(e.to_string()))
-        } else {
-            Ok(0)
-        }
+        NonceProvider::nonce(self, bytes, target_score)
     }
 }
 
 /// Each of the node APIs the client uses.
-#[derive(Eq, PartialEq, Hash)]
+#[derive(Clone, Copy, Eq, PartialEq, Hash)]
 pub enum Api {
     /// `get_health` API
     GetHealth,
@@ -183,6 +697,12 @@ pub enum Api {
     GetOutput,
     /// `get_milestone` API
     GetMilestone,
+    /// `get_message` APIs
+    GetMessage,
+    /// `get_address` APIs
+    GetAddress,
+    /// `get_peers` API
+    GetPeers,
 }
 
 impl FromStr for Api {
@@ -196,6 +716,9 @@ impl FromStr for Api {
             "PostMessage" => Self::PostMessage,
             "GetOutput" => Self::GetOutput,
             "GetMilestone" => Self::GetMilestone,
+            "GetMessage" => Self::GetMessage,
+            "GetAddress" => Self::GetAddress,
+            "GetPeers" => Self::GetPeers,
             _ => return Err(format!("unknown api kind `{}`", s)),
         };
         Ok(t)
@@ -205,31 +728,103 @@ impl FromStr for Api {
 /// An instance of the client using HORNET or Bee URI
 pub struct Client {
     #[allow(dead_code)]
+    #[cfg(not(target_arch = "wasm32"))]
     pub(crate) runtime: Option<Runtime>,
+    /// Every IOTA node added to the client
+    pub(crate) nodes: HashSet<Url>,
+    /// The credentials and the roles of the nodes added with options
+    pub(crate) node_options: HashMap<Url, NodeOptions>,
+    /// Whether the client is offline, without any node
+    pub(crate) offline: bool,
     /// Node pool of synced IOTA nodes
     pub(crate) sync: Arc<RwLock<HashSet<Url>>>,
+    /// Synced IOTA nodes with the PoW feature, used to submit messages without local PoW
+    pub(crate) pow_sync: Arc<RwLock<HashSet<Url>>>,
+    /// Sync status of every IOTA node, as of the last node sync
+    pub(crate) sync_status: Arc<RwLock<HashMap<Url, NodeSyncStatus>>>,
+    /// Round-robin index of the next node to use from the pool
+    pub(crate) node_index: AtomicUsize,
+    /// The last node used for a request
+    pub(crate) current_node: RwLock<Option<Url>>,
+    /// A node pinned by the user, used for every request until it is unpinned
+    pub(crate) pinned_node: RwLock<Option<Url>>,
     /// Flag to stop the node syncing
     pub(crate) sync_kill_sender: Option<Arc<Sender<()>>>,
     /// A reqwest Client to make Requests with
     pub(crate) client: reqwest::Client,
-    /// A MQTT client to subscribe/unsubscribe to topics.
+    /// A MQTT client to subscribe/unsubscribe to topics, shared with the thread polling its events.
     #[cfg(feature = "mqtt")]
-    pub(crate) mqtt_client: Option<MqttClient>,
+    pub(crate) mqtt_client: Option<Arc<MqttConnection>>,
     #[cfg(feature = "mqtt")]
     pub(crate) mqtt_topic_handlers: Arc<RwLock<TopicHandlerMap>>,
+    /// The handlers of the MQTT connection events, kept for the lifetime of the client
+    #[cfg(feature = "mqtt")]
+    pub(crate) mqtt_event_handlers: Arc<RwLock<Vec<Arc<MqttEventHandler>>>>,
     #[cfg(feature = "mqtt")]
     pub(crate) broker_options: BrokerOptions,
-    pub(crate) network_info: Arc<RwLock<NetworkInfo>>,
+    /// The CA certificate trusted by the TLS broker connections, as by the node requests
+    #[cfg(all(feature = "mqtt", not(target_arch = "wasm32")))]
+    pub(crate) ca_certificate: Option<std::path::PathBuf>,
+    /// The network info, reported by the nodes or set with the builder
+    pub(crate) network_info: Arc<RwLock<NetworkState>>,
     /// HTTP request timeout.
     pub(crate) request_timeout: Duration,
     /// HTTP request timeout for each API call.
     pub(crate) api_timeout: HashMap<Api, Duration>,
+    /// Credentials of the node routes that require authentication
+    pub(crate) node_auth: Option<NodeAuth>,
+    /// Whether the balance and the output queries are cross-checked by several nodes
+    pub(crate) quorum: bool,
+    /// Number of nodes queried by a quorum request
+    pub(crate) quorum_size: usize,
+    /// Percentage of the quorum nodes that must agree on the answer
+    pub(crate) quorum_threshold: usize,
+    /// Whether the quorum answers are only accepted from the nodes whose latest milestone is valid
+    pub(crate) quorum_milestone_validation: bool,
+    /// The public keys of the coordinator the milestones are validated against
+    pub(crate) coordinator_keys: CoordinatorKeys,
+    /// The latest milestone index validated for each node
+    pub(crate) validated_milestones: RwLock<HashMap<Url, u64>>,
+    /// Number of address indexes derived and queried at once by the address scans
+    pub(crate) address_scan_window: usize,
+    /// Number of concurrent node requests of the address scans
+    pub(crate) address_scan_parallelism: usize,
+    /// Number of concurrent node requests of the batched lookups
+    pub(crate) request_parallelism: usize,
+    /// The provider the PoW is outsourced to
+    pub(crate) nonce_provider: Option<Arc<dyn NonceProvider>>,
+    /// Whether the local PoW is done when the outsourced provider fails
+    pub(crate) local_pow_fallback: bool,
+    /// The info of every node, shared with the node syncing
+    pub(crate) node_info_cache: Arc<NodeInfoCache>,
+    /// Number of retries of a rate limited request
+    pub(crate) rate_limit_retries: usize,
+    /// The longest delay a rate limited request is retried after
+    pub(crate) rate_limit_max_delay: Duration,
+    /// Hooks invoked around every node request
+    pub(crate) middlewares: Vec<Box<dyn RequestMiddleware>>,
+    /// The permanode of the historical queries, with a trailing slash
+    pub(crate) permanode: Option<Url>,
+    /// Whether the historical queries are only sent to the permanode
+    pub(crate) permanode_only: bool,
+    /// Whether the transfers fail if their input addresses already spent outputs
+    pub(crate) address_reuse_check: bool,
+    /// The outputs found by the output lookups, if the cache is enabled
+    pub(crate) output_cache: Option<OutputCache>,
+    /// The client state kept across restarts
+    pub(crate) storage: ClientStorage,
+    /// The metrics of the node requests, the PoW and the retries
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Arc<MetricsRecorder>,
 }
 
 impl std::fmt::Debug for Client {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut d = f.debug_struct("Client");
-        d.field("sync", &self.sync).field("client", &self.client);
+        d.field("sync", &self.sync)
+            .field("pow_sync", &self.pow_sync)
+            .field("pinned_node", &self.pinned_node)
+            .field("client", &self.client);
         #[cfg(feature = "mqtt")]
         d.field("broker_options", &self.broker_options);
         d.field("network_info", &self.network_info).finish()
@@ -243,6 +838,7 @@ impl Drop for Client {
             sender.send(()).expect("failed to stop syncing process");
         }
 
+        #[cfg(not(target_arch = "wasm32"))]
         if let Some(runtime) = self.runtime.take() {
             runtime.shutdown_background();
         }
@@ -263,12 +859,19 @@ impl Client {
     }
 
     /// Sync the node lists per node_sync_interval milliseconds
+    #[allow(clippy::too_many_arguments)]
+    #[cfg(not(target_arch = "wasm32"))]
     pub(crate) fn start_sync_process(
         runtime: &Runtime,
+        http_client: reqwest::Client,
         sync: Arc<RwLock<HashSet<Url>>>,
+        pow_sync: Arc<RwLock<HashSet<Url>>>,
+        sync_status: Arc<RwLock<HashMap<Url, NodeSyncStatus>>>,
         nodes: HashSet<Url>,
         node_sync_interval: Duration,
-        network_info: Arc<RwLock<NetworkInfo>>,
+        node_info_timeout: Duration,
+        network_info: Arc<RwLock<NetworkState>>,
+        node_info_cache: Arc<NodeInfoCache>,
         mut kill: Receiver<()>,
     ) {
         let node_sync_interval = TokioDuration::from_nanos(node_sync_interval.as_nanos().try_into().unwrap());
@@ -280,76 +883,472 @@ impl Client {
                             // delay first since the first `sync_nodes` call is made by the builder
                             // to ensure the node list is filled before the client is used
                             sleep(node_sync_interval).await;
-                            Client::sync_nodes(&sync, &nodes, &network_info).await;
+                            Client::sync_nodes(
+                                &http_client,
+                                &sync,
+                                &pow_sync,
+                                &sync_status,
+                                &nodes,
+                                node_info_timeout,
+                                &network_info,
+                                &node_info_cache,
+                                node_sync_interval,
+                            )
+                            .await;
                     } => {}
-                    _ = kill.recv() => {}
+                    _ = kill.recv() => break,
                 }
             }
         });
     }
 
+    /// Checks every node with `get_node_info`, concurrently: synced nodes are (re-)admitted to the synced node pool and
+    /// the others are removed until a later sync. A node is synced if it's healthy, on the client network, and its
+    /// milestones are up to date, see [`Client::is_node_synced`]. The info of a node cached less than
+    /// `node_info_max_age` ago isn't fetched again, the sync interval so the sync still sees the info of every interval.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn sync_nodes(
+        http_client: &reqwest::Client,
         sync: &Arc<RwLock<HashSet<Url>>>,
+        pow_sync: &Arc<RwLock<HashSet<Url>>>,
+        sync_status: &Arc<RwLock<HashMap<Url, NodeSyncStatus>>>,
         nodes: &HashSet<Url>,
-        network_info: &Arc<RwLock<NetworkInfo>>,
+        node_info_timeout: Duration,
+        network_info: &Arc<RwLock<NetworkState>>,
+        node_info_cache: &NodeInfoCache,
+        node_info_max_age: Duration,
     ) {
-        let mut synced_nodes = HashSet::new();
+        let infos = futures::future::join_all(nodes.iter().map(|node_url| async move {
+            if let Some(info) = node_info_cache.get_max_age(node_url, node_info_max_age) {
+                return (node_url, Some(info));
+            }
+            let info = Client::get_node_info_with_timeout(http_client, node_url.clone(), node_info_timeout).await;
+            if let Ok(info) = &info {
+                node_info_cache.insert(node_url, info);
+            }
+            (node_url, info.ok())
+        }))
+        .await;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+
+        // the nodes lagging behind the most recent milestone of the healthy nodes aren't synced
+        let (network, network_id) = {
+            let network_info = network_info.read().unwrap();
+            let network_id = Some(network_info.info.network_id.clone()).filter(|_| network_info.overrides.network_id);
+            (network_info.info.network.clone(), network_id)
+        };
+        let network_id = network_id.as_deref();
+        let latest_milestone_index = infos
+            .iter()
+            .filter_map(|(_, info)| info.as_ref())
+            .filter(|info| info.is_healthy && Client::is_network_node(info, &network, network_id))
+            .map(|info| info.latest_milestone_index)
+            .max()
+            .unwrap_or_default();
 
-        for node_url in nodes {
-            // Put the healty node url into the synced_nodes
-            if let Ok(info) = Client::get_node_info(node_url.clone()).await {
-                if info.is_healthy {
-                    if network_info.read().unwrap().network == Network::Testnet && info.network_id == "mainnet"
-                        || network_info.read().unwrap().network == Network::Mainnet && info.network_id != "mainnet"
-                    {
-                        continue;
-                    }
-                    let mut client_network_info = network_info.write().unwrap();
-                    client_network_info.min_pow_score = info.min_pow_score;
-                    if !client_network_info.local_pow {
-                        if info.features.contains(&"PoW".to_string()) {
-                            synced_nodes.insert(node_url.clone());
-                        }
-                    } else {
-                        synced_nodes.insert(node_url.clone());
-                    }
+        let mut synced_nodes = HashSet::new();
+        let mut pow_nodes = HashSet::new();
+        let mut status = HashMap::new();
+        for (node_url, info) in infos {
+            let synced = info.as_ref().map_or(false, |info| {
+                Client::is_network_node(info, &network, network_id)
+                    && Client::is_node_synced(info, latest_milestone_index, now)
+            });
+            if let (true, Some(info)) = (synced, &info) {
+                Client::update_network_info(network_info, info);
+                if info.features.iter().any(|feature| feature == POW_FEATURE) {
+                    pow_nodes.insert(node_url.clone());
                 }
+                synced_nodes.insert(node_url.clone());
             }
+            status.insert(
+                node_url.clone(),
+                NodeSyncStatus {
+                    url: node_url.to_string(),
+                    synced,
+                    latest_milestone_index: info.as_ref().map(|info| info.latest_milestone_index),
+                    solid_milestone_index: info.as_ref().map(|info| info.solid_milestone_index),
+                    last_check: Some(now.as_millis() as u64),
+                },
+            );
         }
 
-        // Update the sync list
+        // Update the sync lists
         *sync.write().unwrap() = synced_nodes;
+        *pow_sync.write().unwrap() = pow_nodes;
+        *sync_status.write().unwrap() = status;
+    }
+
+    // Whether a node is on the network of the client: the network id set with the builder if any, otherwise a testnet
+    // client doesn't use mainnet nodes and vice versa.
+    fn is_network_node(info: &NodeInfo, network: &Network, network_id: Option<&str>) -> bool {
+        if let Some(network_id) = network_id {
+            return info.network_id == network_id;
+        }
+        match network {
+            Network::Testnet => info.network_id != "mainnet",
+            Network::Mainnet => info.network_id == "mainnet",
+        }
+    }
+
+    /// Whether a node is healthy with up to date milestones: its solid milestone and its latest milestone are within
+    /// [`NODE_SYNC_MILESTONE_TOLERANCE`] of its latest milestone and of `latest_milestone_index`, the most recent
+    /// milestone of the client nodes, and its latest milestone isn't older than [`NODE_SYNC_MAX_MILESTONE_AGE`] at
+    /// `now`, a duration since the Unix epoch.
+    pub(crate) fn is_node_synced(info: &NodeInfo, latest_milestone_index: usize, now: Duration) -> bool {
+        info.is_healthy
+            && info.solid_milestone_index + NODE_SYNC_MILESTONE_TOLERANCE >= info.latest_milestone_index
+            && info.latest_milestone_index + NODE_SYNC_MILESTONE_TOLERANCE >= latest_milestone_index
+            && info.latest_milestone_timestamp.map_or(true, |timestamp| {
+                now.as_secs().saturating_sub(timestamp) <= NODE_SYNC_MAX_MILESTONE_AGE.as_secs()
+            })
     }
 
-    /// Get a node candidate from the synced node pool.
+    /// Get a node candidate from the synced node pool, round-robin, or the pinned node.
     pub(crate) fn get_node(&self) -> Result<Url> {
-        let pool = self.sync.read().unwrap();
-        Ok(pool.iter().next().ok_or(Error::SyncedNodePoolEmpty)?.clone())
+        self.select_node(&self.sync, |_| true, Error::SyncedNodePoolEmpty)
+    }
+
+    /// Get a node candidate to submit a message, among the nodes with the submit role: with remote PoW, only nodes
+    /// with the PoW feature and the remote PoW role are used.
+    pub(crate) fn get_submit_node(&self) -> Result<Url> {
+        // the messages of an outsourced PoW hold their nonce
+        if self.network_info.read().unwrap().info.local_pow || self.nonce_provider.is_some() {
+            self.select_node(&self.sync, |options| options.submit, Error::SubmitNodePoolEmpty)
+        } else {
+            self.select_node(
+                &self.pow_sync,
+                |options| options.submit && options.remote_pow,
+                Error::PowNodePoolEmpty,
+            )
+        }
+    }
+
+    /// Whether the node has the role, every role of the nodes added without options.
+    pub(crate) fn has_node_role(&self, node: &Url, role: impl Fn(&NodeOptions) -> bool) -> bool {
+        self.node_options.get(node).map_or(true, role)
+    }
+
+    fn select_node(
+        &self,
+        pool: &RwLock<HashSet<Url>>,
+        role: impl Fn(&NodeOptions) -> bool,
+        empty_error: Error,
+    ) -> Result<Url> {
+        if self.offline {
+            return Err(Error::OfflineClient);
+        }
+        let node = match self.pinned_node.read().unwrap().clone() {
+            Some(node) => node,
+            None => {
+                let pool = pool.read().unwrap();
+                let mut pool: Vec<&Url> = pool.iter().filter(|node| self.has_node_role(node, &role)).collect();
+                if pool.is_empty() {
+                    return Err(empty_error);
+                }
+                // sort the pool so the round-robin order doesn't depend on the hash set order
+                pool.sort();
+                let index = self.node_index.fetch_add(1, Ordering::Relaxed);
+                pool[index % pool.len()].clone()
+            }
+        };
+        self.current_node.write().unwrap().replace(node.clone());
+        Ok(node)
+    }
+
+    /// Sends the request to `quorum_size` distinct synced nodes concurrently, the answer is accepted as soon as
+    /// `quorum_threshold` percent of them agree on it. Each request has its own timeout, so a slow node only fails
+    /// its own answer. Without the quorum, or with a pinned node, the request goes to a single node.
+    /// With [`ClientBuilder::with_quorum_milestone_validation`], the answer of a node only counts if its latest
+    /// milestone is valid, otherwise it fails with [`Error::InvalidMilestone`].
+    /// Fails with [`Error::QuorumError`] listing the answer of every node if they disagree.
+    pub(crate) async fn quorum_request<T, F, Fut>(&self, request: F) -> Result<T>
+    where
+        T: PartialEq + std::fmt::Debug,
+        F: Fn(Url) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        if self.offline || !self.quorum || self.pinned_node.read().unwrap().is_some() {
+            return request(self.get_node()?).await;
+        }
+        let nodes = {
+            let pool = self.sync.read().unwrap();
+            if pool.len() < self.quorum_size {
+                return Err(Error::QuorumPoolSizeError(pool.len(), self.quorum_size));
+            }
+            // the same round-robin order as the single node requests
+            let mut pool: Vec<&Url> = pool.iter().collect();
+            pool.sort();
+            let index = self.node_index.fetch_add(1, Ordering::Relaxed);
+            (0..self.quorum_size)
+                .map(|offset| pool[(index + offset) % pool.len()].clone())
+                .collect::<Vec<Url>>()
+        };
+        // the agreeing answers required, rounded up
+        let required = (self.quorum_size * self.quorum_threshold + 99) / 100;
+
+        let request = &request;
+        let mut requests = nodes
+            .into_iter()
+            .map(|node| async move {
+                if self.quorum_milestone_validation {
+                    if let Err(error) = self.validate_latest_milestone(&node).await {
+                        return (node, Err(error));
+                    }
+                }
+                (node.clone(), request(node).await)
+            })
+            .collect::<FuturesUnordered<_>>();
+        let mut answers: Vec<(Url, Result<T>)> = Vec::new();
+        while let Some((node, answer)) = requests.next().await {
+            if let Ok(value) = &answer {
+                let agreeing = answers
+                    .iter()
+                    .filter(|(_, other)| matches!(other, Ok(agreed) if agreed == value))
+                    .count();
+                if agreeing + 1 >= required {
+                    return answer;
+                }
+            }
+            answers.push((node, answer));
+        }
+        Err(Error::QuorumError(
+            answers
+                .into_iter()
+                .map(|(node, answer)| {
+                    let answer = match answer {
+                        Ok(value) => format!("{:?}", value),
+                        Err(error) => format!("error `{}`", error),
+                    };
+                    (node.to_string(), answer)
+                })
+                .collect(),
+        ))
     }
 
-    /// Gets the network id of the node we're connecting to.
+    /// Gets the node used by the last request, or the pinned node. Useful for debugging.
+    pub fn get_current_node(&self) -> Option<Url> {
+        match self.pinned_node.read().unwrap().clone() {
+            Some(node) => Some(node),
+            None => self.current_node.read().unwrap().clone(),
+        }
+    }
+
+    /// Whether the client is offline, built with [`ClientBuilder::offline`].
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Gets the nodes of the synced node pool.
+    pub fn get_synced_nodes(&self) -> Vec<Url> {
+        let mut nodes: Vec<Url> = self.sync.read().unwrap().iter().cloned().collect();
+        nodes.sort();
+        nodes
+    }
+
+    /// Gets the sync status of every client node as of the last node sync, sorted by URL. With the node syncing
+    /// disabled, the nodes are never checked and all reported synced.
+    pub fn get_node_sync_status(&self) -> Vec<NodeSyncStatus> {
+        let mut status: Vec<NodeSyncStatus> = self.sync_status.read().unwrap().values().cloned().collect();
+        status.sort_by(|a, b| a.url.cmp(&b.url));
+        status
+    }
+
+    /// Pins one of the client nodes: it's used for every request, even if it isn't synced, until `unpin_node` is
+    /// called.
+    pub fn pin_node(&self, url: &str) -> Result<()> {
+        let url = Url::parse(url).map_err(|_| Error::UrlError)?;
+        if !self.nodes.contains(&url) {
+            return Err(Error::UnknownNode(url.to_string()));
+        }
+        self.pinned_node.write().unwrap().replace(url);
+        Ok(())
+    }
+
+    /// Unpins the pinned node, going back to the synced node pool.
+    pub fn unpin_node(&self) {
+        self.pinned_node.write().unwrap().take();
+    }
+
+    /// Gets the network id of the client network, the hash of its network id string, set in the messages.
+    /// The network info is cached, see [`Client::get_network_info`]: with the node syncing disabled, it's fetched from
+    /// a node the first time unless the network id is set with the builder.
     pub async fn get_network_id(&self) -> Result<u64> {
-        let info = self.get_info().await?;
-        let mut hasher = VarBlake2b::new(32).unwrap();
-        hasher.update(info.network_id.as_bytes());
-        let mut result: [u8; 32] = [0; 32];
-        hasher.finalize_variable(|res| {
-            result = res.try_into().unwrap();
-        });
-        let network_id = u64::from_le_bytes(result[0..8].try_into().unwrap());
-        Ok(network_id)
+        Ok(hash_network_id(&self.known_network_info().await?.network_id))
     }
 
     /// Gets the miner to use based on the PoW setting
     pub fn get_pow_provider(&self) -> ClientMiner {
-        ClientMinerBuilder::new()
-            .with_local_pow(self.network_info.read().unwrap().local_pow)
-            .finish()
+        let network_info = self.network_info.read().unwrap();
+        let mut builder = ClientMinerBuilder::new()
+            .with_local_pow(network_info.info.local_pow)
+            .with_local_pow_fallback(self.local_pow_fallback);
+        if let Some(worker_count) = network_info.info.pow_worker_count {
+            builder = builder.with_worker_count(worker_count);
+        }
+        if let Some(nonce_provider) = &self.nonce_provider {
+            builder = builder.with_nonce_provider(nonce_provider.clone());
+        }
+        #[cfg(feature = "metrics")]
+        let builder = builder.with_metrics(self.metrics.clone());
+        builder.finish()
+    }
+
+    /// Gets the minimum PoW score of the client network, cached like the network id, see [`Client::get_network_id`].
+    pub async fn get_min_pow_score(&self) -> Result<f64> {
+        Ok(self.known_network_info().await?.min_pow_score)
     }
 
-    /// Gets the network related information such as network_id and min_pow_score
+    /// Builds a message with the nonce of the miner of the client, see [`Client::get_pow_provider`], reaching the
+    /// minimum PoW score of the network. A failing outsourced provider fails with [`Error::NonceProvider`] unless the
+    /// local PoW fallback is set, see
+    /// [`ClientBuilder::with_local_pow_fallback`](crate::ClientBuilder::with_local_pow_fallback).
+    pub async fn build_message(
+        &self,
+        network_id: u64,
+        parents: (MessageId, MessageId),
+        payload: Option<Payload>,
+    ) -> Result<Message> {
+        let min_pow_score = self.get_min_pow_score().await?;
+        finish_pow(&self.get_pow_provider(), min_pow_score, network_id, parents, payload)
+    }
+
+    /// Gets a snapshot of the metrics of the client: the node requests by endpoint, the failed requests, the duration
+    /// of the PoW, the submitted messages and the retries.
+    #[cfg(feature = "metrics")]
+    pub fn get_metrics(&self) -> Metrics {
+        self.metrics.snapshot()
+    }
+
+    /// Gets the cached network related information such as network_id and min_pow_score. The node syncing caches the
+    /// info of the synced nodes when the client is built and on every sync interval, the parameters set with the
+    /// builder are kept.
     pub fn get_network_info(&self) -> NetworkInfo {
-        self.network_info.read().unwrap().clone()
+        self.network_info.read().unwrap().info.clone()
+    }
+
+    // Gets the network info, first taken from the info of a node if the network isn't known yet, as with the node
+    // syncing disabled.
+    async fn known_network_info(&self) -> Result<NetworkInfo> {
+        if !self.network_info.read().unwrap().is_known() {
+            let info = self.get_info().await?;
+            Client::update_network_info(&self.network_info, &info);
+        }
+        Ok(self.get_network_info())
+    }
+
+    // The network id of the stored state once the network is known, the state isn't worth a request of the node info
+    fn state_network_id(&self) -> Option<String> {
+        let network_info = self.network_info.read().unwrap();
+        Some(network_info.info.network_id.clone()).filter(|_| network_info.is_known())
+    }
+
+    /// The stored state of the key, the default while the network isn't known.
+    pub(crate) async fn load_state<T: DeserializeOwned + Default>(&self, key: StateKey) -> Result<T> {
+        match self.state_network_id() {
+            Some(network_id) => self.storage.load(&network_id, key).await,
+            None => Ok(T::default()),
+        }
+    }
+
+    /// Updates the stored state of the key, nothing is stored while the network isn't known.
+    pub(crate) async fn update_state<T, F>(&self, key: StateKey, update: F) -> Result<()>
+    where
+        T: Serialize + DeserializeOwned + Default,
+        F: FnOnce(&mut T),
+    {
+        match self.state_network_id() {
+            Some(network_id) => self.storage.update(&network_id, key, update).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the address is stored as spent: an address spending an output is spent for good.
+    pub(crate) async fn is_stored_spent(&self, address: &Bech32Address) -> Result<bool> {
+        let spent: HashSet<String> = self.load_state(storage::SPENT_ADDRESSES).await?;
+        Ok(spent.contains(&address.0))
+    }
+
+    /// Stores the address as spent, the address is queried again if it isn't stored.
+    pub(crate) async fn store_spent(&self, address: &Bech32Address) {
+        let _ = self
+            .update_state(storage::SPENT_ADDRESSES, |spent: &mut HashSet<String>| {
+                spent.insert(address.0.clone());
+            })
+            .await;
+    }
+
+    /// The ids of the messages submitted by the clients of the storage and not referenced by a milestone yet, as far
+    /// as the client knows: a message is removed once its metadata is fetched with its milestone, see
+    /// [`ClientBuilder::with_storage`].
+    pub async fn get_pending_messages(&self) -> Result<Vec<MessageId>> {
+        self.known_network_info().await?;
+        let pending: Vec<String> = self.load_state(storage::PENDING_MESSAGES).await?;
+        pending.iter().map(|id| Ok(MessageId::from_str(id)?)).collect()
+    }
+
+    /// Refreshes the cached network info from the info of a synced node and returns it.
+    /// The node syncing also refreshes it on every sync interval, this is only needed with the node syncing disabled
+    /// or to pick up a network change right away. The node info isn't taken from the cache, see
+    /// [`Client::refresh_node_info`].
+    pub async fn refresh_network_info(&self) -> Result<NetworkInfo> {
+        self.refresh_node_info().await?;
+        Ok(self.get_network_info())
+    }
+
+    /// Drops the cached info of every node, the stored infos too, and fetches the info of a synced node again, to pick
+    /// up a node that was just reconfigured. The cached network info is refreshed with it.
+    pub async fn refresh_node_info(&self) -> Result<NodeInfo> {
+        self.node_info_cache.clear();
+        self.update_state(storage::NODE_INFOS, |infos: &mut HashMap<String, StoredNodeInfo>| {
+            infos.clear()
+        })
+        .await?;
+        let info = self.get_info().await?;
+        Client::update_network_info(&self.network_info, &info);
+        Ok(info)
+    }
+
+    // Caches the network id, the minimum PoW score and the bech32 HRP reported by a node, except the ones set with the
+    // builder.
+    fn update_network_info(network_info: &RwLock<NetworkState>, info: &NodeInfo) {
+        let mut network_info = network_info.write().unwrap();
+        let overrides = network_info.overrides;
+        if !overrides.network_id {
+            network_info.info.network_id = info.network_id.clone();
+        }
+        if !overrides.min_pow_score {
+            network_info.info.min_pow_score = info.min_pow_score;
+        }
+        if let (false, Some(bech32_hrp)) = (overrides.bech32_hrp, &info.bech32_hrp) {
+            network_info.info.bech32_hrp = bech32_hrp.clone();
+        }
+        network_info.fetched = true;
+    }
+
+    /// Encodes an address as bech32 with the HRP of the client network.
+    pub fn address_to_bech32(&self, address: &Address) -> Result<Bech32Address> {
+        let bech32_hrp = self.get_network_info().bech32_hrp;
+        let mut bytes = Vec::new();
+        address.pack(&mut bytes)?;
+        bech32::encode(&bech32_hrp, bytes.to_base32())
+            .map(Bech32Address)
+            .map_err(|_| Error::InvalidParameter(format!("bech32 HRP {}", bech32_hrp)))
+    }
+
+    /// Parses a bech32 address of the client network, failing with [`Error::Bech32HrpMismatch`] for an address of
+    /// another network.
+    pub fn parse_bech32_address(&self, address: &Bech32Address) -> Result<Address> {
+        let bech32_hrp = self.get_network_info().bech32_hrp;
+        let hrp = address.0.rsplitn(2, '1').nth(1).unwrap_or_default();
+        if !hrp.eq_ignore_ascii_case(&bech32_hrp) {
+            return Err(Error::Bech32HrpMismatch(
+                address.to_string(),
+                hrp.to_string(),
+                bech32_hrp,
+            ));
+        }
+        Ok(Address::try_from_bech32(&address.0)?)
     }
 
     ///////////////////////////////////////////////////////////////////////
@@ -366,15 +1365,166 @@ impl Client {
     // Node API
     //////////////////////////////////////////////////////////////////////
 
-    fn get_timeout(&self, api: Api) -> Duration {
+    pub(crate) fn get_timeout(&self, api: Api) -> Duration {
         *self.api_timeout.get(&api).unwrap_or(&self.request_timeout)
     }
 
+    // Sends a request to a node, retrying it while the node is rate limited: a 429 or 503 answer is retried after the
+    // `Retry-After` delay of the node, or the exponential backoff without it, up to the rate limit retries.
+    pub(crate) async fn send_request(&self, request: RequestBuilder) -> Result<NodeResponse> {
+        let mut request = request.build()?;
+        let mut retries = 0;
+        loop {
+            let retry = request.try_clone();
+            let response = self.send_request_once(request).await?;
+            let status = response.status();
+            if status != StatusCode::TOO_MANY_REQUESTS && status != StatusCode::SERVICE_UNAVAILABLE {
+                return Ok(response);
+            }
+            let retry_after = retry_after(response.response.headers());
+            let delay = retry_after.unwrap_or_else(|| RATE_LIMIT_BACKOFF * 2u32.saturating_pow(retries as u32));
+            match retry {
+                Some(retry) if retries < self.rate_limit_retries && delay <= self.rate_limit_max_delay => {
+                    #[cfg(feature = "metrics")]
+                    self.metrics.record_rate_limit_retry();
+                    sleep(delay).await;
+                    request = retry;
+                    retries += 1;
+                }
+                _ => {
+                    return Err(Error::RateLimited(
+                        status.as_u16(),
+                        retry_after,
+                        Box::new(response.context()),
+                    ))
+                }
+            }
+        }
+    }
+
+    // Sends a request to a node once, the middlewares see it before it's sent and once the response or the error is
+    // received. The request is recorded in the metrics with the `metrics` feature.
+    pub(crate) async fn send_request_once(&self, mut request: Request) -> Result<NodeResponse> {
+        #[cfg(not(feature = "metrics"))]
+        if self.middlewares.is_empty() {
+            return NodeResponse::execute(&self.client, request).await;
+        }
+        let request_info = RequestInfo::new(&request);
+        middleware::before_request(&self.middlewares, &mut request, &request_info);
+
+        let (method, url) = (request.method().clone(), request.url().clone());
+        let start = Instant::now();
+        let response = self.client.execute(request).await;
+        let response_info = ResponseInfo {
+            status: response.as_ref().ok().map(|response| response.status().as_u16()),
+            elapsed: start.elapsed(),
+            error: response.as_ref().err().map(|error| error.to_string()),
+        };
+        #[cfg(feature = "metrics")]
+        self.metrics
+            .record_request(&request_info, response_info.elapsed, metrics::error_category(&response));
+        middleware::after_response(&self.middlewares, &request_info, &response_info);
+        match response {
+            Ok(response) => Ok(NodeResponse {
+                response,
+                method,
+                url,
+                start,
+            }),
+            Err(error) => Err(Error::request(
+                error,
+                RequestContext::new(&method, &url, response_info.elapsed),
+            )),
+        }
+    }
+
+    // Adds the credentials of the node to the request of a route that requires authentication.
+    fn with_node_auth(&self, node: &Url, request: RequestBuilder) -> RequestBuilder {
+        let auth = self.node_options.get(node).and_then(|options| options.auth.as_ref());
+        match auth.or_else(|| self.node_auth.as_ref()) {
+            Some(NodeAuth::Jwt { jwt }) => request.bearer_auth(jwt),
+            Some(NodeAuth::BasicAuth { username, password }) => request.basic_auth(username, Some(password)),
+            None => request,
+        }
+    }
+
+    /// GET /api/v1/peers endpoint
+    /// Get the peers of the node. The route usually requires authentication, the credentials are set with
+    /// [`ClientBuilder::with_node_auth`] and a refused request fails with [`Error::Unauthorized`].
+    pub async fn get_peers(&self) -> Result<Vec<PeerInfo>> {
+        let node = self.get_node()?;
+        let mut url = node.clone();
+        url.set_path("api/v1/peers");
+        let resp = self
+            .send_request(
+                self.with_node_auth(&node, self.client.get(url.clone()))
+                    .timeout(self.get_timeout(Api::GetPeers)),
+            )
+            .await?;
+
+        if resp.status().as_u16() == 401 {
+            return Err(Error::Unauthorized(url.to_string()));
+        }
+        parse_response!(resp, 200 => {
+            Ok(resp.json::<Response<Vec<PeerInfo>>>().await?.data)
+        })
+    }
+
+    /// Calls a route of the node API the client doesn't cover, such as a new endpoint or the route of a node plugin,
+    /// and gets the `data` of the JSON answer, or the whole answer if it isn't in a `data` envelope. The path is the
+    /// absolute path of the route under `/api/`, such as `/api/v1/milestones/1`, a path escaping it with `.` or `..`
+    /// segments fails with [`Error::InvalidParameter`]. The request goes to the node of the typed calls with its
+    /// credentials, the timeout, the rate limit retries, the metrics and the middlewares. An error status fails with
+    /// [`Error::ResponseError`], [`Error::Unauthorized`] for a 401, and a not found milestone or message with
+    /// [`Error::PrunedData`] if the node pruned it.
+    pub async fn call_node_api(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let node = self.get_node()?;
+        let url = node_api_url(&node, path)?;
+        let mut request = self
+            .with_node_auth(&node, self.client.request(method, url.clone()))
+            .timeout(self.request_timeout);
+        if !query.is_empty() {
+            request = request.query(query);
+        }
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let resp = self.send_request(request).await?;
+
+        if resp.status().as_u16() == 401 {
+            return Err(Error::Unauthorized(url.to_string()));
+        }
+        let result = parse_response!(resp, 200..=299 => {
+            let text = resp.text().await?;
+            // a route can answer without a body
+            if text.trim().is_empty() {
+                Ok(serde_json::Value::Null)
+            } else {
+                Ok(serde_json::from_str::<serde_json::Value>(&text)?)
+            }
+        });
+        match (result, pruned_request(path)) {
+            (Ok(mut answer), _) => Ok(match answer.get_mut("data") {
+                Some(data) => data.take(),
+                None => answer,
+            }),
+            (Err(error), Some(requested)) => Err(self.pruned_error(requested, error).await),
+            (Err(error), None) => Err(error),
+        }
+    }
+
     /// GET /health endpoint
     pub async fn get_node_health<T: IntoUrl>(url: T) -> Result<bool> {
         let mut url = url.into_url()?;
         url.set_path("health");
-        let resp = reqwest::get(url).await?;
+        let http_client = reqwest::Client::new();
+        let resp = NodeResponse::send(&http_client, http_client.get(url)).await?;
 
         match resp.status().as_u16() {
             200 => Ok(true),
@@ -386,11 +1536,9 @@ impl Client {
     pub async fn get_health(&self) -> Result<bool> {
         let mut url = self.get_node()?;
         url.set_path("health");
+        // an unhealthy node answers 503, it isn't rate limited
         let resp = self
-            .client
-            .get(url)
-            .timeout(self.get_timeout(Api::GetHealth))
-            .send()
+            .send_request_once(self.client.get(url).timeout(self.get_timeout(Api::GetHealth)).build()?)
             .await?;
 
         match resp.status().as_u16() {
@@ -403,7 +1551,23 @@ impl Client {
     pub async fn get_node_info<T: IntoUrl>(url: T) -> Result<NodeInfo> {
         let mut url = url.into_url()?;
         url.set_path("api/v1/info");
-        let resp = reqwest::get(url).await?;
+        let http_client = reqwest::Client::new();
+        let resp = NodeResponse::send(&http_client, http_client.get(url)).await?;
+
+        parse_response!(resp, 200 => {
+            Ok(resp.json::<Response<NodeInfo>>().await?.data)
+        })
+    }
+
+    /// GET /api/v1/info endpoint, failing with `Error::Timeout` if the node doesn't answer in time
+    pub(crate) async fn get_node_info_with_timeout(
+        http_client: &reqwest::Client,
+        url: Url,
+        timeout: Duration,
+    ) -> Result<NodeInfo> {
+        let mut url = url;
+        url.set_path("api/v1/info");
+        let resp = NodeResponse::send(http_client, http_client.get(url).timeout(timeout)).await?;
 
         parse_response!(resp, 200 => {
             Ok(resp.json::<Response<NodeInfo>>().await?.data)
@@ -411,19 +1575,47 @@ impl Client {
     }
 
     /// GET /api/v1/info endpoint
+    /// The info of each node is cached for the TTL set with [`ClientBuilder::with_node_info_ttl`], and shared with the
+    /// node syncing. The info is stored too, a client of the same storage takes it until the TTL expires.
     pub async fn get_info(&self) -> Result<NodeInfo> {
-        let mut url = self.get_node()?;
+        let node = self.get_node()?;
+        if let Some(info) = self.node_info_cache.get(&node) {
+            return Ok(info);
+        }
+        let ttl = self.node_info_cache.ttl();
+        let node_key = crate::middleware::redacted_origin(&node);
+        if ttl > Duration::from_secs(0) {
+            let stored: HashMap<String, StoredNodeInfo> =
+                self.load_state(storage::NODE_INFOS).await.unwrap_or_default();
+            if let Some(stored) = stored.get(&node_key) {
+                if unix_time().saturating_sub(stored.fetched_at) < ttl.as_secs() {
+                    return Ok(stored.info.clone());
+                }
+            }
+        }
+        let mut url = node.clone();
         url.set_path("api/v1/info");
         let resp = self
-            .client
-            .get(url)
-            .timeout(self.get_timeout(Api::GetInfo))
-            .send()
+            .send_request(self.client.get(url).timeout(self.get_timeout(Api::GetInfo)))
             .await?;
 
-        parse_response!(resp, 200 => {
+        let info = parse_response!(resp, 200 => {
             Ok(resp.json::<Response<NodeInfo>>().await?.data)
-        })
+        })?;
+        self.node_info_cache.insert(&node, &info);
+        if ttl > Duration::from_secs(0) {
+            let stored = StoredNodeInfo {
+                fetched_at: unix_time(),
+                info: info.clone(),
+            };
+            // the info is fetched again if it isn't stored
+            let _ = self
+                .update_state(storage::NODE_INFOS, |infos: &mut HashMap<String, StoredNodeInfo>| {
+                    infos.insert(node_key, stored);
+                })
+                .await;
+        }
+        Ok(info)
     }
 
     /// GET /api/v1/tips endpoint
@@ -431,10 +1623,7 @@ impl Client {
         let mut url = self.get_node()?;
         url.set_path("api/v1/tips");
         let resp = self
-            .client
-            .get(url)
-            .timeout(self.get_timeout(Api::GetTips))
-            .send()
+            .send_request(self.client.get(url).timeout(self.get_timeout(Api::GetTips)))
             .await?;
 
         parse_response!(resp, 200 => {
@@ -449,26 +1638,62 @@ impl Client {
 
     /// POST /api/v1/messages endpoint
     pub async fn post_message(&self, message: &Message) -> Result<MessageId> {
-        let mut url = self.get_node()?;
+        let mut url = self.get_submit_node()?;
         url.set_path("api/v1/messages");
+        let (remote_pow, network) = {
+            let network_info = self.network_info.read().unwrap();
+            let network = Some(network_info.info.network_id.clone()).filter(|_| network_info.is_known());
+            (!network_info.info.local_pow && message.nonce() == 0, network)
+        };
+        // a node of the client network would reject a message of another network, once its PoW is done
+        if let Some(network) = network {
+            let network_id = hash_network_id(&network);
+            if message.network_id() != network_id {
+                return Err(Error::NetworkIdMismatch(message.network_id(), network, network_id));
+            }
+        }
 
         let message: MessageJson = message.into();
 
         let resp = self
-            .client
-            .post(url)
-            .timeout(self.get_timeout(Api::PostMessage))
-            .header("content-type", "application/json; charset=UTF-8")
-            .json(&message)
-            .send()
+            .send_request(
+                self.client
+                    .post(url.clone())
+                    .timeout(self.get_timeout(Api::PostMessage))
+                    .header("content-type", "application/json; charset=UTF-8")
+                    .json(&message),
+            )
             .await?;
 
-        parse_response!(resp, 201 => {
+        if remote_pow && resp.status().as_u16() != 201 {
+            let status = resp.status().as_u16();
+            let context = resp.context();
+            let text = resp.text().await?;
+            let lowercase_text = text.to_lowercase();
+            if lowercase_text.contains("pow") || lowercase_text.contains("proof of work") {
+                return Err(Error::RemotePowRefused(url.origin().ascii_serialization(), text));
+            }
+            return Err(Error::ResponseError(status, text, Box::new(context)));
+        }
+
+        let message_id = parse_response!(resp, 201 => {
+            #[cfg(feature = "metrics")]
+            self.metrics.record_message_submitted();
             let m = resp.json::<Response<PostMessageId>>().await?.data;
             let mut message_id = [0u8; 32];
             hex::decode_to_slice(m.message_id, &mut message_id)?;
             Ok(MessageId::from(message_id))
-        })
+        })?;
+        // the message is submitted even if it isn't stored as pending
+        let _ = self
+            .update_state(storage::PENDING_MESSAGES, |pending: &mut Vec<String>| {
+                let message_id = message_id.to_string();
+                if !pending.contains(&message_id) {
+                    pending.push(message_id);
+                }
+            })
+            .await;
+        Ok(message_id)
     }
 
     /// GET /api/v1/messages/{messageId} endpoint
@@ -478,71 +1703,157 @@ impl Client {
 
     /// GET /api/v1/outputs/{outputId} endpoint
     /// Find an output by its transaction_id and corresponding output_index.
+    /// Cross-checked by the quorum nodes if the quorum is enabled, see [`ClientBuilder::with_quorum`].
     pub async fn get_output(&self, output_id: &UTXOInput) -> Result<OutputMetadata> {
+        self.get_output_of_type(output_id, OutputType::SignatureLockedSingle)
+            .await
+    }
+
+    // Gets an output of the given type, erroring for the other output types
+    pub(crate) async fn get_output_of_type(
+        &self,
+        output_id: &UTXOInput,
+        output_type: OutputType,
+    ) -> Result<OutputMetadata> {
+        self.get_output_of_types(output_id, &[output_type]).await
+    }
+
+    // Gets an output of one of the given types, erroring for the other output types
+    pub(crate) async fn get_output_of_types(
+        &self,
+        output_id: &UTXOInput,
+        output_types: &[OutputType],
+    ) -> Result<OutputMetadata> {
+        self.quorum_request(|mut url| async move {
+            url.set_path(&format!(
+                "api/v1/outputs/{}{}",
+                output_id.output_id().transaction_id().to_string(),
+                hex::encode(output_id.output_id().index().to_le_bytes())
+            ));
+            let resp = self
+                .send_request(self.client.get(url).timeout(self.get_timeout(Api::GetOutput)))
+                .await?;
+
+            parse_response!(resp, 200 => {
+                let raw = resp.json::<Response<RawOutput>>().await?.data;
+                output_metadata(raw, output_types)
+            })
+        })
+        .await
+    }
+
+    // Gets an output from the output cache if it holds it, the fetched output is cached.
+    async fn get_cached_output(&self, output_id: &UTXOInput) -> Result<OutputMetadata> {
+        let cache = match &self.output_cache {
+            Some(cache) => cache,
+            None => return self.get_output(output_id).await,
+        };
+        if let Some(output) = cache.get(output_id) {
+            return Ok(output);
+        }
+        let output = self.get_output(output_id).await?;
+        cache.insert(output_id, &output);
+        Ok(output)
+    }
+
+    /// The statistics of the output cache, `None` if it's disabled, see [`ClientBuilder::with_output_cache`].
+    pub fn get_output_cache_stats(&self) -> Option<OutputCacheStats> {
+        self.output_cache.as_ref().map(OutputCache::stats)
+    }
+
+    /// Empties the output cache and resets its statistics.
+    pub fn clear_output_cache(&self) {
+        if let Some(cache) = &self.output_cache {
+            cache.clear();
+        }
+    }
+
+    /// GET /api/v1/transactions/{transactionId}/included-message endpoint
+    /// Get the message that got the transaction included in the ledger, a reattachment can carry the same transaction.
+    /// Fails with [`Error::TransactionNotIncluded`] if the transaction isn't included or the node pruned it.
+    pub async fn get_included_message(&self, transaction_id: &TransactionId) -> Result<Message> {
         let mut url = self.get_node()?;
-        url.set_path(&format!(
-            "api/v1/outputs/{}{}",
-            output_id.output_id().transaction_id().to_string(),
-            hex::encode(output_id.output_id().index().to_le_bytes())
-        ));
+        url.set_path(&format!("api/v1/transactions/{}/included-message", transaction_id));
         let resp = self
-            .client
-            .get(url)
-            .timeout(self.get_timeout(Api::GetOutput))
-            .send()
+            .send_request(self.client.get(url).timeout(self.get_timeout(Api::GetMessage)))
             .await?;
 
-        parse_response!(resp, 200 => {
-            let raw = resp.json::<Response<RawOutput>>().await?.data;
-            Ok(OutputMetadata {
-                message_id: hex::decode(raw.message_id)?,
-                transaction_id: hex::decode(raw.transaction_id)?,
-                output_index: raw.output_index,
-                is_spent: raw.is_spent,
-                amount: raw.output.amount,
-                address: {
-                    if raw.output.type_ == 0 && raw.output.address.type_ == 1 {
-                        let mut address = [0u8; ADDRESS_LENGTH];
-                        hex::decode_to_slice(raw.output.address.address, &mut address)?;
-                        Address::from(Ed25519Address::from(address))
-                    } else {
-                        return Err(Error::InvalidParameter("address type".to_string()));
-                    }
-                },
-            })
-        })
+        let result = parse_response!(resp, 200 => {
+            let message = resp.json::<Response<MessageJson>>().await?.data;
+            Ok(message.try_into()?)
+        });
+        match result {
+            Err(Error::ResponseError(404, _, _)) => Err(Error::TransactionNotIncluded(transaction_id.to_string())),
+            message => message,
+        }
     }
+
     /// Find all outputs based on the requests criteria. This method will try to query multiple nodes if
     /// the request amount exceed individual node limit.
+    /// Fails with the first error if a lookup fails, see [`Client::find_outputs_settled`] to keep the other results.
     pub async fn find_outputs(
         &self,
         outputs: &[UTXOInput],
         addresses: &[Bech32Address],
     ) -> Result<Vec<OutputMetadata>> {
-        let mut output_metadata = Vec::<OutputMetadata>::new();
-        // Use a `HashSet` to prevent duplicate output.
-        let mut output_to_query = HashSet::<UTXOInput>::new();
+        let (output_metadata, errors) = self.find_outputs_settled(outputs, addresses).await;
+        match errors.into_iter().next() {
+            Some((_, error)) => Err(error),
+            None => Ok(output_metadata),
+        }
+    }
+
+    /// Find the outputs with the given ids and the outputs of the given addresses, returning the found outputs along
+    /// with the error of every failed lookup. The outputs are deduplicated and the lookups are sent concurrently, see
+    /// [`ClientBuilder::with_request_parallelism`].
+    pub async fn find_outputs_settled(
+        &self,
+        outputs: &[UTXOInput],
+        addresses: &[Bech32Address],
+    ) -> (Vec<OutputMetadata>, Vec<(OutputLookup, Error)>) {
+        let mut errors = Vec::new();
+        // Use a `HashSet` to prevent duplicate output, the query order is kept.
+        let mut seen_outputs = HashSet::<UTXOInput>::new();
+        let mut output_to_query = Vec::new();
 
-        // Collect the `UTXOInput` in the HashSet.
+        // Collect the `UTXOInput`s, then the ones of the addresses.
         for output in outputs {
-            output_to_query.insert(output.to_owned());
+            if seen_outputs.insert(output.to_owned()) {
+                output_to_query.push(output.to_owned());
+            }
         }
-
-        // Use `get_address()` API to get the address outputs first,
-        // then collect the `UTXOInput` in the HashSet.
-        for address in addresses {
-            let address_outputs = self.get_address().outputs(&address).await?;
-            for output in address_outputs.iter() {
-                output_to_query.insert(output.to_owned());
+        let results = futures::stream::iter(addresses)
+            .map(|address| self.get_address().outputs(address))
+            .buffered(self.request_parallelism)
+            .collect::<Vec<Result<_>>>()
+            .await;
+        for (address, result) in addresses.iter().zip(results) {
+            match result {
+                Ok(address_outputs) => {
+                    for output in address_outputs.iter() {
+                        if seen_outputs.insert(output.to_owned()) {
+                            output_to_query.push(output.to_owned());
+                        }
+                    }
+                }
+                Err(error) => errors.push((OutputLookup::Address(address.clone()), error)),
             }
         }
 
-        // Use `get_output` API to get the `OutputMetadata`.
-        for output in output_to_query {
-            let meta_data = self.get_output(&output).await?;
-            output_metadata.push(meta_data);
+        // Use `get_output` API to get the `OutputMetadata`, the cached outputs aren't queried.
+        let mut output_metadata = Vec::<OutputMetadata>::new();
+        let results = futures::stream::iter(&output_to_query)
+            .map(|output| self.get_cached_output(output))
+            .buffered(self.request_parallelism)
+            .collect::<Vec<Result<_>>>()
+            .await;
+        for (output, result) in output_to_query.iter().zip(results) {
+            match result {
+                Ok(meta_data) => output_metadata.push(meta_data),
+                Err(error) => errors.push((OutputLookup::Output(output.to_owned()), error)),
+            }
         }
-        Ok(output_metadata)
+        (output_metadata, errors)
     }
 
     /// GET /api/v1/addresses/{address} endpoint
@@ -552,26 +1863,162 @@ impl Client {
 
     /// GET /api/v1/milestones/{index} endpoint
     /// Get the milestone by the given index.
+    /// Fails with [`Error::PrunedData`] if the node pruned the milestone and the permanode doesn't have it either.
     pub async fn get_milestone(&self, index: u64) -> Result<MilestoneMetadata> {
+        Ok(self.get_milestone_with_source(index).await?.data)
+    }
+
+    /// Get the milestone by the given index, from the permanode if the node pruned it, see
+    /// [`ClientBuilder::with_permanode`]. The answer holds its source.
+    pub async fn get_milestone_with_source(&self, index: u64) -> Result<Sourced<MilestoneMetadata>> {
+        self.historical_request(
+            || self.node_milestone(index),
+            |permanode| self.permanode_milestone(permanode, index),
+            is_not_found,
+        )
+        .await
+    }
+
+    async fn node_milestone(&self, index: u64) -> Result<MilestoneMetadata> {
         let mut url = self.get_node()?;
         url.set_path(&format!("api/v1/milestones/{}", index));
         let resp = self
-            .client
-            .get(url)
-            .timeout(self.get_timeout(Api::GetMilestone))
-            .send()
+            .send_request(self.client.get(url).timeout(self.get_timeout(Api::GetMilestone)))
             .await?;
 
-        parse_response!(resp, 200 => {
+        let result = parse_response!(resp, 200 => {
             let milestone = resp.json::<Response<MilestoneMetadata>>().await?.data;
             Ok(milestone)
-        })
+        });
+        match result {
+            Err(error) => Err(self.pruned_error(PrunedRequest::Milestone(index), error).await),
+            milestone => milestone,
+        }
+    }
+
+    /// Validates the milestone payload against the coordinator keys set with
+    /// [`ClientBuilder::with_coordinator_keys`], see [`validate_milestone`].
+    pub fn validate_milestone(&self, milestone: &MilestonePayload) -> Result<()> {
+        validate_milestone(milestone, &self.coordinator_keys)
+    }
+
+    /// Get the milestone by the given index and validate its message against the coordinator keys, see
+    /// [`validate_milestone_message`]. Fails with [`Error::InvalidMilestone`] if the node answers an invalid one.
+    pub async fn get_validated_milestone(&self, index: u64) -> Result<MilestoneMetadata> {
+        self.node_validated_milestone(self.get_node()?, index).await
+    }
+
+    async fn node_validated_milestone(&self, node: Url, index: u64) -> Result<MilestoneMetadata> {
+        let mut url = node.clone();
+        url.set_path(&format!("api/v1/milestones/{}", index));
+        let resp = self
+            .send_request(self.client.get(url).timeout(self.get_timeout(Api::GetMilestone)))
+            .await?;
+        let milestone = parse_response!(resp, 200 => {
+            Ok(resp.json::<Response<MilestoneMetadata>>().await?.data)
+        })?;
+        let mut url = node;
+        url.set_path(&format!("api/v1/messages/{}/raw", milestone.message_id));
+        let resp = self
+            .send_request(self.client.get(url).timeout(self.get_timeout(Api::GetMessage)))
+            .await?;
+        let bytes = parse_response!(resp, 200 => {
+            resp.bytes().await
+        })?;
+        validate_milestone_message(&bytes, &milestone, &self.coordinator_keys)?;
+        Ok(milestone)
+    }
+
+    // Validates the latest milestone of the node, only again once the node reports a newer one.
+    async fn validate_latest_milestone(&self, node: &Url) -> Result<()> {
+        let info = match self.node_info_cache.get(node) {
+            Some(info) => info,
+            None => {
+                let info =
+                    Client::get_node_info_with_timeout(&self.client, node.clone(), self.get_timeout(Api::GetInfo))
+                        .await?;
+                self.node_info_cache.insert(node, &info);
+                info
+            }
+        };
+        let index = info.latest_milestone_index as u64;
+        if self.validated_milestones.read().unwrap().get(node) == Some(&index) {
+            return Ok(());
+        }
+        self.node_validated_milestone(node.clone(), index).await?;
+        self.validated_milestones.write().unwrap().insert(node.clone(), index);
+        Ok(())
+    }
+
+    /// GET /api/v1/milestones/{index}/utxo-changes endpoint
+    /// Get the outputs created and consumed by the milestone with the given index.
+    /// Fails with [`Error::PrunedData`] if the node pruned the milestone and the permanode doesn't have it either.
+    pub async fn get_milestone_utxo_changes(&self, index: u64) -> Result<MilestoneUtxoChanges> {
+        Ok(self.get_milestone_utxo_changes_with_source(index).await?.data)
+    }
+
+    /// Get the UTXO changes of the milestone with the given index, from the permanode if the node pruned it, see
+    /// [`ClientBuilder::with_permanode`]. The answer holds its source.
+    pub async fn get_milestone_utxo_changes_with_source(&self, index: u64) -> Result<Sourced<MilestoneUtxoChanges>> {
+        self.historical_request(
+            || self.node_milestone_utxo_changes(index),
+            |permanode| self.permanode_milestone_utxo_changes(permanode, index),
+            is_not_found,
+        )
+        .await
+    }
+
+    async fn node_milestone_utxo_changes(&self, index: u64) -> Result<MilestoneUtxoChanges> {
+        let mut url = self.get_node()?;
+        url.set_path(&format!("api/v1/milestones/{}/utxo-changes", index));
+        let resp = self
+            .send_request(self.client.get(url).timeout(self.get_timeout(Api::GetMilestone)))
+            .await?;
+
+        let result = parse_response!(resp, 200 => {
+            Ok(resp.json::<Response<MilestoneUtxoChanges>>().await?.data)
+        });
+        match result {
+            Err(error) => Err(self
+                .pruned_error(PrunedRequest::MilestoneUtxoChanges(index), error)
+                .await),
+            changes => changes,
+        }
+    }
+
+    // Maps the not found error of pruned data to `Error::PrunedData`: a milestone at or below the pruning index of the
+    // node, or a message the node reports as pruned in its answer. A message id doesn't tell the milestone of the
+    // message, so the not found error of a message without such an answer is kept.
+    pub(crate) async fn pruned_error(&self, requested: PrunedRequest, error: Error) -> Error {
+        let maybe_pruned = match (&error, &requested) {
+            (Error::ResponseError(404, body, _), PrunedRequest::Message(_)) => body.to_lowercase().contains("pruned"),
+            (Error::ResponseError(404, _, _), _) => true,
+            _ => false,
+        };
+        if maybe_pruned {
+            if let Ok(info) = self.get_info().await {
+                let pruning_index = info.pruning_index as u64;
+                let pruned = match &requested {
+                    PrunedRequest::Milestone(index) | PrunedRequest::MilestoneUtxoChanges(index) => {
+                        *index <= pruning_index
+                    }
+                    PrunedRequest::Message(_) => true,
+                };
+                if pruned {
+                    return Error::PrunedData {
+                        requested,
+                        pruning_index,
+                    };
+                }
+            }
+        }
+        error
     }
 
     /// Reattaches messages for provided message id. Messages can be reattached only if they are valid and haven't been
     /// confirmed for a while.
     pub async fn reattach(&self, message_id: &MessageId) -> Result<(MessageId, Message)> {
-        let metadata = self.get_message().metadata(message_id).await?;
+        let metadata = self.unreferenced_message_metadata(message_id).await?;
         if metadata.should_reattach.unwrap_or(false) {
             self.reattach_unchecked(message_id).await
         } else {
@@ -583,25 +2030,38 @@ impl Client {
         // Get the Message object by the MessageID.
         let message = self.get_message().data(message_id).await?;
 
-        // Change the fields of parent1 and parent2.
+        // Change the fields of parent1 and parent2, the payload is kept.
         let tips = self.get_tips().await?;
-        let reattach_message = Message::builder()
-            .with_network_id(self.get_network_id().await?)
-            .with_parent1(tips.0)
-            .with_parent2(tips.1)
-            .with_payload(message.payload().to_owned().unwrap())
-            .finish()
-            .map_err(|_| Error::TransactionError)?;
+        let reattach_message = self
+            .build_message(self.get_network_id().await?, tips, message.payload().to_owned())
+            .await?;
 
         // Post the modified
         let message_id = self.post_message(&reattach_message).await?;
+        #[cfg(feature = "metrics")]
+        self.metrics.record_reattachment();
         Ok((message_id, reattach_message))
     }
 
+    /// Broadcasts again a message stored by the node, the packed message answered for the id is checked against the
+    /// id and unpacked before its submission: a message that doesn't match its id or the client can't unpack fails
+    /// with [`Error::InvalidMessage`] and isn't submitted. Returns the packed message, to archive it.
+    pub async fn rebroadcast(&self, message_id: &MessageId) -> Result<Vec<u8>> {
+        let bytes = self.get_message().raw(message_id).await?;
+        let invalid = |error: Error| Error::InvalidMessage(message_id.to_string(), Box::new(error));
+        let packed_message_id = compute_message_id(&bytes);
+        if packed_message_id != *message_id {
+            return Err(invalid(Error::MessageIdMismatch(packed_message_id.to_string())));
+        }
+        let message = Message::unpack(&mut bytes.as_slice()).map_err(|error| invalid(error.into()))?;
+        self.post_message(&message).await?;
+        Ok(bytes)
+    }
+
     /// Promotes a message. The method should validate if a promotion is necessary through get_message. If not, the
     /// method should error out and should not allow unnecessary promotions.
     pub async fn promote(&self, message_id: &MessageId) -> Result<(MessageId, Message)> {
-        let metadata = self.get_message().metadata(message_id).await?;
+        let metadata = self.unreferenced_message_metadata(message_id).await?;
         if metadata.should_promote.unwrap_or(false) {
             self.promote_unchecked(message_id).await
         } else {
@@ -612,17 +2072,41 @@ impl Client {
     async fn promote_unchecked(&self, message_id: &MessageId) -> Result<(MessageId, Message)> {
         // Create a new message (zero value message) for which one tip would be the actual message
         let tips = self.get_tips().await?;
-        let promote_message = Message::builder()
-            .with_network_id(self.get_network_id().await?)
-            .with_parent1(tips.0)
-            .with_parent2(*message_id)
-            .finish()
-            .map_err(|_| Error::TransactionError)?;
+        let promote_message = self
+            .build_message(self.get_network_id().await?, (tips.0, *message_id), None)
+            .await?;
 
         let message_id = self.post_message(&promote_message).await?;
+        #[cfg(feature = "metrics")]
+        self.metrics.record_promotion();
         Ok((message_id, promote_message))
     }
 
+    /// Gets the confirmation of a message, `None` while no milestone references it.
+    pub async fn get_message_confirmation(&self, message_id: &MessageId) -> Result<Option<MessageConfirmation>> {
+        let metadata = self.get_message().metadata(message_id).await?;
+        #[cfg(feature = "metrics")]
+        if metadata.referenced_by_milestone_index.is_some() {
+            self.metrics.record_confirmation();
+        }
+        Ok(metadata
+            .referenced_by_milestone_index
+            .map(|milestone_index| MessageConfirmation {
+                message_id: metadata.message_id,
+                referenced_by_milestone_index: milestone_index,
+                ledger_inclusion_state: metadata.ledger_inclusion_state,
+            }))
+    }
+
+    // Gets the metadata of a message, erroring if a milestone already references it.
+    async fn unreferenced_message_metadata(&self, message_id: &MessageId) -> Result<MessageMetadata> {
+        let metadata = self.get_message().metadata(message_id).await?;
+        match metadata.referenced_by_milestone_index {
+            Some(milestone_index) => Err(Error::MessageAlreadyReferenced(message_id.to_string(), milestone_index)),
+            None => Ok(metadata),
+        }
+    }
+
     //////////////////////////////////////////////////////////////////////
     // High level API
     //////////////////////////////////////////////////////////////////////
@@ -633,62 +2117,286 @@ impl Client {
     }
 
     /// Return a valid unspent address.
-    pub fn get_unspent_address<'a>(&'a self, seed: &'a Seed) -> GetUnspentAddressBuilder<'a> {
-        GetUnspentAddressBuilder::new(self, seed)
+    pub fn get_unspent_address<'a, S: Into<SignerType<'a>>>(&'a self, signer: S) -> GetUnspentAddressBuilder<'a> {
+        GetUnspentAddressBuilder::new(self, signer)
+    }
+
+    /// Consolidates the unspent outputs of the seed addresses into one address, in transactions within the input
+    /// limit.
+    pub fn consolidate_outputs<'a, S: Into<SignerType<'a>>>(&'a self, signer: S) -> ConsolidationBuilder<'a> {
+        ConsolidationBuilder::new(self, signer)
     }
 
-    /// Return a list of addresses from the seed regardless of their validity.
-    pub fn find_addresses<'a>(&'a self, seed: &'a Seed) -> GetAddressesBuilder<'a> {
-        GetAddressesBuilder::new(self, seed)
+    /// Sends a batch of independent transfers from the account of the seed or the signer, selecting their inputs
+    /// from a single snapshot of the unspent outputs so no two transfers spend the same output.
+    pub fn send_batch<'a, S: Into<SignerType<'a>>>(&'a self, signer: S) -> BatchSendBuilder<'a> {
+        BatchSendBuilder::new(self, signer)
     }
 
-    /// Find all messages by provided message IDs.
-    pub async fn find_messages(&self, message_ids: &[MessageId]) -> Result<Vec<Message>> {
-        let mut messages = Vec::new();
+    /// Return a list of addresses from the seed or the signer regardless of their validity.
+    pub fn find_addresses<'a, S: Into<SignerType<'a>>>(&'a self, signer: S) -> GetAddressesBuilder<'a> {
+        GetAddressesBuilder::new(self, signer)
+    }
 
-        // Use a `HashSet` to prevent duplicate message_ids.
-        let mut message_ids_to_query = HashSet::<MessageId>::new();
+    /// Find all messages by the provided indexation keys and message IDs.
+    pub async fn find_messages<I: AsRef<str>>(
+        &self,
+        indexation_keys: &[I],
+        message_ids: &[MessageId],
+    ) -> Result<Vec<Message>> {
+        self.find_messages_with_limit(indexation_keys, message_ids, None).await
+    }
 
-        // Collect the `MessageId` in the HashSet.
+    /// Find the messages by the provided indexation keys and message IDs, fetching at most `limit` messages.
+    /// The messages are fetched concurrently, see [`ClientBuilder::with_request_parallelism`], and the first failed
+    /// request fails the search without waiting for the others, a message the client can't parse with
+    /// [`Error::InvalidMessage`].
+    pub async fn find_messages_with_limit<I: AsRef<str>>(
+        &self,
+        indexation_keys: &[I],
+        message_ids: &[MessageId],
+        limit: Option<usize>,
+    ) -> Result<Vec<Message>> {
+        // Use a `HashSet` to prevent duplicate message_ids, the query order is kept.
+        let mut seen_message_ids = HashSet::<MessageId>::new();
+        let mut message_ids_to_query = Vec::new();
+
+        // Collect the `MessageId`s, then the ones of the indexation keys.
         for message_id in message_ids {
-            message_ids_to_query.insert(message_id.to_owned());
+            if seen_message_ids.insert(*message_id) {
+                message_ids_to_query.push(*message_id);
+            }
+        }
+        for indexation_key in indexation_keys {
+            if limit.map(|limit| message_ids_to_query.len() >= limit).unwrap_or(false) {
+                break;
+            }
+            for message_id in self.get_message().index(indexation_key.as_ref()).await?.iter() {
+                if seen_message_ids.insert(*message_id) {
+                    message_ids_to_query.push(*message_id);
+                }
+            }
+        }
+        if let Some(limit) = limit {
+            message_ids_to_query.truncate(limit);
         }
 
         // Use `get_message().data()` API to get the `Message`.
-        for message_id in message_ids_to_query {
-            let message = self.get_message().data(&message_id).await.unwrap();
-            messages.push(message);
-        }
+        futures::stream::iter(&message_ids_to_query)
+            .map(|message_id| self.get_message().data(message_id))
+            .buffered(self.request_parallelism)
+            .try_collect()
+            .await
+    }
+
+    /// Look the messages of an indexation key up page by page, optionally filtered by their data or the milestone
+    /// referencing them, see [`FindMessagesBuilder`].
+    pub fn find_message_pages(&self, index: &str) -> FindMessagesBuilder<'_> {
+        FindMessagesBuilder::new(self, index)
+    }
+
+    /// Submits data of any length in indexation messages with the index, split into fragments fitting in a message,
+    /// see [`DataMessageBuilder`].
+    pub fn data_message(&self, index: &str, data: Vec<u8>) -> DataMessageBuilder<'_> {
+        DataMessageBuilder::new(self, index, data)
+    }
+
+    /// Reassembles the data submitted with [`data_message`](Self::data_message) from its fragments among the messages
+    /// of the index, with the content hash of the data. A fragment not found among the messages fails with
+    /// [`Error::MissingDataFragments`], fragments with a wrong framing or not matching the hash with
+    /// [`Error::InvalidDataFragments`].
+    pub async fn reassemble_data(&self, index: &str, content_hash: &[u8; 32]) -> Result<Vec<u8>> {
+        crate::api::reassemble_data(self, index, content_hash).await
+    }
 
-        Ok(messages)
+    /// Get the messages with the given ids, in the order of the ids: a message unknown to the node, and to the
+    /// permanode if any, is `None`. The messages are fetched concurrently, see
+    /// [`ClientBuilder::with_request_parallelism`], and the first failed request fails without waiting for the others,
+    /// a message the client can't parse with [`Error::InvalidMessage`].
+    pub async fn get_messages(&self, message_ids: &[MessageId]) -> Result<Vec<Option<Message>>> {
+        futures::stream::iter(message_ids)
+            .map(|message_id| async move {
+                let message = self.get_message().data(message_id).await;
+                if is_not_found(&message) {
+                    Ok(None)
+                } else {
+                    message.map(Some)
+                }
+            })
+            .buffered(self.request_parallelism)
+            .try_collect()
+            .await
     }
 
     /// Return the balance for a provided seed and its wallet chain account index.
     /// Addresses with balance must be consecutive, so this method will return once it encounters a zero
     /// balance address.
-    pub fn get_balance<'a>(&'a self, seed: &'a Seed) -> GetBalanceBuilder<'a> {
-        GetBalanceBuilder::new(self, seed)
+    /// The balances are cross-checked by the quorum nodes if the quorum is enabled, see [`ClientBuilder::with_quorum`].
+    pub fn get_balance<'a, S: Into<SignerType<'a>>>(&'a self, signer: S) -> GetBalanceBuilder<'a> {
+        GetBalanceBuilder::new(self, signer)
+    }
+
+    /// Return the history of a seed, the transactions of its used addresses, see [`GetTransactionHistoryBuilder`].
+    pub fn get_transaction_history<'a, S: Into<SignerType<'a>>>(
+        &'a self,
+        signer: S,
+    ) -> GetTransactionHistoryBuilder<'a> {
+        GetTransactionHistoryBuilder::new(self, signer)
+    }
+
+    /// Return the history of the given addresses: the transactions creating their outputs, spent or not, and the
+    /// transactions consuming their spent outputs, with the value they move from or to each address. A spend without a
+    /// remainder to the addresses is found in the UTXO changes of the milestones, from the milestone referencing the
+    /// first transaction creating a spent output to the solid milestone, see [`Client::get_milestone_utxo_changes`],
+    /// so an old spent output costs a request per milestone since. The transactions the node doesn't know anymore,
+    /// like the pruned ones, are left out, and so are the spends of their outputs. The requests are sent
+    /// concurrently, see [`ClientBuilder::with_request_parallelism`].
+    pub async fn get_address_transaction_history(&self, addresses: &[Bech32Address]) -> Result<TransactionHistory> {
+        let outputs: Vec<(Box<[UTXOInput]>, Box<[UTXOInput]>)> = futures::stream::iter(addresses)
+            .map(|address| async move {
+                let outputs = |include_spent| {
+                    self.get_address().outputs_response(
+                        address,
+                        OutputsOptions {
+                            include_spent,
+                            ..Default::default()
+                        },
+                    )
+                };
+                let (all_outputs, unspent_outputs) = futures::future::try_join(outputs(true), outputs(false)).await?;
+                Ok::<_, Error>((all_outputs.utxo_inputs()?, unspent_outputs.utxo_inputs()?))
+            })
+            .buffered(self.request_parallelism)
+            .try_collect()
+            .await?;
+        let mut output_ids = Vec::new();
+        let mut spent_output_ids = Vec::new();
+        for (all_outputs, unspent_outputs) in outputs {
+            for output_id in all_outputs.into_vec() {
+                if unspent_outputs.contains(&output_id) {
+                    output_ids.push(output_id);
+                } else {
+                    spent_output_ids.push(output_id);
+                }
+            }
+        }
+        crate::api::transaction_history(self, addresses, output_ids, spent_output_ids).await
+    }
+
+    /// Create the proof that the milestone referencing the message includes it, see [`InclusionProof`]. The path from
+    /// the milestone message to the message is searched through the parents with concurrent metadata requests, see
+    /// [`ClientBuilder::with_request_parallelism`]. Fails with [`Error::MessageNotReferenced`] if no milestone
+    /// references the message yet.
+    pub async fn create_inclusion_proof(&self, message_id: &MessageId) -> Result<InclusionProof> {
+        crate::api::create_inclusion_proof(self, message_id).await
     }
 
     /// Return the balance in iota for the given addresses; No seed or security level needed to do this
     /// since we are only checking and already know the addresses.
+    /// Repeated addresses are only queried once, the balances are returned in the order of the first occurrence of
+    /// each address. The balances are fetched concurrently, see [`ClientBuilder::with_request_parallelism`], and the
+    /// first failed request fails with [`Error::AddressBalanceError`] without waiting for the others.
+    /// Each balance is cross-checked by the quorum nodes if the quorum is enabled, see [`ClientBuilder::with_quorum`].
     pub async fn get_address_balances(&self, addresses: &[Bech32Address]) -> Result<Vec<AddressBalancePair>> {
+        futures::stream::iter(unique_addresses(addresses))
+            .map(|address| self.address_balance_pair(address))
+            .buffered(self.request_parallelism)
+            .try_collect()
+            .await
+    }
+
+    /// Return the balances of the given addresses like [`Client::get_address_balances`], along with the error of every
+    /// failed request instead of failing on the first one, in the order of the addresses.
+    pub async fn get_address_balances_settled(
+        &self,
+        addresses: &[Bech32Address],
+    ) -> (Vec<AddressBalancePair>, Vec<(Bech32Address, Error)>) {
+        let addresses = unique_addresses(addresses);
+        let results = futures::stream::iter(&addresses)
+            .map(|address| self.address_balance_pair(address))
+            .buffered(self.request_parallelism)
+            .collect::<Vec<Result<_>>>()
+            .await;
         let mut address_balance_pairs = Vec::new();
-        for address in addresses {
-            let balance = self.get_address().balance(&address).await?;
-            address_balance_pairs.push(AddressBalancePair {
-                address: address.clone(),
-                balance,
-            });
+        let mut errors = Vec::new();
+        for (address, result) in addresses.into_iter().zip(results) {
+            match result {
+                Ok(address_balance_pair) => address_balance_pairs.push(address_balance_pair),
+                Err(error) => errors.push((address.clone(), error)),
+            }
+        }
+        (address_balance_pairs, errors)
+    }
+
+    /// Splits the balance of the address by the ledger inclusion state of the messages creating its unspent outputs:
+    /// an output is confirmed once its message is included in the ledger, and pending until then. The outputs and the
+    /// metadata of their messages are requested concurrently, see
+    /// [`ClientBuilder::with_request_parallelism`](crate::ClientBuilder::with_request_parallelism).
+    pub async fn get_address_balance_split(&self, address: &Bech32Address) -> Result<BalanceSplit> {
+        let output_ids = self.get_address().outputs(address).await?;
+        let output_types = [
+            OutputType::SignatureLockedSingle,
+            OutputType::SignatureLockedDustAllowance,
+        ];
+        let outputs: Vec<OutputMetadata> = futures::stream::iter(output_ids.iter())
+            .map(|output_id| self.get_output_of_types(output_id, &output_types))
+            .buffered(self.request_parallelism)
+            .try_collect()
+            .await?;
+        // The outputs of a message share its inclusion state
+        let mut message_ids = Vec::new();
+        let mut message_indexes = Vec::new();
+        for output in &outputs {
+            let message_id = MessageId::new(
+                output.message_id[..]
+                    .try_into()
+                    .map_err(|_| Error::InvalidParameter(String::from("message id")))?,
+            );
+            match message_ids.iter().position(|id| *id == message_id) {
+                Some(index) => message_indexes.push(index),
+                None => {
+                    message_indexes.push(message_ids.len());
+                    message_ids.push(message_id);
+                }
+            }
+        }
+        let included: Vec<bool> = futures::stream::iter(&message_ids)
+            .map(|message_id| async move {
+                let metadata = self.get_message().metadata(message_id).await?;
+                Ok::<_, Error>(metadata.ledger_inclusion_state == Some(LedgerInclusionState::Included))
+            })
+            .buffered(self.request_parallelism)
+            .try_collect()
+            .await?;
+        let mut balance = BalanceSplit::default();
+        for (output, index) in outputs.iter().zip(message_indexes) {
+            if included[index] {
+                balance.confirmed = checked_amount_sum(vec![balance.confirmed, output.amount])?;
+            } else {
+                balance.pending = checked_amount_sum(vec![balance.pending, output.amount])?;
+            }
         }
-        Ok(address_balance_pairs)
+        Ok(balance)
+    }
+
+    async fn address_balance_pair(&self, address: &Bech32Address) -> Result<AddressBalancePair> {
+        let balance = self
+            .get_address()
+            .balance_response(address)
+            .await
+            .map_err(|e| Error::AddressBalanceError(address.to_string(), Box::new(e)))?;
+        Ok(AddressBalancePair {
+            address: address.clone(),
+            balance: balance.balance,
+            dust_allowed: balance.dust_allowed,
+        })
     }
 
     /// Retries (promotes or reattaches) a message for provided message id. Message should only be
     /// retried only if they are valid and haven't been confirmed for a while.
     pub async fn retry(&self, message_id: &MessageId) -> Result<(MessageId, Message)> {
         // Get the metadata to check if it needs to promote or reattach
-        let message_metadata = self.get_message().metadata(message_id).await?;
+        let message_metadata = self.unreferenced_message_metadata(message_id).await?;
         if message_metadata.should_promote.unwrap_or(false) {
             self.promote_unchecked(message_id).await
         } else if message_metadata.should_reattach.unwrap_or(false) {
@@ -697,4 +2405,252 @@ impl Client {
             Err(Error::NoNeedPromoteOrReattach(message_id.to_string()))
         }
     }
+
+    /// Retries (promotes or reattaches) a message until a milestone references it or one of its reattachments.
+    /// The metadata is polled every `interval`, 5 seconds by default, at most `max_attempts` times, 40 by default.
+    /// Returns the inclusion states of the message and its reattachments once one of them is referenced, or
+    /// `Error::NotIncluded` with the last known states. A conflicting transaction ends the retries with
+    /// `Error::ConflictingTransaction`.
+    pub async fn retry_until_included(
+        &self,
+        message_id: &MessageId,
+        interval: Option<Duration>,
+        max_attempts: Option<u64>,
+    ) -> Result<Vec<MessageInclusionState>> {
+        let interval = interval.unwrap_or(DEFAULT_RETRY_UNTIL_INCLUDED_INTERVAL);
+        let max_attempts = max_attempts.unwrap_or(DEFAULT_RETRY_UNTIL_INCLUDED_MAX_ATTEMPTS);
+        if max_attempts == 0 {
+            return Err(Error::InvalidParameter("max attempts".to_string()));
+        }
+        // the message and its reattachments
+        let mut message_ids = vec![*message_id];
+        let mut states = Vec::new();
+        for attempt in 1..=max_attempts {
+            let mut metadata = Vec::new();
+            // the request errors tell the attempt they failed at
+            for message_id in &message_ids {
+                metadata.push(
+                    self.get_message()
+                        .metadata(message_id)
+                        .await
+                        .map_err(|error| error.with_attempt(attempt))?,
+                );
+            }
+            states = message_ids
+                .iter()
+                .zip(&metadata)
+                .map(|(message_id, metadata)| MessageInclusionState {
+                    message_id: message_id.to_string(),
+                    inclusion_state: metadata.ledger_inclusion_state,
+                })
+                .collect();
+            if let Some(state) = states
+                .iter()
+                .find(|state| state.inclusion_state == Some(LedgerInclusionState::Conflicting))
+            {
+                return Err(Error::ConflictingTransaction(state.message_id.clone()));
+            }
+            if metadata
+                .iter()
+                .any(|metadata| metadata.referenced_by_milestone_index.is_some())
+            {
+                #[cfg(feature = "metrics")]
+                self.metrics.record_confirmation();
+                return Ok(states);
+            }
+            if attempt == max_attempts {
+                break;
+            }
+
+            // only the latest reattachment is retried
+            let latest_message_id = *message_ids.last().expect("the message ids can't be empty");
+            let latest_metadata = metadata.last().expect("the metadata can't be empty");
+            if latest_metadata.should_promote.unwrap_or(false) {
+                self.promote_unchecked(&latest_message_id)
+                    .await
+                    .map_err(|error| error.with_attempt(attempt))?;
+            } else if latest_metadata.should_reattach.unwrap_or(false) {
+                let (reattached_message_id, _) = self
+                    .reattach_unchecked(&latest_message_id)
+                    .await
+                    .map_err(|error| error.with_attempt(attempt))?;
+                message_ids.push(reattached_message_id);
+            }
+            sleep(interval).await;
+        }
+        Err(Error::NotIncluded(message_id.to_string(), max_attempts, states))
+    }
+
+    /// Waits until the confirmed balance of an address reaches `min_amount`, a zero amount is reached by the first
+    /// balance query. The balance is polled every `interval`, 5 seconds by default, and sooner on the output events
+    /// of the address if the client is connected to an MQTT broker. Returns the balance with the unspent outputs of
+    /// the address, or `Error::BalanceTimeout` with the last known balance once `timeout` expired, 10 minutes by
+    /// default. A failed node request ends the wait with its error.
+    pub async fn wait_for_balance(
+        &self,
+        address: &Bech32Address,
+        min_amount: u64,
+        interval: Option<Duration>,
+        timeout: Option<Duration>,
+    ) -> Result<ReachedBalance> {
+        let interval = interval.unwrap_or(DEFAULT_WAIT_FOR_BALANCE_INTERVAL);
+        let deadline = Instant::now() + timeout.unwrap_or(DEFAULT_WAIT_FOR_BALANCE_TIMEOUT);
+        // the output events of the address wake the wait, the topic is unsubscribed once the wait is over
+        #[cfg(feature = "mqtt")]
+        let (_waker, mut events) = {
+            let (sender, events) = futures::channel::mpsc::unbounded();
+            let waker = Topic::new(format!("addresses/{}/outputs", address))
+                .ok()
+                .and_then(|topic| {
+                    TopicWaker::new(self, topic, move |_| {
+                        let _ = sender.unbounded_send(());
+                    })
+                });
+            let events = waker.as_ref().map(|_| events);
+            (waker, events)
+        };
+        loop {
+            let balance = self.get_address().balance(address).await?;
+            if balance >= min_amount {
+                let outputs = self.find_outputs(&[], &[address.clone()]).await?;
+                return Ok(ReachedBalance {
+                    address: address.clone(),
+                    balance,
+                    outputs,
+                });
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Error::BalanceTimeout(address.to_string(), min_amount, balance));
+            }
+            let wait = interval.min(deadline - now);
+            #[cfg(feature = "mqtt")]
+            if let Some(events) = events.as_mut() {
+                futures::future::select(Box::pin(sleep(wait)), events.next()).await;
+                continue;
+            }
+            sleep(wait).await;
+        }
+    }
+
+    /// Delivers the outputs created and consumed on the address of the monitor to the callback until it returns
+    /// `false`: the activity of the milestones since the last processed one is caught up first, then the live events
+    /// are delivered, and the catch-up runs again after every reconnection to the broker, see [`AddressMonitor`]. The
+    /// client has to be connected to a broker already, e.g. by a subscription, otherwise the monitoring fails with
+    /// [`Error::MqttConnectionClosed`], as it does once the connection is lost for good. A failed catch-up ends the
+    /// monitoring with its error, [`AddressMonitor::last_milestone_index`] tells where to resume.
+    #[cfg(feature = "mqtt")]
+    pub async fn monitor_address<C: FnMut(AddressActivity) -> bool>(
+        &self,
+        monitor: &AddressMonitor,
+        mut callback: C,
+    ) -> Result<()> {
+        enum MonitorSignal {
+            Event(TopicEvent),
+            Connected,
+            Closed,
+        }
+        let (sender, mut signals) = futures::channel::mpsc::unbounded();
+        let mut wakers = Vec::new();
+        for topic in monitor.topics()? {
+            let sender = sender.clone();
+            let waker = TopicWaker::new(self, topic, move |event| {
+                let _ = sender.unbounded_send(MonitorSignal::Event(event.clone()));
+            })
+            .ok_or(Error::MqttConnectionClosed)?;
+            wakers.push(waker);
+        }
+        let automatic_reconnect = self.broker_options.automatic_reconnect;
+        let _connection_waker = ConnectionWaker::new(self, move |event| {
+            let signal = match event {
+                MqttEvent::Connected => MonitorSignal::Connected,
+                MqttEvent::Disconnected if !automatic_reconnect => MonitorSignal::Closed,
+                MqttEvent::Error(_) => MonitorSignal::Closed,
+                _ => return,
+            };
+            let _ = sender.unbounded_send(signal);
+        });
+
+        // the live events received during a catch-up wait for it in the channel
+        if !monitor.catch_up(self, &mut callback).await? {
+            return Ok(());
+        }
+        while let Some(signal) = signals.next().await {
+            match signal {
+                // the events of other output types aren't activities of the address
+                MonitorSignal::Event(event) => {
+                    if let Ok(Some(activity)) = monitor.live_activity(&event) {
+                        if !callback(activity) {
+                            return Ok(());
+                        }
+                    }
+                }
+                MonitorSignal::Connected => {
+                    if !monitor.catch_up(self, &mut callback).await? {
+                        return Ok(());
+                    }
+                }
+                MonitorSignal::Closed => break,
+            }
+        }
+        Err(Error::MqttConnectionClosed)
+    }
+}
+
+// Waits for the duration with a browser timer, the tokio timer needs a runtime.
+#[cfg(target_arch = "wasm32")]
+async fn sleep(duration: Duration) {
+    let millis = duration.as_millis().try_into().unwrap_or(u32::MAX);
+    gloo_timers::future::TimeoutFuture::new(millis).await
+}
+
+/// Hashes a network id string into the network id of the messages, the first 8 bytes of its BLAKE2b-256 hash.
+pub fn hash_network_id(network_id: &str) -> u64 {
+    u64::from_le_bytes(blake2b_256(network_id.as_bytes())[0..8].try_into().unwrap())
+}
+
+// The BLAKE2b-256 hash of the bytes, the message and transaction ids.
+pub(crate) fn blake2b_256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = VarBlake2b::new(32).unwrap();
+    hasher.update(bytes);
+    let mut result: [u8; 32] = [0; 32];
+    hasher.finalize_variable(|res| {
+        result = res.try_into().unwrap();
+    });
+    result
+}
+
+/// Computes the id of a packed message, the BLAKE2b-256 hash of its bytes.
+pub fn compute_message_id(message_bytes: &[u8]) -> MessageId {
+    MessageId::new(blake2b_256(message_bytes))
+}
+
+/// Computes the id of a transaction, the BLAKE2b-256 hash of the packed payload with its type.
+pub fn compute_transaction_id(payload: &TransactionPayload) -> TransactionId {
+    let payload = Payload::Transaction(Box::new(payload.clone()));
+    TransactionId::new(blake2b_256(&payload.pack_new()))
+}
+
+/// Verifies an Ed25519 signature of the message bytes, e.g. of a packed transaction essence. Returns false for a
+/// signature of other bytes or by another key, and an error for a public key that isn't 32 bytes or a signature that
+/// isn't 64 bytes.
+pub fn verify_ed25519_signature(public_key: &[u8], signature: &[u8], message: &[u8]) -> Result<bool> {
+    for (field, value, expected_length) in &[
+        ("public key", public_key, ED25519_PUBLIC_KEY_LENGTH),
+        ("signature", signature, ED25519_SIGNATURE_LENGTH),
+    ] {
+        if value.len() != *expected_length {
+            return Err(Error::InvalidParameter(format!(
+                "invalid Ed25519 {} length: expected {} bytes, found {}",
+                field,
+                expected_length,
+                value.len()
+            )));
+        }
+    }
+    let public_key = Ed25519PublicKey::from_bytes(public_key)
+        .map_err(|_| Error::InvalidParameter("invalid Ed25519 public key".to_string()))?;
+    let signature = Ed25519EssenceSignature::from_bytes(signature)
+        .map_err(|_| Error::InvalidParameter("invalid Ed25519 signature".to_string()))?;
+    Ok(public_key.verify(message, &signature).is_ok())
 }
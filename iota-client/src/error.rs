@@ -1,7 +1,16 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-//! Error handling in iota-client crate.
+//! Error handling in iota-client crate. The variants of the optional features are only exposed with their feature.
+
+#[cfg(feature = "client")]
+use crate::types::MessageInclusionState;
+
+#[cfg(feature = "client")]
+use reqwest::{Method, Url};
+
+#[cfg(feature = "client")]
+use std::{fmt, time::Duration};
 
 /// Type alias of `Result` in iota-client
 pub type Result<T> = std::result::Result<T, Error>;
@@ -10,14 +19,61 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// Error type of the iota client crate.
 pub enum Error {
     /// Error when building indexation messages
+    #[cfg(feature = "client")]
     #[error("Error when building indexation message: {0}")]
     IndexationError(String),
     /// Error when building transaction messages
+    #[cfg(feature = "client")]
     #[error("Error when building transaction message")]
     TransactionError,
-    /// The wallet account doesn't have enough balance
-    #[error("The wallet account doesn't have enough balance. It only has {0}")]
-    NotEnoughBalance(u64),
+    /// The wallet account doesn't have enough balance, with the available and the required amounts
+    #[cfg(feature = "client")]
+    #[error("The wallet account doesn't have enough balance. It only has {0}, {1} is required")]
+    NotEnoughBalance(u64, u64),
+    /// An amount, a balance or a sum of amounts exceeds the IOTA supply, see [`IOTA_SUPPLY`](crate::api::IOTA_SUPPLY)
+    #[cfg(feature = "client")]
+    #[error("The amount {0} exceeds the IOTA supply of {}", crate::api::IOTA_SUPPLY)]
+    ValueOverflow(u128),
+    /// Dust error, outputs below the dust threshold can't be created
+    #[cfg(feature = "client")]
+    #[error("Output of {1} to {0} is dust, outputs must transfer at least {2}")]
+    DustError(String, u64, u64),
+    /// A dust output to an address without enough dust allowance, the node would reject it after the PoW
+    #[cfg(feature = "client")]
+    #[error(
+        "Output of {1} to {0} violates the dust protection, the address allows {2} more dust outputs: send at least \
+         1000000 or first add a dust allowance output to the address"
+    )]
+    DustProtection(String, u64, usize),
+    /// A dust remainder to an address without enough dust allowance, the node would reject it after the PoW
+    #[cfg(feature = "client")]
+    #[error(
+        "Remainder of {1} to {0} violates the dust protection, the address allows {2} more dust outputs: change the \
+         amount so the remainder is 0 or at least 1000000, or first add a dust allowance output to the address"
+    )]
+    DustRemainder(String, u64, usize),
+    /// Dust allowance inputs leaving an address more dust outputs than its remaining allowance allows, with the
+    /// number of dust outputs left and allowed
+    #[cfg(feature = "client")]
+    #[error(
+        "Spending the dust allowance of {0} leaves {1} dust outputs on the address, its allowance left allows {2}: \
+         spend the dust outputs too, or keep the dust allowance outputs of the address"
+    )]
+    DustAllowanceInput(String, usize, usize),
+    /// The transaction has more outputs than the protocol allows
+    #[cfg(feature = "client")]
+    #[error("The transaction has {0} outputs, it can have at most {1}")]
+    TooManyOutputs(usize, usize),
+    /// The balance covers the amount but with more inputs than a transaction can have
+    #[cfg(feature = "client")]
+    #[error(
+        "The amount needs {0} inputs, a transaction can have at most {1}: consolidate the outputs first, or send a \
+         smaller amount"
+    )]
+    TooManyInputs(usize, usize),
+    /// Error when signing a prepared transaction
+    #[error("Failed to sign the transaction: {0}")]
+    SigningError(String),
     /// Missing required parameters
     #[error("Must provide required parameter: {0}")]
     MissingParameter(String),
@@ -25,51 +81,476 @@ pub enum Error {
     #[error("Parameter is invalid:{0}")]
     InvalidParameter(String),
     /// Found Spent Address that still has balance
+    #[cfg(feature = "client")]
     #[error("Found Spent Address that still has balance.")]
     SpentAddress,
-    /// Error from RestAPI calls with unexpected status code response
-    #[error("Response error with status code {0}: {1}")]
-    ResponseError(u16, String),
+    /// Error from RestAPI calls with unexpected status code response, with the status, the body and the request
+    #[cfg(feature = "client")]
+    #[error("Response error with status code {0}: {1} ({2})")]
+    ResponseError(u16, String, Box<RequestContext>),
+    /// The node is still rate limited once the retries are exhausted, with the status code, the delay it asks for with
+    /// its `Retry-After` header and the request
+    #[cfg(feature = "client")]
+    #[error("The node is rate limited with status code {0}{} ({2})", format_retry_after(.1))]
+    RateLimited(u16, Option<Duration>, Box<RequestContext>),
     /// No node available in the synced node pool
+    #[cfg(feature = "client")]
     #[error("No node available")]
     SyncedNodePoolEmpty,
+    /// No node with the PoW feature, and the submit and remote PoW roles, available in the synced node pool
+    #[cfg(feature = "client")]
+    #[error("No node with the PoW feature available, enable the local PoW or add a node with remote PoW")]
+    PowNodePoolEmpty,
+    /// No node with the submit role available in the synced node pool
+    #[cfg(feature = "client")]
+    #[error("No node available to submit the messages, add a synced node with the submit role")]
+    SubmitNodePoolEmpty,
+    /// The client is offline, see [`crate::ClientBuilder::offline`]
+    #[cfg(feature = "client")]
+    #[error("offline client: network operation not available")]
+    OfflineClient,
+    /// The node isn't one of the client nodes
+    #[cfg(feature = "client")]
+    #[error("Node {0} isn't one of the client nodes")]
+    UnknownNode(String),
     /// Error on Url type conversion
+    #[cfg(feature = "client")]
     #[error("Failed to parse url")]
     UrlError,
-    /// Errors from reqwest api call
-    #[error("{0}")]
-    ReqwestError(#[from] reqwest::Error),
+    /// Errors from reqwest api call, with the request if it failed on a node request
+    #[cfg(feature = "client")]
+    #[error("{0}{}", format_request_context(.1))]
+    ReqwestError(reqwest::Error, Option<Box<RequestContext>>),
+    /// The node didn't answer before the request timeout
+    #[cfg(feature = "client")]
+    #[error("Request timed out{}", format_request_context(.0))]
+    Timeout(Option<Box<RequestContext>>),
     /// Hex string convert error
     #[error("{0}")]
     FromHexError(#[from] hex::FromHexError),
     /// Message types error
     #[error("{0}")]
     MessageError(bee_message::Error),
+    /// The message is built for another network, with the network id of the message, and the network and the network
+    /// id of the client
+    #[cfg(feature = "client")]
+    #[error("The message is built for the network id {0}, the client network {1} has the network id {2}")]
+    NetworkIdMismatch(u64, String, u64),
+    /// The address is an address of another network, with the HRP of the address and the HRP of the client network
+    #[cfg(feature = "client")]
+    #[error("The address {0} has the bech32 HRP {1}, the addresses of the client network have the HRP {2}")]
+    Bech32HrpMismatch(String, String, String),
     /// The message cannot be promoted or reattached
+    #[cfg(feature = "client")]
     #[error("Message ID `{0}` doesn't need to be promoted or reattached")]
     NoNeedPromoteOrReattach(String),
+    /// The message is already referenced by a milestone, it can't be promoted or reattached
+    #[cfg(feature = "client")]
+    #[error("Message ID `{0}` is already referenced by the milestone {1}")]
+    MessageAlreadyReferenced(String, u64),
+    /// The transaction of the message, or of one of its reattachments, conflicts with the ledger
+    #[cfg(feature = "client")]
+    #[error("The transaction of the message ID `{0}` conflicts with the ledger")]
+    ConflictingTransaction(String),
+    /// Neither the message nor its reattachments were included after the maximum number of attempts
+    #[cfg(feature = "client")]
+    #[error(
+        "Message ID `{0}` wasn't included after {1} attempts, last known states: {}",
+        format_inclusion_states(.2)
+    )]
+    NotIncluded(String, u64, Vec<MessageInclusionState>),
+    /// The balance of the address didn't reach the minimum amount before the timeout
+    #[cfg(feature = "client")]
+    #[error("The balance of the address {0} didn't reach {1} before the timeout, the last known balance is {2}")]
+    BalanceTimeout(String, u64, u64),
+    /// The balance request of an address failed
+    #[cfg(feature = "client")]
+    #[error("Failed to get the balance of the address {0}: {1}")]
+    AddressBalanceError(String, Box<Error>),
+    /// The client can't parse the message answered for the message id
+    #[cfg(feature = "client")]
+    #[error("Invalid message {0}: {1}")]
+    InvalidMessage(String, Box<Error>),
+    /// The packed message hashes to another message id, with the id of the packed message
+    #[cfg(feature = "client")]
+    #[error("The packed message has the message id {0}")]
+    MessageIdMismatch(String),
+    /// The node pruned the requested data: a milestone at or below its pruning index, or a message it reports as pruned
+    #[cfg(feature = "client")]
+    #[error("The node pruned {requested}, its pruning index is {pruning_index}")]
+    PrunedData {
+        /// The pruned data
+        requested: PrunedRequest,
+        /// The pruning index of the node
+        pruning_index: u64,
+    },
+    /// The message isn't referenced by a milestone yet, so there's no proof of its inclusion
+    #[cfg(feature = "client")]
+    #[error("Message ID `{0}` isn't referenced by a milestone yet")]
+    MessageNotReferenced(String),
+    /// The node answers don't link the message to the milestone referencing it, with the message id and the milestone
+    /// index
+    #[cfg(feature = "client")]
+    #[error("Message ID `{0}` isn't in the past cone of the milestone {1} according to the node")]
+    InclusionPathNotFound(String, u64),
+    /// An inclusion proof doesn't verify, with the index of the broken link in its chain and the reason
+    #[cfg(feature = "client")]
+    #[error("Invalid inclusion proof at the link {0}: {1}")]
+    InvalidInclusionProof(usize, String),
+    /// A milestone doesn't validate against the coordinator keys, with its index and the reason
+    #[cfg(feature = "client")]
+    #[error("Invalid milestone {0}: {1}")]
+    InvalidMilestone(u32, String),
+    /// No message including the transaction, it isn't included in the ledger or the node pruned it
+    #[cfg(feature = "client")]
+    #[error("Transaction {0} isn't included in the ledger or is pruned by the node")]
+    TransactionNotIncluded(String),
+    /// An input of a transaction plan was spent since the dry run, with the output id of the input
+    #[cfg(feature = "client")]
+    #[error("The transaction plan is stale, its input {0} was spent since the dry run")]
+    StalePlan(String),
+    /// The message packing a transfer of a batch failed, with the index of the transfer getting the error of the
+    /// message and the error, see [`BatchSendBuilder::with_packing`](crate::api::BatchSendBuilder::with_packing)
+    #[cfg(feature = "client")]
+    #[error("The message packing the transfer {0} of the batch failed: {1}")]
+    BatchMessageFailed(usize, String),
+    /// The input addresses of the transfer already spent outputs, see the address reuse check of the client
+    #[cfg(feature = "client")]
+    #[error("The input addresses {} already spent outputs", .0.join(", "))]
+    AddressReuse(Vec<String>),
+    /// The custom parents of a message unknown to the node
+    #[cfg(feature = "client")]
+    #[error("The node doesn't know the message parents {}", .0.join(", "))]
+    UnknownParents(Vec<String>),
+    /// The node refused a message with custom parents because of its parents
+    #[cfg(feature = "client")]
+    #[error("The node refused the message with the parents {}: {1}", .0.join(", "))]
+    ParentsRejected(Vec<String>, String),
+    /// The node refused the request of an authenticated route
+    #[cfg(feature = "client")]
+    #[error("Unauthorized request to {0}, configure the node auth")]
+    Unauthorized(String),
     /// Mqtt client error
     #[cfg(feature = "mqtt")]
     #[error("{0}")]
     MqttClientError(#[from] paho_mqtt::errors::Error),
     /// Invalid MQTT topic.
+    #[cfg(feature = "mqtt")]
     #[error("The MQTT topic {0} is invalid")]
     InvalidMqttTopic(String),
     /// MQTT connection not found (all nodes MQTT's are disabled)
-    #[error("MQTT connection not found (all nodes have the MQTT plugin disabled)")]
+    #[cfg(feature = "mqtt")]
+    #[error("MQTT connection not found (all nodes with the MQTT role have the MQTT plugin disabled)")]
     MqttConnectionNotFound,
+    /// The MQTT connection isn't established, or was lost for good
+    #[cfg(feature = "mqtt")]
+    #[error("The MQTT connection is closed, subscribe to a topic to connect")]
+    MqttConnectionClosed,
+    /// The MQTT broker answered with TLS to a plain connection (`true`), or without TLS to a TLS connection (`false`)
+    #[cfg(feature = "mqtt")]
+    #[error("The MQTT broker {0} {}", format_scheme_mismatch(*.1))]
+    MqttSchemeMismatch(String, bool),
     /// IO error
     #[error("{0}")]
     IoError(#[from] std::io::Error),
     /// JSON error
+    #[cfg(feature = "client")]
     #[error("{0}")]
     Json(#[from] serde_json::Error),
+    /// The storage of the client state failed, see [`Storage`](crate::Storage)
+    #[cfg(feature = "client")]
+    #[error("Storage error: {0}")]
+    Storage(String),
     /// PoW error
+    #[cfg(feature = "pow")]
     #[error("{0}")]
     Pow(String),
+    /// The operation was cancelled with its [`CancellationToken`](crate::CancellationToken)
+    #[error("The operation was cancelled")]
+    Cancelled,
+    /// The outsourced nonce provider failed or timed out, see
+    /// [`ClientBuilder::with_nonce_provider`](crate::ClientBuilder::with_nonce_provider)
+    #[error("The nonce provider failed: {0}")]
+    NonceProvider(String),
+    /// The node refused to do the PoW of a message submitted without local PoW
+    #[cfg(feature = "client")]
+    #[error("Node {0} refused to do the remote PoW, enable the local PoW: {1}")]
+    RemotePowRefused(String, String),
     /// Address not found
+    #[cfg(feature = "client")]
     #[error("Address not found in range")]
     AddressNotFound,
+    /// Fewer synced nodes than the quorum size
+    #[cfg(feature = "client")]
+    #[error("Not enough synced nodes for the quorum: {0} synced, {1} required")]
+    QuorumPoolSizeError(usize, usize),
+    /// The nodes of the quorum didn't agree on the answer, with the answer of each node
+    #[cfg(feature = "client")]
+    #[error("The quorum nodes disagree: {}", format_quorum_answers(.0))]
+    QuorumError(Vec<(String, String)>),
+    /// No fragment of the data with the hex-encoded content hash was found among the messages of the index, see
+    /// [`Client::reassemble_data`](crate::Client::reassemble_data)
+    #[cfg(feature = "client")]
+    #[error("No fragment of the data {0} found")]
+    DataNotFound(String),
+    /// Fragments of the data are missing among the messages of the index, they might not be solid yet or pruned
+    #[cfg(feature = "client")]
+    #[error(
+        "Fragments {} of the {fragment_count} fragments of the data {content_hash} are missing",
+        format_missing_fragments(.missing)
+    )]
+    MissingDataFragments {
+        /// The hex-encoded content hash of the data
+        content_hash: String,
+        /// The sequence numbers of the missing fragments, the first 100 of them
+        missing: Vec<u32>,
+        /// The number of fragments of the data
+        fragment_count: u32,
+    },
+    /// The fragments of the data with the hex-encoded content hash have an inconsistent framing, or don't add up to
+    /// the data of the hash
+    #[cfg(feature = "client")]
+    #[error("Invalid fragments of the data {0}: {1}")]
+    InvalidDataFragments(String, String),
+    /// The Stronghold snapshot file doesn't exist
+    #[cfg(feature = "stronghold")]
+    #[error("Stronghold snapshot {0} not found")]
+    StrongholdSnapshotNotFound(String),
+    /// The password can't decrypt the Stronghold snapshot
+    #[cfg(feature = "stronghold")]
+    #[error("Invalid password of the Stronghold snapshot {0}")]
+    StrongholdInvalidPassword(String),
+    /// A derivation path with a non-hardened segment or an index above 2^31, Ed25519 SLIP-10 only derives hardened
+    /// segments
+    #[error("Invalid derivation path: {0}")]
+    InvalidDerivationPath(String),
+    /// Stronghold error
+    #[cfg(feature = "stronghold")]
+    #[error("Stronghold error: {0}")]
+    StrongholdError(String),
+    /// No Ledger Nano device or simulator was found
+    #[cfg(feature = "ledger")]
+    #[error("Ledger Nano device not found")]
+    LedgerDeviceNotFound,
+    /// The IOTA app isn't open on the Ledger Nano
+    #[cfg(feature = "ledger")]
+    #[error("The IOTA app isn't open on the Ledger Nano: {0}")]
+    LedgerAppNotOpen(String),
+    /// The user rejected the address or the transaction on the Ledger Nano
+    #[cfg(feature = "ledger")]
+    #[error("Denied by the user on the Ledger Nano")]
+    LedgerDeniedByUser,
+    /// Ledger Nano error
+    #[cfg(feature = "ledger")]
+    #[error("Ledger Nano error: {0}")]
+    LedgerError(String),
+    /// A snapshot file that can't be parsed, or whose entries don't match its header or the other snapshot
+    #[cfg(feature = "snapshot")]
+    #[error("Invalid snapshot at byte {offset}, in the {section}: {reason}")]
+    InvalidSnapshot {
+        /// The offset of the failure from the start of the file
+        offset: u64,
+        /// The section of the failure
+        section: crate::snapshot::SnapshotSection,
+        /// The failure
+        reason: String,
+    },
+    /// The amounts of a snapshot don't add up, with the summed outputs, their sum and the expected sum: the outputs of
+    /// a full snapshot to the supply, or the outputs created by a milestone to the consumed ones
+    #[cfg(feature = "snapshot")]
+    #[error("The {0} of the snapshot sum up to {1}, {2} was expected")]
+    SnapshotSupplyMismatch(String, u128, u128),
+}
+
+/// The node request an error comes from, see [`Error::request_context`].
+#[cfg(feature = "client")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestContext {
+    /// The URL of the request, without the credentials
+    pub url: String,
+    /// The node the request was sent to, without the credentials
+    pub node: String,
+    /// The API command, the method and the path of the request, like `GET /api/v1/info`
+    pub endpoint: String,
+    /// The attempt number of a retried command, starting at 1, see
+    /// [`Client::retry_until_included`](crate::Client::retry_until_included)
+    pub attempt: Option<u64>,
+    /// The time from sending the request to its failure
+    pub elapsed: Duration,
+}
+
+#[cfg(feature = "client")]
+impl RequestContext {
+    pub(crate) fn new(method: &Method, url: &Url, elapsed: Duration) -> Self {
+        let mut url = url.clone();
+        let _ = url.set_username("");
+        let _ = url.set_password(None);
+        Self {
+            node: crate::middleware::redacted_origin(&url),
+            endpoint: format!("{} {}", method, url.path()),
+            url: url.to_string(),
+            attempt: None,
+            elapsed,
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} on {}", self.endpoint, self.node)?;
+        if let Some(attempt) = self.attempt {
+            write!(f, ", attempt {}", attempt)?;
+        }
+        write!(f, ", after {} ms", self.elapsed.as_millis())
+    }
+}
+
+/// The data a node pruned, see [`Error::PrunedData`].
+#[cfg(feature = "client")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PrunedRequest {
+    /// A milestone, with its index
+    Milestone(u64),
+    /// The UTXO changes of a milestone, with its index
+    MilestoneUtxoChanges(u64),
+    /// A message, with its id
+    Message(String),
+}
+
+#[cfg(feature = "client")]
+impl fmt::Display for PrunedRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Milestone(index) => write!(f, "milestone {}", index),
+            Self::MilestoneUtxoChanges(index) => write!(f, "the UTXO changes of milestone {}", index),
+            Self::Message(message_id) => write!(f, "message {}", message_id),
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl Error {
+    // The error of a request that failed without a response.
+    pub(crate) fn request(error: reqwest::Error, context: RequestContext) -> Self {
+        let context = Some(Box::new(context));
+        if error.is_timeout() {
+            Error::Timeout(context)
+        } else {
+            Error::ReqwestError(error, context)
+        }
+    }
+
+    /// The node request the error comes from: a node-reported error, a timeout or a failed connection. The balance
+    /// errors of an address give the request of their inner error.
+    pub fn request_context(&self) -> Option<&RequestContext> {
+        match self {
+            Error::ResponseError(_, _, context) | Error::RateLimited(_, _, context) => Some(context),
+            Error::Timeout(context) | Error::ReqwestError(_, context) => context.as_deref(),
+            Error::AddressBalanceError(_, error) => error.request_context(),
+            _ => None,
+        }
+    }
+
+    /// The node the failed request was sent to, without the credentials.
+    pub fn node(&self) -> Option<&str> {
+        self.request_context().map(|context| context.node.as_str())
+    }
+
+    /// The API command of the failed request, like `GET /api/v1/info`.
+    pub fn endpoint(&self) -> Option<&str> {
+        self.request_context().map(|context| context.endpoint.as_str())
+    }
+
+    /// The attempt number of the retried command the request belongs to.
+    pub fn attempt(&self) -> Option<u64> {
+        self.request_context().and_then(|context| context.attempt)
+    }
+
+    /// The time from sending the failed request to its failure.
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.request_context().map(|context| context.elapsed)
+    }
+
+    // Sets the attempt number of the retried command of a request error.
+    pub(crate) fn with_attempt(mut self, attempt: u64) -> Self {
+        let context = match &mut self {
+            Error::ResponseError(_, _, context) | Error::RateLimited(_, _, context) => Some(context),
+            Error::Timeout(context) | Error::ReqwestError(_, context) => context.as_mut(),
+            _ => None,
+        };
+        if let Some(context) = context {
+            context.attempt.replace(attempt);
+        }
+        self
+    }
+}
+
+#[cfg(feature = "client")]
+fn format_request_context(context: &Option<Box<RequestContext>>) -> String {
+    context
+        .as_ref()
+        .map(|context| format!(" ({})", context))
+        .unwrap_or_default()
+}
+
+#[cfg(feature = "client")]
+fn format_retry_after(retry_after: &Option<Duration>) -> String {
+    match retry_after {
+        Some(delay) => format!(", it asks to wait {} s", delay.as_secs_f64().ceil()),
+        None => String::new(),
+    }
+}
+
+#[cfg(feature = "client")]
+fn format_inclusion_states(states: &[MessageInclusionState]) -> String {
+    states
+        .iter()
+        .map(|state| match state.inclusion_state {
+            Some(inclusion_state) => format!("{} {}", state.message_id, inclusion_state),
+            None => format!("{} pending", state.message_id),
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(feature = "mqtt")]
+fn format_scheme_mismatch(tls: bool) -> &'static str {
+    if tls {
+        "only accepts TLS connections, use the mqtts or wss scheme"
+    } else {
+        "doesn't accept TLS connections, use the mqtt or ws scheme"
+    }
+}
+
+#[cfg(feature = "client")]
+fn format_missing_fragments(missing: &[u32]) -> String {
+    missing
+        .iter()
+        .map(|sequence| sequence.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(feature = "client")]
+fn format_quorum_answers(answers: &[(String, String)]) -> String {
+    answers
+        .iter()
+        .map(|(node, answer)| format!("{} answered {}", node, answer))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// the errors of the node requests are converted with `Error::request`, to keep the request
+#[cfg(feature = "client")]
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            Error::Timeout(None)
+        } else {
+            Error::ReqwestError(error, None)
+        }
+    }
 }
 
 // can't use #[from] on bee_message::Error so manually converting it
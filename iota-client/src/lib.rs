@@ -2,6 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Provides access to the Iota Client API
+//!
+//! The HTTP client is behind the `client` feature, enabled by default with `mqtt` and `pow`: without it the crate
+//! derives the addresses and signs with the [`signer`]s, and finishes the messages with a [`NonceProvider`].
 
 #![deny(unused_extern_crates)]
 #![warn(missing_docs, rust_2018_idioms, unreachable_pub)]
@@ -10,28 +13,68 @@
 extern crate serde;
 
 pub mod api;
+#[cfg(feature = "client")]
 pub mod builder;
+pub mod cancellation;
+#[cfg(feature = "client")]
 pub mod client;
 pub mod error;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "client")]
+pub mod middleware;
+#[cfg(feature = "client")]
 pub mod node;
+pub mod pow;
+pub mod signer;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+#[cfg(feature = "client")]
+pub mod storage;
 pub mod types;
 
 pub use bee_signing_ext::{binary::BIP32Path, Seed};
-pub use builder::ClientBuilder;
+#[cfg(feature = "client")]
+pub use builder::{ClientBuilder, NodeAuth, NodeOptions};
+pub use cancellation::CancellationToken;
+#[cfg(feature = "client")]
 pub use client::*;
 pub use error::*;
+#[cfg(feature = "metrics")]
+pub use metrics::Metrics;
+#[cfg(feature = "client")]
+pub use middleware::{RequestInfo, RequestMiddleware, ResponseInfo};
 #[cfg(feature = "mqtt")]
 pub use node::Topic;
-pub use reqwest::Url;
+#[cfg(feature = "client")]
+pub use node::{DataSource, Sourced};
+#[cfg(feature = "pow")]
+pub use pow::LocalPow;
+pub use pow::{NonceProvider, RemotePow};
+#[cfg(feature = "client")]
+pub use reqwest::{Method, Url};
+#[cfg(feature = "ledger")]
+pub use signer::LedgerSigner;
+#[cfg(feature = "stronghold")]
+pub use signer::StrongholdSigner;
+pub use signer::{Bip39Seed, SignerType};
+#[cfg(all(feature = "client", not(target_arch = "wasm32")))]
+pub use storage::FileStorage;
+#[cfg(feature = "client")]
+pub use storage::{MemoryStorage, Storage, StorageFuture};
 pub use types::*;
 
 /// match a response with an expected status code or return the default error variant.
+#[cfg(feature = "client")]
 #[macro_export]
 macro_rules! parse_response {
     ($response:ident, $expected_status:pat => $ok:block) => {{
         match $response.status().as_u16() {
             $expected_status => $ok,
-            status => Err(Error::ResponseError(status, $response.text().await?)),
+            status => {
+                let context = Box::new($response.context());
+                Err(Error::ResponseError(status, $response.text().await?, context))
+            }
         }
     }};
 }
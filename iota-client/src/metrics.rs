@@ -0,0 +1,257 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Counters and histograms of the client activity, collected with the `metrics` feature and read with
+//! [`Client::get_metrics`](crate::Client::get_metrics)
+
+use crate::middleware::RequestInfo;
+
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex, time::Duration};
+
+/// The upper bounds in seconds of the buckets of the duration histograms, the default buckets of Prometheus.
+pub const DURATION_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// A histogram of durations, in the buckets of [`DURATION_BUCKETS`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Histogram {
+    /// The number of observations up to the upper bound of each bucket, cumulative like the Prometheus buckets
+    pub buckets: Vec<u64>,
+    /// The sum of the observations in seconds
+    pub sum: f64,
+    /// The number of observations
+    pub count: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: vec![0; DURATION_BUCKETS.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bucket, upper_bound) in self.buckets.iter_mut().zip(DURATION_BUCKETS.iter()) {
+            if seconds <= *upper_bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+/// The requests sent to an endpoint of a node.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EndpointMetrics {
+    /// The node, with the credentials of the URL redacted
+    pub node: String,
+    /// The HTTP method
+    pub method: String,
+    /// The path of the route, the ids, the addresses and the indexes in it replaced with `:id`
+    pub endpoint: String,
+    /// The number of requests, with or without a response
+    pub requests: u64,
+    /// The time from sending the requests to receiving the response headers or the error
+    pub latency: Histogram,
+}
+
+/// A snapshot of the metrics of a client. The requests of the node syncing aren't included.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Metrics {
+    /// The requests of each endpoint of each node, sorted by node, method and endpoint
+    pub endpoints: Vec<EndpointMetrics>,
+    /// The failed requests by category: `timeout`, `connection` and `request` for the requests without a response,
+    /// `status_4xx` and `status_5xx` for the error responses
+    pub errors: BTreeMap<String, u64>,
+    /// The duration of the local PoW of the messages
+    pub pow: Histogram,
+    /// The messages accepted by a node
+    #[serde(rename = "messagesSubmitted")]
+    pub messages_submitted: u64,
+    /// The messages promoted by the client
+    pub promotions: u64,
+    /// The messages reattached by the client
+    pub reattachments: u64,
+    /// The confirmations of messages observed by the client, a milestone referencing the message
+    pub confirmations: u64,
+    /// The requests retried after a rate limited answer, 429 or 503, the error answers are also in `errors`
+    #[serde(rename = "rateLimitRetries")]
+    pub rate_limit_retries: u64,
+}
+
+impl Metrics {
+    /// Encodes the metrics in the Prometheus text exposition format, the metric names prefixed with `iota_client_`.
+    pub fn to_prometheus(&self) -> String {
+        let mut text = String::new();
+        text.push_str("# TYPE iota_client_requests_total counter\n");
+        for endpoint in &self.endpoints {
+            let _ = writeln!(
+                text,
+                "iota_client_requests_total{{{}}} {}",
+                endpoint_labels(endpoint),
+                endpoint.requests
+            );
+        }
+        text.push_str("# TYPE iota_client_request_duration_seconds histogram\n");
+        for endpoint in &self.endpoints {
+            write_histogram(
+                &mut text,
+                "iota_client_request_duration_seconds",
+                &endpoint_labels(endpoint),
+                &endpoint.latency,
+            );
+        }
+        text.push_str("# TYPE iota_client_request_errors_total counter\n");
+        for (category, count) in &self.errors {
+            let _ = writeln!(
+                text,
+                "iota_client_request_errors_total{{category=\"{}\"}} {}",
+                category, count
+            );
+        }
+        text.push_str("# TYPE iota_client_pow_duration_seconds histogram\n");
+        write_histogram(&mut text, "iota_client_pow_duration_seconds", "", &self.pow);
+        for (name, value) in &[
+            ("messages_submitted", self.messages_submitted),
+            ("promotions", self.promotions),
+            ("reattachments", self.reattachments),
+            ("confirmations", self.confirmations),
+            ("rate_limit_retries", self.rate_limit_retries),
+        ] {
+            let _ = writeln!(text, "# TYPE iota_client_{}_total counter", name);
+            let _ = writeln!(text, "iota_client_{}_total {}", name, value);
+        }
+        text
+    }
+}
+
+fn endpoint_labels(endpoint: &EndpointMetrics) -> String {
+    format!(
+        "node=\"{}\",method=\"{}\",endpoint=\"{}\"",
+        escape_label(&endpoint.node),
+        endpoint.method,
+        endpoint.endpoint
+    )
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn write_histogram(text: &mut String, name: &str, labels: &str, histogram: &Histogram) {
+    let separator = if labels.is_empty() { "" } else { "," };
+    for (count, upper_bound) in histogram.buckets.iter().zip(DURATION_BUCKETS.iter()) {
+        let _ = writeln!(
+            text,
+            "{}_bucket{{{}{}le=\"{}\"}} {}",
+            name, labels, separator, upper_bound, count
+        );
+    }
+    let _ = writeln!(
+        text,
+        "{}_bucket{{{}{}le=\"+Inf\"}} {}",
+        name, labels, separator, histogram.count
+    );
+    let labels = if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", labels)
+    };
+    let _ = writeln!(text, "{}_sum{} {}", name, labels, histogram.sum);
+    let _ = writeln!(text, "{}_count{} {}", name, labels, histogram.count);
+}
+
+/// The category of a failed request, `None` for a successful one.
+pub(crate) fn error_category(response: &reqwest::Result<reqwest::Response>) -> Option<&'static str> {
+    match response {
+        Ok(response) if response.status().is_client_error() => Some("status_4xx"),
+        Ok(response) if response.status().is_server_error() => Some("status_5xx"),
+        Ok(_) => None,
+        Err(error) if error.is_timeout() => Some("timeout"),
+        Err(error) if error.is_connect() => Some("connection"),
+        Err(_) => Some("request"),
+    }
+}
+
+// The path of a request without its query, the segments holding an id, an address or an index replaced with `:id`.
+fn endpoint(path: &str) -> String {
+    let path = path.split('?').next().unwrap_or_default();
+    path.split('/')
+        .map(|segment| {
+            if segment.len() > 20 || (!segment.is_empty() && segment.bytes().all(|byte| byte.is_ascii_digit())) {
+                ":id"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+/// Collects the metrics of a client.
+#[derive(Debug, Default)]
+pub(crate) struct MetricsRecorder {
+    endpoints: Mutex<BTreeMap<(String, String, String), EndpointMetrics>>,
+    metrics: Mutex<Metrics>,
+}
+
+impl MetricsRecorder {
+    pub(crate) fn record_request(&self, request: &RequestInfo, elapsed: Duration, error_category: Option<&str>) {
+        let endpoint = endpoint(&request.path);
+        let key = (request.node.clone(), request.method.clone(), endpoint.clone());
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let metrics = endpoints.entry(key).or_insert_with(|| EndpointMetrics {
+            node: request.node.clone(),
+            method: request.method.clone(),
+            endpoint,
+            requests: 0,
+            latency: Histogram::default(),
+        });
+        metrics.requests += 1;
+        metrics.latency.observe(elapsed);
+        if let Some(category) = error_category {
+            *self
+                .metrics
+                .lock()
+                .unwrap()
+                .errors
+                .entry(category.to_string())
+                .or_default() += 1;
+        }
+    }
+
+    pub(crate) fn record_pow(&self, duration: Duration) {
+        self.metrics.lock().unwrap().pow.observe(duration);
+    }
+
+    pub(crate) fn record_message_submitted(&self) {
+        self.metrics.lock().unwrap().messages_submitted += 1;
+    }
+
+    pub(crate) fn record_promotion(&self) {
+        self.metrics.lock().unwrap().promotions += 1;
+    }
+
+    pub(crate) fn record_reattachment(&self) {
+        self.metrics.lock().unwrap().reattachments += 1;
+    }
+
+    pub(crate) fn record_confirmation(&self) {
+        self.metrics.lock().unwrap().confirmations += 1;
+    }
+
+    pub(crate) fn record_rate_limit_retry(&self) {
+        self.metrics.lock().unwrap().rate_limit_retries += 1;
+    }
+
+    pub(crate) fn snapshot(&self) -> Metrics {
+        let mut metrics = self.metrics.lock().unwrap().clone();
+        metrics.endpoints = self.endpoints.lock().unwrap().values().cloned().collect();
+        metrics
+    }
+}
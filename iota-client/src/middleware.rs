@@ -0,0 +1,107 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hooks invoked around the node requests of the client, to log or trace them
+
+use reqwest::{
+    header::{HeaderName, HeaderValue},
+    Request, Url,
+};
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    time::Duration,
+};
+
+/// A request to a node, as seen by the middlewares.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestInfo {
+    /// The HTTP method
+    pub method: String,
+    /// The path of the route, with the query
+    pub path: String,
+    /// The size of the request body in bytes
+    pub body_size: usize,
+    /// The node the request is sent to, with the credentials of the URL redacted
+    pub node: String,
+}
+
+impl RequestInfo {
+    pub(crate) fn new(request: &Request) -> Self {
+        let url = request.url();
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        Self {
+            method: request.method().to_string(),
+            path,
+            body_size: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map_or(0, |body| body.len()),
+            node: redacted_origin(url),
+        }
+    }
+}
+
+/// The outcome of a request to a node, as seen by the middlewares.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResponseInfo {
+    /// The HTTP status of the response, `None` if the request failed without a response
+    pub status: Option<u16>,
+    /// The time elapsed from sending the request to receiving the response headers
+    pub elapsed: Duration,
+    /// The error of the request if it failed without a response, like a timeout or a refused connection
+    pub error: Option<String>,
+}
+
+/// Hooks invoked around every request the client sends to a node, set with
+/// [`ClientBuilder::with_middleware`](crate::ClientBuilder::with_middleware). The requests of the node syncing aren't
+/// included.
+///
+/// The hooks can't change the request body, and a panicking hook is ignored: the request is sent as if it succeeded
+/// without adding headers.
+pub trait RequestMiddleware: Send + Sync {
+    /// Called before a request is sent, returns the headers to add to the request, like a trace id.
+    /// Invalid header names or values are skipped.
+    fn before_request(&self, _request: &RequestInfo) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// Called once the response headers are received or the request failed.
+    fn after_response(&self, _request: &RequestInfo, _response: &ResponseInfo) {}
+}
+
+/// Runs the `before_request` hooks and adds their headers to the request.
+pub(crate) fn before_request(middlewares: &[Box<dyn RequestMiddleware>], request: &mut Request, info: &RequestInfo) {
+    for middleware in middlewares {
+        let headers = catch_unwind(AssertUnwindSafe(|| middleware.before_request(info))).unwrap_or_default();
+        for (name, value) in headers {
+            if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&value)) {
+                request.headers_mut().insert(name, value);
+            }
+        }
+    }
+}
+
+/// Runs the `after_response` hooks.
+pub(crate) fn after_response(
+    middlewares: &[Box<dyn RequestMiddleware>],
+    request: &RequestInfo,
+    response: &ResponseInfo,
+) {
+    for middleware in middlewares {
+        let _ = catch_unwind(AssertUnwindSafe(|| middleware.after_response(request, response)));
+    }
+}
+
+// The origin of the URL, without its username and password.
+pub(crate) fn redacted_origin(url: &Url) -> String {
+    let mut url = url.clone();
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    url.set_query(None);
+    url.set_path("");
+    url.to_string()
+}
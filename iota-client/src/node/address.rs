@@ -1,11 +1,12 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{parse_response, types::Bech32Address, AddressBalance, AddressOutputs, Client, Error, Response, Result};
+use crate::{
+    api::checked_amount, node::Sourced, parse_response, types::Bech32Address, AddressBalance, AddressOutputs, Api,
+    Client, Error, OutputsOptions, Response, Result,
+};
 
-use bee_message::prelude::{TransactionId, UTXOInput};
-
-use std::convert::TryInto;
+use bee_message::prelude::UTXOInput;
 
 /// Builder of GET /api/v1/address/{address} endpoint
 pub struct GetAddressBuilder<'a> {
@@ -22,38 +23,87 @@ impl<'a> GetAddressBuilder<'a> {
     /// If count equals maxResults, then there might be more outputs available but those were skipped for performance
     /// reasons. User should sweep the address to reduce the amount of outputs.
     pub async fn balance(self, address: &Bech32Address) -> Result<u64> {
-        let mut url = self.client.get_node()?;
-        url.set_path(&format!("api/v1/addresses/{}", address));
-        let resp = reqwest::get(url).await?;
-
-        parse_response!(resp, 200 => {
-            let r = resp.json::<Response<AddressBalance>>().await?.data;
-            Ok(r.balance)
-        })
+        Ok(self.balance_response(address).await?.balance)
+    }
+
+    /// Consume the builder and get the raw balance response of a given Bech32 encoded address.
+    /// Cross-checked by the quorum nodes if the quorum is enabled.
+    pub(crate) async fn balance_response(self, address: &Bech32Address) -> Result<AddressBalance> {
+        let client = self.client;
+        client
+            .quorum_request(|mut url| async move {
+                url.set_path(&format!("api/v1/addresses/{}", address));
+                let resp = client
+                    .send_request(client.client.get(url).timeout(client.get_timeout(Api::GetAddress)))
+                    .await?;
+
+                parse_response!(resp, 200 => {
+                    let balance = resp.json::<Response<AddressBalance>>().await?.data;
+                    checked_amount(balance.balance)?;
+                    Ok(balance)
+                })
+            })
+            .await
     }
 
     /// Consume the builder and get all outputs that use a given address.
     /// If count equals maxResults, then there might be more outputs available but those were skipped for performance
     /// reasons. User should sweep the address to reduce the amount of outputs.
     pub async fn outputs(self, address: &Bech32Address) -> Result<Box<[UTXOInput]>> {
-        let mut url = self.client.get_node()?;
-        url.set_path(&format!("api/v1/addresses/{}/outputs", address));
-        let resp = reqwest::get(url).await?;
-
-        parse_response!(resp, 200 => {
-            let r = resp.json::<Response<AddressOutputs>>().await?.data.output_ids;
-            r.iter()
-                .map(|s| {
-                    let mut transaction_id = [0u8; 32];
-                    hex::decode_to_slice(&s[..64], &mut transaction_id)?;
-                    let index = u16::from_le_bytes(
-                        hex::decode(&s[64..]).map_err(|_| Error::InvalidParameter("index".to_string()))?[..]
-                            .try_into()
-                            .map_err(|_| Error::InvalidParameter("index".to_string()))?,
-                    );
-                    Ok(UTXOInput::new(TransactionId::new(transaction_id), index)?)
+        self.outputs_response(address, Default::default()).await?.utxo_inputs()
+    }
+
+    /// Consume the builder and get the raw outputs response of a given Bech32 encoded address, filtered with the
+    /// options. The response holds the result count to check if the node truncated the list.
+    /// Cross-checked by the quorum nodes if the quorum is enabled, the output ids are then sorted since the nodes
+    /// don't list them in the same order.
+    pub async fn outputs_response(self, address: &Bech32Address, options: OutputsOptions) -> Result<AddressOutputs> {
+        Ok(self.outputs_response_with_source(address, options).await?.data)
+    }
+
+    /// Consume the builder and get the raw outputs response of a given Bech32 encoded address, in the permanode if
+    /// the spent outputs are included and the node doesn't know any, see
+    /// [`ClientBuilder::with_permanode`](crate::ClientBuilder::with_permanode). The unspent outputs are never pruned.
+    /// The pages of the permanode are merged into a single response. The answer holds its source.
+    pub async fn outputs_response_with_source(
+        self,
+        address: &Bech32Address,
+        options: OutputsOptions,
+    ) -> Result<Sourced<AddressOutputs>> {
+        let client = self.client;
+        client
+            .historical_request(
+                || self.node_outputs_response(address, options),
+                |permanode| client.permanode_address_outputs(permanode, address, options),
+                |answer| options.include_spent && matches!(answer, Ok(outputs) if outputs.output_ids.is_empty()),
+            )
+            .await
+    }
+
+    async fn node_outputs_response(&self, address: &Bech32Address, options: OutputsOptions) -> Result<AddressOutputs> {
+        let client = self.client;
+        client
+            .quorum_request(|mut url| async move {
+                url.set_path(&format!("api/v1/addresses/{}/outputs", address));
+                if options.include_spent {
+                    url.query_pairs_mut().append_pair("include-spent", "true");
+                }
+                if let Some(output_type) = options.output_type {
+                    url.query_pairs_mut()
+                        .append_pair("type", &output_type.kind().to_string());
+                }
+                let resp = client
+                    .send_request(client.client.get(url).timeout(client.get_timeout(Api::GetAddress)))
+                    .await?;
+
+                parse_response!(resp, 200 => {
+                    let mut outputs = resp.json::<Response<AddressOutputs>>().await?.data;
+                    if client.quorum {
+                        outputs.output_ids.sort();
+                    }
+                    Ok(outputs)
                 })
-                .collect::<Result<Box<[UTXOInput]>>>()
-        })
+            })
+            .await
     }
 }
@@ -2,13 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    parse_response, ChildrenMessageIds, Client, Error, MessageIds, MessageJson, MessageMetadata, Response, Result,
+    node::{is_not_found, Sourced},
+    parse_response, storage, Api, ChildrenMessageIds, Client, Error, MessageIds, MessageMetadata, PrunedRequest,
+    Response, Result,
 };
 
 use bee_message::{Message, MessageId};
 
-use std::convert::TryInto;
-
 /// Builder of GET /api/v1/messages/{messageId} endpoint
 pub struct GetMessageBuilder<'a> {
     client: &'a Client,
@@ -23,10 +23,35 @@ impl<'a> GetMessageBuilder<'a> {
     /// GET /api/v1/messages?index={Index} endpoint
     /// Consume the builder and search for messages matching the index
     pub async fn index(self, index: &str) -> Result<Box<[MessageId]>> {
+        Ok(self.index_with_source(index).await?.data)
+    }
+
+    /// Consume the builder and search for messages matching the index, in the permanode if the node doesn't know any,
+    /// see [`ClientBuilder::with_permanode`](crate::ClientBuilder::with_permanode). The answer holds its source.
+    pub async fn index_with_source(self, index: &str) -> Result<Sourced<Box<[MessageId]>>> {
+        self.client
+            .historical_request(
+                || self.node_index(index),
+                |permanode| self.client.permanode_message_ids(permanode, index),
+                |answer| matches!(answer, Ok(ids) if ids.is_empty()) || is_not_found(answer),
+            )
+            .await
+    }
+
+    async fn node_index(&self, index: &str) -> Result<Box<[MessageId]>> {
         let mut url = self.client.get_node()?;
         url.set_path("api/v1/messages");
-        url.set_query(Some(&format!("index={}", index)));
-        let resp = reqwest::get(url).await?;
+        // the index is percent-encoded since it can hold any UTF-8 character
+        url.query_pairs_mut().append_pair("index", index);
+        let resp = self
+            .client
+            .send_request(
+                self.client
+                    .client
+                    .get(url)
+                    .timeout(self.client.get_timeout(Api::GetMessage)),
+            )
+            .await?;
 
         parse_response!(resp, 200 => {
             let ids = resp.json::<Response<MessageIds>>().await?;
@@ -45,14 +70,38 @@ impl<'a> GetMessageBuilder<'a> {
     /// GET /api/v1/messages/{messageID} endpoint
     /// Consume the builder and find a message by its identifer. This method returns the given message object.
     pub async fn data(self, message_id: &MessageId) -> Result<Message> {
+        Ok(self.data_with_source(message_id).await?.data)
+    }
+
+    /// Consume the builder and find a message by its identifer, in the permanode if the node pruned it, see
+    /// [`ClientBuilder::with_permanode`](crate::ClientBuilder::with_permanode). The answer holds its source.
+    pub async fn data_with_source(self, message_id: &MessageId) -> Result<Sourced<Message>> {
+        self.client
+            .historical_request(
+                || self.node_data(message_id),
+                |permanode| self.client.permanode_message(permanode, message_id),
+                is_not_found,
+            )
+            .await
+    }
+
+    async fn node_data(&self, message_id: &MessageId) -> Result<Message> {
         let mut url = self.client.get_node()?;
         url.set_path(&format!("api/v1/messages/{}", message_id));
-        let resp = reqwest::get(url).await?;
+        let resp = self
+            .client
+            .send_request(
+                self.client
+                    .client
+                    .get(url)
+                    .timeout(self.client.get_timeout(Api::GetMessage)),
+            )
+            .await?;
 
-        parse_response!(resp, 200 => {
-            let meta = resp.json::<Response<MessageJson>>().await?;
-            Ok(meta.data.try_into()?)
-        })
+        let result = parse_response!(resp, 200 => {
+            resp.message(message_id).await
+        });
+        self.pruned_result(message_id, result).await
     }
 
     /// GET /api/v1/messages/{messageID}/metadata endpoint
@@ -60,32 +109,82 @@ impl<'a> GetMessageBuilder<'a> {
     pub async fn metadata(self, message_id: &MessageId) -> Result<MessageMetadata> {
         let mut url = self.client.get_node()?;
         url.set_path(&format!("api/v1/messages/{}/metadata", message_id));
-        let resp = reqwest::get(url).await?;
-        parse_response!(resp, 200 => {
+        let resp = self
+            .client
+            .send_request(
+                self.client
+                    .client
+                    .get(url)
+                    .timeout(self.client.get_timeout(Api::GetMessage)),
+            )
+            .await?;
+        let result = parse_response!(resp, 200 => {
             let meta = resp.json::<Response<MessageMetadata>>().await?;
             Ok(meta.data)
-        })
+        });
+        let metadata = self.pruned_result(message_id, result).await?;
+        if metadata.referenced_by_milestone_index.is_some() {
+            self.remove_pending(message_id).await;
+        }
+        Ok(metadata)
     }
 
-    /// GET /api/v1/messages/{messageID}/children endpoint
-    /// Consume the builder and find a message by its identifer. This method returns the given message raw data.
-    pub async fn raw(self, message_id: &MessageId) -> Result<String> {
+    // Removes a message referenced by a milestone from the stored pending messages, the storage is only written if
+    // the message is pending
+    async fn remove_pending(&self, message_id: &MessageId) {
+        let message_id = message_id.to_string();
+        let pending: Vec<String> = self
+            .client
+            .load_state(storage::PENDING_MESSAGES)
+            .await
+            .unwrap_or_default();
+        if pending.contains(&message_id) {
+            let _ = self
+                .client
+                .update_state(storage::PENDING_MESSAGES, |pending: &mut Vec<String>| {
+                    pending.retain(|id| *id != message_id)
+                })
+                .await;
+        }
+    }
+
+    /// GET /api/v1/messages/{messageID}/raw endpoint
+    /// Consume the builder and find a message by its identifer. This method returns the packed bytes of the message.
+    pub async fn raw(self, message_id: &MessageId) -> Result<Vec<u8>> {
         let mut url = self.client.get_node()?;
         url.set_path(&format!("api/v1/messages/{}/raw", message_id));
-        let resp = reqwest::get(url).await?;
+        let resp = self
+            .client
+            .send_request(
+                self.client
+                    .client
+                    .get(url)
+                    .timeout(self.client.get_timeout(Api::GetMessage)),
+            )
+            .await?;
 
-        parse_response!(resp, 200 => {
-            Ok(resp.text().await?)
-        })
+        let result = parse_response!(resp, 200 => {
+            resp.bytes().await
+        });
+        self.pruned_result(message_id, result).await
     }
 
+    /// GET /api/v1/messages/{messageID}/children endpoint
     /// Consume the builder and returns the list of message IDs that reference a message by its identifier.
     pub async fn children(self, message_id: &MessageId) -> Result<Box<[MessageId]>> {
         let mut url = self.client.get_node()?;
         url.set_path(&format!("api/v1/messages/{}/children", message_id));
-        let resp = reqwest::get(url).await?;
+        let resp = self
+            .client
+            .send_request(
+                self.client
+                    .client
+                    .get(url)
+                    .timeout(self.client.get_timeout(Api::GetMessage)),
+            )
+            .await?;
 
-        crate::parse_response!(resp, 200 => {
+        let result = crate::parse_response!(resp, 200 => {
             let meta = resp.json::<Response<ChildrenMessageIds>>().await?;
             meta.data
                 .inner
@@ -96,6 +195,18 @@ impl<'a> GetMessageBuilder<'a> {
                     Ok(MessageId::from(message_id))
                 })
                 .collect::<Result<Box<[MessageId]>>>()
-        })
+        });
+        self.pruned_result(message_id, result).await
+    }
+
+    // Maps the not found error of a message the node reports as pruned to `Error::PrunedData`.
+    async fn pruned_result<T>(&self, message_id: &MessageId, result: Result<T>) -> Result<T> {
+        match result {
+            Err(error) => Err(self
+                .client
+                .pruned_error(PrunedRequest::Message(message_id.to_string()), error)
+                .await),
+            result => result,
+        }
     }
 }
@@ -7,8 +7,13 @@ mod address;
 mod message;
 #[cfg(feature = "mqtt")]
 mod mqtt;
+mod permanode;
 
 pub use address::*;
 pub use message::*;
 #[cfg(feature = "mqtt")]
 pub use mqtt::*;
+#[cfg(feature = "mqtt")]
+pub(crate) use mqtt::{ConnectionWaker, TopicWaker};
+pub(crate) use permanode::is_not_found;
+pub use permanode::{DataSource, Sourced};
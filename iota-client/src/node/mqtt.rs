@@ -2,19 +2,32 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    client::{Client, TopicEvent, TopicHandlerMap},
-    Result,
+    client::{
+        BrokerOptions, Client, MqttEvent, MqttEventHandler, TopicEvent, TopicHandler, TopicHandlerMap,
+        MQTT_RECONNECT_MAX_DELAY, MQTT_RECONNECT_MIN_DELAY,
+    },
+    CancellationToken, Result,
 };
+use bee_message::prelude::Address;
 use paho_mqtt::{
-    Client as MqttClient, ConnectOptionsBuilder, CreateOptionsBuilder, DisconnectOptionsBuilder, SslOptions,
-    MQTT_VERSION_3_1_1,
+    Client as MqttClient, ConnectOptions, ConnectOptionsBuilder, CreateOptionsBuilder, DisconnectOptionsBuilder,
+    Message as MqttMessage, SslOptionsBuilder, MQTT_VERSION_3_1_1,
 };
+use rand::Rng;
 use regex::Regex;
+use reqwest::Url;
 
 use std::{
     convert::TryFrom,
-    sync::{Arc, RwLock},
-    time::Duration,
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Receiver,
+        Arc, RwLock, Weak,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
 macro_rules! lazy_static {
@@ -47,17 +60,26 @@ impl Topic {
         );
         let regexes = lazy_static!(
           [
-            Regex::new(r"messages/([A-Fa-f0-9]{64})/metadata").unwrap(),
-            Regex::new(r"outputs/([A-Fa-f0-9]{64})(\d{4})").unwrap(),
-            // bech32 address
-            Regex::new("addresses/(iota|atoi|iot|toi)1[A-Za-z0-9]+/outputs").unwrap(),
+            Regex::new(r"^messages/([A-Fa-f0-9]{64})/metadata$").unwrap(),
+            // transaction id and output index
+            Regex::new(r"^outputs/([A-Fa-f0-9]{64})([A-Fa-f0-9]{4})$").unwrap(),
             // ED25519 address hex
-            Regex::new("addresses/ed25519/([A-Fa-f0-9]{64})/outputs").unwrap(),
-            Regex::new(r"messages/indexation/(\.)").unwrap()
+            Regex::new("^addresses/ed25519/([A-Fa-f0-9]{64})/outputs$").unwrap(),
+            Regex::new(r"^messages/indexation/([^/]+)$").unwrap()
           ].to_vec() => Vec<Regex>
         );
+        // bech32 address, validated with its checksum
+        let bech32_address_regex = lazy_static!(
+          Regex::new("^addresses/([^/]+)/outputs$").unwrap() => Regex
+        );
         let name = name.into();
-        if valid_topics.iter().any(|valid| valid == &name) || regexes.iter().any(|re| re.is_match(&name)) {
+        let valid = valid_topics.iter().any(|valid| valid == &name)
+            || regexes.iter().any(|re| re.is_match(&name))
+            || bech32_address_regex
+                .captures(&name)
+                .map(|captures| Address::try_from_bech32(&captures[1]).is_ok())
+                .unwrap_or(false);
+        if valid {
             let topic = Self(name);
             Ok(topic)
         } else {
@@ -66,59 +88,330 @@ impl Topic {
     }
 }
 
+/// The MQTT connection of a client.
+pub(crate) struct MqttConnection {
+    pub(crate) client: MqttClient,
+    // set once the connection is lost for good, the next subscription connects again
+    closed: AtomicBool,
+}
+
+// The timeout of the probe of a broker that refused the connection.
+const SCHEME_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+// A CONNECT packet of MQTT 3.1.1, with a clean session and the client id `p`.
+const MQTT_CONNECT_PACKET: [u8; 15] = [0x10, 13, 0, 4, b'M', b'Q', b'T', b'T', 4, 0x02, 0, 20, 0, 1, b'p'];
+
+/// A broker to connect to, from the broker URL of the options or derived from a node URL.
+struct BrokerEndpoint {
+    // the URL of the broker in the errors, as set by the user or the URL of the node
+    url: String,
+    // the paho URI: `tcp`, `ssl`, `ws` or `wss`
+    uri: String,
+    host: String,
+    port: u16,
+    tls: bool,
+    websocket: bool,
+}
+
+impl BrokerEndpoint {
+    fn new(url: String, scheme: &str, host: &str, port: u16, path: &str) -> Self {
+        let websocket = scheme == "ws" || scheme == "wss";
+        let uri = match websocket {
+            true => format!("{}://{}:{}{}", scheme, host, port, path),
+            false => format!("{}://{}:{}", scheme, host, port),
+        };
+        Self {
+            url,
+            uri,
+            host: host.to_string(),
+            port,
+            tls: scheme == "ssl" || scheme == "wss",
+            websocket,
+        }
+    }
+
+    // The broker of the `broker_url` option.
+    fn from_broker_url(broker_url: &str) -> Result<Self> {
+        let invalid =
+            |reason: &str| crate::Error::InvalidParameter(format!("MQTT broker URL {}: {}", broker_url, reason));
+        let url = Url::parse(broker_url).map_err(|e| invalid(&e.to_string()))?;
+        let (scheme, default_port) = match url.scheme() {
+            "mqtt" | "tcp" => ("tcp", 1883),
+            "mqtts" | "ssl" => ("ssl", 8883),
+            "ws" => ("ws", 80),
+            "wss" => ("wss", 443),
+            scheme => {
+                return Err(invalid(&format!(
+                    "unsupported scheme {}, use mqtt, mqtts, ws or wss",
+                    scheme
+                )))
+            }
+        };
+        let host = url
+            .host_str()
+            .filter(|host| !host.is_empty())
+            .ok_or_else(|| invalid("missing host"))?;
+        let path = match url.path() {
+            "" | "/" => "/mqtt",
+            path => path,
+        };
+        Ok(Self::new(
+            broker_url.to_string(),
+            scheme,
+            host,
+            url.port().unwrap_or(default_port),
+            path,
+        ))
+    }
+
+    // The broker of a node: the node doesn't report its broker, it's expected on the node host, over TLS for an
+    // `https` node.
+    fn from_node(node: &Url, use_ws: bool) -> Self {
+        let host = node.host_str().unwrap();
+        let tls = node.scheme() == "https";
+        match (use_ws, tls) {
+            (true, _) => Self::new(
+                node.to_string(),
+                if tls { "wss" } else { "ws" },
+                host,
+                node.port_or_known_default().unwrap(),
+                "/mqtt",
+            ),
+            (false, true) => Self::new(node.to_string(), "ssl", host, 8883, ""),
+            (false, false) => Self::new(node.to_string(), "tcp", host, 1883, ""),
+        }
+    }
+
+    // Whether the broker speaks TLS, probed after a refused connection to report a scheme mismatch instead of a
+    // generic error: a TLS broker answers a plain MQTT CONNECT or websocket handshake with a TLS alert. `None` if the
+    // probe fails or the answer doesn't tell.
+    fn probe_tls(&self) -> Option<bool> {
+        let address = (self.host.as_str(), self.port).to_socket_addrs().ok()?.next()?;
+        let mut stream = TcpStream::connect_timeout(&address, SCHEME_PROBE_TIMEOUT).ok()?;
+        stream.set_read_timeout(Some(SCHEME_PROBE_TIMEOUT)).ok()?;
+        stream.set_write_timeout(Some(SCHEME_PROBE_TIMEOUT)).ok()?;
+        match self.websocket {
+            true => stream
+                .write_all(format!("GET /mqtt HTTP/1.1\r\nHost: {}\r\n\r\n", self.host).as_bytes())
+                .ok()?,
+            false => stream.write_all(&MQTT_CONNECT_PACKET).ok()?,
+        }
+        let mut first_byte = [0u8; 1];
+        stream.read_exact(&mut first_byte).ok()?;
+        match first_byte[0] {
+            // TLS alert or handshake record
+            0x15 | 0x16 => Some(true),
+            // CONNACK or HTTP response
+            0x20 | b'H' => Some(false),
+            _ => None,
+        }
+    }
+}
+
+// The brokers to try, in order: the broker URL of the options, or the brokers of the synced nodes with the MQTT role.
+fn broker_endpoints(client: &Client) -> Result<Vec<BrokerEndpoint>> {
+    match &client.broker_options.broker_url {
+        Some(broker_url) => Ok(vec![BrokerEndpoint::from_broker_url(broker_url)?]),
+        None => Ok(client
+            .sync
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|node| client.has_node_role(node, |options| options.mqtt))
+            .map(|node| BrokerEndpoint::from_node(node, client.broker_options.use_ws))
+            .collect()),
+    }
+}
+
+fn connect_options(client: &Client, endpoint: &BrokerEndpoint) -> Result<ConnectOptions> {
+    let mut ssl_options = SslOptionsBuilder::new();
+    if endpoint.tls {
+        if let Some(ca_certificate) = &client.ca_certificate {
+            ssl_options.trust_store(ca_certificate)?;
+        }
+    }
+    let mut connect_options = ConnectOptionsBuilder::new();
+    connect_options
+        .keep_alive_interval(Duration::from_secs(20))
+        .mqtt_version(MQTT_VERSION_3_1_1)
+        .clean_session(true)
+        .connect_timeout(client.broker_options.timeout)
+        .ssl_options(ssl_options.finalize());
+    if let Some(auth) = &client.broker_options.auth {
+        connect_options
+            .user_name(auth.username.as_str())
+            .password(auth.password.as_str());
+    }
+    Ok(connect_options.finalize())
+}
+
 fn get_mqtt_client(client: &mut Client) -> Result<&MqttClient> {
+    if let Some(connection) = &client.mqtt_client {
+        if connection.closed.load(Ordering::SeqCst) {
+            client.mqtt_client = None;
+        }
+    }
     match client.mqtt_client {
-        Some(ref c) => Ok(c),
+        Some(ref c) => Ok(&c.client),
         None => {
-            for node in client.sync.read().unwrap().iter() {
-                // node.set_path("mqtt");
-                let uri = match client.broker_options.use_ws {
-                    true => format!(
-                        "{}://{}:{}/mqtt",
-                        if node.scheme() == "https" { "wss" } else { "ws" },
-                        node.host_str().unwrap(),
-                        node.port_or_known_default().unwrap()
-                    ),
-                    false => format!("tcp://{}", node.host_str().unwrap(),),
-                };
+            if client.offline {
+                return Err(crate::Error::OfflineClient);
+            }
+            // a broker answering with the other scheme is reported instead of a generic connection error
+            let mut error = None;
+            for endpoint in broker_endpoints(client)? {
                 let mqtt_options = CreateOptionsBuilder::new()
-                    .server_uri(uri)
+                    .server_uri(&endpoint.uri)
                     .client_id("iota.rs")
                     .finalize();
                 let mut mqtt_client = MqttClient::new(mqtt_options)?;
-                let conn_opts = ConnectOptionsBuilder::new()
-                    .keep_alive_interval(Duration::from_secs(20))
-                    .mqtt_version(MQTT_VERSION_3_1_1)
-                    .clean_session(true)
-                    .connect_timeout(client.broker_options.timeout)
-                    .ssl_options(SslOptions::new())
-                    .finalize();
 
-                if mqtt_client.connect(conn_opts).is_ok() {
-                    poll_mqtt(client.mqtt_topic_handlers.clone(), &mut mqtt_client);
-                    client.mqtt_client = Some(mqtt_client);
-                    break;
+                match mqtt_client.connect(connect_options(client, &endpoint)?) {
+                    Ok(_) => {
+                        let receiver = mqtt_client.start_consuming();
+                        // the topics of a connection lost for good
+                        subscribe_stored_topics(&mqtt_client, &client.mqtt_topic_handlers)?;
+                        let connection = Arc::new(MqttConnection {
+                            client: mqtt_client,
+                            closed: AtomicBool::new(false),
+                        });
+                        emit_event(&client.mqtt_event_handlers, MqttEvent::Connected);
+                        poll_mqtt(
+                            Arc::downgrade(&connection),
+                            receiver,
+                            client.mqtt_topic_handlers.clone(),
+                            client.mqtt_event_handlers.clone(),
+                            client.broker_options.clone(),
+                        );
+                        client.mqtt_client = Some(connection);
+                        break;
+                    }
+                    Err(e) => match endpoint.probe_tls() {
+                        Some(tls) if tls != endpoint.tls => {
+                            error.replace(crate::Error::MqttSchemeMismatch(endpoint.url, tls));
+                        }
+                        // the error of the broker URL, the nodes without a broker are skipped
+                        _ if client.broker_options.broker_url.is_some() => {
+                            error.replace(e.into());
+                        }
+                        _ => {}
+                    },
                 }
             }
-            client.mqtt_client.as_ref().ok_or(crate::Error::MqttConnectionNotFound)
+            match (&client.mqtt_client, error) {
+                (Some(connection), _) => Ok(&connection.client),
+                (None, Some(error)) => Err(error),
+                (None, None) => Err(crate::Error::MqttConnectionNotFound),
+            }
+        }
+    }
+}
+
+fn subscribe_stored_topics(
+    mqtt_client: &MqttClient,
+    mqtt_topic_handlers: &RwLock<TopicHandlerMap>,
+) -> std::result::Result<(), paho_mqtt::errors::Error> {
+    let topics: Vec<String> = mqtt_topic_handlers
+        .read()
+        .unwrap()
+        .keys()
+        .map(|t| t.0.clone())
+        .collect();
+    if !topics.is_empty() {
+        mqtt_client.subscribe_many(&topics, &vec![1; topics.len()])?;
+    }
+    Ok(())
+}
+
+fn emit_event(mqtt_event_handlers: &RwLock<Vec<Arc<MqttEventHandler>>>, event: MqttEvent) {
+    for handler in mqtt_event_handlers.read().unwrap().iter() {
+        handler(&event)
+    }
+}
+
+// The delay before a reconnection attempt: doubled by every failed attempt up to the maximum delay, with a random
+// jitter of up to half of it so the clients of a restarted broker don't reconnect all at once.
+fn reconnect_delay(attempt: u32) -> Duration {
+    let delay = MQTT_RECONNECT_MIN_DELAY
+        .checked_mul(1 << (attempt - 1).min(16))
+        .unwrap_or(MQTT_RECONNECT_MAX_DELAY)
+        .min(MQTT_RECONNECT_MAX_DELAY);
+    delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+}
+
+// Reconnects a lost connection and subscribes its topics again, returning `false` if it gave up or if the connection
+// was dropped in between.
+fn reconnect(
+    connection: &Weak<MqttConnection>,
+    mqtt_topic_handlers: &RwLock<TopicHandlerMap>,
+    mqtt_event_handlers: &RwLock<Vec<Arc<MqttEventHandler>>>,
+    broker_options: &BrokerOptions,
+) -> bool {
+    let disconnected_at = Instant::now();
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let delay = reconnect_delay(attempt);
+        if disconnected_at.elapsed() + delay > broker_options.reconnect_window {
+            let error = format!(
+                "failed to reconnect to the MQTT broker within {:?}, after {} attempts",
+                broker_options.reconnect_window,
+                attempt - 1
+            );
+            emit_event(mqtt_event_handlers, MqttEvent::Error(error));
+            return false;
+        }
+        emit_event(mqtt_event_handlers, MqttEvent::Reconnecting { attempt, delay });
+        thread::sleep(delay);
+        let connection = match connection.upgrade() {
+            Some(connection) => connection,
+            None => return false,
+        };
+        if connection.client.reconnect().is_err() {
+            continue;
+        }
+        // the broker forgot the subscriptions of the clean session
+        if subscribe_stored_topics(&connection.client, mqtt_topic_handlers).is_ok() {
+            emit_event(mqtt_event_handlers, MqttEvent::Connected);
+            return true;
         }
     }
 }
 
-fn poll_mqtt(mqtt_topic_handlers: Arc<RwLock<TopicHandlerMap>>, client: &mut MqttClient) {
-    let receiver = client.start_consuming();
-    std::thread::spawn(move || {
+// Polls the events of a connection until it's dropped, or closed if it can't be reconnected.
+fn poll_mqtt(
+    connection: Weak<MqttConnection>,
+    receiver: Receiver<Option<MqttMessage>>,
+    mqtt_topic_handlers: Arc<RwLock<TopicHandlerMap>>,
+    mqtt_event_handlers: Arc<RwLock<Vec<Arc<MqttEventHandler>>>>,
+    broker_options: BrokerOptions,
+) {
+    thread::spawn(move || {
         while let Ok(message) = receiver.recv() {
-            if let Some(message) = message {
-                let topic = message.topic().to_string();
-                let mqtt_topic_handlers_guard = mqtt_topic_handlers.read().unwrap();
-                if let Some(handlers) = mqtt_topic_handlers_guard.get(&Topic(topic.clone())) {
-                    let event = TopicEvent {
-                        topic,
-                        payload: message.payload_str().to_string(),
-                    };
-                    for handler in handlers {
-                        handler(&event)
+            match message {
+                Some(message) => {
+                    let topic = message.topic().to_string();
+                    let mqtt_topic_handlers_guard = mqtt_topic_handlers.read().unwrap();
+                    if let Some(handlers) = mqtt_topic_handlers_guard.get(&Topic(topic.clone())) {
+                        let event = TopicEvent {
+                            topic,
+                            payload: message.payload_str().to_string(),
+                            payload_bytes: message.payload().to_vec(),
+                        };
+                        for handler in handlers {
+                            handler(&event)
+                        }
+                    }
+                }
+                // the connection is lost
+                None => {
+                    emit_event(&mqtt_event_handlers, MqttEvent::Disconnected);
+                    let reconnected = broker_options.automatic_reconnect
+                        && reconnect(&connection, &mqtt_topic_handlers, &mqtt_event_handlers, &broker_options);
+                    if !reconnected {
+                        if let Some(connection) = connection.upgrade() {
+                            connection.closed.store(true, Ordering::SeqCst);
+                        }
+                        break;
                     }
                 }
             }
@@ -152,13 +445,26 @@ impl<'a> MqttManager<'a> {
         MqttTopicManager::new(self.client).unsubscribe()
     }
 
+    /// Adds a handler of the connection events, called from the polling thread of the connection. The handlers are
+    /// kept for the lifetime of the client, add them before subscribing to get the first `Connected` event.
+    pub fn on_event<C: Fn(&MqttEvent) + Send + Sync + 'static>(self, callback: C) {
+        self.client
+            .mqtt_event_handlers
+            .write()
+            .unwrap()
+            .push(Arc::new(Box::new(callback)));
+    }
+
     /// Disconnects the broker.
     /// This will clear the stored topic handlers and close the MQTT connection.
     pub fn disconnect(self) -> Result<()> {
         let timeout = self.client.broker_options.timeout;
-        if let Some(client) = &self.client.mqtt_client {
-            let disconnect_options = DisconnectOptionsBuilder::new().timeout(timeout).finalize();
-            client.disconnect(disconnect_options)?;
+        if let Some(connection) = &self.client.mqtt_client {
+            // a lost connection has nothing to disconnect, dropping it stops its reconnection
+            if connection.client.is_connected() {
+                let disconnect_options = DisconnectOptionsBuilder::new().timeout(timeout).finalize();
+                connection.client.disconnect(disconnect_options)?;
+            }
             self.client.mqtt_client = None;
 
             {
@@ -177,12 +483,17 @@ impl<'a> MqttManager<'a> {
 pub struct MqttTopicManager<'a> {
     client: &'a mut Client,
     topics: Vec<Topic>,
+    cancellation: Option<CancellationToken>,
 }
 
 impl<'a> MqttTopicManager<'a> {
     /// Initializes a new instance of the mqtt topic manager.
     fn new(client: &'a mut Client) -> Self {
-        Self { client, topics: vec![] }
+        Self {
+            client,
+            topics: vec![],
+            cancellation: None,
+        }
     }
 
     /// Add a new topic to the list.
@@ -197,26 +508,50 @@ impl<'a> MqttTopicManager<'a> {
         self
     }
 
+    /// Sets the token cancelling the subscription: its callback is removed from the topics, and the topics left without
+    /// a callback are unsubscribed. The connection is kept, even with the automatic disconnect of the broker options.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation.replace(token);
+        self
+    }
+
     /// Subscribe to the given topics with the callback.
+    /// While the connection is lost, the topics are only stored and subscribed once the client is reconnected.
+    /// A subscription with a cancelled token returns [`Error::Cancelled`](crate::Error::Cancelled).
     pub fn subscribe<C: Fn(&crate::client::TopicEvent) + Send + Sync + 'static>(mut self, callback: C) -> Result<()> {
+        if let Some(token) = &self.cancellation {
+            token.check()?;
+        }
+        let mqtt_topic_handlers = self.client.mqtt_topic_handlers.clone();
         let client = get_mqtt_client(&mut self.client)?;
         let cb = Arc::new(Box::new(callback) as Box<dyn Fn(&crate::client::TopicEvent) + Send + Sync + 'static>);
-        client.subscribe_many(
-            &self.topics.iter().map(|t| t.0.clone()).collect::<Vec<String>>(),
-            &vec![1; self.topics.len()],
-        )?;
+        // stored first so a concurrent reconnection subscribes them
         {
-            let mqtt_topic_handlers = &self.client.mqtt_topic_handlers;
             let mut mqtt_topic_handlers = mqtt_topic_handlers.write().unwrap();
-            for topic in self.topics {
-                match mqtt_topic_handlers.get_mut(&topic) {
+            for topic in &self.topics {
+                match mqtt_topic_handlers.get_mut(topic) {
                     Some(handlers) => handlers.push(cb.clone()),
                     None => {
-                        mqtt_topic_handlers.insert(topic, vec![cb.clone()]);
+                        mqtt_topic_handlers.insert(topic.clone(), vec![cb.clone()]);
                     }
                 }
             }
         }
+        if client.is_connected() {
+            client.subscribe_many(
+                &self.topics.iter().map(|t| t.0.clone()).collect::<Vec<String>>(),
+                &vec![1; self.topics.len()],
+            )?;
+        }
+        if let Some(token) = self.cancellation {
+            // the handlers are only referenced weakly, a token outliving the client doesn't keep them
+            let mqtt_topic_handlers = Arc::downgrade(&mqtt_topic_handlers);
+            let client = client.clone();
+            let topics = self.topics;
+            token
+                .on_cancel(move || cancel_subscription(&mqtt_topic_handlers, &client, topics, &cb))
+                .keep();
+        }
         Ok(())
     }
 
@@ -233,8 +568,12 @@ impl<'a> MqttTopicManager<'a> {
             }
         };
 
-        if let Some(client) = &self.client.mqtt_client {
-            client.unsubscribe_many(&topics.iter().map(|t| t.0.clone()).collect::<Vec<String>>())?;
+        if let Some(connection) = &self.client.mqtt_client {
+            if connection.client.is_connected() {
+                connection
+                    .client
+                    .unsubscribe_many(&topics.iter().map(|t| t.0.clone()).collect::<Vec<String>>())?;
+            }
         }
 
         let empty_topic_handlers = {
@@ -253,3 +592,123 @@ impl<'a> MqttTopicManager<'a> {
         Ok(())
     }
 }
+
+// Removes the handler of a cancelled subscription, the topics left without a handler are unsubscribed.
+fn cancel_subscription(
+    mqtt_topic_handlers: &Weak<RwLock<TopicHandlerMap>>,
+    client: &MqttClient,
+    topics: Vec<Topic>,
+    handler: &Arc<TopicHandler>,
+) {
+    let mqtt_topic_handlers = match mqtt_topic_handlers.upgrade() {
+        Some(mqtt_topic_handlers) => mqtt_topic_handlers,
+        None => return,
+    };
+    let unsubscribed: Vec<String> = {
+        let mut mqtt_topic_handlers = mqtt_topic_handlers.write().unwrap();
+        topics
+            .into_iter()
+            .filter(|topic| match mqtt_topic_handlers.get_mut(topic) {
+                Some(handlers) => {
+                    handlers.retain(|h| !Arc::ptr_eq(h, handler));
+                    if handlers.is_empty() {
+                        mqtt_topic_handlers.remove(topic);
+                        true
+                    } else {
+                        false
+                    }
+                }
+                None => false,
+            })
+            .map(|topic| topic.0)
+            .collect()
+    };
+    if !unsubscribed.is_empty() && client.is_connected() {
+        let _ = client.unsubscribe_many(&unsubscribed);
+    }
+}
+
+/// A topic handler kept for the lifetime of a wait on the established MQTT connection, removed on drop.
+/// The topic is subscribed with its first handler and unsubscribed with its last one.
+pub(crate) struct TopicWaker<'a> {
+    client: &'a Client,
+    topic: Topic,
+    handler: Arc<TopicHandler>,
+}
+
+impl<'a> TopicWaker<'a> {
+    /// Adds the handler of the topic, `None` if the client isn't connected to a broker.
+    pub(crate) fn new<C: Fn(&TopicEvent) + Send + Sync + 'static>(
+        client: &'a Client,
+        topic: Topic,
+        callback: C,
+    ) -> Option<Self> {
+        let connection = client
+            .mqtt_client
+            .as_ref()
+            .filter(|connection| !connection.closed.load(Ordering::SeqCst) && connection.client.is_connected())?;
+        let handler = Arc::new(Box::new(callback) as TopicHandler);
+        let first_handler = {
+            let mut mqtt_topic_handlers = client.mqtt_topic_handlers.write().unwrap();
+            let handlers = mqtt_topic_handlers.entry(topic.clone()).or_default();
+            handlers.push(handler.clone());
+            handlers.len() == 1
+        };
+        let waker = Self { client, topic, handler };
+        if first_handler && connection.client.subscribe(&waker.topic.0, 1).is_err() {
+            return None;
+        }
+        Some(waker)
+    }
+}
+
+/// A connection event handler kept for the lifetime of a wait, removed on drop.
+pub(crate) struct ConnectionWaker<'a> {
+    client: &'a Client,
+    handler: Arc<MqttEventHandler>,
+}
+
+impl<'a> ConnectionWaker<'a> {
+    /// Adds the handler of the connection events.
+    pub(crate) fn new<C: Fn(&MqttEvent) + Send + Sync + 'static>(client: &'a Client, callback: C) -> Self {
+        let handler = Arc::new(Box::new(callback) as MqttEventHandler);
+        client.mqtt_event_handlers.write().unwrap().push(handler.clone());
+        Self { client, handler }
+    }
+}
+
+impl Drop for ConnectionWaker<'_> {
+    fn drop(&mut self) {
+        self.client
+            .mqtt_event_handlers
+            .write()
+            .unwrap()
+            .retain(|handler| !Arc::ptr_eq(handler, &self.handler));
+    }
+}
+
+impl Drop for TopicWaker<'_> {
+    fn drop(&mut self) {
+        let last_handler = {
+            let mut mqtt_topic_handlers = self.client.mqtt_topic_handlers.write().unwrap();
+            let last_handler = match mqtt_topic_handlers.get_mut(&self.topic) {
+                Some(handlers) => {
+                    handlers.retain(|handler| !Arc::ptr_eq(handler, &self.handler));
+                    handlers.is_empty()
+                }
+                None => false,
+            };
+            if last_handler {
+                mqtt_topic_handlers.remove(&self.topic);
+            }
+            last_handler
+        };
+        if last_handler {
+            if let Some(connection) = &self.client.mqtt_client {
+                if connection.client.is_connected() {
+                    let _ = connection.client.unsubscribe(&self.topic.0);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The historical queries of a Chronicle-style permanode, which keeps the data pruned by the nodes. The permanode
+//! serves the node REST routes relative to its URL, e.g. `{permanode}/messages/{messageId}`, with the same responses
+//! except the lists: they're paged with a `state` cursor, given back as the `state` query parameter to get the next
+//! page, and their records are either the ids or objects holding a `messageId` or an `outputId`.
+
+use crate::{
+    parse_response, types::Bech32Address, AddressOutputs, Api, Client, Error, MilestoneMetadata, MilestoneUtxoChanges,
+    OutputsOptions, Response, Result,
+};
+
+use bee_message::{Message, MessageId};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashSet;
+
+/// The most pages fetched for a list, so a permanode returning cursors forever can't hang the query.
+const PERMANODE_PAGE_LIMIT: usize = 1000;
+
+/// The source that served a read query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataSource {
+    /// A node of the client.
+    Node,
+    /// The permanode, for the data pruned by the nodes.
+    Permanode,
+}
+
+/// The answer of a read query with the source that served it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sourced<T> {
+    /// The answer, in the same shape for both sources.
+    pub data: T,
+    /// The source of the answer.
+    pub source: DataSource,
+}
+
+impl<T> Sourced<T> {
+    fn new(data: T, source: DataSource) -> Self {
+        Self { data, source }
+    }
+}
+
+// A record of a permanode list.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PermanodeRecord {
+    Id(String),
+    Message {
+        #[serde(rename = "messageId")]
+        message_id: String,
+    },
+    Output {
+        #[serde(rename = "outputId")]
+        output_id: String,
+    },
+}
+
+impl PermanodeRecord {
+    fn into_id(self) -> String {
+        match self {
+            Self::Id(id) => id,
+            Self::Message { message_id } => message_id,
+            Self::Output { output_id } => output_id,
+        }
+    }
+}
+
+// A page of a permanode list, the cursor of the next page is in the page or next to it.
+#[derive(Deserialize)]
+struct PermanodeList {
+    #[serde(rename = "messageIds", default)]
+    message_ids: Vec<PermanodeRecord>,
+    #[serde(rename = "outputIds", default)]
+    output_ids: Vec<PermanodeRecord>,
+    #[serde(default)]
+    state: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PermanodePage {
+    data: PermanodeList,
+    #[serde(default)]
+    state: Option<String>,
+}
+
+// The URL of a route of the permanode, relative to its URL.
+fn permanode_url(permanode: &Url, route: &str) -> Result<Url> {
+    permanode.join(route).map_err(|_| Error::UrlError)
+}
+
+// Gets every page of a list of the permanode, the ids of its records in the permanode order without duplicates.
+async fn permanode_list(client: &Client, url: Url, api: Api) -> Result<Vec<String>> {
+    let mut ids = Vec::new();
+    let mut seen = HashSet::new();
+    let mut state: Option<String> = None;
+    for _ in 0..PERMANODE_PAGE_LIMIT {
+        let mut page_url = url.clone();
+        if let Some(state) = &state {
+            page_url.query_pairs_mut().append_pair("state", state);
+        }
+        let resp = client
+            .send_request(client.client.get(page_url).timeout(client.get_timeout(api)))
+            .await?;
+        let page = parse_response!(resp, 200 => {
+            Ok(resp.json::<PermanodePage>().await?)
+        })?;
+        let records = page.data.message_ids.into_iter().chain(page.data.output_ids);
+        let mut new_records = 0;
+        for id in records.map(PermanodeRecord::into_id) {
+            if seen.insert(id.clone()) {
+                ids.push(id);
+                new_records += 1;
+            }
+        }
+        // a page without new records ends the list, even with a cursor
+        state = page.state.or(page.data.state).filter(|state| !state.is_empty());
+        if state.is_none() || new_records == 0 {
+            break;
+        }
+    }
+    Ok(ids)
+}
+
+impl Client {
+    /// Sends a read query to a node, then to the permanode if the node answer is `missing` the data, e.g. a `404`
+    /// for a pruned message. The answer of the node is kept if the permanode doesn't have the data either. Only the
+    /// permanode is queried with [`ClientBuilder::with_permanode_only`](crate::ClientBuilder::with_permanode_only).
+    pub(crate) async fn historical_request<T, N, NFut, P, PFut, M>(
+        &self,
+        node_request: N,
+        permanode_request: P,
+        missing: M,
+    ) -> Result<Sourced<T>>
+    where
+        N: FnOnce() -> NFut,
+        NFut: std::future::Future<Output = Result<T>>,
+        P: FnOnce(Url) -> PFut,
+        PFut: std::future::Future<Output = Result<T>>,
+        M: Fn(&Result<T>) -> bool,
+    {
+        let permanode = match &self.permanode {
+            Some(permanode) => permanode.clone(),
+            None => return Ok(Sourced::new(node_request().await?, DataSource::Node)),
+        };
+        if self.permanode_only {
+            return Ok(Sourced::new(permanode_request(permanode).await?, DataSource::Permanode));
+        }
+        let answer = node_request().await;
+        if !missing(&answer) {
+            return Ok(Sourced::new(answer?, DataSource::Node));
+        }
+        let permanode_answer = permanode_request(permanode).await;
+        if missing(&permanode_answer) {
+            return Ok(Sourced::new(answer?, DataSource::Node));
+        }
+        Ok(Sourced::new(permanode_answer?, DataSource::Permanode))
+    }
+
+    /// Gets a message from the permanode.
+    pub(crate) async fn permanode_message(&self, permanode: Url, message_id: &MessageId) -> Result<Message> {
+        let url = permanode_url(&permanode, &format!("messages/{}", message_id))?;
+        let resp = self
+            .send_request(self.client.get(url).timeout(self.get_timeout(Api::GetMessage)))
+            .await?;
+        parse_response!(resp, 200 => {
+            resp.message(message_id).await
+        })
+    }
+
+    /// Gets the ids of the messages of an indexation index from the permanode, every page of them.
+    pub(crate) async fn permanode_message_ids(&self, permanode: Url, index: &str) -> Result<Box<[MessageId]>> {
+        let mut url = permanode_url(&permanode, "messages")?;
+        url.query_pairs_mut().append_pair("index", index);
+        permanode_list(self, url, Api::GetMessage)
+            .await?
+            .iter()
+            .map(|id| {
+                let mut message_id = [0u8; 32];
+                hex::decode_to_slice(id, &mut message_id)?;
+                Ok(MessageId::from(message_id))
+            })
+            .collect()
+    }
+
+    /// Gets the outputs of an address from the permanode, every page of them, filtered with the options.
+    pub(crate) async fn permanode_address_outputs(
+        &self,
+        permanode: Url,
+        address: &Bech32Address,
+        options: OutputsOptions,
+    ) -> Result<AddressOutputs> {
+        let mut url = permanode_url(&permanode, &format!("addresses/{}/outputs", address))?;
+        if options.include_spent {
+            url.query_pairs_mut().append_pair("include-spent", "true");
+        }
+        if let Some(output_type) = options.output_type {
+            url.query_pairs_mut()
+                .append_pair("type", &output_type.kind().to_string());
+        }
+        let output_ids = permanode_list(self, url, Api::GetAddress).await?;
+        // the pages are merged into a single list, which isn't truncated
+        Ok(AddressOutputs {
+            count: output_ids.len(),
+            max_results: output_ids.len(),
+            output_ids: output_ids.into_boxed_slice(),
+        })
+    }
+
+    /// Gets a milestone from the permanode.
+    pub(crate) async fn permanode_milestone(&self, permanode: Url, index: u64) -> Result<MilestoneMetadata> {
+        let url = permanode_url(&permanode, &format!("milestones/{}", index))?;
+        let resp = self
+            .send_request(self.client.get(url).timeout(self.get_timeout(Api::GetMilestone)))
+            .await?;
+        parse_response!(resp, 200 => {
+            Ok(resp.json::<Response<MilestoneMetadata>>().await?.data)
+        })
+    }
+
+    /// Gets the UTXO changes of a milestone from the permanode.
+    pub(crate) async fn permanode_milestone_utxo_changes(
+        &self,
+        permanode: Url,
+        index: u64,
+    ) -> Result<MilestoneUtxoChanges> {
+        let url = permanode_url(&permanode, &format!("milestones/{}/utxo-changes", index))?;
+        let resp = self
+            .send_request(self.client.get(url).timeout(self.get_timeout(Api::GetMilestone)))
+            .await?;
+        parse_response!(resp, 200 => {
+            Ok(resp.json::<Response<MilestoneUtxoChanges>>().await?.data)
+        })
+    }
+}
+
+/// Whether an answer is a `404`, or data pruned by the node.
+pub(crate) fn is_not_found<T>(answer: &Result<T>) -> bool {
+    matches!(
+        answer,
+        Err(Error::ResponseError(404, _, _)) | Err(Error::PrunedData { .. })
+    )
+}
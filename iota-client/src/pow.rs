@@ -0,0 +1,108 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The providers of the nonces of the messages: the local PoW, the PoW of the nodes or an outsourced PoW
+
+#[cfg(feature = "pow")]
+use crate::CancellationToken;
+use crate::{Error, Result};
+
+use bee_common::packable::Packable;
+use bee_message::prelude::{Message, MessageBuilder, MessageId, Payload};
+use bee_pow::providers::{Constant, ConstantBuilder, ProviderBuilder as PowProviderBuilder};
+#[cfg(feature = "pow")]
+use bee_pow::providers::{MinerBuilder, MinerCancel, Provider as PowProvider};
+
+/// Computes the nonce of the messages, set with
+/// [`ClientBuilder::with_nonce_provider`](crate::ClientBuilder::with_nonce_provider) to outsource the PoW, like to a
+/// dedicated PoW service.
+///
+/// The provider is called from the task building the message and may block it, as the local PoW does. A blocking
+/// provider stops on the cancellation of [`CancellationToken::current`](crate::CancellationToken::current).
+pub trait NonceProvider: Send + Sync {
+    /// The nonce for the message to reach the target score, given the packed message without its nonce, the last 8
+    /// bytes. A provider failing or timing out returns an error, reported as [`Error::NonceProvider`].
+    fn nonce(&self, bytes: &[u8], target_score: f64) -> Result<u64>;
+}
+
+/// The local PoW, mining the nonce with the threads of the client, available with the `pow` feature.
+///
+/// The PoW done while a future run with a [`CancellationToken`] is polled is cancelled with it: the worker threads
+/// stop at their next batch of nonces and the nonce is [`Error::Cancelled`].
+#[cfg(feature = "pow")]
+pub struct LocalPow {
+    worker_count: usize,
+}
+
+#[cfg(feature = "pow")]
+impl LocalPow {
+    /// The local PoW with `worker_count` threads.
+    pub fn new(worker_count: usize) -> Self {
+        Self { worker_count }
+    }
+}
+
+#[cfg(feature = "pow")]
+impl NonceProvider for LocalPow {
+    fn nonce(&self, bytes: &[u8], target_score: f64) -> Result<u64> {
+        CancellationToken::check_current()?;
+        let cancel = MinerCancel::new();
+        // the tokens trigger the miner from the thread cancelling them, the handlers are removed with the guards
+        let _guards: Vec<_> = CancellationToken::current_tokens()
+            .iter()
+            .map(|token| {
+                let cancel = cancel.clone();
+                token.on_cancel(move || cancel.trigger())
+            })
+            .collect();
+        let nonce = MinerBuilder::new()
+            .with_num_workers(self.worker_count)
+            .with_cancel(cancel.clone())
+            .finish()
+            .nonce(bytes, target_score);
+        if cancel.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        nonce.map_err(|e| Error::Pow(e.to_string()))
+    }
+}
+
+/// The remote PoW: the messages are submitted with a zero nonce and the node does the PoW, see
+/// [`ClientBuilder::with_local_pow`](crate::ClientBuilder::with_local_pow).
+pub struct RemotePow;
+
+impl NonceProvider for RemotePow {
+    fn nonce(&self, _bytes: &[u8], _target_score: f64) -> Result<u64> {
+        Ok(0)
+    }
+}
+
+/// Builds a message with the nonce of the provider. The message is packed with a zero nonce, the provider gets the
+/// bytes before the nonce and the message is built again with the nonce it returns, so the errors of the provider are
+/// returned as is.
+pub fn finish_pow<P: NonceProvider + ?Sized>(
+    provider: &P,
+    target_score: f64,
+    network_id: u64,
+    parents: (MessageId, MessageId),
+    payload: Option<Payload>,
+) -> Result<Message> {
+    let message = |nonce| {
+        let mut message = MessageBuilder::<Constant>::new()
+            .with_network_id(network_id)
+            .with_parent1(parents.0)
+            .with_parent2(parents.1);
+        if let Some(payload) = payload.clone() {
+            message = message.with_payload(payload);
+        }
+        message
+            .with_nonce_provider(ConstantBuilder::new().with_value(nonce).finish(), 0f64)
+            .finish()
+            .map_err(Error::MessageError)
+    };
+    let mut bytes = Vec::new();
+    message(0)?.pack(&mut bytes)?;
+    // the nonce is the last field of the message
+    bytes.truncate(bytes.len() - std::mem::size_of::<u64>());
+    message(provider.nonce(&bytes, target_score)?)
+}
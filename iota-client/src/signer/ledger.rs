@@ -0,0 +1,144 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    api::{PreparedTransactionData, RemainderData},
+    signer::slip10::{HARDENED, IOTA_COIN_TYPE},
+    Error, Result,
+};
+
+use bee_common::packable::Packable;
+use bee_message::prelude::{Address, Ed25519Address, Ed25519Signature, SignatureUnlock, UnlockBlock};
+use iota_ledger::{api::errors::APIError, LedgerBIP32Index, LedgerHardwareWallet};
+
+// The name of the IOTA app, reported by the device while it's open.
+const IOTA_APP_NAME: &str = "IOTA";
+
+/// A signer delegating the address derivation and the essence signing to a Ledger Nano S or X running the IOTA app,
+/// or to the Speculos simulator. The keys never leave the device, and each signature is confirmed by the user.
+///
+/// The device is opened on each derivation or signature, so it can be plugged in or unlocked after the signer is
+/// created.
+pub struct LedgerSigner {
+    simulator: bool,
+}
+
+impl LedgerSigner {
+    /// Creates a signer of the device, or of the Speculos simulator, failing if the device isn't found or if the IOTA
+    /// app isn't open.
+    pub fn new(simulator: bool) -> Result<Self> {
+        let signer = Self { simulator };
+        signer.open(0)?;
+        Ok(signer)
+    }
+
+    /// Whether the signer is the Speculos simulator.
+    pub fn is_simulator(&self) -> bool {
+        self.simulator
+    }
+
+    /// Derives the address and shows it on the device, so the user can verify it's the address of the device.
+    /// Fails with [`Error::LedgerDeniedByUser`] if the user rejects the address.
+    pub fn display_address(&self, account_index: usize, address_index: usize, internal: bool) -> Result<Address> {
+        self.address(account_index, address_index, internal, true)
+    }
+
+    pub(crate) fn generate_address(
+        &self,
+        account_index: usize,
+        address_index: usize,
+        internal: bool,
+    ) -> Result<Address> {
+        self.address(account_index, address_index, internal, false)
+    }
+
+    /// Signs the essence of the prepared transaction on the device, which shows its outputs to the user, returning the
+    /// signatures of the [`signers`](PreparedTransactionData::signers) in their order.
+    pub(crate) fn sign_essence(&self, prepared: &PreparedTransactionData) -> Result<Vec<Ed25519Signature>> {
+        let ledger = self.open(prepared.account_index)?;
+        // The device signs the packed essence as it's submitted, so it shows the outputs of the submitted transaction
+        let essence = prepared.essence_bytes()?;
+        let inputs = prepared.essence.inputs();
+        let mut key_indexes = Vec::new();
+        for input in inputs {
+            let signing_data = prepared.signing_data(input)?;
+            key_indexes.push(bip32_index(signing_data.address_index, signing_data.internal));
+        }
+        // The device shows the remainder as change if it's an address of the account, it derives it to check it
+        let (has_remainder, remainder_index, remainder_bip32_index) = match &prepared.remainder {
+            Some(RemainderData {
+                output_index,
+                address_index: Some(address_index),
+                internal,
+                ..
+            }) => (true, *output_index as u16, bip32_index(*address_index, *internal)),
+            _ => (false, 0, LedgerBIP32Index::default()),
+        };
+        ledger
+            .prepare_signing(
+                key_indexes,
+                essence,
+                has_remainder,
+                remainder_index,
+                remainder_bip32_index,
+            )
+            .map_err(ledger_error)?;
+        ledger.user_confirm().map_err(ledger_error)?;
+        let mut unlock_blocks = &ledger.sign(inputs.len() as u16).map_err(ledger_error)?[..];
+
+        // The device returns the unlock blocks of the inputs, the signatures are in the order of the signing addresses
+        let mut signatures = Vec::new();
+        for _ in inputs {
+            match UnlockBlock::unpack(&mut unlock_blocks)
+                .map_err(|_| Error::LedgerError("invalid unlock block of the device".to_string()))?
+            {
+                UnlockBlock::Signature(SignatureUnlock::Ed25519(signature)) => signatures.push(signature),
+                UnlockBlock::Reference(_) => {}
+                _ => return Err(Error::LedgerError("unexpected unlock block of the device".to_string())),
+            }
+        }
+        Ok(signatures)
+    }
+
+    fn address(&self, account_index: usize, address_index: usize, internal: bool, show: bool) -> Result<Address> {
+        let ledger = self.open(account_index)?;
+        let addresses = ledger
+            .get_addresses(show, bip32_index(address_index, internal), 1)
+            .map_err(ledger_error)?;
+        let address = addresses
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::LedgerError("no address derived by the device".to_string()))?;
+        Ok(Address::Ed25519(Ed25519Address::new(address)))
+    }
+
+    // Opens the device on the account, checking that the IOTA app is open.
+    fn open(&self, account_index: usize) -> Result<Box<LedgerHardwareWallet>> {
+        let ledger = iota_ledger::get_ledger(IOTA_COIN_TYPE, account_index as u32 | HARDENED, self.simulator)
+            .map_err(ledger_error)?;
+        let (app_name, _version) = ledger.get_opened_app().map_err(ledger_error)?;
+        if app_name != IOTA_APP_NAME {
+            return Err(Error::LedgerAppNotOpen(format!("the opened app is {}", app_name)));
+        }
+        Ok(ledger)
+    }
+}
+
+fn bip32_index(address_index: usize, internal: bool) -> LedgerBIP32Index {
+    LedgerBIP32Index {
+        bip32_change: internal as u32 | HARDENED,
+        bip32_index: address_index as u32 | HARDENED,
+    }
+}
+
+fn ledger_error(error: APIError) -> Error {
+    match error {
+        APIError::ConditionsOfUseNotSatisfied => Error::LedgerDeniedByUser,
+        // the locked device or its dashboard answer to the IOTA app commands with these status words
+        APIError::SecurityStatusNotSatisfied | APIError::ClaNotSupported => {
+            Error::LedgerAppNotOpen("the device is locked or the IOTA app isn't open".to_string())
+        }
+        APIError::TransportError => Error::LedgerDeviceNotFound,
+        error => Error::LedgerError(format!("{:?}", error)),
+    }
+}
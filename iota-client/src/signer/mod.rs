@@ -0,0 +1,151 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The signers holding the key material of an account: they derive the account addresses and sign the transactions
+
+#[cfg(feature = "ledger")]
+mod ledger;
+pub mod slip10;
+#[cfg(feature = "stronghold")]
+mod stronghold;
+
+#[cfg(feature = "ledger")]
+pub use ledger::LedgerSigner;
+#[cfg(feature = "stronghold")]
+pub use stronghold::StrongholdSigner;
+
+use crate::{api::public_key_address, Error, Result};
+
+use bee_message::prelude::{Address, Ed25519Signature};
+use bee_signing_ext::{binary::Ed25519PrivateKey, Seed, Signer};
+use slip10::Chain;
+
+const BIP39_SEED_LENGTH: usize = 64;
+
+/// The key material of an account, the addresses are derived with SLIP-10 on `m/44'/4218'/account'/change'/index'`.
+#[derive(Clone, Copy)]
+pub enum SignerType<'a> {
+    /// A seed held in memory.
+    Seed(&'a Seed),
+    /// The BIP39 seed of a mnemonic held in memory.
+    Bip39Seed(&'a Bip39Seed),
+    /// A seed held in a Stronghold snapshot, the keys never leave the Stronghold.
+    #[cfg(feature = "stronghold")]
+    Stronghold(&'a StrongholdSigner),
+    /// A Ledger Nano running the IOTA app, the keys never leave the device.
+    #[cfg(feature = "ledger")]
+    LedgerNano(&'a LedgerSigner),
+}
+
+/// The 64 bytes BIP39 seed of a mnemonic. It's the SLIP-10 seed of the account as is, so it derives the addresses of
+/// the mnemonic in Firefly and wallet.rs.
+pub struct Bip39Seed([u8; BIP39_SEED_LENGTH]);
+
+impl Bip39Seed {
+    /// Builds the seed from the 64 bytes of the BIP39 seed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != BIP39_SEED_LENGTH {
+            return Err(Error::InvalidParameter(format!(
+                "BIP39 seed of {} bytes, expected {} bytes",
+                bytes.len(),
+                BIP39_SEED_LENGTH
+            )));
+        }
+        let mut seed = [0; BIP39_SEED_LENGTH];
+        seed.copy_from_slice(bytes);
+        Ok(Self(seed))
+    }
+
+    /// Gets the bytes of the seed.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<'a> From<&'a Seed> for SignerType<'a> {
+    fn from(seed: &'a Seed) -> Self {
+        SignerType::Seed(seed)
+    }
+}
+
+impl<'a> From<&'a Bip39Seed> for SignerType<'a> {
+    fn from(seed: &'a Bip39Seed) -> Self {
+        SignerType::Bip39Seed(seed)
+    }
+}
+
+#[cfg(feature = "stronghold")]
+impl<'a> From<&'a StrongholdSigner> for SignerType<'a> {
+    fn from(signer: &'a StrongholdSigner) -> Self {
+        SignerType::Stronghold(signer)
+    }
+}
+
+#[cfg(feature = "ledger")]
+impl<'a> From<&'a LedgerSigner> for SignerType<'a> {
+    fn from(signer: &'a LedgerSigner) -> Self {
+        SignerType::LedgerNano(signer)
+    }
+}
+
+impl SignerType<'_> {
+    /// Derives the address of the account index, address index and internal flag.
+    /// The indexes must be below `2^31`, as the segments of the path are hardened.
+    pub fn generate_address(&self, account_index: usize, address_index: usize, internal: bool) -> Result<Address> {
+        let chain = Chain::bip44(account_index, internal, address_index)?;
+        match self {
+            SignerType::Seed(seed) => Ok(address(&private_key(&ed25519_seed(seed), &chain)?)),
+            SignerType::Bip39Seed(seed) => Ok(address(&private_key(seed.as_bytes(), &chain)?)),
+            #[cfg(feature = "stronghold")]
+            SignerType::Stronghold(signer) => signer.generate_address(&chain),
+            #[cfg(feature = "ledger")]
+            SignerType::LedgerNano(signer) => signer.generate_address(account_index, address_index, internal),
+        }
+    }
+
+    /// Signs the message with the key of the address, returning the signature with the public key.
+    /// A Ledger Nano only signs whole transaction essences, with
+    /// [`PreparedTransactionData::sign`](crate::api::PreparedTransactionData::sign).
+    pub fn sign(
+        &self,
+        account_index: usize,
+        address_index: usize,
+        internal: bool,
+        message: &[u8],
+    ) -> Result<Ed25519Signature> {
+        let chain = Chain::bip44(account_index, internal, address_index)?;
+        match self {
+            SignerType::Seed(seed) => Ok(sign(&private_key(&ed25519_seed(seed), &chain)?, message)),
+            SignerType::Bip39Seed(seed) => Ok(sign(&private_key(seed.as_bytes(), &chain)?, message)),
+            #[cfg(feature = "stronghold")]
+            SignerType::Stronghold(signer) => signer.sign(&chain, message),
+            #[cfg(feature = "ledger")]
+            SignerType::LedgerNano(_) => Err(Error::LedgerError(
+                "the Ledger Nano only signs transaction essences".to_string(),
+            )),
+        }
+    }
+}
+
+fn ed25519_seed(seed: &Seed) -> [u8; 32] {
+    match seed {
+        Seed::Ed25519(seed) => seed.to_bytes(),
+        Seed::Wots(_) => panic!("Wots signing scheme isn't supported."),
+    }
+}
+
+// Derives the private key of the chain from a seed held in memory.
+fn private_key(seed: &[u8], chain: &Chain) -> Result<Ed25519PrivateKey> {
+    let key = slip10::derive_private_key(seed, chain)?;
+    Ed25519PrivateKey::from_bytes(&key).map_err(|_| Error::InvalidParameter("seed inputs".to_string()))
+}
+
+fn address(private_key: &Ed25519PrivateKey) -> Address {
+    public_key_address(&private_key.generate_public_key().to_bytes())
+}
+
+fn sign(private_key: &Ed25519PrivateKey, message: &[u8]) -> Ed25519Signature {
+    let public_key = private_key.generate_public_key().to_bytes();
+    let signature = Box::new(private_key.sign(message).to_bytes());
+    Ed25519Signature::new(public_key, signature)
+}
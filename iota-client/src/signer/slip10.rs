@@ -0,0 +1,146 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The SLIP-10 derivation of the Ed25519 keys on the BIP44 paths `m/44'/4218'/account'/change'/index'`, the paths of
+//! Firefly and wallet.rs. Ed25519 SLIP-10 only derives hardened segments.
+
+use crate::{Error, Result};
+
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha512;
+
+use std::{fmt, str::FromStr};
+
+/// The hardened flag of a segment.
+pub const HARDENED: u32 = 1 << 31;
+/// The BIP44 coin type of IOTA.
+pub const IOTA_COIN_TYPE: u32 = 4218;
+const BIP44_PURPOSE: u32 = 44;
+// The HMAC key of the master key of an Ed25519 SLIP-10 seed.
+const ED25519_CURVE: &[u8] = b"ed25519 seed";
+// The seed length range in bytes, 128 to 512 bits as in BIP32.
+const SEED_LENGTH_MIN: usize = 16;
+const SEED_LENGTH_MAX: usize = 64;
+
+/// A derivation path of hardened segments, as `m/44'/4218'/0'/0'/0'`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chain(Vec<u32>);
+
+impl Chain {
+    /// Builds the chain of the segments, with the hardened flag set. A segment with the flag already set is rejected,
+    /// as the indexes must be below `2^31`.
+    pub fn from_hardened_indexes(indexes: &[u32]) -> Result<Self> {
+        let mut segments = Vec::new();
+        for index in indexes {
+            if index & HARDENED != 0 {
+                return Err(Error::InvalidDerivationPath(format!(
+                    "the index {} isn't below 2^31",
+                    index
+                )));
+            }
+            segments.push(index | HARDENED);
+        }
+        Ok(Self(segments))
+    }
+
+    /// Builds the BIP44 chain `m/44'/4218'/account'/change'/index'` of an address, the change segment is `1'` for the
+    /// internal addresses.
+    pub fn bip44(account_index: usize, internal: bool, address_index: usize) -> Result<Self> {
+        Self::from_hardened_indexes(&[
+            BIP44_PURPOSE,
+            IOTA_COIN_TYPE,
+            derivation_index("account index", account_index)?,
+            internal as u32,
+            derivation_index("address index", address_index)?,
+        ])
+    }
+
+    /// Gets the segments, with the hardened flag set.
+    pub fn segments(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl FromStr for Chain {
+    type Err = Error;
+
+    /// Parses a path as `m/44'/4218'/0'/0'/0'`, a segment without the `'` or `H` hardened suffix is rejected.
+    fn from_str(path: &str) -> Result<Self> {
+        let mut segments = path.split('/');
+        if segments.next() != Some("m") {
+            return Err(Error::InvalidDerivationPath(format!("{} doesn't start with m", path)));
+        }
+        let mut indexes = Vec::new();
+        for segment in segments {
+            let index = match segment.strip_suffix('\'').or_else(|| segment.strip_suffix('H')) {
+                Some(index) => index,
+                None => {
+                    return Err(Error::InvalidDerivationPath(format!(
+                        "the segment {} of {} isn't hardened, Ed25519 SLIP-10 only derives hardened segments",
+                        segment, path
+                    )))
+                }
+            };
+            indexes.push(
+                index
+                    .parse()
+                    .map_err(|_| Error::InvalidDerivationPath(format!("invalid segment {} of {}", segment, path)))?,
+            );
+        }
+        Self::from_hardened_indexes(&indexes)
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for segment in &self.0 {
+            write!(f, "/{}'", segment & !HARDENED)?;
+        }
+        Ok(())
+    }
+}
+
+/// Derives the Ed25519 private key of the chain from the seed, a BIP39 seed of a mnemonic or any seed of 16 to 64
+/// bytes.
+pub fn derive_private_key(seed: &[u8], chain: &Chain) -> Result<[u8; 32]> {
+    if !(SEED_LENGTH_MIN..=SEED_LENGTH_MAX).contains(&seed.len()) {
+        return Err(Error::InvalidParameter(format!(
+            "seed of {} bytes, expected {} to {} bytes",
+            seed.len(),
+            SEED_LENGTH_MIN,
+            SEED_LENGTH_MAX
+        )));
+    }
+    let (mut key, mut chain_code) = hmac_sha512(ED25519_CURVE, &[seed]);
+    for segment in chain.segments() {
+        let (child_key, child_chain_code) = hmac_sha512(&chain_code, &[&[0], &key, &segment.to_be_bytes()]);
+        key = child_key;
+        chain_code = child_chain_code;
+    }
+    Ok(key)
+}
+
+// Splits the HMAC-SHA512 of the data into the key and the chain code.
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_varkey(key).expect("HMAC accepts keys of any length");
+    for data in data {
+        mac.update(data);
+    }
+    let hash = mac.finalize().into_bytes();
+    let mut key = [0; 32];
+    let mut chain_code = [0; 32];
+    key.copy_from_slice(&hash[..32]);
+    chain_code.copy_from_slice(&hash[32..]);
+    (key, chain_code)
+}
+
+fn derivation_index(name: &str, index: usize) -> Result<u32> {
+    if index >= HARDENED as usize {
+        return Err(Error::InvalidDerivationPath(format!(
+            "the {} {} isn't below 2^31",
+            name, index
+        )));
+    }
+    Ok(index as u32)
+}
@@ -0,0 +1,198 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    api::public_key_address,
+    signer::slip10::{self, HARDENED},
+    Error, Result,
+};
+
+use bee_message::prelude::{Address, Ed25519Signature};
+use blake2::{
+    digest::{Update, VariableOutput},
+    VarBlake2b,
+};
+use futures::executor::block_on;
+use iota_stronghold::{
+    hd::Chain, Location, ProcResult, Procedure, RecordHint, ResultMessage, SLIP10DeriveInput, Stronghold,
+};
+use riker::actors::ActorSystem;
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+// The client path of the snapshot, and the vault locations of the seed and of the last derived key.
+const CLIENT_PATH: &[u8] = b"iota.rs";
+const VAULT_PATH: &str = "iota.rs-seed";
+const SEED_RECORD_PATH: &str = "seed";
+const DERIVED_KEY_RECORD_PATH: &str = "derived-key";
+// The lengths of an Ed25519 seed and of the BIP39 seed of a mnemonic.
+const SEED_LENGTHS: [usize; 2] = [32, 64];
+
+/// A signer whose seed is held in a Stronghold snapshot file, encrypted with a password.
+/// The keys are derived and the transactions signed inside the Stronghold, the seed is never read back.
+///
+/// The snapshot holds the Ed25519 seed or the BIP39 seed, so it derives the same addresses as the
+/// [`Seed`](crate::Seed) or the [`Bip39Seed`](crate::Bip39Seed) built from the same bytes.
+pub struct StrongholdSigner {
+    stronghold: Mutex<Stronghold>,
+    snapshot_path: PathBuf,
+}
+
+impl StrongholdSigner {
+    /// Loads the snapshot, decrypted with the password.
+    pub fn load<P: AsRef<Path>>(snapshot_path: P, password: &str) -> Result<Self> {
+        let snapshot_path = snapshot_path.as_ref().to_path_buf();
+        if !snapshot_path.is_file() {
+            return Err(Error::StrongholdSnapshotNotFound(snapshot_path.display().to_string()));
+        }
+        let mut stronghold = init_stronghold()?;
+        match block_on(stronghold.read_snapshot(
+            CLIENT_PATH.to_vec(),
+            None,
+            &password_key(password),
+            None,
+            Some(snapshot_path.clone()),
+        )) {
+            ResultMessage::Ok(_) => {}
+            // the snapshot can't be decrypted, its integrity is checked with the key
+            ResultMessage::Error(_) => {
+                return Err(Error::StrongholdInvalidPassword(snapshot_path.display().to_string()));
+            }
+        }
+        Ok(Self {
+            stronghold: Mutex::new(stronghold),
+            snapshot_path,
+        })
+    }
+
+    /// Creates a snapshot holding the 32 bytes Ed25519 seed or the 64 bytes BIP39 seed, encrypted with the password.
+    /// An existing snapshot isn't overwritten.
+    pub fn create<P: AsRef<Path>>(snapshot_path: P, password: &str, seed: &[u8]) -> Result<Self> {
+        let snapshot_path = snapshot_path.as_ref().to_path_buf();
+        if snapshot_path.exists() {
+            return Err(Error::InvalidParameter(format!(
+                "the Stronghold snapshot {} already exists",
+                snapshot_path.display()
+            )));
+        }
+        if !SEED_LENGTHS.contains(&seed.len()) {
+            return Err(Error::InvalidParameter(format!(
+                "seed of {} bytes, expected {} or {} bytes",
+                seed.len(),
+                SEED_LENGTHS[0],
+                SEED_LENGTHS[1]
+            )));
+        }
+        let mut stronghold = init_stronghold()?;
+        stronghold_result(block_on(stronghold.write_to_vault(
+            seed_location(),
+            seed.to_vec(),
+            record_hint("seed")?,
+            Vec::new(),
+        )))?;
+        stronghold_result(block_on(stronghold.write_all_to_snapshot(
+            &password_key(password),
+            None,
+            Some(snapshot_path.clone()),
+        )))?;
+        Ok(Self {
+            stronghold: Mutex::new(stronghold),
+            snapshot_path,
+        })
+    }
+
+    /// Gets the path of the snapshot.
+    pub fn snapshot_path(&self) -> &Path {
+        &self.snapshot_path
+    }
+
+    pub(crate) fn generate_address(&self, chain: &slip10::Chain) -> Result<Address> {
+        let stronghold = self.stronghold.lock().unwrap();
+        let key = derive_key(&stronghold, chain)?;
+        let public_key = public_key(&stronghold, key)?;
+        Ok(public_key_address(&public_key))
+    }
+
+    pub(crate) fn sign(&self, chain: &slip10::Chain, message: &[u8]) -> Result<Ed25519Signature> {
+        let stronghold = self.stronghold.lock().unwrap();
+        let key = derive_key(&stronghold, chain)?;
+        let public_key = public_key(&stronghold, key.clone())?;
+        let signature = match block_on(stronghold.runtime_exec(Procedure::Ed25519Sign {
+            private_key: key,
+            msg: message.to_vec(),
+        })) {
+            ProcResult::Ed25519Sign(response) => stronghold_result(response)?,
+            _ => {
+                return Err(Error::StrongholdError(
+                    "unexpected response to the sign request".to_string(),
+                ))
+            }
+        };
+        Ok(Ed25519Signature::new(public_key, Box::new(signature)))
+    }
+}
+
+fn init_stronghold() -> Result<Stronghold> {
+    let system = ActorSystem::new().map_err(|e| Error::StrongholdError(e.to_string()))?;
+    Ok(Stronghold::init_stronghold_system(
+        system,
+        CLIENT_PATH.to_vec(),
+        Vec::new(),
+    ))
+}
+
+// Derives the key of the chain with SLIP-10 into the derived key record, overwriting the previous key.
+fn derive_key(stronghold: &Stronghold, chain: &slip10::Chain) -> Result<Location> {
+    let chain = Chain::from_u32_hardened(chain.segments().iter().map(|segment| segment & !HARDENED).collect());
+    let output = Location::generic(VAULT_PATH, DERIVED_KEY_RECORD_PATH);
+    match block_on(stronghold.runtime_exec(Procedure::SLIP10Derive {
+        chain,
+        input: SLIP10DeriveInput::Seed(seed_location()),
+        output: output.clone(),
+        hint: record_hint("derived key")?,
+    })) {
+        ProcResult::SLIP10Derive(response) => stronghold_result(response)?,
+        _ => {
+            return Err(Error::StrongholdError(
+                "unexpected response to the derive request".to_string(),
+            ))
+        }
+    };
+    Ok(output)
+}
+
+fn public_key(stronghold: &Stronghold, key: Location) -> Result<[u8; 32]> {
+    match block_on(stronghold.runtime_exec(Procedure::Ed25519PublicKey { private_key: key })) {
+        ProcResult::Ed25519PublicKey(response) => stronghold_result(response),
+        _ => Err(Error::StrongholdError(
+            "unexpected response to the public key request".to_string(),
+        )),
+    }
+}
+
+fn seed_location() -> Location {
+    Location::generic(VAULT_PATH, SEED_RECORD_PATH)
+}
+
+fn record_hint(hint: &str) -> Result<RecordHint> {
+    RecordHint::new(hint).ok_or_else(|| Error::StrongholdError(format!("invalid record hint {}", hint)))
+}
+
+// The snapshot key, the BLAKE2b-256 hash of the password.
+fn password_key(password: &str) -> Vec<u8> {
+    let mut key = Vec::new();
+    let mut hasher = VarBlake2b::new(32).unwrap();
+    hasher.update(password.as_bytes());
+    hasher.finalize_variable(|hash| key.extend_from_slice(hash));
+    key
+}
+
+fn stronghold_result<T>(response: ResultMessage<T>) -> Result<T> {
+    match response {
+        ResultMessage::Ok(value) => Ok(value),
+        ResultMessage::Error(error) => Err(Error::StrongholdError(error)),
+    }
+}
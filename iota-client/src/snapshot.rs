@@ -0,0 +1,773 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Streaming parser of the Hornet snapshot files, to verify balances offline against a full snapshot and a delta
+//! snapshot on top of it, see [`balance_of`] and [`check_supply`]. A section is read as it's iterated, so a file is
+//! never loaded in memory.
+//!
+//! The layout of a file, with the integers in little endian:
+//! - the header: the version `u8`, the kind `u8` (0 full, 1 delta), the timestamp `u64`, the network id `u64`, the
+//!   solid entry point milestone index `u32`, the ledger milestone index `u32`, the number of solid entry points
+//!   `u64`, the number of outputs `u64` of a full snapshot only, the number of milestone diffs `u64`, and the treasury
+//!   output of a full snapshot only: the id of the milestone which created it, 32 bytes, and its amount `u64`
+//! - the solid entry points: a message id of 32 bytes each
+//! - the outputs of a full snapshot: the message id of 32 bytes, the transaction id of 32 bytes, the output index
+//!   `u16`, the output type `u8`, the address type `u8` (0 Ed25519) with its 32 bytes, and the amount `u64`
+//! - the milestone diffs: the length `u32` of the milestone payload and the payload, the treasury output spent by the
+//!   receipt of the milestone if it has one, the number of created outputs `u64` and the outputs, the number of
+//!   consumed outputs `u64` and the outputs, each followed by the 32 bytes of the consuming transaction id
+//!
+//! The milestone payload is the one of the messages: its type `u32` (1), the index `u32`, the timestamp `u64`, the
+//! number of parents `u8` with their 32 bytes, the inclusion merkle proof of 32 bytes, the next PoW score `u32` and its
+//! milestone index `u32`, the number of public keys `u8` with their 32 bytes, the length `u32` of the receipt payload
+//! and the receipt, 0 without one, then the number of signatures `u8` with their 64 bytes. The receipt ends with its
+//! treasury transaction, the treasury output created by the milestone is its last 9 bytes: the output type `u8` (2)
+//! and the amount `u64`.
+//!
+//! A full snapshot holds the ledger at its ledger milestone, with the milestone diffs from the ledger milestone back
+//! to the solid entry point milestone. A delta snapshot holds the milestone diffs from the solid entry point milestone
+//! of its full snapshot, its ledger milestone, to its own solid entry point milestone.
+
+use crate::{api::IOTA_SUPPLY, Error, OutputType, Result};
+
+use bee_message::prelude::{Address, Ed25519Address, MessageId, TransactionId, UTXOInput};
+
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+};
+
+/// The version of the snapshot files the parser reads.
+pub const SNAPSHOT_VERSION: u8 = 1;
+
+// the offsets of the header fields checked against another snapshot
+const KIND_OFFSET: u64 = 1;
+const NETWORK_ID_OFFSET: u64 = 10;
+const LEDGER_MILESTONE_INDEX_OFFSET: u64 = 22;
+
+// the payload types of a milestone and of its receipt, and the output type of the treasury output of the receipt
+const MILESTONE_PAYLOAD_TYPE: u32 = 1;
+const RECEIPT_PAYLOAD_TYPE: u32 = 3;
+const TREASURY_OUTPUT_TYPE: u8 = 2;
+
+/// The kind of a snapshot file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotKind {
+    /// A full snapshot, with the ledger and the milestone diffs back to its solid entry point milestone
+    Full,
+    /// A delta snapshot, with the milestone diffs since the solid entry point milestone of its full snapshot
+    Delta,
+}
+
+/// The section of a snapshot file, see [`Error::InvalidSnapshot`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SnapshotSection {
+    /// The header
+    Header,
+    /// The solid entry points
+    SolidEntryPoints,
+    /// The outputs of a full snapshot
+    Outputs,
+    /// The milestone diffs
+    MilestoneDiffs,
+}
+
+impl fmt::Display for SnapshotSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Header => write!(f, "header"),
+            Self::SolidEntryPoints => write!(f, "solid entry points"),
+            Self::Outputs => write!(f, "outputs"),
+            Self::MilestoneDiffs => write!(f, "milestone diffs"),
+        }
+    }
+}
+
+/// The header of a snapshot file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotHeader {
+    /// The version of the file format, see [`SNAPSHOT_VERSION`]
+    pub version: u8,
+    /// The kind of the snapshot
+    pub kind: SnapshotKind,
+    /// The UNIX timestamp of the snapshot, in seconds
+    pub timestamp: u64,
+    /// The network id
+    pub network_id: u64,
+    /// The milestone of the solid entry points, the milestone the snapshot was taken at
+    pub sep_milestone_index: u32,
+    /// The milestone of the ledger of a full snapshot, the solid entry point milestone of the full snapshot of a delta
+    pub ledger_milestone_index: u32,
+    /// The number of solid entry points
+    pub sep_count: u64,
+    /// The number of outputs, 0 for a delta snapshot
+    pub output_count: u64,
+    /// The number of milestone diffs
+    pub milestone_diff_count: u64,
+    /// The treasury output of the ledger of a full snapshot, `None` for a delta snapshot
+    pub treasury_output: Option<SnapshotTreasuryOutput>,
+}
+
+/// A treasury output of a snapshot, holding the funds left to migrate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotTreasuryOutput {
+    /// The hex-encoded id of the milestone which created the output
+    pub milestone_id: String,
+    /// The amount of the output
+    pub amount: u64,
+}
+
+/// The treasury change of a milestone with a receipt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreasuryChange {
+    /// The treasury output spent by the receipt
+    pub spent: SnapshotTreasuryOutput,
+    /// The amount of the treasury output created by the receipt
+    pub amount: u64,
+}
+
+/// An output of a snapshot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotOutput {
+    /// The message of the output
+    pub message_id: MessageId,
+    /// The output id, the hex-encoded transaction id followed by the little endian output index
+    pub output_id: String,
+    /// The address of the output
+    pub address: Address,
+    /// The amount of the output
+    pub amount: u64,
+    /// The type of the output
+    pub output_type: OutputType,
+}
+
+/// The outputs a milestone created and consumed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MilestoneDiff {
+    /// The index of the milestone
+    pub milestone_index: u32,
+    /// The treasury change of the receipt of the milestone, `None` without a receipt
+    pub treasury: Option<TreasuryChange>,
+    /// The created outputs, the migrated funds of the receipt included
+    pub created: Vec<SnapshotOutput>,
+    /// The consumed outputs, with the id of the transaction consuming each
+    pub consumed: Vec<(SnapshotOutput, TransactionId)>,
+}
+
+// The bytes of a snapshot file with their offset.
+struct Source<R> {
+    reader: R,
+    offset: u64,
+    section: SnapshotSection,
+}
+
+impl<R: Read> Source<R> {
+    fn invalid(&self, offset: u64, reason: impl Into<String>) -> Error {
+        Error::InvalidSnapshot {
+            offset,
+            section: self.section,
+            reason: reason.into(),
+        }
+    }
+
+    fn fill(&mut self, buf: &mut [u8]) -> Result<()> {
+        let mut read = 0;
+        while read < buf.len() {
+            match self.reader.read(&mut buf[read..]) {
+                Ok(0) => return Err(self.invalid(self.offset + read as u64, "unexpected end of file")),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        self.offset += read as u64;
+        Ok(())
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        let mut buf = [0u8; 1];
+        self.fill(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let mut buf = [0u8; 2];
+        self.fill(&mut buf)?;
+        Ok(u16::from_le_bytes(buf))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let mut buf = [0u8; 4];
+        self.fill(&mut buf)?;
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        let mut buf = [0u8; 8];
+        self.fill(&mut buf)?;
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    fn hash(&mut self) -> Result<[u8; 32]> {
+        let mut buf = [0u8; 32];
+        self.fill(&mut buf)?;
+        Ok(buf)
+    }
+
+    // Skips bytes without keeping them.
+    fn skip(&mut self, mut length: u64) -> Result<()> {
+        let mut buf = [0u8; 64];
+        while length > 0 {
+            let n = length.min(buf.len() as u64) as usize;
+            self.fill(&mut buf[..n])?;
+            length -= n as u64;
+        }
+        Ok(())
+    }
+
+    fn amount(&mut self, what: &str) -> Result<u64> {
+        let offset = self.offset;
+        let amount = self.u64()?;
+        if amount > IOTA_SUPPLY {
+            return Err(self.invalid(offset, format!("invalid amount {} of {}", amount, what)));
+        }
+        Ok(amount)
+    }
+
+    fn treasury_output(&mut self) -> Result<SnapshotTreasuryOutput> {
+        let milestone_id = hex::encode(self.hash()?);
+        let amount = self.amount("the treasury output")?;
+        Ok(SnapshotTreasuryOutput { milestone_id, amount })
+    }
+
+    // Reads a length-prefixed milestone payload: its index, and the amount of the treasury output created by its
+    // receipt if it has one. The other fields are skipped.
+    fn milestone(&mut self) -> Result<(u32, Option<u64>)> {
+        let length = u64::from(self.u32()?);
+        let start = self.offset;
+        let payload_type = self.u32()?;
+        if payload_type != MILESTONE_PAYLOAD_TYPE {
+            return Err(self.invalid(start, format!("payload type {} instead of a milestone", payload_type)));
+        }
+        let milestone_index = self.u32()?;
+        // the timestamp
+        self.skip(8)?;
+        let parents = self.u8()?;
+        // the parents, the inclusion merkle proof, the next PoW score and its milestone index
+        self.skip(32 * u64::from(parents) + 32 + 4 + 4)?;
+        let public_keys = self.u8()?;
+        self.skip(32 * u64::from(public_keys))?;
+        let offset = self.offset;
+        let receipt_length = u64::from(self.u32()?);
+        let treasury_amount = if receipt_length == 0 {
+            None
+        } else {
+            // the type and the treasury output at least, within the milestone
+            if receipt_length < 13 || self.offset + receipt_length > start + length {
+                return Err(self.invalid(offset, format!("invalid receipt length {}", receipt_length)));
+            }
+            let offset = self.offset;
+            let receipt_type = self.u32()?;
+            if receipt_type != RECEIPT_PAYLOAD_TYPE {
+                return Err(self.invalid(offset, format!("payload type {} instead of a receipt", receipt_type)));
+            }
+            self.skip(receipt_length - 13)?;
+            let offset = self.offset;
+            let output_type = self.u8()?;
+            if output_type != TREASURY_OUTPUT_TYPE {
+                return Err(self.invalid(
+                    offset,
+                    format!("output type {} instead of a treasury output", output_type),
+                ));
+            }
+            Some(self.amount("the treasury output of the receipt")?)
+        };
+        let signatures = self.u8()?;
+        self.skip(64 * u64::from(signatures))?;
+        if self.offset - start != length {
+            return Err(self.invalid(
+                start - 4,
+                format!(
+                    "milestone payload of {} bytes instead of {}",
+                    self.offset - start,
+                    length
+                ),
+            ));
+        }
+        Ok((milestone_index, treasury_amount))
+    }
+
+    fn output(&mut self) -> Result<SnapshotOutput> {
+        let message_id = MessageId::new(self.hash()?);
+        let transaction_id = TransactionId::new(self.hash()?);
+        let offset = self.offset;
+        let output_index = self.u16()?;
+        let output_id = UTXOInput::new(transaction_id, output_index)
+            .map_err(|_| self.invalid(offset, format!("invalid output index {}", output_index)))?
+            .output_id()
+            .to_string();
+        let offset = self.offset;
+        let output_type = match self.u8()? {
+            0 => OutputType::SignatureLockedSingle,
+            1 => OutputType::SignatureLockedDustAllowance,
+            kind => return Err(self.invalid(offset, format!("unknown output type {}", kind))),
+        };
+        let offset = self.offset;
+        let address = match self.u8()? {
+            0 => Address::Ed25519(Ed25519Address::new(self.hash()?)),
+            kind => return Err(self.invalid(offset, format!("unknown address type {}", kind))),
+        };
+        let offset = self.offset;
+        let amount = self.u64()?;
+        if amount == 0 || amount > IOTA_SUPPLY {
+            return Err(self.invalid(offset, format!("invalid amount {} of output {}", amount, output_id)));
+        }
+        Ok(SnapshotOutput {
+            message_id,
+            output_id,
+            address,
+            amount,
+            output_type,
+        })
+    }
+}
+
+/// Streaming reader of a snapshot file. The sections are read in order: iterating a section skips the entries left in
+/// the sections before it, and a section already read yields nothing. An error ends the iteration.
+pub struct SnapshotReader<R> {
+    source: Source<R>,
+    header: SnapshotHeader,
+    // the entries left in the section of the source
+    remaining: u64,
+    // the index of the next milestone diff
+    next_milestone_index: u32,
+    failed: bool,
+}
+
+impl SnapshotReader<BufReader<File>> {
+    /// Opens a snapshot file and reads its header.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> SnapshotReader<R> {
+    /// Reads the header of a snapshot.
+    pub fn new(reader: R) -> Result<Self> {
+        let mut source = Source {
+            reader,
+            offset: 0,
+            section: SnapshotSection::Header,
+        };
+        let version = source.u8()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(source.invalid(0, format!("unsupported version {}", version)));
+        }
+        let kind = match source.u8()? {
+            0 => SnapshotKind::Full,
+            1 => SnapshotKind::Delta,
+            kind => return Err(source.invalid(KIND_OFFSET, format!("unknown snapshot kind {}", kind))),
+        };
+        let timestamp = source.u64()?;
+        let network_id = source.u64()?;
+        let sep_milestone_index = source.u32()?;
+        let ledger_milestone_index = source.u32()?;
+        let sep_count = source.u64()?;
+        let output_count = match kind {
+            SnapshotKind::Full => source.u64()?,
+            SnapshotKind::Delta => 0,
+        };
+        let offset = source.offset;
+        let milestone_diff_count = source.u64()?;
+        let treasury_output = match kind {
+            SnapshotKind::Full => Some(source.treasury_output()?),
+            SnapshotKind::Delta => None,
+        };
+        // the diffs of a full snapshot go back from its ledger, the ones of a delta go forward
+        let (expected, next_milestone_index) = match kind {
+            SnapshotKind::Full => (
+                ledger_milestone_index.checked_sub(sep_milestone_index),
+                ledger_milestone_index,
+            ),
+            SnapshotKind::Delta => (
+                sep_milestone_index.checked_sub(ledger_milestone_index),
+                ledger_milestone_index.saturating_add(1),
+            ),
+        };
+        if expected.map(u64::from) != Some(milestone_diff_count) {
+            return Err(source.invalid(
+                offset,
+                format!(
+                    "{} milestone diffs between the ledger milestone {} and the solid entry point milestone {}",
+                    milestone_diff_count, ledger_milestone_index, sep_milestone_index
+                ),
+            ));
+        }
+        source.section = SnapshotSection::SolidEntryPoints;
+        Ok(Self {
+            source,
+            header: SnapshotHeader {
+                version,
+                kind,
+                timestamp,
+                network_id,
+                sep_milestone_index,
+                ledger_milestone_index,
+                sep_count,
+                output_count,
+                milestone_diff_count,
+                treasury_output,
+            },
+            remaining: sep_count,
+            next_milestone_index,
+            failed: false,
+        })
+    }
+
+    /// Gets the header of the snapshot.
+    pub fn header(&self) -> &SnapshotHeader {
+        &self.header
+    }
+
+    /// Gets the offset of the next entry from the start of the file.
+    pub fn offset(&self) -> u64 {
+        self.source.offset
+    }
+
+    /// Reads the next solid entry point.
+    pub fn next_solid_entry_point(&mut self) -> Result<Option<MessageId>> {
+        self.next_entry(SnapshotSection::SolidEntryPoints, Self::solid_entry_point)
+    }
+
+    /// Reads the next output of the ledger of a full snapshot.
+    pub fn next_output(&mut self) -> Result<Option<SnapshotOutput>> {
+        self.next_entry(SnapshotSection::Outputs, |reader| reader.source.output())
+    }
+
+    /// Reads the next milestone diff, the last one is followed by the end of the file.
+    pub fn next_milestone_diff(&mut self) -> Result<Option<MilestoneDiff>> {
+        self.next_entry(SnapshotSection::MilestoneDiffs, Self::milestone_diff)
+    }
+
+    /// Iterates over the solid entry points.
+    pub fn solid_entry_points(&mut self) -> impl Iterator<Item = Result<MessageId>> + '_ {
+        std::iter::from_fn(move || self.next_solid_entry_point().transpose())
+    }
+
+    /// Iterates over the outputs of the ledger of a full snapshot.
+    pub fn outputs(&mut self) -> impl Iterator<Item = Result<SnapshotOutput>> + '_ {
+        std::iter::from_fn(move || self.next_output().transpose())
+    }
+
+    /// Iterates over the milestone diffs.
+    pub fn milestone_diffs(&mut self) -> impl Iterator<Item = Result<MilestoneDiff>> + '_ {
+        std::iter::from_fn(move || self.next_milestone_diff().transpose())
+    }
+
+    fn next_entry<T, F>(&mut self, section: SnapshotSection, read: F) -> Result<Option<T>>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        if self.failed {
+            return Ok(None);
+        }
+        let result = self.enter(section).and_then(|left| {
+            if !left {
+                return Ok(None);
+            }
+            let entry = read(self)?;
+            self.remaining -= 1;
+            self.check_end()?;
+            Ok(Some(entry))
+        });
+        if result.is_err() {
+            self.failed = true;
+        }
+        result
+    }
+
+    // Skips the entries of the sections before the section, `true` if an entry of the section is left.
+    fn enter(&mut self, section: SnapshotSection) -> Result<bool> {
+        while self.source.section < section {
+            if self.remaining > 0 {
+                match self.source.section {
+                    SnapshotSection::SolidEntryPoints => {
+                        self.solid_entry_point()?;
+                    }
+                    _ => {
+                        self.source.output()?;
+                    }
+                }
+                self.remaining -= 1;
+                continue;
+            }
+            let (next, remaining) = match self.source.section {
+                SnapshotSection::SolidEntryPoints => (SnapshotSection::Outputs, self.header.output_count),
+                _ => (SnapshotSection::MilestoneDiffs, self.header.milestone_diff_count),
+            };
+            self.source.section = next;
+            self.remaining = remaining;
+            self.check_end()?;
+        }
+        Ok(self.source.section == section && self.remaining > 0)
+    }
+
+    // The file ends with the last milestone diff.
+    fn check_end(&mut self) -> Result<()> {
+        if self.source.section != SnapshotSection::MilestoneDiffs || self.remaining > 0 {
+            return Ok(());
+        }
+        let offset = self.source.offset;
+        let mut buf = [0u8; 1];
+        loop {
+            match self.source.reader.read(&mut buf) {
+                Ok(0) => return Ok(()),
+                Ok(_) => return Err(self.source.invalid(offset, "unexpected data after the milestone diffs")),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    fn solid_entry_point(&mut self) -> Result<MessageId> {
+        Ok(MessageId::new(self.source.hash()?))
+    }
+
+    fn milestone_diff(&mut self) -> Result<MilestoneDiff> {
+        let offset = self.source.offset;
+        let (milestone_index, treasury_amount) = self.source.milestone()?;
+        if milestone_index != self.next_milestone_index {
+            return Err(self.source.invalid(
+                offset,
+                format!(
+                    "milestone diff {} instead of {}",
+                    milestone_index, self.next_milestone_index
+                ),
+            ));
+        }
+        let treasury = match treasury_amount {
+            Some(amount) => Some(TreasuryChange {
+                spent: self.source.treasury_output()?,
+                amount,
+            }),
+            None => None,
+        };
+        // the counts aren't trusted to allocate the outputs
+        let mut created = Vec::new();
+        for _ in 0..self.source.u64()? {
+            created.push(self.source.output()?);
+        }
+        let mut consumed = Vec::new();
+        for _ in 0..self.source.u64()? {
+            let output = self.source.output()?;
+            consumed.push((output, TransactionId::new(self.source.hash()?)));
+        }
+        self.next_milestone_index = match self.header.kind {
+            SnapshotKind::Full => milestone_index.wrapping_sub(1),
+            SnapshotKind::Delta => milestone_index.wrapping_add(1),
+        };
+        Ok(MilestoneDiff {
+            milestone_index,
+            treasury,
+            created,
+            consumed,
+        })
+    }
+}
+
+/// The balances of addresses at the solid entry point milestone of a snapshot, see [`balance_of`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotBalances {
+    network_id: u64,
+    milestone_index: u32,
+    balances: HashMap<Address, u64>,
+}
+
+impl SnapshotBalances {
+    /// Gets the network id of the snapshots.
+    pub fn network_id(&self) -> u64 {
+        self.network_id
+    }
+
+    /// Gets the milestone of the balances, the solid entry point milestone of the last applied snapshot.
+    pub fn milestone_index(&self) -> u32 {
+        self.milestone_index
+    }
+
+    /// Gets the balance of an address, `None` for an address the balances weren't computed for.
+    pub fn balance(&self, address: &Address) -> Option<u64> {
+        self.balances.get(address).copied()
+    }
+
+    /// Gets the balances of the addresses.
+    pub fn balances(&self) -> &HashMap<Address, u64> {
+        &self.balances
+    }
+
+    /// Applies the milestone diffs of a delta snapshot, taken after the full snapshot of the balances.
+    pub fn apply_delta<P: AsRef<Path>>(&mut self, delta_path: P) -> Result<()> {
+        let mut reader = SnapshotReader::open(delta_path)?;
+        let header = reader.header().clone();
+        if header.kind != SnapshotKind::Delta {
+            return Err(invalid_header(
+                KIND_OFFSET,
+                "a full snapshot, a delta snapshot is expected",
+            ));
+        }
+        if header.network_id != self.network_id {
+            return Err(invalid_header(
+                NETWORK_ID_OFFSET,
+                format!(
+                    "the network id {} differs from the network id {} of the balances",
+                    header.network_id, self.network_id
+                ),
+            ));
+        }
+        if header.ledger_milestone_index != self.milestone_index {
+            return Err(invalid_header(
+                LEDGER_MILESTONE_INDEX_OFFSET,
+                format!(
+                    "the delta snapshot starts at milestone {}, the balances are at milestone {}",
+                    header.ledger_milestone_index, self.milestone_index
+                ),
+            ));
+        }
+        for solid_entry_point in reader.solid_entry_points() {
+            solid_entry_point?;
+        }
+        let mut offset = reader.offset();
+        while let Some(diff) = reader.next_milestone_diff()? {
+            let position = (offset, SnapshotSection::MilestoneDiffs);
+            for output in &diff.created {
+                self.add(output, position)?;
+            }
+            for (output, _) in &diff.consumed {
+                self.remove(output, position)?;
+            }
+            offset = reader.offset();
+        }
+        self.milestone_index = header.sep_milestone_index;
+        Ok(())
+    }
+
+    // The position is the offset and the section of the output, or of its milestone diff.
+    fn add(&mut self, output: &SnapshotOutput, position: (u64, SnapshotSection)) -> Result<()> {
+        if let Some(balance) = self.balances.get_mut(&output.address) {
+            *balance = balance
+                .checked_add(output.amount)
+                .filter(|balance| *balance <= IOTA_SUPPLY)
+                .ok_or_else(|| inconsistent_balance(output, position))?;
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, output: &SnapshotOutput, position: (u64, SnapshotSection)) -> Result<()> {
+        if let Some(balance) = self.balances.get_mut(&output.address) {
+            *balance = balance
+                .checked_sub(output.amount)
+                .ok_or_else(|| inconsistent_balance(output, position))?;
+        }
+        Ok(())
+    }
+}
+
+fn invalid_header(offset: u64, reason: impl Into<String>) -> Error {
+    Error::InvalidSnapshot {
+        offset,
+        section: SnapshotSection::Header,
+        reason: reason.into(),
+    }
+}
+
+// The amounts of the outputs are within the supply, a balance out of it means the ledger and the diffs don't match.
+fn inconsistent_balance(output: &SnapshotOutput, (offset, section): (u64, SnapshotSection)) -> Error {
+    Error::InvalidSnapshot {
+        offset,
+        section,
+        reason: format!(
+            "output {} takes the balance of its address out of the supply",
+            output.output_id
+        ),
+    }
+}
+
+/// Computes the balances of addresses at the solid entry point milestone of a full snapshot: the outputs of the ledger
+/// are summed, then the milestone diffs rolled back. A delta snapshot is applied on top with
+/// [`SnapshotBalances::apply_delta`].
+pub fn balance_of<P: AsRef<Path>>(addresses: &[Address], snapshot_path: P) -> Result<SnapshotBalances> {
+    let mut reader = SnapshotReader::open(snapshot_path)?;
+    let header = reader.header().clone();
+    if header.kind != SnapshotKind::Full {
+        return Err(invalid_header(
+            KIND_OFFSET,
+            "a delta snapshot, a full snapshot is expected",
+        ));
+    }
+    let mut balances = SnapshotBalances {
+        network_id: header.network_id,
+        milestone_index: header.sep_milestone_index,
+        balances: addresses.iter().map(|address| (address.clone(), 0)).collect(),
+    };
+    // the offsets of the entries are the ones after the solid entry points
+    for solid_entry_point in reader.solid_entry_points() {
+        solid_entry_point?;
+    }
+    let mut offset = reader.offset();
+    while let Some(output) = reader.next_output()? {
+        balances.add(&output, (offset, SnapshotSection::Outputs))?;
+        offset = reader.offset();
+    }
+    // the diffs go back from the ledger milestone
+    while let Some(diff) = reader.next_milestone_diff()? {
+        let position = (offset, SnapshotSection::MilestoneDiffs);
+        for output in &diff.created {
+            balances.remove(output, position)?;
+        }
+        for (output, _) in &diff.consumed {
+            balances.add(output, position)?;
+        }
+        offset = reader.offset();
+    }
+    Ok(balances)
+}
+
+/// Checks the amounts of a snapshot: the outputs and the treasury output of a full snapshot sum up to the
+/// [`IOTA_SUPPLY`], the header doesn't hold the total, and each milestone diff creates as much as it consumes, the
+/// treasury outputs of its receipt included. Fails with [`Error::SnapshotSupplyMismatch`] otherwise.
+pub fn check_supply<P: AsRef<Path>>(snapshot_path: P) -> Result<()> {
+    let mut reader = SnapshotReader::open(snapshot_path)?;
+    if let Some(treasury_output) = reader.header().treasury_output.clone() {
+        let mut supply = u128::from(treasury_output.amount);
+        for output in reader.outputs() {
+            supply += u128::from(output?.amount);
+        }
+        if supply != u128::from(IOTA_SUPPLY) {
+            return Err(Error::SnapshotSupplyMismatch(
+                "outputs and treasury".to_string(),
+                supply,
+                u128::from(IOTA_SUPPLY),
+            ));
+        }
+    }
+    for diff in reader.milestone_diffs() {
+        let diff = diff?;
+        let mut created: u128 = diff.created.iter().map(|output| u128::from(output.amount)).sum();
+        let mut consumed: u128 = diff.consumed.iter().map(|(output, _)| u128::from(output.amount)).sum();
+        // the migrated funds of a receipt come from the treasury
+        if let Some(treasury) = &diff.treasury {
+            created += u128::from(treasury.amount);
+            consumed += u128::from(treasury.spent.amount);
+        }
+        if created != consumed {
+            return Err(Error::SnapshotSupplyMismatch(
+                format!("outputs created by milestone {}", diff.milestone_index),
+                created,
+                consumed,
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,235 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Storage of the client state across restarts: the discovered address indexes, the known spent addresses, the
+//! node infos and the submitted messages not confirmed yet
+
+use crate::{Error, NodeInfo, Result};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+/// The future of a [`Storage`] operation.
+pub type StorageFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// A key-value storage of the client state, set with
+/// [`ClientBuilder::with_storage`](crate::ClientBuilder::with_storage). The keys are grouped in namespaces and the
+/// client stores JSON values.
+///
+/// A failing operation returns an error, reported as [`Error::Storage`] by the built-in storages.
+pub trait Storage: Send + Sync {
+    /// The value of the key, `None` if it isn't stored.
+    fn get<'a>(&'a self, namespace: &'a str, key: &'a str) -> StorageFuture<'a, Option<Vec<u8>>>;
+
+    /// Stores the value of the key, replacing the previous one.
+    fn set<'a>(&'a self, namespace: &'a str, key: &'a str, value: Vec<u8>) -> StorageFuture<'a, ()>;
+
+    /// Deletes the key, deleting a key that isn't stored succeeds.
+    fn delete<'a>(&'a self, namespace: &'a str, key: &'a str) -> StorageFuture<'a, ()>;
+}
+
+impl<S: Storage + ?Sized> Storage for Arc<S> {
+    fn get<'a>(&'a self, namespace: &'a str, key: &'a str) -> StorageFuture<'a, Option<Vec<u8>>> {
+        (**self).get(namespace, key)
+    }
+
+    fn set<'a>(&'a self, namespace: &'a str, key: &'a str, value: Vec<u8>) -> StorageFuture<'a, ()> {
+        (**self).set(namespace, key, value)
+    }
+
+    fn delete<'a>(&'a self, namespace: &'a str, key: &'a str) -> StorageFuture<'a, ()> {
+        (**self).delete(namespace, key)
+    }
+}
+
+/// The storage of the client by default, the state is kept for the lifetime of the process. Share it between clients
+/// with an `Arc`.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    entries: Mutex<HashMap<(String, String), Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    /// An empty storage.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get<'a>(&'a self, namespace: &'a str, key: &'a str) -> StorageFuture<'a, Option<Vec<u8>>> {
+        let value = self
+            .entries
+            .lock()
+            .unwrap()
+            .get(&(namespace.to_string(), key.to_string()))
+            .cloned();
+        Box::pin(async move { Ok(value) })
+    }
+
+    fn set<'a>(&'a self, namespace: &'a str, key: &'a str, value: Vec<u8>) -> StorageFuture<'a, ()> {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert((namespace.to_string(), key.to_string()), value);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn delete<'a>(&'a self, namespace: &'a str, key: &'a str) -> StorageFuture<'a, ()> {
+        self.entries
+            .lock()
+            .unwrap()
+            .remove(&(namespace.to_string(), key.to_string()));
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// A storage in a directory, set with
+/// [`ClientBuilder::with_storage_path`](crate::ClientBuilder::with_storage_path): each namespace is a subdirectory
+/// holding a file per key, named with the hex encoded key. A value is written to a temporary file first, so an
+/// interrupted write keeps the previous value.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    path: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStorage {
+    /// The storage in the directory at `path`, created if it doesn't exist.
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        std::fs::create_dir_all(&path).map_err(|e| storage_error(&path, e))?;
+        Ok(Self { path })
+    }
+
+    fn file(&self, namespace: &str, key: &str) -> PathBuf {
+        self.path.join(hex::encode(namespace)).join(hex::encode(key))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn storage_error(path: &Path, error: std::io::Error) -> Error {
+    Error::Storage(format!("{}: {}", path.display(), error))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for FileStorage {
+    fn get<'a>(&'a self, namespace: &'a str, key: &'a str) -> StorageFuture<'a, Option<Vec<u8>>> {
+        Box::pin(async move {
+            let file = self.file(namespace, key);
+            match std::fs::read(&file) {
+                Ok(value) => Ok(Some(value)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(storage_error(&file, e)),
+            }
+        })
+    }
+
+    fn set<'a>(&'a self, namespace: &'a str, key: &'a str, value: Vec<u8>) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            let file = self.file(namespace, key);
+            let directory = file.parent().expect("the file of a key is in its namespace directory");
+            std::fs::create_dir_all(directory).map_err(|e| storage_error(directory, e))?;
+            let temporary = file.with_extension("tmp");
+            std::fs::write(&temporary, value).map_err(|e| storage_error(&temporary, e))?;
+            std::fs::rename(&temporary, &file).map_err(|e| storage_error(&file, e))
+        })
+    }
+
+    fn delete<'a>(&'a self, namespace: &'a str, key: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            let file = self.file(namespace, key);
+            match std::fs::remove_file(&file) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(storage_error(&file, e)),
+                _ => Ok(()),
+            }
+        })
+    }
+}
+
+/// The (namespace, key) of a part of the client state.
+pub(crate) type StateKey = (&'static str, &'static str);
+
+/// The first unused public address index of each account, by the address at the index 0 of the account and the
+/// account index.
+pub(crate) const UNUSED_ADDRESS_INDEXES: StateKey = ("addresses", "unusedIndexes");
+/// The bech32 addresses known to have spent an output.
+pub(crate) const SPENT_ADDRESSES: StateKey = ("addresses", "spent");
+/// The info of each node with the time it was fetched.
+pub(crate) const NODE_INFOS: StateKey = ("nodes", "infos");
+/// The ids of the submitted messages not referenced by a milestone yet.
+pub(crate) const PENDING_MESSAGES: StateKey = ("messages", "pending");
+
+/// A stored node info, with the unix time in seconds it was fetched.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct StoredNodeInfo {
+    #[serde(rename = "fetchedAt")]
+    pub(crate) fetched_at: u64,
+    pub(crate) info: NodeInfo,
+}
+
+// The network id of the stored state.
+const NETWORK_ID: StateKey = ("client", "networkId");
+// Every part of the state, deleted when the network id changes.
+const STATE: [StateKey; 4] = [UNUSED_ADDRESS_INDEXES, SPENT_ADDRESSES, NODE_INFOS, PENDING_MESSAGES];
+
+/// The client state in its storage. The state belongs to a network: the state of another network id is deleted
+/// before it's read or updated.
+pub(crate) struct ClientStorage {
+    storage: Arc<dyn Storage>,
+    // serializes the updates, which read the state then write it again
+    lock: tokio::sync::Mutex<()>,
+}
+
+impl ClientStorage {
+    pub(crate) fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// The state of the key for the network, the default if nothing is stored.
+    pub(crate) async fn load<T: DeserializeOwned + Default>(&self, network_id: &str, key: StateKey) -> Result<T> {
+        let _guard = self.lock.lock().await;
+        self.load_unlocked(network_id, key).await
+    }
+
+    /// Updates the state of the key for the network.
+    pub(crate) async fn update<T, F>(&self, network_id: &str, key: StateKey, update: F) -> Result<()>
+    where
+        T: Serialize + DeserializeOwned + Default,
+        F: FnOnce(&mut T),
+    {
+        let _guard = self.lock.lock().await;
+        let mut state = self.load_unlocked(network_id, key).await?;
+        update(&mut state);
+        self.storage.set(key.0, key.1, serde_json::to_vec(&state)?).await
+    }
+
+    async fn load_unlocked<T: DeserializeOwned + Default>(&self, network_id: &str, key: StateKey) -> Result<T> {
+        let stored_network_id = self.storage.get(NETWORK_ID.0, NETWORK_ID.1).await?;
+        if stored_network_id.as_deref() != Some(network_id.as_bytes()) {
+            for (namespace, key) in &STATE {
+                self.storage.delete(namespace, key).await?;
+            }
+            self.storage
+                .set(NETWORK_ID.0, NETWORK_ID.1, network_id.as_bytes().to_vec())
+                .await?;
+            return Ok(T::default());
+        }
+        match self.storage.get(key.0, key.1).await? {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(T::default()),
+        }
+    }
+}
@@ -9,7 +9,7 @@ use bee_message::{
     prelude::*,
 };
 use bee_pow::providers::{Constant, ConstantBuilder, ProviderBuilder as PowProviderBuilder};
-use serde::ser::Serializer;
+use serde::{de::Deserializer, ser::Serializer};
 
 use std::{
     convert::{From, TryFrom, TryInto},
@@ -54,7 +54,7 @@ pub trait ResponseType {}
 impl ResponseType for Message {}
 
 /// Response from the Iota node.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Response<T: ResponseType> {
     pub(crate) data: T,
 }
@@ -67,7 +67,7 @@ impl<T: ResponseType> Response<T> {
 }
 
 /// Response of GET /api/v1/info endpoint
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct NodeInfo {
     /// Iota node Name
     pub name: String,
@@ -88,17 +88,120 @@ pub struct NodeInfo {
     /// solid milestone index
     #[serde(rename = "solidMilestoneIndex")]
     pub solid_milestone_index: usize,
+    /// Unix timestamp of the latest milestone, in seconds, not reported by older nodes
+    #[serde(rename = "latestMilestoneTimestamp", default)]
+    pub latest_milestone_timestamp: Option<u64>,
     /// pruning index
     #[serde(rename = "pruningIndex")]
     pub pruning_index: usize,
+    /// Bech32 human readable part of the network addresses, not reported by older nodes
+    #[serde(rename = "bech32HRP", default)]
+    pub bech32_hrp: Option<String>,
     /// features
     pub features: Vec<String>,
 }
 
 impl ResponseType for NodeInfo {}
 
+/// The sync status of a client node, as of the last check of the node syncing
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NodeSyncStatus {
+    /// The node URL
+    pub url: String,
+    /// Whether the node is in the synced node pool
+    pub synced: bool,
+    /// The latest milestone index reported by the node, `None` if it didn't answer or was never checked
+    #[serde(rename = "latestMilestoneIndex")]
+    pub latest_milestone_index: Option<usize>,
+    /// The solid milestone index reported by the node, `None` if it didn't answer or was never checked
+    #[serde(rename = "solidMilestoneIndex")]
+    pub solid_milestone_index: Option<usize>,
+    /// Unix timestamp of the last check, in milliseconds, `None` with the node syncing disabled
+    #[serde(rename = "lastCheck")]
+    pub last_check: Option<u64>,
+}
+
+/// Relation of a peer with the node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PeerRelation {
+    /// A peer added in the node configuration
+    #[serde(rename = "known")]
+    Known,
+    /// A peer that connected to the node without being configured
+    #[serde(rename = "unknown")]
+    Unknown,
+    /// A peer found by the autopeering
+    #[serde(rename = "autopeered")]
+    Autopeered,
+}
+
+/// Gossip metrics of a peer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GossipMetrics {
+    /// Number of new messages received from the peer
+    #[serde(rename = "newMessages")]
+    pub new_messages: u64,
+    /// Number of already known messages received from the peer
+    #[serde(rename = "knownMessages")]
+    pub known_messages: u64,
+    /// Number of messages received from the peer
+    #[serde(rename = "receivedMessages")]
+    pub received_messages: u64,
+    /// Number of message requests received from the peer
+    #[serde(rename = "receivedMessageRequests")]
+    pub received_message_requests: u64,
+    /// Number of milestone requests received from the peer
+    #[serde(rename = "receivedMilestoneRequests")]
+    pub received_milestone_requests: u64,
+    /// Number of heartbeats received from the peer
+    #[serde(rename = "receivedHeartbeats")]
+    pub received_heartbeats: u64,
+    /// Number of messages sent to the peer
+    #[serde(rename = "sentMessages")]
+    pub sent_messages: u64,
+    /// Number of message requests sent to the peer
+    #[serde(rename = "sentMessageRequests")]
+    pub sent_message_requests: u64,
+    /// Number of milestone requests sent to the peer
+    #[serde(rename = "sentMilestoneRequests")]
+    pub sent_milestone_requests: u64,
+    /// Number of heartbeats sent to the peer
+    #[serde(rename = "sentHeartbeats")]
+    pub sent_heartbeats: u64,
+    /// Number of packets dropped for the peer
+    #[serde(rename = "droppedPackets")]
+    pub dropped_packets: u64,
+}
+
+/// Gossip state of a connected peer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Gossip {
+    /// Gossip metrics
+    pub metrics: GossipMetrics,
+}
+
+/// Peer of the node returned by GET /api/v1/peers endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerInfo {
+    /// Peer identity
+    pub id: String,
+    /// Multi-addresses of the peer
+    #[serde(rename = "multiAddresses")]
+    pub multi_addresses: Vec<String>,
+    /// Alias of the peer in the node configuration
+    pub alias: Option<String>,
+    /// Relation of the peer with the node
+    pub relation: PeerRelation,
+    /// Whether the node is connected to the peer
+    pub connected: bool,
+    /// Gossip state, only set if the peer is connected
+    pub gossip: Option<Gossip>,
+}
+
+impl ResponseType for Vec<PeerInfo> {}
+
 /// Response of GET /api/v1/tips endpoint
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct Tips {
     /// Message ID of tip 1
     #[serde(rename = "tip1MessageId")]
@@ -110,7 +213,7 @@ pub(crate) struct Tips {
 
 impl ResponseType for Tips {}
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct PostMessageId {
     #[serde(rename = "messageId")]
     pub(crate) message_id: String,
@@ -119,7 +222,7 @@ pub(crate) struct PostMessageId {
 impl ResponseType for PostMessageId {}
 
 /// Collection of meesage ID
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct MessageIds {
     #[serde(rename = "messageIds")]
     pub(crate) inner: Box<[String]>,
@@ -128,7 +231,7 @@ pub(crate) struct MessageIds {
 impl ResponseType for MessageIds {}
 
 /// Response of GET /api/v1/messages/{messageId} endpoint
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MessageMetadata {
     /// Message ID
     #[serde(rename = "messageId")]
@@ -151,14 +254,62 @@ pub struct MessageMetadata {
     /// Referenced by milestone index
     #[serde(rename = "referencedByMilestoneIndex")]
     pub referenced_by_milestone_index: Option<u64>,
-    /// Ledger inclusion state
+    /// Ledger inclusion state, set once the message is referenced by a milestone
     #[serde(rename = "ledgerInclusionState")]
-    pub ledger_inclusion_state: Option<String>,
+    pub ledger_inclusion_state: Option<LedgerInclusionState>,
 }
 
 impl ResponseType for MessageMetadata {}
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Ledger inclusion state of a message referenced by a milestone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LedgerInclusionState {
+    /// The transaction of the message was applied to the ledger
+    Included,
+    /// The transaction of the message conflicts with the ledger and was ignored
+    Conflicting,
+    /// The message doesn't hold a transaction
+    NoTransaction,
+}
+
+impl fmt::Display for LedgerInclusionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self {
+            Self::Included => "included",
+            Self::Conflicting => "conflicting",
+            Self::NoTransaction => "noTransaction",
+        };
+        write!(f, "{}", state)
+    }
+}
+
+/// Confirmation of a message, reported once a milestone references it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageConfirmation {
+    /// Message ID
+    #[serde(rename = "messageId")]
+    pub message_id: String,
+    /// Index of the milestone referencing the message
+    #[serde(rename = "referencedByMilestoneIndex")]
+    pub referenced_by_milestone_index: u64,
+    /// Ledger inclusion state
+    #[serde(rename = "ledgerInclusionState")]
+    pub ledger_inclusion_state: Option<LedgerInclusionState>,
+}
+
+/// Inclusion state of a message retried until included
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageInclusionState {
+    /// Message ID
+    #[serde(rename = "messageId")]
+    pub message_id: String,
+    /// Ledger inclusion state, `None` while no milestone references the message
+    #[serde(rename = "inclusionState")]
+    pub inclusion_state: Option<LedgerInclusionState>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct ChildrenMessageIds {
     #[serde(rename = "childrenMessageIds")]
     pub(crate) inner: Box<[String]>,
@@ -166,16 +317,18 @@ pub(crate) struct ChildrenMessageIds {
 
 impl ResponseType for ChildrenMessageIds {}
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct AddressBalance {
     pub(crate) count: usize,
     pub(crate) balance: u64,
+    #[serde(rename = "dustAllowed", default)]
+    pub(crate) dust_allowed: bool,
 }
 
 impl ResponseType for AddressBalance {}
 
 /// Output raw data
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct RawOutput {
     #[serde(rename = "messageId")]
     pub(crate) message_id: String,
@@ -190,7 +343,7 @@ pub(crate) struct RawOutput {
 
 impl ResponseType for RawOutput {}
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct SLS {
     #[serde(rename = "type")]
     pub(crate) type_: u8,
@@ -198,7 +351,7 @@ pub(crate) struct SLS {
     pub(crate) amount: u64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct SLSAddress {
     #[serde(rename = "type")]
     pub(crate) type_: u8,
@@ -212,14 +365,30 @@ where
     s.serialize_str(hex::encode(x).as_str())
 }
 
+fn deserialize_from_hex<'de, D>(d: D) -> std::result::Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = <String as serde::Deserialize<'de>>::deserialize(d)?;
+    hex::decode(s).map_err(serde::de::Error::custom)
+}
+
 /// Output data
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct OutputMetadata {
     /// Message ID of the output
-    #[serde(rename = "messageId", serialize_with = "serialize_as_hex")]
+    #[serde(
+        rename = "messageId",
+        serialize_with = "serialize_as_hex",
+        deserialize_with = "deserialize_from_hex"
+    )]
     pub message_id: Vec<u8>,
     /// Transaction ID of the output
-    #[serde(rename = "transactionId", serialize_with = "serialize_as_hex")]
+    #[serde(
+        rename = "transactionId",
+        serialize_with = "serialize_as_hex",
+        deserialize_with = "deserialize_from_hex"
+    )]
     pub transaction_id: Vec<u8>,
     /// Output index.
     #[serde(rename = "outputIndex")]
@@ -233,18 +402,85 @@ pub struct OutputMetadata {
     pub amount: u64,
 }
 
+/// A lookup of [`crate::Client::find_outputs_settled`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputLookup {
+    /// The lookup of an output by its id.
+    Output(UTXOInput),
+    /// The lookup of the outputs of an address.
+    Address(Bech32Address),
+}
+
 /// Outputs that use a given address.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AddressOutputs {
     /// Outputs used by the address.
     #[serde(rename = "outputIds")]
     pub output_ids: Box<[String]>,
+    /// The number of returned outputs.
+    #[serde(default)]
+    pub count: usize,
+    /// The maximum number of outputs returned by the node, the list may be truncated if `count` reaches it.
+    #[serde(rename = "maxResults", default)]
+    pub max_results: usize,
 }
 
 impl ResponseType for AddressOutputs {}
 
+impl AddressOutputs {
+    /// Parses the output ids, the hex-encoded transaction id followed by the little endian output index.
+    pub fn utxo_inputs(&self) -> Result<Box<[UTXOInput]>> {
+        self.output_ids
+            .iter()
+            .map(|s| {
+                let mut transaction_id = [0u8; 32];
+                hex::decode_to_slice(&s[..64], &mut transaction_id)?;
+                let index = u16::from_le_bytes(
+                    hex::decode(&s[64..]).map_err(|_| crate::Error::InvalidParameter("index".to_string()))?[..]
+                        .try_into()
+                        .map_err(|_| crate::Error::InvalidParameter("index".to_string()))?,
+                );
+                Ok(UTXOInput::new(TransactionId::new(transaction_id), index)?)
+            })
+            .collect::<Result<Box<[UTXOInput]>>>()
+    }
+
+    /// Whether the node capped the number of returned outputs.
+    pub fn is_truncated(&self) -> bool {
+        self.max_results > 0 && self.count >= self.max_results
+    }
+}
+
+/// The output types the address outputs can be filtered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutputType {
+    /// Signature locked single output.
+    SignatureLockedSingle,
+    /// Signature locked dust allowance output.
+    SignatureLockedDustAllowance,
+}
+
+impl OutputType {
+    /// The type of the output in the node API.
+    pub fn kind(&self) -> u8 {
+        match self {
+            Self::SignatureLockedSingle => 0,
+            Self::SignatureLockedDustAllowance => 1,
+        }
+    }
+}
+
+/// Filters of the address outputs request.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutputsOptions {
+    /// Also returns the spent outputs.
+    pub include_spent: bool,
+    /// Only returns the outputs of the given type.
+    pub output_type: Option<OutputType>,
+}
+
 /// Milestone from Iota node
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MilestoneMetadata {
     /// Milestone index
     #[serde(rename = "index")]
@@ -258,17 +494,56 @@ pub struct MilestoneMetadata {
 
 impl ResponseType for MilestoneMetadata {}
 
+/// The outputs created and consumed by a milestone.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MilestoneUtxoChanges {
+    /// Milestone index
+    pub index: u64,
+    /// The ids of the outputs created by the milestone
+    #[serde(rename = "createdOutputs")]
+    pub created_outputs: Vec<String>,
+    /// The ids of the outputs consumed by the milestone
+    #[serde(rename = "consumedOutputs")]
+    pub consumed_outputs: Vec<String>,
+}
+
+impl ResponseType for MilestoneUtxoChanges {}
+
 /// Address and the coresponding balance returned by the get_address_balances() API.
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AddressBalancePair {
     /// Address
     pub address: Bech32Address,
     /// Balance in the address
     pub balance: u64,
+    /// Whether the address accepts dust outputs
+    pub dust_allowed: bool,
+}
+
+/// The balance of an account split by the ledger inclusion state of the messages creating its outputs, returned by
+/// [`GetBalanceBuilder::finish_split`](crate::api::GetBalanceBuilder::finish_split).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceSplit {
+    /// The amount of the outputs whose message is included in the ledger.
+    pub confirmed: u64,
+    /// The amount of the outputs whose message isn't included yet.
+    pub pending: u64,
+}
+
+/// The balance of an address once it reached the minimum amount of
+/// [`Client::wait_for_balance`](crate::Client::wait_for_balance).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReachedBalance {
+    /// Address
+    pub address: Bech32Address,
+    /// The confirmed balance of the address
+    pub balance: u64,
+    /// The unspent outputs of the address holding the balance
+    pub outputs: Vec<OutputMetadata>,
 }
 
 /// JSON struct for Message
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MessageJson {
     #[serde(rename = "networkId")]
     network_id: String,
@@ -278,7 +553,7 @@ pub struct MessageJson {
     parent2: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     payload: Option<PayloadJson>,
-    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(skip_serializing_if = "String::is_empty", default)]
     nonce: String,
 }
 
@@ -339,7 +614,7 @@ impl TryFrom<MessageJson> for Message {
 }
 
 /// The JSON representation of the transaction payload.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TransactionPayloadJson {
     #[serde(rename = "type")]
     type_: u8,
@@ -349,7 +624,7 @@ pub struct TransactionPayloadJson {
 }
 
 /// The JSON representation of the milestone payload.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MilestonePayloadJson {
     #[serde(rename = "type")]
     type_: u8,
@@ -361,7 +636,7 @@ pub struct MilestonePayloadJson {
 }
 
 /// The JSON representation of the indexation payload.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct IndexationPayloadJson {
     #[serde(rename = "type")]
     type_: u8,
@@ -370,7 +645,7 @@ pub struct IndexationPayloadJson {
 }
 
 /// Each of the possible payload types.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum PayloadJson {
     /// The transaction payload.
@@ -460,7 +735,7 @@ fn get_payload_from_json(
 }
 
 /// JSON struct for TransactionPayloadEssence
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TransactionPayloadEssenceJson {
     #[serde(rename = "type")]
     type_: u8,
@@ -530,7 +805,7 @@ impl TryFrom<TransactionPayloadEssenceJson> for TransactionPayloadEssence {
 }
 
 /// JSON struct for Input
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct InputJson {
     #[serde(rename = "type")]
     type_: u8,
@@ -565,7 +840,7 @@ impl TryFrom<InputJson> for Input {
 }
 
 /// JSON struct for Output
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct OutputJson {
     #[serde(rename = "type")]
     type_: u8,
@@ -596,7 +871,7 @@ impl TryFrom<OutputJson> for Output {
 }
 
 /// JSON struct for Address, hex encoded
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AddressJson {
     #[serde(rename = "type")]
     type_: u8,
@@ -632,7 +907,7 @@ impl TryFrom<AddressJson> for Address {
 }
 
 /// JSON struct for UnlockBlock
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct UnlockBlockJson {
     #[serde(rename = "type")]
     type_: u8,
@@ -679,7 +954,7 @@ impl TryFrom<UnlockBlockJson> for UnlockBlock {
 }
 
 /// JSON struct for SignatureUnlock
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SignatureUnlockJson {
     #[serde(rename = "type")]
     type_: u8,
@@ -0,0 +1,66 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota_client::{Error, Seed};
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+
+fn offline_client() -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node("http://localhost:14265")
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+#[test]
+fn test_reference_addresses() {
+    let seed = Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap();
+    let addresses = offline_client()
+        .find_addresses(&seed)
+        .with_account_index(0)
+        .with_range(0..2)
+        .get_all()
+        .unwrap()
+        .into_iter()
+        .map(|(address, internal)| (address.to_string(), internal))
+        .collect::<Vec<(String, bool)>>();
+    assert_eq!(
+        addresses,
+        vec![
+            (
+                "iot1qxt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupxgecea4".to_string(),
+                false
+            ),
+            (
+                "iot1q9rxpfvaz2peggq6f8k9cj8zfsxuw69e4nszjyv5kuf8yt70t2847f28d0q".to_string(),
+                true
+            ),
+            (
+                "iot1q9nrumvaex24dy0duulp4q07lpa00w20ze6jfd0xly422kdcjxzakmdytyl".to_string(),
+                false
+            ),
+            (
+                "iot1qypvxmuyq97gtl98jfs4tdtwkvh4qjj0slng5jcg4x7vs3muds74y5sdql9".to_string(),
+                true
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_invalid_ranges() {
+    let seed = Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap();
+    let client = offline_client();
+    #[allow(clippy::reversed_empty_ranges)]
+    let ranges = vec![5..2, 0..100_000, (1 << 31)..(1 << 31) + 1];
+    for range in ranges {
+        let result = client
+            .find_addresses(&seed)
+            .with_account_index(0)
+            .with_range(range)
+            .get_all();
+        assert!(matches!(result, Err(Error::InvalidParameter(_))));
+    }
+}
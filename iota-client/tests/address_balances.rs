@@ -0,0 +1,92 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Bulk address balance tests against a mock node counting the balance requests.
+
+mod common;
+
+use common::start_mock_node;
+use iota_client::{Bech32Address, Error};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+const ADDRESS_1: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+const ADDRESS_2: &str = "iot1q95jpvtk7cf7c7l9ne50c684jl4n8ya0srm5clpak7qes9ratu0eyf5eyz5";
+// The node fails to look up the balance of this address.
+const FAILING_ADDRESS: &str = "iot1qxgamuxntdxq06q4zpmvmdnhf8e6gsczzhjqdmjwp2fxxsenk5ecr5zlqx9";
+
+// Starts a mock node and returns the client with the number of balance requests.
+fn client() -> (iota_client::Client, Arc<AtomicUsize>) {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let requests_ = requests.clone();
+    let url = start_mock_node(move |method, path, _| match path.strip_prefix("/api/v1/addresses/") {
+        Some(address) if method == "GET" => {
+            requests_.fetch_add(1, Ordering::SeqCst);
+            if address == FAILING_ADDRESS {
+                return ("500 Internal Server Error", String::new());
+            }
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "addressType": 1,
+                    "address": address,
+                    "balance": if address == ADDRESS_1 { 1_000_000 } else { 0 },
+                    "dustAllowed": address == ADDRESS_1,
+                }})
+                .to_string(),
+            )
+        }
+        _ => ("404 Not Found", String::new()),
+    });
+    let client = iota_client::Client::build()
+        .with_node(&url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap();
+    (client, requests)
+}
+
+fn addresses(addresses: &[&str]) -> Vec<Bech32Address> {
+    addresses.iter().map(|address| Bech32Address::from(*address)).collect()
+}
+
+#[tokio::test]
+async fn test_get_address_balances() {
+    let (client, requests) = client();
+    let balances = client
+        .get_address_balances(&addresses(&[ADDRESS_2, ADDRESS_1, ADDRESS_2]))
+        .await
+        .unwrap();
+
+    // the repeated address is only queried once, the order of the first occurrences is kept
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+    let balances = balances
+        .iter()
+        .map(|pair| (pair.address.to_string(), pair.balance, pair.dust_allowed))
+        .collect::<Vec<(String, u64, bool)>>();
+    assert_eq!(
+        balances,
+        vec![
+            (ADDRESS_2.to_string(), 0, false),
+            (ADDRESS_1.to_string(), 1_000_000, true),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_get_address_balances_failing_address() {
+    let (client, _) = client();
+    let result = client
+        .get_address_balances(&addresses(&[ADDRESS_1, FAILING_ADDRESS, ADDRESS_2]))
+        .await;
+    match result {
+        Err(Error::AddressBalanceError(address, error)) => {
+            assert_eq!(address, FAILING_ADDRESS);
+            assert!(matches!(*error, Error::ResponseError(500, _, _)));
+        }
+        _ => panic!("the failed balance request isn't reported"),
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Address outputs tests against a mock node answering according to the query filters.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::start_mock_node;
+use iota_client::{Bech32Address, OutputType, OutputsOptions};
+use std::str::FromStr;
+
+const ADDRESS: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+const UNSPENT_OUTPUT_ID: &str = "00000000000000000000000000000000000000000000000000000000000000010000";
+const SPENT_OUTPUT_ID: &str = "00000000000000000000000000000000000000000000000000000000000000020100";
+const DUST_ALLOWANCE_OUTPUT_ID: &str = "00000000000000000000000000000000000000000000000000000000000000030000";
+
+fn client() -> iota_client::Client {
+    let url = start_mock_node(|method, path, _| {
+        let outputs_path = format!("/api/v1/addresses/{}/outputs", ADDRESS);
+        let (output_ids, max_results) = match path.strip_prefix(outputs_path.as_str()) {
+            Some(_) if method != "GET" => return ("404 Not Found", String::new()),
+            Some("") => (vec![UNSPENT_OUTPUT_ID], 1000),
+            Some("?include-spent=true") => (vec![UNSPENT_OUTPUT_ID, SPENT_OUTPUT_ID], 1000),
+            // the node caps the outputs of this request
+            Some("?include-spent=true&type=1") => (vec![DUST_ALLOWANCE_OUTPUT_ID], 1),
+            _ => return ("404 Not Found", String::new()),
+        };
+        (
+            "200 OK",
+            serde_json::json!({ "data": {
+                "address": ADDRESS,
+                "maxResults": max_results,
+                "count": output_ids.len(),
+                "outputIds": output_ids,
+            }})
+            .to_string(),
+        )
+    });
+    iota_client::Client::build()
+        .with_node(&url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+fn output_ids(output_ids: &[&str]) -> Vec<UTXOInput> {
+    output_ids
+        .iter()
+        .map(|output_id| UTXOInput::from_str(output_id).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_address_outputs() {
+    let outputs = client()
+        .get_address()
+        .outputs(&Bech32Address(ADDRESS.to_string()))
+        .await
+        .unwrap();
+    assert_eq!(outputs.to_vec(), output_ids(&[UNSPENT_OUTPUT_ID]));
+}
+
+#[tokio::test]
+async fn test_address_outputs_include_spent() {
+    let response = client()
+        .get_address()
+        .outputs_response(
+            &Bech32Address(ADDRESS.to_string()),
+            OutputsOptions {
+                include_spent: true,
+                output_type: None,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.count, 2);
+    assert_eq!(response.max_results, 1000);
+    assert!(!response.is_truncated());
+    assert_eq!(
+        response.utxo_inputs().unwrap().to_vec(),
+        output_ids(&[UNSPENT_OUTPUT_ID, SPENT_OUTPUT_ID])
+    );
+}
+
+#[tokio::test]
+async fn test_address_outputs_by_type() {
+    let response = client()
+        .get_address()
+        .outputs_response(
+            &Bech32Address(ADDRESS.to_string()),
+            OutputsOptions {
+                include_spent: true,
+                output_type: Some(OutputType::SignatureLockedDustAllowance),
+            },
+        )
+        .await
+        .unwrap();
+    assert!(response.is_truncated());
+    assert_eq!(
+        response.utxo_inputs().unwrap().to_vec(),
+        output_ids(&[DUST_ALLOWANCE_OUTPUT_ID])
+    );
+}
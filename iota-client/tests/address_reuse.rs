@@ -0,0 +1,129 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Address reuse check tests against a mock node where some account addresses already spent outputs.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{utxo_mock_node_with_spent_outputs, MockOutput};
+use iota_client::{
+    api::{AccountAddress, RemainderStrategy},
+    Client, Error, Seed,
+};
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+const TRANSACTION_ID_1: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const TRANSACTION_ID_2: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+const SPENT_TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000003";
+const RECIPIENT: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+
+fn client(url: &str, address_reuse_check: bool) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(false)
+        .with_address_reuse_check(address_reuse_check)
+        .finish()
+        .unwrap()
+}
+
+// The first public addresses of the seed's first account.
+fn account_addresses() -> Vec<AccountAddress> {
+    let seed = Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap();
+    client("http://localhost:14265", false)
+        .find_addresses(&seed)
+        .with_account_index(0)
+        .with_range(0..2)
+        .get_all()
+        .unwrap()
+        .into_iter()
+        .filter(|(_, internal)| !internal)
+        .enumerate()
+        .map(|(address_index, (address, internal))| AccountAddress {
+            address: Address::try_from_bech32(&address.to_string()).unwrap(),
+            address_index,
+            internal,
+        })
+        .collect()
+}
+
+// 3 Mi on the address 0 and 2 Mi on the address 1, with a spent output on the given addresses.
+fn mock_node(spent_from: &[usize]) -> String {
+    let addresses = account_addresses();
+    let outputs = vec![
+        MockOutput {
+            transaction_id: TRANSACTION_ID_1,
+            index: 0,
+            address: addresses[0].address.to_bech32(),
+            amount: 3_000_000,
+        },
+        MockOutput {
+            transaction_id: TRANSACTION_ID_2,
+            index: 1,
+            address: addresses[1].address.to_bech32(),
+            amount: 2_000_000,
+        },
+    ];
+    let spent_outputs = spent_from
+        .iter()
+        .map(|position| MockOutput {
+            transaction_id: SPENT_TRANSACTION_ID,
+            index: *position as u16,
+            address: addresses[*position].address.to_bech32(),
+            amount: 1_000_000,
+        })
+        .collect();
+    utxo_mock_node_with_spent_outputs(outputs, spent_outputs)
+}
+
+// Prepares a transfer of the amount from the account, the largest output on the address 0 is selected first and
+// receives the remainder.
+async fn prepare(client: &Client, amount: u64, address_reuse_allowed: bool) -> Result<usize, Error> {
+    client
+        .send()
+        .with_account_addresses(account_addresses())
+        .with_output(&RECIPIENT.to_string().into(), amount)
+        .unwrap()
+        .with_remainder_strategy(RemainderStrategy::ReuseFirstInputAddress)
+        .with_address_reuse_allowed(address_reuse_allowed)
+        .prepare_transaction()
+        .await
+        .map(|prepared| prepared.inputs.len())
+}
+
+#[tokio::test]
+async fn test_clean_inputs() {
+    let url = mock_node(&[]);
+    assert_eq!(prepare(&client(&url, true), 4_000_000, false).await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_reused_inputs() {
+    let url = mock_node(&[0, 1]);
+    let addresses = account_addresses()
+        .iter()
+        .map(|address| address.address.to_bech32())
+        .collect::<Vec<String>>();
+    match prepare(&client(&url, true), 4_000_000, false).await {
+        Err(Error::AddressReuse(reused)) => assert_eq!(reused, addresses),
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    // the check is opt-in and can be overridden by a transfer
+    assert_eq!(prepare(&client(&url, false), 4_000_000, false).await.unwrap(), 2);
+    assert_eq!(prepare(&client(&url, true), 4_000_000, true).await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_mixed_inputs() {
+    let url = mock_node(&[1]);
+    let client = client(&url, true);
+    // only the clean address 0 is spent from
+    assert_eq!(prepare(&client, 1_000_000, false).await.unwrap(), 1);
+    match prepare(&client, 4_000_000, false).await {
+        Err(Error::AddressReuse(reused)) => assert_eq!(reused, vec![account_addresses()[1].address.to_bech32()]),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
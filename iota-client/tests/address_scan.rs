@@ -0,0 +1,188 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Windowed address scan tests against a mock node with activity at scattered address indexes, the mock node counts
+// the requests and the most requests it handled at once.
+
+mod common;
+
+use common::start_concurrent_mock_node;
+use iota_client::{Error, Seed};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+// The used public address indexes, the gaps are below the gap limit up to the index 200.
+const USED_INDEXES: [usize; 15] = [0, 13, 27, 40, 55, 69, 84, 98, 113, 127, 142, 156, 171, 185, 200];
+
+fn seed() -> Seed {
+    Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap()
+}
+
+fn client(url: &str, parallelism: usize) -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_address_scan_parallelism(parallelism)
+        .finish()
+        .unwrap()
+}
+
+// The public addresses of the seed's first account.
+fn public_addresses(count: usize) -> Vec<String> {
+    client("http://localhost:14265", 1)
+        .find_addresses(&seed())
+        .with_account_index(0)
+        .with_range(0..count)
+        .finish()
+        .unwrap()
+        .into_iter()
+        .map(|address| address.to_string())
+        .collect()
+}
+
+#[derive(Default)]
+struct RequestStats {
+    requests: AtomicUsize,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+// Starts a mock node where the given public address indexes hold 1 Mi each in an output, every request takes 10 ms.
+fn scan_mock_node(used_indexes: &[usize]) -> (String, Arc<RequestStats>) {
+    let addresses = public_addresses(used_indexes.iter().max().unwrap() + 1);
+    let used: HashMap<String, usize> = used_indexes
+        .iter()
+        .map(|index| (addresses[*index].clone(), *index))
+        .collect();
+    let stats = Arc::new(RequestStats::default());
+    let stats_ = stats.clone();
+    let url = start_concurrent_mock_node(move |method, path, _| {
+        stats_.requests.fetch_add(1, Ordering::SeqCst);
+        let in_flight = stats_.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        stats_.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(10));
+        stats_.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        // the outputs are unspent, they're listed with and without `include-spent=true`
+        let path = match path.split('?').next().unwrap().strip_prefix("/api/v1/addresses/") {
+            Some(path) if method == "GET" => path,
+            _ => return ("404 Not Found", String::new()),
+        };
+        if let Some(address) = path.strip_suffix("/outputs") {
+            let output_ids: Vec<String> = used
+                .get(address)
+                .map(|index| format!("{}0000", hex::encode([*index as u8; 32])))
+                .into_iter()
+                .collect();
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "address": address,
+                    "maxResults": 1000,
+                    "count": output_ids.len(),
+                    "outputIds": output_ids,
+                }})
+                .to_string(),
+            )
+        } else {
+            let (balance, count) = if used.contains_key(path) {
+                (1_000_000, 1)
+            } else {
+                (0, 0)
+            };
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "address": path,
+                    "balance": balance,
+                    "count": count,
+                    "dustAllowed": false,
+                }})
+                .to_string(),
+            )
+        }
+    });
+    (url, stats)
+}
+
+async fn get_balance(url: &str, parallelism: usize) -> (u64, Vec<String>) {
+    let (balance, addresses) = client(url, parallelism)
+        .get_balance(&seed())
+        .with_account_index(0)
+        // the mock node only answers the address balances
+        .with_confirmed_only(false)
+        .finish_with_addresses()
+        .await
+        .unwrap();
+    (
+        balance,
+        addresses.into_iter().map(|pair| pair.address.to_string()).collect(),
+    )
+}
+
+#[tokio::test]
+async fn test_windowed_balance_scan() {
+    let addresses = public_addresses(201);
+    let used_addresses: Vec<String> = USED_INDEXES.iter().map(|index| addresses[*index].clone()).collect();
+
+    // the serial scan
+    let (url, serial_stats) = scan_mock_node(&USED_INDEXES);
+    let serial = get_balance(&url, 1).await;
+    assert_eq!(serial, (15_000_000, used_addresses));
+    assert_eq!(serial_stats.max_in_flight.load(Ordering::SeqCst), 1);
+
+    let (url, stats) = scan_mock_node(&USED_INDEXES);
+    assert_eq!(get_balance(&url, 10).await, serial);
+    // the indexes 201 to 220 are unused, the scan stops in the window of the indexes 220 to 239: 12 windows of 20
+    // public and 20 internal addresses, the addresses without a balance are checked for spent outputs, the requests
+    // run 10 at a time instead of one after the other
+    let requests = stats.requests.load(Ordering::SeqCst);
+    assert_eq!(requests, 2 * 12 * 40 - USED_INDEXES.len());
+    assert_eq!(requests, serial_stats.requests.load(Ordering::SeqCst));
+    assert_eq!(stats.max_in_flight.load(Ordering::SeqCst), 10);
+}
+
+#[tokio::test]
+async fn test_windowed_unspent_address_scan() {
+    // the public addresses 0 to 24 are used
+    let used_indexes: Vec<usize> = (0..25).collect();
+    for parallelism in &[1, 10] {
+        let (url, stats) = scan_mock_node(&used_indexes);
+        let (address, index) = client(&url, *parallelism)
+            .get_unspent_address(&seed())
+            .with_account_index(0)
+            .get()
+            .await
+            .unwrap();
+        assert_eq!((address.to_string(), index), (public_addresses(26)[25].clone(), 25));
+        // the outputs, with and without the spent ones, of the public addresses of the two windows of 20 indexes
+        assert_eq!(stats.requests.load(Ordering::SeqCst), 2 * 40);
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_address_scan_options() {
+    let builder = || {
+        iota_client::Client::build()
+            .with_node("http://localhost:14265")
+            .unwrap()
+            .with_node_sync_disabled()
+    };
+    assert!(matches!(
+        builder().with_address_scan_window(0).finish(),
+        Err(Error::InvalidParameter(_))
+    ));
+    assert!(matches!(
+        builder().with_address_scan_parallelism(0).finish(),
+        Err(Error::InvalidParameter(_))
+    ));
+}
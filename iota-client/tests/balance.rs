@@ -0,0 +1,144 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Seed balance tests against a mock node holding balances on the first addresses of an account.
+
+mod common;
+
+use common::start_mock_node;
+use iota_client::Seed;
+use std::collections::HashMap;
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+
+fn seed() -> Seed {
+    Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap()
+}
+
+// The public addresses of the seed's first account.
+fn public_addresses() -> Vec<String> {
+    offline_client()
+        .find_addresses(&seed())
+        .with_account_index(0)
+        .with_range(0..20)
+        .finish()
+        .unwrap()
+        .into_iter()
+        .map(|address| address.to_string())
+        .collect()
+}
+
+fn offline_client() -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node("http://localhost:14265")
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+// Starts a mock node with the (balance, unspent output count) of the given public address indexes, the addresses of
+// the spent indexes only hold a spent output, listed with `include-spent=true`.
+fn balance_mock_node(balances: &[(usize, u64, usize)], spent: &[usize]) -> String {
+    let addresses = public_addresses();
+    let balances: HashMap<String, (u64, usize)> = balances
+        .iter()
+        .map(|(index, balance, count)| (addresses[*index].clone(), (*balance, *count)))
+        .collect();
+    let spent: Vec<String> = spent.iter().map(|index| addresses[*index].clone()).collect();
+    start_mock_node(move |method, path, _| match path.strip_prefix("/api/v1/addresses/") {
+        Some(path) if method == "GET" && path.contains('/') => {
+            let (address, query) = path.split_at(path.find('/').unwrap());
+            let output_ids: Vec<String> = match spent.iter().position(|spent| spent == address) {
+                Some(index) if query == "/outputs?include-spent=true" => {
+                    vec![format!("{}0000", hex::encode([index as u8 + 1; 32]))]
+                }
+                _ => Vec::new(),
+            };
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "address": address,
+                    "maxResults": 1000,
+                    "count": output_ids.len(),
+                    "outputIds": output_ids,
+                }})
+                .to_string(),
+            )
+        }
+        Some(address) if method == "GET" => {
+            let (balance, count) = balances.get(address).cloned().unwrap_or((0, 0));
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "address": address,
+                    "balance": balance,
+                    "count": count,
+                    "dustAllowed": false,
+                }})
+                .to_string(),
+            )
+        }
+        _ => ("404 Not Found", String::new()),
+    })
+}
+
+async fn get_balance(node: &str, gap_limit: usize) -> (u64, Vec<String>) {
+    let client = iota_client::Client::build()
+        .with_node(node)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap();
+    let (balance, addresses) = client
+        .get_balance(&seed())
+        .with_account_index(0)
+        .with_gap_limit(gap_limit)
+        // the mock node only answers the address balances
+        .with_confirmed_only(false)
+        .finish_with_addresses()
+        .await
+        .unwrap();
+    (
+        balance,
+        addresses.into_iter().map(|pair| pair.address.to_string()).collect(),
+    )
+}
+
+#[tokio::test]
+async fn test_gap_limit() {
+    let node = balance_mock_node(&[(0, 1_000_000, 1), (2, 2_000_000, 1), (7, 4_000_000, 2)], &[]);
+    let addresses = public_addresses();
+
+    // the addresses 3, 4 and 5 are unused
+    let (balance, used_addresses) = get_balance(&node, 3).await;
+    assert_eq!(balance, 3_000_000);
+    assert_eq!(used_addresses, vec![addresses[0].clone(), addresses[2].clone()]);
+
+    let (balance, used_addresses) = get_balance(&node, 10).await;
+    assert_eq!(balance, 7_000_000);
+    assert_eq!(
+        used_addresses,
+        vec![addresses[0].clone(), addresses[2].clone(), addresses[7].clone()]
+    );
+}
+
+#[tokio::test]
+async fn test_zero_balance_address_with_activity_is_used() {
+    // the address 5 was used but its outputs are spent, the node only lists them with the spent outputs
+    let node = balance_mock_node(&[(0, 1_000_000, 1), (2, 2_000_000, 1), (7, 4_000_000, 2)], &[5]);
+    let (balance, used_addresses) = get_balance(&node, 3).await;
+    assert_eq!(balance, 7_000_000);
+    assert_eq!(used_addresses.len(), 4);
+}
+
+#[tokio::test]
+async fn test_invalid_gap_limit() {
+    let result = offline_client()
+        .get_balance(&seed())
+        .with_account_index(0)
+        .with_gap_limit(0)
+        .finish()
+        .await;
+    assert!(matches!(result, Err(iota_client::Error::InvalidParameter(_))));
+}
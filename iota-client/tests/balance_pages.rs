@@ -0,0 +1,227 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Paged balance search tests against a mock node holding a long history: the first public addresses of an account
+// have an unspent and a spent output each. The mock node records the address index of every request.
+
+mod common;
+
+use bee_message::prelude::UTXOInput;
+use common::start_mock_node;
+use iota_client::{BalancePage, Client, Seed};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+// the number of used public addresses
+const USED_ADDRESSES: usize = 50;
+const GAP_LIMIT: usize = 10;
+
+fn seed() -> Seed {
+    Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap()
+}
+
+fn client(url: &str) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+fn unspent_output_id(address_index: usize) -> String {
+    format!("{:064x}0000", address_index)
+}
+
+fn spent_output_id(address_index: usize) -> String {
+    format!("{:064x}0100", address_index)
+}
+
+fn output(output_id: &str) -> UTXOInput {
+    UTXOInput::from_str(output_id).unwrap()
+}
+
+#[derive(Default)]
+struct Requests {
+    // the address indexes of the balance requests, in their order
+    balances: Vec<usize>,
+    // the number of outputs requests of the used addresses
+    outputs: usize,
+    // the number of outputs requests of the addresses without a balance, to find their spent outputs
+    unused_outputs: usize,
+}
+
+// Starts a mock node holding the history, the addresses of the later indexes are unknown.
+fn history_mock_node() -> (String, Arc<Mutex<Requests>>) {
+    let addresses: HashMap<String, usize> = client("http://localhost:14265")
+        .find_addresses(&seed())
+        .with_account_index(0)
+        .with_range(0..USED_ADDRESSES + 3 * GAP_LIMIT)
+        .get_all()
+        .unwrap()
+        .into_iter()
+        .enumerate()
+        .map(|(position, (address, _))| (address.to_string(), position / 2))
+        .collect();
+    let used: Vec<String> = client("http://localhost:14265")
+        .find_addresses(&seed())
+        .with_account_index(0)
+        .with_range(0..USED_ADDRESSES)
+        .finish()
+        .unwrap()
+        .into_iter()
+        .map(|address| address.to_string())
+        .collect();
+    let requests = Arc::new(Mutex::new(Requests::default()));
+    let requests_ = requests.clone();
+    let url = start_mock_node(move |method, path, _| {
+        let path = match path.strip_prefix("/api/v1/addresses/") {
+            Some(path) if method == "GET" => path,
+            _ => return ("404 Not Found", String::new()),
+        };
+        let (address, outputs) = match path.find('/') {
+            Some(separator) => (&path[..separator], Some(&path[separator..])),
+            None => (path, None),
+        };
+        let index = addresses[address];
+        let used_index = used.iter().position(|used| used == address);
+        let mut requests = requests_.lock().unwrap();
+        let answer = match outputs {
+            None => {
+                requests.balances.push(index);
+                serde_json::json!({ "data": {
+                    "address": address,
+                    "balance": if used_index.is_some() { 1_000_000 } else { 0 },
+                    "count": if used_index.is_some() { 1 } else { 0 },
+                    "dustAllowed": false,
+                }})
+            }
+            Some(outputs) => {
+                let output_ids = match used_index {
+                    Some(index) => {
+                        requests.outputs += 1;
+                        if outputs == "/outputs?include-spent=true" {
+                            vec![spent_output_id(index), unspent_output_id(index)]
+                        } else {
+                            vec![unspent_output_id(index)]
+                        }
+                    }
+                    None => {
+                        assert_eq!(outputs, "/outputs?include-spent=true");
+                        requests.unused_outputs += 1;
+                        Vec::new()
+                    }
+                };
+                serde_json::json!({ "data": {
+                    "address": address,
+                    "maxResults": 1000,
+                    "count": output_ids.len(),
+                    "outputIds": output_ids,
+                }})
+            }
+        };
+        ("200 OK", answer.to_string())
+    });
+    (url, requests)
+}
+
+// The first pages of a balance search, up to the given number of pages.
+async fn balance_pages(client: &Client, initial_address_index: usize, pages: usize) -> Vec<BalancePage> {
+    let mut search = client
+        .get_balance(&seed())
+        .with_account_index(0)
+        .with_initial_address_index(initial_address_index)
+        .with_gap_limit(GAP_LIMIT)
+        .finish_pages()
+        .unwrap();
+    let mut found = Vec::new();
+    for _ in 0..pages {
+        match search.next_page().await.unwrap() {
+            Some(page) => found.push(page),
+            None => break,
+        }
+    }
+    found
+}
+
+fn address_indexes(pages: &[BalancePage]) -> Vec<usize> {
+    pages
+        .iter()
+        .flat_map(|page| page.addresses.iter().map(|address| address.address_index))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_balance_pages() {
+    let (url, requests) = history_mock_node();
+    let pages = balance_pages(&client(&url), 0, usize::MAX).await;
+
+    // a page per window, the last window has no used address
+    assert_eq!(pages.len(), USED_ADDRESSES / GAP_LIMIT + 1);
+    for (position, page) in pages.iter().enumerate() {
+        assert!(page.addresses.len() <= GAP_LIMIT);
+        assert_eq!(page.next_address_index, (position + 1) * GAP_LIMIT);
+    }
+    assert!(pages.last().unwrap().addresses.is_empty());
+    assert_eq!(address_indexes(&pages), (0..USED_ADDRESSES).collect::<Vec<usize>>());
+    for address in pages.iter().flat_map(|page| &page.addresses) {
+        assert!(!address.internal);
+        assert_eq!(address.balance, 1_000_000);
+        assert_eq!(address.outputs.len(), 1);
+        assert_eq!(address.outputs[0], output(&unspent_output_id(address.address_index)));
+        assert_eq!(address.spent_outputs.len(), 1);
+        assert_eq!(
+            address.spent_outputs[0].to_string(),
+            spent_output_id(address.address_index)
+        );
+    }
+
+    // the public and internal addresses of every scanned index, all the outputs of the ones without a balance, then
+    // the unspent and all outputs of the used ones
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.balances.len(), 2 * (USED_ADDRESSES + GAP_LIMIT));
+    assert_eq!(
+        requests.unused_outputs,
+        2 * (USED_ADDRESSES + GAP_LIMIT) - USED_ADDRESSES
+    );
+    assert_eq!(requests.outputs, 2 * USED_ADDRESSES);
+}
+
+#[tokio::test]
+async fn test_balance_pages_stop_early() {
+    let (url, requests) = history_mock_node();
+    let pages = balance_pages(&client(&url), 0, 1).await;
+    assert_eq!(address_indexes(&pages), (0..GAP_LIMIT).collect::<Vec<usize>>());
+
+    // the following windows aren't requested
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.balances.len(), 2 * GAP_LIMIT);
+    assert_eq!(requests.unused_outputs, GAP_LIMIT);
+    assert_eq!(requests.outputs, 2 * GAP_LIMIT);
+}
+
+#[tokio::test]
+async fn test_balance_pages_resume() {
+    let (url, requests) = history_mock_node();
+    let client = client(&url);
+    let pages = balance_pages(&client, 0, 2).await;
+    let cursor = pages.last().unwrap().next_address_index;
+    assert_eq!(cursor, 2 * GAP_LIMIT);
+
+    requests.lock().unwrap().balances.clear();
+    let resumed_pages = balance_pages(&client, cursor, usize::MAX).await;
+    assert_eq!(
+        address_indexes(&resumed_pages),
+        (cursor..USED_ADDRESSES).collect::<Vec<usize>>()
+    );
+    // the resumed search starts at the cursor
+    assert!(requests.lock().unwrap().balances.iter().all(|index| *index >= cursor));
+
+    // the pages match the pages of a search without an interruption
+    let all_pages = balance_pages(&client, 0, usize::MAX).await;
+    assert_eq!(all_pages[2..], resumed_pages[..]);
+}
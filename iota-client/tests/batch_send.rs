@@ -0,0 +1,177 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Batch transfer tests against a mock node holding outputs of 1 Mi on the addresses of the seed: no two transactions
+// of a batch spend the same output, and a transfer the account can't fund only fails itself.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{utxo_mock_node_with_messages, MockOutput, MESSAGE_ID};
+use iota_client::{Bech32Address, Error, Seed};
+use std::collections::HashSet;
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+const TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+
+fn seed() -> Seed {
+    Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap()
+}
+
+fn client(url: &str) -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(false)
+        .finish()
+        .unwrap()
+}
+
+// The public addresses of the first 3 indexes of an account of the seed.
+fn public_addresses(account_index: usize) -> Vec<Bech32Address> {
+    client("http://localhost:14265")
+        .find_addresses(&seed())
+        .with_account_index(account_index)
+        .with_range(0..3)
+        .get_all()
+        .unwrap()
+        .into_iter()
+        .filter(|(_, internal)| !internal)
+        .map(|(address, _)| address)
+        .collect()
+}
+
+// `count` outputs of 1 Mi spread over the public addresses of the first account.
+fn utxo_set(count: u16) -> Vec<MockOutput> {
+    let addresses = public_addresses(0);
+    (0..count)
+        .map(|index| MockOutput {
+            transaction_id: TRANSACTION_ID,
+            index,
+            address: addresses[index as usize % addresses.len()].to_string(),
+            amount: 1_000_000,
+        })
+        .collect()
+}
+
+// The output ids spent by the transaction of the message.
+fn inputs(message: &Message) -> Vec<String> {
+    let transaction = match message.payload() {
+        Some(Payload::Transaction(transaction)) => transaction,
+        _ => panic!("the message doesn't have a transaction payload"),
+    };
+    transaction
+        .essence()
+        .inputs()
+        .iter()
+        .map(|input| match input {
+            Input::UTXO(input) => input.output_id().to_string(),
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+// Checks that no output is spent twice across the messages, returning the number of spent outputs.
+fn spent_outputs(messages: &[Message]) -> usize {
+    let inputs: Vec<String> = messages.iter().flat_map(inputs).collect();
+    let distinct: HashSet<&String> = inputs.iter().collect();
+    assert_eq!(distinct.len(), inputs.len(), "double spent inputs: {:?}", inputs);
+    inputs.len()
+}
+
+#[tokio::test]
+async fn test_batch_without_double_spent_inputs() {
+    let (url, messages) = utxo_mock_node_with_messages(utxo_set(5));
+    let seed = seed();
+    let recipients = public_addresses(1);
+    let client = client(&url);
+    let mut batch = client.send_batch(&seed).with_parallelism(2);
+    // the transfer of 10 Mi can't be funded, the 4 others spend an output of 1 Mi each
+    for amount in &[1_000_000, 1_000_000, 10_000_000, 1_000_000, 1_000_000] {
+        batch = batch.with_transfer(vec![(recipients[0].clone(), *amount)]);
+    }
+    let results = batch.finish().await.unwrap();
+
+    assert_eq!(results.len(), 5);
+    for (index, result) in results.iter().enumerate() {
+        match result {
+            Err(Error::NotEnoughBalance(available, required)) if index == 2 => {
+                // the outputs of the first transfers are reserved
+                assert_eq!((*available, *required), (3_000_000, 10_000_000));
+            }
+            Ok(message_id) if index != 2 => assert_eq!(message_id.to_string(), MESSAGE_ID),
+            r => panic!("unexpected result of the transfer {}: {:?}", index, r),
+        }
+    }
+    let messages = messages.lock().unwrap();
+    assert_eq!(messages.len(), 4);
+    assert_eq!(spent_outputs(&messages), 4);
+}
+
+#[tokio::test]
+async fn test_batch_packing() {
+    let (url, messages) = utxo_mock_node_with_messages(utxo_set(5));
+    let seed = seed();
+    let recipients = public_addresses(1);
+    let client = client(&url);
+    let mut batch = client.send_batch(&seed).with_packing(true);
+    for recipient in &recipients {
+        batch = batch.with_transfer(vec![(recipient.clone(), 1_000_000)]);
+    }
+    let results = batch.finish().await.unwrap();
+    assert!(results.iter().all(|result| result.is_ok()), "{:?}", results);
+
+    // a single transaction sends to every recipient
+    let messages = messages.lock().unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(spent_outputs(&messages), recipients.len());
+    match messages[0].payload() {
+        Some(Payload::Transaction(transaction)) => assert_eq!(transaction.essence().outputs().len(), recipients.len()),
+        _ => unreachable!(),
+    }
+}
+
+#[tokio::test]
+async fn test_batch_packing_split() {
+    let (url, messages) = utxo_mock_node_with_messages(utxo_set(5));
+    let seed = seed();
+    let recipients = public_addresses(1);
+    let client = client(&url);
+    // the packed transaction of 12 Mi can't be funded, so its transfers are sent on their own
+    let results = client
+        .send_batch(&seed)
+        .with_packing(true)
+        .with_transfer(vec![(recipients[0].clone(), 1_000_000)])
+        .with_transfer(vec![(recipients[1].clone(), 10_000_000)])
+        .with_transfer(vec![(recipients[0].clone(), 1_000_000)])
+        .finish()
+        .await
+        .unwrap();
+    assert!(results[0].is_ok() && results[2].is_ok(), "{:?}", results);
+    assert!(
+        matches!(results[1], Err(Error::NotEnoughBalance(_, 10_000_000))),
+        "{:?}",
+        results[1]
+    );
+    let messages = messages.lock().unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(spent_outputs(&messages), 2);
+}
+
+#[tokio::test]
+async fn test_batch_parameters() {
+    let (url, messages) = utxo_mock_node_with_messages(utxo_set(1));
+    let seed = seed();
+    let client = client(&url);
+    assert!(client.send_batch(&seed).finish().await.unwrap().is_empty());
+    let error = client
+        .send_batch(&seed)
+        .with_parallelism(0)
+        .with_transfer(vec![(public_addresses(1)[0].clone(), 1_000_000)])
+        .finish()
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::InvalidParameter(_)), "{:?}", error);
+    assert!(messages.lock().unwrap().is_empty());
+}
@@ -0,0 +1,253 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Node API passthrough tests against a mock node serving a plugin route to authenticated requests, echoing the posted
+// bodies, with the milestones up to 10 pruned and a route rate limited once.
+
+mod common;
+
+use common::start_mock_node_with_headers;
+use iota_client::{Client, Error, Method, NodeAuth, PrunedRequest, RequestInfo, RequestMiddleware, ResponseInfo};
+use serde_json::json;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+const JWT: &str = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiJob3JuZXQifQ.signature";
+
+fn node(limited: Arc<AtomicUsize>) -> String {
+    start_mock_node_with_headers(move |method, path, headers, body| {
+        let authorized = headers
+            .iter()
+            .any(|(name, value)| name == "authorization" && *value == format!("Bearer {}", JWT));
+        match (method, path) {
+            ("GET", "/api/v1/info") => (
+                "200 OK",
+                json!({ "data": {
+                    "name": "mock",
+                    "version": "0.0.0",
+                    "isHealthy": true,
+                    "networkId": "testnet",
+                    "minPowScore": 4000,
+                    "latestMilestoneIndex": 176,
+                    "solidMilestoneIndex": 176,
+                    "pruningIndex": 10,
+                    "features": [],
+                }})
+                .to_string(),
+            ),
+            ("GET", "/api/plugins/participation/events?type=0&limit=2") if authorized => (
+                "200 OK",
+                json!({ "data": { "eventIds": ["event1", "event2"] } }).to_string(),
+            ),
+            ("GET", path) if path.starts_with("/api/plugins/") => ("401 Unauthorized", String::new()),
+            ("POST", "/api/v1/echo") => {
+                let body: serde_json::Value = serde_json::from_slice(body).unwrap_or_default();
+                ("201 Created", json!({ "data": { "echo": body } }).to_string())
+            }
+            // an answer outside of a data envelope and an empty answer
+            ("GET", "/api/v1/raw") => ("200 OK", json!({ "raw": true }).to_string()),
+            ("DELETE", "/api/v1/peers/peer1") => ("204 No Content", String::new()),
+            ("GET", "/api/v1/limited")
+                if limited
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                    .is_ok() =>
+            {
+                ("429 Too Many Requests", String::new())
+            }
+            ("GET", "/api/v1/limited") => ("200 OK", json!({ "data": "done" }).to_string()),
+            _ => (
+                "404 Not Found",
+                json!({"error":{"code":"404","message":"not found"}}).to_string(),
+            ),
+        }
+    })
+}
+
+fn client(url: &str) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_node_auth(NodeAuth::Jwt { jwt: JWT.to_string() })
+        .with_rate_limit_retries(1)
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_call_node_api_get_with_query() {
+    let client = client(&node(Arc::new(AtomicUsize::new(0))));
+    let events = client
+        .call_node_api(
+            Method::GET,
+            "/api/plugins/participation/events",
+            &[("type", "0"), ("limit", "2")],
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(events, json!({ "eventIds": ["event1", "event2"] }));
+
+    let raw = client
+        .call_node_api(Method::GET, "/api/v1/raw", &[], None)
+        .await
+        .unwrap();
+    assert_eq!(raw, json!({ "raw": true }));
+    let deleted = client
+        .call_node_api(Method::DELETE, "/api/v1/peers/peer1", &[], None)
+        .await
+        .unwrap();
+    assert_eq!(deleted, serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn test_call_node_api_post_with_body() {
+    let client = client(&node(Arc::new(AtomicUsize::new(0))));
+    let body = json!({ "value": 1, "tags": ["a", "b"] });
+    let answer = client
+        .call_node_api(Method::POST, "/api/v1/echo", &[], Some(&body))
+        .await
+        .unwrap();
+    assert_eq!(answer, json!({ "echo": body }));
+}
+
+#[tokio::test]
+async fn test_call_node_api_unauthorized() {
+    let url = node(Arc::new(AtomicUsize::new(0)));
+    let client = Client::build()
+        .with_node(&url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap();
+    let error = client
+        .call_node_api(Method::GET, "/api/plugins/participation/events", &[], None)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::Unauthorized(_)), "{:?}", error);
+}
+
+#[tokio::test]
+async fn test_call_node_api_escaping_paths() {
+    let client = client(&node(Arc::new(AtomicUsize::new(0))));
+    for path in &[
+        "/health",
+        "api/v1/info",
+        "/api/v1/../../health",
+        "/api/v1/%2E%2e/info",
+        "/api/v1//info",
+        "/api/v1/info?limit=1",
+        "/api/v1\\..\\info",
+    ] {
+        let error = client.call_node_api(Method::GET, path, &[], None).await.unwrap_err();
+        assert!(matches!(error, Error::InvalidParameter(_)), "{}: {:?}", path, error);
+    }
+}
+
+#[tokio::test]
+async fn test_call_node_api_error_mapping() {
+    let limited = Arc::new(AtomicUsize::new(1));
+    let client = client(&node(limited.clone()));
+    // a not found milestone at or below the pruning index was pruned
+    let error = client
+        .call_node_api(Method::GET, "/api/v1/milestones/5", &[], None)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            error,
+            Error::PrunedData {
+                requested: PrunedRequest::Milestone(5),
+                pruning_index: 10
+            }
+        ),
+        "{:?}",
+        error
+    );
+    let error = client
+        .call_node_api(Method::GET, "/api/v1/milestones/20", &[], None)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::ResponseError(404, _, _)), "{:?}", error);
+
+    // the rate limited request is retried
+    let answer = client
+        .call_node_api(Method::GET, "/api/v1/limited", &[], None)
+        .await
+        .unwrap();
+    assert_eq!(answer, json!("done"));
+    limited.store(2, Ordering::SeqCst);
+    let error = client
+        .call_node_api(Method::GET, "/api/v1/limited", &[], None)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::RateLimited(429, None, _)), "{:?}", error);
+}
+
+// Records the requests and the responses.
+#[derive(Default)]
+struct RecordingMiddleware {
+    responses: Arc<Mutex<Vec<(RequestInfo, ResponseInfo)>>>,
+}
+
+impl RequestMiddleware for RecordingMiddleware {
+    fn after_response(&self, request: &RequestInfo, response: &ResponseInfo) {
+        self.responses.lock().unwrap().push((request.clone(), response.clone()));
+    }
+}
+
+#[tokio::test]
+async fn test_call_node_api_middleware() {
+    let url = node(Arc::new(AtomicUsize::new(0)));
+    let middleware = RecordingMiddleware::default();
+    let responses = middleware.responses.clone();
+    let client = Client::build()
+        .with_node(&url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_request_timeout(Duration::from_secs(5))
+        .with_middleware(middleware)
+        .finish()
+        .unwrap();
+    let body = json!({ "value": 1 });
+    client
+        .call_node_api(Method::POST, "/api/v1/echo", &[], Some(&body))
+        .await
+        .unwrap();
+    client
+        .call_node_api(Method::GET, "/api/v1/raw", &[("a", "b")], None)
+        .await
+        .unwrap();
+    let responses = responses.lock().unwrap();
+    assert_eq!(responses.len(), 2);
+    let (request, response) = &responses[0];
+    assert_eq!(
+        (request.method.as_str(), request.path.as_str()),
+        ("POST", "/api/v1/echo")
+    );
+    assert_eq!(request.body_size, body.to_string().len());
+    assert_eq!(response.status, Some(201));
+    assert_eq!(responses[1].0.path, "/api/v1/raw?a=b");
+}
+
+#[cfg(feature = "metrics")]
+#[tokio::test]
+async fn test_call_node_api_metrics() {
+    let client = client(&node(Arc::new(AtomicUsize::new(0))));
+    for index in &["5", "20"] {
+        let path = format!("/api/v1/milestones/{}", index);
+        client.call_node_api(Method::GET, &path, &[], None).await.unwrap_err();
+    }
+    let metrics = client.get_metrics();
+    let milestones = metrics
+        .endpoints
+        .iter()
+        .find(|endpoint| endpoint.endpoint == "/api/v1/milestones/:id")
+        .unwrap();
+    assert_eq!((milestones.method.as_str(), milestones.requests), ("GET", 2));
+}
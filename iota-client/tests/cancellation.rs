@@ -0,0 +1,163 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Cancellation tests against mock nodes: the operations run with a token cancelled mid-flight return
+// `Error::Cancelled` right away, whatever they were waiting for.
+
+mod common;
+
+use bee_message::prelude::MessageId;
+use common::{node_info, start_concurrent_mock_node, tips};
+use iota_client::{CancellationToken, Client, Error};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+const PENDING_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000004";
+// The delay before the token is cancelled, and the bound of the return of a cancelled operation after it.
+const CANCEL_AFTER: Duration = Duration::from_millis(300);
+const CANCEL_DELAY: Duration = Duration::from_millis(700);
+
+// A message which is neither referenced nor promotable or reattachable, `retry_until_included` waits for it.
+fn pending_metadata() -> String {
+    serde_json::json!({ "data": {
+        "messageId": PENDING_MESSAGE_ID,
+        "parent1MessageId": PENDING_MESSAGE_ID,
+        "parent2MessageId": PENDING_MESSAGE_ID,
+        "isSolid": true,
+        "shouldPromote": false,
+        "shouldReattach": false,
+    }})
+    .to_string()
+}
+
+// A node with the metadata of the pending message, answered after `delay`, and the count of the metadata requests.
+fn pending_mock_node(delay: Duration, min_pow_score: f64) -> (String, Arc<AtomicUsize>) {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let requests_ = requests.clone();
+    let url = start_concurrent_mock_node(move |method, path, _| {
+        if method == "GET" && path == "/api/v1/info" {
+            ("200 OK", node_info(min_pow_score, &[]))
+        } else if method == "GET" && path == "/api/v1/tips" {
+            ("200 OK", tips())
+        } else if method == "GET" && path == format!("/api/v1/messages/{}/metadata", PENDING_MESSAGE_ID) {
+            requests_.fetch_add(1, Ordering::SeqCst);
+            thread::sleep(delay);
+            ("200 OK", pending_metadata())
+        } else {
+            ("404 Not Found", String::new())
+        }
+    });
+    (url, requests)
+}
+
+fn client(url: &str, local_pow: bool) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(local_pow)
+        .with_pow_worker_count(2)
+        .finish()
+        .unwrap()
+}
+
+// Cancels the token from another thread after `CANCEL_AFTER`.
+fn cancel_later(token: &CancellationToken) -> Instant {
+    let token = token.clone();
+    thread::spawn(move || {
+        thread::sleep(CANCEL_AFTER);
+        token.cancel();
+    });
+    Instant::now()
+}
+
+#[tokio::test]
+async fn test_cancel_polling_loop() {
+    let (url, requests) = pending_mock_node(Duration::from_millis(0), 4000f64);
+    let client = client(&url, false);
+    let message_id = MessageId::from_str(PENDING_MESSAGE_ID).unwrap();
+    let token = CancellationToken::new();
+    let started = cancel_later(&token);
+    // the loop sleeps for a minute between the attempts
+    let result = token
+        .run(client.retry_until_included(&message_id, Some(Duration::from_secs(60)), None))
+        .await;
+    assert!(matches!(result, Err(Error::Cancelled)), "{:?}", result);
+    assert!(started.elapsed() < CANCEL_AFTER + CANCEL_DELAY);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    // a cancelled token stays cancelled, its operations aren't started
+    assert!(token.is_cancelled());
+    assert!(matches!(token.check(), Err(Error::Cancelled)));
+    let result = token.run(client.get_message().metadata(&message_id)).await;
+    assert!(matches!(result, Err(Error::Cancelled)), "{:?}", result);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_cancel_in_flight_request() {
+    let (url, requests) = pending_mock_node(Duration::from_secs(10), 4000f64);
+    let client = client(&url, false);
+    let message_id = MessageId::from_str(PENDING_MESSAGE_ID).unwrap();
+    let token = CancellationToken::new();
+    let started = cancel_later(&token);
+    let result = token.run(client.retry_until_included(&message_id, None, None)).await;
+    // the request is dropped instead of timing out
+    assert!(matches!(result, Err(Error::Cancelled)), "{:?}", result);
+    assert!(started.elapsed() < CANCEL_AFTER + CANCEL_DELAY);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    // an operation completed before the cancellation is returned as is
+    let token = CancellationToken::new();
+    let result = token.run(client.get_tips()).await;
+    assert!(result.is_ok());
+}
+
+#[cfg(feature = "pow")]
+#[tokio::test]
+async fn test_cancel_local_pow() {
+    // a score far out of reach, the PoW only stops on cancellation
+    let (url, _) = pending_mock_node(Duration::from_millis(0), 1e12f64);
+    let client = client(&url, true);
+    let token = CancellationToken::new();
+    let started = cancel_later(&token);
+    let result = token
+        .run(
+            client
+                .send()
+                .with_index("iota.rs cancellation")
+                .with_data(b"unreachable PoW score".to_vec())
+                .finish(),
+        )
+        .await;
+    assert!(matches!(result, Err(Error::Cancelled)), "{:?}", result);
+    assert!(started.elapsed() < CANCEL_AFTER + CANCEL_DELAY);
+}
+
+#[cfg(feature = "pow")]
+#[tokio::test]
+async fn test_cancel_local_nonce_provider() {
+    use iota_client::{LocalPow, NonceProvider};
+
+    // the PoW observes the token of the future polling it, an outer one too
+    let outer = CancellationToken::new();
+    let inner = CancellationToken::new();
+    let started = cancel_later(&outer);
+    let nonce = outer
+        .run(inner.run(async {
+            assert!(CancellationToken::current().is_some());
+            LocalPow::new(2).nonce(&[0u8; 64], 1e12f64)
+        }))
+        .await;
+    assert!(matches!(nonce, Err(Error::Cancelled)), "{:?}", nonce);
+    assert!(started.elapsed() < CANCEL_AFTER + CANCEL_DELAY);
+    assert!(!inner.is_cancelled());
+    assert!(CancellationToken::current().is_none());
+}
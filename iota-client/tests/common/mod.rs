@@ -0,0 +1,320 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// A minimal HTTP mock node, the handler maps the method, path and body of a request to a status and a response.
+
+// not every test uses every helper
+#![allow(dead_code)]
+
+use bee_message::prelude::*;
+use iota_client::MessageJson;
+use std::{
+    convert::TryInto,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+pub const MESSAGE_ID: &str = "52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649";
+
+// Starts a mock node on a random port and returns its URL.
+pub fn start_mock_node<F>(handler: F) -> String
+where
+    F: Fn(&str, &str, &[u8]) -> (&'static str, String) + Send + 'static,
+{
+    start_mock_node_with_headers(move |method, path, _, body| handler(method, path, body))
+}
+
+// Starts a mock node whose handler also gets the request headers, with lowercase names.
+pub fn start_mock_node_with_headers<F>(handler: F) -> String
+where
+    F: Fn(&str, &str, &[(String, String)], &[u8]) -> (&'static str, String) + Send + 'static,
+{
+    start_mock_node_with_bytes(move |method, path, headers, body| {
+        let (status, response) = handler(method, path, headers, body);
+        (status, response.into_bytes())
+    })
+}
+
+// Starts a mock node whose handler answers bytes, such as the packed messages of the `/raw` endpoints.
+pub fn start_mock_node_with_bytes<F>(handler: F) -> String
+where
+    F: Fn(&str, &str, &[(String, String)], &[u8]) -> (&'static str, Vec<u8>) + Send + 'static,
+{
+    start_mock_node_with_response_headers(move |method, path, headers, body| {
+        let (status, response) = handler(method, path, headers, body);
+        (status, Vec::new(), response)
+    })
+}
+
+// Starts a mock node whose handler also answers response headers, such as the `Retry-After` of a rate limited node.
+pub fn start_mock_node_with_response_headers<F>(handler: F) -> String
+where
+    F: Fn(&str, &str, &[(String, String)], &[u8]) -> (&'static str, Vec<(&'static str, String)>, Vec<u8>)
+        + Send
+        + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            handle_request(stream.unwrap(), &handler);
+        }
+    });
+    url
+}
+
+// Starts a mock node handling every request on its own thread, so the concurrent requests of the client overlap.
+pub fn start_concurrent_mock_node<F>(handler: F) -> String
+where
+    F: Fn(&str, &str, &[u8]) -> (&'static str, String) + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handler = Arc::new(handler);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let handler = handler.clone();
+            thread::spawn(move || {
+                handle_request(
+                    stream.unwrap(),
+                    &|method: &str, path: &str, _: &[(String, String)], body: &[u8]| {
+                        let (status, response) = handler(method, path, body);
+                        (status, Vec::new(), response.into_bytes())
+                    },
+                )
+            });
+        }
+    });
+    url
+}
+
+fn handle_request<F>(mut stream: TcpStream, handler: &F)
+where
+    F: Fn(&str, &str, &[(String, String)], &[u8]) -> (&'static str, Vec<(&'static str, String)>, Vec<u8>),
+{
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let mut headers = Vec::new();
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        if header.trim().is_empty() {
+            break;
+        }
+        if let Some(separator) = header.find(':') {
+            let (name, value) = header.split_at(separator);
+            headers.push((name.trim().to_lowercase(), value[1..].trim().to_string()));
+        }
+    }
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .map_or(0, |(_, length)| length.parse().unwrap());
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).unwrap();
+
+    let request = request_line.split_whitespace().take(2).collect::<Vec<&str>>();
+    let (status, response_headers, response) = handler(request[0], request[1], &headers, &body);
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n",
+        status,
+        response.len(),
+    )
+    .unwrap();
+    for (name, value) in response_headers {
+        write!(stream, "{}: {}\r\n", name, value).unwrap();
+    }
+    write!(stream, "\r\n").unwrap();
+    stream.write_all(&response).unwrap();
+}
+
+// The node info response.
+pub fn node_info(min_pow_score: f64, features: &[&str]) -> String {
+    serde_json::json!({ "data": {
+        "name": "mock",
+        "version": "0.0.0",
+        "isHealthy": true,
+        "networkId": "testnet",
+        "minPowScore": min_pow_score,
+        "latestMilestoneIndex": 1,
+        "solidMilestoneIndex": 1,
+        "pruningIndex": 0,
+        "features": features,
+    }})
+    .to_string()
+}
+
+// The tips response.
+pub fn tips() -> String {
+    serde_json::json!({ "data": { "tip1MessageId": MESSAGE_ID, "tip2MessageId": MESSAGE_ID } }).to_string()
+}
+
+// An unspent output of the UTXO mock node.
+pub struct MockOutput {
+    pub transaction_id: &'static str,
+    pub index: u16,
+    pub address: String,
+    pub amount: u64,
+}
+
+impl MockOutput {
+    pub fn id(&self) -> String {
+        format!("{}{}", self.transaction_id, hex::encode(self.index.to_le_bytes()))
+    }
+}
+
+// Starts a mock node holding the given outputs.
+pub fn utxo_mock_node(outputs: Vec<MockOutput>) -> String {
+    utxo_mock_node_with_messages(outputs).0
+}
+
+// Starts a mock node holding the given outputs, the posted messages are validated and recorded.
+pub fn utxo_mock_node_with_messages(outputs: Vec<MockOutput>) -> (String, Arc<Mutex<Vec<Message>>>) {
+    utxo_mock_node_with_dust_allowances(outputs, Vec::new())
+}
+
+// Starts a mock node holding the given signature locked single and dust allowance outputs, the posted messages are
+// validated and recorded, and every message is referenced by a milestone. An address holding a dust allowance output
+// allows dust.
+pub fn utxo_mock_node_with_dust_allowances(
+    outputs: Vec<MockOutput>,
+    dust_allowance_outputs: Vec<MockOutput>,
+) -> (String, Arc<Mutex<Vec<Message>>>) {
+    utxo_mock_node_with_history(outputs, dust_allowance_outputs, Vec::new())
+}
+
+// Starts a mock node holding the given outputs, the spent outputs are only listed with the spent outputs of their
+// address.
+pub fn utxo_mock_node_with_spent_outputs(outputs: Vec<MockOutput>, spent_outputs: Vec<MockOutput>) -> String {
+    utxo_mock_node_with_history(outputs, Vec::new(), spent_outputs).0
+}
+
+fn utxo_mock_node_with_history(
+    outputs: Vec<MockOutput>,
+    dust_allowance_outputs: Vec<MockOutput>,
+    spent_outputs: Vec<MockOutput>,
+) -> (String, Arc<Mutex<Vec<Message>>>) {
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let messages_ = messages.clone();
+    let typed_outputs = outputs
+        .into_iter()
+        .map(|output| (0u8, output))
+        .chain(dust_allowance_outputs.into_iter().map(|output| (1u8, output)))
+        .collect::<Vec<(u8, MockOutput)>>();
+    let url = start_mock_node(move |method, path, body| {
+        let (path, query) = match path.find('?') {
+            Some(separator) => (&path[..separator], &path[separator + 1..]),
+            None => (path, ""),
+        };
+        if method == "GET" && path == "/api/v1/info" {
+            ("200 OK", node_info(4000f64, &["PoW"]))
+        } else if method == "GET" && path == "/api/v1/tips" {
+            ("200 OK", tips())
+        } else if let Some(address) = path
+            .strip_prefix("/api/v1/addresses/")
+            .and_then(|path| path.strip_suffix("/outputs"))
+        {
+            let output_type = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("type="))
+                .map(|output_type| output_type.parse::<u8>().unwrap());
+            let include_spent = query.split('&').any(|pair| pair == "include-spent=true");
+            let output_ids = typed_outputs
+                .iter()
+                .filter(|(type_, output)| output.address == address && output_type.map_or(true, |t| t == *type_))
+                .map(|(_, output)| output.id())
+                .chain(
+                    spent_outputs
+                        .iter()
+                        .filter(|output| {
+                            include_spent && output.address == address && output_type.map_or(true, |t| t == 0)
+                        })
+                        .map(MockOutput::id),
+                )
+                .collect::<Vec<String>>();
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "address": address,
+                    "maxResults": 1000,
+                    "count": output_ids.len(),
+                    "outputIds": output_ids,
+                }})
+                .to_string(),
+            )
+        } else if let Some(address) = path
+            .strip_prefix("/api/v1/addresses/")
+            .filter(|address| !address.contains('/'))
+        {
+            let address_outputs = typed_outputs
+                .iter()
+                .filter(|(_, output)| output.address == address)
+                .collect::<Vec<&(u8, MockOutput)>>();
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "addressType": 1,
+                    "address": address,
+                    "maxResults": 1000,
+                    "count": address_outputs.len(),
+                    "balance": address_outputs.iter().map(|(_, output)| output.amount).sum::<u64>(),
+                    "dustAllowed": address_outputs.iter().any(|(output_type, _)| *output_type == 1),
+                }})
+                .to_string(),
+            )
+        } else if let Some((output_type, output)) = path
+            .strip_prefix("/api/v1/outputs/")
+            .and_then(|id| typed_outputs.iter().find(|(_, output)| output.id() == id))
+        {
+            let address = match Address::try_from_bech32(&output.address).unwrap() {
+                Address::Ed25519(address) => address.to_string(),
+                _ => unreachable!(),
+            };
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "messageId": MESSAGE_ID,
+                    "transactionId": output.transaction_id,
+                    "outputIndex": output.index,
+                    "isSpent": false,
+                    "output": {
+                        "type": output_type,
+                        "address": { "type": 1, "address": address },
+                        "amount": output.amount,
+                    },
+                }})
+                .to_string(),
+            )
+        } else if method == "POST" && path == "/api/v1/messages" {
+            let message: MessageJson = serde_json::from_slice(body).unwrap();
+            messages_.lock().unwrap().push(message.try_into().unwrap());
+            (
+                "201 Created",
+                serde_json::json!({ "data": { "messageId": MESSAGE_ID } }).to_string(),
+            )
+        } else if let Some(message_id) = path
+            .strip_prefix("/api/v1/messages/")
+            .and_then(|path| path.strip_suffix("/metadata"))
+        {
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "messageId": message_id,
+                    "parent1MessageId": MESSAGE_ID,
+                    "parent2MessageId": MESSAGE_ID,
+                    "isSolid": true,
+                    "referencedByMilestoneIndex": 1,
+                    "ledgerInclusionState": "included",
+                }})
+                .to_string(),
+            )
+        } else {
+            ("404 Not Found", String::new())
+        }
+    });
+    (url, messages)
+}
@@ -0,0 +1,103 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Message confirmation tests against a mock node whose milestone references the message after two polls.
+
+mod common;
+
+use bee_message::prelude::MessageId;
+use common::{start_mock_node, MESSAGE_ID};
+use iota_client::{api::ConfirmationTracker, LedgerInclusionState};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+const PARENT: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+// Starts a mock node and returns the client with the number of metadata requests.
+fn client() -> (iota_client::Client, Arc<AtomicUsize>) {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let requests_ = requests.clone();
+    let url = start_mock_node(move |method, path, _| {
+        if method != "GET" || path != format!("/api/v1/messages/{}/metadata", MESSAGE_ID) {
+            return ("404 Not Found", String::new());
+        }
+        let mut metadata = serde_json::json!({
+            "messageId": MESSAGE_ID,
+            "parent1MessageId": PARENT,
+            "parent2MessageId": PARENT,
+            "isSolid": true,
+        });
+        // the first two polls see the message before a milestone references it
+        if requests_.fetch_add(1, Ordering::SeqCst) >= 2 {
+            metadata["referencedByMilestoneIndex"] = 176.into();
+            metadata["ledgerInclusionState"] = "included".into();
+        }
+        ("200 OK", serde_json::json!({ "data": metadata }).to_string())
+    });
+    let client = iota_client::Client::build()
+        .with_node(&url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap();
+    (client, requests)
+}
+
+fn message_id() -> MessageId {
+    MessageId::from_str(MESSAGE_ID).unwrap()
+}
+
+#[tokio::test]
+async fn test_poll_confirmation() {
+    let (client, requests) = client();
+    let tracker = ConfirmationTracker::new();
+    assert!(tracker.watch(message_id()));
+    assert!(!tracker.watch(message_id()));
+
+    let mut confirmations = Vec::new();
+    for _ in 0..4 {
+        confirmations.extend(tracker.poll(&client).await);
+    }
+    // the message isn't queried anymore once confirmed
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+    assert_eq!(confirmations.len(), 1);
+    let confirmation = &confirmations[0];
+    assert_eq!(confirmation.message_id, MESSAGE_ID);
+    assert_eq!(confirmation.referenced_by_milestone_index, 176);
+    assert_eq!(
+        confirmation.ledger_inclusion_state,
+        Some(LedgerInclusionState::Included)
+    );
+
+    // a confirmed message can't be watched again
+    assert!(!tracker.watch(message_id()));
+    assert!(tracker.watched().is_empty());
+}
+
+#[tokio::test]
+async fn test_unwatch_confirmation() {
+    let (client, _) = client();
+    let tracker = ConfirmationTracker::new();
+    tracker.watch(message_id());
+    tracker.poll(&client).await;
+    assert!(tracker.unwatch(&message_id()));
+    assert!(!tracker.unwatch(&message_id()));
+
+    for _ in 0..3 {
+        assert!(tracker.poll(&client).await.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn test_get_message_confirmation() {
+    let (client, _) = client();
+    for _ in 0..2 {
+        assert_eq!(client.get_message_confirmation(&message_id()).await.unwrap(), None);
+    }
+    assert!(client.get_message_confirmation(&message_id()).await.unwrap().is_some());
+}
@@ -0,0 +1,167 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Confirmed balance tests against a mock node holding a confirmed and a pending output on the first address of an
+// account: the message of the confirmed output is included in the ledger, the other isn't referenced yet.
+
+mod common;
+
+use bee_message::prelude::Address;
+use common::start_mock_node;
+use iota_client::{BalanceSplit, Client, Seed};
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+const CONFIRMED_MESSAGE_ID: &str = "52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649";
+const PENDING_MESSAGE_ID: &str = "81855ad8681d0d86d1e91e00167939cb6694d2c422acd208a0072939487f6999";
+const CONFIRMED_TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const PENDING_TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+
+fn seed() -> Seed {
+    Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap()
+}
+
+fn client(url: &str) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+// The first public address of the seed's first account.
+fn address() -> String {
+    client("http://localhost:14265")
+        .find_addresses(&seed())
+        .with_account_index(0)
+        .with_range(0..1)
+        .finish()
+        .unwrap()[0]
+        .to_string()
+}
+
+// Starts a mock node holding 3 Mi confirmed and 2 Mi pending on the address, the other addresses are unused.
+fn balance_mock_node(address: String) -> String {
+    // (transaction id, message id, amount)
+    let outputs = [
+        (CONFIRMED_TRANSACTION_ID, CONFIRMED_MESSAGE_ID, 3_000_000u64),
+        (PENDING_TRANSACTION_ID, PENDING_MESSAGE_ID, 2_000_000u64),
+    ];
+    let output_id = |transaction_id: &str| format!("{}0000", transaction_id);
+    let hex_address = match Address::try_from_bech32(&address).unwrap() {
+        Address::Ed25519(address) => address.to_string(),
+        _ => unreachable!(),
+    };
+    start_mock_node(move |method, path, _| {
+        if method != "GET" {
+            return ("404 Not Found", String::new());
+        }
+        let path = path.split('?').next().unwrap();
+        let answer = if let Some(outputs_address) = path
+            .strip_prefix("/api/v1/addresses/")
+            .and_then(|path| path.strip_suffix("/outputs"))
+        {
+            let output_ids: Vec<String> = outputs
+                .iter()
+                .filter(|_| outputs_address == address)
+                .map(|(transaction_id, _, _)| output_id(transaction_id))
+                .collect();
+            serde_json::json!({ "data": {
+                "address": outputs_address,
+                "maxResults": 1000,
+                "count": output_ids.len(),
+                "outputIds": output_ids,
+            }})
+        } else if let Some(balance_address) = path.strip_prefix("/api/v1/addresses/") {
+            let used = balance_address == address;
+            serde_json::json!({ "data": {
+                "address": balance_address,
+                "balance": if used { 5_000_000 } else { 0 },
+                "count": if used { 2 } else { 0 },
+                "dustAllowed": false,
+            }})
+        } else if let Some((transaction_id, message_id, amount)) =
+            path.strip_prefix("/api/v1/outputs/").and_then(|id| {
+                outputs
+                    .iter()
+                    .find(|(transaction_id, _, _)| output_id(transaction_id) == id)
+            })
+        {
+            serde_json::json!({ "data": {
+                "messageId": message_id,
+                "transactionId": transaction_id,
+                "outputIndex": 0,
+                "isSpent": false,
+                "output": {
+                    "type": 0,
+                    "address": { "type": 1, "address": hex_address },
+                    "amount": amount,
+                },
+            }})
+        } else if let Some(message_id) = path
+            .strip_prefix("/api/v1/messages/")
+            .and_then(|path| path.strip_suffix("/metadata"))
+        {
+            let confirmed = message_id == CONFIRMED_MESSAGE_ID;
+            serde_json::json!({ "data": {
+                "messageId": message_id,
+                "parent1MessageId": CONFIRMED_MESSAGE_ID,
+                "parent2MessageId": CONFIRMED_MESSAGE_ID,
+                "isSolid": true,
+                "referencedByMilestoneIndex": if confirmed { Some(1) } else { None },
+                "ledgerInclusionState": if confirmed { Some("included") } else { None },
+            }})
+        } else {
+            return ("404 Not Found", String::new());
+        };
+        ("200 OK", answer.to_string())
+    })
+}
+
+#[tokio::test]
+async fn test_confirmed_balance() {
+    let url = balance_mock_node(address());
+    let client = client(&url);
+    let seed = seed();
+    let get_balance = || client.get_balance(&seed).with_account_index(0).with_gap_limit(2);
+
+    // only the confirmed output is counted by default
+    assert_eq!(get_balance().finish().await.unwrap(), 3_000_000);
+    let (balance, addresses) = get_balance().finish_with_addresses().await.unwrap();
+    assert_eq!(balance, 3_000_000);
+    assert_eq!(addresses.len(), 1);
+    assert_eq!(addresses[0].address.to_string(), address());
+    assert_eq!(addresses[0].balance, 3_000_000);
+
+    // the balance answered by the node counts the pending output
+    assert_eq!(
+        get_balance().with_confirmed_only(false).finish().await.unwrap(),
+        5_000_000
+    );
+}
+
+#[tokio::test]
+async fn test_balance_split() {
+    let url = balance_mock_node(address());
+    let client = client(&url);
+    let expected = BalanceSplit {
+        confirmed: 3_000_000,
+        pending: 2_000_000,
+    };
+    for confirmed_only in &[true, false] {
+        let seed = seed();
+        let split = client
+            .get_balance(&seed)
+            .with_account_index(0)
+            .with_gap_limit(2)
+            .with_confirmed_only(*confirmed_only)
+            .finish_split()
+            .await
+            .unwrap();
+        assert_eq!(split, expected);
+    }
+    assert_eq!(
+        client.get_address_balance_split(&address().into()).await.unwrap(),
+        expected
+    );
+}
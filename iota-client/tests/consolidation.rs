@@ -0,0 +1,225 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Output consolidation tests against a mock node holding many outputs on the addresses of the seed.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{utxo_mock_node_with_dust_allowances, MockOutput};
+use iota_client::{api::INPUT_COUNT_MAX, Error, Seed};
+use std::collections::HashSet;
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+const TRANSACTION_IDS: [&str; 3] = [
+    "0000000000000000000000000000000000000000000000000000000000000001",
+    "0000000000000000000000000000000000000000000000000000000000000002",
+    "0000000000000000000000000000000000000000000000000000000000000003",
+];
+const DUST_ALLOWANCE_TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000004";
+
+fn seed() -> Seed {
+    Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap()
+}
+
+fn client(url: &str) -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(false)
+        .finish()
+        .unwrap()
+}
+
+// The public and internal addresses of the first 3 indexes of the seed's first account.
+fn addresses() -> Vec<String> {
+    client("http://localhost:14265")
+        .find_addresses(&seed())
+        .with_account_index(0)
+        .with_range(0..3)
+        .get_all()
+        .unwrap()
+        .into_iter()
+        .map(|(address, _)| address.to_string())
+        .collect()
+}
+
+fn output(transaction_id: &'static str, index: u16, address: &str) -> MockOutput {
+    MockOutput {
+        transaction_id,
+        index,
+        address: address.to_string(),
+        amount: 1_000_000,
+    }
+}
+
+// 300 outputs of 1 Mi spread over the public and internal addresses.
+fn utxo_set(addresses: &[String]) -> Vec<MockOutput> {
+    let mut outputs = Vec::new();
+    for (transaction, transaction_id) in TRANSACTION_IDS.iter().enumerate() {
+        for index in 0..100 {
+            let address = &addresses[(transaction * 100 + index as usize) % addresses.len()];
+            outputs.push(output(transaction_id, index, address));
+        }
+    }
+    outputs
+}
+
+fn transaction(message: &Message) -> &TransactionPayload {
+    match message.payload() {
+        Some(Payload::Transaction(transaction)) => transaction,
+        _ => panic!("the message doesn't have a transaction payload"),
+    }
+}
+
+fn output_ids(essence: &TransactionPayloadEssence) -> Vec<String> {
+    essence
+        .inputs()
+        .iter()
+        .map(|input| match input {
+            Input::UTXO(input) => input.output_id().to_string(),
+            _ => panic!("a consolidation input must be a UTXO input"),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_consolidate_outputs() {
+    let addresses = addresses();
+    let (url, messages) = utxo_mock_node_with_dust_allowances(utxo_set(&addresses), Vec::new());
+    let message_ids = client(&url)
+        .consolidate_outputs(&seed())
+        .with_account_index(0)
+        .with_range(0..3)
+        .finish()
+        .await
+        .unwrap();
+    assert_eq!(message_ids.len(), 3);
+
+    let messages = messages.lock().unwrap();
+    let batch_sizes = messages
+        .iter()
+        .map(|message| transaction(message).essence().inputs().len())
+        .collect::<Vec<usize>>();
+    assert_eq!(batch_sizes, vec![INPUT_COUNT_MAX, INPUT_COUNT_MAX, 46]);
+
+    let mut consumed = HashSet::new();
+    for message in messages.iter() {
+        let transaction = transaction(message);
+        let essence = transaction.essence();
+        // the mock node validated the signatures, every input is unlocked
+        assert_eq!(transaction.unlock_blocks().len(), essence.inputs().len());
+        consumed.extend(output_ids(essence));
+        // the whole batch is sent to the first public address
+        match &essence.outputs()[..] {
+            [Output::SignatureLockedSingle(output)] => {
+                assert_eq!(output.address().to_bech32(), addresses[0]);
+                assert_eq!(output.amount(), essence.inputs().len() as u64 * 1_000_000);
+            }
+            _ => panic!("a consolidation transaction must have a single output"),
+        }
+    }
+    assert_eq!(consumed.len(), 300);
+}
+
+#[tokio::test]
+async fn test_consolidate_dust_allowance_outputs() {
+    let addresses = addresses();
+    let outputs = vec![
+        output(TRANSACTION_IDS[0], 0, &addresses[0]),
+        output(TRANSACTION_IDS[0], 1, &addresses[2]),
+    ];
+    let dust_allowance_outputs = vec![output(DUST_ALLOWANCE_TRANSACTION_ID, 0, &addresses[3])];
+    let (url, messages) = utxo_mock_node_with_dust_allowances(outputs, dust_allowance_outputs);
+
+    // the dust allowance outputs are skipped by default
+    let message_ids = client(&url)
+        .consolidate_outputs(&seed())
+        .with_range(0..3)
+        .with_wait_for_confirmation(true)
+        .finish()
+        .await
+        .unwrap();
+    assert_eq!(message_ids.len(), 1);
+    let message_ids = client(&url)
+        .consolidate_outputs(&seed())
+        .with_range(0..3)
+        .with_dust_allowance_outputs(true)
+        .finish()
+        .await
+        .unwrap();
+    assert_eq!(message_ids.len(), 1);
+
+    let messages = messages.lock().unwrap();
+    let essences = messages
+        .iter()
+        .map(|message| transaction(message).essence())
+        .collect::<Vec<&TransactionPayloadEssence>>();
+    assert_eq!(essences[0].inputs().len(), 2);
+    assert_eq!(essences[1].inputs().len(), 3);
+    let dust_allowance_output_id = format!("{}0000", DUST_ALLOWANCE_TRANSACTION_ID);
+    assert!(!output_ids(essences[0]).contains(&dust_allowance_output_id));
+    assert!(output_ids(essences[1]).contains(&dust_allowance_output_id));
+    // the allowance stays an allowance, on the consolidation address
+    match &essences[1].outputs()[..] {
+        [Output::SignatureLockedSingle(output), Output::SignatureLockedDustAllowance(allowance)] => {
+            assert_eq!(output.address().to_bech32(), addresses[0]);
+            assert_eq!(output.amount(), 2_000_000);
+            assert_eq!(allowance.address().to_bech32(), addresses[0]);
+            assert_eq!(allowance.amount(), 1_000_000);
+        }
+        _ => panic!("the consolidation must keep the dust allowance"),
+    }
+}
+
+#[tokio::test]
+async fn test_consolidate_dust_allowance_of_dust_outputs() {
+    let addresses = addresses();
+    // the first batch ends with the dust output of the 4th address, the second one only has its allowance
+    let mut outputs = (0..INPUT_COUNT_MAX as u16 - 1)
+        .map(|index| output(TRANSACTION_IDS[0], index, &addresses[index as usize % 3]))
+        .collect::<Vec<MockOutput>>();
+    outputs.push(MockOutput {
+        amount: 100_000,
+        ..output(TRANSACTION_IDS[1], 0, &addresses[3])
+    });
+    let dust_allowance_outputs = vec![output(DUST_ALLOWANCE_TRANSACTION_ID, 0, &addresses[3])];
+    let (url, messages) = utxo_mock_node_with_dust_allowances(outputs, dust_allowance_outputs);
+
+    // the first batch isn't confirmed, the dust output still needs the allowance
+    let error = client(&url)
+        .consolidate_outputs(&seed())
+        .with_range(0..3)
+        .with_dust_allowance_outputs(true)
+        .finish()
+        .await
+        .unwrap_err();
+    match error {
+        Error::DustAllowanceInput(address, dust_outputs, allowed) => {
+            assert_eq!((address, dust_outputs, allowed), (addresses[3].clone(), 1, 0));
+        }
+        error => panic!("unexpected error {:?}", error),
+    }
+    assert_eq!(messages.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_consolidate_nothing() {
+    let addresses = addresses();
+    let (url, messages) = utxo_mock_node_with_dust_allowances(Vec::new(), Vec::new());
+    let message_ids = client(&url).consolidate_outputs(&seed()).finish().await.unwrap();
+    assert!(message_ids.is_empty());
+
+    // a single output on the consolidation address stays there
+    let outputs = vec![output(TRANSACTION_IDS[0], 0, &addresses[0])];
+    let (url, _) = utxo_mock_node_with_dust_allowances(outputs, Vec::new());
+    let message_ids = client(&url)
+        .consolidate_outputs(&seed())
+        .with_range(0..3)
+        .finish()
+        .await
+        .unwrap();
+    assert!(message_ids.is_empty());
+    assert!(messages.lock().unwrap().is_empty());
+}
@@ -0,0 +1,287 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Data message tests against a mock node keeping the posted messages, the data is split into fragment messages and
+// reassembled from their index.
+
+mod common;
+
+use bee_common::packable::Packable;
+use bee_message::prelude::*;
+use common::{node_info, start_mock_node, tips};
+use iota_client::{
+    api::{DATA_FRAGMENT_HEADER_LENGTH, MESSAGE_LENGTH_MAX},
+    Client, Error, MessageJson,
+};
+use std::{
+    collections::HashSet,
+    convert::TryInto,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+// The messages posted to the mock node, with their id.
+#[derive(Default)]
+struct Tangle {
+    messages: Vec<(String, serde_json::Value)>,
+    // the messages left out of the index lookups
+    hidden: HashSet<String>,
+}
+
+fn data_mock_node() -> (String, Arc<Mutex<Tangle>>) {
+    let tangle = Arc::new(Mutex::new(Tangle::default()));
+    let tangle_ = tangle.clone();
+    let url = start_mock_node(move |method, path, body| {
+        let mut tangle = tangle_.lock().unwrap();
+        if method == "GET" && path == "/api/v1/info" {
+            ("200 OK", node_info(4000f64, &["PoW"]))
+        } else if method == "GET" && path == "/api/v1/tips" {
+            ("200 OK", tips())
+        } else if method == "POST" && path == "/api/v1/messages" {
+            let message_id = format!("{:064x}", tangle.messages.len() + 1);
+            tangle
+                .messages
+                .push((message_id.clone(), serde_json::from_slice(body).unwrap()));
+            (
+                "201 Created",
+                serde_json::json!({ "data": { "messageId": message_id } }).to_string(),
+            )
+        } else if let Some(index) = path.strip_prefix("/api/v1/messages?index=") {
+            let message_ids: Vec<&String> = tangle
+                .messages
+                .iter()
+                .filter(|(message_id, message)| {
+                    message["payload"]["index"] == index && !tangle.hidden.contains(message_id)
+                })
+                .map(|(message_id, _)| message_id)
+                .collect();
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "index": index,
+                    "maxResults": 1000,
+                    "count": message_ids.len(),
+                    "messageIds": message_ids,
+                }})
+                .to_string(),
+            )
+        } else if let Some((_, message)) = path
+            .strip_prefix("/api/v1/messages/")
+            .and_then(|id| tangle.messages.iter().find(|(message_id, _)| message_id == id))
+        {
+            ("200 OK", serde_json::json!({ "data": message }).to_string())
+        } else {
+            ("404 Not Found", String::new())
+        }
+    });
+    (url, tangle)
+}
+
+fn client(url: &str) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(false)
+        .finish()
+        .unwrap()
+}
+
+// 100 KB of data which doesn't repeat within a fragment.
+fn data() -> Vec<u8> {
+    (0..100_000u32).map(|i| (i * 7 + i / 256) as u8).collect()
+}
+
+fn posted_message(tangle: &Arc<Mutex<Tangle>>, message_id: &MessageId) -> Message {
+    let tangle = tangle.lock().unwrap();
+    let (_, message) = tangle
+        .messages
+        .iter()
+        .find(|(id, _)| *id == message_id.to_string())
+        .unwrap();
+    let message: MessageJson = serde_json::from_value(message.clone()).unwrap();
+    message.try_into().unwrap()
+}
+
+fn fragment_data(message: &Message) -> Vec<u8> {
+    match message.payload() {
+        Some(Payload::Indexation(indexation)) => indexation.data().to_vec(),
+        _ => panic!("the message doesn't have an indexation payload"),
+    }
+}
+
+#[tokio::test]
+async fn test_data_message_round_trip() {
+    let (url, tangle) = data_mock_node();
+    let client = client(&url);
+    let data = data();
+    let data_message = client.data_message("data", data.clone()).finish().await.unwrap();
+
+    // a fragment fills a message, the last one holding the rest
+    let fragment_count = data_message.message_ids.len();
+    assert_eq!(fragment_count, 4);
+    let mut fragments = Vec::new();
+    for (sequence, message_id) in data_message.message_ids.iter().enumerate() {
+        let message = posted_message(&tangle, message_id);
+        assert!(message.packed_len() <= MESSAGE_LENGTH_MAX);
+        let fragment = fragment_data(&message);
+        assert_eq!(fragment[0], 1);
+        assert_eq!(u32::from_le_bytes(fragment[1..5].try_into().unwrap()), sequence as u32);
+        assert_eq!(
+            u32::from_le_bytes(fragment[5..9].try_into().unwrap()),
+            fragment_count as u32
+        );
+        assert_eq!(
+            &fragment[9..DATA_FRAGMENT_HEADER_LENGTH],
+            &data_message.content_hash[..]
+        );
+        if sequence < fragment_count - 1 {
+            assert_eq!(message.packed_len(), MESSAGE_LENGTH_MAX);
+        }
+        fragments.extend_from_slice(&fragment[DATA_FRAGMENT_HEADER_LENGTH..]);
+    }
+    assert_eq!(fragments, data);
+
+    // the fragments are found whatever the order of the index lookup, among the other messages of the index
+    client
+        .send()
+        .with_index("data")
+        .with_data(b"unrelated".to_vec())
+        .finish()
+        .await
+        .unwrap();
+    tangle.lock().unwrap().messages.reverse();
+    let reassembled = client
+        .reassemble_data("data", &data_message.content_hash)
+        .await
+        .unwrap();
+    assert_eq!(reassembled, data);
+}
+
+#[tokio::test]
+async fn test_data_message_chaining() {
+    let (url, tangle) = data_mock_node();
+    let client = client(&url);
+    let data = data();
+    let data_message = client
+        .data_message("chained", data.clone())
+        .with_chaining(true)
+        .with_fragment_length(10_000)
+        .finish()
+        .await
+        .unwrap();
+    assert_eq!(data_message.message_ids.len(), 10);
+    // the message of a fragment is the parent of the next one, the first one is attached to the tips
+    let tip = MessageId::from_str(common::MESSAGE_ID).unwrap();
+    let mut parent = tip;
+    for message_id in &data_message.message_ids {
+        let message = posted_message(&tangle, message_id);
+        assert_eq!(*message.parent1(), parent);
+        assert_eq!(*message.parent2(), tip);
+        parent = *message_id;
+    }
+    assert_eq!(
+        client
+            .reassemble_data("chained", &data_message.content_hash)
+            .await
+            .unwrap(),
+        data
+    );
+
+    // empty data is a single empty fragment
+    let data_message = client.data_message("empty", Vec::new()).finish().await.unwrap();
+    assert_eq!(data_message.message_ids.len(), 1);
+    let fragment = fragment_data(&posted_message(&tangle, &data_message.message_ids[0]));
+    assert_eq!(fragment.len(), DATA_FRAGMENT_HEADER_LENGTH);
+    assert!(client
+        .reassemble_data("empty", &data_message.content_hash)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_data_message_invalid_parameters() {
+    let (url, tangle) = data_mock_node();
+    let client = client(&url);
+    let result = client.data_message(&"i".repeat(65), data()).finish().await;
+    assert!(matches!(result, Err(Error::IndexationError(_))));
+    let result = client
+        .data_message("data", data())
+        .with_fragment_length(0)
+        .finish()
+        .await;
+    assert!(matches!(result, Err(Error::InvalidParameter(_))));
+    let result = client
+        .data_message("data", data())
+        .with_fragment_length(MESSAGE_LENGTH_MAX)
+        .finish()
+        .await;
+    assert!(matches!(result, Err(Error::InvalidParameter(_))));
+    assert!(tangle.lock().unwrap().messages.is_empty());
+}
+
+#[tokio::test]
+async fn test_reassemble_missing_and_corrupted_fragments() {
+    let (url, tangle) = data_mock_node();
+    let client = client(&url);
+    let data_message = client
+        .data_message("data", data())
+        .with_fragment_length(10_000)
+        .finish()
+        .await
+        .unwrap();
+    let content_hash = data_message.content_hash;
+
+    // no fragment of another hash
+    let result = client.reassemble_data("data", &[0u8; 32]).await;
+    assert!(matches!(result, Err(Error::DataNotFound(hash)) if hash == hex::encode([0u8; 32])));
+
+    // the missing fragments are listed
+    for sequence in &[2, 7] {
+        let hidden = data_message.message_ids[*sequence].to_string();
+        tangle.lock().unwrap().hidden.insert(hidden);
+    }
+    match client.reassemble_data("data", &content_hash).await {
+        Err(Error::MissingDataFragments {
+            content_hash: hash,
+            missing,
+            fragment_count,
+        }) => {
+            assert_eq!(hash, hex::encode(content_hash));
+            assert_eq!(missing, vec![2, 7]);
+            assert_eq!(fragment_count, 10);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+    tangle.lock().unwrap().hidden.clear();
+
+    // a fragment whose data, after the framing header, was altered doesn't match the content hash
+    {
+        let mut tangle = tangle.lock().unwrap();
+        let (_, message) = &mut tangle.messages[3];
+        let mut altered = hex::decode(message["payload"]["data"].as_str().unwrap()).unwrap();
+        altered[DATA_FRAGMENT_HEADER_LENGTH] ^= 1;
+        message["payload"]["data"] = hex::encode(altered).into();
+    }
+    let result = client.reassemble_data("data", &content_hash).await;
+    assert!(matches!(result, Err(Error::InvalidDataFragments(hash, _)) if hash == hex::encode(content_hash)));
+}
+
+#[tokio::test]
+async fn test_reassemble_conflicting_fragments() {
+    let (url, tangle) = data_mock_node();
+    let client = client(&url);
+    // the fragments of the same data split twice have the same hash but another fragment count
+    let data_message = client.data_message("data", data()).finish().await.unwrap();
+    let other = client
+        .data_message("data", data())
+        .with_fragment_length(20_000)
+        .finish()
+        .await
+        .unwrap();
+    assert_eq!(other.content_hash, data_message.content_hash);
+    assert_eq!(tangle.lock().unwrap().messages.len(), 9);
+    let result = client.reassemble_data("data", &data_message.content_hash).await;
+    assert!(matches!(result, Err(Error::InvalidDataFragments(..))));
+}
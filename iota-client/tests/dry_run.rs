@@ -0,0 +1,160 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Dry run tests against a mock node holding a known UTXO set: the plan of a transfer is built without any message
+// posted, and submitting it posts its essence.
+
+mod common;
+
+use bee_common::packable::Packable;
+use bee_message::prelude::*;
+use common::{utxo_mock_node_with_messages, MockOutput};
+use iota_client::{api::TransactionPlan, Client, Error, Seed};
+use std::sync::{Arc, Mutex};
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+const TRANSACTION_ID_1: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const TRANSACTION_ID_2: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+const RECIPIENT: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+
+fn seed() -> Seed {
+    Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap()
+}
+
+fn client(url: &str) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(false)
+        .finish()
+        .unwrap()
+}
+
+// The first public and internal addresses of the seed's first account.
+fn addresses() -> (String, String) {
+    let addresses = client("http://localhost:14265")
+        .find_addresses(&seed())
+        .with_account_index(0)
+        .with_range(0..1)
+        .get_all()
+        .unwrap();
+    let address = |internal| {
+        addresses
+            .iter()
+            .find(|(_, address_internal)| *address_internal == internal)
+            .map(|(address, _)| address.to_string())
+            .unwrap()
+    };
+    (address(false), address(true))
+}
+
+// 3 Mi and 2 Mi on the first public address, or only the 2 Mi once the 3 Mi output is spent.
+fn utxo_set(spent: bool) -> Vec<MockOutput> {
+    let (address, _) = addresses();
+    let mut outputs = vec![MockOutput {
+        transaction_id: TRANSACTION_ID_2,
+        index: 1,
+        address: address.clone(),
+        amount: 2_000_000,
+    }];
+    if !spent {
+        outputs.push(MockOutput {
+            transaction_id: TRANSACTION_ID_1,
+            index: 0,
+            address,
+            amount: 3_000_000,
+        });
+    }
+    outputs
+}
+
+async fn dry_run(url: &str, amount: u64, sign: bool) -> TransactionPlan {
+    let seed = seed();
+    client(url)
+        .send()
+        .with_seed(&seed)
+        .with_output(&RECIPIENT.to_string().into(), amount)
+        .unwrap()
+        .with_dust_check_disabled()
+        .dry_run(sign)
+        .await
+        .unwrap()
+}
+
+fn packed<P: Packable>(packable: &P) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    packable.pack(&mut bytes).map_err(|_| ()).unwrap();
+    bytes
+}
+
+// The transaction of the only posted message.
+fn posted_transaction(messages: &Arc<Mutex<Vec<Message>>>) -> (Message, TransactionPayload) {
+    let messages = messages.lock().unwrap();
+    assert_eq!(messages.len(), 1);
+    match messages[0].payload() {
+        Some(Payload::Transaction(transaction)) => (messages[0].clone(), *transaction.clone()),
+        _ => panic!("the message doesn't have a transaction payload"),
+    }
+}
+
+#[tokio::test]
+async fn test_dry_run() {
+    let (url, messages) = utxo_mock_node_with_messages(utxo_set(false));
+    let plan = dry_run(&url, 4_000_000, false).await;
+    assert!(messages.lock().unwrap().is_empty());
+    assert!(plan.payload.is_none());
+    assert_eq!(plan.prepared.inputs.len(), 2);
+    let remainder = plan.prepared.remainder.clone().unwrap();
+    assert_eq!(remainder.amount, 1_000_000);
+    assert_eq!(remainder.address.to_bech32(), addresses().1);
+    assert!(plan.dust_warnings.is_empty());
+
+    let seed = seed();
+    let essence = plan.prepared.essence_bytes().unwrap();
+    let message_length = plan.message_length;
+    client(&url).send().with_seed(&seed).finish_plan(plan).await.unwrap();
+    let (message, transaction) = posted_transaction(&messages);
+    assert_eq!(packed(transaction.essence()), essence);
+    assert_eq!(message.packed_len(), message_length);
+}
+
+#[tokio::test]
+async fn test_signed_dry_run() {
+    let (url, messages) = utxo_mock_node_with_messages(utxo_set(false));
+    let plan = dry_run(&url, 4_000_000, true).await;
+    assert!(messages.lock().unwrap().is_empty());
+    let payload = plan.payload.clone().unwrap();
+    assert_eq!(packed(payload.essence()), plan.prepared.essence_bytes().unwrap());
+
+    // the signed plan doesn't need the seed
+    let message_length = plan.message_length;
+    client(&url).send().finish_plan(plan).await.unwrap();
+    let (message, transaction) = posted_transaction(&messages);
+    assert_eq!(packed(&transaction), packed(&payload));
+    assert_eq!(message.packed_len(), message_length);
+}
+
+#[tokio::test]
+async fn test_dust_warnings() {
+    let (url, _) = utxo_mock_node_with_messages(utxo_set(false));
+    let plan = dry_run(&url, 4_500_000, false).await;
+    assert_eq!(plan.dust_warnings.len(), 1);
+    assert_eq!(plan.dust_warnings[0].address.to_string(), addresses().1);
+    assert_eq!(plan.dust_warnings[0].amount, 500_000);
+    assert!(plan.dust_warnings[0].remainder);
+}
+
+#[tokio::test]
+async fn test_stale_plan() {
+    let (url, _) = utxo_mock_node_with_messages(utxo_set(false));
+    let plan = dry_run(&url, 4_000_000, true).await;
+
+    // the 3 Mi input is spent on the node submitting the plan
+    let (url, messages) = utxo_mock_node_with_messages(utxo_set(true));
+    match client(&url).send().finish_plan(plan).await {
+        Err(Error::StalePlan(output_id)) => assert_eq!(output_id, format!("{}0000", TRANSACTION_ID_1)),
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert!(messages.lock().unwrap().is_empty());
+}
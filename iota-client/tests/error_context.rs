@@ -0,0 +1,101 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Request context tests of the errors, against a mock node with a slow route to time out.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{start_mock_node, MESSAGE_ID};
+use iota_client::{Client, Error};
+use std::{str::FromStr, thread, time::Duration};
+
+// A mock node answering the info, 404 for the messages and the `api/v1/tips` route after 2 seconds.
+fn mock_node() -> String {
+    start_mock_node(|method, path, _| match (method, path) {
+        ("GET", "/api/v1/info") => ("200 OK", common::node_info(4000f64, &[])),
+        ("GET", "/api/v1/tips") => {
+            thread::sleep(Duration::from_secs(2));
+            ("200 OK", common::tips())
+        }
+        _ => (
+            "404 Not Found",
+            r#"{"error":{"code":"404","message":"message not found"}}"#.to_string(),
+        ),
+    })
+}
+
+// A client of the mock node, with credentials in the node URL.
+fn client(url: &str) -> Client {
+    let url = url.replace("http://", "http://user:secret@");
+    Client::build()
+        .with_node(&url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_request_timeout(Duration::from_millis(500))
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_timeout_context() {
+    let url = mock_node();
+    let error = client(&url).get_tips().await.unwrap_err();
+    assert!(matches!(error, Error::Timeout(Some(_))));
+
+    let context = error.request_context().unwrap();
+    assert_eq!(context.url, format!("{}/api/v1/tips", url));
+    assert_eq!(error.node(), Some(format!("{}/", url).as_str()));
+    assert_eq!(error.endpoint(), Some("GET /api/v1/tips"));
+    assert_eq!(error.attempt(), None);
+    let elapsed = error.elapsed().unwrap();
+    assert!(elapsed >= Duration::from_millis(500), "elapsed {:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(2), "elapsed {:?}", elapsed);
+
+    let message = error.to_string();
+    assert!(message.starts_with(&format!("Request timed out (GET /api/v1/tips on {}/, after ", url)));
+    assert!(!message.contains("secret"));
+}
+
+#[tokio::test]
+async fn test_response_error_context() {
+    let url = mock_node();
+    let message_id = MessageId::from_str(MESSAGE_ID).unwrap();
+    let error = client(&url).get_message().data(&message_id).await.unwrap_err();
+    assert!(matches!(error, Error::ResponseError(404, _, _)));
+
+    let endpoint = format!("GET /api/v1/messages/{}", MESSAGE_ID);
+    assert_eq!(error.node(), Some(format!("{}/", url).as_str()));
+    assert_eq!(error.endpoint(), Some(endpoint.as_str()));
+    assert_eq!(error.attempt(), None);
+    assert!(error.elapsed().unwrap() < Duration::from_millis(500));
+
+    let message = error.to_string();
+    assert!(message.starts_with("Response error with status code 404: "));
+    assert!(message.contains("message not found"));
+    assert!(message.contains(&format!("({} on {}/, after ", endpoint, url)));
+    assert!(!message.contains("secret"));
+}
+
+#[tokio::test]
+async fn test_retried_command_attempt() {
+    let url = mock_node();
+    let message_id = MessageId::from_str(MESSAGE_ID).unwrap();
+    let error = client(&url)
+        .retry_until_included(&message_id, Some(Duration::from_millis(10)), Some(3))
+        .await
+        .unwrap_err();
+    assert_eq!(error.attempt(), Some(1));
+    assert_eq!(
+        error.endpoint(),
+        Some(format!("GET /api/v1/messages/{}/metadata", MESSAGE_ID).as_str())
+    );
+    assert!(error.to_string().contains(", attempt 1, after "));
+}
+
+#[test]
+fn test_no_request_context() {
+    let error = Error::InvalidParameter("max attempts".to_string());
+    assert_eq!(error.request_context(), None);
+    assert_eq!(error.node(), None);
+}
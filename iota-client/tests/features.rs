@@ -0,0 +1,173 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Feature tests, run for each combination of features, like `cargo test --no-default-features --features pow --test
+// features`: the error variants of the features and the address derivation without the HTTP client.
+
+use bee_message::prelude::Address;
+use iota_client::{Error, Seed, SignerType};
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+
+// The feature of an error variant, `None` for the variants of every build. The match has no wildcard: a variant
+// exposed without its feature, or missing with it, fails the build of the test.
+fn feature(error: &Error) -> Option<&'static str> {
+    match error {
+        Error::SigningError(_)
+        | Error::MissingParameter(_)
+        | Error::InvalidParameter(_)
+        | Error::FromHexError(_)
+        | Error::MessageError(_)
+        | Error::IoError(_)
+        | Error::NonceProvider(_)
+        | Error::Cancelled
+        | Error::InvalidDerivationPath(_) => None,
+        #[cfg(feature = "client")]
+        Error::IndexationError(_)
+        | Error::TransactionError
+        | Error::NotEnoughBalance(..)
+        | Error::ValueOverflow(_)
+        | Error::DustError(..)
+        | Error::DustProtection(..)
+        | Error::DustRemainder(..)
+        | Error::DustAllowanceInput(..)
+        | Error::TooManyOutputs(..)
+        | Error::TooManyInputs(..)
+        | Error::SpentAddress
+        | Error::ResponseError(..)
+        | Error::RateLimited(..)
+        | Error::SyncedNodePoolEmpty
+        | Error::PowNodePoolEmpty
+        | Error::SubmitNodePoolEmpty
+        | Error::OfflineClient
+        | Error::UnknownNode(_)
+        | Error::UrlError
+        | Error::ReqwestError(..)
+        | Error::Timeout(_)
+        | Error::NetworkIdMismatch(..)
+        | Error::Bech32HrpMismatch(..)
+        | Error::NoNeedPromoteOrReattach(_)
+        | Error::MessageAlreadyReferenced(..)
+        | Error::ConflictingTransaction(_)
+        | Error::NotIncluded(..)
+        | Error::BalanceTimeout(..)
+        | Error::AddressBalanceError(..)
+        | Error::InvalidMessage(..)
+        | Error::MessageIdMismatch(_)
+        | Error::PrunedData { .. }
+        | Error::MessageNotReferenced(_)
+        | Error::InclusionPathNotFound(..)
+        | Error::InvalidInclusionProof(..)
+        | Error::InvalidMilestone(..)
+        | Error::TransactionNotIncluded(_)
+        | Error::StalePlan(_)
+        | Error::BatchMessageFailed(..)
+        | Error::AddressReuse(_)
+        | Error::UnknownParents(_)
+        | Error::ParentsRejected(..)
+        | Error::Unauthorized(_)
+        | Error::Json(_)
+        | Error::Storage(_)
+        | Error::RemotePowRefused(..)
+        | Error::AddressNotFound
+        | Error::QuorumPoolSizeError(..)
+        | Error::QuorumError(_)
+        | Error::DataNotFound(_)
+        | Error::MissingDataFragments { .. }
+        | Error::InvalidDataFragments(..) => Some("client"),
+        #[cfg(feature = "mqtt")]
+        Error::MqttClientError(_)
+        | Error::InvalidMqttTopic(_)
+        | Error::MqttConnectionNotFound
+        | Error::MqttConnectionClosed
+        | Error::MqttSchemeMismatch(..) => Some("mqtt"),
+        #[cfg(feature = "pow")]
+        Error::Pow(_) => Some("pow"),
+        #[cfg(feature = "stronghold")]
+        Error::StrongholdSnapshotNotFound(_) | Error::StrongholdInvalidPassword(_) | Error::StrongholdError(_) => {
+            Some("stronghold")
+        }
+        #[cfg(feature = "ledger")]
+        Error::LedgerDeviceNotFound
+        | Error::LedgerAppNotOpen(_)
+        | Error::LedgerDeniedByUser
+        | Error::LedgerError(_) => Some("ledger"),
+        #[cfg(feature = "snapshot")]
+        Error::InvalidSnapshot { .. } | Error::SnapshotSupplyMismatch(..) => Some("snapshot"),
+    }
+}
+
+#[test]
+fn test_error_variants_of_every_build() {
+    assert_eq!(feature(&Error::InvalidParameter("index".to_string())), None);
+    assert_eq!(feature(&Error::NonceProvider("timeout".to_string())), None);
+    assert_eq!(feature(&Error::InvalidDerivationPath("m/0".to_string())), None);
+}
+
+#[cfg(feature = "client")]
+#[test]
+fn test_error_variants_of_the_client() {
+    assert_eq!(feature(&Error::OfflineClient), Some("client"));
+    assert_eq!(feature(&Error::NotEnoughBalance(0, 1)), Some("client"));
+}
+
+#[cfg(feature = "mqtt")]
+#[test]
+fn test_error_variants_of_mqtt() {
+    assert_eq!(feature(&Error::MqttConnectionNotFound), Some("mqtt"));
+}
+
+#[cfg(feature = "pow")]
+#[test]
+fn test_error_variants_of_the_local_pow() {
+    assert_eq!(feature(&Error::Pow("no nonce".to_string())), Some("pow"));
+}
+
+#[cfg(feature = "stronghold")]
+#[test]
+fn test_error_variants_of_stronghold() {
+    assert_eq!(
+        feature(&Error::StrongholdError("locked".to_string())),
+        Some("stronghold")
+    );
+}
+
+#[cfg(feature = "ledger")]
+#[test]
+fn test_error_variants_of_the_ledger() {
+    assert_eq!(feature(&Error::LedgerDeniedByUser), Some("ledger"));
+}
+
+#[cfg(feature = "snapshot")]
+#[test]
+fn test_error_variants_of_snapshots() {
+    assert_eq!(
+        feature(&Error::SnapshotSupplyMismatch("outputs".to_string(), 0, 1)),
+        Some("snapshot")
+    );
+}
+
+#[test]
+fn test_address_derivation() {
+    let seed = Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap();
+    let signer = SignerType::from(&seed);
+    let public = signer.generate_address(0, 0, false).unwrap();
+    assert_eq!(public, signer.generate_address(0, 0, false).unwrap());
+    assert_ne!(public, signer.generate_address(0, 0, true).unwrap());
+    assert!(matches!(public, Address::Ed25519(_)));
+
+    // the client derives the same addresses
+    #[cfg(feature = "client")]
+    {
+        let addresses = iota_client::ClientBuilder::offline()
+            .with_bech32_hrp("iot")
+            .finish()
+            .unwrap()
+            .find_addresses(&seed)
+            .with_account_index(0)
+            .with_range(0..1)
+            .finish()
+            .unwrap();
+        assert_eq!(Address::try_from_bech32(&addresses[0]).unwrap(), public);
+    }
+}
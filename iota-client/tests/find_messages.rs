@@ -0,0 +1,119 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Message search tests against a mock node holding messages under two indexation keys.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::start_mock_node;
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+const MESSAGE_ID_1: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const MESSAGE_ID_2: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+const MESSAGE_ID_3: &str = "0000000000000000000000000000000000000000000000000000000000000003";
+
+// The message 2 is indexed under both keys.
+fn indexed_message_ids(query: &str) -> Option<Vec<&'static str>> {
+    match query {
+        "index=iota.rs" => Some(vec![MESSAGE_ID_1, MESSAGE_ID_2]),
+        // the space is percent-encoded in the query
+        "index=iota+rs" => Some(vec![MESSAGE_ID_2, MESSAGE_ID_3]),
+        _ => None,
+    }
+}
+
+// Starts a mock node and returns its URL with a counter of the fetched messages.
+fn messages_mock_node() -> (String, Arc<AtomicUsize>) {
+    let fetched = Arc::new(AtomicUsize::new(0));
+    let fetched_ = fetched.clone();
+    let url = start_mock_node(move |method, path, _| {
+        if method != "GET" {
+            ("404 Not Found", String::new())
+        } else if let Some(message_ids) = path.strip_prefix("/api/v1/messages?").and_then(indexed_message_ids) {
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "index": "",
+                    "maxResults": 1000,
+                    "count": message_ids.len(),
+                    "messageIds": message_ids,
+                }})
+                .to_string(),
+            )
+        } else if let Some(message_id) = path.strip_prefix("/api/v1/messages/") {
+            fetched_.fetch_add(1, Ordering::SeqCst);
+            // the payload data holds the requested id so the messages can be told apart
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "networkId": "1",
+                    "parent1MessageId": MESSAGE_ID_1,
+                    "parent2MessageId": MESSAGE_ID_1,
+                    "payload": { "type": 2, "index": "iota.rs", "data": hex::encode(message_id) },
+                    "nonce": "0",
+                }})
+                .to_string(),
+            )
+        } else {
+            ("404 Not Found", String::new())
+        }
+    });
+    (url, fetched)
+}
+
+fn client(url: &str) -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+// Returns the ids held in the payloads of the messages.
+fn requested_ids(messages: &[Message]) -> Vec<String> {
+    messages
+        .iter()
+        .map(|message| match message.payload() {
+            Some(Payload::Indexation(indexation)) => String::from_utf8(indexation.data().to_vec()).unwrap(),
+            _ => panic!("the message doesn't have an indexation payload"),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_find_messages_deduplicates() {
+    let (url, fetched) = messages_mock_node();
+    let message_id_1 = MessageId::from_str(MESSAGE_ID_1).unwrap();
+    let messages = client(&url)
+        .find_messages(&["iota.rs", "iota rs"], &[message_id_1])
+        .await
+        .unwrap();
+    assert_eq!(requested_ids(&messages), vec![MESSAGE_ID_1, MESSAGE_ID_2, MESSAGE_ID_3]);
+    assert_eq!(fetched.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_find_messages_with_limit() {
+    let (url, fetched) = messages_mock_node();
+    let messages = client(&url)
+        .find_messages_with_limit(&["iota.rs", "iota rs"], &[], Some(2))
+        .await
+        .unwrap();
+    assert_eq!(requested_ids(&messages), vec![MESSAGE_ID_1, MESSAGE_ID_2]);
+    assert_eq!(fetched.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_find_messages_unknown_index() {
+    let (url, _) = messages_mock_node();
+    let result = client(&url).find_messages(&["unknown"], &[]).await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,118 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Output search tests against a mock node where some lookups fail.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{start_mock_node, MESSAGE_ID};
+use iota_client::{Bech32Address, Error, OutputLookup};
+use std::str::FromStr;
+
+const ADDRESS: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+const ADDRESS_HEX: &str = "644eb5594b880f7da82f35902296199a0eb9186999797f661b324f8d754a3a09";
+// The node fails to look up the outputs of this address.
+const FAILING_ADDRESS: &str = "iot1q95jpvtk7cf7c7l9ne50c684jl4n8ya0srm5clpak7qes9ratu0eyf5eyz5";
+const OUTPUT_ID_1: &str = "00000000000000000000000000000000000000000000000000000000000000010000";
+const OUTPUT_ID_2: &str = "00000000000000000000000000000000000000000000000000000000000000020100";
+// An output the node doesn't know.
+const UNKNOWN_OUTPUT_ID: &str = "00000000000000000000000000000000000000000000000000000000000000030000";
+
+fn client() -> iota_client::Client {
+    let url = start_mock_node(|method, path, _| {
+        if method != "GET" {
+            ("404 Not Found", String::new())
+        } else if path == format!("/api/v1/addresses/{}/outputs", ADDRESS) {
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "address": ADDRESS,
+                    "maxResults": 1000,
+                    "count": 2,
+                    "outputIds": [OUTPUT_ID_1, OUTPUT_ID_2],
+                }})
+                .to_string(),
+            )
+        } else if let Some(output_id) = path
+            .strip_prefix("/api/v1/outputs/")
+            .filter(|id| *id == OUTPUT_ID_1 || *id == OUTPUT_ID_2)
+        {
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "messageId": MESSAGE_ID,
+                    "transactionId": &output_id[..64],
+                    "outputIndex": u16::from_le_bytes([hex::decode(&output_id[64..]).unwrap()[0], 0]),
+                    "isSpent": false,
+                    "output": {
+                        "type": 0,
+                        "address": { "type": 1, "address": ADDRESS_HEX },
+                        "amount": 1_000_000,
+                    },
+                }})
+                .to_string(),
+            )
+        } else if path == format!("/api/v1/addresses/{}/outputs", FAILING_ADDRESS) {
+            ("500 Internal Server Error", String::new())
+        } else {
+            ("404 Not Found", String::new())
+        }
+    });
+    iota_client::Client::build()
+        .with_node(&url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+fn output_id(output_id: &str) -> UTXOInput {
+    UTXOInput::from_str(output_id).unwrap()
+}
+
+#[tokio::test]
+async fn test_find_outputs_settled() {
+    let (outputs, errors) = client()
+        .find_outputs_settled(
+            &[output_id(OUTPUT_ID_1), output_id(UNKNOWN_OUTPUT_ID)],
+            &[
+                Bech32Address(ADDRESS.to_string()),
+                Bech32Address(FAILING_ADDRESS.to_string()),
+            ],
+        )
+        .await;
+
+    // the output 1 is requested by id and through the address, but only fetched once
+    let output_indexes = outputs.iter().map(|output| output.output_index).collect::<Vec<u16>>();
+    assert_eq!(output_indexes, vec![0, 1]);
+
+    let lookups = errors
+        .iter()
+        .map(|(lookup, _)| lookup.clone())
+        .collect::<Vec<OutputLookup>>();
+    assert_eq!(
+        lookups,
+        vec![
+            OutputLookup::Address(Bech32Address(FAILING_ADDRESS.to_string())),
+            OutputLookup::Output(output_id(UNKNOWN_OUTPUT_ID)),
+        ]
+    );
+    assert!(matches!(errors[0].1, Error::ResponseError(500, _, _)));
+    assert!(matches!(errors[1].1, Error::ResponseError(404, _, _)));
+}
+
+#[tokio::test]
+async fn test_find_outputs() {
+    let outputs = client()
+        .find_outputs(&[output_id(OUTPUT_ID_2)], &[Bech32Address(ADDRESS.to_string())])
+        .await
+        .unwrap();
+    assert_eq!(outputs.len(), 2);
+
+    // a single failed lookup fails the search
+    let result = client()
+        .find_outputs(&[output_id(UNKNOWN_OUTPUT_ID)], &[Bech32Address(ADDRESS.to_string())])
+        .await;
+    assert!(matches!(result, Err(Error::ResponseError(404, _, _))));
+}
@@ -0,0 +1,135 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Message lookup tests against a mock node holding two messages, a corrupted message and an index without messages.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::start_mock_node;
+use iota_client::{Client, Error};
+use std::str::FromStr;
+
+const MESSAGE_ID_1: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const MESSAGE_ID_2: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+const UNKNOWN_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000003";
+const CORRUPTED_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000004";
+
+fn messages_mock_node() -> String {
+    start_mock_node(move |method, path, _| {
+        if method != "GET" {
+            return ("404 Not Found", String::new());
+        }
+        match path.strip_prefix("/api/v1/messages/") {
+            Some(message_id) if message_id == MESSAGE_ID_1 || message_id == MESSAGE_ID_2 => (
+                "200 OK",
+                // the payload data holds the requested id so the messages can be told apart
+                serde_json::json!({ "data": {
+                    "networkId": "1",
+                    "parent1MessageId": MESSAGE_ID_1,
+                    "parent2MessageId": MESSAGE_ID_1,
+                    "payload": { "type": 2, "index": "iota.rs", "data": hex::encode(message_id) },
+                    "nonce": "0",
+                }})
+                .to_string(),
+            ),
+            // the payload data isn't hex encoded
+            Some(CORRUPTED_MESSAGE_ID) => (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "networkId": "1",
+                    "parent1MessageId": MESSAGE_ID_1,
+                    "parent2MessageId": MESSAGE_ID_1,
+                    "payload": { "type": 2, "index": "iota.rs", "data": "not hex" },
+                    "nonce": "0",
+                }})
+                .to_string(),
+            ),
+            Some(_) => ("404 Not Found", String::new()),
+            None if path.starts_with("/api/v1/messages?index=") => {
+                let message_ids: &[&str] = if path.ends_with("=corrupted") {
+                    &[MESSAGE_ID_1, CORRUPTED_MESSAGE_ID]
+                } else {
+                    &[]
+                };
+                (
+                    "200 OK",
+                    serde_json::json!({ "data": {
+                        "index": "",
+                        "maxResults": 1000,
+                        "count": message_ids.len(),
+                        "messageIds": message_ids,
+                    }})
+                    .to_string(),
+                )
+            }
+            None => ("404 Not Found", String::new()),
+        }
+    })
+}
+
+fn client(url: &str) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+fn message_id(message_id: &str) -> MessageId {
+    MessageId::from_str(message_id).unwrap()
+}
+
+// Returns the id held in the payload of the message.
+fn requested_id(message: &Message) -> String {
+    match message.payload() {
+        Some(Payload::Indexation(indexation)) => String::from_utf8(indexation.data().to_vec()).unwrap(),
+        _ => panic!("the message doesn't have an indexation payload"),
+    }
+}
+
+#[tokio::test]
+async fn test_get_messages() {
+    let url = messages_mock_node();
+    let messages = client(&url)
+        .get_messages(&[
+            message_id(MESSAGE_ID_2),
+            message_id(UNKNOWN_MESSAGE_ID),
+            message_id(MESSAGE_ID_1),
+        ])
+        .await
+        .unwrap();
+    let requested_ids: Vec<Option<String>> = messages
+        .iter()
+        .map(|message| message.as_ref().map(requested_id))
+        .collect();
+    assert_eq!(
+        requested_ids,
+        vec![Some(MESSAGE_ID_2.to_string()), None, Some(MESSAGE_ID_1.to_string())]
+    );
+    assert!(client(&url).get_messages(&[]).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_corrupted_message() {
+    let url = messages_mock_node();
+    let result = client(&url)
+        .get_messages(&[message_id(MESSAGE_ID_1), message_id(CORRUPTED_MESSAGE_ID)])
+        .await;
+    match result {
+        Err(Error::InvalidMessage(message_id, _)) => assert_eq!(message_id, CORRUPTED_MESSAGE_ID),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_find_messages() {
+    let url = messages_mock_node();
+    let client = client(&url);
+    assert!(client.find_messages(&["empty"], &[]).await.unwrap().is_empty());
+    match client.find_messages(&["corrupted"], &[]).await {
+        Err(Error::InvalidMessage(message_id, _)) => assert_eq!(message_id, CORRUPTED_MESSAGE_ID),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Included message API tests against a mock node replaying a captured transaction message.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::start_mock_node;
+use iota_client::Error;
+use std::str::FromStr;
+
+const INCLUDED_TRANSACTION_ID: &str = "939e770bbaeb805324030e052048cc448b86a74139158f4d0800b6144e127437";
+const PENDING_TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+
+const TRANSACTION_MESSAGE: &str = r#"{
+    "data": {
+        "networkId": "6530425480034647824",
+        "parent1MessageId": "5054db5972192fd4c3d582f6f32ec73535378f93f08928ea6785a33ebdb39826",
+        "parent2MessageId": "1ea01cbf6f148e855551ab56c02da7881253939011f4612e89dc5e292dc83114",
+        "payload": {
+            "type": 0,
+            "essence": {
+                "type": 0,
+                "inputs": [
+                    {
+                        "type": 0,
+                        "transactionId": "939e770bbaeb805324030e052048cc448b86a74139158f4d0800b6144e127437",
+                        "transactionOutputIndex": 1
+                    }
+                ],
+                "outputs": [
+                    {
+                        "type": 0,
+                        "address": {
+                            "type": 1,
+                            "address": "644eb5594b880f7da82f35902296199a0eb9186999797f661b324f8d754a3a09"
+                        },
+                        "amount": 1000000
+                    }
+                ],
+                "payload": {
+                    "type": 2,
+                    "index": "IOTA.RS TRANSACTION",
+                    "data": "494f54412e525320"
+                }
+            },
+            "unlockBlocks": [
+                {
+                    "type": 0,
+                    "signature": {
+                        "type": 1,
+                        "publicKey": "dda7a4395bd2035f6fee2d060ce844101efb612f3bcf7f49e749683f124014f8",
+                        "signature": "1a6ddcc49f0715395f0c8dcfd106a1833624abd85679370b4c9603c1e9c6d80a3ecc63c1ba1ffe1ffa98b64b2cf610ce3df86c6b8470e4006e7fef189df1ebde"
+                    }
+                }
+            ]
+        },
+        "nonce": "7378697629483838793"
+    }
+}"#;
+
+const NOT_FOUND: &str = r#"{"error":{"code":"404","message":"included message not found"}}"#;
+
+fn client() -> iota_client::Client {
+    let included_message_path = format!("/api/v1/transactions/{}/included-message", INCLUDED_TRANSACTION_ID);
+    let url = start_mock_node(move |method, path, _| match method {
+        "GET" if path == included_message_path => ("200 OK", TRANSACTION_MESSAGE.to_string()),
+        _ => ("404 Not Found", NOT_FOUND.to_string()),
+    });
+    iota_client::Client::build()
+        .with_node(&url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_get_included_message() {
+    let transaction_id = TransactionId::from_str(INCLUDED_TRANSACTION_ID).unwrap();
+    let message = client().get_included_message(&transaction_id).await.unwrap();
+    assert_eq!(
+        message.parent1().to_string(),
+        "5054db5972192fd4c3d582f6f32ec73535378f93f08928ea6785a33ebdb39826"
+    );
+    match message.payload() {
+        Some(Payload::Transaction(transaction)) => assert_eq!(transaction.unlock_blocks().len(), 1),
+        _ => panic!("the included message must have a transaction payload"),
+    }
+}
+
+#[tokio::test]
+async fn test_get_included_message_not_included() {
+    let transaction_id = TransactionId::from_str(PENDING_TRANSACTION_ID).unwrap();
+    let result = client().get_included_message(&transaction_id).await;
+    assert!(matches!(result, Err(Error::TransactionNotIncluded(id)) if id == PENDING_TRANSACTION_ID));
+}
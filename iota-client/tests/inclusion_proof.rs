@@ -0,0 +1,345 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Inclusion proof tests against a mock tangle signed by a test coordinator: the milestone 5 references the message
+// `direct` as a parent and the message `deep` through the chain `milestone -> top -> middle -> deep`, every message
+// also approves an older message referenced by the milestone 4. The tangle is generated, a devnet coordinator key
+// can't sign the test messages. `PROOF_FIXTURE_CHAIN` was packed and signed apart from this crate, as in RFC-0019.
+
+mod common;
+
+use bee_common::packable::Packable;
+use bee_message::{
+    payload::milestone::{MilestonePayloadEssence, MILESTONE_MERKLE_PROOF_LENGTH},
+    prelude::*,
+};
+use bee_pow::providers::{Constant, ConstantBuilder};
+use bee_signing_ext::{binary::Ed25519PrivateKey, Signer};
+use common::{start_mock_node_with_bytes, MESSAGE_ID};
+use iota_client::{
+    api::{milestone_essence_hash, verify_inclusion_proof, CoordinatorKeys, CoordinatorPublicKey, InclusionProof},
+    compute_message_id, Client, Error,
+};
+use std::{collections::HashMap, str::FromStr};
+
+const MILESTONE_INDEX: u32 = 5;
+const MILESTONE_TIMESTAMP: u64 = 1_000;
+
+// The proof of an indexation message referenced by the milestone 600001 of a coordinator of the mainnet key ranges,
+// signed by the keys of `PROOF_FIXTURE_PUBLIC_KEYS`.
+const PROOF_FIXTURE_MESSAGE_ID: &str = "7baf733f3581f999f691c2937fa335f501645480177a7bb89ead572caa2ce414";
+const PROOF_FIXTURE_MILESTONE_INDEX: u32 = 600001;
+const PROOF_FIXTURE_CHAIN: [&str; 2] = [
+    concat!(
+        "9a55fde341bd7f9a7baf733f3581f999f691c2937fa335f501645480177a7bb89ead572caa2ce4148f1ae7a3b7d1f2c0",
+        "4e6ee3a2b9d24c1e4a1f7b0b6a4fb1c3e3d28f5c0a9d1e773201000001000000c127090081364f60000000007baf733f",
+        "3581f999f691c2937fa335f501645480177a7bb89ead572caa2ce4148f1ae7a3b7d1f2c04e6ee3a2b9d24c1e4a1f7b0b",
+        "6a4fb1c3e3d28f5c0a9d1e770b4bdf0ad2231230da4493b5a81a3e64f59414f9dd796e03eae344886ebd6a2f026e3b11",
+        "48fbb944889b9070a13a603987f7e75d49a0239ae719d6abb3c2d9e0d8fde48071bc24b50b6ab53e557d90c50f68d617",
+        "e89a04ce4204939aab91a5b7da027b7372e9987895e2178613a56ab0b312b669da399993ad703d6fed0c1b44a56cc89e",
+        "fcd2b0a15d533b7e158cd7ee082577ef0f4ade0d4127c44bcaca09904b0ecd4a3e335fd7f3bd0fdc780684f21fc310d0",
+        "54db00dd6be1766f51d4eda0424e43e42cb4f4ae8936a8a87ac7e514ac9df4f34567c7740d541b473cac390b14020000",
+        "000000000000",
+    ),
+    concat!(
+        "9a55fde341bd7f9a8f1ae7a3b7d1f2c04e6ee3a2b9d24c1e4a1f7b0b6a4fb1c3e3d28f5c0a9d1e778f1ae7a3b7d1f2c0",
+        "4e6ee3a2b9d24c1e4a1f7b0b6a4fb1c3e3d28f5c0a9d1e772c000000020000001700494f54412e525320494e434c5553",
+        "494f4e2050524f4f460b00000050524f56454e20444154410000000000000000",
+    ),
+];
+const PROOF_FIXTURE_PUBLIC_KEYS: [(&str, u32, u32); 2] = [
+    (
+        "6e3b1148fbb944889b9070a13a603987f7e75d49a0239ae719d6abb3c2d9e0d8",
+        0,
+        777600,
+    ),
+    (
+        "fde48071bc24b50b6ab53e557d90c50f68d617e89a04ce4204939aab91a5b7da",
+        552960,
+        2108160,
+    ),
+];
+
+fn build_message(parents: (MessageId, MessageId), payload: Payload) -> Message {
+    MessageBuilder::<Constant>::new()
+        .with_network_id(1)
+        .with_parent1(parents.0)
+        .with_parent2(parents.1)
+        .with_payload(payload)
+        .with_nonce_provider(ConstantBuilder::new().with_value(0).finish(), 0f64)
+        .finish()
+        .unwrap()
+}
+
+fn indexation_message(data: &str, parents: (MessageId, MessageId)) -> Message {
+    build_message(
+        parents,
+        Payload::Indexation(Box::new(
+            IndexationPayload::new("IOTA.RS".to_string(), data.as_bytes()).unwrap(),
+        )),
+    )
+}
+
+fn coordinator_key(byte: u8) -> Ed25519PrivateKey {
+    Ed25519PrivateKey::from_bytes(&[byte; 32]).unwrap()
+}
+
+fn public_key(key: &Ed25519PrivateKey) -> [u8; 32] {
+    key.generate_public_key().to_bytes()
+}
+
+// The test coordinator: the keys 1 and 2 sign every milestone.
+fn coordinator_keys() -> CoordinatorKeys {
+    CoordinatorKeys {
+        public_keys: vec![1, 2]
+            .into_iter()
+            .map(|byte| CoordinatorPublicKey {
+                public_key: hex::encode(public_key(&coordinator_key(byte))),
+                start: 0,
+                end: None,
+            })
+            .collect(),
+        signature_count: 2,
+    }
+}
+
+fn milestone_message(parents: (MessageId, MessageId), keys: &[Ed25519PrivateKey]) -> Message {
+    let essence = MilestonePayloadEssence::new(
+        MILESTONE_INDEX,
+        MILESTONE_TIMESTAMP,
+        parents.0,
+        parents.1,
+        [0u8; MILESTONE_MERKLE_PROOF_LENGTH],
+        keys.iter().map(public_key).collect(),
+    );
+    let signatures = keys
+        .iter()
+        .map(|key| Box::new(key.sign(&milestone_essence_hash(&essence)).to_bytes()) as Box<[u8]>)
+        .collect();
+    build_message(
+        parents,
+        Payload::Milestone(Box::new(MilestonePayload::new(essence, signatures))),
+    )
+}
+
+struct Tangle {
+    url: String,
+    milestone: MessageId,
+    direct: MessageId,
+    deep: MessageId,
+    unreferenced: MessageId,
+}
+
+fn tangle() -> Tangle {
+    let old = MessageId::from_str(MESSAGE_ID).unwrap();
+    let deep = indexation_message("deep", (old, old));
+    let middle = indexation_message("middle", (old, deep.id().0));
+    let top = indexation_message("top", (middle.id().0, old));
+    let direct = indexation_message("direct", (old, old));
+    let milestone = milestone_message((top.id().0, direct.id().0), &[coordinator_key(1), coordinator_key(2)]);
+    let unreferenced = indexation_message("unreferenced", (milestone.id().0, old));
+
+    // the packed messages with the milestone referencing them
+    let mut messages: HashMap<MessageId, (Vec<u8>, Option<u32>)> = HashMap::new();
+    messages.insert(old, (indexation_message("old", (old, old)).pack_new(), Some(4)));
+    for message in &[&deep, &middle, &top, &direct, &milestone] {
+        messages.insert(message.id().0, (message.pack_new(), Some(MILESTONE_INDEX)));
+    }
+    messages.insert(unreferenced.id().0, (unreferenced.pack_new(), None));
+    let milestone_id = milestone.id().0;
+
+    let url = start_mock_node_with_bytes(move |method, path, _, _| {
+        let message = |suffix: &str| {
+            path.strip_prefix("/api/v1/messages/")
+                .and_then(|path| path.strip_suffix(suffix))
+                .and_then(|id| {
+                    messages
+                        .get(&MessageId::from_str(id).ok()?)
+                        .map(|message| (id, message))
+                })
+        };
+        let answer = if method != "GET" {
+            None
+        } else if let Some((id, (packed, milestone_index))) = message("/metadata") {
+            let parsed = Message::unpack(&mut packed.as_slice()).unwrap();
+            Some(
+                serde_json::json!({ "data": {
+                    "messageId": id,
+                    "parent1MessageId": parsed.parent1().to_string(),
+                    "parent2MessageId": parsed.parent2().to_string(),
+                    "isSolid": true,
+                    "referencedByMilestoneIndex": milestone_index,
+                }})
+                .to_string()
+                .into_bytes(),
+            )
+        } else if let Some((_, (packed, _))) = message("/raw") {
+            Some(packed.clone())
+        } else if path == format!("/api/v1/milestones/{}", MILESTONE_INDEX) {
+            Some(
+                serde_json::json!({ "data": {
+                    "index": MILESTONE_INDEX,
+                    "messageId": milestone_id.to_string(),
+                    "timestamp": MILESTONE_TIMESTAMP,
+                }})
+                .to_string()
+                .into_bytes(),
+            )
+        } else {
+            None
+        };
+        match answer {
+            Some(answer) => ("200 OK", answer),
+            None => ("404 Not Found", Vec::new()),
+        }
+    });
+    Tangle {
+        url,
+        milestone: milestone.id().0,
+        direct: direct.id().0,
+        deep: deep.id().0,
+        unreferenced: unreferenced.id().0,
+    }
+}
+
+fn client(url: &str) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+fn chain_ids(chain: &[String]) -> Vec<MessageId> {
+    chain
+        .iter()
+        .map(|packed| compute_message_id(&hex::decode(packed).unwrap()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_inclusion_proof_of_a_milestone_parent() {
+    let tangle = tangle();
+    let proof = client(&tangle.url)
+        .create_inclusion_proof(&tangle.direct)
+        .await
+        .unwrap();
+    assert_eq!(proof.message_id, tangle.direct.to_string());
+    assert_eq!(proof.milestone_index, MILESTONE_INDEX);
+    assert_eq!(chain_ids(&proof.chain), vec![tangle.milestone, tangle.direct]);
+    verify_inclusion_proof(&proof, &coordinator_keys()).unwrap();
+
+    // the milestone isn't signed by the mainnet coordinator
+    assert!(matches!(
+        verify_inclusion_proof(&proof, &CoordinatorKeys::mainnet()),
+        Err(Error::InvalidInclusionProof(0, reason)) if reason.contains("doesn't sign the milestone 5")
+    ));
+    // the key 2 doesn't sign the milestone 5 anymore
+    let mut keys = coordinator_keys();
+    keys.public_keys[1].end = Some(MILESTONE_INDEX);
+    assert!(matches!(
+        verify_inclusion_proof(&proof, &keys),
+        Err(Error::InvalidInclusionProof(0, reason)) if reason.contains("doesn't sign the milestone 5")
+    ));
+    // the coordinator signs with more keys
+    let mut keys = coordinator_keys();
+    keys.signature_count = 3;
+    assert!(matches!(
+        verify_inclusion_proof(&proof, &keys),
+        Err(Error::InvalidInclusionProof(0, reason)) if reason.contains("2 signatures")
+    ));
+}
+
+#[tokio::test]
+async fn test_inclusion_proof_through_a_chain_of_parents() {
+    let tangle = tangle();
+    let proof = client(&tangle.url).create_inclusion_proof(&tangle.deep).await.unwrap();
+    let chain = chain_ids(&proof.chain);
+    assert_eq!(chain.len(), 4);
+    assert_eq!((chain[0], chain[3]), (tangle.milestone, tangle.deep));
+
+    // the proof is verified from its JSON, without the node
+    let json = serde_json::to_string(&proof).unwrap();
+    let proof: InclusionProof = serde_json::from_str(&json).unwrap();
+    verify_inclusion_proof(&proof, &coordinator_keys()).unwrap();
+}
+
+#[tokio::test]
+async fn test_tampered_inclusion_proof() {
+    let tangle = tangle();
+    let proof = client(&tangle.url).create_inclusion_proof(&tangle.deep).await.unwrap();
+    let keys = coordinator_keys();
+    let broken_link = |proof: InclusionProof| match verify_inclusion_proof(&proof, &keys) {
+        Err(Error::InvalidInclusionProof(link, _)) => link,
+        r => panic!("expected an invalid inclusion proof, got {:?}", r),
+    };
+
+    // another payload in the middle message changes its id, it isn't a parent of the top message anymore
+    let mut tampered = proof.clone();
+    let middle = Message::unpack(&mut hex::decode(&proof.chain[2]).unwrap().as_slice()).unwrap();
+    let forged = indexation_message("forged", (*middle.parent1(), *middle.parent2()));
+    tampered.chain[2] = hex::encode(forged.pack_new());
+    assert_eq!(broken_link(tampered), 2);
+
+    // a missing link
+    let mut tampered = proof.clone();
+    tampered.chain.remove(2);
+    assert_eq!(broken_link(tampered), 2);
+
+    // the chain doesn't end at the proven message
+    let mut tampered = proof.clone();
+    tampered.message_id = tangle.direct.to_string();
+    assert_eq!(broken_link(tampered), 3);
+
+    // a milestone of another index
+    let mut tampered = proof.clone();
+    tampered.milestone_index = MILESTONE_INDEX + 1;
+    assert_eq!(broken_link(tampered), 0);
+
+    // bytes that aren't a packed message
+    let mut tampered = proof;
+    tampered.chain[1].push_str("00");
+    assert_eq!(broken_link(tampered), 1);
+}
+
+#[test]
+fn test_inclusion_proof_fixture() {
+    let keys = CoordinatorKeys {
+        public_keys: PROOF_FIXTURE_PUBLIC_KEYS
+            .iter()
+            .map(|(public_key, start, end)| CoordinatorPublicKey {
+                public_key: public_key.to_string(),
+                start: *start,
+                end: Some(*end),
+            })
+            .collect(),
+        signature_count: 2,
+    };
+    let proof = InclusionProof {
+        message_id: PROOF_FIXTURE_MESSAGE_ID.to_string(),
+        milestone_index: PROOF_FIXTURE_MILESTONE_INDEX,
+        chain: PROOF_FIXTURE_CHAIN.iter().map(|packed| packed.to_string()).collect(),
+    };
+    verify_inclusion_proof(&proof, &keys).unwrap();
+
+    // a flipped byte of the last milestone signature, before the nonce
+    let mut tampered = proof;
+    let mut milestone = hex::decode(&tampered.chain[0]).unwrap();
+    let length = milestone.len();
+    milestone[length - 9] ^= 1;
+    tampered.chain[0] = hex::encode(milestone);
+    assert!(matches!(
+        verify_inclusion_proof(&tampered, &keys),
+        Err(Error::InvalidInclusionProof(0, reason)) if reason.contains("doesn't sign the essence")
+    ));
+}
+
+#[tokio::test]
+async fn test_inclusion_proof_of_an_unreferenced_message() {
+    let tangle = tangle();
+    assert!(matches!(
+        client(&tangle.url).create_inclusion_proof(&tangle.unreferenced).await,
+        Err(Error::MessageNotReferenced(id)) if id == tangle.unreferenced.to_string()
+    ));
+}
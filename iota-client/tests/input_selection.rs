@@ -0,0 +1,373 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Input selection tests over synthetic UTXO sets, and against a mock node for the selection of the send builder.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{utxo_mock_node_with_dust_allowances, MockOutput};
+use iota_client::{
+    api::{select_inputs, AccountAddress, InputCandidate, InputSelectionStrategy, InputSigningData, INPUT_COUNT_MAX},
+    Error, OutputType, Seed,
+};
+use std::str::FromStr;
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+const TRANSACTION_ID_1: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const TRANSACTION_ID_2: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+const TRANSACTION_ID_3: &str = "0000000000000000000000000000000000000000000000000000000000000003";
+const RECIPIENT: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+const MI: u64 = 1_000_000;
+
+fn input(index: u16) -> UTXOInput {
+    UTXOInput::new(TransactionId::from_str(TRANSACTION_ID_1).unwrap(), index).unwrap()
+}
+
+fn candidate(index: u16, amount: u64, output_type: OutputType) -> InputCandidate {
+    InputCandidate {
+        signing_data: InputSigningData {
+            input: input(index),
+            address: Address::Ed25519(Ed25519Address::new([index as u8; 32])),
+            address_index: index as usize,
+            internal: false,
+        },
+        amount,
+        output_type,
+    }
+}
+
+// Signature locked single outputs of the amounts, the output index is the position of the amount.
+fn utxo_set(amounts: &[u64]) -> Vec<InputCandidate> {
+    amounts
+        .iter()
+        .enumerate()
+        .map(|(index, amount)| candidate(index as u16, *amount, OutputType::SignatureLockedSingle))
+        .collect()
+}
+
+// The output indexes of the selected inputs, in their order.
+fn select(
+    candidates: &[InputCandidate],
+    amount: u64,
+    strategy: &InputSelectionStrategy,
+) -> iota_client::Result<Vec<u16>> {
+    Ok(select_inputs(candidates, amount, strategy, false)?
+        .into_iter()
+        .map(|candidate| candidate.signing_data.input.output_id().index())
+        .collect())
+}
+
+#[test]
+fn test_largest_first() {
+    let candidates = utxo_set(&[MI, 5 * MI, 3 * MI, 2 * MI]);
+    let strategy = InputSelectionStrategy::LargestFirst;
+    assert_eq!(select(&candidates, 6 * MI, &strategy).unwrap(), vec![1, 2]);
+    assert_eq!(select(&candidates, 5 * MI, &strategy).unwrap(), vec![1]);
+    assert_eq!(select(&candidates, 11 * MI, &strategy).unwrap(), vec![1, 2, 3, 0]);
+    assert_eq!(InputSelectionStrategy::default(), strategy);
+}
+
+#[test]
+fn test_smallest_first() {
+    let candidates = utxo_set(&[MI, 5 * MI, 3 * MI, 2 * MI]);
+    let strategy = InputSelectionStrategy::SmallestFirst;
+    assert_eq!(select(&candidates, 6 * MI, &strategy).unwrap(), vec![0, 3, 2]);
+    assert_eq!(select(&candidates, MI / 2, &strategy).unwrap(), vec![0]);
+    assert_eq!(select(&candidates, 11 * MI, &strategy).unwrap(), vec![0, 3, 2, 1]);
+}
+
+#[test]
+fn test_manual() {
+    let candidates = utxo_set(&[MI, 5 * MI, 3 * MI, 2 * MI]);
+    let strategy = InputSelectionStrategy::Manual(vec![input(0), input(1), input(2), input(3)]);
+    // the candidates are taken in their order until the amount is covered
+    assert_eq!(select(&candidates, 6 * MI, &strategy).unwrap(), vec![0, 1]);
+    assert_eq!(select(&candidates, 7 * MI, &strategy).unwrap(), vec![0, 1, 2]);
+}
+
+#[test]
+fn test_selection_is_deterministic() {
+    // the ties on the amount are broken by the output id, whatever the order of the UTXO set
+    let candidates = utxo_set(&[2 * MI, MI, 2 * MI, 2 * MI, MI]);
+    let mut reversed = candidates.clone();
+    reversed.reverse();
+    for strategy in &[
+        InputSelectionStrategy::LargestFirst,
+        InputSelectionStrategy::SmallestFirst,
+    ] {
+        assert_eq!(
+            select(&candidates, 3 * MI, strategy).unwrap(),
+            select(&reversed, 3 * MI, strategy).unwrap()
+        );
+    }
+    assert_eq!(
+        select(&candidates, 3 * MI, &InputSelectionStrategy::LargestFirst).unwrap(),
+        vec![3, 2]
+    );
+    assert_eq!(
+        select(&candidates, 3 * MI, &InputSelectionStrategy::SmallestFirst).unwrap(),
+        vec![1, 4, 0]
+    );
+}
+
+#[test]
+fn test_dust_allowance_outputs_are_skipped() {
+    let mut candidates = utxo_set(&[MI, 2 * MI]);
+    candidates.push(candidate(2, 10 * MI, OutputType::SignatureLockedDustAllowance));
+    let strategy = InputSelectionStrategy::LargestFirst;
+    assert_eq!(select(&candidates, 3 * MI, &strategy).unwrap(), vec![1, 0]);
+    assert!(matches!(
+        select(&candidates, 4 * MI, &strategy),
+        Err(Error::NotEnoughBalance(3_000_000, 4_000_000))
+    ));
+    let selected = select_inputs(&candidates, 4 * MI, &strategy, true).unwrap();
+    assert_eq!(selected, vec![candidates[2].clone()]);
+}
+
+#[test]
+fn test_input_count_limit() {
+    let candidates = utxo_set(&[MI; 200]);
+    for strategy in &[
+        InputSelectionStrategy::LargestFirst,
+        InputSelectionStrategy::SmallestFirst,
+    ] {
+        assert_eq!(
+            select(&candidates, INPUT_COUNT_MAX as u64 * MI, strategy)
+                .unwrap()
+                .len(),
+            INPUT_COUNT_MAX
+        );
+        // the balance is sufficient, but only with more inputs than a transaction can have
+        assert!(matches!(
+            select(&candidates, 150 * MI, strategy),
+            Err(Error::TooManyInputs(150, INPUT_COUNT_MAX))
+        ));
+        assert!(matches!(
+            select(&candidates, 201 * MI, strategy),
+            Err(Error::NotEnoughBalance(200_000_000, 201_000_000))
+        ));
+    }
+    let strategy = InputSelectionStrategy::Manual((0..200).map(input).collect());
+    assert!(matches!(
+        select(&candidates, 150 * MI, &strategy),
+        Err(Error::TooManyInputs(150, INPUT_COUNT_MAX))
+    ));
+}
+
+#[test]
+fn test_smallest_first_within_the_input_count_limit() {
+    // the smallest outputs alone would need more than the maximum of inputs, the largest output replaces one of them
+    let mut amounts = vec![MI; 200];
+    amounts.push(100 * MI);
+    let candidates = utxo_set(&amounts);
+    let selected = select(&candidates, 150 * MI, &InputSelectionStrategy::SmallestFirst).unwrap();
+    assert_eq!(selected.len(), INPUT_COUNT_MAX);
+    assert_eq!(selected.last(), Some(&200));
+    assert_eq!(
+        select(&candidates, 150 * MI, &InputSelectionStrategy::LargestFirst)
+            .unwrap()
+            .len(),
+        51
+    );
+}
+
+fn client(url: &str) -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(false)
+        .finish()
+        .unwrap()
+}
+
+// The first public addresses of the seed's first account followed by its first internal addresses.
+fn account_addresses() -> Vec<AccountAddress> {
+    let seed = Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap();
+    let (public, internal): (Vec<AccountAddress>, Vec<AccountAddress>) = client("http://localhost:14265")
+        .find_addresses(&seed)
+        .with_account_index(0)
+        .with_range(0..2)
+        .get_all()
+        .unwrap()
+        .into_iter()
+        .enumerate()
+        // the addresses are (public, internal) pairs for each index
+        .map(|(position, (address, internal))| AccountAddress {
+            address: Address::try_from_bech32(&address.to_string()).unwrap(),
+            address_index: position / 2,
+            internal,
+        })
+        .partition(|address| !address.internal);
+    public.into_iter().chain(internal).collect()
+}
+
+// The ids of the inputs of the prepared transaction, in the protocol order of the essence inputs.
+async fn prepared_inputs(
+    url: &str,
+    amount: u64,
+    strategy: InputSelectionStrategy,
+    include_dust_allowance: bool,
+) -> iota_client::Result<Vec<(String, u16)>> {
+    let prepared = client(url)
+        .send()
+        .with_account_addresses(account_addresses())
+        .with_output(&RECIPIENT.to_string().into(), amount)?
+        .with_input_selection_strategy(strategy)
+        .with_dust_allowance_inputs(include_dust_allowance)
+        .with_dust_check_disabled()
+        .prepare_transaction()
+        .await?;
+    Ok(prepared
+        .inputs
+        .iter()
+        .map(|input| {
+            (
+                input.input.output_id().transaction_id().to_string(),
+                input.input.output_id().index(),
+            )
+        })
+        .collect())
+}
+
+#[tokio::test]
+async fn test_send_builder_strategies() {
+    // 2 Mi and 3 Mi on the address 0, 1 Mi on the address 1, and a dust allowance of 10 Mi on the address 1
+    let addresses: Vec<String> = account_addresses()
+        .iter()
+        .map(|address| address.address.to_bech32())
+        .collect();
+    let outputs = vec![
+        MockOutput {
+            transaction_id: TRANSACTION_ID_1,
+            index: 0,
+            address: addresses[0].clone(),
+            amount: 2 * MI,
+        },
+        MockOutput {
+            transaction_id: TRANSACTION_ID_1,
+            index: 1,
+            address: addresses[0].clone(),
+            amount: 3 * MI,
+        },
+        MockOutput {
+            transaction_id: TRANSACTION_ID_2,
+            index: 0,
+            address: addresses[1].clone(),
+            amount: MI,
+        },
+    ];
+    let dust_allowance = MockOutput {
+        transaction_id: TRANSACTION_ID_3,
+        index: 0,
+        address: addresses[1].clone(),
+        amount: 10 * MI,
+    };
+    let (url, _) = utxo_mock_node_with_dust_allowances(outputs, vec![dust_allowance]);
+    let id = |transaction_id: &str, index: u16| (transaction_id.to_string(), index);
+
+    let inputs = prepared_inputs(&url, 4 * MI, InputSelectionStrategy::LargestFirst, false)
+        .await
+        .unwrap();
+    // the selected inputs are sorted whatever the strategy
+    assert_eq!(inputs, vec![id(TRANSACTION_ID_1, 0), id(TRANSACTION_ID_1, 1)]);
+    let inputs = prepared_inputs(&url, 4 * MI, InputSelectionStrategy::SmallestFirst, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        inputs,
+        vec![
+            id(TRANSACTION_ID_1, 0),
+            id(TRANSACTION_ID_1, 1),
+            id(TRANSACTION_ID_2, 0)
+        ]
+    );
+    let manual = InputSelectionStrategy::Manual(vec![
+        UTXOInput::new(TransactionId::from_str(TRANSACTION_ID_2).unwrap(), 0).unwrap(),
+        UTXOInput::new(TransactionId::from_str(TRANSACTION_ID_1).unwrap(), 1).unwrap(),
+    ]);
+    let inputs = prepared_inputs(&url, 4 * MI, manual, false).await.unwrap();
+    assert_eq!(inputs, vec![id(TRANSACTION_ID_1, 1), id(TRANSACTION_ID_2, 0)]);
+
+    // the dust allowance output is only spent if allowed
+    let result = prepared_inputs(&url, 7 * MI, InputSelectionStrategy::LargestFirst, false).await;
+    assert!(matches!(result, Err(Error::NotEnoughBalance(6_000_000, 7_000_000))));
+    let inputs = prepared_inputs(&url, 7 * MI, InputSelectionStrategy::LargestFirst, true)
+        .await
+        .unwrap();
+    assert_eq!(inputs, vec![id(TRANSACTION_ID_3, 0)]);
+}
+
+// The ids of the inputs of the prepared transaction with the dust check, selecting the dust allowance outputs.
+async fn prepared_inputs_with_dust_check(url: &str, amount: u64) -> iota_client::Result<Vec<(String, u16)>> {
+    let prepared = client(url)
+        .send()
+        .with_account_addresses(account_addresses())
+        .with_output(&RECIPIENT.to_string().into(), amount)?
+        .with_dust_allowance_inputs(true)
+        .prepare_transaction()
+        .await?;
+    Ok(prepared
+        .inputs
+        .iter()
+        .map(|input| {
+            (
+                input.input.output_id().transaction_id().to_string(),
+                input.input.output_id().index(),
+            )
+        })
+        .collect())
+}
+
+#[tokio::test]
+async fn test_dust_allowance_inputs() {
+    // a dust output of 0.5 Mi on the address 0, 2 Mi on the address 1, and dust allowance outputs of 1 Mi on the
+    // address 0
+    let addresses: Vec<String> = account_addresses()
+        .iter()
+        .map(|address| address.address.to_bech32())
+        .collect();
+    let outputs = || {
+        vec![
+            MockOutput {
+                transaction_id: TRANSACTION_ID_1,
+                index: 0,
+                address: addresses[0].clone(),
+                amount: MI / 2,
+            },
+            MockOutput {
+                transaction_id: TRANSACTION_ID_2,
+                index: 0,
+                address: addresses[1].clone(),
+                amount: 2 * MI,
+            },
+        ]
+    };
+    let dust_allowance = |index: u16| MockOutput {
+        transaction_id: TRANSACTION_ID_3,
+        index,
+        address: addresses[0].clone(),
+        amount: MI,
+    };
+    let id = |transaction_id: &str, index: u16| (transaction_id.to_string(), index);
+
+    // the single dust allowance output backs the dust output
+    let (url, _) = utxo_mock_node_with_dust_allowances(outputs(), vec![dust_allowance(0)]);
+    match prepared_inputs_with_dust_check(&url, 3 * MI).await {
+        Err(Error::DustAllowanceInput(address, 1, 0)) => assert_eq!(address, addresses[0]),
+        result => panic!("unexpected result {:?}", result),
+    }
+    // the dust allowance outputs aren't selected by default, the dust check can be disabled
+    let result = prepared_inputs(&url, 3 * MI, InputSelectionStrategy::LargestFirst, false).await;
+    assert!(matches!(result, Err(Error::NotEnoughBalance(2_500_000, 3_000_000))));
+    let inputs = prepared_inputs(&url, 3 * MI, InputSelectionStrategy::LargestFirst, true)
+        .await
+        .unwrap();
+    assert_eq!(inputs, vec![id(TRANSACTION_ID_2, 0), id(TRANSACTION_ID_3, 0)]);
+
+    // the other dust allowance output still backs the dust output
+    let (url, _) = utxo_mock_node_with_dust_allowances(outputs(), vec![dust_allowance(0), dust_allowance(1)]);
+    let inputs = prepared_inputs_with_dust_check(&url, 3 * MI).await.unwrap();
+    assert_eq!(inputs, vec![id(TRANSACTION_ID_2, 0), id(TRANSACTION_ID_3, 1)]);
+}
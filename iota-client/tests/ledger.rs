@@ -0,0 +1,122 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Ledger Nano signer tests, run against the Speculos simulator with the IOTA app and the automatic approval of the
+// prompts: `cargo test --features ledger --test ledger -- --ignored`.
+
+#![cfg(feature = "ledger")]
+
+use bee_common::packable::Packable;
+use bee_message::prelude::*;
+use bee_signing_ext::{
+    binary::{Ed25519PublicKey, Ed25519Signature as Ed25519EssenceSignature},
+    Verifier,
+};
+use blake2::{
+    digest::{Update, VariableOutput},
+    VarBlake2b,
+};
+use iota_client::{
+    api::{InputSigningData, PreparedTransactionData},
+    LedgerSigner,
+};
+use std::str::FromStr;
+
+const TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const RECIPIENT: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+
+fn client() -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node("http://localhost:14265")
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+fn essence_hash(essence: &[u8]) -> Vec<u8> {
+    let mut hash = Vec::new();
+    let mut hasher = VarBlake2b::new(32).unwrap();
+    hasher.update(essence);
+    hasher.finalize_variable(|digest| hash.extend_from_slice(digest));
+    hash
+}
+
+#[test]
+#[ignore]
+fn test_ledger_displays_the_derived_address() {
+    let ledger = LedgerSigner::new(true).unwrap();
+    let addresses = client()
+        .find_addresses(&ledger)
+        .with_account_index(0)
+        .with_range(0..1)
+        .finish()
+        .unwrap();
+    let displayed = ledger.display_address(0, 0, false).unwrap();
+    assert_eq!(displayed.to_bech32("iot"), addresses[0].to_string());
+}
+
+#[test]
+#[ignore]
+fn test_ledger_signs_the_submitted_essence() {
+    let ledger = LedgerSigner::new(true).unwrap();
+    let addresses = client()
+        .find_addresses(&ledger)
+        .with_account_index(0)
+        .with_range(0..2)
+        .finish()
+        .unwrap();
+    let inputs: Vec<UTXOInput> = (0..3)
+        .map(|index| UTXOInput::new(TransactionId::from_str(TRANSACTION_ID).unwrap(), index).unwrap())
+        .collect();
+    let mut essence = TransactionPayloadEssence::builder();
+    for input in &inputs {
+        essence = essence.add_input(Input::UTXO(input.clone()));
+    }
+    let essence = essence
+        .add_output(
+            SignatureLockedSingleOutput::new(Address::try_from_bech32(RECIPIENT).unwrap(), 3_000_000)
+                .unwrap()
+                .into(),
+        )
+        .finish()
+        .unwrap();
+    // the inputs 0 and 2 are unlocked by the same address
+    let prepared = PreparedTransactionData {
+        essence,
+        account_index: 0,
+        inputs: inputs
+            .iter()
+            .zip([0, 1, 0].iter())
+            .map(|(input, address_index)| InputSigningData {
+                input: input.clone(),
+                address: Address::try_from_bech32(&addresses[*address_index].to_string()).unwrap(),
+                address_index: *address_index,
+                internal: false,
+            })
+            .collect(),
+        remainder: None,
+    };
+    let payload = prepared.sign(&ledger).unwrap();
+
+    let mut submitted_essence = Vec::new();
+    payload.essence().pack(&mut submitted_essence).unwrap();
+    let essence = prepared.essence_bytes().unwrap();
+    assert_eq!(essence_hash(&submitted_essence), essence_hash(&essence));
+
+    let signatures: Vec<&Ed25519Signature> = payload
+        .unlock_blocks()
+        .iter()
+        .filter_map(|unlock_block| match unlock_block {
+            UnlockBlock::Signature(SignatureUnlock::Ed25519(signature)) => Some(signature),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(signatures.len(), 2);
+    assert!(matches!(payload.unlock_blocks()[2], UnlockBlock::Reference(_)));
+    for signature in signatures {
+        let public_key = Ed25519PublicKey::from_bytes(signature.public_key()).unwrap();
+        let essence_signature = Ed25519EssenceSignature::from_bytes(signature.signature()).unwrap();
+        assert!(public_key.verify(&submitted_essence, &essence_signature).is_ok());
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Message id, transaction id and Ed25519 signature utilities, checked against ids computed independently from the
+// packed bytes of the messages.
+
+use bee_common::packable::Packable;
+use bee_message::prelude::*;
+use iota_client::{compute_message_id, compute_transaction_id, verify_ed25519_signature, Error, MessageJson};
+use std::convert::TryInto;
+
+const TRANSACTION_MESSAGE: &str = r#"{
+    "networkId": "6530425480034647824",
+    "parent1MessageId": "5054db5972192fd4c3d582f6f32ec73535378f93f08928ea6785a33ebdb39826",
+    "parent2MessageId": "1ea01cbf6f148e855551ab56c02da7881253939011f4612e89dc5e292dc83114",
+    "payload": {
+        "type": 0,
+        "essence": {
+            "type": 0,
+            "inputs": [
+                {
+                    "type": 0,
+                    "transactionId": "939e770bbaeb805324030e052048cc448b86a74139158f4d0800b6144e127437",
+                    "transactionOutputIndex": 1
+                }
+            ],
+            "outputs": [
+                {
+                    "type": 0,
+                    "address": {
+                        "type": 1,
+                        "address": "644eb5594b880f7da82f35902296199a0eb9186999797f661b324f8d754a3a09"
+                    },
+                    "amount": 1000000
+                }
+            ],
+            "payload": {
+                "type": 2,
+                "index": "IOTA.RS TRANSACTION",
+                "data": "494f54412e525320"
+            }
+        },
+        "unlockBlocks": [
+            {
+                "type": 0,
+                "signature": {
+                    "type": 1,
+                    "publicKey": "dda7a4395bd2035f6fee2d060ce844101efb612f3bcf7f49e749683f124014f8",
+                    "signature": "1a6ddcc49f0715395f0c8dcfd106a1833624abd85679370b4c9603c1e9c6d80a3ecc63c1ba1ffe1ffa98b64b2cf610ce3df86c6b8470e4006e7fef189df1ebde"
+                }
+            }
+        ]
+    },
+    "nonce": "7378697629483838793"
+}"#;
+const TRANSACTION_MESSAGE_ID: &str = "3e448717ec15c96bdcdf76b503db93596bfefcb1edeaa92ce9f0a6c8f9075007";
+const TRANSACTION_ID: &str = "7bf5fbf72b73f12e7324cfe8cbe79b22b8631ee9dbcdc0f374e4fd4f9267f8f9";
+
+// The packed essence of the transaction above, signed by the key of the secret key bytes 0 to 31.
+const ESSENCE: &str = "00010000939e770bbaeb805324030e052048cc448b86a74139158f4d0800b6144e127437010001000001644eb5594b880f7da82f35902296199a0eb9186999797f661b324f8d754a3a0940420f000000000025000000020000001300494f54412e5253205452414e53414354494f4e08000000494f54412e525320";
+const PUBLIC_KEY: &str = "03a107bff3ce10be1d70dd18e74bc09967e4d6309ba50d5f1ddc8664125531b8";
+const SIGNATURE: &str = "05ae67757d424ba34bdccccaec9517d9b8ab8f72bd67e13741ee121a5c4a8df61c238a52a552e3fb3129908521d6de4de896105f302c38fc63c2a2bb2671310f";
+
+fn transaction_message() -> Message {
+    serde_json::from_str::<MessageJson>(TRANSACTION_MESSAGE)
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn test_compute_message_id() {
+    let message = transaction_message();
+    let message_id = compute_message_id(&message.pack_new());
+    assert_eq!(message_id.to_string(), TRANSACTION_MESSAGE_ID);
+    assert_eq!(message_id, message.id().0);
+}
+
+#[test]
+fn test_compute_transaction_id() {
+    let message = transaction_message();
+    let transaction = match message.payload() {
+        Some(Payload::Transaction(transaction)) => transaction,
+        _ => panic!("expected a transaction payload"),
+    };
+    assert_eq!(compute_transaction_id(transaction).to_string(), TRANSACTION_ID);
+}
+
+#[test]
+fn test_verify_ed25519_signature() {
+    let essence = hex::decode(ESSENCE).unwrap();
+    let public_key = hex::decode(PUBLIC_KEY).unwrap();
+    let signature = hex::decode(SIGNATURE).unwrap();
+    assert!(verify_ed25519_signature(&public_key, &signature, &essence).unwrap());
+
+    // a signature of other bytes
+    assert!(!verify_ed25519_signature(&public_key, &signature, &essence[1..]).unwrap());
+    // a signature by another key
+    let other_key = hex::decode("dda7a4395bd2035f6fee2d060ce844101efb612f3bcf7f49e749683f124014f8").unwrap();
+    assert!(!verify_ed25519_signature(&other_key, &signature, &essence).unwrap());
+}
+
+#[test]
+fn test_verify_ed25519_signature_lengths() {
+    let public_key = hex::decode(PUBLIC_KEY).unwrap();
+    let signature = hex::decode(SIGNATURE).unwrap();
+    match verify_ed25519_signature(&public_key[1..], &signature, b"essence") {
+        Err(Error::InvalidParameter(message)) => {
+            assert_eq!(
+                message,
+                "invalid Ed25519 public key length: expected 32 bytes, found 31"
+            )
+        }
+        r => panic!("expected an invalid public key length, got {:?}", r),
+    }
+    match verify_ed25519_signature(&public_key, &signature[..32], b"essence") {
+        Err(Error::InvalidParameter(message)) => {
+            assert_eq!(message, "invalid Ed25519 signature length: expected 64 bytes, found 32")
+        }
+        r => panic!("expected an invalid signature length, got {:?}", r),
+    }
+}
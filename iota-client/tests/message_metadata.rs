@@ -0,0 +1,115 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Message read API tests against a mock node replaying captured node responses.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::start_mock_node;
+use iota_client::LedgerInclusionState;
+use std::str::FromStr;
+
+const CONFIRMED_MESSAGE_ID: &str = "b0b7ec4a6d1cb5c5a6e5c1b7c7a4c1c325d98223a4778839a2c43338ab8e5ea4";
+const UNREFERENCED_MESSAGE_ID: &str = "6ba348b6e97904fd4cc0b6a7e4bf107ba8c4b6ac07d8c7f8bd0c3b6a6b7f9a31";
+
+// A transaction confirmed by the milestone 176.
+const CONFIRMED_METADATA: &str = r#"{
+    "data": {
+        "messageId": "b0b7ec4a6d1cb5c5a6e5c1b7c7a4c1c325d98223a4778839a2c43338ab8e5ea4",
+        "parent1MessageId": "2d1752b6f0b8d4ea6b4ed8be1ac4f7f3de1b9d3c8e0df8e3a2c51c686c9c9b36",
+        "parent2MessageId": "7c4c1f8b1f16f0a1d6b5b8e3d5a36a63e1a6a5e7a09b1a7ac1e06a0c0e5f0b21",
+        "isSolid": true,
+        "referencedByMilestoneIndex": 176,
+        "ledgerInclusionState": "included"
+    }
+}"#;
+
+// A message waiting to be referenced, the node omits the milestone fields.
+const UNREFERENCED_METADATA: &str = r#"{
+    "data": {
+        "messageId": "6ba348b6e97904fd4cc0b6a7e4bf107ba8c4b6ac07d8c7f8bd0c3b6a6b7f9a31",
+        "parent1MessageId": "b0b7ec4a6d1cb5c5a6e5c1b7c7a4c1c325d98223a4778839a2c43338ab8e5ea4",
+        "parent2MessageId": "2d1752b6f0b8d4ea6b4ed8be1ac4f7f3de1b9d3c8e0df8e3a2c51c686c9c9b36",
+        "isSolid": true,
+        "shouldPromote": false,
+        "shouldReattach": false
+    }
+}"#;
+
+const CHILDREN: &str = r#"{
+    "data": {
+        "messageId": "b0b7ec4a6d1cb5c5a6e5c1b7c7a4c1c325d98223a4778839a2c43338ab8e5ea4",
+        "maxResults": 1000,
+        "count": 1,
+        "childrenMessageIds": ["6ba348b6e97904fd4cc0b6a7e4bf107ba8c4b6ac07d8c7f8bd0c3b6a6b7f9a31"]
+    }
+}"#;
+
+fn client() -> iota_client::Client {
+    let url = start_mock_node(|method, path, _| {
+        let response = match (method, path.strip_prefix("/api/v1/messages/")) {
+            ("GET", Some(path)) if path == format!("{}/metadata", CONFIRMED_MESSAGE_ID) => CONFIRMED_METADATA,
+            ("GET", Some(path)) if path == format!("{}/metadata", UNREFERENCED_MESSAGE_ID) => UNREFERENCED_METADATA,
+            ("GET", Some(path)) if path == format!("{}/children", CONFIRMED_MESSAGE_ID) => CHILDREN,
+            _ => return ("404 Not Found", String::new()),
+        };
+        ("200 OK", response.to_string())
+    });
+    iota_client::Client::build()
+        .with_node(&url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_confirmed_message_metadata() {
+    let message_id = MessageId::from_str(CONFIRMED_MESSAGE_ID).unwrap();
+    let metadata = client().get_message().metadata(&message_id).await.unwrap();
+    assert_eq!(metadata.message_id, CONFIRMED_MESSAGE_ID);
+    assert!(metadata.is_solid);
+    assert_eq!(metadata.should_promote, None);
+    assert_eq!(metadata.should_reattach, None);
+    assert_eq!(metadata.referenced_by_milestone_index, Some(176));
+    assert_eq!(metadata.ledger_inclusion_state, Some(LedgerInclusionState::Included));
+}
+
+#[tokio::test]
+async fn test_unreferenced_message_metadata() {
+    let message_id = MessageId::from_str(UNREFERENCED_MESSAGE_ID).unwrap();
+    let metadata = client().get_message().metadata(&message_id).await.unwrap();
+    assert_eq!(metadata.parent1, CONFIRMED_MESSAGE_ID);
+    assert_eq!(metadata.should_promote, Some(false));
+    assert_eq!(metadata.should_reattach, Some(false));
+    assert_eq!(metadata.referenced_by_milestone_index, None);
+    assert_eq!(metadata.ledger_inclusion_state, None);
+}
+
+#[test]
+fn test_ledger_inclusion_state() {
+    for (state, name) in &[
+        (LedgerInclusionState::Included, "included"),
+        (LedgerInclusionState::Conflicting, "conflicting"),
+        (LedgerInclusionState::NoTransaction, "noTransaction"),
+    ] {
+        assert_eq!(serde_json::to_string(state).unwrap(), format!("\"{}\"", name));
+        assert_eq!(
+            serde_json::from_str::<LedgerInclusionState>(&format!("\"{}\"", name)).unwrap(),
+            *state
+        );
+        assert_eq!(state.to_string(), *name);
+    }
+    assert!(serde_json::from_str::<LedgerInclusionState>("\"unknown\"").is_err());
+}
+
+#[tokio::test]
+async fn test_message_children() {
+    let message_id = MessageId::from_str(CONFIRMED_MESSAGE_ID).unwrap();
+    let children = client().get_message().children(&message_id).await.unwrap();
+    assert_eq!(
+        children.to_vec(),
+        vec![MessageId::from_str(UNREFERENCED_MESSAGE_ID).unwrap()]
+    );
+}
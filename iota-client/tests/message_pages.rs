@@ -0,0 +1,178 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Paged index lookup tests against a mock node holding 250 messages under an indexation key: the data of the message
+// `i` is `i` as 3 digits and it is referenced by the milestone `i`.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::start_mock_node;
+use iota_client::Error;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+const MESSAGE_COUNT: usize = 250;
+
+fn message_id(i: usize) -> String {
+    format!("{:064x}", i)
+}
+
+// Starts a mock node and returns its URL with a counter of the fetched messages.
+fn pages_mock_node() -> (String, Arc<AtomicUsize>) {
+    let fetched = Arc::new(AtomicUsize::new(0));
+    let fetched_ = fetched.clone();
+    let url = start_mock_node(move |method, path, _| {
+        let message_index = |id: &str| (0..MESSAGE_COUNT).find(|i| message_id(*i) == id);
+        let answer = if method != "GET" {
+            None
+        } else if path == "/api/v1/messages?index=iota.rs" {
+            let message_ids: Vec<String> = (0..MESSAGE_COUNT).map(message_id).collect();
+            Some(serde_json::json!({ "data": {
+                "index": "",
+                "maxResults": 1000,
+                "count": message_ids.len(),
+                "messageIds": message_ids,
+            }}))
+        } else if let Some(i) = path
+            .strip_prefix("/api/v1/messages/")
+            .and_then(|path| path.strip_suffix("/metadata"))
+            .and_then(message_index)
+        {
+            Some(serde_json::json!({ "data": {
+                "messageId": message_id(i),
+                "parent1MessageId": message_id(0),
+                "parent2MessageId": message_id(0),
+                "isSolid": true,
+                "referencedByMilestoneIndex": i,
+                "ledgerInclusionState": "noTransaction",
+            }}))
+        } else if let Some(i) = path.strip_prefix("/api/v1/messages/").and_then(message_index) {
+            fetched_.fetch_add(1, Ordering::SeqCst);
+            Some(serde_json::json!({ "data": {
+                "networkId": "1",
+                "parent1MessageId": message_id(0),
+                "parent2MessageId": message_id(0),
+                "payload": { "type": 2, "index": "iota.rs", "data": hex::encode(format!("{:03}", i)) },
+                "nonce": "0",
+            }}))
+        } else {
+            None
+        };
+        match answer {
+            Some(answer) => ("200 OK", answer.to_string()),
+            None => ("404 Not Found", String::new()),
+        }
+    });
+    (url, fetched)
+}
+
+fn client(url: &str) -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+// Returns the data of the messages.
+fn message_data(messages: &[Message]) -> Vec<String> {
+    messages
+        .iter()
+        .map(|message| match message.payload() {
+            Some(Payload::Indexation(indexation)) => String::from_utf8(indexation.data().to_vec()).unwrap(),
+            _ => panic!("the message doesn't have an indexation payload"),
+        })
+        .collect()
+}
+
+fn data_range(range: std::ops::Range<usize>) -> Vec<String> {
+    range.map(|i| format!("{:03}", i)).collect()
+}
+
+#[tokio::test]
+async fn test_message_pages() {
+    let (url, fetched) = pages_mock_node();
+    let client = client(&url);
+    let mut pages = client.find_message_pages("iota.rs").finish_pages().unwrap();
+    let mut cursors = Vec::new();
+    let mut data = Vec::new();
+    while let Some(page) = pages.next_page().await.unwrap() {
+        assert_eq!(page.has_more, page.next_cursor.is_some());
+        cursors.push(page.next_cursor);
+        data.extend(message_data(&page.messages));
+    }
+    assert_eq!(cursors, vec![Some("100".to_string()), Some("200".to_string()), None]);
+    assert_eq!(data, data_range(0..MESSAGE_COUNT));
+    assert_eq!(fetched.load(Ordering::SeqCst), MESSAGE_COUNT);
+
+    // a lookup resumed from a cursor starts at its page
+    let mut pages = client
+        .find_message_pages("iota.rs")
+        .with_cursor("200")
+        .finish_pages()
+        .unwrap();
+    let page = pages.next_page().await.unwrap().unwrap();
+    assert_eq!(message_data(&page.messages), data_range(200..MESSAGE_COUNT));
+    assert!(!page.has_more);
+    assert!(pages.next_page().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_message_pages_stop_early() {
+    let (url, fetched) = pages_mock_node();
+    let client = client(&url);
+    let mut pages = client
+        .find_message_pages("iota.rs")
+        .with_page_size(100)
+        .finish_pages()
+        .unwrap();
+    assert_eq!(fetched.load(Ordering::SeqCst), 0);
+    let page = pages.next_page().await.unwrap().unwrap();
+    assert_eq!(page.messages.len(), 100);
+    drop(pages);
+    assert_eq!(fetched.load(Ordering::SeqCst), 100);
+}
+
+#[tokio::test]
+async fn test_message_pages_filters() {
+    let (url, _) = pages_mock_node();
+    let client = client(&url);
+
+    let mut pages = client
+        .find_message_pages("iota.rs")
+        .with_data_prefix(b"1")
+        .finish_pages()
+        .unwrap();
+    let mut page_lengths = Vec::new();
+    let mut data = Vec::new();
+    while let Some(page) = pages.next_page().await.unwrap() {
+        page_lengths.push(page.messages.len());
+        data.extend(message_data(&page.messages));
+    }
+    assert_eq!(page_lengths, vec![0, 100, 0]);
+    assert_eq!(data, data_range(100..200));
+
+    let mut pages = client
+        .find_message_pages("iota.rs")
+        .with_cursor("200")
+        .with_referenced_after(240)
+        .with_data_prefix(b"24")
+        .finish_pages()
+        .unwrap();
+    let page = pages.next_page().await.unwrap().unwrap();
+    assert_eq!(message_data(&page.messages), data_range(241..MESSAGE_COUNT));
+}
+
+#[tokio::test]
+async fn test_message_pages_invalid_parameters() {
+    let (url, _) = pages_mock_node();
+    let client = client(&url);
+    let result = client.find_message_pages("iota.rs").with_page_size(0).finish_pages();
+    assert!(matches!(result, Err(Error::InvalidParameter(parameter)) if parameter == "page size"));
+    let result = client.find_message_pages("iota.rs").with_cursor("next").finish_pages();
+    assert!(matches!(result, Err(Error::InvalidParameter(parameter)) if parameter == "cursor"));
+}
@@ -0,0 +1,131 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Metrics tests against a mock node, driving a few messages, a promotion and failed requests through the client.
+
+#![cfg(feature = "metrics")]
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{node_info, start_mock_node, tips, MESSAGE_ID};
+use std::str::FromStr;
+
+const PROMOTABLE_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const REFERENCED_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+const UNKNOWN_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000003";
+
+fn metrics_mock_node() -> String {
+    start_mock_node(move |method, path, _| {
+        let metadata = path
+            .strip_prefix("/api/v1/messages/")
+            .and_then(|path| path.strip_suffix("/metadata"))
+            .filter(|message_id| [PROMOTABLE_MESSAGE_ID, REFERENCED_MESSAGE_ID].contains(message_id));
+        if method == "GET" && path == "/api/v1/info" {
+            ("200 OK", node_info(10f64, &[]))
+        } else if method == "GET" && path == "/api/v1/tips" {
+            ("200 OK", tips())
+        } else if method == "POST" && path == "/api/v1/messages" {
+            (
+                "201 Created",
+                serde_json::json!({ "data": { "messageId": MESSAGE_ID } }).to_string(),
+            )
+        } else if let Some(message_id) = metadata {
+            let referenced = message_id == REFERENCED_MESSAGE_ID;
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "messageId": message_id,
+                    "parent1MessageId": MESSAGE_ID,
+                    "parent2MessageId": MESSAGE_ID,
+                    "isSolid": true,
+                    "shouldPromote": !referenced,
+                    "shouldReattach": false,
+                    "referencedByMilestoneIndex": if referenced { Some(10) } else { None },
+                    "ledgerInclusionState": if referenced { Some("noTransaction") } else { None },
+                }})
+                .to_string(),
+            )
+        } else if path.starts_with("/api/v1/milestones/") {
+            ("500 Internal Server Error", String::new())
+        } else {
+            ("404 Not Found", String::new())
+        }
+    })
+}
+
+fn message_id(id: &str) -> MessageId {
+    MessageId::from_str(id).unwrap()
+}
+
+#[tokio::test]
+async fn test_metrics() {
+    let url = metrics_mock_node();
+    let client = iota_client::Client::build()
+        .with_node(&url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_network_id("testnet")
+        .with_min_pow_score(10f64)
+        .with_local_pow(true)
+        .finish()
+        .unwrap();
+    assert_eq!(client.get_metrics(), iota_client::Metrics::default());
+
+    client.send().with_index("iota.rs metrics test").finish().await.unwrap();
+    client.promote(&message_id(PROMOTABLE_MESSAGE_ID)).await.unwrap();
+    let confirmation = client
+        .get_message_confirmation(&message_id(REFERENCED_MESSAGE_ID))
+        .await
+        .unwrap();
+    assert!(confirmation.is_some());
+    assert!(client.get_milestone(1).await.is_err());
+    assert!(client
+        .get_message()
+        .metadata(&message_id(UNKNOWN_MESSAGE_ID))
+        .await
+        .is_err());
+
+    let metrics = client.get_metrics();
+    let node = format!("{}/", url);
+    let requests: Vec<(&str, &str, u64)> = metrics
+        .endpoints
+        .iter()
+        .inspect(|endpoint| {
+            assert_eq!(endpoint.node, node);
+            assert_eq!(endpoint.latency.count, endpoint.requests);
+        })
+        .map(|endpoint| (endpoint.method.as_str(), endpoint.endpoint.as_str(), endpoint.requests))
+        .collect();
+    assert_eq!(
+        requests,
+        vec![
+            ("GET", "/api/v1/messages/:id/metadata", 3),
+            ("GET", "/api/v1/milestones/:id", 1),
+            ("GET", "/api/v1/tips", 2),
+            ("POST", "/api/v1/messages", 2),
+        ]
+    );
+    let errors: Vec<(&str, u64)> = metrics
+        .errors
+        .iter()
+        .map(|(category, count)| (category.as_str(), *count))
+        .collect();
+    assert_eq!(errors, vec![("status_4xx", 1), ("status_5xx", 1)]);
+    // the message and the promotion
+    assert_eq!(metrics.pow.count, 2);
+    assert_eq!(metrics.messages_submitted, 2);
+    assert_eq!(metrics.promotions, 1);
+    assert_eq!(metrics.reattachments, 0);
+    assert_eq!(metrics.confirmations, 1);
+
+    let text = metrics.to_prometheus();
+    assert!(text.contains(&format!(
+        "iota_client_requests_total{{node=\"{}\",method=\"POST\",endpoint=\"/api/v1/messages\"}} 2\n",
+        node
+    )));
+    assert!(text.contains("iota_client_request_errors_total{category=\"status_5xx\"} 1\n"));
+    assert!(text.contains("iota_client_pow_duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+    assert!(text.contains("iota_client_pow_duration_seconds_count 2\n"));
+    assert!(text.contains("iota_client_messages_submitted_total 2\n"));
+}
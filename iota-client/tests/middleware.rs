@@ -0,0 +1,142 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Middleware tests against a mock node recording the request headers, with a slow route to time out.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{start_mock_node_with_headers, MESSAGE_ID};
+use iota_client::{Error, RequestInfo, RequestMiddleware, ResponseInfo};
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+// Records the requests and the responses, and adds a trace id header to every request.
+#[derive(Clone, Default)]
+struct RecordingMiddleware {
+    requests: Arc<Mutex<Vec<RequestInfo>>>,
+    responses: Arc<Mutex<Vec<(RequestInfo, ResponseInfo)>>>,
+}
+
+impl RequestMiddleware for RecordingMiddleware {
+    fn before_request(&self, request: &RequestInfo) -> Vec<(String, String)> {
+        self.requests.lock().unwrap().push(request.clone());
+        vec![("x-trace-id".to_string(), "trace-1".to_string())]
+    }
+
+    fn after_response(&self, request: &RequestInfo, response: &ResponseInfo) {
+        self.responses.lock().unwrap().push((request.clone(), response.clone()));
+    }
+}
+
+struct PanickingMiddleware;
+
+impl RequestMiddleware for PanickingMiddleware {
+    fn before_request(&self, _: &RequestInfo) -> Vec<(String, String)> {
+        panic!("before_request failure")
+    }
+
+    fn after_response(&self, _: &RequestInfo, _: &ResponseInfo) {
+        panic!("after_response failure")
+    }
+}
+
+// A mock node answering the info and the tips, 404 for the messages and the `api/v1/milestones/1` route after 2
+// seconds. The trace id header of every request is recorded.
+fn mock_node(trace_ids: Arc<Mutex<Vec<Option<String>>>>) -> String {
+    start_mock_node_with_headers(move |method, path, headers, _| {
+        let trace_id = headers
+            .iter()
+            .find(|(name, _)| name == "x-trace-id")
+            .map(|(_, value)| value.clone());
+        trace_ids.lock().unwrap().push(trace_id);
+        match (method, path) {
+            ("GET", "/api/v1/info") => ("200 OK", common::node_info(4000f64, &[])),
+            ("GET", "/api/v1/tips") => ("200 OK", common::tips()),
+            ("GET", "/api/v1/milestones/1") => {
+                thread::sleep(Duration::from_secs(2));
+                ("404 Not Found", String::new())
+            }
+            _ => ("404 Not Found", String::new()),
+        }
+    })
+}
+
+fn client<M: RequestMiddleware + 'static>(url: &str, middleware: M) -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_request_timeout(Duration::from_millis(500))
+        .with_middleware(middleware)
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_middleware_observes_requests() {
+    let trace_ids = Arc::new(Mutex::new(Vec::new()));
+    let url = mock_node(trace_ids.clone());
+    let middleware = RecordingMiddleware::default();
+    let client = client(&url, middleware.clone());
+
+    client.get_info().await.unwrap();
+    let message_id = MessageId::from_str(MESSAGE_ID).unwrap();
+    assert!(matches!(
+        client.get_message().data(&message_id).await,
+        Err(Error::ResponseError(404, _, _))
+    ));
+    assert!(matches!(client.get_milestone(1).await, Err(Error::Timeout(_))));
+
+    let requests = middleware.requests.lock().unwrap();
+    let paths = requests
+        .iter()
+        .map(|request| request.path.as_str())
+        .collect::<Vec<&str>>();
+    let message_path = format!("/api/v1/messages/{}", MESSAGE_ID);
+    assert_eq!(paths, vec!["/api/v1/info", &message_path, "/api/v1/milestones/1"]);
+    assert!(requests
+        .iter()
+        .all(|request| request.method == "GET" && request.body_size == 0));
+    assert_eq!(requests[0].node, format!("{}/", url));
+
+    let responses = middleware.responses.lock().unwrap();
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[0].1.status, Some(200));
+    assert_eq!(responses[0].1.error, None);
+    assert_eq!(responses[1].1.status, Some(404));
+    assert_eq!(responses[2].0.path, "/api/v1/milestones/1");
+    assert_eq!(responses[2].1.status, None);
+    assert!(responses[2].1.error.as_ref().unwrap().contains("timed out"));
+    assert!(responses[2].1.elapsed >= Duration::from_millis(500));
+
+    // the injected header reached the node
+    let trace_ids = trace_ids.lock().unwrap();
+    assert_eq!(trace_ids.len(), 3);
+    assert!(trace_ids.iter().all(|trace_id| trace_id.as_deref() == Some("trace-1")));
+}
+
+#[tokio::test]
+async fn test_middleware_redacts_credentials() {
+    let url = mock_node(Default::default());
+    let url_with_credentials = url.replace("http://", "http://user:password@");
+    let middleware = RecordingMiddleware::default();
+    let client = client(&url_with_credentials, middleware.clone());
+
+    client.get_tips().await.unwrap();
+    let requests = middleware.requests.lock().unwrap();
+    assert_eq!(requests[0].node, format!("{}/", url));
+}
+
+#[tokio::test]
+async fn test_panicking_middleware_ignored() {
+    let trace_ids = Arc::new(Mutex::new(Vec::new()));
+    let url = mock_node(trace_ids.clone());
+    let info = client(&url, PanickingMiddleware).get_info().await.unwrap();
+    assert_eq!(info.name, "mock");
+    assert_eq!(*trace_ids.lock().unwrap(), vec![None]);
+}
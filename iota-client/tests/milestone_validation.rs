@@ -0,0 +1,348 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Milestone validation tests against a test coordinator of two keys signing each milestone: a milestone signed by
+// the coordinator passes, a flipped signature byte, a key out of its index range or a missing signature fail. The
+// milestones are generated, the mainnet coordinator keys can't sign the test messages. Each signature signs the
+// BLAKE2b-256 hash of the packed essence as in RFC-0019, `MILESTONE_FIXTURE` was packed and signed apart from this
+// crate so the hashing is checked against bytes it didn't produce.
+
+mod common;
+
+use bee_common::packable::Packable;
+use bee_message::{
+    payload::milestone::{MilestonePayloadEssence, MILESTONE_MERKLE_PROOF_LENGTH},
+    prelude::*,
+};
+use bee_pow::providers::{Constant, ConstantBuilder};
+use bee_signing_ext::{binary::Ed25519PrivateKey, Signer};
+use common::{node_info, start_mock_node_with_bytes, MESSAGE_ID};
+use iota_client::{
+    api::{
+        milestone_essence_hash, validate_milestone, validate_milestone_message, CoordinatorKeys, CoordinatorPublicKey,
+        MAINNET_COORDINATOR_PUBLIC_KEYS,
+    },
+    Bech32Address, Client, Error, MilestoneMetadata,
+};
+use std::str::FromStr;
+
+const MILESTONE_INDEX: u32 = 1;
+const MILESTONE_TIMESTAMP: u64 = 1_000;
+const ADDRESS: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+
+// The milestone 600000 of a coordinator of the mainnet key ranges, signed by the keys of
+// `MILESTONE_FIXTURE_PUBLIC_KEYS`, in a message of the mainnet network id.
+const MILESTONE_FIXTURE: &str = concat!(
+    "9a55fde341bd7f9a8f1ae7a3b7d1f2c04e6ee3a2b9d24c1e4a1f7b0b6a4fb1c3e3d28f5c0a9d1e772c9e0df5d0a5c3b1",
+    "f67e91d0c7c4f1a6e5b4d3c2a1f0e9d8c7b6a5f4e3d2c1b03201000001000000c027090077364f60000000008f1ae7a3",
+    "b7d1f2c04e6ee3a2b9d24c1e4a1f7b0b6a4fb1c3e3d28f5c0a9d1e772c9e0df5d0a5c3b1f67e91d0c7c4f1a6e5b4d3c2",
+    "a1f0e9d8c7b6a5f4e3d2c1b089e3da2cb4a47d82a019c6266f2a07c3bebeb9151ca51084bb7c7c1e6094c0c5026e3b11",
+    "48fbb944889b9070a13a603987f7e75d49a0239ae719d6abb3c2d9e0d8fde48071bc24b50b6ab53e557d90c50f68d617",
+    "e89a04ce4204939aab91a5b7da028723ad026cfe78348bd9ba082bc2c749c7351c2af7d2ded617ab63d0e86cdc1f88ab",
+    "1957bb69848f3924ecfff3a414d7f3f905b33e08402d59e92f59ea1c930a2519f52c5357bd23d29df4f70fdb49439c88",
+    "01fb2513db265fc83d0d4f97dda89b6672ff6b13e17335594da3accc9a1302bb701c8dfb28b1a62e9f337a14fa080000",
+    "000000000000",
+);
+const MILESTONE_FIXTURE_ID: &str = "ccd5ef414560d7e69fda50cbcb7fda2d97d6f4d1d0757119b252f5fca5088eb2";
+const MILESTONE_FIXTURE_INDEX: u32 = 600000;
+const MILESTONE_FIXTURE_TIMESTAMP: u64 = 1615804023;
+const MILESTONE_FIXTURE_PUBLIC_KEYS: [(&str, u32, u32); 2] = [
+    (
+        "6e3b1148fbb944889b9070a13a603987f7e75d49a0239ae719d6abb3c2d9e0d8",
+        0,
+        777600,
+    ),
+    (
+        "fde48071bc24b50b6ab53e557d90c50f68d617e89a04ce4204939aab91a5b7da",
+        552960,
+        2108160,
+    ),
+];
+
+fn coordinator_key(byte: u8) -> Ed25519PrivateKey {
+    Ed25519PrivateKey::from_bytes(&[byte; 32]).unwrap()
+}
+
+fn public_key(key: &Ed25519PrivateKey) -> [u8; 32] {
+    key.generate_public_key().to_bytes()
+}
+
+// The test coordinator: the key 1 signs every milestone, the key 2 up to the index 10 and the key 3 from there.
+fn coordinator_keys() -> CoordinatorKeys {
+    let key = |byte: u8, start: u32, end: Option<u32>| CoordinatorPublicKey {
+        public_key: hex::encode(public_key(&coordinator_key(byte))),
+        start,
+        end,
+    };
+    CoordinatorKeys {
+        public_keys: vec![key(1, 0, None), key(2, 0, Some(10)), key(3, 10, None)],
+        signature_count: 2,
+    }
+}
+
+fn milestone(index: u32, keys: &[Ed25519PrivateKey]) -> MilestonePayload {
+    let parent = MessageId::from_str(MESSAGE_ID).unwrap();
+    let essence = MilestonePayloadEssence::new(
+        index,
+        MILESTONE_TIMESTAMP,
+        parent,
+        parent,
+        [0u8; MILESTONE_MERKLE_PROOF_LENGTH],
+        keys.iter().map(public_key).collect(),
+    );
+    let signatures = keys
+        .iter()
+        .map(|key| Box::new(key.sign(&milestone_essence_hash(&essence)).to_bytes()) as Box<[u8]>)
+        .collect();
+    MilestonePayload::new(essence, signatures)
+}
+
+// The milestone with a byte of its first signature flipped.
+fn flipped_signature(milestone: &MilestonePayload) -> MilestonePayload {
+    let mut signatures = milestone.signatures().clone();
+    signatures[0][0] ^= 1;
+    MilestonePayload::new(milestone.essence().clone(), signatures)
+}
+
+fn milestone_message(milestone: MilestonePayload) -> Message {
+    let parent = MessageId::from_str(MESSAGE_ID).unwrap();
+    MessageBuilder::<Constant>::new()
+        .with_network_id(1)
+        .with_parent1(parent)
+        .with_parent2(parent)
+        .with_payload(Payload::Milestone(Box::new(milestone)))
+        .with_nonce_provider(ConstantBuilder::new().with_value(0).finish(), 0f64)
+        .finish()
+        .unwrap()
+}
+
+fn metadata(message: &Message) -> MilestoneMetadata {
+    MilestoneMetadata {
+        index: u64::from(MILESTONE_INDEX),
+        message_id: message.id().0.to_string(),
+        timestamp: MILESTONE_TIMESTAMP,
+    }
+}
+
+fn is_invalid(result: iota_client::Result<()>, index: u32, reason: &str) -> bool {
+    match result {
+        Err(Error::InvalidMilestone(invalid, message)) => invalid == index && message.contains(reason),
+        r => panic!("expected an invalid milestone {} ({}), got {:?}", index, reason, r),
+    }
+}
+
+#[test]
+fn test_validate_milestone() {
+    let keys = coordinator_keys();
+    let genuine = milestone(MILESTONE_INDEX, &[coordinator_key(1), coordinator_key(2)]);
+    validate_milestone(&genuine, &keys).unwrap();
+    assert!(is_invalid(
+        validate_milestone(&flipped_signature(&genuine), &keys),
+        MILESTONE_INDEX,
+        "doesn't sign the essence"
+    ));
+
+    // the key 3 only signs from the index 10, the key 2 before it
+    assert!(is_invalid(
+        validate_milestone(&milestone(9, &[coordinator_key(1), coordinator_key(3)]), &keys),
+        9,
+        "doesn't sign the milestone 9"
+    ));
+    validate_milestone(&milestone(10, &[coordinator_key(1), coordinator_key(3)]), &keys).unwrap();
+    assert!(is_invalid(
+        validate_milestone(&milestone(10, &[coordinator_key(1), coordinator_key(2)]), &keys),
+        10,
+        "doesn't sign the milestone 10"
+    ));
+    // a key out of the coordinator
+    assert!(is_invalid(
+        validate_milestone(&milestone(1, &[coordinator_key(1), coordinator_key(4)]), &keys),
+        1,
+        "doesn't sign the milestone 1"
+    ));
+
+    // the signature count
+    assert!(is_invalid(
+        validate_milestone(&milestone(1, &[coordinator_key(1)]), &keys),
+        1,
+        "1 signatures"
+    ));
+    assert!(is_invalid(
+        validate_milestone(&milestone(1, &[coordinator_key(1), coordinator_key(1)]), &keys),
+        1,
+        "signs twice"
+    ));
+    assert!(is_invalid(
+        validate_milestone(&milestone(0, &[coordinator_key(1), coordinator_key(2)]), &keys),
+        0,
+        "the index is 0"
+    ));
+}
+
+#[test]
+fn test_validate_milestone_fixture() {
+    let keys = CoordinatorKeys {
+        public_keys: MILESTONE_FIXTURE_PUBLIC_KEYS
+            .iter()
+            .map(|(public_key, start, end)| CoordinatorPublicKey {
+                public_key: public_key.to_string(),
+                start: *start,
+                end: Some(*end),
+            })
+            .collect(),
+        signature_count: 2,
+    };
+    let bytes = hex::decode(MILESTONE_FIXTURE).unwrap();
+    let metadata = MilestoneMetadata {
+        index: u64::from(MILESTONE_FIXTURE_INDEX),
+        message_id: MILESTONE_FIXTURE_ID.to_string(),
+        timestamp: MILESTONE_FIXTURE_TIMESTAMP,
+    };
+    validate_milestone_message(&bytes, &metadata, &keys).unwrap();
+
+    let message = Message::unpack(&mut &bytes[..]).unwrap();
+    let milestone = match message.payload() {
+        Some(Payload::Milestone(milestone)) => milestone,
+        _ => panic!("expected a milestone payload"),
+    };
+    assert!(is_invalid(
+        validate_milestone(&flipped_signature(milestone), &keys),
+        MILESTONE_FIXTURE_INDEX,
+        "doesn't sign the essence"
+    ));
+    // the keys don't sign for the mainnet
+    assert!(is_invalid(
+        validate_milestone(milestone, &CoordinatorKeys::mainnet()),
+        MILESTONE_FIXTURE_INDEX,
+        "doesn't sign the milestone 600000"
+    ));
+}
+
+#[test]
+fn test_mainnet_coordinator_keys() {
+    let keys = CoordinatorKeys::default();
+    assert_eq!(keys, CoordinatorKeys::mainnet());
+    assert_eq!(keys.signature_count, 2);
+    assert_eq!(keys.public_keys.len(), MAINNET_COORDINATOR_PUBLIC_KEYS.len());
+    assert_eq!(keys.public_keys[2].start, 552960);
+    assert_eq!(keys.public_keys[2].end, Some(2108160));
+
+    // the test coordinator doesn't sign for the mainnet
+    let milestone = milestone(MILESTONE_INDEX, &[coordinator_key(1), coordinator_key(2)]);
+    assert!(is_invalid(
+        validate_milestone(&milestone, &keys),
+        MILESTONE_INDEX,
+        "doesn't sign the milestone 1"
+    ));
+
+    let json = serde_json::json!({
+        "publicKeys": [{ "publicKey": keys.public_keys[0].public_key, "start": 0 }],
+        "signatureCount": 1,
+    });
+    let parsed: CoordinatorKeys = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.public_keys[0].end, None);
+}
+
+#[test]
+fn test_validate_milestone_message() {
+    let keys = coordinator_keys();
+    let message = milestone_message(milestone(MILESTONE_INDEX, &[coordinator_key(1), coordinator_key(2)]));
+    let bytes = message.pack_new();
+    validate_milestone_message(&bytes, &metadata(&message), &keys).unwrap();
+
+    // the node declares another timestamp
+    let mut declared = metadata(&message);
+    declared.timestamp += 1;
+    assert!(is_invalid(
+        validate_milestone_message(&bytes, &declared, &keys),
+        MILESTONE_INDEX,
+        "the node declares the index 1 and the timestamp 1001"
+    ));
+    // the message isn't the one of the metadata
+    let mut declared = metadata(&message);
+    declared.message_id = MESSAGE_ID.to_string();
+    assert!(is_invalid(
+        validate_milestone_message(&bytes, &declared, &keys),
+        MILESTONE_INDEX,
+        "isn't the milestone message"
+    ));
+}
+
+// A node whose latest milestone is signed by the test coordinator, or with a flipped signature byte, answering the
+// balance requests with `balance`.
+fn mock_node(flipped: bool, balance: u64) -> String {
+    let mut milestone = milestone(MILESTONE_INDEX, &[coordinator_key(1), coordinator_key(2)]);
+    if flipped {
+        milestone = flipped_signature(&milestone);
+    }
+    let message = milestone_message(milestone);
+    let metadata = metadata(&message);
+    let bytes = message.pack_new();
+    start_mock_node_with_bytes(move |method, path, _, _| {
+        let json = |value: serde_json::Value| ("200 OK", value.to_string().into_bytes());
+        match (method, path) {
+            ("GET", "/api/v1/info") => ("200 OK", node_info(4000f64, &[]).into_bytes()),
+            ("GET", "/api/v1/milestones/1") => json(serde_json::json!({ "data": metadata })),
+            ("GET", p) if p == format!("/api/v1/messages/{}/raw", metadata.message_id) => ("200 OK", bytes.clone()),
+            ("GET", p) if p == format!("/api/v1/addresses/{}", ADDRESS) => json(serde_json::json!({ "data": {
+                "addressType": 1,
+                "address": ADDRESS,
+                "balance": balance,
+                "count": 1,
+                "dustAllowed": false,
+            }})),
+            _ => ("404 Not Found", Vec::new()),
+        }
+    })
+}
+
+fn client(urls: &[String], validation: bool) -> Client {
+    let urls = urls.iter().map(|url| url.as_str()).collect::<Vec<&str>>();
+    Client::build()
+        .with_nodes(&urls)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_quorum(true)
+        .with_quorum_size(2)
+        .with_quorum_threshold(50)
+        .with_quorum_milestone_validation(validation)
+        .with_coordinator_keys(coordinator_keys())
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_get_validated_milestone() {
+    let genuine = client(&[mock_node(false, 1)], false);
+    assert_eq!(
+        genuine.get_validated_milestone(1).await.unwrap().index,
+        u64::from(MILESTONE_INDEX)
+    );
+
+    let flipped = client(&[mock_node(true, 1)], false);
+    assert!(is_invalid(
+        flipped.get_validated_milestone(1).await.map(|_| ()),
+        MILESTONE_INDEX,
+        "doesn't sign the essence"
+    ));
+}
+
+#[tokio::test]
+async fn test_quorum_milestone_validation() {
+    let address: Bech32Address = ADDRESS.to_string().into();
+    // a single agreeing answer is enough, the node of the invalid milestone answers another balance
+    let urls = vec![mock_node(false, 1), mock_node(true, 2)];
+    for _ in 0..4 {
+        assert_eq!(client(&urls, true).get_address().balance(&address).await.unwrap(), 1);
+    }
+
+    // without a valid milestone no answer counts
+    let client = client(&[mock_node(true, 2), mock_node(true, 2)], true);
+    match client.get_address().balance(&address).await {
+        Err(Error::QuorumError(answers)) => {
+            assert_eq!(answers.len(), 2);
+            assert!(answers.iter().all(|(_, answer)| answer.contains("Invalid milestone 1")));
+        }
+        r => panic!("expected a quorum error, got {:?}", r),
+    }
+}
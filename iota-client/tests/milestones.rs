@@ -0,0 +1,109 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Milestone API tests against a mock node replaying captured node responses, with the milestones up to 10 pruned.
+
+mod common;
+
+use common::{start_mock_node, MESSAGE_ID};
+use iota_client::{Error, PrunedRequest};
+
+const PRUNING_INDEX: u64 = 10;
+
+const MILESTONE: &str = r#"{
+    "data": {
+        "index": 176,
+        "messageId": "52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649",
+        "timestamp": 1609950538
+    }
+}"#;
+
+const MILESTONE_UTXO_CHANGES: &str = r#"{
+    "data": {
+        "index": 176,
+        "createdOutputs": [
+            "00000000000000000000000000000000000000000000000000000000000000010000",
+            "00000000000000000000000000000000000000000000000000000000000000010100"
+        ],
+        "consumedOutputs": ["00000000000000000000000000000000000000000000000000000000000000020000"]
+    }
+}"#;
+
+const NOT_FOUND: &str = r#"{"error":{"code":"404","message":"milestone not found"}}"#;
+
+fn client() -> iota_client::Client {
+    let url = start_mock_node(|method, path, _| match (method, path) {
+        ("GET", "/api/v1/info") => (
+            "200 OK",
+            serde_json::json!({ "data": {
+                "name": "mock",
+                "version": "0.0.0",
+                "isHealthy": true,
+                "networkId": "testnet",
+                "minPowScore": 4000,
+                "latestMilestoneIndex": 176,
+                "solidMilestoneIndex": 176,
+                "pruningIndex": PRUNING_INDEX,
+                "features": [],
+            }})
+            .to_string(),
+        ),
+        ("GET", "/api/v1/milestones/176") => ("200 OK", MILESTONE.to_string()),
+        ("GET", "/api/v1/milestones/176/utxo-changes") => ("200 OK", MILESTONE_UTXO_CHANGES.to_string()),
+        _ => ("404 Not Found", NOT_FOUND.to_string()),
+    });
+    iota_client::Client::build()
+        .with_node(&url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_get_milestone() {
+    let milestone = client().get_milestone(176).await.unwrap();
+    assert_eq!(milestone.index, 176);
+    assert_eq!(milestone.message_id, MESSAGE_ID);
+    assert_eq!(milestone.timestamp, 1609950538);
+}
+
+#[tokio::test]
+async fn test_get_milestone_utxo_changes() {
+    let changes = client().get_milestone_utxo_changes(176).await.unwrap();
+    assert_eq!(changes.index, 176);
+    assert_eq!(
+        changes.created_outputs,
+        vec![
+            "00000000000000000000000000000000000000000000000000000000000000010000",
+            "00000000000000000000000000000000000000000000000000000000000000010100",
+        ]
+    );
+    assert_eq!(
+        changes.consumed_outputs,
+        vec!["00000000000000000000000000000000000000000000000000000000000000020000"]
+    );
+}
+
+#[tokio::test]
+async fn test_pruned_milestone() {
+    let client = client();
+    for index in &[1, PRUNING_INDEX] {
+        assert!(matches!(
+            client.get_milestone(*index).await,
+            Err(Error::PrunedData { requested: PrunedRequest::Milestone(i), pruning_index: PRUNING_INDEX })
+                if i == *index
+        ));
+        assert!(matches!(
+            client.get_milestone_utxo_changes(*index).await,
+            Err(Error::PrunedData { requested: PrunedRequest::MilestoneUtxoChanges(i), pruning_index: PRUNING_INDEX })
+                if i == *index
+        ));
+    }
+
+    // a milestone above the pruning index that the node doesn't know keeps the node error
+    match client.get_milestone(PRUNING_INDEX + 1).await {
+        Err(Error::ResponseError(404, body, _)) => assert_eq!(body, NOT_FOUND),
+        _ => panic!("the unknown milestone isn't reported as not found"),
+    }
+}
@@ -0,0 +1,771 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "mqtt")]
+
+mod common;
+
+use bee_message::prelude::Address;
+use common::start_mock_node;
+use iota_client::{
+    api::{AddressActivity, AddressMonitor},
+    Bech32Address, BrokerOptions, CancellationToken, Client, Error, MqttEvent, Topic, TopicEvent,
+};
+use std::{
+    io::{Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+const MESSAGE_ID: &str = "0db692542a11a45ef6c96d4774c9ed404bdb2ff13916821afc0fc0c8f294c1d4";
+const BECH32_ADDRESS: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+
+#[test]
+fn test_valid_topics() {
+    for topic in &[
+        "milestones/latest".to_string(),
+        "messages/referenced".to_string(),
+        "messages/indexation/iota.rs".to_string(),
+        format!("messages/{}/metadata", MESSAGE_ID),
+        format!("outputs/{}0100", MESSAGE_ID),
+        format!("addresses/{}/outputs", BECH32_ADDRESS),
+        format!("addresses/ed25519/{}/outputs", MESSAGE_ID),
+    ] {
+        assert!(Topic::new(topic.as_str()).is_ok(), "{} should be valid", topic);
+    }
+}
+
+#[test]
+fn test_invalid_topics() {
+    for topic in &[
+        "milestones".to_string(),
+        "messages/indexation/".to_string(),
+        format!("prefix/messages/{}/metadata", MESSAGE_ID),
+        format!("messages/{}/metadata/suffix", MESSAGE_ID),
+        format!("outputs/{}", MESSAGE_ID),
+        // invalid checksum
+        format!("addresses/{}q/outputs", &BECH32_ADDRESS[..BECH32_ADDRESS.len() - 1]),
+        format!("addresses/{}/outputs", MESSAGE_ID),
+    ] {
+        assert!(Topic::new(topic.as_str()).is_err(), "{} should be invalid", topic);
+    }
+}
+
+#[test]
+fn test_topic_event_output() {
+    let payload = serde_json::json!({
+        "messageId": MESSAGE_ID,
+        "transactionId": MESSAGE_ID,
+        "outputIndex": 1,
+        "isSpent": false,
+        "output": { "type": 0, "address": { "type": 1, "address": MESSAGE_ID }, "amount": 1_000_000 },
+    })
+    .to_string();
+    let event = TopicEvent {
+        topic: format!("addresses/{}/outputs", BECH32_ADDRESS),
+        payload_bytes: payload.as_bytes().to_vec(),
+        payload,
+    };
+    let output = event.output().unwrap();
+    assert_eq!(hex::encode(&output.transaction_id), MESSAGE_ID);
+    assert_eq!(output.output_index, 1);
+    assert_eq!(output.amount, 1_000_000);
+
+    // the messages are published as their bytes
+    let event = TopicEvent {
+        topic: "messages".to_string(),
+        payload: String::new(),
+        payload_bytes: vec![1, 2, 3],
+    };
+    assert!(event.message().is_err());
+}
+
+// The TCP connections of the client go to the default MQTT port of the node host.
+const BROKER_ADDRESS: &str = "127.0.0.1:1883";
+const EVENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+// A connection of the mock broker with its CONNECT packet and its subscribed topics.
+struct BrokerConnection {
+    stream: TcpStream,
+    connect: Vec<u8>,
+    topics: Vec<String>,
+}
+
+// A minimal MQTT 3.1.1 broker: it accepts every connection and subscription and publishes with QoS 0.
+struct MockBroker {
+    address: SocketAddr,
+    connections: Arc<Mutex<Vec<BrokerConnection>>>,
+    stopped: Arc<AtomicBool>,
+    listener: Option<JoinHandle<()>>,
+}
+
+fn read_packet(stream: &mut TcpStream) -> Option<(u8, Vec<u8>)> {
+    let mut byte = [0u8; 1];
+    stream.read_exact(&mut byte).ok()?;
+    let packet_type = byte[0];
+    let mut length = 0usize;
+    let mut shift = 0;
+    loop {
+        stream.read_exact(&mut byte).ok()?;
+        length |= ((byte[0] & 0x7f) as usize) << shift;
+        shift += 7;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+    }
+    let mut body = vec![0u8; length];
+    stream.read_exact(&mut body).ok()?;
+    Some((packet_type, body))
+}
+
+fn write_packet(stream: &mut TcpStream, packet_type: u8, body: &[u8]) {
+    let mut packet = vec![packet_type];
+    let mut length = body.len();
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+    let _ = stream.write_all(&packet);
+}
+
+fn handle_connection(mut stream: TcpStream, index: usize, connections: Arc<Mutex<Vec<BrokerConnection>>>) {
+    while let Some((packet_type, body)) = read_packet(&mut stream) {
+        match packet_type >> 4 {
+            // CONNECT
+            1 => {
+                connections.lock().unwrap()[index].connect = body;
+                write_packet(&mut stream, 0x20, &[0, 0]);
+            }
+            // SUBSCRIBE
+            8 => {
+                let mut topics = Vec::new();
+                let mut position = 2;
+                while position < body.len() {
+                    let length = u16::from_be_bytes([body[position], body[position + 1]]) as usize;
+                    topics.push(String::from_utf8(body[position + 2..position + 2 + length].to_vec()).unwrap());
+                    position += length + 3;
+                }
+                let mut suback = body[..2].to_vec();
+                suback.extend(topics.iter().map(|_| 0u8));
+                write_packet(&mut stream, 0x90, &suback);
+                connections.lock().unwrap()[index].topics.extend(topics);
+            }
+            // UNSUBSCRIBE
+            10 => write_packet(&mut stream, 0xb0, &body[..2]),
+            // PINGREQ
+            12 => write_packet(&mut stream, 0xd0, &[]),
+            // DISCONNECT
+            14 => break,
+            _ => {}
+        }
+    }
+}
+
+// The user name and the password of a CONNECT packet.
+fn connect_credentials(connect: &[u8]) -> Option<(String, String)> {
+    let flags = *connect.get(7)?;
+    if flags & 0xc0 != 0xc0 {
+        return None;
+    }
+    // the client id, the user name and the password follow the variable header
+    let mut fields = Vec::new();
+    let mut position = 10;
+    for _ in 0..3 {
+        let length = u16::from_be_bytes([*connect.get(position)?, *connect.get(position + 1)?]) as usize;
+        fields.push(String::from_utf8(connect.get(position + 2..position + 2 + length)?.to_vec()).ok()?);
+        position += length + 2;
+    }
+    Some((fields[1].clone(), fields[2].clone()))
+}
+
+impl MockBroker {
+    // The broker on the default MQTT port of the node host.
+    fn start() -> Self {
+        Self::start_at(BROKER_ADDRESS)
+    }
+
+    // The broker on a free port, connected to with the `broker_url` option.
+    fn start_on_free_port() -> Self {
+        Self::start_at("127.0.0.1:0")
+    }
+
+    fn start_at(address: &str) -> Self {
+        let listener = TcpListener::bind(address).expect("the port of the mock broker is in use");
+        let address = listener.local_addr().unwrap();
+        let connections = Arc::new(Mutex::new(Vec::new()));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let connections_ = connections.clone();
+        let stopped_ = stopped.clone();
+        let listener = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped_.load(Ordering::SeqCst) {
+                    break;
+                }
+                let stream = stream.unwrap();
+                let index = {
+                    let mut connections = connections_.lock().unwrap();
+                    connections.push(BrokerConnection {
+                        stream: stream.try_clone().unwrap(),
+                        connect: Vec::new(),
+                        topics: Vec::new(),
+                    });
+                    connections.len() - 1
+                };
+                let connections = connections_.clone();
+                thread::spawn(move || handle_connection(stream, index, connections));
+            }
+        });
+        Self {
+            address,
+            connections,
+            stopped,
+            listener: Some(listener),
+        }
+    }
+
+    fn is_subscribed(&self, topic: &str) -> bool {
+        self.connections
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|connection| connection.topics.iter().any(|t| t == topic))
+    }
+
+    fn credentials(&self) -> Vec<Option<(String, String)>> {
+        self.connections
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|connection| connect_credentials(&connection.connect))
+            .collect()
+    }
+
+    fn wait_for_subscription(&self, topic: &str) {
+        let started = Instant::now();
+        while !self.is_subscribed(topic) {
+            assert!(started.elapsed() < EVENT_TIMEOUT, "{} wasn't subscribed", topic);
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    fn publish(&self, topic: &str, payload: &str) {
+        let mut body = (topic.len() as u16).to_be_bytes().to_vec();
+        body.extend_from_slice(topic.as_bytes());
+        body.extend_from_slice(payload.as_bytes());
+        for connection in self.connections.lock().unwrap().iter_mut() {
+            if connection.topics.iter().any(|t| t == topic) {
+                write_packet(&mut connection.stream, 0x30, &body);
+            }
+        }
+    }
+}
+
+impl Drop for MockBroker {
+    // closes the connections and stops listening, the port can be bound again once it's dropped
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        for connection in self.connections.lock().unwrap().drain(..) {
+            let _ = connection.stream.shutdown(Shutdown::Both);
+        }
+        let _ = TcpStream::connect(self.address);
+        self.listener.take().unwrap().join().unwrap();
+    }
+}
+
+fn mqtt_client(broker_options: BrokerOptions) -> (Client, Receiver<MqttEvent>) {
+    let mut client = Client::build()
+        .with_node("http://127.0.0.1:14265")
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_mqtt_broker_options(broker_options.use_websockets(false))
+        .finish()
+        .unwrap();
+    let (tx, rx) = channel();
+    let tx = Mutex::new(tx);
+    client.subscriber().on_event(move |event| {
+        let _ = tx.lock().unwrap().send(event.clone());
+    });
+    (client, rx)
+}
+
+// Waits for the next event matching the predicate, skipping the others.
+fn wait_for_event(events: &Receiver<MqttEvent>, predicate: impl Fn(&MqttEvent) -> bool) -> MqttEvent {
+    let started = Instant::now();
+    loop {
+        let timeout = EVENT_TIMEOUT
+            .checked_sub(started.elapsed())
+            .expect("no matching MQTT event");
+        let event = events.recv_timeout(timeout).expect("no matching MQTT event");
+        if predicate(&event) {
+            return event;
+        }
+    }
+}
+
+fn reconnect_after_broker_restart() {
+    let broker = MockBroker::start();
+    let (mut client, events) = mqtt_client(BrokerOptions::new());
+    let (tx, payloads) = channel();
+    let tx = Mutex::new(tx);
+    client
+        .subscriber()
+        .with_topic(Topic::new("milestones/latest").unwrap())
+        .subscribe(move |event| {
+            let _ = tx.lock().unwrap().send(event.payload.clone());
+        })
+        .unwrap();
+    assert_eq!(events.recv_timeout(EVENT_TIMEOUT).unwrap(), MqttEvent::Connected);
+    broker.wait_for_subscription("milestones/latest");
+    broker.publish("milestones/latest", "1");
+    assert_eq!(payloads.recv_timeout(EVENT_TIMEOUT).unwrap(), "1");
+
+    drop(broker);
+    assert_eq!(events.recv_timeout(EVENT_TIMEOUT).unwrap(), MqttEvent::Disconnected);
+    match events.recv_timeout(EVENT_TIMEOUT).unwrap() {
+        MqttEvent::Reconnecting { attempt, delay } => {
+            assert_eq!(attempt, 1);
+            assert!(delay >= Duration::from_millis(500) && delay <= Duration::from_secs(1));
+        }
+        event => panic!("unexpected event {:?}", event),
+    }
+    thread::sleep(Duration::from_secs(2));
+
+    // the topic is subscribed again once reconnected
+    let broker = MockBroker::start();
+    wait_for_event(&events, |event| event == &MqttEvent::Connected);
+    broker.wait_for_subscription("milestones/latest");
+    broker.publish("milestones/latest", "2");
+    assert_eq!(payloads.recv_timeout(EVENT_TIMEOUT).unwrap(), "2");
+
+    client.subscriber().disconnect().unwrap();
+}
+
+fn reconnect_window_elapsed() {
+    let broker = MockBroker::start();
+    let (mut client, events) = mqtt_client(BrokerOptions::new().reconnect_window(Duration::from_millis(100)));
+    client
+        .subscriber()
+        .with_topic(Topic::new("milestones/solid").unwrap())
+        .subscribe(|_| {})
+        .unwrap();
+    assert_eq!(events.recv_timeout(EVENT_TIMEOUT).unwrap(), MqttEvent::Connected);
+
+    drop(broker);
+    assert_eq!(events.recv_timeout(EVENT_TIMEOUT).unwrap(), MqttEvent::Disconnected);
+    // the first delay is already longer than the window
+    match events.recv_timeout(EVENT_TIMEOUT).unwrap() {
+        MqttEvent::Error(error) => assert!(error.contains("after 0 attempts"), "{}", error),
+        event => panic!("unexpected event {:?}", event),
+    }
+
+    // the next subscription connects again, with the stored topics
+    let broker = MockBroker::start();
+    client
+        .subscriber()
+        .with_topic(Topic::new("milestones/latest").unwrap())
+        .subscribe(|_| {})
+        .unwrap();
+    assert_eq!(events.recv_timeout(EVENT_TIMEOUT).unwrap(), MqttEvent::Connected);
+    broker.wait_for_subscription("milestones/solid");
+    broker.wait_for_subscription("milestones/latest");
+
+    client.subscriber().disconnect().unwrap();
+}
+
+#[test]
+fn test_broker_reconnection() {
+    // the scenarios share the port of the broker, they can't run in parallel
+    reconnect_after_broker_restart();
+    reconnect_window_elapsed();
+}
+
+#[test]
+fn test_broker_url_with_auth() {
+    let broker = MockBroker::start_on_free_port();
+    let broker_options = BrokerOptions::new()
+        .broker_url(&format!("mqtt://{}", broker.address))
+        .auth("user", "secret");
+    let (mut client, events) = mqtt_client(broker_options);
+    client
+        .subscriber()
+        .with_topic(Topic::new("milestones/latest").unwrap())
+        .subscribe(|_| {})
+        .unwrap();
+    assert_eq!(events.recv_timeout(EVENT_TIMEOUT).unwrap(), MqttEvent::Connected);
+    broker.wait_for_subscription("milestones/latest");
+    assert_eq!(
+        broker.credentials(),
+        vec![Some(("user".to_string(), "secret".to_string()))]
+    );
+
+    client.subscriber().disconnect().unwrap();
+}
+
+#[test]
+fn test_cancelled_subscription() {
+    let broker = MockBroker::start_on_free_port();
+    let broker_options = BrokerOptions::new().broker_url(&format!("mqtt://{}", broker.address));
+    let (mut client, events) = mqtt_client(broker_options);
+    let subscribe = |client: &mut Client, token: Option<&CancellationToken>| {
+        let (tx, rx) = channel();
+        let tx = Mutex::new(tx);
+        let mut subscriber = client.subscriber().with_topic(Topic::new("milestones/latest").unwrap());
+        if let Some(token) = token {
+            subscriber = subscriber.with_cancellation(token.clone());
+        }
+        subscriber
+            .subscribe(move |event| {
+                let _ = tx.lock().unwrap().send(event.payload.clone());
+            })
+            .map(|_| rx)
+    };
+    let token = CancellationToken::new();
+    let kept = subscribe(&mut client, None).unwrap();
+    let cancelled = subscribe(&mut client, Some(&token)).unwrap();
+    assert_eq!(events.recv_timeout(EVENT_TIMEOUT).unwrap(), MqttEvent::Connected);
+    broker.wait_for_subscription("milestones/latest");
+    broker.publish("milestones/latest", "1");
+    assert_eq!(kept.recv_timeout(EVENT_TIMEOUT).unwrap(), "1");
+    assert_eq!(cancelled.recv_timeout(EVENT_TIMEOUT).unwrap(), "1");
+
+    // the callback of the cancelled subscription is removed, the other one still gets the events of the topic
+    token.cancel();
+    broker.publish("milestones/latest", "2");
+    assert_eq!(kept.recv_timeout(EVENT_TIMEOUT).unwrap(), "2");
+    assert!(cancelled.try_recv().is_err());
+    let result = subscribe(&mut client, Some(&token));
+    assert!(matches!(result, Err(Error::Cancelled)));
+
+    client.subscriber().disconnect().unwrap();
+}
+
+// The ledger of the mock node of the address monitor: the outputs of the address created or consumed by each milestone
+// up to the solid one.
+#[derive(Default)]
+struct Ledger {
+    solid_index: u64,
+    // (milestone index, output index, consumed)
+    changes: Vec<(u64, u16, bool)>,
+}
+
+fn output_id(index: u16) -> String {
+    format!("{}{}", MESSAGE_ID, hex::encode(index.to_le_bytes()))
+}
+
+// The output of the address, as published and as answered by the node.
+fn output_json(index: u16, is_spent: bool) -> serde_json::Value {
+    let address = match Address::try_from_bech32(BECH32_ADDRESS).unwrap() {
+        Address::Ed25519(address) => address.to_string(),
+        _ => unreachable!(),
+    };
+    serde_json::json!({
+        "messageId": MESSAGE_ID,
+        "transactionId": MESSAGE_ID,
+        "outputIndex": index,
+        "isSpent": is_spent,
+        "output": { "type": 0, "address": { "type": 1, "address": address }, "amount": 1_000_000 },
+    })
+}
+
+fn ledger_node(ledger: Arc<Mutex<Ledger>>) -> String {
+    start_mock_node(move |_, path, _| {
+        let ledger = ledger.lock().unwrap();
+        let changes = ledger.changes.iter().filter(|(index, ..)| *index <= ledger.solid_index);
+        let data = if path == "/api/v1/info" {
+            serde_json::json!({
+                "name": "mock",
+                "version": "0.0.0",
+                "isHealthy": true,
+                "networkId": "testnet",
+                "minPowScore": 4000,
+                "latestMilestoneIndex": ledger.solid_index,
+                "solidMilestoneIndex": ledger.solid_index,
+                "pruningIndex": 0,
+                "features": [],
+            })
+        } else if path == format!("/api/v1/addresses/{}/outputs?include-spent=true", BECH32_ADDRESS) {
+            let output_ids: Vec<String> = changes
+                .filter(|(.., consumed)| !consumed)
+                .map(|(_, output, _)| output_id(*output))
+                .collect();
+            serde_json::json!({
+                "address": BECH32_ADDRESS,
+                "maxResults": 1000,
+                "count": output_ids.len(),
+                "outputIds": output_ids,
+            })
+        } else if let Some(milestone_index) = path
+            .strip_prefix("/api/v1/milestones/")
+            .and_then(|path| path.strip_suffix("/utxo-changes"))
+        {
+            let milestone_index: u64 = milestone_index.parse().unwrap();
+            let ids = |consumed: bool| -> Vec<String> {
+                ledger
+                    .changes
+                    .iter()
+                    .filter(|change| change.0 == milestone_index && change.2 == consumed)
+                    .map(|(_, output, _)| output_id(*output))
+                    .collect()
+            };
+            // an output of another address changed by the milestone too
+            let mut created_outputs = ids(false);
+            created_outputs.push(output_id(99));
+            serde_json::json!({
+                "index": milestone_index,
+                "createdOutputs": created_outputs,
+                "consumedOutputs": ids(true),
+            })
+        } else if let Some(output) = path
+            .strip_prefix("/api/v1/outputs/")
+            .and_then(|id| (0..10).find(|index| output_id(*index) == id))
+        {
+            output_json(output, changes.clone().any(|change| change.1 == output && change.2))
+        } else {
+            return ("404 Not Found", String::new());
+        };
+        ("200 OK", serde_json::json!({ "data": data }).to_string())
+    })
+}
+
+fn activity(output: u16, consumed: bool, milestone_index: Option<u64>) -> AddressActivity {
+    let event = TopicEvent {
+        topic: format!("addresses/{}/outputs", BECH32_ADDRESS),
+        payload: output_json(output, consumed).to_string(),
+        payload_bytes: Vec::new(),
+    };
+    AddressActivity {
+        output_id: output_id(output),
+        output: event.output().unwrap(),
+        consumed,
+        milestone_index,
+    }
+}
+
+#[test]
+fn test_address_monitor_catch_up_after_reconnection() {
+    let ledger = Arc::new(Mutex::new(Ledger {
+        solid_index: 2,
+        changes: vec![(2, 0, false)],
+    }));
+    let node_url = ledger_node(ledger.clone());
+    let broker = MockBroker::start_on_free_port();
+    let broker_address = broker.address;
+    let mut client = Client::build()
+        .with_node(&node_url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_node_info_ttl(Duration::from_secs(0))
+        .with_mqtt_broker_options(
+            BrokerOptions::new()
+                .broker_url(&format!("mqtt://{}", broker_address))
+                .use_websockets(false),
+        )
+        .finish()
+        .unwrap();
+    let (tx, events) = channel();
+    let tx = Mutex::new(tx);
+    client.subscriber().on_event(move |event| {
+        let _ = tx.lock().unwrap().send(event.clone());
+    });
+    client
+        .subscriber()
+        .with_topic(Topic::new("milestones/latest").unwrap())
+        .subscribe(|_| {})
+        .unwrap();
+    assert_eq!(events.recv_timeout(EVENT_TIMEOUT).unwrap(), MqttEvent::Connected);
+
+    let client = Arc::new(client);
+    let monitor = Arc::new(AddressMonitor::new(Bech32Address(BECH32_ADDRESS.to_string()), Some(1)));
+    let (tx, activities) = channel();
+    let monitoring = {
+        let (client, monitor) = (client.clone(), monitor.clone());
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(client.monitor_address(&monitor, move |activity| {
+                // the last activity of the test ends the monitoring
+                let last = activity.output_id == output_id(3);
+                tx.send(activity).unwrap();
+                !last
+            }))
+        })
+    };
+    let address_topic = format!("addresses/{}/outputs", BECH32_ADDRESS);
+    let next_activity = || activities.recv_timeout(EVENT_TIMEOUT).unwrap();
+
+    // the milestones since the starting one are caught up, then the live events are delivered
+    assert_eq!(next_activity(), activity(0, false, Some(2)));
+    broker.wait_for_subscription(&address_topic);
+    broker.publish(&address_topic, &output_json(1, false).to_string());
+    assert_eq!(next_activity(), activity(1, false, None));
+
+    // the activity of the milestones solidified while disconnected is caught up once reconnected
+    drop(broker);
+    wait_for_event(&events, |event| event == &MqttEvent::Disconnected);
+    {
+        let mut ledger = ledger.lock().unwrap();
+        ledger.changes.extend(vec![(3, 1, false), (3, 0, true), (4, 2, false)]);
+        ledger.solid_index = 4;
+    }
+    let broker = MockBroker::start_at(&broker_address.to_string());
+    wait_for_event(&events, |event| event == &MqttEvent::Connected);
+    assert_eq!(next_activity(), activity(0, true, Some(3)));
+    assert_eq!(next_activity(), activity(2, false, Some(4)));
+
+    // an activity delivered by the catch-up isn't delivered again by its live event
+    broker.wait_for_subscription(&address_topic);
+    broker.publish(&address_topic, &output_json(2, false).to_string());
+    broker.publish(&address_topic, &output_json(3, false).to_string());
+    assert_eq!(next_activity(), activity(3, false, None));
+    monitoring.join().unwrap().unwrap();
+    assert!(activities.try_recv().is_err());
+    assert_eq!(monitor.last_milestone_index(), Some(4));
+
+    let mut client = Arc::try_unwrap(client).unwrap_or_else(|_| panic!("the monitoring is over"));
+    client.subscriber().disconnect().unwrap();
+}
+
+#[test]
+fn test_address_monitor_without_connection() {
+    let client = Client::build()
+        .with_node("http://127.0.0.1:14265")
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap();
+    let monitor = AddressMonitor::new(Bech32Address(BECH32_ADDRESS.to_string()), None);
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let result = runtime.block_on(client.monitor_address(&monitor, |_| true));
+    assert!(matches!(result, Err(Error::MqttConnectionClosed)), "{:?}", result);
+    assert_eq!(monitor.last_milestone_index(), None);
+}
+
+#[test]
+fn test_invalid_broker_url() {
+    for broker_url in &["http://127.0.0.1:1883", "mqtt://"] {
+        let (mut client, _) = mqtt_client(BrokerOptions::new().broker_url(broker_url));
+        let result = client
+            .subscriber()
+            .with_topic(Topic::new("milestones/latest").unwrap())
+            .subscribe(|_| {});
+        assert!(
+            matches!(result, Err(Error::InvalidParameter(_))),
+            "{}: {:?}",
+            broker_url,
+            result
+        );
+    }
+}
+
+// A broker only accepting TLS connections: it answers the first bytes of every connection with a TLS alert.
+fn start_tls_only_listener() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut buffer = [0u8; 256];
+            if stream.read(&mut buffer).is_ok() {
+                // protocol version alert
+                let _ = stream.write_all(&[0x15, 0x03, 0x03, 0x00, 0x02, 0x02, 0x46]);
+            }
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    });
+    address
+}
+
+#[test]
+fn test_plain_connection_to_tls_broker() {
+    let address = start_tls_only_listener();
+    let broker_url = format!("mqtt://{}", address);
+    let broker_options = BrokerOptions::new()
+        .broker_url(&broker_url)
+        .timeout(Duration::from_secs(5));
+    let (mut client, _) = mqtt_client(broker_options);
+    let started = Instant::now();
+    let result = client
+        .subscriber()
+        .with_topic(Topic::new("milestones/latest").unwrap())
+        .subscribe(|_| {});
+    match result {
+        Err(Error::MqttSchemeMismatch(url, true)) => assert_eq!(url, broker_url),
+        result => panic!("unexpected result {:?}", result),
+    }
+    // reported once the connection fails, not after the timeout
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_tls_connection_to_plain_broker() {
+    let broker = MockBroker::start_on_free_port();
+    let broker_url = format!("mqtts://{}", broker.address);
+    let broker_options = BrokerOptions::new()
+        .broker_url(&broker_url)
+        .timeout(Duration::from_secs(5));
+    let (mut client, _) = mqtt_client(broker_options);
+    let result = client
+        .subscriber()
+        .with_topic(Topic::new("milestones/latest").unwrap())
+        .subscribe(|_| {});
+    match result {
+        Err(Error::MqttSchemeMismatch(url, false)) => assert_eq!(url, broker_url),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+// A TLS connection to a local mosquitto with a self-signed certificate, run with
+// ```sh
+// IOTA_MQTT_TLS_BROKER=mqtts://localhost:8883 IOTA_MQTT_CA_CERTIFICATE=ca.crt cargo test --features mqtt -- --ignored
+// ```
+// The certificates are generated with
+// ```sh
+// openssl req -x509 -newkey rsa:2048 -nodes -days 1 -subj "/CN=iota.rs test CA" -keyout ca.key -out ca.crt
+// openssl req -newkey rsa:2048 -nodes -subj "/CN=localhost" -keyout server.key -out server.csr
+// openssl x509 -req -in server.csr -CA ca.crt -CAkey ca.key -CAcreateserial -days 1 -out server.crt
+// ```
+// and the broker is started with `mosquitto -c mosquitto.conf`, the configuration being
+// ```text
+// listener 8883
+// allow_anonymous true
+// cafile ca.crt
+// certfile server.crt
+// keyfile server.key
+// ```
+#[test]
+#[ignore]
+fn test_mosquitto_tls() {
+    let broker_url = std::env::var("IOTA_MQTT_TLS_BROKER").expect("IOTA_MQTT_TLS_BROKER isn't set");
+    let ca_certificate = std::env::var("IOTA_MQTT_CA_CERTIFICATE").expect("IOTA_MQTT_CA_CERTIFICATE isn't set");
+    let mut client = Client::build()
+        .with_node("http://127.0.0.1:14265")
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_ca_certificate(&ca_certificate)
+        .with_mqtt_broker_options(BrokerOptions::new().broker_url(&broker_url))
+        .finish()
+        .unwrap();
+    let (tx, events) = channel();
+    let tx = Mutex::new(tx);
+    client.subscriber().on_event(move |event| {
+        let _ = tx.lock().unwrap().send(event.clone());
+    });
+    client
+        .subscriber()
+        .with_topic(Topic::new("milestones/latest").unwrap())
+        .subscribe(|_| {})
+        .unwrap();
+    assert_eq!(events.recv_timeout(EVENT_TIMEOUT).unwrap(), MqttEvent::Connected);
+}
@@ -0,0 +1,270 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Network info tests against mock nodes of private tangles.
+
+mod common;
+
+use bee_common::packable::Packable;
+use bee_message::prelude::*;
+use bee_pow::score::compute_pow_score;
+use common::{start_mock_node, tips, MESSAGE_ID};
+use iota_client::{hash_network_id, Error, MessageJson, Seed};
+use std::{
+    convert::TryInto,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+const DEVNET_ID: &str = "my-devnet";
+const DEVNET_MIN_POW_SCORE: f64 = 10f64;
+const MAINNET_ADDRESS: &str = "iota1qyenxvenxvenxvenxvenxvenxvenxvenxvenxvenxvenxvenxvenxu7jn4m";
+
+fn private_tangle_node() -> String {
+    start_mock_node(|method, path, _| match (method, path) {
+        ("GET", "/api/v1/info") => (
+            "200 OK",
+            serde_json::json!({ "data": {
+                "name": "HORNET",
+                "version": "0.6.0",
+                "isHealthy": true,
+                "networkId": "private-tangle",
+                "bech32HRP": "atoi",
+                "minPowScore": 100,
+                "latestMilestoneIndex": 1,
+                "solidMilestoneIndex": 1,
+                "pruningIndex": 0,
+                "features": [],
+            }})
+            .to_string(),
+        ),
+        _ => ("404 Not Found", String::new()),
+    })
+}
+
+#[tokio::test]
+async fn test_refresh_network_info() {
+    let client = iota_client::Client::build()
+        .with_node(&private_tangle_node())
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap();
+    // without the node syncing, the defaults are kept until a refresh
+    assert_eq!(client.get_network_info().bech32_hrp, "iot");
+
+    let network_info = client.refresh_network_info().await.unwrap();
+    assert_eq!(network_info.network_id, "private-tangle");
+    assert_eq!(network_info.bech32_hrp, "atoi");
+    assert_eq!(network_info.min_pow_score, 100f64);
+    assert_eq!(client.get_network_info(), network_info);
+}
+
+#[test]
+fn test_node_sync_network_info() {
+    let client = iota_client::Client::build()
+        .with_node(&private_tangle_node())
+        .unwrap()
+        .finish()
+        .unwrap();
+    let network_info = client.get_network_info();
+    assert_eq!(network_info.network_id, "private-tangle");
+    assert_eq!(network_info.bech32_hrp, "atoi");
+    assert_eq!(network_info.min_pow_score, 100f64);
+}
+
+#[tokio::test]
+async fn test_node_info_without_bech32_hrp() {
+    let url = start_mock_node(|_, _, _| ("200 OK", common::node_info(4000f64, &[])));
+    let client = iota_client::Client::build()
+        .with_node(&url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_bech32_hrp("atoi")
+        .finish()
+        .unwrap();
+    assert_eq!(client.get_info().await.unwrap().bech32_hrp, None);
+    // the configured HRP is kept if the node doesn't report one
+    assert_eq!(client.refresh_network_info().await.unwrap().bech32_hrp, "atoi");
+}
+
+// The info requests and the messages submitted to a devnet node.
+#[derive(Default)]
+struct DevnetNode {
+    info_requests: AtomicUsize,
+    messages: Mutex<Vec<Message>>,
+}
+
+// Starts a mock node of a private tangle with its own network id, HRP and minimum PoW score.
+fn devnet_node() -> (String, Arc<DevnetNode>) {
+    let node = Arc::new(DevnetNode::default());
+    let node_ = node.clone();
+    let url = start_mock_node(move |method, path, body| match (method, path) {
+        ("GET", "/api/v1/info") => {
+            node_.info_requests.fetch_add(1, Ordering::SeqCst);
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "name": "HORNET",
+                    "version": "0.6.0",
+                    "isHealthy": true,
+                    "networkId": DEVNET_ID,
+                    "bech32HRP": "dev",
+                    "minPowScore": DEVNET_MIN_POW_SCORE,
+                    "latestMilestoneIndex": 1,
+                    "solidMilestoneIndex": 1,
+                    "pruningIndex": 0,
+                    "features": [],
+                }})
+                .to_string(),
+            )
+        }
+        ("GET", "/api/v1/tips") => ("200 OK", tips()),
+        ("POST", "/api/v1/messages") => {
+            let message: MessageJson = serde_json::from_slice(body).unwrap();
+            node_.messages.lock().unwrap().push(message.try_into().unwrap());
+            (
+                "201 Created",
+                serde_json::json!({ "data": { "messageId": MESSAGE_ID } }).to_string(),
+            )
+        }
+        _ => ("404 Not Found", String::new()),
+    });
+    (url, node)
+}
+
+fn devnet_client(url: &str) -> iota_client::ClientBuilder {
+    iota_client::Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_local_pow(true)
+        .with_pow_worker_count(1)
+}
+
+fn seed() -> Seed {
+    Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap()
+}
+
+async fn send_indexation(client: &iota_client::Client) -> iota_client::Result<MessageId> {
+    client
+        .send()
+        .with_index("iota.rs private tangle test")
+        .with_data(b"devnet".to_vec())
+        .finish()
+        .await
+}
+
+#[tokio::test]
+async fn test_private_tangle_network_params() {
+    let (url, node) = devnet_node();
+    let client = devnet_client(&url).finish().unwrap();
+    // the network info is cached when the client is built
+    let network_info = client.get_network_info();
+    assert_eq!(network_info.network_id, DEVNET_ID);
+    assert_eq!(network_info.bech32_hrp, "dev");
+    assert_eq!(network_info.min_pow_score, DEVNET_MIN_POW_SCORE);
+    let info_requests = node.info_requests.load(Ordering::SeqCst);
+
+    let addresses = client
+        .find_addresses(&seed())
+        .with_account_index(0)
+        .with_range(0..2)
+        .get_all()
+        .unwrap();
+    assert!(addresses.iter().all(|(address, _)| address.starts_with("dev1")));
+
+    send_indexation(&client).await.unwrap();
+    let message = node.messages.lock().unwrap().pop().unwrap();
+    assert_eq!(message.network_id(), hash_network_id(DEVNET_ID));
+    let mut bytes = Vec::new();
+    message.pack(&mut bytes).unwrap();
+    assert!(compute_pow_score(&bytes) >= DEVNET_MIN_POW_SCORE);
+    // the message is built without asking the node for its info again
+    assert_eq!(node.info_requests.load(Ordering::SeqCst), info_requests);
+}
+
+#[tokio::test]
+async fn test_mixed_networks() {
+    let (url, node) = devnet_node();
+    let client = devnet_client(&url).finish().unwrap();
+
+    // a message built for another network isn't submitted
+    let testnet_id = hash_network_id("testnet");
+    let result = client
+        .send()
+        .with_network_id(testnet_id)
+        .with_index("iota.rs private tangle test")
+        .with_data(b"testnet".to_vec())
+        .finish()
+        .await;
+    assert!(matches!(
+        result,
+        Err(Error::NetworkIdMismatch(network_id, network, _)) if network_id == testnet_id && network == DEVNET_ID
+    ));
+    assert!(node.messages.lock().unwrap().is_empty());
+
+    // an address of another network
+    let result = client.send().with_output(&MAINNET_ADDRESS.into(), 1_000_000).err();
+    assert!(matches!(
+        result,
+        Some(Error::Bech32HrpMismatch(address, hrp, network_hrp))
+            if address == MAINNET_ADDRESS && hrp == "iota" && network_hrp == "dev"
+    ));
+}
+
+#[tokio::test]
+async fn test_network_info_fetched_once_without_node_sync() {
+    let (url, node) = devnet_node();
+    let client = devnet_client(&url).with_node_sync_disabled().finish().unwrap();
+    assert_eq!(node.info_requests.load(Ordering::SeqCst), 0);
+    send_indexation(&client).await.unwrap();
+    send_indexation(&client).await.unwrap();
+    assert_eq!(node.info_requests.load(Ordering::SeqCst), 1);
+    let messages = node.messages.lock().unwrap();
+    assert_eq!(messages.len(), 2);
+    assert!(messages
+        .iter()
+        .all(|message| message.network_id() == hash_network_id(DEVNET_ID)));
+}
+
+#[tokio::test]
+async fn test_offline_network_params() {
+    // no node answers, the network parameters are set with the builder
+    let client = iota_client::Client::build()
+        .with_node("http://localhost:14265")
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_network_id(DEVNET_ID)
+        .with_min_pow_score(DEVNET_MIN_POW_SCORE)
+        .with_bech32_hrp("dev")
+        .finish()
+        .unwrap();
+    assert_eq!(client.get_network_id().await.unwrap(), hash_network_id(DEVNET_ID));
+    assert_eq!(client.get_min_pow_score().await.unwrap(), DEVNET_MIN_POW_SCORE);
+    let addresses = client
+        .find_addresses(&seed())
+        .with_account_index(0)
+        .with_range(0..1)
+        .finish()
+        .unwrap();
+    assert!(addresses[0].starts_with("dev1"));
+}
+
+#[test]
+fn test_network_params_set_with_the_builder() {
+    let (url, _) = devnet_node();
+    // the nodes of another network aren't synced
+    let client = devnet_client(&url).with_network_id("testnet").finish().unwrap();
+    assert!(client.get_synced_nodes().is_empty());
+    assert_eq!(client.get_network_info().bech32_hrp, "iot");
+
+    // the parameters set with the builder are kept, the others are the ones of the node
+    let client = devnet_client(&url).with_min_pow_score(1000f64).finish().unwrap();
+    let network_info = client.get_network_info();
+    assert_eq!(network_info.network_id, DEVNET_ID);
+    assert_eq!(network_info.bech32_hrp, "dev");
+    assert_eq!(network_info.min_pow_score, 1000f64);
+}
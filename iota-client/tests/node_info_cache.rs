@@ -0,0 +1,115 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Node info cache tests against mock nodes counting their info requests.
+
+mod common;
+
+use common::{node_info, start_mock_node};
+use iota_client::{Client, ClientBuilder};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+// A mock node counting its info requests.
+fn mock_node() -> (String, Arc<AtomicUsize>) {
+    let info_requests = Arc::new(AtomicUsize::new(0));
+    let info_requests_ = info_requests.clone();
+    let url = start_mock_node(move |method, path, _| match (method, path) {
+        ("GET", "/api/v1/info") => {
+            info_requests_.fetch_add(1, Ordering::SeqCst);
+            ("200 OK", node_info(4000f64, &[]))
+        }
+        _ => ("404 Not Found", String::new()),
+    });
+    (url, info_requests)
+}
+
+fn builder(urls: &[&str]) -> ClientBuilder {
+    Client::build().with_nodes(urls).unwrap()
+}
+
+#[tokio::test]
+async fn test_node_info_cached_within_ttl() {
+    let (first_node, first_requests) = mock_node();
+    let (second_node, second_requests) = mock_node();
+    // the node syncing fetches the info of both nodes
+    let client = builder(&[&first_node, &second_node]).finish().unwrap();
+    assert_eq!(first_requests.load(Ordering::SeqCst), 1);
+    assert_eq!(second_requests.load(Ordering::SeqCst), 1);
+
+    for i in 0..50 {
+        match i % 3 {
+            0 => assert_eq!(client.get_info().await.unwrap().name, "mock"),
+            1 => assert_eq!(client.get_min_pow_score().await.unwrap(), 4000f64),
+            _ => assert_eq!(client.get_network_info().network_id, "testnet"),
+        }
+    }
+    assert_eq!(first_requests.load(Ordering::SeqCst), 1);
+    assert_eq!(second_requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_node_info_cached_without_node_syncing() {
+    let (url, info_requests) = mock_node();
+    let client = builder(&[&url]).with_node_sync_disabled().finish().unwrap();
+    assert_eq!(info_requests.load(Ordering::SeqCst), 0);
+
+    for _ in 0..50 {
+        client.get_info().await.unwrap();
+        client.get_network_id().await.unwrap();
+    }
+    assert_eq!(info_requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_node_info_expired() {
+    let ttl = Duration::from_millis(100);
+    let (url, info_requests) = mock_node();
+    let client = builder(&[&url])
+        .with_node_sync_disabled()
+        .with_node_info_ttl(ttl)
+        .finish()
+        .unwrap();
+    client.get_info().await.unwrap();
+    client.get_info().await.unwrap();
+    assert_eq!(info_requests.load(Ordering::SeqCst), 1);
+
+    thread::sleep(ttl * 2);
+    client.get_info().await.unwrap();
+    assert_eq!(info_requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_refresh_node_info() {
+    let (url, info_requests) = mock_node();
+    let client = builder(&[&url]).with_node_sync_disabled().finish().unwrap();
+    client.get_info().await.unwrap();
+    assert_eq!(client.refresh_node_info().await.unwrap().name, "mock");
+    assert_eq!(info_requests.load(Ordering::SeqCst), 2);
+    // the refreshed info is cached
+    client.get_info().await.unwrap();
+    assert_eq!(info_requests.load(Ordering::SeqCst), 2);
+
+    client.refresh_network_info().await.unwrap();
+    assert_eq!(info_requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_node_info_cache_disabled() {
+    let (url, info_requests) = mock_node();
+    let client = builder(&[&url])
+        .with_node_sync_disabled()
+        .with_node_info_ttl(Duration::from_secs(0))
+        .finish()
+        .unwrap();
+    for _ in 0..5 {
+        client.get_info().await.unwrap();
+    }
+    assert_eq!(info_requests.load(Ordering::SeqCst), 5);
+}
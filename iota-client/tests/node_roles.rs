@@ -0,0 +1,173 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Node role tests against a read-only public mock node and a private mock node submitting the messages, recording
+// the requests each node receives.
+
+mod common;
+
+use common::{node_info, start_mock_node_with_headers, tips, MESSAGE_ID};
+use iota_client::{Client, Error, NodeAuth, NodeOptions};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+struct MockNode {
+    url: String,
+    // the method and the path of the requests, without the node info requests of the node syncing
+    requests: Arc<Mutex<Vec<String>>>,
+    // the authorization header of the last request
+    authorization: Arc<Mutex<Option<String>>>,
+    // the node answers an error while it's down
+    down: Arc<AtomicBool>,
+}
+
+fn mock_node(down: bool) -> MockNode {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let requests_ = requests.clone();
+    let authorization = Arc::new(Mutex::new(None));
+    let authorization_ = authorization.clone();
+    let down = Arc::new(AtomicBool::new(down));
+    let down_ = down.clone();
+    let url = start_mock_node_with_headers(move |method, path, headers, _| {
+        if down_.load(Ordering::SeqCst) {
+            return ("500 Internal Server Error", String::new());
+        }
+        if path != "/api/v1/info" {
+            requests_.lock().unwrap().push(format!("{} {}", method, path));
+        }
+        *authorization_.lock().unwrap() = headers
+            .iter()
+            .find(|(name, _)| name == "authorization")
+            .map(|(_, value)| value.clone());
+        match (method, path) {
+            ("GET", "/api/v1/info") => ("200 OK", node_info(10f64, &["PoW"])),
+            ("GET", "/api/v1/tips") => ("200 OK", tips()),
+            ("GET", "/api/v1/peers") => ("200 OK", serde_json::json!({ "data": [] }).to_string()),
+            ("POST", "/api/v1/messages") => (
+                "201 Created",
+                serde_json::json!({ "data": { "messageId": MESSAGE_ID } }).to_string(),
+            ),
+            _ => ("404 Not Found", String::new()),
+        }
+    });
+    MockNode {
+        url,
+        requests,
+        authorization,
+        down,
+    }
+}
+
+impl MockNode {
+    fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+// A client of the public node, reads only, and of the private node, doing the submissions and their remote PoW.
+fn client(public: &MockNode, private: &MockNode, node_sync: bool, local_pow: bool) -> Client {
+    let builder = Client::build()
+        .with_node_options(
+            &public.url,
+            NodeOptions {
+                submit: false,
+                remote_pow: false,
+                mqtt: false,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .with_node_options(
+            &private.url,
+            NodeOptions {
+                auth: Some(NodeAuth::Jwt {
+                    jwt: "private".to_string(),
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .with_local_pow(local_pow);
+    let builder = if node_sync {
+        builder
+    } else {
+        builder.with_node_sync_disabled()
+    };
+    builder.finish().unwrap()
+}
+
+#[tokio::test]
+async fn test_reads_and_submissions() {
+    let public = mock_node(false);
+    let private = mock_node(false);
+    let client = client(&public, &private, false, false);
+
+    // the reads go to both nodes
+    for _ in 0..4 {
+        client.get_tips().await.unwrap();
+    }
+    assert_eq!(public.requests(), vec!["GET /api/v1/tips"; 2]);
+    assert_eq!(private.requests(), vec!["GET /api/v1/tips"; 2]);
+
+    // the messages and their remote PoW only go to the private node
+    for _ in 0..2 {
+        client.send().with_index("roles").finish().await.unwrap();
+    }
+    let posts = |node: &MockNode| {
+        node.requests()
+            .into_iter()
+            .filter(|request| request == "POST /api/v1/messages")
+            .count()
+    };
+    assert_eq!(posts(&public), 0);
+    assert_eq!(posts(&private), 2);
+}
+
+#[tokio::test]
+async fn test_node_auth() {
+    let public = mock_node(false);
+    let private = mock_node(false);
+    let client = client(&public, &private, false, false);
+    client.pin_node(&private.url).unwrap();
+    client.get_peers().await.unwrap();
+    assert_eq!(private.authorization.lock().unwrap().as_deref(), Some("Bearer private"));
+
+    // the public node has no credentials
+    client.pin_node(&public.url).unwrap();
+    client.get_peers().await.unwrap();
+    assert_eq!(*public.authorization.lock().unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_unsynced_submit_node() {
+    let public = mock_node(false);
+    let private = mock_node(true);
+    let client = client(&public, &private, true, false);
+    assert_eq!(client.get_synced_nodes().len(), 1);
+
+    // the reads still work, the submissions fail without a node allowing them
+    client.get_tips().await.unwrap();
+    let error = client.send().with_index("roles").finish().await.unwrap_err();
+    assert!(matches!(error, Error::PowNodePoolEmpty), "unexpected error {}", error);
+
+    // with the local PoW, a node with the submit role is still needed
+    let client = self::client(&public, &private, true, true);
+    let error = client.send().with_index("roles").finish().await.unwrap_err();
+    assert!(
+        matches!(error, Error::SubmitNodePoolEmpty),
+        "unexpected error {}",
+        error
+    );
+    assert!(public
+        .requests()
+        .iter()
+        .all(|request| request != "POST /api/v1/messages"));
+
+    // the private node is used again once it's synced
+    private.down.store(false, Ordering::SeqCst);
+    let client = self::client(&public, &private, true, false);
+    client.send().with_index("roles").finish().await.unwrap();
+    assert_eq!(private.requests().last().unwrap(), "POST /api/v1/messages");
+}
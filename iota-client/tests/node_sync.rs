@@ -0,0 +1,114 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Node syncing tests against mock nodes whose milestones go stale between two syncs.
+
+mod common;
+
+use common::start_mock_node;
+use iota_client::{Client, Url};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const SYNC_INTERVAL: Duration = Duration::from_millis(200);
+
+// A mock node at the milestone `milestone_index`, or 10 milestones behind while `stale` is set.
+fn mock_node(milestone_index: usize, latest_milestone_timestamp: Option<u64>, stale: Arc<AtomicBool>) -> String {
+    start_mock_node(move |method, path, _| match (method, path) {
+        ("GET", "/api/v1/info") => {
+            let milestone_index = if stale.load(Ordering::SeqCst) {
+                milestone_index - 10
+            } else {
+                milestone_index
+            };
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "name": "mock",
+                    "version": "0.0.0",
+                    "isHealthy": true,
+                    "networkId": "testnet",
+                    "minPowScore": 4000,
+                    "latestMilestoneIndex": milestone_index,
+                    "solidMilestoneIndex": milestone_index,
+                    "latestMilestoneTimestamp": latest_milestone_timestamp,
+                    "pruningIndex": 0,
+                    "features": [],
+                }})
+                .to_string(),
+            )
+        }
+        _ => ("404 Not Found", String::new()),
+    })
+}
+
+fn client(urls: &[&String]) -> Client {
+    let urls = urls.iter().map(|url| url.as_str()).collect::<Vec<&str>>();
+    Client::build()
+        .with_nodes(&urls)
+        .unwrap()
+        .with_node_sync_interval(SYNC_INTERVAL)
+        .finish()
+        .unwrap()
+}
+
+fn url(url: &str) -> Url {
+    Url::parse(url).unwrap()
+}
+
+#[test]
+fn test_stale_node_evicted_and_readmitted() {
+    let stale = Arc::new(AtomicBool::new(false));
+    let fresh_node = mock_node(100, None, Arc::new(AtomicBool::new(false)));
+    let stale_node = mock_node(100, None, stale.clone());
+    let client = client(&[&fresh_node, &stale_node]);
+    assert_eq!(client.get_synced_nodes().len(), 2);
+
+    stale.store(true, Ordering::SeqCst);
+    thread::sleep(SYNC_INTERVAL * 3);
+    assert_eq!(client.get_synced_nodes(), vec![url(&fresh_node)]);
+    let status = client
+        .get_node_sync_status()
+        .into_iter()
+        .find(|status| url(&status.url) == url(&stale_node))
+        .unwrap();
+    assert!(!status.synced);
+    assert_eq!(status.latest_milestone_index, Some(90));
+    assert_eq!(status.solid_milestone_index, Some(90));
+    assert!(status.last_check.is_some());
+
+    stale.store(false, Ordering::SeqCst);
+    thread::sleep(SYNC_INTERVAL * 3);
+    assert_eq!(client.get_synced_nodes().len(), 2);
+    assert!(client.get_node_sync_status().iter().all(|status| status.synced));
+}
+
+#[test]
+fn test_old_milestone_not_synced() {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let recent_node = mock_node(100, Some(now), Arc::new(AtomicBool::new(false)));
+    let old_node = mock_node(100, Some(now - 3600), Arc::new(AtomicBool::new(false)));
+    let client = client(&[&recent_node, &old_node]);
+    assert_eq!(client.get_synced_nodes(), vec![url(&recent_node)]);
+}
+
+#[test]
+fn test_node_sync_status_disabled() {
+    let client = Client::build()
+        .with_node("http://localhost:14265")
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap();
+    let status = client.get_node_sync_status();
+    assert_eq!(status.len(), 1);
+    assert!(status[0].synced);
+    assert_eq!(status[0].latest_milestone_index, None);
+    assert_eq!(status[0].last_check, None);
+}
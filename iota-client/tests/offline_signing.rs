@@ -0,0 +1,358 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Offline signing tests, the transaction is prepared without the seed, signed offline and submitted to a mock node,
+// or prepared and signed by an offline client from the unspent outputs exported by the online client.
+
+mod common;
+
+use bee_common::packable::Packable;
+use bee_message::prelude::*;
+use bee_signing_ext::{
+    binary::{BIP32Path, Ed25519PrivateKey},
+    Signer,
+};
+use common::{utxo_mock_node_with_messages, MockOutput};
+use iota_client::{
+    api::{AccountAddress, InputCandidate, InputSigningData, PreparedTransactionData, RemainderStrategy},
+    ClientBuilder, Error, OutputType, Seed,
+};
+use std::str::FromStr;
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+const OTHER_SEED: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+const TRANSACTION_ID_1: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const TRANSACTION_ID_2: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+const RECIPIENT: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+
+fn seed(seed: &str) -> Seed {
+    Seed::from_ed25519_bytes(&hex::decode(seed).unwrap()).unwrap()
+}
+
+fn client(url: &str) -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(false)
+        .finish()
+        .unwrap()
+}
+
+// The first public addresses of the seed's first account followed by its first internal addresses, as known by the
+// online client.
+fn account_addresses() -> Vec<AccountAddress> {
+    let (public, internal): (Vec<AccountAddress>, Vec<AccountAddress>) = client("http://localhost:14265")
+        .find_addresses(&seed(SEED))
+        .with_account_index(0)
+        .with_range(0..3)
+        .get_all()
+        .unwrap()
+        .into_iter()
+        .enumerate()
+        // the addresses are (public, internal) pairs for each index
+        .map(|(position, (address, internal))| AccountAddress {
+            address: Address::try_from_bech32(&address.to_string()).unwrap(),
+            address_index: position / 2,
+            internal,
+        })
+        .partition(|address| !address.internal);
+    public.into_iter().chain(internal).collect()
+}
+
+// 3 Mi on the address 0 and 2 Mi on the address 2.
+fn utxo_set() -> Vec<MockOutput> {
+    let addresses = account_addresses();
+    vec![
+        MockOutput {
+            transaction_id: TRANSACTION_ID_1,
+            index: 0,
+            address: addresses[0].address.to_bech32(),
+            amount: 3_000_000,
+        },
+        MockOutput {
+            transaction_id: TRANSACTION_ID_2,
+            index: 1,
+            address: addresses[2].address.to_bech32(),
+            amount: 2_000_000,
+        },
+    ]
+}
+
+fn transaction(message: &Message) -> &TransactionPayload {
+    match message.payload() {
+        Some(Payload::Transaction(transaction)) => transaction,
+        _ => panic!("the message doesn't have a transaction payload"),
+    }
+}
+
+#[tokio::test]
+async fn test_offline_signing() {
+    let (url, messages) = utxo_mock_node_with_messages(utxo_set());
+    let client = client(&url);
+
+    // online, without the seed
+    let prepared = client
+        .send()
+        .with_account_addresses(account_addresses())
+        .with_output(&RECIPIENT.to_string().into(), 4_000_000)
+        .unwrap()
+        .prepare_transaction()
+        .await
+        .unwrap();
+    assert_eq!(prepared.inputs.len(), 2);
+    assert_eq!(prepared.essence.outputs().len(), 2);
+
+    // offline
+    let payload = prepared.sign(&seed(SEED)).unwrap();
+    assert_eq!(payload.unlock_blocks().len(), 2);
+
+    // online
+    let (_, message) = client.send().finish_signed_transaction(payload).await.unwrap();
+    // the mock node validates the posted message
+    let posted = messages.lock().unwrap().pop().unwrap();
+    assert_eq!(posted, message);
+
+    // the signatures are deterministic, so the payload matches the one signed by the online send
+    let seed = seed(SEED);
+    let (_, online_message) = client
+        .send()
+        .with_seed(&seed)
+        .with_output(&RECIPIENT.to_string().into(), 4_000_000)
+        .unwrap()
+        .finish_with_message()
+        .await
+        .unwrap();
+    assert_eq!(transaction(&message), transaction(&online_message));
+}
+
+#[tokio::test]
+async fn test_sign_with_another_seed() {
+    let (url, _) = utxo_mock_node_with_messages(utxo_set());
+    let prepared = client(&url)
+        .send()
+        .with_account_addresses(account_addresses())
+        .with_output(&RECIPIENT.to_string().into(), 2_000_000)
+        .unwrap()
+        .prepare_transaction()
+        .await
+        .unwrap();
+    assert!(matches!(prepared.sign(&seed(OTHER_SEED)), Err(Error::SigningError(_))));
+
+    // an input without signing data can't be signed
+    let mut prepared = prepared;
+    prepared.inputs.clear();
+    assert!(matches!(prepared.sign(&seed(SEED)), Err(Error::SigningError(_))));
+}
+
+// An external signer holding the Ed25519 seed, signing the essence with the key of each signer.
+fn external_signatures(prepared: &PreparedTransactionData, seed: &str) -> Vec<Ed25519Signature> {
+    let seed = match self::seed(seed) {
+        Seed::Ed25519(seed) => seed,
+        _ => unreachable!(),
+    };
+    let essence = prepared.essence_bytes().unwrap();
+    prepared
+        .signers()
+        .unwrap()
+        .into_iter()
+        .map(|signer| {
+            let path = BIP32Path::from_str(&format!(
+                "m/44'/4218'/{}'/{}'/{}'",
+                prepared.account_index, signer.internal as u32, signer.address_index
+            ))
+            .unwrap();
+            let private_key = Ed25519PrivateKey::generate_from_seed(&seed, &path).unwrap();
+            Ed25519Signature::new(
+                private_key.generate_public_key().to_bytes(),
+                Box::new(private_key.sign(&essence).to_bytes()),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_external_signatures() {
+    let (url, _) = utxo_mock_node_with_messages(utxo_set());
+    let prepared = client(&url)
+        .send()
+        .with_account_addresses(account_addresses())
+        .with_output(&RECIPIENT.to_string().into(), 4_000_000)
+        .unwrap()
+        .prepare_transaction()
+        .await
+        .unwrap();
+    assert_eq!(prepared.signers().unwrap().len(), 2);
+
+    let signatures = external_signatures(&prepared, SEED);
+    let payload = prepared.with_signatures(signatures.clone()).unwrap();
+    assert_eq!(payload, prepared.sign(&seed(SEED)).unwrap());
+
+    // one signature per signer
+    let result = prepared.with_signatures(signatures[..1].to_vec());
+    assert!(matches!(result, Err(Error::SigningError(_))));
+    // the key of another address
+    let result = prepared.with_signatures(external_signatures(&prepared, OTHER_SEED));
+    assert!(matches!(result, Err(Error::SigningError(_))));
+    // the signature of another message
+    let signatures = signatures
+        .iter()
+        .map(|signature| Ed25519Signature::new(*signature.public_key(), vec![0u8; 64].into_boxed_slice()))
+        .collect();
+    assert!(matches!(
+        prepared.with_signatures(signatures),
+        Err(Error::SigningError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_prepare_without_seed_or_addresses() {
+    let (url, _) = utxo_mock_node_with_messages(utxo_set());
+    let result = client(&url)
+        .send()
+        .with_output(&RECIPIENT.to_string().into(), 2_000_000)
+        .unwrap()
+        .prepare_transaction()
+        .await;
+    assert!(matches!(result, Err(Error::MissingParameter(parameter)) if parameter == "Seed"));
+}
+
+fn offline_client() -> iota_client::Client {
+    ClientBuilder::offline()
+        .with_network_id("testnet")
+        .with_bech32_hrp("iot")
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_offline_client() {
+    let (url, messages) = utxo_mock_node_with_messages(utxo_set());
+    let online_client = client(&url);
+
+    // online, the unspent outputs of the account are exported as JSON
+    let candidates = online_client
+        .send()
+        .with_account_addresses(account_addresses())
+        .get_input_candidates()
+        .await
+        .unwrap();
+    assert_eq!(candidates.len(), 2);
+    let exported = serde_json::to_string(&candidates).unwrap();
+
+    // offline, without any node
+    let offline_client = offline_client();
+    assert!(offline_client.is_offline());
+    let address = offline_client
+        .find_addresses(&seed(SEED))
+        .with_account_index(0)
+        .with_range(0..1)
+        .finish()
+        .unwrap()
+        .remove(0);
+    assert_eq!(
+        offline_client.parse_bech32_address(&address).unwrap(),
+        account_addresses()[0].address
+    );
+    let candidates: Vec<InputCandidate> = serde_json::from_str(&exported).unwrap();
+    let prepared = offline_client
+        .send()
+        .with_input_candidates(candidates)
+        .with_output(&RECIPIENT.to_string().into(), 4_000_000)
+        .unwrap()
+        .with_remainder_strategy(RemainderStrategy::ReuseFirstInputAddress)
+        .prepare_transaction()
+        .await
+        .unwrap();
+    assert_eq!(prepared.inputs.len(), 2);
+    let payload = prepared.sign(&seed(SEED)).unwrap();
+
+    // online
+    let (_, message) = online_client
+        .send()
+        .finish_signed_transaction(payload.clone())
+        .await
+        .unwrap();
+    assert_eq!(messages.lock().unwrap().pop().unwrap(), message);
+    assert_eq!(transaction(&message), &payload);
+
+    // the message id and bytes are computed without a node
+    let (message_id, bytes) = message.id();
+    let unpacked = Message::unpack(&mut bytes.as_slice()).unwrap();
+    assert_eq!(unpacked, message);
+    assert_eq!(unpacked.id().0, message_id);
+    assert_eq!(
+        offline_client.get_network_id().await.unwrap(),
+        iota_client::hash_network_id("testnet")
+    );
+}
+
+#[tokio::test]
+async fn test_offline_client_network_operations() {
+    let offline_client = offline_client();
+    assert!(matches!(offline_client.get_info().await, Err(Error::OfflineClient)));
+    assert!(matches!(offline_client.get_tips().await, Err(Error::OfflineClient)));
+    let result = offline_client.send().with_index("IOTA.RS OFFLINE").finish().await;
+    assert!(matches!(result, Err(Error::OfflineClient)));
+    assert_eq!(
+        Error::OfflineClient.to_string(),
+        "offline client: network operation not available"
+    );
+
+    // the inputs are looked up on a node without the exported unspent outputs, and so is the change address
+    let seed = seed(SEED);
+    let result = offline_client
+        .send()
+        .with_seed(&seed)
+        .with_output(&RECIPIENT.to_string().into(), 1_000_000)
+        .unwrap()
+        .prepare_transaction()
+        .await;
+    assert!(matches!(result, Err(Error::OfflineClient)));
+    // 3 Mi on the address 0
+    let address = account_addresses().remove(0);
+    let candidates = vec![InputCandidate {
+        signing_data: InputSigningData {
+            input: UTXOInput::new(TransactionId::from_str(TRANSACTION_ID_1).unwrap(), 0).unwrap(),
+            address: address.address,
+            address_index: address.address_index,
+            internal: address.internal,
+        },
+        amount: 3_000_000,
+        output_type: OutputType::SignatureLockedSingle,
+    }];
+    let result = offline_client
+        .send()
+        .with_seed(&seed)
+        .with_input_candidates(candidates.clone())
+        .with_output(&RECIPIENT.to_string().into(), 1_000_000)
+        .unwrap()
+        .prepare_transaction()
+        .await;
+    assert!(matches!(result, Err(Error::OfflineClient)));
+    // the dust protection of the recipient is checked on a node
+    let result = offline_client
+        .send()
+        .with_input_candidates(candidates)
+        .with_output(&RECIPIENT.to_string().into(), 500_000)
+        .unwrap()
+        .with_remainder_strategy(RemainderStrategy::ReuseFirstInputAddress)
+        .prepare_transaction()
+        .await;
+    assert!(matches!(result, Err(Error::OfflineClient)));
+
+    // an offline client has no nodes and needs the bech32 HRP
+    assert!(matches!(
+        ClientBuilder::offline().finish(),
+        Err(Error::MissingParameter(_))
+    ));
+    let result = ClientBuilder::offline()
+        .with_node("http://localhost:14265")
+        .unwrap()
+        .with_bech32_hrp("iot")
+        .finish();
+    assert!(matches!(result, Err(Error::InvalidParameter(_))));
+    // the network id can't be fetched
+    let result = ClientBuilder::offline().with_bech32_hrp("iot").finish().unwrap();
+    assert!(matches!(result.get_network_id().await, Err(Error::OfflineClient)));
+}
@@ -0,0 +1,138 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Output cache tests against a mock node holding many outputs, every other output spent. The mock node records the
+// looked up outputs.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{start_concurrent_mock_node, MESSAGE_ID};
+use iota_client::{Client, OutputCacheStats};
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+const ADDRESS_HEX: &str = "644eb5594b880f7da82f35902296199a0eb9186999797f661b324f8d754a3a09";
+const OUTPUTS: usize = 1000;
+
+fn output_id(index: usize) -> String {
+    format!("{:064x}0000", index)
+}
+
+fn is_spent(index: usize) -> bool {
+    index % 2 == 0
+}
+
+fn output_ids() -> Vec<UTXOInput> {
+    (0..OUTPUTS)
+        .map(|index| UTXOInput::from_str(&output_id(index)).unwrap())
+        .collect()
+}
+
+// Starts a mock node holding the outputs, returns its URL and the indexes of the looked up outputs.
+fn outputs_mock_node() -> (String, Arc<Mutex<Vec<usize>>>) {
+    let looked_up = Arc::new(Mutex::new(Vec::new()));
+    let looked_up_ = looked_up.clone();
+    let url = start_concurrent_mock_node(move |method, path, _| {
+        let output_id = match path.strip_prefix("/api/v1/outputs/") {
+            Some(output_id) if method == "GET" => output_id,
+            _ => return ("404 Not Found", String::new()),
+        };
+        let index = usize::from_str_radix(&output_id[..64], 16).unwrap();
+        looked_up_.lock().unwrap().push(index);
+        (
+            "200 OK",
+            serde_json::json!({ "data": {
+                "messageId": MESSAGE_ID,
+                "transactionId": &output_id[..64],
+                "outputIndex": 0,
+                "isSpent": is_spent(index),
+                "output": {
+                    "type": 0,
+                    "address": { "type": 1, "address": ADDRESS_HEX },
+                    "amount": 1_000_000,
+                },
+            }})
+            .to_string(),
+        )
+    });
+    (url, looked_up)
+}
+
+fn client(url: &str, unspent_ttl: Option<Duration>) -> Client {
+    let builder = Client::build().with_node(url).unwrap().with_node_sync_disabled();
+    let builder = match unspent_ttl {
+        Some(unspent_ttl) => builder.with_output_cache(unspent_ttl),
+        None => builder,
+    };
+    builder.finish().unwrap()
+}
+
+// Finds the outputs, checks they're in the order of the ids and returns the sorted indexes of the looked up outputs.
+async fn find_outputs(client: &Client, looked_up: &Mutex<Vec<usize>>) -> Vec<usize> {
+    looked_up.lock().unwrap().clear();
+    let outputs = client.find_outputs(&output_ids(), &[]).await.unwrap();
+    let indexes: Vec<usize> = outputs
+        .iter()
+        .map(|output| {
+            let index = usize::from_str_radix(&hex::encode(&output.transaction_id), 16).unwrap();
+            assert_eq!(output.is_spent, is_spent(index));
+            index
+        })
+        .collect();
+    assert_eq!(indexes, (0..OUTPUTS).collect::<Vec<usize>>());
+    let mut looked_up = looked_up.lock().unwrap().clone();
+    looked_up.sort_unstable();
+    looked_up
+}
+
+#[tokio::test]
+async fn test_spent_outputs_cached() {
+    let (url, looked_up) = outputs_mock_node();
+    let client = client(&url, Some(Duration::from_secs(0)));
+
+    assert_eq!(find_outputs(&client, &looked_up).await.len(), OUTPUTS);
+    // only the unspent outputs are looked up again
+    let unspent: Vec<usize> = (0..OUTPUTS).filter(|index| !is_spent(*index)).collect();
+    assert_eq!(find_outputs(&client, &looked_up).await, unspent);
+    assert_eq!(
+        client.get_output_cache_stats(),
+        Some(OutputCacheStats {
+            hits: (OUTPUTS / 2) as u64,
+            misses: (OUTPUTS + OUTPUTS / 2) as u64,
+            spent: OUTPUTS / 2,
+            unspent: 0,
+        })
+    );
+
+    client.clear_output_cache();
+    assert_eq!(client.get_output_cache_stats(), Some(Default::default()));
+    assert_eq!(find_outputs(&client, &looked_up).await.len(), OUTPUTS);
+}
+
+#[tokio::test]
+async fn test_unspent_outputs_ttl() {
+    let (url, looked_up) = outputs_mock_node();
+    let client = client(&url, Some(Duration::from_millis(500)));
+
+    assert_eq!(find_outputs(&client, &looked_up).await.len(), OUTPUTS);
+    assert!(find_outputs(&client, &looked_up).await.is_empty());
+    let stats = client.get_output_cache_stats().unwrap();
+    assert_eq!((stats.spent, stats.unspent), (OUTPUTS / 2, OUTPUTS / 2));
+
+    // the unspent outputs expired
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(find_outputs(&client, &looked_up).await.len(), OUTPUTS / 2);
+}
+
+#[tokio::test]
+async fn test_output_cache_disabled() {
+    let (url, looked_up) = outputs_mock_node();
+    let client = client(&url, None);
+    assert_eq!(find_outputs(&client, &looked_up).await.len(), OUTPUTS);
+    assert_eq!(find_outputs(&client, &looked_up).await.len(), OUTPUTS);
+    assert_eq!(client.get_output_cache_stats(), None);
+}
@@ -0,0 +1,230 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Custom parents tests against a mock node knowing some messages and recording the posted messages.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{node_info, start_mock_node, utxo_mock_node_with_messages, MockOutput, MESSAGE_ID};
+use iota_client::{api::MESSAGE_PARENTS_RANGE, Client, Error, MessageJson, Seed};
+use std::{
+    convert::TryInto,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+// The known messages, in the protocol order.
+const PARENT_1: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+const PARENT_2: &str = "2222222222222222222222222222222222222222222222222222222222222222";
+// A message unknown to the node, also refused as parent of a posted message.
+const UNKNOWN_PARENT: &str = "3333333333333333333333333333333333333333333333333333333333333333";
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+const TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const RECIPIENT: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+
+struct MockNode {
+    url: String,
+    tips_requests: Arc<AtomicUsize>,
+    messages: Arc<Mutex<Vec<Message>>>,
+}
+
+fn mock_node() -> MockNode {
+    let tips_requests = Arc::new(AtomicUsize::new(0));
+    let tips_requests_ = tips_requests.clone();
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let messages_ = messages.clone();
+    let url = start_mock_node(move |method, path, body| {
+        let known = |message_id: &str| message_id == PARENT_1 || message_id == PARENT_2;
+        if method == "GET" && path == "/api/v1/info" {
+            ("200 OK", node_info(4000f64, &["PoW"]))
+        } else if method == "GET" && path == "/api/v1/tips" {
+            tips_requests_.fetch_add(1, Ordering::SeqCst);
+            ("200 OK", common::tips())
+        } else if let Some(message_id) = path
+            .strip_prefix("/api/v1/messages/")
+            .and_then(|path| path.strip_suffix("/metadata"))
+            .filter(|message_id| known(message_id))
+        {
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "messageId": message_id,
+                    "parent1MessageId": MESSAGE_ID,
+                    "parent2MessageId": MESSAGE_ID,
+                    "isSolid": true,
+                }})
+                .to_string(),
+            )
+        } else if method == "POST" && path == "/api/v1/messages" {
+            let message: MessageJson = serde_json::from_slice(body).unwrap();
+            let message: Message = message.try_into().unwrap();
+            let parents = [message.parent1().to_string(), message.parent2().to_string()];
+            if parents.iter().all(|parent| known(parent)) {
+                messages_.lock().unwrap().push(message);
+                (
+                    "201 Created",
+                    serde_json::json!({ "data": { "messageId": MESSAGE_ID } }).to_string(),
+                )
+            } else {
+                (
+                    "400 Bad Request",
+                    r#"{"error":{"code":"400","message":"invalid parameter, error: parents not found"}}"#.to_string(),
+                )
+            }
+        } else {
+            ("404 Not Found", String::new())
+        }
+    });
+    MockNode {
+        url,
+        tips_requests,
+        messages,
+    }
+}
+
+fn client(url: &str) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(false)
+        .finish()
+        .unwrap()
+}
+
+fn message_id(message_id: &str) -> MessageId {
+    MessageId::from_str(message_id).unwrap()
+}
+
+// The parents of the message, parent1 then parent2.
+fn parents(message: &Message) -> Vec<String> {
+    vec![message.parent1().to_string(), message.parent2().to_string()]
+}
+
+#[tokio::test]
+async fn test_indexation_parents() {
+    let node = mock_node();
+    // the parents are given in the reverse order
+    client(&node.url)
+        .send()
+        .with_index("parents")
+        .with_parents(vec![message_id(PARENT_2), message_id(PARENT_1)])
+        .unwrap()
+        .finish()
+        .await
+        .unwrap();
+    assert_eq!(node.tips_requests.load(Ordering::SeqCst), 0);
+    let messages = node.messages.lock().unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(parents(&messages[0]), vec![PARENT_1, PARENT_2]);
+}
+
+#[tokio::test]
+async fn test_single_parent() {
+    let node = mock_node();
+    client(&node.url)
+        .send()
+        .with_index("parents")
+        .with_parents(vec![message_id(PARENT_2)])
+        .unwrap()
+        .finish()
+        .await
+        .unwrap();
+    assert_eq!(parents(&node.messages.lock().unwrap()[0]), vec![PARENT_2, PARENT_2]);
+}
+
+#[tokio::test]
+async fn test_transfer_parents() {
+    let seed = Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap();
+    let (address, _) = client("http://localhost:14265")
+        .find_addresses(&seed)
+        .with_account_index(0)
+        .with_range(0..1)
+        .get_all()
+        .unwrap()
+        .remove(0);
+    let (url, messages) = utxo_mock_node_with_messages(vec![MockOutput {
+        transaction_id: TRANSACTION_ID,
+        index: 0,
+        address: address.to_string(),
+        amount: 1_000_000,
+    }]);
+    client(&url)
+        .send()
+        .with_seed(&seed)
+        .with_output(&RECIPIENT.to_string().into(), 1_000_000)
+        .unwrap()
+        .with_parents(vec![message_id(PARENT_2), message_id(PARENT_1)])
+        .unwrap()
+        .finish()
+        .await
+        .unwrap();
+    let messages = messages.lock().unwrap();
+    assert!(matches!(messages[0].payload(), Some(Payload::Transaction(_))));
+    assert_eq!(parents(&messages[0]), vec![PARENT_1, PARENT_2]);
+}
+
+#[tokio::test]
+async fn test_unknown_parents() {
+    let node = mock_node();
+    let error = client(&node.url)
+        .send()
+        .with_index("parents")
+        .with_parents(vec![message_id(UNKNOWN_PARENT), message_id(PARENT_1)])
+        .unwrap()
+        .finish()
+        .await
+        .unwrap_err();
+    match error {
+        Error::UnknownParents(unknown) => assert_eq!(unknown, vec![UNKNOWN_PARENT]),
+        error => panic!("unexpected error {}", error),
+    }
+    assert!(node.messages.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_rejected_parents() {
+    let node = mock_node();
+    let error = client(&node.url)
+        .send()
+        .with_index("parents")
+        .with_parents(vec![message_id(UNKNOWN_PARENT), message_id(PARENT_1)])
+        .unwrap()
+        .with_parents_check_disabled()
+        .finish()
+        .await
+        .unwrap_err();
+    match error {
+        Error::ParentsRejected(parents, answer) => {
+            assert_eq!(parents, vec![PARENT_1, UNKNOWN_PARENT]);
+            assert!(answer.contains("parents not found"));
+        }
+        error => panic!("unexpected error {}", error),
+    }
+}
+
+#[test]
+fn test_invalid_parents() {
+    let client = client("http://localhost:14265");
+    let too_many = (0..=*MESSAGE_PARENTS_RANGE.end())
+        .map(|index| MessageId::new([index as u8; 32]))
+        .collect();
+    assert!(matches!(
+        client.send().with_parents(too_many),
+        Err(Error::InvalidParameter(_))
+    ));
+    assert!(matches!(
+        client.send().with_parents(Vec::new()),
+        Err(Error::InvalidParameter(_))
+    ));
+    assert!(matches!(
+        client
+            .send()
+            .with_parents(vec![message_id(PARENT_1), message_id(PARENT_1)]),
+        Err(Error::InvalidParameter(_))
+    ));
+}
@@ -0,0 +1,144 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Peers API tests against a mock node replaying a captured response to authenticated requests.
+
+mod common;
+
+use common::start_mock_node_with_headers;
+use iota_client::{Error, NodeAuth, PeerRelation};
+
+const JWT: &str = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiJob3JuZXQifQ.signature";
+// `admin:secret` encoded in base64
+const BASIC_AUTH: &str = "Basic YWRtaW46c2VjcmV0";
+
+const PEERS: &str = r#"{
+    "data": [
+        {
+            "id": "12D3KooWRVt2HXaQiJDBBcjhMdVc3fvEfuGciJgCgiJbRMbAiGNW",
+            "multiAddresses": ["/ip4/192.168.1.10/tcp/15600"],
+            "alias": "hornet-1",
+            "relation": "known",
+            "connected": true,
+            "gossip": {
+                "heartbeat": {
+                    "solidMilestoneIndex": 176,
+                    "prunedMilestoneIndex": 0,
+                    "latestMilestoneIndex": 176,
+                    "connectedNeighbors": 3,
+                    "syncedNeighbors": 3
+                },
+                "metrics": {
+                    "newMessages": 40,
+                    "knownMessages": 16,
+                    "receivedMessages": 56,
+                    "receivedMessageRequests": 2,
+                    "receivedMilestoneRequests": 1,
+                    "receivedHeartbeats": 30,
+                    "sentMessages": 48,
+                    "sentMessageRequests": 3,
+                    "sentMilestoneRequests": 0,
+                    "sentHeartbeats": 31,
+                    "droppedPackets": 0
+                }
+            }
+        },
+        {
+            "id": "12D3KooWC7hHjaWQJ9ehNrekjVavHHJYtuip2DbteQHNDELFGwGo",
+            "multiAddresses": [],
+            "relation": "autopeered",
+            "connected": false
+        }
+    ]
+}"#;
+
+// Starts a mock node answering the peers requests sent with the JWT or the basic auth credentials.
+fn client(auth: Option<NodeAuth>) -> iota_client::Client {
+    let url = start_mock_node_with_headers(|method, path, headers, _| {
+        let authorized = headers.iter().any(|(name, value)| {
+            name == "authorization" && (*value == format!("Bearer {}", JWT) || value == BASIC_AUTH)
+        });
+        match (method, path) {
+            ("GET", "/api/v1/peers") if authorized => ("200 OK", PEERS.to_string()),
+            ("GET", "/api/v1/peers") => ("401 Unauthorized", String::new()),
+            _ => ("404 Not Found", String::new()),
+        }
+    });
+    let mut builder = iota_client::Client::build()
+        .with_node(&url)
+        .unwrap()
+        .with_node_sync_disabled();
+    if let Some(auth) = auth {
+        builder = builder.with_node_auth(auth);
+    }
+    builder.finish().unwrap()
+}
+
+#[tokio::test]
+async fn test_get_peers() {
+    let peers = client(Some(NodeAuth::Jwt { jwt: JWT.to_string() }))
+        .get_peers()
+        .await
+        .unwrap();
+    assert_eq!(peers.len(), 2);
+
+    let peer = &peers[0];
+    assert_eq!(peer.id, "12D3KooWRVt2HXaQiJDBBcjhMdVc3fvEfuGciJgCgiJbRMbAiGNW");
+    assert_eq!(peer.multi_addresses, vec!["/ip4/192.168.1.10/tcp/15600"]);
+    assert_eq!(peer.alias.as_deref(), Some("hornet-1"));
+    assert_eq!(peer.relation, PeerRelation::Known);
+    assert!(peer.connected);
+    let metrics = &peer.gossip.as_ref().unwrap().metrics;
+    assert_eq!(metrics.known_messages, 16);
+    assert_eq!(metrics.received_messages, 56);
+    assert_eq!(metrics.sent_messages, 48);
+
+    // a disconnected peer has no alias nor gossip
+    let peer = &peers[1];
+    assert_eq!(peer.alias, None);
+    assert_eq!(peer.relation, PeerRelation::Autopeered);
+    assert!(!peer.connected);
+    assert_eq!(peer.gossip, None);
+}
+
+#[tokio::test]
+async fn test_get_peers_basic_auth() {
+    let auth = NodeAuth::BasicAuth {
+        username: "admin".to_string(),
+        password: "secret".to_string(),
+    };
+    assert_eq!(client(Some(auth)).get_peers().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_get_peers_unauthorized() {
+    for auth in vec![
+        None,
+        Some(NodeAuth::Jwt {
+            jwt: "invalid".to_string(),
+        }),
+    ] {
+        match client(auth).get_peers().await {
+            Err(error @ Error::Unauthorized(_)) => assert!(error.to_string().contains("configure the node auth")),
+            _ => panic!("the refused request isn't reported as unauthorized"),
+        }
+    }
+}
+
+#[test]
+fn test_node_auth_json() {
+    assert_eq!(
+        serde_json::from_str::<NodeAuth>(r#"{ "jwt": "token" }"#).unwrap(),
+        NodeAuth::Jwt {
+            jwt: "token".to_string()
+        }
+    );
+    assert_eq!(
+        serde_json::from_str::<NodeAuth>(r#"{ "username": "admin", "password": "secret" }"#).unwrap(),
+        NodeAuth::BasicAuth {
+            username: "admin".to_string(),
+            password: "secret".to_string(),
+        }
+    );
+    assert!(serde_json::from_str::<NodeAuth>(r#"{ "username": "admin" }"#).is_err());
+}
@@ -0,0 +1,345 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Permanode fallback tests against a mock node, with the milestones up to 10 pruned, and a mock permanode serving the
+// pruned data under `/api/mainnet/` with paged lists.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{start_mock_node, MESSAGE_ID};
+use iota_client::{Bech32Address, Client, DataSource, Error, OutputsOptions, PrunedRequest};
+
+const PRUNING_INDEX: u64 = 10;
+const KNOWN_INDEX: &str = "known";
+const PRUNED_INDEX: &str = "pruned";
+const ADDRESS: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+const NODE_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const PRUNED_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+const UNKNOWN_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000003";
+const PAGED_MESSAGE_IDS: [&str; 3] = [
+    "0000000000000000000000000000000000000000000000000000000000000004",
+    "0000000000000000000000000000000000000000000000000000000000000005",
+    "0000000000000000000000000000000000000000000000000000000000000006",
+];
+const OUTPUT_IDS: [&str; 2] = [
+    "00000000000000000000000000000000000000000000000000000000000000010000",
+    "00000000000000000000000000000000000000000000000000000000000000020100",
+];
+
+const NOT_FOUND: &str = r#"{"error":{"code":"404","message":"not found"}}"#;
+
+// A message without payload, told apart by its network id: 1 from the node, 2 from the permanode.
+fn message(network_id: u64) -> String {
+    serde_json::json!({ "data": {
+        "networkId": network_id.to_string(),
+        "parent1MessageId": MESSAGE_ID,
+        "parent2MessageId": MESSAGE_ID,
+        "nonce": "0",
+    }})
+    .to_string()
+}
+
+fn milestone(index: u64) -> String {
+    serde_json::json!({ "data": {
+        "index": index,
+        "messageId": MESSAGE_ID,
+        "timestamp": 1609950538,
+    }})
+    .to_string()
+}
+
+fn node() -> String {
+    start_mock_node(|method, path, _| {
+        if method != "GET" {
+            return ("404 Not Found", NOT_FOUND.to_string());
+        }
+        let outputs_path = format!("/api/v1/addresses/{}/outputs", ADDRESS);
+        match path {
+            "/api/v1/info" => (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "name": "mock",
+                    "version": "0.0.0",
+                    "isHealthy": true,
+                    "networkId": "testnet",
+                    "minPowScore": 4000,
+                    "latestMilestoneIndex": 176,
+                    "solidMilestoneIndex": 176,
+                    "pruningIndex": PRUNING_INDEX,
+                    "features": [],
+                }})
+                .to_string(),
+            ),
+            "/api/v1/milestones/176" => ("200 OK", milestone(176)),
+            path if path == format!("/api/v1/messages/{}", NODE_MESSAGE_ID) => ("200 OK", message(1)),
+            path if path.starts_with("/api/v1/messages?index=") => {
+                let message_ids = match path.trim_start_matches("/api/v1/messages?index=") {
+                    KNOWN_INDEX => vec![NODE_MESSAGE_ID],
+                    _ => vec![],
+                };
+                (
+                    "200 OK",
+                    serde_json::json!({ "data": {
+                        "maxResults": 1000,
+                        "count": message_ids.len(),
+                        "messageIds": message_ids,
+                    }})
+                    .to_string(),
+                )
+            }
+            // the node only has the unspent output
+            path if path.starts_with(outputs_path.as_str()) => {
+                let output_ids = match path.strip_prefix(outputs_path.as_str()) {
+                    Some("") => vec![OUTPUT_IDS[0]],
+                    _ => vec![],
+                };
+                (
+                    "200 OK",
+                    serde_json::json!({ "data": {
+                        "address": ADDRESS,
+                        "maxResults": 1000,
+                        "count": output_ids.len(),
+                        "outputIds": output_ids,
+                    }})
+                    .to_string(),
+                )
+            }
+            _ => ("404 Not Found", NOT_FOUND.to_string()),
+        }
+    })
+}
+
+fn permanode() -> String {
+    let url = start_mock_node(|method, path, _| {
+        if method != "GET" {
+            return ("404 Not Found", NOT_FOUND.to_string());
+        }
+        let outputs_path = format!("/api/mainnet/addresses/{}/outputs", ADDRESS);
+        let page = match path {
+            "/api/mainnet/milestones/5" => return ("200 OK", milestone(5)),
+            "/api/mainnet/milestones/176" => return ("200 OK", milestone(176)),
+            path if [NODE_MESSAGE_ID, PRUNED_MESSAGE_ID]
+                .iter()
+                .chain(PAGED_MESSAGE_IDS.iter())
+                .any(|id| path == format!("/api/mainnet/messages/{}", id)) =>
+            {
+                return ("200 OK", message(2));
+            }
+            // the pages hold ids or records, overlap, and the cursor is next to the list or in it
+            "/api/mainnet/messages?index=pruned" => serde_json::json!({
+                "data": {
+                    "messageIds": [{ "messageId": PAGED_MESSAGE_IDS[0] }, PAGED_MESSAGE_IDS[1]],
+                },
+                "state": "page2",
+            }),
+            "/api/mainnet/messages?index=pruned&state=page2" => serde_json::json!({
+                "data": {
+                    "messageIds": [PAGED_MESSAGE_IDS[1], PAGED_MESSAGE_IDS[2]],
+                    "state": "page3",
+                },
+            }),
+            "/api/mainnet/messages?index=pruned&state=page3" => serde_json::json!({ "data": { "messageIds": [] } }),
+            "/api/mainnet/messages?index=known" => serde_json::json!({ "data": { "messageIds": [MESSAGE_ID] } }),
+            path if path.starts_with("/api/mainnet/messages?index=") => {
+                serde_json::json!({ "data": { "messageIds": [] } })
+            }
+            path if path == format!("{}?include-spent=true", outputs_path) => serde_json::json!({
+                "data": { "outputIds": [{ "outputId": OUTPUT_IDS[0] }] },
+                "state": "page2",
+            }),
+            path if path == format!("{}?include-spent=true&state=page2", outputs_path) => serde_json::json!({
+                "data": { "outputIds": [OUTPUT_IDS[1]] },
+            }),
+            _ => return ("404 Not Found", NOT_FOUND.to_string()),
+        };
+        ("200 OK", page.to_string())
+    });
+    format!("{}/api/mainnet", url)
+}
+
+fn client(permanode_only: bool) -> Client {
+    Client::build()
+        .with_node(&node())
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_permanode(&permanode())
+        .unwrap()
+        .with_permanode_only(permanode_only)
+        .finish()
+        .unwrap()
+}
+
+fn message_id(message_id: &str) -> MessageId {
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(message_id, &mut bytes).unwrap();
+    MessageId::from(bytes)
+}
+
+fn message_ids(message_ids: &[&str]) -> Vec<MessageId> {
+    message_ids.iter().map(|id| message_id(id)).collect()
+}
+
+#[tokio::test]
+async fn test_message_from_node() {
+    let message = client(false)
+        .get_message()
+        .data_with_source(&message_id(NODE_MESSAGE_ID))
+        .await
+        .unwrap();
+    assert_eq!(message.source, DataSource::Node);
+    assert_eq!(message.data.network_id(), 1);
+}
+
+#[tokio::test]
+async fn test_message_from_permanode() {
+    let message = client(false)
+        .get_message()
+        .data_with_source(&message_id(PRUNED_MESSAGE_ID))
+        .await
+        .unwrap();
+    assert_eq!(message.source, DataSource::Permanode);
+    assert_eq!(message.data.network_id(), 2);
+
+    // the plain query falls back as well
+    let message = client(false)
+        .get_message()
+        .data(&message_id(PRUNED_MESSAGE_ID))
+        .await
+        .unwrap();
+    assert_eq!(message.network_id(), 2);
+}
+
+#[tokio::test]
+async fn test_message_unknown() {
+    match client(false)
+        .get_message()
+        .data_with_source(&message_id(UNKNOWN_MESSAGE_ID))
+        .await
+    {
+        Err(Error::ResponseError(404, body, _)) => assert_eq!(body, NOT_FOUND),
+        r => panic!("expected the not found error of the node, got {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn test_index() {
+    let client = client(false);
+    let known = client.get_message().index_with_source(KNOWN_INDEX).await.unwrap();
+    assert_eq!(known.source, DataSource::Node);
+    assert_eq!(known.data.to_vec(), message_ids(&[NODE_MESSAGE_ID]));
+
+    // the pages of the permanode are merged without duplicates
+    let pruned = client.get_message().index_with_source(PRUNED_INDEX).await.unwrap();
+    assert_eq!(pruned.source, DataSource::Permanode);
+    assert_eq!(pruned.data.to_vec(), message_ids(&PAGED_MESSAGE_IDS));
+
+    // both lists are empty
+    let unknown = client.get_message().index_with_source("unknown").await.unwrap();
+    assert_eq!(unknown.source, DataSource::Node);
+    assert!(unknown.data.is_empty());
+
+    let found = client.find_messages(&[PRUNED_INDEX], &[]).await.unwrap();
+    assert_eq!(found.len(), PAGED_MESSAGE_IDS.len());
+}
+
+#[tokio::test]
+async fn test_milestones() {
+    let client = client(false);
+    let milestone = client.get_milestone_with_source(176).await.unwrap();
+    assert_eq!(milestone.source, DataSource::Node);
+    assert_eq!(milestone.data.index, 176);
+
+    let milestone = client.get_milestone_with_source(5).await.unwrap();
+    assert_eq!(milestone.source, DataSource::Permanode);
+    assert_eq!(milestone.data.index, 5);
+    assert_eq!(milestone.data.message_id, MESSAGE_ID);
+
+    // a pruned milestone the permanode doesn't have either keeps the error of the node
+    assert!(matches!(
+        client.get_milestone(1).await,
+        Err(Error::PrunedData {
+            requested: PrunedRequest::Milestone(1),
+            pruning_index: PRUNING_INDEX
+        })
+    ));
+}
+
+#[tokio::test]
+async fn test_address_outputs() {
+    let client = client(false);
+    let address = Bech32Address(ADDRESS.to_string());
+
+    // the unspent outputs are never pruned, an empty list of the node isn't checked against the permanode
+    let unspent = client
+        .get_address()
+        .outputs_response_with_source(&address, Default::default())
+        .await
+        .unwrap();
+    assert_eq!(unspent.source, DataSource::Node);
+    assert_eq!(unspent.data.output_ids.to_vec(), vec![OUTPUT_IDS[0]]);
+
+    let options = OutputsOptions {
+        include_spent: true,
+        ..Default::default()
+    };
+    let spent = client
+        .get_address()
+        .outputs_response_with_source(&address, options)
+        .await
+        .unwrap();
+    assert_eq!(spent.source, DataSource::Permanode);
+    assert_eq!(spent.data.output_ids.to_vec(), OUTPUT_IDS.to_vec());
+    assert_eq!(spent.data.count, OUTPUT_IDS.len());
+}
+
+#[tokio::test]
+async fn test_permanode_only() {
+    let client = client(true);
+    let message = client
+        .get_message()
+        .data_with_source(&message_id(NODE_MESSAGE_ID))
+        .await
+        .unwrap();
+    assert_eq!(message.source, DataSource::Permanode);
+    assert_eq!(message.data.network_id(), 2);
+
+    let known = client.get_message().index_with_source(KNOWN_INDEX).await.unwrap();
+    assert_eq!(known.source, DataSource::Permanode);
+    assert_eq!(known.data.to_vec(), message_ids(&[MESSAGE_ID]));
+
+    let milestone = client.get_milestone_with_source(176).await.unwrap();
+    assert_eq!(milestone.source, DataSource::Permanode);
+
+    // the errors of the permanode are returned as they are
+    match client.get_milestone(1).await {
+        Err(Error::ResponseError(404, body, _)) => assert_eq!(body, NOT_FOUND),
+        r => panic!("expected the not found error of the permanode, got {:?}", r),
+    }
+}
+
+#[test]
+fn test_permanode_options() {
+    assert!(matches!(
+        Client::build()
+            .with_node("http://localhost:14265")
+            .unwrap()
+            .with_node_sync_disabled()
+            .with_permanode_only(true)
+            .finish(),
+        Err(Error::InvalidParameter(_))
+    ));
+    assert!(matches!(
+        Client::build().with_permanode("not a url"),
+        Err(Error::UrlError)
+    ));
+    assert!(matches!(
+        iota_client::ClientBuilder::offline()
+            .with_network_id("testnet")
+            .with_bech32_hrp("atoi")
+            .with_permanode("http://localhost:8080/api/mainnet")
+            .unwrap()
+            .finish(),
+        Err(Error::InvalidParameter(_))
+    ));
+}
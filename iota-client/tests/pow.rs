@@ -0,0 +1,204 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// PoW tests against a mock node that validates the PoW score of the submitted messages.
+
+mod common;
+
+use bee_common::packable::Packable;
+use bee_message::prelude::*;
+use bee_pow::score::compute_pow_score;
+
+use common::{node_info, start_mock_node, tips, MESSAGE_ID};
+use iota_client::{hash_network_id, pow::finish_pow, Error, LocalPow, MessageJson, NonceProvider, RemotePow};
+use std::{
+    convert::TryInto,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+const MIN_POW_SCORE: f64 = 10f64;
+
+fn pow_mock_node(features: &'static [&'static str]) -> String {
+    start_mock_node(move |method, path, body| match (method, path) {
+        ("GET", "/api/v1/info") => ("200 OK", node_info(MIN_POW_SCORE, features)),
+        ("GET", "/api/v1/tips") => ("200 OK", tips()),
+        ("POST", "/api/v1/messages") => {
+            let message: MessageJson = serde_json::from_slice(body).unwrap();
+            let message: Message = message.try_into().unwrap();
+            if message.nonce() == 0 {
+                // the node does the PoW itself
+                if features.contains(&"PoW") {
+                    (
+                        "201 Created",
+                        serde_json::json!({ "data": { "messageId": MESSAGE_ID } }).to_string(),
+                    )
+                } else {
+                    (
+                        "400 Bad Request",
+                        "proof of work is not enabled on this node".to_string(),
+                    )
+                }
+            } else {
+                let mut bytes = Vec::new();
+                message.pack(&mut bytes).unwrap();
+                let score = compute_pow_score(&bytes);
+                if score >= MIN_POW_SCORE {
+                    (
+                        "201 Created",
+                        serde_json::json!({ "data": { "messageId": MESSAGE_ID } }).to_string(),
+                    )
+                } else {
+                    (
+                        "400 Bad Request",
+                        format!("invalid message: PoW score {} too low", score),
+                    )
+                }
+            }
+        }
+        _ => ("404 Not Found", String::new()),
+    })
+}
+
+#[tokio::test]
+async fn test_local_pow_reaches_the_node_score() {
+    let client = iota_client::Client::build()
+        .with_node(&pow_mock_node(&[]))
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(true)
+        .with_pow_worker_count(1)
+        .finish()
+        .unwrap();
+    let message_id = client
+        .send()
+        .with_index("iota.rs PoW test")
+        .with_data(b"tiny PoW score".to_vec())
+        .finish()
+        .await
+        .unwrap();
+    assert_eq!(message_id.to_string(), MESSAGE_ID);
+    assert_eq!(client.get_network_info().min_pow_score, MIN_POW_SCORE);
+}
+
+#[tokio::test]
+async fn test_remote_pow_refused() {
+    let client = iota_client::Client::build()
+        .with_node(&pow_mock_node(&[]))
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(false)
+        .finish()
+        .unwrap();
+    let result = client.send().with_index("iota.rs PoW test").finish().await;
+    assert!(matches!(result, Err(Error::RemotePowRefused(_, _))));
+}
+
+#[tokio::test]
+async fn test_remote_pow() {
+    let client = iota_client::Client::build()
+        .with_node(&pow_mock_node(&["PoW"]))
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(false)
+        .finish()
+        .unwrap();
+    let message_id = client.send().with_index("iota.rs PoW test").finish().await.unwrap();
+    assert_eq!(message_id.to_string(), MESSAGE_ID);
+}
+
+const INDEX: &str = "iota.rs PoW test";
+const DATA: &[u8] = b"outsourced PoW";
+
+// A PoW service answering the nonce it computed beforehand for the only message it expects.
+struct PrecomputedNonce {
+    bytes: Vec<u8>,
+    nonce: Option<u64>,
+    calls: Arc<AtomicUsize>,
+}
+
+impl NonceProvider for PrecomputedNonce {
+    fn nonce(&self, bytes: &[u8], target_score: f64) -> iota_client::Result<u64> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        assert_eq!(bytes, &self.bytes[..]);
+        assert_eq!(target_score, MIN_POW_SCORE);
+        self.nonce
+            .ok_or_else(|| Error::NonceProvider("the PoW service timed out".to_string()))
+    }
+}
+
+// The indexation message the client builds on the tips of the mock node, packed without its nonce.
+fn message_bytes() -> Vec<u8> {
+    let tip = MessageId::from_str(MESSAGE_ID).unwrap();
+    let payload = IndexationPayload::new(INDEX.to_string(), DATA).unwrap();
+    let message = finish_pow(
+        &RemotePow,
+        MIN_POW_SCORE,
+        hash_network_id("testnet"),
+        (tip, tip),
+        Some(Payload::Indexation(Box::new(payload))),
+    )
+    .unwrap();
+    let mut bytes = Vec::new();
+    message.pack(&mut bytes).unwrap();
+    bytes.truncate(bytes.len() - std::mem::size_of::<u64>());
+    bytes
+}
+
+fn outsourced_pow_client(provider: PrecomputedNonce, local_pow_fallback: bool) -> iota_client::Client {
+    // the mock node doesn't do the PoW, the nonce is checked against its score
+    iota_client::Client::build()
+        .with_node(&pow_mock_node(&[]))
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(false)
+        .with_pow_worker_count(1)
+        .with_nonce_provider(provider)
+        .with_local_pow_fallback(local_pow_fallback)
+        .finish()
+        .unwrap()
+}
+
+async fn send_indexation(client: &iota_client::Client) -> iota_client::Result<MessageId> {
+    client.send().with_index(INDEX).with_data(DATA.to_vec()).finish().await
+}
+
+#[tokio::test]
+async fn test_outsourced_pow() {
+    let bytes = message_bytes();
+    let nonce = LocalPow::new(1).nonce(&bytes, MIN_POW_SCORE).unwrap();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let client = outsourced_pow_client(
+        PrecomputedNonce {
+            bytes,
+            nonce: Some(nonce),
+            calls: calls.clone(),
+        },
+        false,
+    );
+    let message_id = send_indexation(&client).await.unwrap();
+    assert_eq!(message_id.to_string(), MESSAGE_ID);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_outsourced_pow_failure() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let provider = || PrecomputedNonce {
+        bytes: message_bytes(),
+        nonce: None,
+        calls: calls.clone(),
+    };
+    match send_indexation(&outsourced_pow_client(provider(), false)).await {
+        Err(Error::NonceProvider(error)) => assert_eq!(error, "the PoW service timed out"),
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    // the local PoW takes over
+    let message_id = send_indexation(&outsourced_pow_client(provider(), true)).await.unwrap();
+    assert_eq!(message_id.to_string(), MESSAGE_ID);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
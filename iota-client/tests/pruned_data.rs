@@ -0,0 +1,151 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Pruned data tests against a mock node with the milestones up to 10 pruned, which reports a pruned message in its not
+// found answer, and a mock permanode serving the pruned data.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{start_mock_node, MESSAGE_ID};
+use iota_client::{Client, DataSource, Error, PrunedRequest};
+
+const PRUNING_INDEX: u64 = 10;
+const PRUNED_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const UNKNOWN_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+
+const NOT_FOUND: &str = r#"{"error":{"code":"404","message":"not found"}}"#;
+const PRUNED: &str = r#"{"error":{"code":"404","message":"message not found, it was pruned"}}"#;
+
+fn utxo_changes(index: u64) -> String {
+    serde_json::json!({ "data": {
+        "index": index,
+        "createdOutputs": ["00000000000000000000000000000000000000000000000000000000000000010000"],
+        "consumedOutputs": [],
+    }})
+    .to_string()
+}
+
+fn node() -> String {
+    start_mock_node(|method, path, _| match (method, path) {
+        ("GET", "/api/v1/info") => (
+            "200 OK",
+            serde_json::json!({ "data": {
+                "name": "mock",
+                "version": "0.0.0",
+                "isHealthy": true,
+                "networkId": "testnet",
+                "minPowScore": 4000,
+                "latestMilestoneIndex": 176,
+                "solidMilestoneIndex": 176,
+                "pruningIndex": PRUNING_INDEX,
+                "features": [],
+            }})
+            .to_string(),
+        ),
+        ("GET", path) if path.starts_with(&format!("/api/v1/messages/{}", PRUNED_MESSAGE_ID)) => {
+            ("404 Not Found", PRUNED.to_string())
+        }
+        _ => ("404 Not Found", NOT_FOUND.to_string()),
+    })
+}
+
+fn permanode() -> String {
+    let url = start_mock_node(|method, path, _| match (method, path) {
+        ("GET", "/api/mainnet/milestones/5/utxo-changes") => ("200 OK", utxo_changes(5)),
+        ("GET", path) if path == format!("/api/mainnet/messages/{}", PRUNED_MESSAGE_ID) => (
+            "200 OK",
+            serde_json::json!({ "data": {
+                "networkId": "2",
+                "parent1MessageId": MESSAGE_ID,
+                "parent2MessageId": MESSAGE_ID,
+                "nonce": "0",
+            }})
+            .to_string(),
+        ),
+        _ => ("404 Not Found", NOT_FOUND.to_string()),
+    });
+    format!("{}/api/mainnet", url)
+}
+
+fn client(with_permanode: bool) -> Client {
+    let mut builder = Client::build().with_node(&node()).unwrap().with_node_sync_disabled();
+    if with_permanode {
+        builder = builder.with_permanode(&permanode()).unwrap();
+    }
+    builder.finish().unwrap()
+}
+
+fn message_id(message_id: &str) -> MessageId {
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(message_id, &mut bytes).unwrap();
+    MessageId::from(bytes)
+}
+
+#[tokio::test]
+async fn test_pruned_message() {
+    let client = client(false);
+    let pruned = message_id(PRUNED_MESSAGE_ID);
+    let expected = PrunedRequest::Message(PRUNED_MESSAGE_ID.to_string());
+    assert!(matches!(
+        client.get_message().data(&pruned).await,
+        Err(Error::PrunedData { requested, pruning_index: PRUNING_INDEX }) if requested == expected
+    ));
+    assert!(matches!(
+        client.get_message().metadata(&pruned).await,
+        Err(Error::PrunedData { requested, pruning_index: PRUNING_INDEX }) if requested == expected
+    ));
+    assert!(matches!(
+        client.get_message().children(&pruned).await,
+        Err(Error::PrunedData { requested, pruning_index: PRUNING_INDEX }) if requested == expected
+    ));
+
+    // a message the node doesn't report as pruned keeps the node error
+    match client.get_message().data(&message_id(UNKNOWN_MESSAGE_ID)).await {
+        Err(Error::ResponseError(404, body, _)) => assert_eq!(body, NOT_FOUND),
+        r => panic!("expected the not found error of the node, got {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn test_pruned_milestone_utxo_changes() {
+    let client = client(false);
+    assert!(matches!(
+        client.get_milestone_utxo_changes(5).await,
+        Err(Error::PrunedData {
+            requested: PrunedRequest::MilestoneUtxoChanges(5),
+            pruning_index: PRUNING_INDEX
+        })
+    ));
+
+    // the milestones above the pruning index aren't pruned, only unknown to the node
+    match client.get_milestone_utxo_changes(PRUNING_INDEX + 1).await {
+        Err(Error::ResponseError(404, body, _)) => assert_eq!(body, NOT_FOUND),
+        r => panic!("expected the not found error of the node, got {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn test_pruned_data_from_permanode() {
+    let client = client(true);
+    let message = client
+        .get_message()
+        .data_with_source(&message_id(PRUNED_MESSAGE_ID))
+        .await
+        .unwrap();
+    assert_eq!(message.source, DataSource::Permanode);
+    assert_eq!(message.data.network_id(), 2);
+
+    let changes = client.get_milestone_utxo_changes_with_source(5).await.unwrap();
+    assert_eq!(changes.source, DataSource::Permanode);
+    assert_eq!(changes.data.index, 5);
+
+    // the permanode doesn't have every pruned milestone, the error of the node is kept
+    assert!(matches!(
+        client.get_milestone_utxo_changes(1).await,
+        Err(Error::PrunedData {
+            requested: PrunedRequest::MilestoneUtxoChanges(1),
+            pruning_index: PRUNING_INDEX
+        })
+    ));
+}
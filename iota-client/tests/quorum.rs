@@ -0,0 +1,246 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Quorum tests against mock nodes answering the balance and the output requests, some of them disagreeing or slow.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{start_mock_node, MESSAGE_ID};
+use iota_client::{Bech32Address, Error, MessageJson};
+use std::{
+    convert::TryInto,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+const ADDRESS: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+const OUTPUT_IDS: [&str; 2] = [
+    "00000000000000000000000000000000000000000000000000000000000000010000",
+    "00000000000000000000000000000000000000000000000000000000000000020000",
+];
+const INDEXATION_MESSAGE: &str = r#"{
+    "networkId": "6530425480034647824",
+    "parent1MessageId": "2e071ee19dc58d250e0e084a1ac890a9769896cd4c5689fd7f202bfc6c8d574c",
+    "parent2MessageId": "4375fb2a9d6b0b5a6c529bde678f227192d409b75cf87f7245ceeed8ed611664",
+    "payload": {
+        "type": 2,
+        "index": "HORNET Spammer",
+        "data": "42696e61727920697320746865206675747572652e"
+    },
+    "nonce": "36952"
+}"#;
+
+// A mock node answering the balance requests with `balance` after `delay`, and listing the output ids in the given
+// order. The requests are counted.
+fn mock_node(balance: u64, output_ids: Vec<&'static str>, delay: Duration, requests: Arc<AtomicUsize>) -> String {
+    start_mock_node(move |method, path, _| {
+        requests.fetch_add(1, Ordering::SeqCst);
+        thread::sleep(delay);
+        match (method, path) {
+            ("GET", "/api/v1/addresses/iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj") => (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "addressType": 1,
+                    "address": ADDRESS,
+                    "balance": balance,
+                    "count": 1,
+                    "dustAllowed": false,
+                }})
+                .to_string(),
+            ),
+            ("GET", "/api/v1/addresses/iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj/outputs") => (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "addressType": 1,
+                    "address": ADDRESS,
+                    "outputIds": output_ids,
+                    "count": output_ids.len(),
+                    "maxResults": 1000,
+                }})
+                .to_string(),
+            ),
+            ("POST", "/api/v1/messages") => (
+                "201 Created",
+                serde_json::json!({ "data": { "messageId": MESSAGE_ID } }).to_string(),
+            ),
+            _ => ("404 Not Found", String::new()),
+        }
+    })
+}
+
+fn fast_node(balance: u64, requests: &Arc<AtomicUsize>) -> String {
+    mock_node(balance, OUTPUT_IDS.to_vec(), Duration::from_millis(0), requests.clone())
+}
+
+fn client(urls: &[String], quorum_threshold: usize, request_timeout: Duration) -> iota_client::Client {
+    let urls = urls.iter().map(|url| url.as_str()).collect::<Vec<&str>>();
+    iota_client::Client::build()
+        .with_nodes(&urls)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_quorum(true)
+        .with_quorum_threshold(quorum_threshold)
+        .with_request_timeout(request_timeout)
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_quorum_balance() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let urls = vec![
+        fast_node(1_000_000, &requests),
+        fast_node(1_000_000, &requests),
+        fast_node(2_000_000, &requests),
+    ];
+    let address = Bech32Address::from(ADDRESS);
+
+    // 2 of the 3 nodes agree
+    let balance = client(&urls, 66, Duration::from_secs(5))
+        .get_address()
+        .balance(&address)
+        .await
+        .unwrap();
+    assert_eq!(balance, 1_000_000);
+
+    // every node must agree
+    let result = client(&urls, 100, Duration::from_secs(5))
+        .get_address()
+        .balance(&address)
+        .await;
+    match result {
+        Err(Error::QuorumError(answers)) => {
+            assert_eq!(answers.len(), 3);
+            for url in &urls {
+                assert!(answers.iter().any(|(node, _)| node.starts_with(url.as_str())));
+            }
+            assert_eq!(
+                answers.iter().filter(|(_, answer)| answer.contains("2000000")).count(),
+                1
+            );
+        }
+        _ => panic!("the nodes must disagree"),
+    }
+
+    let balances = client(&urls, 100, Duration::from_secs(5))
+        .get_address_balances(&[address])
+        .await;
+    assert!(matches!(balances, Err(Error::AddressBalanceError(_, error)) if matches!(*error, Error::QuorumError(_))));
+}
+
+#[tokio::test]
+async fn test_quorum_slow_node() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let slow_node = mock_node(
+        2_000_000,
+        OUTPUT_IDS.to_vec(),
+        Duration::from_millis(2000),
+        requests.clone(),
+    );
+    let urls = vec![
+        fast_node(1_000_000, &requests),
+        fast_node(1_000_000, &requests),
+        slow_node,
+    ];
+    let address = Bech32Address::from(ADDRESS);
+
+    // the answer of the fast nodes is enough
+    let start = Instant::now();
+    let balance = client(&urls, 66, Duration::from_secs(5))
+        .get_address()
+        .balance(&address)
+        .await
+        .unwrap();
+    assert_eq!(balance, 1_000_000);
+    assert!(start.elapsed() < Duration::from_millis(1000));
+
+    // the slow node times out instead of stalling the quorum
+    let start = Instant::now();
+    let result = client(&urls, 100, Duration::from_millis(300))
+        .get_address()
+        .balance(&address)
+        .await;
+    match result {
+        Err(Error::QuorumError(answers)) => {
+            assert_eq!(answers.len(), 3);
+            assert!(answers.iter().any(|(_, answer)| answer.contains("timed out")));
+        }
+        _ => panic!("the slow node must time out"),
+    }
+    assert!(start.elapsed() < Duration::from_millis(1500));
+}
+
+#[tokio::test]
+async fn test_quorum_address_outputs() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let mut reversed = OUTPUT_IDS.to_vec();
+    reversed.reverse();
+    let urls = vec![
+        fast_node(1_000_000, &requests),
+        fast_node(1_000_000, &requests),
+        mock_node(1_000_000, reversed, Duration::from_millis(0), requests.clone()),
+    ];
+
+    // the nodes agree on the outputs listed in another order
+    let outputs = client(&urls, 100, Duration::from_secs(5))
+        .get_address()
+        .outputs(&Bech32Address::from(ADDRESS))
+        .await
+        .unwrap();
+    assert_eq!(outputs.len(), 2);
+}
+
+#[tokio::test]
+async fn test_quorum_pool_size() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let urls = vec![fast_node(1_000_000, &requests), fast_node(1_000_000, &requests)];
+    let result = client(&urls, 66, Duration::from_secs(5))
+        .get_address()
+        .balance(&Bech32Address::from(ADDRESS))
+        .await;
+    assert!(matches!(result, Err(Error::QuorumPoolSizeError(2, 3))));
+    assert_eq!(requests.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_quorum_bypassed_by_submission() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let urls = vec![
+        fast_node(1_000_000, &requests),
+        fast_node(1_000_000, &requests),
+        fast_node(1_000_000, &requests),
+    ];
+    let message: Message = serde_json::from_str::<MessageJson>(INDEXATION_MESSAGE)
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let message_id = client(&urls, 66, Duration::from_secs(5))
+        .post_message(&message)
+        .await
+        .unwrap();
+    assert_eq!(message_id.to_string(), MESSAGE_ID);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_quorum_options() {
+    let build = |quorum_size, quorum_threshold| {
+        iota_client::Client::build()
+            .with_node("http://localhost:14265")
+            .unwrap()
+            .with_node_sync_disabled()
+            .with_quorum(true)
+            .with_quorum_size(quorum_size)
+            .with_quorum_threshold(quorum_threshold)
+            .finish()
+    };
+    assert!(build(2, 100).is_ok());
+    assert!(matches!(build(0, 66), Err(Error::InvalidParameter(_))));
+    assert!(matches!(build(3, 0), Err(Error::InvalidParameter(_))));
+    assert!(matches!(build(3, 101), Err(Error::InvalidParameter(_))));
+}
@@ -0,0 +1,140 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Rate limit tests against a mock node answering 429 or 503 to the first tips requests, with or without a
+// `Retry-After` header: the retries wait the delay of the node, or the backoff without it, up to the retry budget.
+
+mod common;
+
+use common::{start_mock_node_with_response_headers, tips};
+use iota_client::{Client, Error, RATE_LIMIT_BACKOFF};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+// A node answering `status` with the `Retry-After` header to the first `limited` tips requests, counting them.
+fn mock_node(status: &'static str, retry_after: Option<String>, limited: usize, requests: Arc<AtomicUsize>) -> String {
+    start_mock_node_with_response_headers(move |method, path, _, _| {
+        if method != "GET" || path != "/api/v1/tips" {
+            return ("404 Not Found", Vec::new(), Vec::new());
+        }
+        if requests.fetch_add(1, Ordering::SeqCst) < limited {
+            let headers = retry_after
+                .iter()
+                .map(|retry_after| ("retry-after", retry_after.clone()))
+                .collect();
+            (status, headers, Vec::new())
+        } else {
+            ("200 OK", Vec::new(), tips().into_bytes())
+        }
+    })
+}
+
+fn client(url: &str, retries: usize) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_rate_limit_retries(retries)
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_retry_after_seconds() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let url = mock_node("429 Too Many Requests", Some("1".to_string()), 2, requests.clone());
+    let start = Instant::now();
+    client(&url, 3).get_tips().await.unwrap();
+    assert!(
+        start.elapsed() >= Duration::from_secs(2),
+        "elapsed {:?}",
+        start.elapsed()
+    );
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_retry_after_http_date() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let date = (chrono::Utc::now() + chrono::Duration::seconds(2))
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string();
+    let url = mock_node("503 Service Unavailable", Some(date), 1, requests.clone());
+    let start = Instant::now();
+    client(&url, 3).get_tips().await.unwrap();
+    // the date has a precision of a second
+    assert!(
+        start.elapsed() >= Duration::from_secs(1),
+        "elapsed {:?}",
+        start.elapsed()
+    );
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_backoff_without_retry_after() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let url = mock_node("503 Service Unavailable", None, 1, requests.clone());
+    let start = Instant::now();
+    client(&url, 3).get_tips().await.unwrap();
+    assert!(start.elapsed() >= RATE_LIMIT_BACKOFF, "elapsed {:?}", start.elapsed());
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_exhausted_retries() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let url = mock_node("429 Too Many Requests", Some("1".to_string()), 10, requests.clone());
+    let error = client(&url, 1).get_tips().await.unwrap_err();
+    assert!(
+        matches!(&error, Error::RateLimited(429, Some(delay), _) if *delay == Duration::from_secs(1)),
+        "{:?}",
+        error
+    );
+    assert!(error.to_string().contains("it asks to wait 1 s"), "{}", error);
+    assert_eq!(error.endpoint(), Some("GET /api/v1/tips"));
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+    // without retries
+    let requests = Arc::new(AtomicUsize::new(0));
+    let url = mock_node("503 Service Unavailable", None, 10, requests.clone());
+    let error = client(&url, 0).get_tips().await.unwrap_err();
+    assert!(matches!(error, Error::RateLimited(503, None, _)), "{:?}", error);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_rate_limit_max_delay() {
+    // the node asks to wait longer than the max delay
+    let requests = Arc::new(AtomicUsize::new(0));
+    let url = mock_node("429 Too Many Requests", Some("120".to_string()), 1, requests.clone());
+    let start = Instant::now();
+    let error = client(&url, 3).get_tips().await.unwrap_err();
+    assert!(
+        start.elapsed() < Duration::from_secs(1),
+        "elapsed {:?}",
+        start.elapsed()
+    );
+    assert!(error.to_string().contains("it asks to wait 120 s"), "{}", error);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(feature = "metrics")]
+#[tokio::test]
+async fn test_rate_limit_metrics() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let url = mock_node("429 Too Many Requests", Some("0".to_string()), 2, requests.clone());
+    let client = client(&url, 3);
+    client.get_tips().await.unwrap();
+    let metrics = client.get_metrics();
+    assert_eq!(metrics.rate_limit_retries, 2);
+    assert_eq!(metrics.errors.get("status_4xx"), Some(&2));
+    assert!(metrics
+        .to_prometheus()
+        .contains("iota_client_rate_limit_retries_total 2\n"));
+}
@@ -0,0 +1,128 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Rebroadcast tests against a mock node answering the packed messages, recording the messages submitted to it.
+
+mod common;
+
+use bee_common::packable::Packable;
+use bee_message::prelude::*;
+use common::{start_mock_node_with_bytes, MESSAGE_ID};
+use iota_client::{compute_message_id, Client, Error, MessageJson};
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+const UNKNOWN_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000003";
+
+fn message(data: &str) -> Message {
+    serde_json::from_value::<MessageJson>(serde_json::json!({
+        "networkId": "1",
+        "parent1MessageId": MESSAGE_ID,
+        "parent2MessageId": MESSAGE_ID,
+        "payload": { "type": 2, "index": "494f54412e5253", "data": hex::encode(data) },
+        "nonce": "10760600709663927622",
+    }))
+    .unwrap()
+    .try_into()
+    .unwrap()
+}
+
+// Starts a mock node answering the packed messages under the given ids, returns its URL and the submitted messages.
+fn rebroadcast_mock_node(packed_messages: HashMap<MessageId, Vec<u8>>) -> (String, Arc<Mutex<Vec<MessageJson>>>) {
+    let submitted = Arc::new(Mutex::new(Vec::new()));
+    let submitted_ = submitted.clone();
+    let url = start_mock_node_with_bytes(move |method, path, _, body| match method {
+        "GET" => {
+            let packed_message = path
+                .strip_prefix("/api/v1/messages/")
+                .and_then(|path| path.strip_suffix("/raw"))
+                .and_then(|message_id| packed_messages.get(&MessageId::from_str(message_id).unwrap()));
+            match packed_message {
+                Some(packed_message) => ("200 OK", packed_message.clone()),
+                None => ("404 Not Found", Vec::new()),
+            }
+        }
+        "POST" if path == "/api/v1/messages" => {
+            let message: MessageJson = serde_json::from_slice(body).unwrap();
+            let message_id = compute_message_id(&TryInto::<Message>::try_into(message.clone()).unwrap().pack_new());
+            submitted_.lock().unwrap().push(message);
+            (
+                "201 Created",
+                serde_json::json!({ "data": { "messageId": message_id.to_string() } })
+                    .to_string()
+                    .into_bytes(),
+            )
+        }
+        _ => ("404 Not Found", Vec::new()),
+    });
+    (url, submitted)
+}
+
+fn client(url: &str) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_rebroadcast() {
+    let message = message("stored");
+    let packed_message = message.pack_new();
+    let message_id = compute_message_id(&packed_message);
+    let (url, submitted) = rebroadcast_mock_node(vec![(message_id, packed_message.clone())].into_iter().collect());
+
+    assert_eq!(client(&url).rebroadcast(&message_id).await.unwrap(), packed_message);
+    assert_eq!(*submitted.lock().unwrap(), vec![MessageJson::from(&message)]);
+}
+
+#[tokio::test]
+async fn test_rebroadcast_unknown_message() {
+    let (url, submitted) = rebroadcast_mock_node(HashMap::new());
+    let message_id = MessageId::from_str(UNKNOWN_MESSAGE_ID).unwrap();
+    match client(&url).rebroadcast(&message_id).await {
+        Err(Error::ResponseError(404, ..)) => {}
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert!(submitted.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_rebroadcast_invalid_message() {
+    // the node answers another message for the id
+    let message_id = compute_message_id(&message("stored").pack_new());
+    let other_packed_message = message("other").pack_new();
+    let other_message_id = compute_message_id(&other_packed_message);
+    // the bytes hash to their id but aren't a message
+    let garbage = vec![1, 2, 3];
+    let garbage_id = compute_message_id(&garbage);
+    let (url, submitted) = rebroadcast_mock_node(
+        vec![(message_id, other_packed_message), (garbage_id, garbage)]
+            .into_iter()
+            .collect(),
+    );
+    let client = client(&url);
+
+    match client.rebroadcast(&message_id).await {
+        Err(Error::InvalidMessage(id, error)) => {
+            assert_eq!(id, message_id.to_string());
+            assert!(
+                matches!(*error, Error::MessageIdMismatch(ref id) if *id == other_message_id.to_string()),
+                "unexpected error {}",
+                error
+            );
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+    match client.rebroadcast(&garbage_id).await {
+        Err(Error::InvalidMessage(id, _)) => assert_eq!(id, garbage_id.to_string()),
+        result => panic!("unexpected result {:?}", result),
+    }
+    assert!(submitted.lock().unwrap().is_empty());
+}
@@ -0,0 +1,144 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Concurrent balance lookup tests against a mock node answering every balance after a delay, the mock node counts
+// the most requests it handled at once.
+
+mod common;
+
+use common::start_concurrent_mock_node;
+use iota_client::{Bech32Address, Client, Error, Seed};
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+const DELAY: Duration = Duration::from_millis(100);
+
+fn client(url: &str, parallelism: usize) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_request_parallelism(parallelism)
+        .finish()
+        .unwrap()
+}
+
+// The public addresses of the seed's first account.
+fn addresses(count: usize) -> Vec<Bech32Address> {
+    client("http://localhost:14265", 1)
+        .find_addresses(&Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap())
+        .with_account_index(0)
+        .with_range(0..count)
+        .finish()
+        .unwrap()
+}
+
+// Starts a mock node answering the balances after the delay, the failing addresses get an error without any delay.
+fn balance_mock_node(failing: Vec<Bech32Address>) -> (String, Arc<AtomicUsize>) {
+    let failing: HashSet<String> = failing.into_iter().map(|address| address.to_string()).collect();
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight_ = max_in_flight.clone();
+    let url = start_concurrent_mock_node(move |method, path, _| {
+        let address = match path.strip_prefix("/api/v1/addresses/") {
+            Some(address) if method == "GET" => address,
+            _ => return ("404 Not Found", String::new()),
+        };
+        if failing.contains(address) {
+            return ("500 Internal Server Error", String::new());
+        }
+        let requests = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        max_in_flight_.fetch_max(requests, Ordering::SeqCst);
+        thread::sleep(DELAY);
+        in_flight.fetch_sub(1, Ordering::SeqCst);
+        (
+            "200 OK",
+            serde_json::json!({ "data": {
+                "address": address,
+                "balance": 1_000_000,
+                "count": 1,
+                "dustAllowed": false,
+            }})
+            .to_string(),
+        )
+    });
+    (url, max_in_flight)
+}
+
+fn balance_addresses(balances: &[iota_client::AddressBalancePair]) -> Vec<Bech32Address> {
+    balances.iter().map(|balance| balance.address.clone()).collect()
+}
+
+#[tokio::test]
+async fn test_parallel_balances() {
+    let addresses = addresses(20);
+    let (url, max_in_flight) = balance_mock_node(Vec::new());
+    let client = client(&url, 5);
+
+    let start = Instant::now();
+    let balances = client.get_address_balances(&addresses).await.unwrap();
+    let elapsed = start.elapsed();
+
+    // ceil(20 / 5) rounds of requests, instead of 20 sequential requests
+    assert!(elapsed >= DELAY * 4, "elapsed {:?}", elapsed);
+    assert!(elapsed < DELAY * 8, "elapsed {:?}", elapsed);
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 5);
+    assert_eq!(balance_addresses(&balances), addresses);
+}
+
+#[tokio::test]
+async fn test_fail_fast() {
+    let addresses = addresses(10);
+    let (url, _) = balance_mock_node(vec![addresses[0].clone()]);
+
+    let start = Instant::now();
+    match client(&url, 5).get_address_balances(&addresses).await {
+        Err(Error::AddressBalanceError(address, _)) => assert_eq!(address, addresses[0].to_string()),
+        result => panic!("unexpected result {:?}", result),
+    }
+    // the requests in flight aren't awaited
+    assert!(start.elapsed() < DELAY, "elapsed {:?}", start.elapsed());
+}
+
+#[tokio::test]
+async fn test_settled_balances() {
+    let addresses = addresses(10);
+    let failing = vec![addresses[3].clone(), addresses[7].clone()];
+    let (url, _) = balance_mock_node(failing.clone());
+
+    let (balances, errors) = client(&url, 3).get_address_balances_settled(&addresses).await;
+    let succeeded: Vec<Bech32Address> = addresses
+        .iter()
+        .filter(|address| !failing.contains(address))
+        .cloned()
+        .collect();
+    assert_eq!(balance_addresses(&balances), succeeded);
+    let failed: Vec<Bech32Address> = errors
+        .iter()
+        .map(|(address, error)| {
+            assert!(
+                matches!(error, Error::AddressBalanceError(..)),
+                "unexpected error {}",
+                error
+            );
+            address.clone()
+        })
+        .collect();
+    assert_eq!(failed, failing);
+}
+
+#[test]
+fn test_invalid_parallelism() {
+    assert!(matches!(
+        Client::build().with_request_parallelism(0).finish(),
+        Err(Error::InvalidParameter(_))
+    ));
+}
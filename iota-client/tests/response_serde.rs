@@ -0,0 +1,240 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Serialization round trips of the response types over node answers: the serialized value is the node answer and
+// deserializes to the same response.
+
+use bee_message::prelude::*;
+use iota_client::{
+    AddressBalancePair, AddressOutputs, MessageJson, MessageMetadata, MilestoneMetadata, MilestoneUtxoChanges,
+    NodeInfo, NodeSyncStatus, OutputMetadata, PeerInfo, ReachedBalance,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use std::fmt::Debug;
+
+const MESSAGE_ID: &str = "52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649";
+const TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const ADDRESS: &str = "6920b176f613ec7be59e68fc68f597eb3393af80f74c7c3db78198147d5f1f92";
+
+// Deserializes the node answer, checks the response serializes back to it and survives a clone.
+fn assert_round_trip<T>(answer: Value)
+where
+    T: Serialize + DeserializeOwned + Clone + Debug + PartialEq,
+{
+    let response: T = serde_json::from_value(answer.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&response).unwrap(), answer);
+    assert_eq!(serde_json::from_value::<T>(answer).unwrap(), response.clone());
+}
+
+// Checks a response built by the client deserializes from its serialized value.
+fn assert_value_round_trip<T>(response: T)
+where
+    T: Serialize + DeserializeOwned + Clone + Debug + PartialEq,
+{
+    let value = serde_json::to_value(response.clone()).unwrap();
+    assert_eq!(serde_json::from_value::<T>(value).unwrap(), response);
+}
+
+#[test]
+fn test_node_info() {
+    assert_round_trip::<NodeInfo>(json!({
+        "name": "HORNET",
+        "version": "0.6.0-alpha",
+        "isHealthy": true,
+        "networkId": "testnet5",
+        "minPowScore": 4000.0,
+        "latestMilestoneIndex": 82847,
+        "solidMilestoneIndex": 82847,
+        "latestMilestoneTimestamp": 1609950538,
+        "pruningIndex": 0,
+        "bech32HRP": "atoi",
+        "features": ["PoW"],
+    }));
+    // an older node without the timestamp and the HRP
+    assert_round_trip::<NodeInfo>(json!({
+        "name": "HORNET",
+        "version": "0.5.3",
+        "isHealthy": false,
+        "networkId": "testnet4",
+        "minPowScore": 4000.0,
+        "latestMilestoneIndex": 1000,
+        "solidMilestoneIndex": 998,
+        "latestMilestoneTimestamp": null,
+        "pruningIndex": 10,
+        "bech32HRP": null,
+        "features": [],
+    }));
+}
+
+#[test]
+fn test_peers() {
+    assert_round_trip::<Vec<PeerInfo>>(json!([
+        {
+            "id": "12D3KooWFJ8Nq6gHLLvigTpPSbyMmLk35k1TcpJof8Y4y8yFAB32",
+            "multiAddresses": ["/ip4/127.0.0.1/tcp/15600"],
+            "alias": "neighbor",
+            "relation": "known",
+            "connected": true,
+            "gossip": {
+                "metrics": {
+                    "newMessages": 40,
+                    "knownMessages": 21,
+                    "receivedMessages": 61,
+                    "receivedMessageRequests": 3,
+                    "receivedMilestoneRequests": 1,
+                    "receivedHeartbeats": 12,
+                    "sentMessages": 50,
+                    "sentMessageRequests": 4,
+                    "sentMilestoneRequests": 2,
+                    "sentHeartbeats": 12,
+                    "droppedPackets": 0,
+                },
+            },
+        },
+        {
+            "id": "12D3KooWC7uE9w3RN4Vh1FJAZa8SbE8yMWR6wCVBajcWpyWguV73",
+            "multiAddresses": [],
+            "alias": null,
+            "relation": "autopeered",
+            "connected": false,
+            "gossip": null,
+        },
+    ]));
+}
+
+#[test]
+fn test_message_metadata() {
+    assert_round_trip::<MessageMetadata>(json!({
+        "messageId": MESSAGE_ID,
+        "parent1MessageId": MESSAGE_ID,
+        "parent2MessageId": MESSAGE_ID,
+        "isSolid": true,
+        "shouldPromote": null,
+        "shouldReattach": null,
+        "referencedByMilestoneIndex": 82847,
+        "ledgerInclusionState": "noTransaction",
+    }));
+    assert_round_trip::<MessageMetadata>(json!({
+        "messageId": MESSAGE_ID,
+        "parent1MessageId": MESSAGE_ID,
+        "parent2MessageId": MESSAGE_ID,
+        "isSolid": false,
+        "shouldPromote": true,
+        "shouldReattach": false,
+        "referencedByMilestoneIndex": null,
+        "ledgerInclusionState": null,
+    }));
+}
+
+#[test]
+fn test_messages() {
+    // an indexation message
+    assert_round_trip::<MessageJson>(json!({
+        "networkId": "6530425480034647824",
+        "parent1MessageId": MESSAGE_ID,
+        "parent2MessageId": MESSAGE_ID,
+        "payload": { "type": 2, "index": "494f54412e5253", "data": "68656c6c6f" },
+        "nonce": "10760600709663927622",
+    }));
+    // a transaction message without nonce, before its PoW
+    assert_round_trip::<MessageJson>(json!({
+        "networkId": "6530425480034647824",
+        "parent1MessageId": MESSAGE_ID,
+        "parent2MessageId": MESSAGE_ID,
+        "payload": {
+            "type": 0,
+            "essence": {
+                "type": 0,
+                "inputs": [{ "type": 0, "transactionId": TRANSACTION_ID, "transactionOutputIndex": 0 }],
+                "outputs": [{ "type": 0, "address": { "type": 1, "address": ADDRESS }, "amount": 1000000 }],
+                "payload": { "type": 2, "index": "494f54412e5253", "data": "" },
+            },
+            "unlockBlocks": [
+                {
+                    "type": 0,
+                    "signature": {
+                        "type": 1,
+                        "publicKey": ADDRESS,
+                        "signature": format!("{}{}", MESSAGE_ID, MESSAGE_ID),
+                    },
+                },
+                { "type": 1, "reference": 0 },
+            ],
+        },
+    }));
+    // a milestone message
+    assert_round_trip::<MessageJson>(json!({
+        "networkId": "6530425480034647824",
+        "parent1MessageId": MESSAGE_ID,
+        "parent2MessageId": MESSAGE_ID,
+        "payload": {
+            "type": 1,
+            "index": 82847,
+            "timestamp": 1609950538,
+            "inclusionMerkleProof": MESSAGE_ID,
+            "signatures": [format!("{}{}", MESSAGE_ID, MESSAGE_ID)],
+        },
+        "nonce": "2305843009213712200",
+    }));
+}
+
+#[test]
+fn test_address_outputs() {
+    assert_round_trip::<AddressOutputs>(json!({
+        "outputIds": [format!("{}0000", TRANSACTION_ID), format!("{}0100", TRANSACTION_ID)],
+        "count": 2,
+        "maxResults": 1000,
+    }));
+}
+
+#[test]
+fn test_milestones() {
+    assert_round_trip::<MilestoneMetadata>(json!({
+        "index": 82847,
+        "messageId": MESSAGE_ID,
+        "timestamp": 1609950538,
+    }));
+    assert_round_trip::<MilestoneUtxoChanges>(json!({
+        "index": 82847,
+        "createdOutputs": [format!("{}0000", TRANSACTION_ID)],
+        "consumedOutputs": [],
+    }));
+}
+
+#[test]
+fn test_client_responses() {
+    let mut address = [0u8; 32];
+    hex::decode_to_slice(ADDRESS, &mut address).unwrap();
+    let output = OutputMetadata {
+        message_id: hex::decode(MESSAGE_ID).unwrap(),
+        transaction_id: hex::decode(TRANSACTION_ID).unwrap(),
+        output_index: 1,
+        is_spent: false,
+        address: Address::from(Ed25519Address::from(address)),
+        amount: 1_000_000,
+    };
+    let value = serde_json::to_value(&output).unwrap();
+    assert_eq!(value["messageId"], MESSAGE_ID);
+    assert_eq!(value["transactionId"], TRANSACTION_ID);
+    assert_value_round_trip(output.clone());
+
+    let bech32_address = "atoi1qp5jpvtk7cf7c7l9ne50c684jl4n8ya0srm5clpak7qes9ratu0eysflmsz".into();
+    assert_value_round_trip(AddressBalancePair {
+        address: bech32_address,
+        balance: 1_000_000,
+        dust_allowed: false,
+    });
+    assert_value_round_trip(ReachedBalance {
+        address: "atoi1qp5jpvtk7cf7c7l9ne50c684jl4n8ya0srm5clpak7qes9ratu0eysflmsz".into(),
+        balance: 1_000_000,
+        outputs: vec![output],
+    });
+    assert_round_trip::<NodeSyncStatus>(json!({
+        "url": "http://localhost:14265/",
+        "synced": true,
+        "latestMilestoneIndex": 82847,
+        "solidMilestoneIndex": 82846,
+        "lastCheck": 1609950538000u64,
+    }));
+}
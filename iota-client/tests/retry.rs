@@ -0,0 +1,294 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Promotion and reattachment tests against a mock node, checking the follow-up messages that are posted.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{node_info, start_mock_node, tips, MESSAGE_ID};
+use iota_client::{Error, LedgerInclusionState, MessageJson};
+use std::{
+    convert::TryInto,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+const PROMOTABLE_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const REATTACHABLE_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+const REFERENCED_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000003";
+const PENDING_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000004";
+// The parents of the original messages, the tips of the mock node are `MESSAGE_ID`.
+const PARENT_MESSAGE_ID: &str = "00000000000000000000000000000000000000000000000000000000000000ff";
+
+const CONFLICTING_MESSAGE_ID: &str = "0000000000000000000000000000000000000000000000000000000000000005";
+
+fn metadata(
+    message_id: &str,
+    (should_promote, should_reattach): (Option<bool>, Option<bool>),
+    referenced_by_milestone_index: Option<u64>,
+    ledger_inclusion_state: &str,
+) -> serde_json::Value {
+    serde_json::json!({
+        "messageId": message_id,
+        "parent1MessageId": PARENT_MESSAGE_ID,
+        "parent2MessageId": PARENT_MESSAGE_ID,
+        "isSolid": true,
+        "shouldPromote": should_promote,
+        "shouldReattach": should_reattach,
+        "referencedByMilestoneIndex": referenced_by_milestone_index,
+        "ledgerInclusionState": referenced_by_milestone_index.map(|_| ledger_inclusion_state),
+    })
+}
+
+fn message_metadata(message_id: &str) -> Option<serde_json::Value> {
+    let (retry, referenced_by_milestone_index, ledger_inclusion_state) = match message_id {
+        PROMOTABLE_MESSAGE_ID => ((Some(true), Some(false)), None, "noTransaction"),
+        REATTACHABLE_MESSAGE_ID => ((Some(false), Some(true)), None, "noTransaction"),
+        REFERENCED_MESSAGE_ID => ((None, None), Some(10), "noTransaction"),
+        PENDING_MESSAGE_ID => ((Some(false), Some(false)), None, "noTransaction"),
+        CONFLICTING_MESSAGE_ID => ((None, None), Some(10), "conflicting"),
+        _ => return None,
+    };
+    Some(metadata(
+        message_id,
+        retry,
+        referenced_by_milestone_index,
+        ledger_inclusion_state,
+    ))
+}
+
+// Starts a mock node and returns its URL with the messages posted to it.
+fn retry_mock_node() -> (String, Arc<Mutex<Vec<Message>>>) {
+    retry_mock_node_with_metadata(message_metadata)
+}
+
+// Starts a mock node answering the metadata requests with `message_metadata`, the posted messages get `MESSAGE_ID`.
+fn retry_mock_node_with_metadata<F>(message_metadata: F) -> (String, Arc<Mutex<Vec<Message>>>)
+where
+    F: Fn(&str) -> Option<serde_json::Value> + Send + 'static,
+{
+    let posted = Arc::new(Mutex::new(Vec::new()));
+    let posted_ = posted.clone();
+    let url = start_mock_node(move |method, path, body| {
+        if method == "GET" && path == "/api/v1/info" {
+            ("200 OK", node_info(4000f64, &["PoW"]))
+        } else if method == "GET" && path == "/api/v1/tips" {
+            ("200 OK", tips())
+        } else if method == "POST" && path == "/api/v1/messages" {
+            let message: MessageJson = serde_json::from_slice(body).unwrap();
+            posted_.lock().unwrap().push(message.try_into().unwrap());
+            (
+                "201 Created",
+                serde_json::json!({ "data": { "messageId": MESSAGE_ID } }).to_string(),
+            )
+        } else if let Some(metadata) = path
+            .strip_prefix("/api/v1/messages/")
+            .and_then(|path| path.strip_suffix("/metadata"))
+            .and_then(|message_id| message_metadata(message_id))
+        {
+            ("200 OK", serde_json::json!({ "data": metadata }).to_string())
+        } else if path.strip_prefix("/api/v1/messages/").is_some() {
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "networkId": "1",
+                    "parent1MessageId": PARENT_MESSAGE_ID,
+                    "parent2MessageId": PARENT_MESSAGE_ID,
+                    "payload": { "type": 2, "index": "iota.rs", "data": hex::encode("retry") },
+                    "nonce": "0",
+                }})
+                .to_string(),
+            )
+        } else {
+            ("404 Not Found", String::new())
+        }
+    });
+    (url, posted)
+}
+
+fn client(url: &str) -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(false)
+        .finish()
+        .unwrap()
+}
+
+fn message_id(message_id: &str) -> MessageId {
+    MessageId::from_str(message_id).unwrap()
+}
+
+// A promotion has no payload and references the promoted message.
+fn assert_promotion(message: &Message, promoted_message_id: &str) {
+    assert_eq!(message.parent1(), &message_id(MESSAGE_ID));
+    assert_eq!(message.parent2(), &message_id(promoted_message_id));
+    assert!(message.payload().is_none());
+}
+
+// A reattachment keeps the payload with new tips.
+fn assert_reattachment(message: &Message) {
+    assert_eq!(message.parent1(), &message_id(MESSAGE_ID));
+    assert_eq!(message.parent2(), &message_id(MESSAGE_ID));
+    match message.payload() {
+        Some(Payload::Indexation(indexation)) => {
+            assert_eq!(indexation.index(), "iota.rs");
+            assert_eq!(indexation.data(), b"retry");
+        }
+        _ => panic!("the reattached message doesn't have the original payload"),
+    }
+}
+
+#[tokio::test]
+async fn test_promote() {
+    let (url, posted) = retry_mock_node();
+    let (new_message_id, message) = client(&url).promote(&message_id(PROMOTABLE_MESSAGE_ID)).await.unwrap();
+    assert_eq!(new_message_id, message_id(MESSAGE_ID));
+    assert_promotion(&message, PROMOTABLE_MESSAGE_ID);
+    assert_eq!(posted.lock().unwrap().as_slice(), &[message]);
+
+    let result = client(&url).promote(&message_id(REATTACHABLE_MESSAGE_ID)).await;
+    assert!(matches!(result, Err(Error::NoNeedPromoteOrReattach(_))));
+}
+
+#[tokio::test]
+async fn test_reattach() {
+    let (url, posted) = retry_mock_node();
+    let (new_message_id, message) = client(&url)
+        .reattach(&message_id(REATTACHABLE_MESSAGE_ID))
+        .await
+        .unwrap();
+    assert_eq!(new_message_id, message_id(MESSAGE_ID));
+    assert_reattachment(&message);
+    assert_eq!(posted.lock().unwrap().as_slice(), &[message]);
+
+    let result = client(&url).reattach(&message_id(PROMOTABLE_MESSAGE_ID)).await;
+    assert!(matches!(result, Err(Error::NoNeedPromoteOrReattach(_))));
+}
+
+#[tokio::test]
+async fn test_retry() {
+    let (url, posted) = retry_mock_node();
+    let client = client(&url);
+
+    let (_, message) = client.retry(&message_id(PROMOTABLE_MESSAGE_ID)).await.unwrap();
+    assert_promotion(&message, PROMOTABLE_MESSAGE_ID);
+
+    let (_, message) = client.retry(&message_id(REATTACHABLE_MESSAGE_ID)).await.unwrap();
+    assert_reattachment(&message);
+
+    let result = client.retry(&message_id(PENDING_MESSAGE_ID)).await;
+    assert!(matches!(result, Err(Error::NoNeedPromoteOrReattach(_))));
+    assert_eq!(posted.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_retry_referenced_message() {
+    let (url, posted) = retry_mock_node();
+    let client = client(&url);
+    let referenced_message_id = message_id(REFERENCED_MESSAGE_ID);
+
+    let result = client.retry(&referenced_message_id).await;
+    assert!(matches!(result, Err(Error::MessageAlreadyReferenced(id, 10)) if id == REFERENCED_MESSAGE_ID));
+    assert!(matches!(
+        client.promote(&referenced_message_id).await,
+        Err(Error::MessageAlreadyReferenced(..))
+    ));
+    assert!(matches!(
+        client.reattach(&referenced_message_id).await,
+        Err(Error::MessageAlreadyReferenced(..))
+    ));
+    assert!(posted.lock().unwrap().is_empty());
+}
+
+// The message is reattached on the first poll, the reattachment is promoted on the second poll and referenced on the
+// third one.
+#[tokio::test]
+async fn test_retry_until_included() {
+    let polls = Arc::new(AtomicUsize::new(0));
+    let (url, posted) = retry_mock_node_with_metadata(move |message_id| match message_id {
+        REATTACHABLE_MESSAGE_ID => {
+            polls.fetch_add(1, Ordering::SeqCst);
+            message_metadata(message_id)
+        }
+        // the reattachment
+        MESSAGE_ID if polls.load(Ordering::SeqCst) < 3 => {
+            Some(metadata(message_id, (Some(true), Some(false)), None, "included"))
+        }
+        MESSAGE_ID => Some(metadata(message_id, (None, None), Some(12), "included")),
+        _ => message_metadata(message_id),
+    });
+
+    let states = client(&url)
+        .retry_until_included(
+            &message_id(REATTACHABLE_MESSAGE_ID),
+            Some(Duration::from_millis(10)),
+            Some(5),
+        )
+        .await
+        .unwrap();
+    assert_eq!(states.len(), 2);
+    assert_eq!(states[0].message_id, REATTACHABLE_MESSAGE_ID);
+    assert_eq!(states[0].inclusion_state, None);
+    assert_eq!(states[1].message_id, MESSAGE_ID);
+    assert_eq!(states[1].inclusion_state, Some(LedgerInclusionState::Included));
+
+    let posted = posted.lock().unwrap();
+    assert_eq!(posted.len(), 2);
+    assert_reattachment(&posted[0]);
+    assert_promotion(&posted[1], MESSAGE_ID);
+}
+
+#[tokio::test]
+async fn test_retry_until_included_max_attempts() {
+    let (url, posted) = retry_mock_node();
+    let result = client(&url)
+        .retry_until_included(
+            &message_id(PENDING_MESSAGE_ID),
+            Some(Duration::from_millis(10)),
+            Some(3),
+        )
+        .await;
+    match result {
+        Err(error @ Error::NotIncluded(..)) => {
+            assert!(error
+                .to_string()
+                .ends_with(&format!("states: {} pending", PENDING_MESSAGE_ID)));
+            match error {
+                Error::NotIncluded(id, 3, states) => {
+                    assert_eq!(id, PENDING_MESSAGE_ID);
+                    assert_eq!(states.len(), 1);
+                    assert_eq!(states[0].inclusion_state, None);
+                }
+                _ => unreachable!(),
+            }
+        }
+        _ => panic!("the pending message must not be included"),
+    }
+    assert!(posted.lock().unwrap().is_empty());
+
+    let result = client(&url)
+        .retry_until_included(&message_id(PENDING_MESSAGE_ID), None, Some(0))
+        .await;
+    assert!(matches!(result, Err(Error::InvalidParameter(_))));
+}
+
+#[tokio::test]
+async fn test_retry_until_included_conflicting() {
+    let (url, posted) = retry_mock_node();
+    let result = client(&url)
+        .retry_until_included(
+            &message_id(CONFLICTING_MESSAGE_ID),
+            Some(Duration::from_millis(10)),
+            Some(3),
+        )
+        .await;
+    assert!(matches!(result, Err(Error::ConflictingTransaction(id)) if id == CONFLICTING_MESSAGE_ID));
+    assert!(posted.lock().unwrap().is_empty());
+}
@@ -0,0 +1,482 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Value transfer tests against a mock node holding a known UTXO set.
+
+mod common;
+
+use bee_common::packable::Packable;
+use bee_message::prelude::*;
+use common::{
+    utxo_mock_node, utxo_mock_node_with_dust_allowances, utxo_mock_node_with_messages, MockOutput, MESSAGE_ID,
+};
+use iota_client::{
+    api::{RemainderStrategy, DUST_THRESHOLD, MESSAGE_LENGTH_MAX, OUTPUT_COUNT_MAX},
+    Error, Seed,
+};
+use std::collections::{HashMap, HashSet};
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+const TRANSACTION_ID_1: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const TRANSACTION_ID_2: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+const TRANSACTION_ID_3: &str = "0000000000000000000000000000000000000000000000000000000000000003";
+const RECIPIENT: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+const COLD_ADDRESS: &str = "iot1qyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zvlp4jc";
+
+fn seed() -> Seed {
+    Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap()
+}
+
+// The public addresses of the seed's first account.
+fn public_addresses() -> Vec<String> {
+    account_addresses(false)
+}
+
+// The public or internal addresses of the seed's first account.
+fn account_addresses(internal: bool) -> Vec<String> {
+    let client = iota_client::Client::build()
+        .with_node("http://localhost:14265")
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap();
+    client
+        .find_addresses(&seed())
+        .with_account_index(0)
+        .with_range(0..3)
+        .get_all()
+        .unwrap()
+        .into_iter()
+        .filter(|(_, address_internal)| *address_internal == internal)
+        .map(|(address, _)| address.to_string())
+        .collect()
+}
+
+// 3 Mi on the address 0 and 2 Mi on the address 2.
+fn utxo_set() -> Vec<MockOutput> {
+    let addresses = public_addresses();
+    vec![
+        MockOutput {
+            transaction_id: TRANSACTION_ID_1,
+            index: 0,
+            address: addresses[0].clone(),
+            amount: 3_000_000,
+        },
+        MockOutput {
+            transaction_id: TRANSACTION_ID_2,
+            index: 1,
+            address: addresses[2].clone(),
+            amount: 2_000_000,
+        },
+    ]
+}
+
+fn client(url: &str) -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_local_pow(false)
+        .finish()
+        .unwrap()
+}
+
+async fn send(amount: u64) -> iota_client::Result<(MessageId, Message)> {
+    let seed = seed();
+    client(&utxo_mock_node(utxo_set()))
+        .send()
+        .with_seed(&seed)
+        .with_output(&RECIPIENT.to_string().into(), amount)?
+        .finish_with_message()
+        .await
+}
+
+// Returns the inputs and outputs of the transaction, with the number of unlock blocks.
+fn transaction(message: &Message) -> (HashSet<(String, u16)>, HashMap<String, u64>, usize) {
+    let transaction = match message.payload() {
+        Some(Payload::Transaction(transaction)) => transaction,
+        _ => panic!("the message doesn't have a transaction payload"),
+    };
+    let inputs = transaction
+        .essence()
+        .inputs()
+        .iter()
+        .map(|input| match input {
+            Input::UTXO(input) => (
+                input.output_id().transaction_id().to_string(),
+                input.output_id().index(),
+            ),
+            _ => unreachable!(),
+        })
+        .collect();
+    let outputs = transaction
+        .essence()
+        .outputs()
+        .iter()
+        .map(|output| match output {
+            Output::SignatureLockedSingle(output) => (output.address().to_bech32(), output.amount()),
+            _ => unreachable!(),
+        })
+        .collect();
+    (inputs, outputs, transaction.unlock_blocks().len())
+}
+
+#[tokio::test]
+async fn test_send_with_remainder() {
+    let (message_id, message) = send(4_000_000).await.unwrap();
+    assert_eq!(message_id.to_string(), MESSAGE_ID);
+
+    let (inputs, outputs, unlock_blocks) = transaction(&message);
+    let expected_inputs: HashSet<(String, u16)> =
+        vec![(TRANSACTION_ID_1.to_string(), 0), (TRANSACTION_ID_2.to_string(), 1)]
+            .into_iter()
+            .collect();
+    assert_eq!(inputs, expected_inputs);
+    // the remainder goes to the first internal address without outputs
+    let remainder_address = account_addresses(true)[0].clone();
+    let expected_outputs: HashMap<String, u64> =
+        vec![(RECIPIENT.to_string(), 4_000_000), (remainder_address, 1_000_000)]
+            .into_iter()
+            .collect();
+    assert_eq!(outputs, expected_outputs);
+    assert_eq!(unlock_blocks, 2);
+}
+
+#[tokio::test]
+async fn test_send_without_remainder() {
+    let (_, message) = send(3_000_000).await.unwrap();
+    let (inputs, outputs, unlock_blocks) = transaction(&message);
+    assert_eq!(inputs, vec![(TRANSACTION_ID_1.to_string(), 0)].into_iter().collect());
+    assert_eq!(outputs, vec![(RECIPIENT.to_string(), 3_000_000)].into_iter().collect());
+    assert_eq!(unlock_blocks, 1);
+
+    let (_, message) = send(5_000_000).await.unwrap();
+    let (inputs, outputs, _) = transaction(&message);
+    assert_eq!(inputs.len(), 2);
+    assert_eq!(outputs, vec![(RECIPIENT.to_string(), 5_000_000)].into_iter().collect());
+}
+
+#[tokio::test]
+async fn test_send_dust() {
+    // dust output to an address without dust allowance, rejected before the message is sent
+    let (url, messages) = utxo_mock_node_with_messages(utxo_set());
+    let seed = seed();
+    let result = client(&url)
+        .send()
+        .with_seed(&seed)
+        .with_output(&RECIPIENT.to_string().into(), DUST_THRESHOLD - 1)
+        .unwrap()
+        .finish()
+        .await;
+    assert!(matches!(result, Err(Error::DustProtection(address, 999_999, 0)) if address == RECIPIENT));
+    assert!(messages.lock().unwrap().is_empty());
+
+    // without the check, the node validates it
+    let (_, message) = client(&url)
+        .send()
+        .with_seed(&seed)
+        .with_output(&RECIPIENT.to_string().into(), DUST_THRESHOLD - 1)
+        .unwrap()
+        .with_dust_check_disabled()
+        .finish_with_message()
+        .await
+        .unwrap();
+    let (_, outputs, _) = transaction(&message);
+    assert_eq!(outputs[RECIPIENT], 999_999);
+
+    // dust remainder
+    let result = send(2_500_000).await;
+    let remainder_address = account_addresses(true)[0].clone();
+    let message = result.as_ref().unwrap_err().to_string();
+    assert!(matches!(result, Err(Error::DustRemainder(address, 500_000, 0)) if address == remainder_address));
+    assert!(message.starts_with(&format!(
+        "Remainder of 500000 to {} violates the dust protection",
+        remainder_address
+    )));
+}
+
+#[tokio::test]
+async fn test_send_dust_with_allowance() {
+    // a dust allowance of 1 Mi allows 10 dust outputs
+    let dust_allowance = MockOutput {
+        transaction_id: TRANSACTION_ID_3,
+        index: 0,
+        address: RECIPIENT.to_string(),
+        amount: 1_000_000,
+    };
+    let (url, _) = utxo_mock_node_with_dust_allowances(utxo_set(), vec![dust_allowance]);
+    let seed = seed();
+    let (_, message) = client(&url)
+        .send()
+        .with_seed(&seed)
+        .with_output(&RECIPIENT.to_string().into(), 500_000)
+        .unwrap()
+        .finish_with_message()
+        .await
+        .unwrap();
+    let (_, outputs, _) = transaction(&message);
+    assert_eq!(outputs[RECIPIENT], 500_000);
+
+    // the address already holds its 10 dust outputs
+    let mut outputs = utxo_set();
+    outputs.extend((1..11).map(|index| MockOutput {
+        transaction_id: TRANSACTION_ID_3,
+        index,
+        address: RECIPIENT.to_string(),
+        amount: 100_000,
+    }));
+    let dust_allowance = MockOutput {
+        transaction_id: TRANSACTION_ID_3,
+        index: 0,
+        address: RECIPIENT.to_string(),
+        amount: 1_000_000,
+    };
+    let (url, _) = utxo_mock_node_with_dust_allowances(outputs, vec![dust_allowance]);
+    let result = client(&url)
+        .send()
+        .with_seed(&seed)
+        .with_output(&RECIPIENT.to_string().into(), 500_000)
+        .unwrap()
+        .finish()
+        .await;
+    assert!(matches!(result, Err(Error::DustProtection(address, 500_000, 0)) if address == RECIPIENT));
+}
+
+#[tokio::test]
+async fn test_send_multiple_outputs() {
+    let addresses = public_addresses();
+    let seed = seed();
+    let (_, message) = client(&utxo_mock_node(utxo_set()))
+        .send()
+        .with_seed(&seed)
+        .with_output(&RECIPIENT.to_string().into(), 1_500_000)
+        .unwrap()
+        .with_dust_allowance_output(&addresses[1].clone().into(), 1_000_000)
+        .unwrap()
+        .with_output(&addresses[1].clone().into(), 1_000_000)
+        .unwrap()
+        // merged with the first output to the recipient
+        .with_output(&RECIPIENT.to_string().into(), 500_000)
+        .unwrap()
+        .finish_with_message()
+        .await
+        .unwrap();
+    let transaction = match message.payload() {
+        Some(Payload::Transaction(transaction)) => transaction,
+        _ => panic!("the message doesn't have a transaction payload"),
+    };
+    // 4 Mi are spent from the two inputs and the remainder goes to the change address
+    assert_eq!(transaction.essence().inputs().len(), 2);
+    let outputs = transaction
+        .essence()
+        .outputs()
+        .iter()
+        .map(|output| match output {
+            Output::SignatureLockedSingle(output) => (output.address().to_bech32(), output.amount(), 0),
+            Output::SignatureLockedDustAllowance(output) => (output.address().to_bech32(), output.amount(), 1),
+            _ => unreachable!(),
+        })
+        .collect::<HashSet<(String, u64, u8)>>();
+    let expected_outputs = vec![
+        (RECIPIENT.to_string(), 2_000_000, 0),
+        (addresses[1].clone(), 1_000_000, 1),
+        (addresses[1].clone(), 1_000_000, 0),
+        (account_addresses(true)[0].clone(), 1_000_000, 0),
+    ]
+    .into_iter()
+    .collect::<HashSet<(String, u64, u8)>>();
+    assert_eq!(outputs, expected_outputs);
+
+    // the outputs are in the protocol order
+    let serialized_outputs = transaction
+        .essence()
+        .outputs()
+        .iter()
+        .map(|output| {
+            let mut bytes = Vec::new();
+            output.pack(&mut bytes).unwrap();
+            bytes
+        })
+        .collect::<Vec<Vec<u8>>>();
+    let mut sorted_outputs = serialized_outputs.clone();
+    sorted_outputs.sort();
+    assert_eq!(serialized_outputs, sorted_outputs);
+}
+
+#[tokio::test]
+async fn test_send_too_many_outputs() {
+    let seed = seed();
+    let mut sender = client(&utxo_mock_node(utxo_set())).send().with_seed(&seed);
+    for index in 0..OUTPUT_COUNT_MAX + 1 {
+        sender = sender
+            .with_output_hex(&format!("{:064x}", index + 1), 1_000_000)
+            .unwrap();
+    }
+    let result = sender.finish().await;
+    assert!(matches!(result, Err(Error::TooManyOutputs(128, OUTPUT_COUNT_MAX))));
+
+    // a dust allowance output must transfer at least 1 Mi
+    let result = client(&utxo_mock_node(utxo_set()))
+        .send()
+        .with_dust_allowance_output(&RECIPIENT.to_string().into(), 500_000);
+    assert!(matches!(result, Err(Error::DustError(address, 500_000, DUST_THRESHOLD)) if address == RECIPIENT));
+}
+
+#[tokio::test]
+async fn test_send_with_indexation() {
+    let (url, messages) = utxo_mock_node_with_messages(utxo_set());
+    let seed = seed();
+    let (_, message) = client(&url)
+        .send()
+        .with_seed(&seed)
+        .with_output(&RECIPIENT.to_string().into(), 1_000_000)
+        .unwrap()
+        .with_index("order-42")
+        .with_data(b"paid".to_vec())
+        .finish_with_message()
+        .await
+        .unwrap();
+    // the indexation payload is nested in the essence, no other message is sent
+    let posted = messages.lock().unwrap().clone();
+    assert_eq!(posted, vec![message]);
+    let transaction = match posted[0].payload() {
+        Some(Payload::Transaction(transaction)) => transaction,
+        _ => panic!("the message doesn't have a transaction payload"),
+    };
+    match transaction.essence().payload() {
+        Some(Payload::Indexation(indexation)) => {
+            assert_eq!(indexation.index(), "order-42");
+            assert_eq!(indexation.data(), &b"paid"[..]);
+        }
+        _ => panic!("the essence doesn't have an indexation payload"),
+    }
+
+    // the index and data lengths are checked before the PoW
+    let client = client(&url);
+    let sender = || {
+        client
+            .send()
+            .with_seed(&seed)
+            .with_output(&RECIPIENT.to_string().into(), 1_000_000)
+            .unwrap()
+    };
+    let result = sender().with_index(&"i".repeat(65)).finish().await;
+    assert!(matches!(result, Err(Error::IndexationError(_))));
+    let result = sender().with_index("").finish().await;
+    assert!(matches!(result, Err(Error::IndexationError(_))));
+    let result = sender()
+        .with_index("order-42")
+        .with_data(vec![0; MESSAGE_LENGTH_MAX + 1])
+        .finish()
+        .await;
+    assert!(matches!(result, Err(Error::IndexationError(_))));
+    let result = sender().with_data(b"paid".to_vec()).prepare_transaction().await;
+    assert!(matches!(result, Err(Error::MissingParameter(parameter)) if parameter == "index"));
+    assert_eq!(messages.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_send_not_enough_balance() {
+    let result = send(6_000_000).await;
+    assert!(matches!(result, Err(Error::NotEnoughBalance(5_000_000, 6_000_000))));
+}
+
+// Sends 4 Mi from the 3 Mi and 2 Mi inputs with the remainder strategy, returning the outputs of the transaction.
+async fn send_with_remainder(
+    url: &str,
+    strategy: RemainderStrategy,
+) -> iota_client::Result<(HashMap<String, u64>, Option<iota_client::api::RemainderData>)> {
+    let seed = seed();
+    let sender = client(url)
+        .send()
+        .with_seed(&seed)
+        .with_output(&RECIPIENT.to_string().into(), 4_000_000)?
+        .with_remainder_strategy(strategy);
+    let prepared = sender.prepare_transaction().await?;
+    let (_, message, sent_remainder) = sender.finish_with_remainder().await?;
+    assert_eq!(sent_remainder, prepared.remainder);
+    let (_, outputs, _) = transaction(&message);
+    // the remainder output is identified by its index in the essence outputs
+    if let Some(remainder) = &prepared.remainder {
+        match &prepared.essence.outputs()[remainder.output_index] {
+            Output::SignatureLockedSingle(output) => assert_eq!(*output.address(), remainder.address),
+            _ => panic!("the remainder output isn't a signature locked single output"),
+        }
+    }
+    Ok((outputs, prepared.remainder))
+}
+
+#[tokio::test]
+async fn test_remainder_strategies() {
+    let url = utxo_mock_node(utxo_set());
+
+    let (outputs, remainder) = send_with_remainder(&url, RemainderStrategy::ChangeAddress)
+        .await
+        .unwrap();
+    let change_address = account_addresses(true)[0].clone();
+    assert_eq!(outputs[&change_address], 1_000_000);
+    let remainder = remainder.unwrap();
+    assert_eq!(remainder.address.to_bech32(), change_address);
+    assert_eq!(
+        (remainder.amount, remainder.address_index, remainder.internal),
+        (1_000_000, Some(0), true)
+    );
+
+    // the first input is the largest one, on the address 0
+    let (outputs, remainder) = send_with_remainder(&url, RemainderStrategy::ReuseFirstInputAddress)
+        .await
+        .unwrap();
+    assert_eq!(outputs[&public_addresses()[0]], 1_000_000);
+    let remainder = remainder.unwrap();
+    assert_eq!((remainder.address_index, remainder.internal), (Some(0), false));
+
+    let (outputs, remainder) = send_with_remainder(&url, RemainderStrategy::Custom(COLD_ADDRESS.into()))
+        .await
+        .unwrap();
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(outputs[COLD_ADDRESS], 1_000_000);
+    assert_eq!(remainder.unwrap().address_index, None);
+
+    // the custom address must be an address of the client network
+    let testnet_address = "atoi1qyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3z0aj4re";
+    let result = send_with_remainder(&url, RemainderStrategy::Custom(testnet_address.into())).await;
+    assert!(matches!(
+        result,
+        Err(Error::Bech32HrpMismatch(address, hrp, _)) if address == testnet_address && hrp == "atoi"
+    ));
+}
+
+#[tokio::test]
+async fn test_change_address_skips_used_addresses() {
+    // the first internal address already received an output
+    let mut outputs = utxo_set();
+    outputs.push(MockOutput {
+        transaction_id: TRANSACTION_ID_3,
+        index: 0,
+        address: account_addresses(true)[0].clone(),
+        amount: 500_000,
+    });
+    let url = utxo_mock_node(outputs);
+    let (outputs, remainder) = send_with_remainder(&url, RemainderStrategy::ChangeAddress)
+        .await
+        .unwrap();
+    assert_eq!(outputs[&account_addresses(true)[1]], 1_000_000);
+    assert_eq!(remainder.unwrap().address_index, Some(1));
+}
+
+#[tokio::test]
+async fn test_dust_remainder_to_first_output() {
+    let seed = seed();
+    let (_, message) = client(&utxo_mock_node(utxo_set()))
+        .send()
+        .with_seed(&seed)
+        .with_output(&RECIPIENT.to_string().into(), 2_500_000)
+        .unwrap()
+        .with_dust_remainder_to_first_output(true)
+        .finish_with_message()
+        .await
+        .unwrap();
+    let (inputs, outputs, _) = transaction(&message);
+    assert_eq!(inputs, vec![(TRANSACTION_ID_1.to_string(), 0)].into_iter().collect());
+    assert_eq!(outputs, vec![(RECIPIENT.to_string(), 3_000_000)].into_iter().collect());
+}
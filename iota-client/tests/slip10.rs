@@ -0,0 +1,181 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// SLIP-10 derivation tests, with the Ed25519 vectors of the SLIP-10 specification, the addresses of the hex seed of
+// the iota.rs address vectors and the addresses of a mnemonic derived from its BIP39 seed.
+
+use iota_client::{
+    signer::slip10::{derive_private_key, Chain},
+    Bip39Seed, Error, Seed,
+};
+use std::str::FromStr;
+
+// The seed of the iota.rs address vectors, whose first address is
+// `atoi1qzt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupx3y7x0r` there: the Ed25519 address
+// `96f9de0989e77d0e150e850a5a600e83045fa57419eaf3b20225b763d4e23813` with the address type 0 of the later Chrysalis
+// releases, the type is 1 here.
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+
+// BIP39 seed without passphrase of `abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon
+// abandon about`, the mnemonic of the BIP84 test vectors
+const MNEMONIC_SEED: &str = "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4";
+
+fn client() -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node("http://localhost:14265")
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+#[test]
+fn test_slip10_ed25519_vectors() {
+    let vectors = [
+        (
+            "000102030405060708090a0b0c0d0e0f",
+            "m/0'",
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+        ),
+        (
+            "000102030405060708090a0b0c0d0e0f",
+            "m/0'/1'/2'/2'/1000000000'",
+            "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+        ),
+        (
+            "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a29f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542",
+            "m/0'/2147483647'/1'/2147483646'/2'",
+            "551d333177df541ad876a60ea71f00447931c0a9da16f227c11ea080d7391b8d",
+        ),
+    ];
+    for (seed, path, private_key) in vectors.iter() {
+        let chain = Chain::from_str(path).unwrap();
+        assert_eq!(
+            hex::encode(derive_private_key(&hex::decode(seed).unwrap(), &chain).unwrap()),
+            *private_key
+        );
+    }
+}
+
+#[test]
+fn test_bip44_chain() {
+    let chain = Chain::bip44(1, true, 2).unwrap();
+    assert_eq!(chain.to_string(), "m/44'/4218'/1'/1'/2'");
+    assert_eq!(Chain::from_str("m/44H/4218H/1H/1H/2H").unwrap(), chain);
+}
+
+#[test]
+fn test_non_hardened_paths_are_rejected() {
+    assert!(matches!(
+        Chain::from_str("m/44'/4218'/0'/0/0"),
+        Err(Error::InvalidDerivationPath(_))
+    ));
+    assert!(matches!(
+        Chain::from_str("m/44'/4218'/2147483648'"),
+        Err(Error::InvalidDerivationPath(_))
+    ));
+    assert!(matches!(
+        Chain::bip44(1 << 31, false, 0),
+        Err(Error::InvalidDerivationPath(_))
+    ));
+    assert!(matches!(
+        client()
+            .find_addresses(&Seed::from_ed25519_bytes(&[0; 32]).unwrap())
+            .with_account_index(1 << 31)
+            .with_range(0..1)
+            .finish(),
+        Err(Error::InvalidDerivationPath(_))
+    ));
+}
+
+#[test]
+fn test_seed_addresses() {
+    // a 32 bytes seed is the SLIP-10 seed as is, it derives the addresses it derived before the SLIP-10 paths
+    let seed = Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap();
+    let client = iota_client::Client::build()
+        .with_node("http://localhost:14265")
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_bech32_hrp("atoi")
+        .finish()
+        .unwrap();
+    let addresses: Vec<(String, bool)> = client
+        .find_addresses(&seed)
+        .with_account_index(0)
+        .with_range(0..2)
+        .get_all()
+        .unwrap()
+        .into_iter()
+        .map(|(address, internal)| (address.to_string(), internal))
+        .collect();
+    assert_eq!(
+        addresses,
+        vec![
+            (
+                "atoi1qxt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupxtmtev5".to_string(),
+                false
+            ),
+            (
+                "atoi1q9rxpfvaz2peggq6f8k9cj8zfsxuw69e4nszjyv5kuf8yt70t28472g5d7p".to_string(),
+                true
+            ),
+            (
+                "atoi1q9nrumvaex24dy0duulp4q07lpa00w20ze6jfd0xly422kdcjxzakc0ht47".to_string(),
+                false
+            ),
+            (
+                "atoi1qypvxmuyq97gtl98jfs4tdtwkvh4qjj0slng5jcg4x7vs3muds74yhj7qwy".to_string(),
+                true
+            ),
+        ]
+    );
+}
+
+// No published IOTA vector covers a BIP39 seed, the addresses are pinned as derived with SLIP-10 from the BIP39 seed,
+// which the specification vectors above check.
+#[test]
+fn test_mnemonic_addresses() {
+    let seed = Bip39Seed::from_bytes(&hex::decode(MNEMONIC_SEED).unwrap()).unwrap();
+    let client = client();
+    let addresses = client
+        .find_addresses(&seed)
+        .with_account_index(0)
+        .with_range(0..2)
+        .get_all()
+        .unwrap();
+    let addresses: Vec<(String, bool)> = addresses
+        .into_iter()
+        .map(|(address, internal)| (address.to_string(), internal))
+        .collect();
+    assert_eq!(
+        addresses,
+        vec![
+            (
+                "iot1qym9ka8j0jnud47wqxwhxpp0shxyvflp4mkzk7pzn98pvqgzxnjhvu4uya0".to_string(),
+                false
+            ),
+            (
+                "iot1q8m26fc7sxg8zg0qa4ksp76f8wc756wzz607d4avf6hqgrrgpyyesdpyapv".to_string(),
+                true
+            ),
+            (
+                "iot1q8vz77h2tjdu2c5e2l75k587mwfz3fggjznngxj7m5y8sj38czwmcdfvmw0".to_string(),
+                false
+            ),
+            (
+                "iot1qys0tcnpw3tkllvqx7w2zrxgjpm3cmvfp2t0e6v9da2q0zvjhqrqqgfjn8u".to_string(),
+                true
+            ),
+        ]
+    );
+    let account_1 = client
+        .find_addresses(&seed)
+        .with_account_index(1)
+        .with_range(0..1)
+        .finish()
+        .unwrap();
+    assert_eq!(
+        account_1[0].to_string(),
+        "iot1qxnu43j3pn84p3h6ag3wx6dja80g426c2q4rw7s2yxmkkatctl567uq78za"
+    );
+}
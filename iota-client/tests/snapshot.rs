@@ -0,0 +1,568 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Snapshot tests against generated snapshots: a full snapshot at milestone 10 with its ledger at milestone 12, and a
+// delta snapshot from milestone 10 to 13. The files are created in the temporary directory. A delta snapshot written
+// field by field from the Hornet file format checks the layout of the writer.
+
+#![cfg(feature = "snapshot")]
+
+use bee_message::prelude::*;
+use iota_client::{
+    api::IOTA_SUPPLY,
+    snapshot::{
+        balance_of, check_supply, SnapshotKind, SnapshotReader, SnapshotSection, SnapshotTreasuryOutput, TreasuryChange,
+    },
+    Error, OutputType,
+};
+use std::path::PathBuf;
+
+const NETWORK_ID: u64 = 6_530_425_480_034_647_824;
+const FULL_HEADER_LENGTH: usize = 90;
+const OUTPUT_LENGTH: usize = 108;
+
+// An output of the `[transaction; 32]` transaction to the `[address; 32]` Ed25519 address.
+#[derive(Clone)]
+struct Output {
+    transaction: u8,
+    index: u16,
+    address: u8,
+    amount: u64,
+    dust_allowance: bool,
+}
+
+fn output(transaction: u8, address: u8, amount: u64) -> Output {
+    Output {
+        transaction,
+        index: 0,
+        address,
+        amount,
+        dust_allowance: false,
+    }
+}
+
+fn output_id(output: &Output) -> String {
+    format!(
+        "{}{}",
+        hex::encode([output.transaction; 32]),
+        hex::encode(output.index.to_le_bytes())
+    )
+}
+
+fn address(address: u8) -> Address {
+    Address::Ed25519(Ed25519Address::new([address; 32]))
+}
+
+fn output_bytes(output: &Output) -> Vec<u8> {
+    let mut bytes = vec![0xab; 32];
+    bytes.extend_from_slice(&[output.transaction; 32]);
+    bytes.extend_from_slice(&output.index.to_le_bytes());
+    bytes.push(output.dust_allowance as u8);
+    bytes.push(0);
+    bytes.extend_from_slice(&[output.address; 32]);
+    bytes.extend_from_slice(&output.amount.to_le_bytes());
+    bytes
+}
+
+// The treasury amounts spent and created by a receipt, with its migrated funds.
+type Receipt = (u64, u64, Vec<Output>);
+
+// A milestone diff, the index and the receipt with the created and the consumed outputs, the migrated funds included.
+type Diff = (u32, Option<Receipt>, Vec<Output>, Vec<Output>);
+
+const TREASURY_AMOUNT: u64 = 2_500_000;
+
+// A milestone payload of two parents, a public key and a signature.
+fn milestone_bytes(index: u32, receipt: &Option<Receipt>) -> Vec<u8> {
+    let mut bytes = 1u32.to_le_bytes().to_vec();
+    bytes.extend_from_slice(&index.to_le_bytes());
+    bytes.extend_from_slice(&1_620_000_000u64.to_le_bytes());
+    bytes.push(2);
+    bytes.extend_from_slice(&[0xee; 64]);
+    // the inclusion merkle proof, the next PoW score and its milestone index
+    bytes.extend_from_slice(&[0; 40]);
+    bytes.push(1);
+    bytes.extend_from_slice(&[0x11; 32]);
+    match receipt {
+        Some((_, amount, funds)) => {
+            let mut receipt = 3u32.to_le_bytes().to_vec();
+            receipt.extend_from_slice(&index.to_le_bytes());
+            receipt.push(1);
+            receipt.extend_from_slice(&(funds.len() as u16).to_le_bytes());
+            for output in funds {
+                // the tail transaction hash of the legacy network
+                receipt.extend_from_slice(&[0x99; 49]);
+                receipt.push(0);
+                receipt.extend_from_slice(&[output.address; 32]);
+                receipt.extend_from_slice(&output.amount.to_le_bytes());
+            }
+            // the treasury transaction, from the treasury input to the treasury output
+            receipt.extend_from_slice(&46u32.to_le_bytes());
+            receipt.extend_from_slice(&4u32.to_le_bytes());
+            receipt.push(1);
+            receipt.extend_from_slice(&[0x09; 32]);
+            receipt.push(2);
+            receipt.extend_from_slice(&amount.to_le_bytes());
+            bytes.extend_from_slice(&(receipt.len() as u32).to_le_bytes());
+            bytes.extend(receipt);
+        }
+        None => bytes.extend_from_slice(&0u32.to_le_bytes()),
+    }
+    bytes.push(1);
+    bytes.extend_from_slice(&[0x22; 64]);
+    bytes
+}
+
+fn snapshot_bytes(
+    kind: SnapshotKind,
+    sep_index: u32,
+    ledger_index: u32,
+    outputs: &[Output],
+    diffs: &[Diff],
+) -> Vec<u8> {
+    let mut bytes = vec![1, (kind == SnapshotKind::Delta) as u8];
+    bytes.extend_from_slice(&1_620_000_000u64.to_le_bytes());
+    bytes.extend_from_slice(&NETWORK_ID.to_le_bytes());
+    bytes.extend_from_slice(&sep_index.to_le_bytes());
+    bytes.extend_from_slice(&ledger_index.to_le_bytes());
+    bytes.extend_from_slice(&2u64.to_le_bytes());
+    if kind == SnapshotKind::Full {
+        bytes.extend_from_slice(&(outputs.len() as u64).to_le_bytes());
+    }
+    bytes.extend_from_slice(&(diffs.len() as u64).to_le_bytes());
+    if kind == SnapshotKind::Full {
+        bytes.extend_from_slice(&[0x0b; 32]);
+        bytes.extend_from_slice(&TREASURY_AMOUNT.to_le_bytes());
+    }
+    bytes.extend_from_slice(&[0x01; 32]);
+    bytes.extend_from_slice(&[0x02; 32]);
+    for output in outputs {
+        bytes.extend(output_bytes(output));
+    }
+    for (index, receipt, created, consumed) in diffs {
+        let milestone = milestone_bytes(*index, receipt);
+        bytes.extend_from_slice(&(milestone.len() as u32).to_le_bytes());
+        bytes.extend(milestone);
+        if let Some((spent, _, _)) = receipt {
+            bytes.extend_from_slice(&[0x09; 32]);
+            bytes.extend_from_slice(&spent.to_le_bytes());
+        }
+        bytes.extend_from_slice(&(created.len() as u64).to_le_bytes());
+        for output in created {
+            bytes.extend(output_bytes(output));
+        }
+        bytes.extend_from_slice(&(consumed.len() as u64).to_le_bytes());
+        for output in consumed {
+            bytes.extend(output_bytes(output));
+            bytes.extend_from_slice(&[0xcd; 32]);
+        }
+    }
+    bytes
+}
+
+// Milestone 11 moves 5 Mi of address 3 to a dust allowance of address 2 and migrates 0.5 Mi of the treasury to address
+// 4, milestone 12 moves 1.5 Mi of address 3 to address 1. Address 1 holds 1 Mi at milestone 10.
+fn full_snapshot_outputs() -> (Vec<Output>, Vec<Diff>) {
+    let dust_allowance = Output {
+        dust_allowance: true,
+        index: 1,
+        ..output(11, 2, 5_000_000)
+    };
+    let migrated = output(15, 4, 500_000);
+    let outputs = vec![
+        output(1, 1, 1_000_000),
+        output(12, 1, 1_500_000),
+        dust_allowance.clone(),
+        output(13, 3, 500_000),
+        migrated.clone(),
+        output(0, 3, IOTA_SUPPLY - 8_500_000 - TREASURY_AMOUNT),
+    ];
+    let diffs = vec![
+        (
+            12,
+            None,
+            vec![output(12, 1, 1_500_000), output(13, 3, 500_000)],
+            vec![output(4, 3, 2_000_000)],
+        ),
+        (
+            11,
+            Some((TREASURY_AMOUNT + 500_000, TREASURY_AMOUNT, vec![migrated.clone()])),
+            vec![dust_allowance, migrated],
+            vec![output(3, 3, 5_000_000)],
+        ),
+    ];
+    (outputs, diffs)
+}
+
+fn full_snapshot() -> Vec<u8> {
+    let (outputs, diffs) = full_snapshot_outputs();
+    snapshot_bytes(SnapshotKind::Full, 10, 12, &outputs, &diffs)
+}
+
+// The diffs of the full snapshot, then milestone 13 moves the 1 Mi of address 1 to address 2.
+fn delta_snapshot() -> Vec<u8> {
+    let (_, mut diffs) = full_snapshot_outputs();
+    diffs.reverse();
+    diffs.push((13, None, vec![output(14, 2, 1_000_000)], vec![output(1, 1, 1_000_000)]));
+    snapshot_bytes(SnapshotKind::Delta, 13, 10, &[], &diffs)
+}
+
+// A delta snapshot from milestone 10 to 11, whose receipt migrates 0.5 Mi of the treasury to address 4, written field
+// by field after the file format of Hornet.
+fn hornet_delta_snapshot() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    // the version, the delta kind, the timestamp and the network id
+    bytes.extend_from_slice(&[1, 1]);
+    bytes.extend_from_slice(&[0x00, 0x3d, 0x8f, 0x60, 0, 0, 0, 0]);
+    bytes.extend_from_slice(&[0x10, 0xff, 0x9f, 0xd9, 0x5f, 0xbb, 0xa0, 0x5a]);
+    // the solid entry point and the ledger milestone indexes, the numbers of solid entry points and milestone diffs
+    bytes.extend_from_slice(&[11, 0, 0, 0, 10, 0, 0, 0]);
+    bytes.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 0]);
+    bytes.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+    bytes.extend_from_slice(&[0x01; 32]);
+    bytes.extend_from_slice(&[0x02; 32]);
+
+    // the milestone payload of 374 bytes: the type, the index and the timestamp
+    bytes.extend_from_slice(&[0x76, 0x01, 0, 0]);
+    bytes.extend_from_slice(&[1, 0, 0, 0, 11, 0, 0, 0]);
+    bytes.extend_from_slice(&[0x00, 0x3d, 0x8f, 0x60, 0, 0, 0, 0]);
+    // the parents, the inclusion merkle proof, the next PoW score and its milestone index, the public keys
+    bytes.push(2);
+    bytes.extend_from_slice(&[0xee; 64]);
+    bytes.extend_from_slice(&[0; 40]);
+    bytes.push(1);
+    bytes.extend_from_slice(&[0x11; 32]);
+    // the receipt of 151 bytes: the type, the migration milestone index, the final flag and the migrated funds
+    bytes.extend_from_slice(&[0x97, 0, 0, 0]);
+    bytes.extend_from_slice(&[3, 0, 0, 0, 11, 0, 0, 0, 1]);
+    bytes.extend_from_slice(&[1, 0]);
+    bytes.extend_from_slice(&[0x99; 49]);
+    bytes.push(0);
+    bytes.extend_from_slice(&[0x04; 32]);
+    bytes.extend_from_slice(&[0x20, 0xa1, 0x07, 0, 0, 0, 0, 0]);
+    // the treasury transaction of 46 bytes, spending the treasury of milestone 0x09.. into 2.5 Mi
+    bytes.extend_from_slice(&[46, 0, 0, 0, 4, 0, 0, 0]);
+    bytes.push(1);
+    bytes.extend_from_slice(&[0x09; 32]);
+    bytes.push(2);
+    bytes.extend_from_slice(&[0xa0, 0x25, 0x26, 0, 0, 0, 0, 0]);
+    // the signatures
+    bytes.push(1);
+    bytes.extend_from_slice(&[0x22; 64]);
+
+    // the spent treasury output of 3 Mi
+    bytes.extend_from_slice(&[0x09; 32]);
+    bytes.extend_from_slice(&[0xc0, 0xc6, 0x2d, 0, 0, 0, 0, 0]);
+    // the created output, the migrated funds, and no consumed output
+    bytes.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+    bytes.extend_from_slice(&[0xab; 32]);
+    bytes.extend_from_slice(&[0x0f; 32]);
+    bytes.extend_from_slice(&[0, 0, 0, 0]);
+    bytes.extend_from_slice(&[0x04; 32]);
+    bytes.extend_from_slice(&[0x20, 0xa1, 0x07, 0, 0, 0, 0, 0]);
+    bytes.extend_from_slice(&[0; 8]);
+    bytes
+}
+
+// Removes the file once the test is done.
+struct SnapshotFile(PathBuf);
+
+impl SnapshotFile {
+    fn new(name: &str, bytes: &[u8]) -> Self {
+        let path = std::env::temp_dir().join(format!("iota-client-{}-{}.bin", name, std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for SnapshotFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn assert_invalid(error: Error, expected_offset: u64, expected_section: SnapshotSection) {
+    match error {
+        Error::InvalidSnapshot {
+            offset,
+            section,
+            reason,
+        } => assert_eq!((offset, section), (expected_offset, expected_section), "{}", reason),
+        error => panic!("unexpected error {:?}", error),
+    }
+}
+
+#[test]
+fn test_snapshot_reader() {
+    let bytes = full_snapshot();
+    let mut reader = SnapshotReader::new(bytes.as_slice()).unwrap();
+    let header = reader.header().clone();
+    assert_eq!(header.kind, SnapshotKind::Full);
+    assert_eq!(header.network_id, NETWORK_ID);
+    assert_eq!((header.sep_milestone_index, header.ledger_milestone_index), (10, 12));
+    assert_eq!(
+        (header.sep_count, header.output_count, header.milestone_diff_count),
+        (2, 6, 2)
+    );
+    assert_eq!(
+        header.treasury_output,
+        Some(SnapshotTreasuryOutput {
+            milestone_id: hex::encode([0x0b; 32]),
+            amount: TREASURY_AMOUNT,
+        })
+    );
+    assert_eq!(reader.offset(), FULL_HEADER_LENGTH as u64);
+
+    // the outputs skip the solid entry points
+    let outputs: Vec<(String, Address, u64, OutputType)> = reader
+        .outputs()
+        .map(|output| {
+            let output = output.unwrap();
+            (output.output_id, output.address, output.amount, output.output_type)
+        })
+        .collect();
+    let (expected, _) = full_snapshot_outputs();
+    assert_eq!(outputs.len(), expected.len());
+    assert_eq!(
+        outputs[0],
+        (
+            output_id(&expected[0]),
+            address(1),
+            1_000_000,
+            OutputType::SignatureLockedSingle
+        )
+    );
+    assert_eq!(outputs[2].0, output_id(&expected[2]));
+    assert_eq!(outputs[2].3, OutputType::SignatureLockedDustAllowance);
+    assert_eq!(reader.solid_entry_points().count(), 0);
+
+    let diffs: Vec<(u32, Option<TreasuryChange>, usize, usize)> = reader
+        .milestone_diffs()
+        .map(|diff| {
+            let diff = diff.unwrap();
+            (
+                diff.milestone_index,
+                diff.treasury,
+                diff.created.len(),
+                diff.consumed.len(),
+            )
+        })
+        .collect();
+    let treasury = TreasuryChange {
+        spent: SnapshotTreasuryOutput {
+            milestone_id: hex::encode([0x09; 32]),
+            amount: TREASURY_AMOUNT + 500_000,
+        },
+        amount: TREASURY_AMOUNT,
+    };
+    assert_eq!(diffs, vec![(12, None, 2, 1), (11, Some(treasury), 2, 1)]);
+    assert_eq!(reader.offset(), bytes.len() as u64);
+
+    let bytes = delta_snapshot();
+    let mut reader = SnapshotReader::new(bytes.as_slice()).unwrap();
+    assert_eq!(reader.header().kind, SnapshotKind::Delta);
+    assert_eq!(reader.header().treasury_output, None);
+    assert_eq!(reader.solid_entry_points().count(), 2);
+    assert_eq!(reader.outputs().count(), 0);
+    let indexes: Vec<u32> = reader
+        .milestone_diffs()
+        .map(|diff| diff.unwrap().milestone_index)
+        .collect();
+    assert_eq!(indexes, vec![11, 12, 13]);
+}
+
+#[test]
+fn test_hornet_snapshot_layout() {
+    let bytes = hornet_delta_snapshot();
+    let migrated = output(15, 4, 500_000);
+    let receipt = (TREASURY_AMOUNT + 500_000, TREASURY_AMOUNT, vec![migrated.clone()]);
+    assert_eq!(
+        bytes,
+        snapshot_bytes(
+            SnapshotKind::Delta,
+            11,
+            10,
+            &[],
+            &[(11, Some(receipt), vec![migrated], vec![])]
+        )
+    );
+
+    let mut reader = SnapshotReader::new(bytes.as_slice()).unwrap();
+    assert_eq!(reader.header().network_id, NETWORK_ID);
+    let diff = reader.next_milestone_diff().unwrap().unwrap();
+    assert_eq!(diff.milestone_index, 11);
+    assert_eq!(
+        diff.treasury,
+        Some(TreasuryChange {
+            spent: SnapshotTreasuryOutput {
+                milestone_id: hex::encode([0x09; 32]),
+                amount: 3_000_000,
+            },
+            amount: TREASURY_AMOUNT,
+        })
+    );
+    assert_eq!(diff.created[0].address, address(4));
+    assert!(diff.consumed.is_empty());
+    assert_eq!(reader.offset(), bytes.len() as u64);
+    let delta = SnapshotFile::new("hornet-delta-snapshot", &bytes);
+    check_supply(&delta.0).unwrap();
+
+    // the length of the milestone payload covers its fields
+    let diffs_offset = 42 + 64;
+    let mut corrupted = bytes.clone();
+    corrupted[diffs_offset] += 1;
+    let error = SnapshotReader::new(corrupted.as_slice())
+        .unwrap()
+        .next_milestone_diff()
+        .unwrap_err();
+    assert_invalid(error, diffs_offset as u64, SnapshotSection::MilestoneDiffs);
+}
+
+#[test]
+fn test_snapshot_balances_with_delta() {
+    let full = SnapshotFile::new("full-snapshot", &full_snapshot());
+    let delta = SnapshotFile::new("delta-snapshot", &delta_snapshot());
+    let mut balances = balance_of(&[address(1), address(2), address(4)], &full.0).unwrap();
+    // the diffs of the full snapshot are rolled back from its ledger milestone
+    assert_eq!((balances.network_id(), balances.milestone_index()), (NETWORK_ID, 10));
+    assert_eq!(balances.balance(&address(1)), Some(1_000_000));
+    assert_eq!(balances.balance(&address(2)), Some(0));
+    assert_eq!(balances.balance(&address(3)), None);
+    // the funds migrated by milestone 11
+    assert_eq!(balances.balance(&address(4)), Some(0));
+
+    balances.apply_delta(&delta.0).unwrap();
+    assert_eq!(balances.milestone_index(), 13);
+    assert_eq!(balances.balance(&address(1)), Some(1_500_000));
+    assert_eq!(balances.balance(&address(2)), Some(6_000_000));
+    assert_eq!(balances.balance(&address(4)), Some(500_000));
+
+    // the delta starts at milestone 10, the balances are at milestone 13 now
+    assert_invalid(balances.apply_delta(&delta.0).unwrap_err(), 22, SnapshotSection::Header);
+    assert_invalid(balances.apply_delta(&full.0).unwrap_err(), 1, SnapshotSection::Header);
+    assert_invalid(
+        balance_of(&[address(1)], &delta.0).unwrap_err(),
+        1,
+        SnapshotSection::Header,
+    );
+}
+
+#[test]
+fn test_snapshot_supply() {
+    let full = SnapshotFile::new("supply-full-snapshot", &full_snapshot());
+    let delta = SnapshotFile::new("supply-delta-snapshot", &delta_snapshot());
+    check_supply(&full.0).unwrap();
+    check_supply(&delta.0).unwrap();
+
+    let (mut outputs, diffs) = full_snapshot_outputs();
+    outputs.push(output(20, 4, 1_000_000));
+    let minted = SnapshotFile::new(
+        "minted-snapshot",
+        &snapshot_bytes(SnapshotKind::Full, 10, 12, &outputs, &diffs),
+    );
+    match check_supply(&minted.0).unwrap_err() {
+        Error::SnapshotSupplyMismatch(what, sum, expected) => {
+            assert_eq!(what, "outputs and treasury");
+            assert_eq!(
+                (sum, expected),
+                (u128::from(IOTA_SUPPLY) + 1_000_000, u128::from(IOTA_SUPPLY))
+            );
+        }
+        error => panic!("unexpected error {:?}", error),
+    }
+
+    let unbalanced = snapshot_bytes(
+        SnapshotKind::Delta,
+        11,
+        10,
+        &[],
+        &[(11, None, vec![output(14, 2, 2_000_000)], vec![output(1, 1, 1_000_000)])],
+    );
+    let unbalanced = SnapshotFile::new("unbalanced-snapshot", &unbalanced);
+    match check_supply(&unbalanced.0).unwrap_err() {
+        Error::SnapshotSupplyMismatch(what, sum, expected) => {
+            assert_eq!(what, "outputs created by milestone 11");
+            assert_eq!((sum, expected), (2_000_000, 1_000_000));
+        }
+        error => panic!("unexpected error {:?}", error),
+    }
+
+    // the receipt migrates more than it takes from the treasury
+    let migrated = output(15, 4, 1_000_000);
+    let receipt = (TREASURY_AMOUNT + 500_000, TREASURY_AMOUNT, vec![migrated.clone()]);
+    let unbalanced = snapshot_bytes(
+        SnapshotKind::Delta,
+        11,
+        10,
+        &[],
+        &[(11, Some(receipt), vec![migrated], vec![])],
+    );
+    let unbalanced = SnapshotFile::new("unbalanced-receipt-snapshot", &unbalanced);
+    match check_supply(&unbalanced.0).unwrap_err() {
+        Error::SnapshotSupplyMismatch(what, sum, expected) => {
+            assert_eq!(what, "outputs created by milestone 11");
+            assert_eq!((sum, expected), (3_500_000, 3_000_000));
+        }
+        error => panic!("unexpected error {:?}", error),
+    }
+}
+
+#[test]
+fn test_corrupted_snapshots() {
+    let bytes = full_snapshot();
+    let outputs_offset = FULL_HEADER_LENGTH + 64;
+
+    // truncated in the transaction id of the first output
+    let truncated = &bytes[..outputs_offset + 50];
+    let mut reader = SnapshotReader::new(truncated).unwrap();
+    let error = reader.outputs().next().unwrap().unwrap_err();
+    assert_invalid(error, truncated.len() as u64, SnapshotSection::Outputs);
+    // the iteration ends with the error
+    assert!(reader.milestone_diffs().next().is_none());
+    let truncated = SnapshotFile::new("truncated-snapshot", truncated);
+    assert_invalid(
+        balance_of(&[address(1)], &truncated.0).unwrap_err(),
+        (outputs_offset + 50) as u64,
+        SnapshotSection::Outputs,
+    );
+
+    // an unknown type of the second output
+    let mut corrupted = bytes.clone();
+    let output_type_offset = outputs_offset + OUTPUT_LENGTH + 66;
+    corrupted[output_type_offset] = 7;
+    let error = SnapshotReader::new(corrupted.as_slice())
+        .unwrap()
+        .outputs()
+        .find_map(|output| output.err())
+        .unwrap();
+    assert_invalid(error, output_type_offset as u64, SnapshotSection::Outputs);
+
+    // a header truncated in the amount of the treasury output
+    let error = SnapshotReader::new(&bytes[..FULL_HEADER_LENGTH - 4]).err().unwrap();
+    assert_invalid(error, (FULL_HEADER_LENGTH - 4) as u64, SnapshotSection::Header);
+
+    // data after the last milestone diff
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    let error = SnapshotReader::new(trailing.as_slice())
+        .unwrap()
+        .milestone_diffs()
+        .find_map(|diff| diff.err())
+        .unwrap();
+    assert_invalid(error, bytes.len() as u64, SnapshotSection::MilestoneDiffs);
+
+    // milestone diffs out of order
+    let (outputs, mut diffs) = full_snapshot_outputs();
+    diffs.reverse();
+    let unordered = snapshot_bytes(SnapshotKind::Full, 10, 12, &outputs, &diffs);
+    let error = SnapshotReader::new(unordered.as_slice())
+        .unwrap()
+        .milestone_diffs()
+        .find_map(|diff| diff.err())
+        .unwrap();
+    assert_invalid(
+        error,
+        (outputs_offset + outputs.len() * OUTPUT_LENGTH) as u64,
+        SnapshotSection::MilestoneDiffs,
+    );
+}
@@ -0,0 +1,214 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Client storage tests against a mock node recording its requests, where the first three addresses of the seed hold
+// an output and the message `REFERENCED_MESSAGE_ID` is referenced by a milestone.
+
+mod common;
+
+use bee_message::prelude::*;
+use bee_pow::providers::{Constant, ConstantBuilder};
+use common::{node_info, start_mock_node, MESSAGE_ID};
+use iota_client::{hash_network_id, Client, ClientBuilder, MemoryStorage, Seed};
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+const REFERENCED_MESSAGE_ID: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+
+fn seed() -> Seed {
+    Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap()
+}
+
+fn builder(url: &str, network_id: &str) -> ClientBuilder {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .with_network_id(network_id)
+        .with_address_scan_window(1)
+}
+
+// The public addresses of the seed's first account.
+fn public_addresses() -> Vec<String> {
+    builder("http://localhost:14265", "testnet")
+        .finish()
+        .unwrap()
+        .find_addresses(&seed())
+        .with_account_index(0)
+        .with_range(0..4)
+        .finish()
+        .unwrap()
+        .into_iter()
+        .map(|address| address.to_string())
+        .collect()
+}
+
+// A mock node with the paths of its requests.
+fn mock_node() -> (String, Arc<Mutex<Vec<String>>>) {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let used_addresses = public_addresses()[..3].to_vec();
+    let recorded = requests.clone();
+    let url = start_mock_node(move |method, path, _| {
+        recorded.lock().unwrap().push(path.to_string());
+        // the outputs are unspent, they're listed with and without `include-spent=true`
+        let path = path.split('?').next().unwrap();
+        let metadata = path
+            .strip_prefix("/api/v1/messages/")
+            .and_then(|path| path.strip_suffix("/metadata"));
+        if method == "GET" && path == "/api/v1/info" {
+            ("200 OK", node_info(4000f64, &[]))
+        } else if method == "POST" && path == "/api/v1/messages" {
+            (
+                "201 Created",
+                serde_json::json!({ "data": { "messageId": REFERENCED_MESSAGE_ID } }).to_string(),
+            )
+        } else if let Some(message_id) = metadata {
+            let referenced = message_id == REFERENCED_MESSAGE_ID;
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "messageId": message_id,
+                    "parent1MessageId": MESSAGE_ID,
+                    "parent2MessageId": MESSAGE_ID,
+                    "isSolid": true,
+                    "referencedByMilestoneIndex": if referenced { Some(2) } else { None },
+                }})
+                .to_string(),
+            )
+        } else if let Some(address) = path
+            .strip_prefix("/api/v1/addresses/")
+            .and_then(|path| path.strip_suffix("/outputs"))
+        {
+            let output_ids: Vec<String> = match used_addresses.iter().position(|used| used == address) {
+                Some(index) => vec![format!("{}0000", hex::encode([index as u8 + 1; 32]))],
+                None => Vec::new(),
+            };
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "address": address,
+                    "maxResults": 1000,
+                    "count": output_ids.len(),
+                    "outputIds": output_ids,
+                }})
+                .to_string(),
+            )
+        } else {
+            ("404 Not Found", String::new())
+        }
+    });
+    (url, requests)
+}
+
+// The number of requests to the path, the recorded requests are cleared.
+fn take_requests(requests: &Mutex<Vec<String>>, matches: impl Fn(&str) -> bool) -> usize {
+    requests.lock().unwrap().drain(..).filter(|path| matches(path)).count()
+}
+
+// The outputs of an address are requested with and without the spent ones.
+fn is_outputs_request(path: &str) -> bool {
+    path.split('?').next().unwrap().ends_with("/outputs")
+}
+
+async fn unspent_address_index(client: &Client) -> usize {
+    client
+        .get_unspent_address(&seed())
+        .with_account_index(0)
+        .get()
+        .await
+        .unwrap()
+        .1
+}
+
+#[tokio::test]
+async fn test_storage_skips_the_discovered_addresses() {
+    let (url, requests) = mock_node();
+    let path = std::env::temp_dir().join(format!("iota-client-storage-test-{}", std::process::id()));
+    let client = |network_id| builder(&url, network_id).with_storage_path(&path).finish().unwrap();
+
+    assert_eq!(unspent_address_index(&client("testnet")).await, 3);
+    assert_eq!(take_requests(&requests, is_outputs_request), 2 * 4);
+
+    // a client of the same storage starts at the unused address found, after a restart too
+    assert_eq!(unspent_address_index(&client("testnet")).await, 3);
+    assert_eq!(take_requests(&requests, is_outputs_request), 2);
+
+    // the state of another network is dropped
+    assert_eq!(unspent_address_index(&client("othernet")).await, 3);
+    assert_eq!(take_requests(&requests, is_outputs_request), 2 * 4);
+    assert_eq!(unspent_address_index(&client("testnet")).await, 3);
+    assert_eq!(take_requests(&requests, is_outputs_request), 2 * 4);
+
+    // an initial address index isn't replaced by the stored one
+    let client = client("testnet");
+    let (_, index) = client
+        .get_unspent_address(&seed())
+        .with_account_index(0)
+        .with_initial_address_index(0)
+        .get()
+        .await
+        .unwrap();
+    assert_eq!(index, 3);
+    assert_eq!(take_requests(&requests, is_outputs_request), 2 * 4);
+
+    std::fs::remove_dir_all(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_storage_shares_the_node_info() {
+    let (url, requests) = mock_node();
+    let storage = Arc::new(MemoryStorage::new());
+    let client = || builder(&url, "testnet").with_storage(storage.clone()).finish().unwrap();
+
+    client().get_info().await.unwrap();
+    client().get_info().await.unwrap();
+    assert_eq!(take_requests(&requests, |path| path == "/api/v1/info"), 1);
+
+    // a refresh fetches the info again
+    client().refresh_node_info().await.unwrap();
+    client().get_info().await.unwrap();
+    assert_eq!(take_requests(&requests, |path| path == "/api/v1/info"), 1);
+
+    // without a TTL the info is neither cached nor stored
+    let client = || {
+        builder(&url, "testnet")
+            .with_storage(storage.clone())
+            .with_node_info_ttl(std::time::Duration::from_secs(0))
+            .finish()
+            .unwrap()
+    };
+    client().get_info().await.unwrap();
+    client().get_info().await.unwrap();
+    assert_eq!(take_requests(&requests, |path| path == "/api/v1/info"), 2);
+}
+
+#[tokio::test]
+async fn test_storage_of_the_pending_messages() {
+    let (url, _) = mock_node();
+    let storage = Arc::new(MemoryStorage::new());
+    let client = || builder(&url, "testnet").with_storage(storage.clone()).finish().unwrap();
+
+    let message = MessageBuilder::<Constant>::new()
+        .with_network_id(hash_network_id("testnet"))
+        .with_parent1(MessageId::from_str(MESSAGE_ID).unwrap())
+        .with_parent2(MessageId::from_str(MESSAGE_ID).unwrap())
+        .with_payload(Payload::Indexation(Box::new(
+            IndexationPayload::new("IOTA.RS".to_string(), b"pending").unwrap(),
+        )))
+        .with_nonce_provider(ConstantBuilder::new().with_value(0).finish(), 0f64)
+        .finish()
+        .unwrap();
+    let message_id = client().post_message(&message).await.unwrap();
+    assert_eq!(client().get_pending_messages().await.unwrap(), vec![message_id]);
+
+    // a message without a milestone stays pending
+    let unreferenced = MessageId::from_str(MESSAGE_ID).unwrap();
+    client().get_message().metadata(&unreferenced).await.unwrap();
+    assert_eq!(client().get_pending_messages().await.unwrap(), vec![message_id]);
+
+    client().get_message().metadata(&message_id).await.unwrap();
+    assert!(client().get_pending_messages().await.unwrap().is_empty());
+}
@@ -0,0 +1,134 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Stronghold signer tests, the snapshots are created in the temporary directory and compared with the in-memory seed.
+
+#![cfg(feature = "stronghold")]
+
+use bee_message::prelude::*;
+use iota_client::{
+    api::{InputSigningData, PreparedTransactionData},
+    Error, Seed, StrongholdSigner,
+};
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+const PASSWORD: &str = "password";
+const TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const RECIPIENT: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+
+fn seed() -> Seed {
+    Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap()
+}
+
+// A snapshot path unique to the test, the snapshot is removed when the path is dropped.
+struct SnapshotPath(PathBuf);
+
+impl SnapshotPath {
+    fn new(name: &str) -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        Self(std::env::temp_dir().join(format!("iota-client-{}-{}.stronghold", name, nanos)))
+    }
+}
+
+impl Drop for SnapshotPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn client() -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node("http://localhost:14265")
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+#[test]
+fn test_stronghold_addresses_match_the_seed() {
+    let path = SnapshotPath::new("addresses");
+    StrongholdSigner::create(&path.0, PASSWORD, &hex::decode(SEED).unwrap()).unwrap();
+    let signer = StrongholdSigner::load(&path.0, PASSWORD).unwrap();
+
+    let client = client();
+    let seed = seed();
+    for account_index in 0..2 {
+        let seed_addresses = client
+            .find_addresses(&seed)
+            .with_account_index(account_index)
+            .with_range(0..5)
+            .get_all()
+            .unwrap();
+        let stronghold_addresses = client
+            .find_addresses(&signer)
+            .with_account_index(account_index)
+            .with_range(0..5)
+            .get_all()
+            .unwrap();
+        assert_eq!(stronghold_addresses, seed_addresses);
+    }
+}
+
+#[test]
+fn test_stronghold_signature_matches_the_seed() {
+    let path = SnapshotPath::new("signature");
+    let signer = StrongholdSigner::create(&path.0, PASSWORD, &hex::decode(SEED).unwrap()).unwrap();
+
+    let addresses = client()
+        .find_addresses(&seed())
+        .with_account_index(0)
+        .with_range(0..2)
+        .finish()
+        .unwrap();
+    let input = UTXOInput::new(TransactionId::from_str(TRANSACTION_ID).unwrap(), 0).unwrap();
+    let essence = TransactionPayloadEssence::builder()
+        .add_input(Input::UTXO(input.clone()))
+        .add_output(
+            SignatureLockedSingleOutput::new(Address::try_from_bech32(RECIPIENT).unwrap(), 1_000_000)
+                .unwrap()
+                .into(),
+        )
+        .finish()
+        .unwrap();
+    let prepared = PreparedTransactionData {
+        essence,
+        account_index: 0,
+        inputs: vec![InputSigningData {
+            input,
+            address: Address::try_from_bech32(&addresses[1].to_string()).unwrap(),
+            address_index: 1,
+            internal: false,
+        }],
+        remainder: None,
+    };
+
+    // Ed25519 signatures are deterministic
+    assert_eq!(prepared.sign(&signer).unwrap(), prepared.sign(&seed()).unwrap());
+}
+
+#[test]
+fn test_stronghold_errors() {
+    let path = SnapshotPath::new("errors");
+    assert!(matches!(
+        StrongholdSigner::load(&path.0, PASSWORD),
+        Err(Error::StrongholdSnapshotNotFound(_))
+    ));
+
+    StrongholdSigner::create(&path.0, PASSWORD, &hex::decode(SEED).unwrap()).unwrap();
+    assert!(matches!(
+        StrongholdSigner::load(&path.0, "wrong password"),
+        Err(Error::StrongholdInvalidPassword(_))
+    ));
+    // the existing snapshot isn't overwritten
+    assert!(matches!(
+        StrongholdSigner::create(&path.0, PASSWORD, &[0; 32]),
+        Err(Error::InvalidParameter(_))
+    ));
+    assert!(StrongholdSigner::load(&path.0, PASSWORD).is_ok());
+}
@@ -0,0 +1,372 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Transaction history tests against a mock ledger: a deposit of 3 Mi to the own address, then a spend of 1 Mi from it
+// with a remainder of 2 Mi, whose transaction was reattached, then in a swept ledger a spend of the whole remainder
+// without a remainder back.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{node_info, start_mock_node};
+use iota_client::{api::AddressDelta, Bech32Address, Client, LedgerInclusionState, MessageJson};
+use std::convert::TryInto;
+
+const OWN_ADDRESS: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+const OTHER_ADDRESS: &str = "2222222222222222222222222222222222222222222222222222222222222222";
+// the transaction funding the sender of the deposit
+const FUNDING_TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const DEPOSIT_TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+const SPEND_TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000003";
+const SWEEP_TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000004";
+// the signatures aren't checked by the client
+const PUBLIC_KEY: &str = "dda7a4395bd2035f6fee2d060ce844101efb612f3bcf7f49e749683f124014f8";
+const SIGNATURE: &str = concat!(
+    "1a6ddcc49f0715395f0c8dcfd106a1833624abd85679370b4c9603c1e9c6d80a",
+    "3ecc63c1ba1ffe1ffa98b64b2cf610ce3df86c6b8470e4006e7fef189df1ebde"
+);
+
+// A message with a transaction payload spending the first output of `input` to the (address, amount) outputs, in a
+// different message for each `tip`.
+fn transaction_message(input: &str, outputs: &[(&str, u64)], tip: &str) -> String {
+    let outputs: Vec<serde_json::Value> = outputs
+        .iter()
+        .map(|(address, amount)| {
+            serde_json::json!({ "type": 0, "address": { "type": 1, "address": address }, "amount": amount })
+        })
+        .collect();
+    serde_json::json!({ "data": {
+        "networkId": "1",
+        "parent1MessageId": tip,
+        "parent2MessageId": tip,
+        "payload": {
+            "type": 0,
+            "essence": {
+                "type": 0,
+                "inputs": [{ "type": 0, "transactionId": input, "transactionOutputIndex": 0 }],
+                "outputs": outputs,
+            },
+            "unlockBlocks": [{
+                "type": 0,
+                "signature": {
+                    "type": 1,
+                    "publicKey": PUBLIC_KEY,
+                    "signature": SIGNATURE,
+                },
+            }],
+        },
+        "nonce": "0",
+    }})
+    .to_string()
+}
+
+fn message_id(message: &str) -> String {
+    let message: serde_json::Value = serde_json::from_str(message).unwrap();
+    let message: MessageJson = serde_json::from_value(message["data"].clone()).unwrap();
+    let message: Message = message.try_into().unwrap();
+    message.id().0.to_string()
+}
+
+struct Ledger {
+    url: String,
+    deposit_message_id: String,
+    spend_message_id: String,
+    reattachment_message_id: String,
+    sweep_message_id: String,
+}
+
+fn ledger(swept: bool) -> Ledger {
+    let deposit = transaction_message(
+        FUNDING_TRANSACTION_ID,
+        &[(OWN_ADDRESS, 3_000_000), (OTHER_ADDRESS, 2_000_000)],
+        &"0".repeat(64),
+    );
+    let spend = transaction_message(
+        DEPOSIT_TRANSACTION_ID,
+        &[(OWN_ADDRESS, 2_000_000), (OTHER_ADDRESS, 1_000_000)],
+        &"0".repeat(64),
+    );
+    // the same transaction with other parents
+    let reattachment = transaction_message(
+        DEPOSIT_TRANSACTION_ID,
+        &[(OWN_ADDRESS, 2_000_000), (OTHER_ADDRESS, 1_000_000)],
+        &"f".repeat(64),
+    );
+    // the remainder of the spend to the other address
+    let sweep = transaction_message(SPEND_TRANSACTION_ID, &[(OTHER_ADDRESS, 2_000_000)], &"0".repeat(64));
+    let (deposit_message_id, spend_message_id, reattachment_message_id, sweep_message_id) = (
+        message_id(&deposit),
+        message_id(&spend),
+        message_id(&reattachment),
+        message_id(&sweep),
+    );
+    // (message id, milestone index, milestone timestamp)
+    let milestones = [
+        (deposit_message_id.clone(), 5u64, 1_000u64),
+        (spend_message_id.clone(), 7u64, 2_000u64),
+        (sweep_message_id.clone(), 9u64, 3_000u64),
+    ];
+    // (milestone index, created outputs, consumed outputs), the other milestones up to the solid milestone 10 don't
+    // change the ledger
+    let mut utxo_changes = vec![
+        (
+            5u64,
+            vec![
+                format!("{}0000", DEPOSIT_TRANSACTION_ID),
+                format!("{}0100", DEPOSIT_TRANSACTION_ID),
+            ],
+            vec![format!("{}0000", FUNDING_TRANSACTION_ID)],
+        ),
+        (
+            7u64,
+            vec![
+                format!("{}0000", SPEND_TRANSACTION_ID),
+                format!("{}0100", SPEND_TRANSACTION_ID),
+            ],
+            vec![format!("{}0000", DEPOSIT_TRANSACTION_ID)],
+        ),
+        (
+            9u64,
+            vec![format!("{}0000", SWEEP_TRANSACTION_ID)],
+            vec![format!("{}0000", SPEND_TRANSACTION_ID)],
+        ),
+    ];
+    if !swept {
+        utxo_changes.truncate(2);
+    }
+    // (output id, address, amount): the spend and the deposit are referenced in the reverse order of the list
+    let outputs = [
+        (format!("{}0000", SPEND_TRANSACTION_ID), OWN_ADDRESS, 2_000_000u64),
+        (format!("{}0000", DEPOSIT_TRANSACTION_ID), OWN_ADDRESS, 3_000_000u64),
+        (format!("{}0000", FUNDING_TRANSACTION_ID), OTHER_ADDRESS, 5_000_000u64),
+    ];
+    let url = start_mock_node(move |method, path, _| {
+        if method != "GET" {
+            return ("404 Not Found", String::new());
+        }
+        let own_address = Address::Ed25519(Ed25519Address::new(hex_bytes(OWN_ADDRESS)));
+        let answer = if path.starts_with("/api/v1/addresses/") && path.contains("/outputs") {
+            let path = path.trim_start_matches("/api/v1/addresses/");
+            let separator = path.find("/outputs").unwrap();
+            let (address, include_spent) = (&path[..separator], &path[separator..] == "/outputs?include-spent=true");
+            // the deposit output is spent, and so is the remainder of the spend in the swept ledger
+            let unspent_outputs = if swept { 0 } else { 1 };
+            let output_ids: Vec<&String> = match Address::try_from_bech32(address) {
+                Ok(address) if address == own_address => outputs
+                    .iter()
+                    .take(if include_spent { 2 } else { unspent_outputs })
+                    .map(|(id, _, _)| id)
+                    .collect(),
+                _ => Vec::new(),
+            };
+            serde_json::json!({ "data": {
+                "address": address,
+                "maxResults": 1000,
+                "count": output_ids.len(),
+                "outputIds": output_ids,
+            }})
+            .to_string()
+        } else if let Some((output_id, address, amount)) = path
+            .strip_prefix("/api/v1/outputs/")
+            .and_then(|id| outputs.iter().find(|(output_id, _, _)| output_id == id))
+        {
+            serde_json::json!({ "data": {
+                "messageId": "0".repeat(64),
+                "transactionId": &output_id[..64],
+                "outputIndex": 0,
+                "isSpent": true,
+                "output": { "type": 0, "address": { "type": 1, "address": address }, "amount": amount },
+            }})
+            .to_string()
+        } else if path == format!("/api/v1/transactions/{}/included-message", DEPOSIT_TRANSACTION_ID) {
+            deposit.clone()
+        } else if path == format!("/api/v1/transactions/{}/included-message", SPEND_TRANSACTION_ID) {
+            spend.clone()
+        } else if swept && path == format!("/api/v1/transactions/{}/included-message", SWEEP_TRANSACTION_ID) {
+            sweep.clone()
+        } else if path == "/api/v1/info" {
+            let mut info: serde_json::Value = serde_json::from_str(&node_info(4000f64, &[])).unwrap();
+            info["data"]["solidMilestoneIndex"] = 10.into();
+            info.to_string()
+        } else if let Some(milestone_index) = path
+            .strip_prefix("/api/v1/milestones/")
+            .and_then(|path| path.strip_suffix("/utxo-changes"))
+            .and_then(|index| index.parse::<u64>().ok())
+            .filter(|index| (1..=10).contains(index))
+        {
+            let (created, consumed) = utxo_changes
+                .iter()
+                .find(|(index, _, _)| *index == milestone_index)
+                .map(|(_, created, consumed)| (created.clone(), consumed.clone()))
+                .unwrap_or_default();
+            serde_json::json!({ "data": {
+                "index": milestone_index,
+                "createdOutputs": created,
+                "consumedOutputs": consumed,
+            }})
+            .to_string()
+        } else if let Some((message_id, milestone_index, _)) = path
+            .strip_prefix("/api/v1/messages/")
+            .and_then(|path| path.strip_suffix("/metadata"))
+            .and_then(|id| milestones.iter().find(|(message_id, _, _)| message_id == id))
+        {
+            serde_json::json!({ "data": {
+                "messageId": message_id,
+                "parent1MessageId": "0".repeat(64),
+                "parent2MessageId": "0".repeat(64),
+                "isSolid": true,
+                "referencedByMilestoneIndex": milestone_index,
+                "ledgerInclusionState": "included",
+            }})
+            .to_string()
+        } else if let Some((message_id, milestone_index, timestamp)) = path
+            .strip_prefix("/api/v1/milestones/")
+            .and_then(|index| milestones.iter().find(|(_, i, _)| i.to_string() == index))
+        {
+            serde_json::json!({ "data": {
+                "index": milestone_index,
+                "messageId": message_id,
+                "timestamp": timestamp,
+            }})
+            .to_string()
+        } else {
+            return ("404 Not Found", String::new());
+        };
+        ("200 OK", answer)
+    });
+    Ledger {
+        url,
+        deposit_message_id,
+        spend_message_id,
+        reattachment_message_id,
+        sweep_message_id,
+    }
+}
+
+fn hex_bytes(hex: &str) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(hex, &mut bytes).unwrap();
+    bytes
+}
+
+fn client(url: &str) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+fn bech32(client: &Client, address: &str) -> Bech32Address {
+    client
+        .address_to_bech32(&Address::Ed25519(Ed25519Address::new(hex_bytes(address))))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_transaction_history() {
+    let ledger = ledger(false);
+    let client = client(&ledger.url);
+    let own = bech32(&client, OWN_ADDRESS);
+    let other = bech32(&client, OTHER_ADDRESS);
+    let history = client.get_address_transaction_history(&[own.clone()]).await.unwrap();
+
+    // the deposit then the spend, by milestone
+    assert_eq!(history.entries.len(), 2);
+    let (deposit, spend) = (&history.entries[0], &history.entries[1]);
+    assert_eq!(deposit.transaction_id, DEPOSIT_TRANSACTION_ID);
+    assert_eq!(deposit.message_id, ledger.deposit_message_id);
+    assert_eq!((deposit.milestone_index, deposit.timestamp), (Some(5), Some(1_000)));
+    assert_eq!(deposit.inclusion_state, Some(LedgerInclusionState::Included));
+    assert_eq!(
+        deposit.deltas,
+        vec![AddressDelta {
+            address: own.clone(),
+            delta: 3_000_000
+        }]
+    );
+    assert_eq!(deposit.counterparts, vec![other.clone()]);
+
+    // the spend of the deposit output sends the remainder back, it is only listed with its included message
+    assert_eq!(spend.transaction_id, SPEND_TRANSACTION_ID);
+    assert_eq!(spend.message_id, ledger.spend_message_id);
+    assert_ne!(spend.message_id, ledger.reattachment_message_id);
+    assert_eq!((spend.milestone_index, spend.timestamp), (Some(7), Some(2_000)));
+    assert_eq!(spend.delta(), -1_000_000);
+    assert_eq!(spend.counterparts, vec![other.clone()]);
+
+    assert_eq!(
+        history.to_csv(),
+        format!(
+            "milestoneIndex,timestamp,messageId,transactionId,address,delta,counterparts,inclusionState\n\
+             5,1000,{},{},{},3000000,{},included\n\
+             7,2000,{},{},{},-1000000,{},included\n",
+            ledger.deposit_message_id,
+            DEPOSIT_TRANSACTION_ID,
+            own,
+            other,
+            ledger.spend_message_id,
+            SPEND_TRANSACTION_ID,
+            own,
+            other,
+        )
+    );
+
+    let lines: Vec<serde_json::Value> = history
+        .to_json_lines()
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(
+        lines[1],
+        serde_json::json!({
+            "transactionId": SPEND_TRANSACTION_ID,
+            "messageId": ledger.spend_message_id,
+            "milestoneIndex": 7,
+            "timestamp": 2000,
+            "inclusionState": "included",
+            "deltas": [{ "address": own.to_string(), "delta": -1000000 }],
+            "counterparts": [other.to_string()],
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_transaction_history_unused_address() {
+    let ledger = ledger(false);
+    let client = client(&ledger.url);
+    let history = client
+        .get_address_transaction_history(&[bech32(&client, &"3".repeat(64))])
+        .await
+        .unwrap();
+    assert!(history.entries.is_empty());
+    assert_eq!(history.to_json_lines().unwrap(), "");
+}
+
+#[tokio::test]
+async fn test_transaction_history_spend_without_remainder() {
+    let ledger = ledger(true);
+    let client = client(&ledger.url);
+    let own = bech32(&client, OWN_ADDRESS);
+    let other = bech32(&client, OTHER_ADDRESS);
+    let history = client.get_address_transaction_history(&[own.clone()]).await.unwrap();
+
+    // the sweep creates no output on the own address, it's found in the UTXO changes of the milestone 9
+    assert_eq!(history.entries.len(), 3);
+    let sweep = &history.entries[2];
+    assert_eq!(sweep.transaction_id, SWEEP_TRANSACTION_ID);
+    assert_eq!(sweep.message_id, ledger.sweep_message_id);
+    assert_eq!((sweep.milestone_index, sweep.timestamp), (Some(9), Some(3_000)));
+    assert_eq!(
+        sweep.deltas,
+        vec![AddressDelta {
+            address: own.clone(),
+            delta: -2_000_000
+        }]
+    );
+    assert_eq!(sweep.counterparts, vec![other]);
+    assert_eq!(history.entries.iter().map(|entry| entry.delta()).sum::<i64>(), 0);
+}
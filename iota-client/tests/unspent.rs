@@ -0,0 +1,164 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Unspent address tests against a mock node where the first three addresses of the seed are used.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{start_mock_node, MESSAGE_ID};
+use iota_client::Seed;
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+
+struct MockOutput {
+    id: String,
+    address: String,
+    is_spent: bool,
+}
+
+fn seed() -> Seed {
+    Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap()
+}
+
+fn client(url: &str) -> iota_client::Client {
+    iota_client::Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+// The public addresses of the seed's first account.
+fn public_addresses() -> Vec<String> {
+    client("http://localhost:14265")
+        .find_addresses(&seed())
+        .with_account_index(0)
+        .with_range(0..5)
+        .finish()
+        .unwrap()
+        .into_iter()
+        .map(|address| address.to_string())
+        .collect()
+}
+
+// The address 0 was spent from, the address 1 holds a balance and the address 2 was spent from and holds a balance.
+fn outputs() -> Vec<MockOutput> {
+    let addresses = public_addresses();
+    let output = |index: u8, address: &str, is_spent| MockOutput {
+        id: format!("{}0000", hex::encode([index; 32])),
+        address: address.to_string(),
+        is_spent,
+    };
+    vec![
+        output(1, &addresses[0], true),
+        output(2, &addresses[1], false),
+        output(3, &addresses[2], true),
+        output(4, &addresses[2], false),
+    ]
+}
+
+fn unspent_mock_node() -> String {
+    let outputs = outputs();
+    start_mock_node(move |method, path, _| {
+        // the spent outputs are only listed with `include-spent=true`, like the nodes do
+        let (path, include_spent) = match path.find('?') {
+            Some(separator) => (
+                &path[..separator],
+                path[separator + 1..]
+                    .split('&')
+                    .any(|pair| pair == "include-spent=true"),
+            ),
+            None => (path, false),
+        };
+        if method != "GET" {
+            ("404 Not Found", String::new())
+        } else if let Some(address) = path
+            .strip_prefix("/api/v1/addresses/")
+            .and_then(|path| path.strip_suffix("/outputs"))
+        {
+            let output_ids = outputs
+                .iter()
+                .filter(|output| output.address == address && (include_spent || !output.is_spent))
+                .map(|output| output.id.clone())
+                .collect::<Vec<String>>();
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "address": address,
+                    "maxResults": 1000,
+                    "count": output_ids.len(),
+                    "outputIds": output_ids,
+                }})
+                .to_string(),
+            )
+        } else if let Some(output) = path
+            .strip_prefix("/api/v1/outputs/")
+            .and_then(|id| outputs.iter().find(|output| output.id == id))
+        {
+            let address = match Address::try_from_bech32(&output.address).unwrap() {
+                Address::Ed25519(address) => address.to_string(),
+                _ => unreachable!(),
+            };
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "messageId": MESSAGE_ID,
+                    "transactionId": &output.id[..64],
+                    "outputIndex": 0,
+                    "isSpent": output.is_spent,
+                    "output": {
+                        "type": 0,
+                        "address": { "type": 1, "address": address },
+                        "amount": 1_000_000,
+                    },
+                }})
+                .to_string(),
+            )
+        } else {
+            ("404 Not Found", String::new())
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_unspent_address_skips_used_addresses() {
+    let seed = seed();
+    let (address, index) = client(&unspent_mock_node())
+        .get_unspent_address(&seed)
+        .with_account_index(0)
+        .get()
+        .await
+        .unwrap();
+    assert_eq!(index, 3);
+    assert_eq!(address.to_string(), public_addresses()[3]);
+}
+
+#[tokio::test]
+async fn test_unspent_address_include_funded() {
+    let seed = seed();
+    let (address, index) = client(&unspent_mock_node())
+        .get_unspent_address(&seed)
+        .with_account_index(0)
+        .with_include_funded(true)
+        .get()
+        .await
+        .unwrap();
+    assert_eq!(index, 1);
+    assert_eq!(address.to_string(), public_addresses()[1]);
+}
+
+#[tokio::test]
+async fn test_unspent_address_initial_address_index() {
+    let seed = seed();
+    let (address, index) = client(&unspent_mock_node())
+        .get_unspent_address(&seed)
+        .with_account_index(0)
+        .with_initial_address_index(4)
+        .get()
+        .await
+        .unwrap();
+    assert_eq!(index, 4);
+    assert_eq!(address.to_string(), public_addresses()[4]);
+}
@@ -0,0 +1,247 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Value overflow tests with amounts at and beyond the IOTA supply: the input selection, the outputs of the send
+// builder, the balances and outputs answered by a node and the balance aggregation.
+
+mod common;
+
+use bee_message::prelude::*;
+use common::{start_mock_node, MESSAGE_ID};
+use iota_client::{
+    api::{select_inputs, InputCandidate, InputSelectionStrategy, InputSigningData, IOTA_SUPPLY},
+    Bech32Address, Client, Error, OutputType, Seed,
+};
+use std::str::FromStr;
+
+const SEED: &str = "256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+const TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const RECIPIENT: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+
+fn seed() -> Seed {
+    Seed::from_ed25519_bytes(&hex::decode(SEED).unwrap()).unwrap()
+}
+
+fn client(url: &str) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+fn is_overflow<T: std::fmt::Debug>(result: iota_client::Result<T>, amount: u128) -> bool {
+    match result {
+        Err(Error::ValueOverflow(overflow)) => overflow == amount,
+        r => panic!("expected a value overflow of {}, got {:?}", amount, r),
+    }
+}
+
+fn candidates(amounts: &[u64]) -> Vec<InputCandidate> {
+    amounts
+        .iter()
+        .enumerate()
+        .map(|(index, amount)| InputCandidate {
+            signing_data: InputSigningData {
+                input: UTXOInput::new(TransactionId::from_str(TRANSACTION_ID).unwrap(), index as u16).unwrap(),
+                address: Address::Ed25519(Ed25519Address::new([index as u8; 32])),
+                address_index: index,
+                internal: false,
+            },
+            amount: *amount,
+            output_type: OutputType::SignatureLockedSingle,
+        })
+        .collect()
+}
+
+fn select(amounts: &[u64], amount: u64) -> iota_client::Result<Vec<InputCandidate>> {
+    select_inputs(
+        &candidates(amounts),
+        amount,
+        &InputSelectionStrategy::SmallestFirst,
+        false,
+    )
+}
+
+#[test]
+fn test_input_selection_overflow() {
+    // the whole supply in one output
+    assert_eq!(select(&[IOTA_SUPPLY], IOTA_SUPPLY).unwrap().len(), 1);
+    assert_eq!(select(&[IOTA_SUPPLY - 1, 1], IOTA_SUPPLY).unwrap().len(), 2);
+
+    let supply = u128::from(IOTA_SUPPLY);
+    assert!(is_overflow(select(&[IOTA_SUPPLY, 1], 1), supply + 1));
+    // the amounts would wrap around in a u64 sum
+    assert!(is_overflow(
+        select(&[u64::MAX, u64::MAX, 2], 1),
+        2 * u128::from(u64::MAX) + 2
+    ));
+    assert!(is_overflow(select(&[1], IOTA_SUPPLY + 1), supply + 1));
+    assert!(is_overflow(select(&[1], u64::MAX), u128::from(u64::MAX)));
+}
+
+#[tokio::test]
+async fn test_send_outputs_overflow() {
+    // the outputs are checked before any node request
+    let client = client("http://localhost:1");
+    let seed = seed();
+    let recipient: Bech32Address = RECIPIENT.to_string().into();
+    assert!(is_overflow(
+        client
+            .send()
+            .with_seed(&seed)
+            .with_output(&recipient, IOTA_SUPPLY + 1)
+            .map(|_| ()),
+        u128::from(IOTA_SUPPLY) + 1
+    ));
+    assert!(is_overflow(
+        client
+            .send()
+            .with_seed(&seed)
+            .with_output(&recipient, u64::MAX)
+            .map(|_| ()),
+        u128::from(u64::MAX)
+    ));
+
+    // outputs within the supply, but not their total
+    let other = client
+        .find_addresses(&seed)
+        .with_account_index(1)
+        .with_range(0..1)
+        .finish()
+        .unwrap()
+        .remove(0);
+    let result = client
+        .send()
+        .with_seed(&seed)
+        .with_output(&recipient, IOTA_SUPPLY)
+        .unwrap()
+        .with_output(&other, 1)
+        .unwrap()
+        .prepare_transaction()
+        .await
+        .map(|_| ());
+    assert!(is_overflow(result, u128::from(IOTA_SUPPLY) + 1));
+    // the outputs to the same address are merged
+    let result = client
+        .send()
+        .with_seed(&seed)
+        .with_output(&recipient, IOTA_SUPPLY / 2 + 1)
+        .unwrap()
+        .with_output(&recipient, IOTA_SUPPLY / 2 + 1)
+        .unwrap()
+        .prepare_transaction()
+        .await
+        .map(|_| ());
+    assert!(is_overflow(result, u128::from(IOTA_SUPPLY / 2 + 1) * 2));
+}
+
+// A node answering the address balances, without any spent output, and an output of the amount.
+fn mock_node(balances: Vec<(String, u64)>, output_amount: u64) -> String {
+    start_mock_node(move |method, path, _| {
+        if method != "GET" {
+            ("404 Not Found", String::new())
+        } else if let Some(address) = path
+            .strip_prefix("/api/v1/addresses/")
+            .and_then(|path| path.strip_suffix("/outputs?include-spent=true"))
+        {
+            // the addresses without a balance have no spent outputs either
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "address": address,
+                    "maxResults": 1000,
+                    "count": 0,
+                    "outputIds": [],
+                }})
+                .to_string(),
+            )
+        } else if let Some(address) = path.strip_prefix("/api/v1/addresses/") {
+            let balance = balances
+                .iter()
+                .find(|(used, _)| used == address)
+                .map(|(_, balance)| *balance)
+                .unwrap_or(0);
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "address": address,
+                    "balance": balance,
+                    "count": if balance == 0 { 0 } else { 1 },
+                    "dustAllowed": false,
+                }})
+                .to_string(),
+            )
+        } else if let Some(output_id) = path.strip_prefix("/api/v1/outputs/") {
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "messageId": MESSAGE_ID,
+                    "transactionId": &output_id[..64],
+                    "outputIndex": 0,
+                    "isSpent": false,
+                    "output": {
+                        "type": 0,
+                        "address": { "type": 1, "address": hex::encode([1u8; 32]) },
+                        "amount": output_amount,
+                    },
+                }})
+                .to_string(),
+            )
+        } else {
+            ("404 Not Found", String::new())
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_node_values_overflow() {
+    let output_id = UTXOInput::new(TransactionId::from_str(TRANSACTION_ID).unwrap(), 0).unwrap();
+    let recipient: Bech32Address = RECIPIENT.to_string().into();
+
+    let url = mock_node(vec![(RECIPIENT.to_string(), IOTA_SUPPLY)], IOTA_SUPPLY);
+    assert_eq!(client(&url).get_output(&output_id).await.unwrap().amount, IOTA_SUPPLY);
+    assert_eq!(
+        client(&url).get_address().balance(&recipient).await.unwrap(),
+        IOTA_SUPPLY
+    );
+
+    // the answers are rejected when they're parsed
+    let url = mock_node(vec![(RECIPIENT.to_string(), IOTA_SUPPLY + 1)], u64::MAX);
+    assert!(is_overflow(
+        client(&url).get_output(&output_id).await,
+        u128::from(u64::MAX)
+    ));
+    assert!(is_overflow(
+        client(&url).get_address().balance(&recipient).await,
+        u128::from(IOTA_SUPPLY) + 1
+    ));
+}
+
+#[tokio::test]
+async fn test_balance_aggregation_overflow() {
+    let seed = seed();
+    let addresses = client("http://localhost:1")
+        .find_addresses(&seed)
+        .with_account_index(0)
+        .with_range(0..2)
+        .finish()
+        .unwrap();
+    // each balance is within the supply, but not their sum
+    let url = mock_node(
+        addresses
+            .iter()
+            .map(|address| (address.to_string(), IOTA_SUPPLY / 2 + 1))
+            .collect(),
+        0,
+    );
+    let result = client(&url)
+        .get_balance(&seed)
+        .with_account_index(0)
+        .with_gap_limit(2)
+        .with_confirmed_only(false)
+        .finish()
+        .await;
+    assert!(is_overflow(result, u128::from(IOTA_SUPPLY / 2 + 1) * 2));
+}
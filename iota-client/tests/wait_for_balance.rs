@@ -0,0 +1,169 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Balance wait tests against a mock node where an output of the address appears on the third balance poll.
+
+mod common;
+
+use common::{start_mock_node, MESSAGE_ID};
+use iota_client::{Bech32Address, Client, Error};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+const ADDRESS: &str = "iot1q9jyad2efwyq7ldg9u6eqg5krxdqawgcdxvhjlmxrveylrt4fgaqj30s9qj";
+const ADDRESS_HEX: &str = "644eb5594b880f7da82f35902296199a0eb9186999797f661b324f8d754a3a09";
+const TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const OUTPUT_ID: &str = "00000000000000000000000000000000000000000000000000000000000000010100";
+// The address the node doesn't answer the balance of.
+const FAILING_ADDRESS: &str = "iot1q95jpvtk7cf7c7l9ne50c684jl4n8ya0srm5clpak7qes9ratu0eyf5eyz5";
+const AMOUNT: u64 = 1_000_000;
+
+// A mock node where the output of the address appears on the third balance request, counting the balance requests.
+fn mock_node() -> (String, Arc<AtomicUsize>) {
+    let balance_requests = Arc::new(AtomicUsize::new(0));
+    let balance_requests_ = balance_requests.clone();
+    let url = start_mock_node(move |method, path, _| {
+        let paid = balance_requests_.load(Ordering::SeqCst) >= 3;
+        if method != "GET" {
+            ("404 Not Found", String::new())
+        } else if path == format!("/api/v1/addresses/{}", ADDRESS) {
+            let paid = balance_requests_.fetch_add(1, Ordering::SeqCst) >= 2;
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "addressType": 1,
+                    "address": ADDRESS_HEX,
+                    "maxResults": 1000,
+                    "count": if paid { 1 } else { 0 },
+                    "balance": if paid { AMOUNT } else { 0 },
+                    "dustAllowed": false,
+                }})
+                .to_string(),
+            )
+        } else if path == format!("/api/v1/addresses/{}/outputs", ADDRESS) {
+            let output_ids = if paid { vec![OUTPUT_ID] } else { Vec::new() };
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "address": ADDRESS_HEX,
+                    "maxResults": 1000,
+                    "count": output_ids.len(),
+                    "outputIds": output_ids,
+                }})
+                .to_string(),
+            )
+        } else if paid && path == format!("/api/v1/outputs/{}", OUTPUT_ID) {
+            (
+                "200 OK",
+                serde_json::json!({ "data": {
+                    "messageId": MESSAGE_ID,
+                    "transactionId": TRANSACTION_ID,
+                    "outputIndex": 1,
+                    "isSpent": false,
+                    "output": {
+                        "type": 0,
+                        "address": { "type": 1, "address": ADDRESS_HEX },
+                        "amount": AMOUNT,
+                    },
+                }})
+                .to_string(),
+            )
+        } else if path == format!("/api/v1/addresses/{}", FAILING_ADDRESS) {
+            ("500 Internal Server Error", String::new())
+        } else {
+            ("404 Not Found", String::new())
+        }
+    });
+    (url, balance_requests)
+}
+
+fn client(url: &str) -> Client {
+    Client::build()
+        .with_node(url)
+        .unwrap()
+        .with_node_sync_disabled()
+        .finish()
+        .unwrap()
+}
+
+fn address(address: &str) -> Bech32Address {
+    Bech32Address(address.to_string())
+}
+
+#[tokio::test]
+async fn test_wait_for_balance() {
+    let (url, balance_requests) = mock_node();
+    let reached = client(&url)
+        .wait_for_balance(
+            &address(ADDRESS),
+            AMOUNT,
+            Some(Duration::from_millis(50)),
+            Some(Duration::from_secs(10)),
+        )
+        .await
+        .unwrap();
+    assert_eq!(balance_requests.load(Ordering::SeqCst), 3);
+    assert_eq!(reached.address, address(ADDRESS));
+    assert_eq!(reached.balance, AMOUNT);
+    assert_eq!(reached.outputs.len(), 1);
+    let output = &reached.outputs[0];
+    assert_eq!(hex::encode(&output.message_id), MESSAGE_ID);
+    assert_eq!(hex::encode(&output.transaction_id), TRANSACTION_ID);
+    assert_eq!(output.output_index, 1);
+    assert_eq!(output.amount, AMOUNT);
+    assert!(!output.is_spent);
+}
+
+#[tokio::test]
+async fn test_wait_for_zero_balance() {
+    let (url, balance_requests) = mock_node();
+    let reached = client(&url)
+        .wait_for_balance(&address(ADDRESS), 0, None, None)
+        .await
+        .unwrap();
+    assert_eq!(balance_requests.load(Ordering::SeqCst), 1);
+    assert_eq!(reached.balance, 0);
+    assert!(reached.outputs.is_empty());
+}
+
+#[tokio::test]
+async fn test_wait_for_balance_timeout() {
+    let (url, balance_requests) = mock_node();
+    let error = client(&url)
+        .wait_for_balance(
+            &address(ADDRESS),
+            AMOUNT + 1,
+            Some(Duration::from_millis(20)),
+            Some(Duration::from_millis(200)),
+        )
+        .await
+        .unwrap_err();
+    match error {
+        Error::BalanceTimeout(timed_out_address, min_amount, balance) => {
+            assert_eq!(timed_out_address, ADDRESS);
+            assert_eq!(min_amount, AMOUNT + 1);
+            assert_eq!(balance, AMOUNT);
+        }
+        error => panic!("unexpected error {}", error),
+    }
+    assert!(balance_requests.load(Ordering::SeqCst) > 3);
+}
+
+#[tokio::test]
+async fn test_wait_for_balance_node_error() {
+    let (url, _) = mock_node();
+    let error = client(&url)
+        .wait_for_balance(&address(FAILING_ADDRESS), AMOUNT, None, Some(Duration::from_secs(10)))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, Error::ResponseError(500, _, _)),
+        "unexpected error {}",
+        error
+    );
+}
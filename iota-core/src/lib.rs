@@ -17,6 +17,7 @@ pub use bee_pow as pow;
 pub use bee_signing_ext as signing;
 pub use iota_client as client;
 
+#[cfg(feature = "client")]
 pub use client::{Client, ClientBuilder};
 
 // TODO prelude